@@ -1,4 +1,4 @@
-use engine::{Engine, WindowManager};
+use engine::{Engine, EngineEvent, WindowManager};
 use winit::{
     application::ApplicationHandler,
     event::{DeviceEvent, ElementState, WindowEvent},
@@ -46,24 +46,31 @@ impl App {
 
         self.engine.init();
 
-        let event_loop = EventLoop::new()?;
+        let event_loop = EventLoop::<EngineEvent>::with_user_event().build()?;
         event_loop.set_control_flow(ControlFlow::Poll);
+        self.window_manager
+            .set_event_loop_proxy(event_loop.create_proxy());
         event_loop.run_app(self)?;
 
         Ok(())
     }
 }
 
-impl ApplicationHandler for App {
+impl ApplicationHandler<EngineEvent> for App {
     fn resumed(&mut self, event_loop: &ActiveEventLoop) {
         // Crée la fenêtre principale / editor window.
         let window = pollster::block_on(
             self.window_manager
-                .create_window::<EditorWindow>(event_loop),
-        )
-        .unwrap();
-
-        self.window_manager.set_active_window(window);
+                .create_window::<EditorWindow>(event_loop, &self.engine.handle()),
+        );
+
+        match window {
+            Ok(window) => self.window_manager.set_active_window(window),
+            Err(e) => {
+                log::error!("Failed to create the editor window: {}", e);
+                event_loop.exit();
+            }
+        }
     }
 
     fn window_event(
@@ -84,7 +91,7 @@ impl ApplicationHandler for App {
 
             match event {
                 WindowEvent::CloseRequested => {
-                    // shutdown propre des subsystèmes
+                    // Arrêt ordonné des subsystèmes dans `exiting` (`Engine::shutdown`)
                     event_loop.exit();
                 }
                 WindowEvent::RedrawRequested => {
@@ -122,9 +129,28 @@ impl ApplicationHandler for App {
                         }
                     }
                 }
-                WindowEvent::MouseInput { state, .. } => {
+                WindowEvent::CursorMoved { position, .. } => {
+                    // Confinement à un rectangle / recentrage du mode relatif émulé
+                    let mut state = window.state().lock().unwrap();
+                    state.handle_mouse_moved((position.x as f32, position.y as f32));
+                    state.cursor_mut().handle_cursor_moved(wnd, position);
+                }
+                WindowEvent::CursorLeft { .. } => {
+                    window.state().lock().unwrap().set_cursor_position(None);
+                }
+                WindowEvent::MouseWheel { delta, .. } if !consumed => {
+                    window.state().lock().unwrap().handle_mouse_wheel(delta);
+                }
+                WindowEvent::Focused(focused) => {
+                    let mut state = window.state().lock().unwrap();
+                    state.cursor_mut().handle_focus(wnd, focused);
+                }
+                WindowEvent::MouseInput { state, button, .. } => {
                     if !consumed && state == ElementState::Pressed {
                         window.set_mouse_capture(true);
+                        window.state().lock().unwrap().press_mouse_button(button);
+                    } else if state == ElementState::Released {
+                        window.state().lock().unwrap().release_mouse_button(button);
                     }
                 }
                 _ => {}
@@ -132,6 +158,20 @@ impl ApplicationHandler for App {
         }
     }
 
+    fn about_to_wait(&mut self, _event_loop: &ActiveEventLoop) {
+        // Manettes : appliquées à l'`Input` de la fenêtre active avant son prochain redraw
+        self.window_manager.update_gamepads();
+    }
+
+    fn user_event(&mut self, _event_loop: &ActiveEventLoop, event: EngineEvent) {
+        self.window_manager.handle_user_event(event);
+    }
+
+    fn exiting(&mut self, _event_loop: &ActiveEventLoop) {
+        // Arrêt ordonné : écritures, threads, ressources GPU des fenêtres puis surfaces
+        self.engine.shutdown(&mut self.window_manager);
+    }
+
     fn device_event(
         &mut self,
         _event_loop: &ActiveEventLoop,
@@ -1,15 +1,24 @@
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     sync::{Arc, Mutex},
+    time::Instant,
 };
 
 use egui_wgpu::wgpu::{self};
 use engine::{
-    Camera2D, CameraMovement, DeltaTimer, EguiPass, PassContext, PassManager, Scene, Sprite,
-    SpritePass, Window, WindowFactory, WindowState,
+    AssetKind, AssetLoader, AssetWatcher, Camera2D, CameraMovement, ColorPicker, CommandPalette,
+    DayNightCycle, DebugDraw, DeltaTimer, EditorPreferences, EguiPass, EngineHandle, EngineInfo,
+    EntityClipboard, EntitySnapshot, ExternalEditor, FontFallbacks, FramePhase, GamepadAxis,
+    GlobalTransform, Highlight, HudLayer, ImportPipeline, Input, InputSettings, LightingPass, Mat4,
+    MemoryCategory, MemoryPanel, ModManager, Name, OutlinePass, PaletteEntry, PaletteTarget,
+    Parent, PassContext, PassManager, PrefabLibrary, Profiler, ProfilerPanel, ReplayViewer,
+    RewindBuffer, Scene, ShapePass, SpawnEntry, Spawner, SpawnerEvent, Sprite, SpritePass,
+    SpriteSlicer, Texture2D, Tilemap, TilemapEditor, TilemapPass, Transform, Vec2, Vfs,
+    WeatherPass, Window, WindowFactory, WindowState,
 };
 
-use winit::{dpi::PhysicalSize, event::DeviceEvent, keyboard::KeyCode, window::CursorGrabMode};
+use hecs::Entity;
+use winit::{dpi::PhysicalSize, event::DeviceEvent, keyboard::KeyCode};
 
 pub struct EditorWindow {
     window: Arc<winit::window::Window>,
@@ -19,6 +28,72 @@ pub struct EditorWindow {
     pub delta_timer: DeltaTimer,
     pressed_keys: HashSet<KeyCode>,
     pass_manager: PassManager,
+    /// Copie des infos GPU : `draw` est appelé pendant que le WindowState est verrouillé.
+    engine_info: EngineInfo,
+    show_engine_info: bool,
+    mods: Arc<Mutex<ModManager>>,
+    show_mods: bool,
+    color_picker: ColorPicker,
+    /// Couleur de travail de la fenêtre "Colors" (gestion des palettes).
+    scratch_color: [f32; 4],
+    show_colors: bool,
+    vfs: Arc<Vfs>,
+    preferences: EditorPreferences,
+    /// Le thème est appliqué au contexte egui au prochain `draw`.
+    preferences_dirty: bool,
+    /// Réglages des axes et de la souris, édités dans "Preferences".
+    input_settings: InputSettings,
+    /// Les réglages sont passés à l'`Input` de la fenêtre au prochain `render`.
+    input_settings_dirty: bool,
+    show_preferences: bool,
+    external_editor: ExternalEditor,
+    show_external_editor: bool,
+    /// Recharge les shaders et assets modifiés sur le disque (`None` si la surveillance a
+    /// échoué).
+    asset_watcher: Option<AssetWatcher>,
+    /// Réimporte les assets (et leurs `.meta`) modifiés sur le disque.
+    import_pipeline: ImportPipeline,
+    /// Assets modifiés dans l'éditeur externe, rechargés dans `render` (device disponible).
+    pending_reload: Vec<String>,
+    /// Polices de `assets/fonts`, en secours des polices d'egui.
+    fonts: FontFallbacks,
+    /// Les polices sont repassées à egui au prochain `draw` (chargement, rechargement).
+    fonts_dirty: bool,
+    sprite_slicer: SpriteSlicer,
+    show_sprite_slicer: bool,
+    tilemap_editor: TilemapEditor,
+    show_tilemap_editor: bool,
+    replay_viewer: ReplayViewer,
+    show_replay_viewer: bool,
+    memory_panel: MemoryPanel,
+    show_memory: bool,
+    /// Overlay des temps de frame et draw calls (`FrameStats`).
+    show_frame_stats: bool,
+    /// Profileur CPU : enregistre tant que la fenêtre est ouverte.
+    profiler_panel: ProfilerPanel,
+    show_profiler: bool,
+    /// Derniers ticks de la scène, pour remonter le temps quand un bug apparaît.
+    rewind: RewindBuffer,
+    show_rewind: bool,
+    /// Formes dessinées par la `ShapePass` (gizmos de l'éditeur).
+    debug_draw: DebugDraw,
+    show_scene: bool,
+    /// Entités sélectionnées dans la fenêtre "Scene" (copier / coller / dupliquer).
+    selection: Vec<Entity>,
+    /// Entités de la sélection auxquelles l'éditeur a ajouté un `Highlight`.
+    highlighted: Vec<Entity>,
+    entity_clipboard: EntityClipboard,
+    /// Texte d'entités à coller : les textures sont chargées dans `render`, où le device
+    /// est disponible.
+    pending_paste: Option<String>,
+    loader: AssetLoader,
+    /// Prefabs enregistrés depuis la fenêtre "Scene", utilisés par les `Spawner`.
+    prefabs: PrefabLibrary,
+    /// Fait tourner les spawners de la scène (vagues, pickups) dans l'éditeur.
+    run_spawners: bool,
+    command_palette: CommandPalette,
+    /// Assets proposés par la palette, listés à son ouverture.
+    palette_assets: Vec<String>,
 
     // NEW: accumulate raw mouse delta here too (optional),
     // mais on peut aussi appeler scene.accumulate_mouse directement depuis device_event.
@@ -30,7 +105,7 @@ impl EditorWindow {
     const INITIAL_WIDTH: u32 = 1280;
     const INITIAL_HEIGHT: u32 = 720;
 
-    pub async fn new(window: winit::window::Window) -> Self {
+    pub async fn new(window: winit::window::Window, engine: EngineHandle) -> anyhow::Result<Self> {
         let _ =
             window.request_inner_size(PhysicalSize::new(Self::INITIAL_WIDTH, Self::INITIAL_HEIGHT));
 
@@ -41,7 +116,7 @@ impl EditorWindow {
         let window_width = window.inner_size().width;
         let window_height = window.inner_size().height;
 
-        let state = WindowState::new(
+        let mut state = WindowState::new(
             &instance,
             surface,
             &window,
@@ -49,16 +124,32 @@ impl EditorWindow {
             Self::INITIAL_HEIGHT,
         )
         .await;
+        // Les frames trop longues listent les chargements d'assets en cours
+        state.watchdog.watch_loader(engine.loader.clone());
+        // Le stick gauche déplace la caméra : le curseur virtuel suit le stick droit
+        state.virtual_cursor.axes = (GamepadAxis::RightStickX, GamepadAxis::RightStickY);
 
         let device = &state.device;
         let surface_format = state.config.format;
         let queue = &state.queue;
+        let engine_info = state.info.clone();
 
-        let camera = Camera2D::new(window_width as f32, window_height as f32);
-        let scene = Scene::new("Test Scene".to_string(), camera);
+        let preferences = EditorPreferences::load(&engine.vfs).unwrap_or_else(|e| {
+            log::error!("Failed to load editor preferences: {:#}", e);
+            EditorPreferences::default()
+        });
+
+        let input_settings = InputSettings::load(&engine.vfs).unwrap_or_else(|e| {
+            log::error!("Failed to load input settings: {:#}", e);
+            InputSettings::default()
+        });
+
+        let mut camera = Camera2D::new(window_width as f32, window_height as f32);
+        camera.speed = preferences.camera_speed;
+        let mut scene = Scene::new("Test Scene".to_string(), camera);
         let mut pass_manager = PassManager::new();
 
-        let mut sprite_pass = SpritePass::new(&device, surface_format);
+        let sprite_pass = SpritePass::new(device, surface_format, &engine.loader)?;
 
         // let test_sprite = Sprite::from_file(
         //     device,
@@ -76,13 +167,57 @@ impl EditorWindow {
             eprintln!("Failed to load sprite: {}", err);
             std::process::exit(1);
         });
-        sprite_pass.add_sprite(test_sprite, device);
+        scene.spawn_sprite("Test Sprite", Transform::default(), test_sprite);
 
         pass_manager.add(sprite_pass);
+        // Tilemaps de la scène, dessinées sous les sprites
+        pass_manager.add(TilemapPass::new(device, surface_format, &engine.loader)?);
+        // Éclairage 2D : ambiante blanche, la scène reste inchangée tant qu'elle n'a pas
+        // de lumières
+        let mut lighting_pass = LightingPass::new(device, surface_format, &engine.loader)?;
+        lighting_pass.ambient = [1.0; 3];
+        pass_manager.add(lighting_pass);
+        // Météo de la scène (pluie, neige, brouillard), par-dessus l'éclairage
+        pass_manager.add(WeatherPass::new(device, surface_format, &engine.loader)?);
+        // Contour des entités surlignées (sélection de l'éditeur)
+        pass_manager.add(OutlinePass::new(device, surface_format, &engine.loader)?);
+        // Formes de debug et gizmos, par-dessus la scène
+        let debug_draw = DebugDraw::new();
+        pass_manager.add(ShapePass::new(
+            device,
+            surface_format,
+            &engine.loader,
+            debug_draw.clone(),
+        )?);
+        // Sprites du HUD (entités `ScreenSpace`), dans le repère de la fenêtre
+        pass_manager.add(SpritePass::hud(
+            device,
+            surface_format,
+            &engine.loader,
+            HudLayer::default(),
+        )?);
         // Add the Egui pass so UI is drawn via the PassManager system
         pass_manager.add(EguiPass::new());
 
-        Self {
+        let import_pipeline = ImportPipeline::new(engine.loader.clone());
+        let imported = import_pipeline.import_dir("assets");
+        if !imported.is_empty() {
+            log::info!("Imported {} assets", imported.len());
+        }
+
+        let mut fonts = FontFallbacks::new();
+        let fonts_dirty = fonts.load_dir(&engine.loader, "assets/fonts") > 0;
+
+        let asset_watcher = AssetWatcher::new(engine.loader.clone())
+            .and_then(|mut watcher| {
+                watcher.watch("engine/shaders")?;
+                watcher.watch("assets")?;
+                Ok(watcher)
+            })
+            .inspect_err(|e| log::warn!("Asset hot-reload disabled: {:#}", e))
+            .ok();
+
+        Ok(Self {
             window,
             state: Arc::new(Mutex::new(state)),
             scene,
@@ -90,9 +225,53 @@ impl EditorWindow {
             mouse_captured: false,
             delta_timer: DeltaTimer::new(),
             pressed_keys: HashSet::new(),
+            engine_info,
+            show_engine_info: false,
+            mods: engine.mods.clone(),
+            show_mods: false,
+            color_picker: ColorPicker::new(engine.vfs.clone()),
+            scratch_color: [1.0; 4],
+            show_colors: false,
+            vfs: engine.vfs.clone(),
+            preferences,
+            preferences_dirty: true,
+            input_settings,
+            input_settings_dirty: true,
+            show_preferences: false,
+            external_editor: ExternalEditor::new(engine.loader.clone()),
+            show_external_editor: false,
+            asset_watcher,
+            import_pipeline,
+            pending_reload: Vec::new(),
+            fonts,
+            fonts_dirty,
+            sprite_slicer: SpriteSlicer::new(engine.vfs.clone()),
+            show_sprite_slicer: false,
+            tilemap_editor: TilemapEditor::new(engine.vfs.clone()),
+            show_tilemap_editor: false,
+            replay_viewer: ReplayViewer::new(engine.vfs.clone()),
+            show_replay_viewer: false,
+            memory_panel: MemoryPanel::new(),
+            show_memory: false,
+            show_frame_stats: false,
+            profiler_panel: ProfilerPanel::new(),
+            show_profiler: false,
+            rewind: RewindBuffer::default(),
+            show_rewind: false,
+            debug_draw,
+            show_scene: false,
+            selection: Vec::new(),
+            highlighted: Vec::new(),
+            entity_clipboard: EntityClipboard::new(),
+            pending_paste: None,
+            loader: engine.loader.clone(),
+            prefabs: PrefabLibrary::new(),
+            run_spawners: false,
+            command_palette: CommandPalette::new(),
+            palette_assets: Vec::new(),
             pending_mouse_dx: 0.0,
             pending_mouse_dy: 0.0,
-        }
+        })
     }
 
     pub fn id(&self) -> winit::window::WindowId {
@@ -108,27 +287,460 @@ impl EditorWindow {
     //     self.pressed_keys.remove(&key);
     // }
 
-    // AJOUT: Traitement continu du mouvement basé sur les touches pressées
-    fn process_continuous_movement(&mut self, delta_time: f32) {
-        if self.pressed_keys.is_empty() {
-            return;
+    /// Déplacement et zoom continus de la caméra, d'après les actions de `InputMap::editor`.
+    fn process_continuous_movement(&mut self, delta_time: f32, input: &Input) {
+        let camera = &mut self.scene.camera;
+        for (action, direction) in [
+            ("move_up", CameraMovement::Up),
+            ("move_down", CameraMovement::Down),
+            ("move_left", CameraMovement::Left),
+            ("move_right", CameraMovement::Right),
+        ] {
+            // Stick à mi-course : caméra à mi-vitesse
+            let value = input.value(action);
+            if value > 0.0 {
+                camera.process_movement(direction, delta_time * value);
+            }
         }
 
-        let scene = &mut self.scene;
+        let zoom = input.axis("zoom_out", "zoom_in");
+        if zoom != 0.0 {
+            camera.set_zoom(camera.zoom * (1.0 + zoom * delta_time));
+        }
+    }
+
+    /// Entités de la scène (arborescence) et actions du presse-papiers sur la sélection.
+    fn scene_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            let selected = !self.selection.is_empty();
+            if ui
+                .add_enabled(selected, egui::Button::new("Copy"))
+                .clicked()
+            {
+                self.copy_selection(ui.ctx());
+            }
+            let can_paste = !self.entity_clipboard.is_empty();
+            if ui
+                .add_enabled(can_paste, egui::Button::new("Paste"))
+                .clicked()
+            {
+                self.pending_paste = Some(self.entity_clipboard.text().to_string());
+            }
+            if ui
+                .add_enabled(selected, egui::Button::new("Duplicate"))
+                .clicked()
+            {
+                self.duplicate_selection();
+            }
+        });
+        ui.checkbox(&mut self.run_spawners, "Run spawners");
+        egui::CollapsingHeader::new("Render settings").show(ui, |ui| {
+            // L'UI de l'éditeur reste toujours dessinée
+            let names: Vec<String> = self
+                .pass_manager
+                .ordered_names()
+                .into_iter()
+                .filter(|name| *name != "egui_pass")
+                .map(str::to_string)
+                .collect();
+            let settings = &mut self.scene.render_settings;
+            for name in names {
+                let mut enabled = settings.is_pass_enabled(&name);
+                if ui.checkbox(&mut enabled, &name).changed() {
+                    settings.set_pass_enabled(&name, enabled);
+                }
+            }
+        });
+        egui::CollapsingHeader::new("Environment").show(ui, |ui| {
+            let environment = &mut self.scene.environment;
+            egui::Grid::new("environment_grid").show(ui, |ui| {
+                ui.label("Rain");
+                ui.add(egui::Slider::new(
+                    &mut environment.rain.intensity,
+                    0.0..=1.0,
+                ));
+                ui.end_row();
+                ui.label("Snow");
+                ui.add(egui::Slider::new(
+                    &mut environment.snow.intensity,
+                    0.0..=1.0,
+                ));
+                ui.end_row();
+                ui.label("Fog");
+                ui.add(egui::Slider::new(&mut environment.fog.density, 0.0..=1.0));
+                ui.end_row();
+                ui.label("Wind");
+                ui.add(egui::Slider::new(&mut environment.wind, -400.0..=400.0));
+                ui.end_row();
+            });
+
+            let mut cycle_enabled = self.scene.day_night.is_some();
+            if ui.checkbox(&mut cycle_enabled, "Day/night cycle").changed() {
+                self.scene.day_night = cycle_enabled.then(|| DayNightCycle::new(240.0));
+                if !cycle_enabled {
+                    self.scene.environment.ambient = None;
+                }
+            }
+            if let Some(cycle) = &mut self.scene.day_night {
+                ui.horizontal(|ui| {
+                    ui.label("Time");
+                    let mut time = cycle.time;
+                    if ui.add(egui::Slider::new(&mut time, 0.0..=24.0)).changed() {
+                        cycle.set_time(time);
+                    }
+                    ui.checkbox(&mut cycle.paused, "Paused");
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Day length (s)");
+                    ui.add(egui::DragValue::new(&mut cycle.day_length).range(1.0..=3600.0));
+                });
+            }
+        });
+        ui.separator();
+
+        self.selection.retain(|&entity| self.scene.contains(entity));
+        if let [entity] = self.selection[..] {
+            self.selected_entity_ui(ui, entity);
+            ui.separator();
+        }
+        if !self.prefabs.is_empty() {
+            egui::CollapsingHeader::new("Prefabs").show(ui, |ui| {
+                let mut remove = None;
+                for name in self.prefabs.names() {
+                    ui.horizontal(|ui| {
+                        ui.label(name);
+                        if ui.small_button("x").clicked() {
+                            remove = Some(name.to_string());
+                        }
+                    });
+                }
+                if let Some(name) = remove {
+                    self.prefabs.remove(&name);
+                }
+            });
+            ui.separator();
+        }
+        let roots: Vec<Entity> = self
+            .scene
+            .world
+            .query::<Option<&Parent>>()
+            .iter()
+            .filter(|(_, parent)| parent.is_none())
+            .map(|(entity, _)| entity)
+            .collect();
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            let mut stack: Vec<(Entity, usize)> = roots.into_iter().rev().map(|e| (e, 0)).collect();
+            while let Some((entity, depth)) = stack.pop() {
+                let label = self
+                    .scene
+                    .world
+                    .get::<&Name>(entity)
+                    .map(|name| name.as_str().to_string())
+                    .unwrap_or_else(|_| format!("Entity {:?}", entity));
+                let selected = self.selection.contains(&entity);
+                let response = ui
+                    .horizontal(|ui| {
+                        ui.add_space(depth as f32 * 12.0);
+                        ui.selectable_label(selected, label)
+                    })
+                    .inner;
+                if response.clicked() {
+                    // Ctrl+clic : ajoute / retire de la sélection
+                    if ui.input(|i| i.modifiers.command) {
+                        match self.selection.iter().position(|&e| e == entity) {
+                            Some(index) => {
+                                self.selection.remove(index);
+                            }
+                            None => self.selection.push(entity),
+                        }
+                    } else {
+                        self.selection = vec![entity];
+                    }
+                }
+                let children = self.scene.children_of(entity);
+                stack.extend(children.into_iter().rev().map(|child| (child, depth + 1)));
+            }
+        });
+    }
+
+    /// Prefab et spawner de l'entité sélectionnée.
+    fn selected_entity_ui(&mut self, ui: &mut egui::Ui, entity: Entity) {
+        let name = self
+            .scene
+            .world
+            .get::<&Name>(entity)
+            .map(|name| name.as_str().to_string())
+            .unwrap_or_else(|_| format!("Entity {:?}", entity));
+        if ui
+            .button("Save as prefab")
+            .on_hover_text(format!("Register as prefab {:?} for spawners", name))
+            .clicked()
+            && let Some(snapshot) = self.scene.snapshot(entity)
+        {
+            self.prefabs.insert(name, snapshot);
+        }
+
+        let has_spawner = self.scene.world.get::<&Spawner>(entity).is_ok();
+        egui::CollapsingHeader::new("Spawner")
+            .default_open(has_spawner)
+            .show(ui, |ui| {
+                if !has_spawner {
+                    if ui.button("Add spawner").clicked() {
+                        let table = self
+                            .prefabs
+                            .names()
+                            .map(|name| SpawnEntry::new(name, 1.0))
+                            .collect();
+                        let _ = self
+                            .scene
+                            .world
+                            .insert_one(entity, Spawner::new(table, 1.0));
+                    }
+                    return;
+                }
+                if let Ok(mut spawner) = self.scene.world.get::<&mut Spawner>(entity) {
+                    spawner.ui(ui, &self.prefabs);
+                }
+                if ui.button("Remove spawner").clicked() {
+                    let _ = self.scene.world.remove_one::<Spawner>(entity);
+                }
+            });
+    }
 
-        // Traiter chaque direction pressée
-        for key in &self.pressed_keys {
-            let direction = match key {
-                KeyCode::KeyW => Some(CameraMovement::Up),
-                KeyCode::KeyS => Some(CameraMovement::Down),
-                KeyCode::KeyA => Some(CameraMovement::Left),
-                KeyCode::KeyD => Some(CameraMovement::Right),
+    /// Ctrl+C / Ctrl+V / Ctrl+D sur les entités sélectionnées, quand aucun champ texte n'a
+    /// le focus. Le copier / coller passe par le presse-papiers système, pour coller dans une
+    /// autre instance de l'éditeur.
+    fn clipboard_shortcuts(&mut self, ctx: &egui::Context) {
+        if ctx.wants_keyboard_input() {
+            return;
+        }
+        let (copy, paste) = ctx.input(|i| {
+            let copy = i.events.iter().any(|e| matches!(e, egui::Event::Copy));
+            let paste = i.events.iter().find_map(|e| match e {
+                egui::Event::Paste(text) if EntitySnapshot::is_entities_text(text) => {
+                    Some(text.clone())
+                }
                 _ => None,
-            };
+            });
+            (copy, paste)
+        });
+        let duplicate = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::D);
+
+        if copy && !self.selection.is_empty() {
+            self.copy_selection(ctx);
+        }
+        if paste.is_some() {
+            self.pending_paste = paste;
+        }
+        if ctx.input_mut(|i| i.consume_shortcut(&duplicate)) {
+            self.duplicate_selection();
+        }
+    }
+
+    /// Commandes de la palette : (identifiant, libellé). Toutes ouvrent une fenêtre.
+    const PALETTE_COMMANDS: [(&str, &str); 14] = [
+        ("scene", "Open Scene"),
+        ("tilemap", "Open Tilemap editor"),
+        ("sprite_slicer", "Open Sprite slicer"),
+        ("replay", "Open Replay viewer"),
+        ("memory", "Open Memory"),
+        ("rewind", "Open Rewind"),
+        ("colors", "Open Colors"),
+        ("mods", "Open Mods"),
+        ("preferences", "Open Preferences"),
+        ("external_editor", "Open External editor"),
+        ("engine_info", "Open About GPU"),
+        ("toggle_spawners", "Toggle spawners"),
+        ("toggle_frame_stats", "Toggle frame stats"),
+        ("profiler", "Open Profiler"),
+    ];
+
+    /// Palette Ctrl+P : entités de la scène, assets et commandes de l'éditeur.
+    fn command_palette(&mut self, ctx: &egui::Context) {
+        let was_open = self.command_palette.is_open();
+        self.command_palette.handle_shortcut(ctx);
+        if !self.command_palette.is_open() {
+            return;
+        }
+        if !was_open {
+            self.palette_assets = self.vfs.list_files("assets");
+        }
+
+        let mut entries: Vec<PaletteEntry> = self
+            .scene
+            .world
+            .query::<&Name>()
+            .iter()
+            .map(|(entity, name)| PaletteEntry::new(name.as_str(), PaletteTarget::Entity(entity)))
+            .collect();
+        entries.extend(
+            Self::PALETTE_COMMANDS.iter().map(|(id, label)| {
+                PaletteEntry::new(*label, PaletteTarget::Command(id.to_string()))
+            }),
+        );
+        entries.extend(self.palette_assets.iter().map(|path| {
+            let name = path.rsplit('/').next().unwrap_or(path);
+            PaletteEntry::new(name, PaletteTarget::Asset(path.clone())).detail(path.as_str())
+        }));
+
+        match self.command_palette.ui(ctx, &entries) {
+            Some(PaletteTarget::Entity(entity)) => self.focus_entity(entity),
+            Some(PaletteTarget::Asset(path)) => {
+                if let Err(e) = self
+                    .external_editor
+                    .open(&self.preferences.external_editor, &path)
+                {
+                    log::error!("Failed to open {:?}: {:#}", path, e);
+                }
+            }
+            Some(PaletteTarget::Command(id)) => self.run_command(&id),
+            None => {}
+        }
+    }
+
+    /// Sélectionne `entity` et centre la caméra dessus.
+    fn focus_entity(&mut self, entity: Entity) {
+        let position = match self.scene.world.get::<&GlobalTransform>(entity) {
+            Ok(global) => Some(global.translation()),
+            Err(_) => self
+                .scene
+                .world
+                .get::<&Transform>(entity)
+                .ok()
+                .map(|transform| transform.position),
+        };
+        if let Some(position) = position {
+            self.scene
+                .camera
+                .center_on(Vec2::new(position.x, position.y));
+        }
+        self.selection = vec![entity];
+        self.show_scene = true;
+    }
+
+    fn run_command(&mut self, id: &str) {
+        match id {
+            "scene" => self.show_scene = true,
+            "tilemap" => self.show_tilemap_editor = true,
+            "sprite_slicer" => self.show_sprite_slicer = true,
+            "replay" => self.show_replay_viewer = true,
+            "memory" => self.show_memory = true,
+            "rewind" => self.show_rewind = true,
+            "colors" => self.show_colors = true,
+            "mods" => self.show_mods = true,
+            "preferences" => self.show_preferences = true,
+            "external_editor" => self.show_external_editor = true,
+            "engine_info" => self.show_engine_info = true,
+            "toggle_spawners" => self.run_spawners = !self.run_spawners,
+            "toggle_frame_stats" => self.show_frame_stats = !self.show_frame_stats,
+            "profiler" => self.set_profiler_open(true),
+            other => log::warn!("Unknown editor command {:?}", other),
+        }
+    }
+
+    /// Ouvre ou ferme la fenêtre du profileur, qui n'enregistre que pendant qu'elle est ouverte.
+    fn set_profiler_open(&mut self, open: bool) {
+        self.show_profiler = open;
+        Profiler::set_enabled(open);
+    }
+
+    /// Surligne la sélection et retire le contour des entités désélectionnées. Les
+    /// `Highlight` posés par le jeu ne sont pas touchés.
+    fn highlight_selection(&mut self) {
+        for entity in std::mem::take(&mut self.highlighted) {
+            if self.selection.contains(&entity) {
+                self.highlighted.push(entity);
+            } else {
+                let _ = self.scene.world.remove_one::<Highlight>(entity);
+            }
+        }
+        for &entity in &self.selection {
+            if self.highlighted.contains(&entity)
+                || self.scene.world.get::<&Highlight>(entity).is_ok()
+            {
+                continue;
+            }
+            if self
+                .scene
+                .world
+                .insert_one(entity, Highlight::SELECTION)
+                .is_ok()
+            {
+                self.highlighted.push(entity);
+            }
+        }
+    }
+
+    fn copy_selection(&mut self, ctx: &egui::Context) {
+        let text = self.entity_clipboard.copy(&self.scene, &self.selection);
+        ctx.copy_text(text);
+    }
+
+    fn duplicate_selection(&mut self) {
+        let selection = std::mem::take(&mut self.selection);
+        self.selection = selection
+            .into_iter()
+            .filter_map(|entity| self.scene.duplicate(entity))
+            .collect();
+    }
+
+    /// Recharge les assets modifiés sur le disque (dépendances en premier) : shaders des
+    /// passes, textures de la scène, puis caches des passes.
+    fn reload_assets(&mut self, paths: Vec<String>, window_state: &WindowState) {
+        let (device, queue) = (&window_state.device, &window_state.queue);
+        for path in paths {
+            match AssetKind::from_path(&path) {
+                AssetKind::Shader => {
+                    self.pass_manager.reload_shader(&path, device, &self.loader);
+                }
+                AssetKind::Texture => {
+                    let loader = &self.loader;
+                    let reloaded = self.scene.reload_texture(&path, |linear| {
+                        Ok(Arc::new(if linear {
+                            loader.load_texture_linear(&path, device, queue)?
+                        } else {
+                            loader.load_texture(&path, device, queue)?
+                        }))
+                    });
+                    match reloaded {
+                        Ok(count) => log::info!("Reloaded texture {:?} ({} uses)", path, count),
+                        Err(e) => log::error!("Failed to reload texture {:?}: {:#}", path, e),
+                    }
+                }
+                AssetKind::Font => match self.fonts.reload(&self.loader, &path) {
+                    Ok(reloaded) => self.fonts_dirty |= reloaded,
+                    Err(e) => log::error!("Failed to reload font {:?}: {:#}", path, e),
+                },
+                AssetKind::Other => {}
+            }
+            self.pass_manager.reload_asset(&path);
+        }
+    }
 
-            if let Some(dir) = direction {
-                scene.camera.process_movement(dir, delta_time);
+    /// Colle `text` dans la scène ; les textures référencées sont chargées une seule fois.
+    /// Les entités collées deviennent la sélection.
+    fn paste_entities(&mut self, text: &str, window_state: &WindowState) {
+        let mut textures: HashMap<(String, bool), Arc<Texture2D>> = HashMap::new();
+        let loader = &self.loader;
+        let mut load_texture = |path: &str, linear: bool| -> anyhow::Result<Arc<Texture2D>> {
+            if let Some(texture) = textures.get(&(path.to_string(), linear)) {
+                return Ok(texture.clone());
             }
+            let (device, queue) = (&window_state.device, &window_state.queue);
+            let texture = Arc::new(if linear {
+                loader.load_texture_linear(path, device, queue)?
+            } else {
+                loader.load_texture(path, device, queue)?
+            });
+            textures.insert((path.to_string(), linear), texture.clone());
+            Ok(texture)
+        };
+        match self
+            .entity_clipboard
+            .paste(&mut self.scene, Some(text), &mut load_texture)
+        {
+            Ok(entities) => self.selection = entities,
+            Err(e) => log::error!("Failed to paste entities: {:#}", e),
         }
     }
 }
@@ -151,7 +763,198 @@ impl Window for EditorWindow {
                     println!("Editor UI clicked");
                 }
                 ui.label("Editor tools...");
+                if ui.button("About GPU").clicked() {
+                    self.show_engine_info = !self.show_engine_info;
+                }
+                if ui.button("Mods").clicked() {
+                    self.show_mods = !self.show_mods;
+                }
+                if ui.button("Colors").clicked() {
+                    self.show_colors = !self.show_colors;
+                }
+                if ui.button("Preferences").clicked() {
+                    self.show_preferences = !self.show_preferences;
+                }
+                if ui.button("External editor").clicked() {
+                    self.show_external_editor = !self.show_external_editor;
+                }
+                if ui.button("Sprite slicer").clicked() {
+                    self.show_sprite_slicer = !self.show_sprite_slicer;
+                }
+                if ui.button("Replay").clicked() {
+                    self.show_replay_viewer = !self.show_replay_viewer;
+                }
+                if ui.button("Memory").clicked() {
+                    self.show_memory = !self.show_memory;
+                }
+                if ui.button("Frame stats").clicked() {
+                    self.show_frame_stats = !self.show_frame_stats;
+                }
+                if ui.button("Profiler").clicked() {
+                    self.set_profiler_open(!self.show_profiler);
+                }
+                if ui.button("Rewind").clicked() {
+                    self.show_rewind = !self.show_rewind;
+                }
+                if ui.button("Tilemap").clicked() {
+                    self.show_tilemap_editor = !self.show_tilemap_editor;
+                }
+                if ui.button("Scene").clicked() {
+                    self.show_scene = !self.show_scene;
+                }
+            });
+
+        let mut show_scene = self.show_scene;
+        egui::Window::new("Scene")
+            .open(&mut show_scene)
+            .default_width(240.0)
+            .show(ctx, |ui| self.scene_ui(ui));
+        self.show_scene = show_scene;
+        self.clipboard_shortcuts(ctx);
+        self.command_palette(ctx);
+
+        egui::Window::new("External editor")
+            .open(&mut self.show_external_editor)
+            .show(ctx, |ui| {
+                self.external_editor
+                    .ui(ui, &self.preferences.external_editor);
+            });
+
+        egui::Window::new("Sprite slicer")
+            .open(&mut self.show_sprite_slicer)
+            .default_width(640.0)
+            .show(ctx, |ui| {
+                self.sprite_slicer.ui(ui);
+            });
+
+        egui::Window::new("Replay viewer")
+            .open(&mut self.show_replay_viewer)
+            .default_width(360.0)
+            .show(ctx, |ui| {
+                self.replay_viewer.ui(ui);
+            });
+
+        if self.show_memory {
+            // Les entités sont estimées seulement quand le panneau est ouvert
+            let (bytes, entities) = self.scene.memory_estimate();
+            engine::set_memory_gauge(MemoryCategory::Entities, bytes, entities);
+        }
+        egui::Window::new("Memory")
+            .open(&mut self.show_memory)
+            .default_width(420.0)
+            .show(ctx, |ui| {
+                self.memory_panel.ui(ui);
+            });
+
+        let mut show_profiler = self.show_profiler;
+        egui::Window::new("Profiler")
+            .open(&mut show_profiler)
+            .default_width(640.0)
+            .show(ctx, |ui| {
+                self.profiler_panel.ui(ui);
+            });
+        if show_profiler != self.show_profiler {
+            self.set_profiler_open(show_profiler);
+        }
+
+        let mut restored = false;
+        egui::Window::new("Rewind")
+            .open(&mut self.show_rewind)
+            .default_width(320.0)
+            .show(ctx, |ui| {
+                restored = self.rewind.ui(ui, &mut self.scene);
+            });
+        if restored {
+            // Les entités ont été recréées
+            self.selection.clear();
+        }
+
+        // Édition de la première tilemap de la scène (créée depuis la fenêtre s'il n'y en a pas)
+        let mut new_map = None;
+        egui::Window::new("Tilemap")
+            .open(&mut self.show_tilemap_editor)
+            .default_width(320.0)
+            .show(ctx, |ui| {
+                let query = self.scene.world.query_mut::<&mut Tilemap>();
+                match query.into_iter().next() {
+                    Some((_, map)) => self.tilemap_editor.ui(ui, map),
+                    None => new_map = self.tilemap_editor.new_map_ui(ui),
+                }
+            });
+        if let Some(map) = new_map {
+            self.scene
+                .spawn((Name::new("Tilemap"), Transform::default(), map));
+        }
+        if self.show_tilemap_editor {
+            let query =
+                self.scene
+                    .world
+                    .query_mut::<(&mut Tilemap, Option<&GlobalTransform>, Option<&Transform>)>();
+            if let Some((_, (map, global, transform))) = query.into_iter().next() {
+                let model = global
+                    .map(GlobalTransform::matrix)
+                    .or_else(|| transform.map(Transform::matrix))
+                    .unwrap_or_else(Mat4::identity);
+                self.tilemap_editor
+                    .viewport(ctx, &self.scene.camera, map, &model);
+            }
+        }
+
+        // Fichiers modifiés dans l'éditeur externe -> hot-reload
+        let reload = self.external_editor.poll_changes();
+        if !reload.is_empty() {
+            log::info!("Assets changed on disk, reload order: {:?}", reload);
+            self.pending_reload.extend(reload);
+        }
+
+        let mut preferences_changed = false;
+        let mut input_settings_changed = false;
+        egui::Window::new("Preferences")
+            .open(&mut self.show_preferences)
+            .show(ctx, |ui| {
+                preferences_changed = self.preferences.ui(ui);
+                ui.collapsing("Input", |ui| {
+                    input_settings_changed = self.input_settings.ui(ui);
+                });
             });
+        if input_settings_changed {
+            if let Err(e) = self.input_settings.save(&self.vfs) {
+                log::error!("Failed to save input settings: {:#}", e);
+            }
+            self.input_settings_dirty = true;
+        }
+        if preferences_changed {
+            if let Err(e) = self.preferences.save(&self.vfs) {
+                log::error!("Failed to save editor preferences: {:#}", e);
+            }
+            self.scene.camera.speed = self.preferences.camera_speed;
+            self.preferences_dirty = true;
+        }
+        if self.preferences_dirty {
+            ctx.set_visuals(self.preferences.theme.visuals());
+            self.preferences_dirty = false;
+        }
+        if self.fonts_dirty {
+            ctx.set_fonts(self.fonts.definitions("en"));
+            self.fonts_dirty = false;
+        }
+
+        egui::Window::new("Colors")
+            .open(&mut self.show_colors)
+            .show(ctx, |ui| {
+                self.color_picker
+                    .edit(ui, "scratch_color", &mut self.scratch_color);
+            });
+
+        let mods = self.mods.clone();
+        egui::Window::new("Mods")
+            .open(&mut self.show_mods)
+            .show(ctx, |ui| {
+                mods.lock().unwrap().ui(ui);
+            });
+
+        self.engine_info
+            .show_window(ctx, &mut self.show_engine_info);
     }
 
     fn is_mouse_captured(&self) -> bool {
@@ -159,21 +962,15 @@ impl Window for EditorWindow {
     }
 
     fn set_mouse_capture(&mut self, capture: bool) {
-        self.mouse_captured = capture;
-
-        if capture {
-            self.window()
-                .set_cursor_grab(CursorGrabMode::Locked)
-                .or_else(|_| self.window().set_cursor_grab(CursorGrabMode::Confined))
-                .ok();
-            self.window().set_cursor_visible(false);
-        } else {
-            self.window()
-                .set_cursor_grab(CursorGrabMode::None)
-                .or_else(|_| self.window().set_cursor_grab(CursorGrabMode::Confined))
-                .ok();
-            self.window().set_cursor_visible(true);
+        // Les clics dans le viewport peignent la tilemap
+        if capture && self.show_tilemap_editor && self.tilemap_editor.is_painting() {
+            return;
         }
+        self.mouse_captured = capture;
+        self.state
+            .lock()
+            .unwrap()
+            .set_mouse_capture(&self.window, capture);
     }
 
     fn render(
@@ -183,29 +980,111 @@ impl Window for EditorWindow {
         window_state: &mut WindowState,
     ) {
         let delta_time = self.delta_timer.update();
+        window_state.frame_stats.record_timer(&self.delta_timer);
+        window_state.frame_stats.overlay = self.show_frame_stats;
 
-        self.process_continuous_movement(delta_time);
+        if self.input_settings_dirty {
+            window_state.input_mut().settings = self.input_settings.clone();
+            self.input_settings_dirty = false;
+        }
+        self.process_continuous_movement(delta_time, window_state.input());
+
+        // Molette : zoom de 10% par cran, centré sur le curseur
+        let (_, scroll) = window_state.take_scroll_delta();
+        if scroll != 0.0 {
+            let camera = &mut self.scene.camera;
+            let anchor = window_state.cursor_world_position(camera);
+            camera.set_zoom(camera.zoom * 1.1f32.powf(scroll));
+            if let Some(before) = anchor
+                && let Some(after) = window_state.cursor_world_position(camera)
+            {
+                camera.position += before - after;
+            }
+        }
+
+        if let Some(text) = self.pending_paste.take() {
+            self.paste_entities(&text, window_state);
+        }
+
+        // Gizmo de l'origine du monde : axe x en rouge, axe y en vert
+        let origin = Vec2::zeros();
+        self.debug_draw
+            .line(origin, Vec2::new(64.0, 0.0), [1.0, 0.2, 0.2, 1.0]);
+        self.debug_draw
+            .line(origin, Vec2::new(0.0, 64.0), [0.2, 1.0, 0.2, 1.0]);
 
         // Prefer consuming mouse delta from the central WindowState input.
-        let (dx, dy) = window_state.take_mouse_delta();
+        let mouse_delta = window_state.take_mouse_delta();
+        let (dx, dy) = window_state.input().mouse_delta(mouse_delta);
+        let sensitivity = self.preferences.mouse_sensitivity;
+        let (dx, dy) = (dx * sensitivity, dy * sensitivity);
         if window_state.is_mouse_captured() && (dx != 0.0 || dy != 0.0) {
             // apply to the scene (single-threaded ownership)
             self.scene.accumulate_mouse(dx, dy);
         } else if self.mouse_captured {
             // fallback: if for some reason local accumulation exists, consume it.
             if self.pending_mouse_dx != 0.0 || self.pending_mouse_dy != 0.0 {
-                self.scene
-                    .accumulate_mouse(self.pending_mouse_dx, self.pending_mouse_dy);
+                self.scene.accumulate_mouse(
+                    self.pending_mouse_dx * sensitivity,
+                    self.pending_mouse_dy * sensitivity,
+                );
 
                 self.pending_mouse_dx = 0.0;
                 self.pending_mouse_dy = 0.0;
             }
         }
 
-        self.scene.update(delta_time);
+        // Temps de jeu : ralenti / figé par `DeltaTimer::set_time_scale` et `hit_stop`
+        let game_delta_time = self.delta_timer.scaled_delta_time();
+        let update_started = Instant::now();
+        self.scene.update(game_delta_time);
+
+        // Enregistrement / lecture du replay, trajectoires par-dessus la scène
+        let pressed_keys = &self.pressed_keys;
+        self.replay_viewer.update(delta_time, &self.scene, || {
+            let mut inputs: Vec<String> = pressed_keys
+                .iter()
+                .map(|key| format!("{:?}", key))
+                .collect();
+            inputs.sort();
+            inputs
+        });
+        if self.show_replay_viewer {
+            self.replay_viewer.draw(&self.debug_draw);
+        }
+
+        // Simulation figée pendant l'inspection d'un tick passé
+        self.rewind.update(delta_time, &self.scene);
+        if self.run_spawners && !self.rewind.is_paused() {
+            for event in self.scene.update_spawners(&self.prefabs, game_delta_time) {
+                if let SpawnerEvent::WavesCompleted { spawner } = event {
+                    log::info!("Spawner {:?}: all waves completed", spawner);
+                }
+            }
+        }
+        window_state
+            .frame_budgets
+            .record(FramePhase::Update, update_started.elapsed());
+
+        let mut reload = std::mem::take(&mut self.pending_reload);
+        if let Some(watcher) = &mut self.asset_watcher {
+            reload.extend(watcher.poll_changes());
+        }
+        let reload_scope = tracing::trace_span!("reload_assets").entered();
+        let reimported = self.import_pipeline.reimport_changed(&reload);
+        if !reimported.is_empty() {
+            log::info!("Reimported {:?}", reimported);
+        }
+        self.reload_assets(reload, window_state);
+        drop(reload_scope);
+
+        self.highlight_selection();
 
         // 5) Prepare GPU uploads using WindowState helpers
-        self.scene.prepare_gpu(window_state.queue());
+        let queue = window_state.queue.clone();
+        window_state
+            .frame_budgets
+            .measure(FramePhase::PrepareGpu, || self.scene.prepare_gpu(&queue));
 
         self.scene.render(
             encoder,
@@ -214,14 +1093,16 @@ impl Window for EditorWindow {
             window_state.queue(),
         );
 
-        let queue = window_state.queue.clone();
         let mut pass_ctx = PassContext {
             encoder,
-            target: &surface_view,
+            target: surface_view,
+            depth: None,
             queue: &queue,
             camera: &self.scene.camera,
-            window: &*self.window,
+            scene: &self.scene,
+            window: &self.window,
             window_state,
+            viewport: None,
         };
 
         self.pass_manager.execute_all(&mut pass_ctx);
@@ -244,6 +1125,14 @@ impl Window for EditorWindow {
         }
     }
 
+    fn on_shutdown(&mut self) {
+        // Plus de rechargement pendant l'arrêt, puis textures, buffers et pipelines libérés
+        // avant la surface
+        self.asset_watcher = None;
+        self.pass_manager.clear();
+        self.scene.clear();
+    }
+
     fn on_key_pressed(&mut self, key: KeyCode) {
         self.pressed_keys.insert(key);
     }
@@ -272,13 +1161,19 @@ impl Window for EditorWindow {
 impl WindowFactory for EditorWindow {
     fn create(
         winit_window: winit::window::Window,
+        engine: EngineHandle,
     ) -> std::pin::Pin<
         Box<dyn std::future::Future<Output = Result<Self, Box<dyn std::error::Error>>> + Send>,
     >
     where
         Self: Sized,
     {
-        Box::pin(async move { Ok(EditorWindow::new(winit_window).await) })
+        // Le message garde toute la chaîne de contexte d'`anyhow` (passe, asset, device...)
+        Box::pin(async move {
+            EditorWindow::new(winit_window, engine)
+                .await
+                .map_err(|e| format!("{:#}", e).into())
+        })
     }
 }
 
@@ -7,6 +7,34 @@ use crate::app::App;
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    // `app pack <dossier> <archive>` : packe les assets pour un jeu livré (voir `ZipFs`)
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("pack") {
+        let dir = args.get(2).map_or("assets", String::as_str);
+        let output = args
+            .get(3)
+            .map_or(engine::Engine::ASSETS_ARCHIVE, String::as_str);
+        let count = engine::pack_directory(dir, output)?;
+        println!("Packed {} files from {:?} into {:?}", count, dir, output);
+        return Ok(());
+    }
+    // `app check-case [dossier]` : références d'assets dont la casse diffère du disque
+    if args.get(1).map(String::as_str) == Some("check-case") {
+        let dir = args.get(2).map_or("assets", String::as_str);
+        let mismatches = engine::find_case_mismatches(dir)?;
+        for mismatch in &mismatches {
+            println!(
+                "{}: {:?} only matches {:?} ignoring case",
+                mismatch.file, mismatch.reference, mismatch.actual
+            );
+        }
+        println!("{} case mismatches in {:?}", mismatches.len(), dir);
+        if !mismatches.is_empty() {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     let mut app = App::new();
     app.init()?;
 
@@ -0,0 +1,123 @@
+//! Benchmarks du pipeline de sprites sur les scènes de `BenchScene` : tri, batching et
+//! construction des instances des sprites (`SpritePass::prepare_frame`), culling des chunks
+//! et instances de la grande tilemap.
+//!
+//! ```text
+//! cargo bench -p engine                 # toutes les mesures
+//! cargo bench -p engine -- tilemap      # seulement celles dont le nom contient "tilemap"
+//! ```
+//!
+//! Les mesures passent par criterion, qui garde les résultats précédents dans
+//! `target/criterion` et signale les régressions d'une exécution à l'autre.
+//!
+//! Les sprites ont besoin d'un device wgpu (textures, bind groups) : sans adaptateur, seules
+//! les mesures de la tilemap sont faites.
+
+use std::{hint::black_box, io::Cursor, path::PathBuf, sync::Arc};
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use egui_wgpu::wgpu;
+use engine::{AssetLoader, BenchScene, SpritePass, Texture2D, Tilemap, Vfs};
+use nalgebra::Matrix4;
+
+fn gpu() -> Option<(wgpu::Device, wgpu::Queue)> {
+    let instance = wgpu::Instance::default();
+    let adapter =
+        pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions::default()))
+            .ok()?;
+    pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor::default())).ok()
+}
+
+/// Quatre textures unies de 128 x 16 (huit images d'animation de 16 x 16).
+fn textures(device: &wgpu::Device, queue: &wgpu::Queue) -> Vec<Arc<Texture2D>> {
+    [[255, 80, 80], [80, 255, 80], [80, 80, 255], [255, 255, 255]]
+        .into_iter()
+        .map(|[r, g, b]| {
+            let image = image::RgbaImage::from_pixel(128, 16, image::Rgba([r, g, b, 255]));
+            let mut png = Cursor::new(Vec::new());
+            image
+                .write_to(&mut png, image::ImageFormat::Png)
+                .expect("encode bench texture");
+            Arc::new(Texture2D::from_bytes(device, queue, png.get_ref()).expect("bench texture"))
+        })
+        .collect()
+}
+
+fn sprite_benches(c: &mut Criterion) {
+    let Some((device, queue)) = gpu() else {
+        println!("No wgpu adapter: skipping the sprite benchmarks");
+        return;
+    };
+    let vfs = Arc::new(Vfs::new());
+    let engine_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../engine");
+    vfs.mount_os("engine", engine_dir, "Engine", false);
+    let loader = AssetLoader::new(vfs);
+    let textures = textures(&device, &queue);
+
+    for scene in [
+        BenchScene::Sprites10k,
+        BenchScene::AnimatedSprites1k,
+        BenchScene::ParticleStorm,
+    ] {
+        let mut pass = SpritePass::new(&device, wgpu::TextureFormat::Bgra8UnormSrgb, &loader)
+            .expect("sprite pass");
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Bench Encoder"),
+        });
+        let mut world = scene.build(&textures);
+        let counts = pass.prepare_frame(&device, &mut encoder, &world);
+        println!(
+            "{}: {} instances in {} draw calls",
+            scene.name(),
+            counts.instances,
+            counts.draw_calls
+        );
+
+        let mut time = 0.0;
+        c.bench_function(&format!("scene_update/{}", scene.name()), |b| {
+            b.iter(|| {
+                time += 1.0 / 60.0;
+                scene.update(&mut world, time);
+            })
+        });
+        c.bench_function(&format!("sprite_batching/{}", scene.name()), |b| {
+            b.iter(|| black_box(pass.prepare_frame(&device, &mut encoder, &world)))
+        });
+    }
+}
+
+fn tilemap_benches(c: &mut Criterion) {
+    let scene = BenchScene::LargeTilemap.build(&[]);
+    let mut query = scene.world.query::<&Tilemap>();
+    let Some((_, map)) = query.iter().next() else {
+        return;
+    };
+    let mut camera = engine::Camera2D::new(BenchScene::VIEWPORT.0, BenchScene::VIEWPORT.1);
+    let model = Matrix4::identity();
+
+    // La caméra parcourt la carte en diagonale
+    let mut step = 0u32;
+    c.bench_function("tilemap_culling/large_tilemap", |b| {
+        b.iter(|| {
+            step = (step + 1) % 256;
+            camera.position = [step as f32 * 60.0, step as f32 * 60.0].into();
+            let (min, max) = camera.visible_rect();
+            black_box(map.visible_chunks(&model, min.into(), max.into()))
+        })
+    });
+
+    let (min, max) = camera.visible_rect();
+    let visible = map.visible_chunks(&model, min.into(), max.into());
+    c.bench_function("tilemap_instances/large_tilemap", |b| {
+        b.iter(|| {
+            for layer in 0..map.layers().len() {
+                for &(chunk_x, chunk_y) in &visible {
+                    black_box(map.chunk_instances(layer, chunk_x, chunk_y));
+                }
+            }
+        })
+    });
+}
+
+criterion_group!(benches, tilemap_benches, sprite_benches);
+criterion_main!(benches);
@@ -0,0 +1,505 @@
+//! Succès et quêtes : définitions chargées depuis le VFS, progression calculée à partir du
+//! `Blackboard`, événements de déblocage et popup par défaut.
+//!
+//! Chaque objectif suit une entrée du blackboard (compteur ou drapeau) jusqu'à une cible.
+//! Un succès / une quête débloqué(e) est écrit(e) dans le blackboard
+//! (`achievement.<id> = bool true`) : l'état est donc sauvegardé et rechargé avec lui
+//! (`Blackboard::save` / `Blackboard::load`).
+//!
+//! ```text
+//! achievement first_blood
+//! title = First blood
+//! description = Defeat your first enemy
+//! objective = enemies_killed 1
+//!
+//! quest lost_key
+//! title = The lost key
+//! requires = first_blood
+//! objective = keys_found 3 Find the three keys
+//! objective = door_open 1 Open the door
+//! ```
+
+use std::{
+    collections::{HashMap, VecDeque},
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result, anyhow, bail};
+
+use crate::{Blackboard, BlackboardValue, Vfs};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AchievementKind {
+    Achievement,
+    Quest,
+}
+
+impl AchievementKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            AchievementKind::Achievement => "achievement",
+            AchievementKind::Quest => "quest",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [AchievementKind::Achievement, AchievementKind::Quest]
+            .into_iter()
+            .find(|kind| kind.name() == name)
+    }
+}
+
+/// Objectif : l'entrée `key` du blackboard doit atteindre `target`. Un booléen vaut 1 s'il
+/// est vrai, 0 sinon.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Objective {
+    pub key: String,
+    pub target: f64,
+    /// Texte affiché dans le journal de quêtes (vide = la clé).
+    pub label: String,
+}
+
+impl Objective {
+    pub fn new(key: impl Into<String>, target: f64) -> Self {
+        Self {
+            key: key.into(),
+            target,
+            label: String::new(),
+        }
+    }
+
+    /// Avancement dans `0..=1`.
+    pub fn progress(&self, blackboard: &Blackboard) -> f32 {
+        let value = match blackboard.get(&self.key) {
+            Some(BlackboardValue::Bool(value)) => f64::from(u8::from(*value)),
+            Some(value) => value.as_float().unwrap_or(0.0),
+            None => 0.0,
+        };
+        if self.target <= 0.0 {
+            return 1.0;
+        }
+        (value / self.target).clamp(0.0, 1.0) as f32
+    }
+}
+
+/// Définition d'un succès ou d'une quête.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AchievementDef {
+    pub id: String,
+    pub kind: AchievementKind,
+    pub title: String,
+    pub description: String,
+    /// Tous doivent être atteints. Sans objectif, seul `Achievements::unlock` le débloque.
+    pub objectives: Vec<Objective>,
+    /// Succès / quêtes à débloquer avant que celui-ci ne progresse.
+    pub requires: Vec<String>,
+    /// Titre et description masqués tant qu'il n'est pas débloqué.
+    pub hidden: bool,
+}
+
+impl AchievementDef {
+    pub fn new(id: impl Into<String>, kind: AchievementKind) -> Self {
+        let id = id.into();
+        Self {
+            title: id.clone(),
+            id,
+            kind,
+            description: String::new(),
+            objectives: Vec::new(),
+            requires: Vec::new(),
+            hidden: false,
+        }
+    }
+
+    /// Clé du blackboard qui enregistre le déblocage.
+    pub fn unlocked_key(&self) -> String {
+        format!("{}{}", Achievements::UNLOCKED_PREFIX, self.id)
+    }
+
+    /// Moyenne de l'avancement des objectifs, dans `0..=1`.
+    pub fn progress(&self, blackboard: &Blackboard) -> f32 {
+        if self.objectives.is_empty() {
+            return 0.0;
+        }
+        let total: f32 = self.objectives.iter().map(|o| o.progress(blackboard)).sum();
+        total / self.objectives.len() as f32
+    }
+
+    pub fn is_complete(&self, blackboard: &Blackboard) -> bool {
+        !self.objectives.is_empty()
+            && self
+                .objectives
+                .iter()
+                .all(|objective| objective.progress(blackboard) >= 1.0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AchievementEvent {
+    /// L'avancement (voir `AchievementDef::progress`) a changé.
+    Progress {
+        id: String,
+        progress: f32,
+    },
+    Unlocked {
+        id: String,
+    },
+}
+
+/// Ensemble des définitions et suivi de leur progression.
+#[derive(Debug, Clone, Default)]
+pub struct Achievements {
+    definitions: Vec<AchievementDef>,
+    /// Dernier avancement signalé, par id.
+    progress: HashMap<String, f32>,
+    /// Révision du blackboard lors du dernier `update`.
+    revision: Option<u64>,
+}
+
+impl Achievements {
+    /// Préfixe des clés de déblocage dans le blackboard.
+    pub const UNLOCKED_PREFIX: &str = "achievement.";
+
+    pub fn new(definitions: Vec<AchievementDef>) -> Self {
+        Self {
+            definitions,
+            ..Default::default()
+        }
+    }
+
+    /// Lit le format texte (voir le module).
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut definitions: Vec<AchievementDef> = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let context = || format!("line {}", number + 1);
+
+            let Some((key, value)) = line.split_once('=') else {
+                let (kind, id) = line.split_once(' ').unwrap_or((line, ""));
+                let kind = AchievementKind::from_name(kind)
+                    .ok_or_else(|| anyhow!("unknown entry {:?}", kind))
+                    .with_context(context)?;
+                let id = id.trim();
+                if id.is_empty() {
+                    bail!("line {}: missing {} id", number + 1, kind.name());
+                }
+                if definitions.iter().any(|def| def.id == id) {
+                    bail!("line {}: duplicate id {:?}", number + 1, id);
+                }
+                definitions.push(AchievementDef::new(id, kind));
+                continue;
+            };
+
+            let def = definitions
+                .last_mut()
+                .ok_or_else(|| anyhow!("line {}: property outside of an entry", number + 1))?;
+            let value = value.trim();
+            match key.trim() {
+                "title" => def.title = value.to_string(),
+                "description" => def.description = value.to_string(),
+                "hidden" => def.hidden = value.parse().with_context(context)?,
+                "requires" => def.requires.extend(
+                    value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|id| !id.is_empty())
+                        .map(str::to_string),
+                ),
+                "objective" => {
+                    let mut parts = value.splitn(3, ' ');
+                    let key = parts.next().filter(|key| !key.is_empty());
+                    let key = key.ok_or_else(|| {
+                        anyhow!("line {}: expected `objective = key [target]`", number + 1)
+                    })?;
+                    let target = match parts.next() {
+                        Some(target) => target.parse().with_context(context)?,
+                        None => 1.0,
+                    };
+                    let mut objective = Objective::new(key, target);
+                    objective.label = parts.next().unwrap_or("").trim().to_string();
+                    def.objectives.push(objective);
+                }
+                other => log::warn!("Unknown achievement key {:?} (line {})", other, number + 1),
+            }
+        }
+
+        for def in &definitions {
+            if let Some(missing) = def
+                .requires
+                .iter()
+                .find(|id| !definitions.iter().any(|other| &other.id == *id))
+            {
+                bail!("{:?} requires unknown entry {:?}", def.id, missing);
+            }
+        }
+        Ok(Self::new(definitions))
+    }
+
+    pub fn load(vfs: &Vfs, path: &str) -> Result<Self> {
+        let text = vfs.read_to_string(path)?;
+        Self::parse(&text).with_context(|| format!("failed to parse {:?}", path))
+    }
+
+    pub fn definitions(&self) -> &[AchievementDef] {
+        &self.definitions
+    }
+
+    pub fn get(&self, id: &str) -> Option<&AchievementDef> {
+        self.definitions.iter().find(|def| def.id == id)
+    }
+
+    pub fn is_unlocked(&self, blackboard: &Blackboard, id: &str) -> bool {
+        blackboard
+            .get_bool(&format!("{}{}", Self::UNLOCKED_PREFIX, id))
+            .unwrap_or(false)
+    }
+
+    /// Vrai si tous les prérequis de `def` sont débloqués.
+    pub fn is_available(&self, blackboard: &Blackboard, def: &AchievementDef) -> bool {
+        def.requires
+            .iter()
+            .all(|id| self.is_unlocked(blackboard, id))
+    }
+
+    /// Débloque `id` (ex: succès sans objectif, déclenché par le jeu). Retourne `false` s'il
+    /// n'existe pas ou était déjà débloqué.
+    pub fn unlock(&mut self, blackboard: &mut Blackboard, id: &str) -> bool {
+        let Some(def) = self.get(id) else {
+            return false;
+        };
+        if self.is_unlocked(blackboard, id) {
+            return false;
+        }
+        blackboard.set(def.unlocked_key(), true);
+        true
+    }
+
+    /// Oublie tous les déblocages (nouvelle partie).
+    pub fn reset(&mut self, blackboard: &mut Blackboard) {
+        for def in &self.definitions {
+            blackboard.remove(&def.unlocked_key());
+        }
+        self.progress.clear();
+        self.revision = None;
+    }
+
+    /// À appeler après les systèmes de jeu : recalcule la progression si le blackboard a
+    /// changé et débloque ce qui est terminé (ce qui peut rendre d'autres quêtes
+    /// disponibles, traitées dans le même appel).
+    pub fn update(&mut self, blackboard: &mut Blackboard) -> Vec<AchievementEvent> {
+        let mut events = Vec::new();
+        while self.revision != Some(blackboard.revision()) {
+            self.revision = Some(blackboard.revision());
+            for def in &self.definitions {
+                if self.is_unlocked(blackboard, &def.id) || !self.is_available(blackboard, def) {
+                    continue;
+                }
+                let progress = def.progress(blackboard);
+                if self.progress.insert(def.id.clone(), progress) != Some(progress) {
+                    events.push(AchievementEvent::Progress {
+                        id: def.id.clone(),
+                        progress,
+                    });
+                }
+                if def.is_complete(blackboard) {
+                    blackboard.set(def.unlocked_key(), true);
+                    events.push(AchievementEvent::Unlocked { id: def.id.clone() });
+                }
+            }
+        }
+        events
+    }
+
+    /// Journal : quêtes puis succès, avec leur avancement.
+    pub fn ui(&self, ui: &mut egui::Ui, blackboard: &Blackboard) {
+        for kind in [AchievementKind::Quest, AchievementKind::Achievement] {
+            let defs: Vec<&AchievementDef> = self
+                .definitions
+                .iter()
+                .filter(|def| def.kind == kind)
+                .collect();
+            if defs.is_empty() {
+                continue;
+            }
+            ui.heading(match kind {
+                AchievementKind::Quest => "Quests",
+                AchievementKind::Achievement => "Achievements",
+            });
+            for def in defs {
+                let unlocked = self.is_unlocked(blackboard, &def.id);
+                if def.hidden && !unlocked {
+                    ui.label(egui::RichText::new("???").weak());
+                    continue;
+                }
+                ui.horizontal(|ui| {
+                    ui.label(if unlocked { "✔" } else { "•" });
+                    ui.strong(&def.title);
+                    if !self.is_available(blackboard, def) {
+                        ui.label(egui::RichText::new("(locked)").weak());
+                    }
+                });
+                if !def.description.is_empty() {
+                    ui.label(&def.description);
+                }
+                if unlocked {
+                    continue;
+                }
+                for objective in &def.objectives {
+                    let label = if objective.label.is_empty() {
+                        &objective.key
+                    } else {
+                        &objective.label
+                    };
+                    ui.add(
+                        egui::ProgressBar::new(objective.progress(blackboard))
+                            .desired_width(200.0)
+                            .text(label.as_str()),
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Popup par défaut affichée au déblocage, une à la fois.
+#[derive(Debug, Default)]
+pub struct AchievementPopup {
+    queue: VecDeque<(String, String)>,
+    current: Option<((String, String), Instant)>,
+}
+
+impl AchievementPopup {
+    /// Durée d'affichage de chaque popup.
+    pub const DURATION: Duration = Duration::from_secs(4);
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ajoute une popup par événement `Unlocked` de `events`.
+    pub fn push_events(&mut self, achievements: &Achievements, events: &[AchievementEvent]) {
+        for event in events {
+            if let AchievementEvent::Unlocked { id } = event
+                && let Some(def) = achievements.get(id)
+            {
+                let heading = match def.kind {
+                    AchievementKind::Achievement => "Achievement unlocked",
+                    AchievementKind::Quest => "Quest completed",
+                };
+                self.queue
+                    .push_back((heading.to_string(), def.title.clone()));
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.current.is_none() && self.queue.is_empty()
+    }
+
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        let now = Instant::now();
+        if self
+            .current
+            .as_ref()
+            .is_some_and(|(_, shown)| now.duration_since(*shown) >= Self::DURATION)
+        {
+            self.current = None;
+        }
+        if self.current.is_none() {
+            self.current = self.queue.pop_front().map(|popup| (popup, now));
+        }
+        let Some(((heading, title), shown)) = &self.current else {
+            return;
+        };
+
+        // Fondu sur la dernière demi-seconde
+        let remaining = Self::DURATION.saturating_sub(now.duration_since(*shown));
+        let opacity = (remaining.as_secs_f32() / 0.5).min(1.0);
+        egui::Area::new(egui::Id::new("achievement_popup"))
+            .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 24.0))
+            .interactable(false)
+            .show(ctx, |ui| {
+                ui.set_opacity(opacity);
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.label(egui::RichText::new(heading.as_str()).small().weak());
+                    ui.label(egui::RichText::new(title.as_str()).strong().size(18.0));
+                });
+            });
+        ctx.request_repaint();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEFINITIONS: &str = "
+achievement first_blood
+title = First blood
+objective = enemies_killed 1
+
+quest lost_key
+requires = first_blood
+objective = keys_found 3 Find the three keys
+objective = door_open
+";
+
+    #[test]
+    fn parse_definitions() {
+        let achievements = Achievements::parse(DEFINITIONS).unwrap();
+        let quest = achievements.get("lost_key").unwrap();
+        assert_eq!(quest.kind, AchievementKind::Quest);
+        assert_eq!(quest.title, "lost_key");
+        assert_eq!(quest.requires, ["first_blood"]);
+        assert_eq!(quest.objectives[0].target, 3.0);
+        assert_eq!(quest.objectives[0].label, "Find the three keys");
+        assert_eq!(quest.objectives[1], Objective::new("door_open", 1.0));
+
+        assert!(Achievements::parse("title = orphan").is_err());
+        assert!(Achievements::parse("quest a\nrequires = b").is_err());
+        assert!(Achievements::parse("badge a").is_err());
+    }
+
+    #[test]
+    fn progress_unlocks_and_chains() {
+        let mut achievements = Achievements::parse(DEFINITIONS).unwrap();
+        let mut blackboard = Blackboard::new();
+        blackboard.set("keys_found", 3);
+        blackboard.set("door_open", true);
+
+        // La quête attend son prérequis
+        let events = achievements.update(&mut blackboard);
+        assert_eq!(
+            events,
+            [AchievementEvent::Progress {
+                id: "first_blood".into(),
+                progress: 0.0
+            }]
+        );
+        assert!(achievements.update(&mut blackboard).is_empty());
+
+        blackboard.add_int("enemies_killed", 1);
+        let events = achievements.update(&mut blackboard);
+        let unlocked: Vec<&AchievementEvent> = events
+            .iter()
+            .filter(|e| matches!(e, AchievementEvent::Unlocked { .. }))
+            .collect();
+        assert_eq!(unlocked.len(), 2);
+        assert!(achievements.is_unlocked(&blackboard, "lost_key"));
+        assert_eq!(blackboard.get_bool("achievement.first_blood"), Some(true));
+
+        // L'état est restauré avec le blackboard
+        let restored = Blackboard::parse(&blackboard.encode()).unwrap();
+        let mut reloaded = Achievements::parse(DEFINITIONS).unwrap();
+        assert!(reloaded.is_unlocked(&restored, "first_blood"));
+        assert!(
+            !reloaded
+                .update(&mut restored.clone())
+                .iter()
+                .any(|e| matches!(e, AchievementEvent::Unlocked { .. }))
+        );
+    }
+}
@@ -0,0 +1,514 @@
+//! Facade d'analytics respectueuse de la vie privée.
+//!
+//! - Opt-in : désactivée par défaut, `track` ne fait rien tant que le projet ne l'active pas.
+//! - Les événements sont mis en file d'attente et persistés via le VFS (un événement par ligne,
+//!   ajouté à la fin du fichier à chaque `track`), pour survivre à un redémarrage hors-ligne.
+//! - `flush` envoie la file via un `AnalyticsTransport` (`HttpTransport` : POST JSON vers
+//!   l'endpoint du projet) et ne la vide qu'en cas de succès.
+//! - `AnalyticsConfig` (`assets/analytics.cfg`, au format `clé = valeur`) active la collecte et
+//!   donne l'endpoint : `Analytics::configure`.
+//! - `register_shutdown` écrit la file et tente un dernier envoi pendant l'étape
+//!   `FlushWrites` de l'arrêt du moteur.
+
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result, anyhow};
+
+use crate::{EngineHandle, ShutdownStage, Vfs};
+
+/// Un événement d'analytics : nom + propriétés libres.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnalyticsEvent {
+    pub name: String,
+    /// Secondes depuis l'epoch UNIX au moment du `track`.
+    pub timestamp: u64,
+    pub props: BTreeMap<String, String>,
+}
+
+impl AnalyticsEvent {
+    /// Encode l'événement sur une ligne : `timestamp\tname\tkey=value\t...` (échappé).
+    fn encode(&self) -> String {
+        let mut line = format!("{}\t{}", self.timestamp, escape(&self.name));
+        for (key, value) in &self.props {
+            line.push('\t');
+            line.push_str(&escape(key));
+            line.push('=');
+            line.push_str(&escape(value));
+        }
+        line
+    }
+
+    /// Objet JSON `{"name":...,"timestamp":...,"props":{...}}`, pour `HttpTransport`.
+    fn to_json(&self) -> String {
+        let props: Vec<String> = self
+            .props
+            .iter()
+            .map(|(key, value)| format!("{}:{}", json_string(key), json_string(value)))
+            .collect();
+        format!(
+            "{{\"name\":{},\"timestamp\":{},\"props\":{{{}}}}}",
+            json_string(&self.name),
+            self.timestamp,
+            props.join(",")
+        )
+    }
+
+    fn decode(line: &str) -> Option<Self> {
+        let mut fields = line.split('\t');
+        let timestamp = fields.next()?.parse().ok()?;
+        let name = unescape(fields.next()?);
+        let mut props = BTreeMap::new();
+        for field in fields {
+            let (key, value) = field.split_once('=')?;
+            props.insert(unescape(key), unescape(value));
+        }
+        Some(Self {
+            name,
+            timestamp,
+            props,
+        })
+    }
+}
+
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '=' => out.push_str("\\e"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn unescape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => out.push('\t'),
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            Some('e') => out.push('='),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Envoi effectif des événements (ex: POST HTTP vers l'endpoint configuré du projet).
+pub trait AnalyticsTransport: Send + Sync {
+    /// Envoie un lot d'événements. Une erreur laisse les événements dans la file.
+    fn send(&self, endpoint: &str, events: &[AnalyticsEvent]) -> Result<()>;
+}
+
+/// Transport HTTP : les événements partent en un POST, un tableau JSON d'objets
+/// `{"name", "timestamp", "props"}`. Un statut d'erreur (4xx, 5xx) est un échec.
+pub struct HttpTransport {
+    agent: ureq::Agent,
+}
+
+impl Default for HttpTransport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HttpTransport {
+    /// Au-delà, l'envoi est abandonné (la file est gardée pour le prochain `flush`).
+    pub const TIMEOUT: Duration = Duration::from_secs(10);
+
+    pub fn new() -> Self {
+        let agent = ureq::Agent::config_builder()
+            .timeout_global(Some(Self::TIMEOUT))
+            .build()
+            .into();
+        Self { agent }
+    }
+}
+
+impl AnalyticsTransport for HttpTransport {
+    fn send(&self, endpoint: &str, events: &[AnalyticsEvent]) -> Result<()> {
+        let events: Vec<String> = events.iter().map(AnalyticsEvent::to_json).collect();
+        self.agent
+            .post(endpoint)
+            .header("Content-Type", "application/json")
+            .send(format!("[{}]", events.join(",")))
+            .with_context(|| format!("failed to send analytics events to {:?}", endpoint))?;
+        Ok(())
+    }
+}
+
+/// Configuration d'analytics du projet.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AnalyticsConfig {
+    /// Collecte activée par le projet (opt-in).
+    pub enabled: bool,
+    /// URL qui reçoit les événements (`HttpTransport`).
+    pub endpoint: Option<String>,
+}
+
+impl AnalyticsConfig {
+    /// Fichier de configuration, dans les assets du projet.
+    pub const PATH: &str = "assets/analytics.cfg";
+
+    /// Parse la configuration. Les clés absentes gardent leur valeur par défaut.
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut config = Self::default();
+
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| anyhow!("line {}: expected `key = value`", number + 1))?;
+            let (key, value) = (key.trim(), value.trim());
+
+            match key {
+                "enabled" => {
+                    config.enabled = value.parse().with_context(|| {
+                        format!("line {}: invalid {} {:?}", number + 1, key, value)
+                    })?
+                }
+                "endpoint" => config.endpoint = Some(value.to_string()).filter(|v| !v.is_empty()),
+                other => log::warn!(
+                    "Unknown analytics setting {:?} (line {})",
+                    other,
+                    number + 1
+                ),
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// Charge la configuration du projet : collecte désactivée si le fichier n'existe pas.
+    pub fn load(vfs: &Vfs) -> Result<Self> {
+        if !vfs.exists(Self::PATH) {
+            return Ok(Self::default());
+        }
+        let text = vfs.read_to_string(Self::PATH)?;
+        Self::parse(&text).with_context(|| format!("failed to parse {:?}", Self::PATH))
+    }
+}
+
+pub struct Analytics {
+    vfs: Arc<Vfs>,
+    /// Chemin VFS du fichier de file d'attente (doit être sur un mount writable).
+    queue_path: String,
+    endpoint: Option<String>,
+    transport: Option<Box<dyn AnalyticsTransport>>,
+    enabled: bool,
+    queue: Vec<AnalyticsEvent>,
+}
+
+impl Analytics {
+    /// Crée la facade (désactivée) et recharge la file persistée s'il y en a une.
+    pub fn new(vfs: Arc<Vfs>, queue_path: impl Into<String>) -> Self {
+        let mut analytics = Self {
+            vfs,
+            queue_path: queue_path.into(),
+            endpoint: None,
+            transport: None,
+            enabled: false,
+            queue: Vec::new(),
+        };
+        analytics.load_queue();
+        analytics
+    }
+
+    /// Active ou désactive la collecte (opt-in par projet).
+    /// Désactiver vide la file et le fichier persistant.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled && !self.queue.is_empty() {
+            self.queue.clear();
+            if let Err(e) = self.persist() {
+                log::warn!("Analytics: failed to clear queue: {:#}", e);
+            }
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Applique la configuration du projet : opt-in et, s'il y en a un, endpoint servi par un
+    /// `HttpTransport`.
+    pub fn configure(&mut self, config: &AnalyticsConfig) {
+        self.set_enabled(config.enabled);
+        if let Some(endpoint) = &config.endpoint {
+            self.set_endpoint(endpoint.clone(), HttpTransport::new());
+        }
+    }
+
+    /// Configure l'endpoint et le transport utilisés par `flush`.
+    pub fn set_endpoint(
+        &mut self,
+        endpoint: impl Into<String>,
+        transport: impl AnalyticsTransport + 'static,
+    ) {
+        self.endpoint = Some(endpoint.into());
+        self.transport = Some(Box::new(transport));
+    }
+
+    /// Enregistre un événement (no-op si la collecte est désactivée) et l'ajoute au fichier de
+    /// la file.
+    pub fn track<K, V>(&mut self, name: &str, props: impl IntoIterator<Item = (K, V)>)
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        if !self.enabled {
+            return;
+        }
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        let event = AnalyticsEvent {
+            name: name.to_string(),
+            timestamp,
+            props: props
+                .into_iter()
+                .map(|(k, v)| (k.into(), v.into()))
+                .collect(),
+        };
+
+        let line = format!("{}\n", event.encode());
+        self.queue.push(event);
+        if let Err(e) = self.vfs.append_bytes(&self.queue_path, line.as_bytes()) {
+            log::warn!("Analytics: failed to persist event: {:#}", e);
+        }
+    }
+
+    /// Événements en attente d'envoi.
+    pub fn pending(&self) -> &[AnalyticsEvent] {
+        &self.queue
+    }
+
+    /// Envoie la file via le transport. En cas d'échec (hors-ligne...), la file est conservée
+    /// pour un prochain essai. Retourne le nombre d'événements envoyés.
+    pub fn flush(&mut self) -> Result<usize> {
+        if !self.enabled || self.queue.is_empty() {
+            return Ok(0);
+        }
+
+        let (Some(endpoint), Some(transport)) = (&self.endpoint, &self.transport) else {
+            return Err(anyhow!("analytics endpoint is not configured"));
+        };
+
+        transport.send(endpoint, &self.queue)?;
+
+        let sent = self.queue.len();
+        self.queue.clear();
+        self.persist()?;
+        Ok(sent)
+    }
+
+    /// Pendant l'étape `FlushWrites` de `Engine::shutdown` : écrit la file dans le VFS puis
+    /// tente de l'envoyer. Un envoi impossible (hors-ligne) n'est pas une erreur, la file
+    /// sera renvoyée au prochain lancement.
+    pub fn register_shutdown(analytics: &Arc<Mutex<Analytics>>, engine: &EngineHandle) {
+        let analytics = analytics.clone();
+        engine.on_shutdown(ShutdownStage::FlushWrites, "analytics", move || {
+            let mut analytics = analytics.lock().unwrap();
+            if !analytics.enabled {
+                return Ok(());
+            }
+            analytics.persist()?;
+            if analytics.endpoint.is_some()
+                && let Err(e) = analytics.flush()
+            {
+                log::info!(
+                    "Analytics: {} events kept for the next start: {:#}",
+                    analytics.queue.len(),
+                    e
+                );
+            }
+            Ok(())
+        });
+    }
+
+    /// Réécrit tout le fichier de la file (après un envoi, ou si un ajout a échoué).
+    fn persist(&self) -> Result<()> {
+        let mut data = String::new();
+        for event in &self.queue {
+            data.push_str(&event.encode());
+            data.push('\n');
+        }
+        self.vfs.write_bytes(&self.queue_path, data.as_bytes())
+    }
+
+    fn load_queue(&mut self) {
+        if !self.vfs.exists(&self.queue_path) {
+            return;
+        }
+
+        match self.vfs.read_to_string(&self.queue_path) {
+            Ok(data) => {
+                self.queue = data
+                    .lines()
+                    .filter(|l| !l.is_empty())
+                    .filter_map(|l| {
+                        let event = AnalyticsEvent::decode(l);
+                        if event.is_none() {
+                            log::warn!("Analytics: skipping malformed queued event {:?}", l);
+                        }
+                        event
+                    })
+                    .collect();
+            }
+            Err(e) => log::warn!("Analytics: failed to load queue: {:#}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+
+    struct Recorder(Arc<Mutex<Vec<AnalyticsEvent>>>, bool);
+
+    impl AnalyticsTransport for Recorder {
+        fn send(&self, _endpoint: &str, events: &[AnalyticsEvent]) -> Result<()> {
+            if !self.1 {
+                return Err(anyhow!("offline"));
+            }
+            self.0.lock().unwrap().extend_from_slice(events);
+            Ok(())
+        }
+    }
+
+    fn vfs_in(dir: &std::path::Path) -> Arc<Vfs> {
+        let vfs = Arc::new(Vfs::new());
+        vfs.mount_os("user", dir, "user", true);
+        vfs
+    }
+
+    #[test]
+    fn disabled_by_default() {
+        let dir = tempdir().unwrap();
+        let mut analytics = Analytics::new(vfs_in(dir.path()), "user/analytics.queue");
+
+        analytics.track("level_complete", [("level", "1")]);
+        assert!(analytics.pending().is_empty());
+    }
+
+    #[test]
+    fn queue_survives_restart_and_flushes_when_online() {
+        let dir = tempdir().unwrap();
+        let vfs = vfs_in(dir.path());
+
+        let mut analytics = Analytics::new(vfs.clone(), "user/analytics.queue");
+        analytics.set_enabled(true);
+        analytics.track("level_complete", [("level", "1-2"), ("time", "a\tb=c")]);
+
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        analytics.set_endpoint("https://example.invalid", Recorder(sent.clone(), false));
+        assert!(analytics.flush().is_err());
+        assert_eq!(analytics.pending().len(), 1);
+
+        // "Redémarrage" : la file est relue depuis le VFS
+        let mut analytics = Analytics::new(vfs, "user/analytics.queue");
+        analytics.set_enabled(true);
+        assert_eq!(analytics.pending().len(), 1);
+        assert_eq!(analytics.pending()[0].props["time"], "a\tb=c");
+
+        analytics.set_endpoint("https://example.invalid", Recorder(sent.clone(), true));
+        assert_eq!(analytics.flush().unwrap(), 1);
+        assert!(analytics.pending().is_empty());
+        assert_eq!(sent.lock().unwrap()[0].name, "level_complete");
+    }
+
+    #[test]
+    fn config_enables_collection_and_events_are_appended() {
+        let config = AnalyticsConfig::parse(
+            "# Analytics\nenabled = true\nendpoint = https://example.invalid/events\n",
+        )
+        .unwrap();
+        assert_eq!(
+            config.endpoint.as_deref(),
+            Some("https://example.invalid/events")
+        );
+
+        let dir = tempdir().unwrap();
+        let vfs = vfs_in(dir.path());
+        let mut analytics = Analytics::new(vfs.clone(), "user/analytics.queue");
+        analytics.configure(&config);
+        assert!(analytics.is_enabled());
+        analytics.track("start", [("mode", "story")]);
+        analytics.track("quit", [("reason", "say \"bye\"\n")]);
+
+        let queued = vfs.read_to_string("user/analytics.queue").unwrap();
+        assert_eq!(queued.lines().count(), 2);
+        assert_eq!(
+            analytics.pending()[1].to_json(),
+            format!(
+                "{{\"name\":\"quit\",\"timestamp\":{},\"props\":{{\"reason\":\"say \\\"bye\\\"\\n\"}}}}",
+                analytics.pending()[1].timestamp
+            )
+        );
+    }
+
+    #[test]
+    fn shutdown_flushes_the_queue() {
+        let dir = tempdir().unwrap();
+        let analytics = Arc::new(Mutex::new(Analytics::new(
+            vfs_in(dir.path()),
+            "user/analytics.queue",
+        )));
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        {
+            let mut analytics = analytics.lock().unwrap();
+            analytics.set_enabled(true);
+            analytics.set_endpoint("https://example.invalid", Recorder(sent.clone(), true));
+            analytics.track("quit", [("reason", "menu")]);
+        }
+
+        let engine = crate::Engine::default();
+        Analytics::register_shutdown(&analytics, &engine.handle());
+        let mut hooks = engine.shutdown_hooks.lock().unwrap().take();
+        assert_eq!(hooks.run_stage(ShutdownStage::FlushWrites), 0);
+        assert_eq!(sent.lock().unwrap().len(), 1);
+        assert!(analytics.lock().unwrap().pending().is_empty());
+    }
+}
@@ -0,0 +1,208 @@
+//! Graphe de dépendances entre assets (scene -> prefab -> texture/material -> shader).
+//!
+//! Les assets sont identifiés par leur chemin VFS. Quand un asset change (hot-reload),
+//! `invalidate` retourne l'asset lui-même et tout ce qui en dépend (directement ou non),
+//! dans un ordre où chaque asset apparaît après ses dépendances : on peut donc recharger
+//! la liste dans l'ordre sans jamais reconstruire un asset à partir d'une version périmée.
+
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+#[derive(Debug, Default, Clone)]
+pub struct AssetGraph {
+    /// asset -> assets dont il dépend
+    dependencies: BTreeMap<String, BTreeSet<String>>,
+    /// asset -> assets qui dépendent de lui
+    dependents: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl AssetGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Déclare que `dependent` référence `dependency` (ex: "assets/level.scene" -> "assets/hero.png").
+    pub fn add_dependency(&mut self, dependent: impl Into<String>, dependency: impl Into<String>) {
+        let dependent = dependent.into();
+        let dependency = dependency.into();
+        self.dependents
+            .entry(dependency.clone())
+            .or_default()
+            .insert(dependent.clone());
+        self.dependencies
+            .entry(dependent)
+            .or_default()
+            .insert(dependency);
+    }
+
+    /// Supprime un lien de dépendance.
+    pub fn remove_dependency(&mut self, dependent: &str, dependency: &str) {
+        if let Some(deps) = self.dependencies.get_mut(dependent) {
+            deps.remove(dependency);
+            if deps.is_empty() {
+                self.dependencies.remove(dependent);
+            }
+        }
+        if let Some(users) = self.dependents.get_mut(dependency) {
+            users.remove(dependent);
+            if users.is_empty() {
+                self.dependents.remove(dependency);
+            }
+        }
+    }
+
+    /// Oublie toutes les dépendances sortantes de `asset` (à appeler avant de le recharger :
+    /// le loader les redéclare en le relisant).
+    pub fn clear_dependencies(&mut self, asset: &str) {
+        if let Some(deps) = self.dependencies.remove(asset) {
+            for dep in deps {
+                if let Some(users) = self.dependents.get_mut(&dep) {
+                    users.remove(asset);
+                    if users.is_empty() {
+                        self.dependents.remove(&dep);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Retire complètement un asset du graphe (dans les deux sens).
+    pub fn remove_asset(&mut self, asset: &str) {
+        self.clear_dependencies(asset);
+        if let Some(users) = self.dependents.remove(asset) {
+            for user in users {
+                if let Some(deps) = self.dependencies.get_mut(&user) {
+                    deps.remove(asset);
+                    if deps.is_empty() {
+                        self.dependencies.remove(&user);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Dépendances directes de `asset`.
+    pub fn dependencies_of(&self, asset: &str) -> impl Iterator<Item = &str> {
+        self.dependencies
+            .get(asset)
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+    }
+
+    /// Assets qui référencent directement `asset`.
+    pub fn dependents_of(&self, asset: &str) -> impl Iterator<Item = &str> {
+        self.dependents
+            .get(asset)
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+    }
+
+    /// Retourne `asset` et tous ses dépendants transitifs, triés pour le rechargement :
+    /// chaque entrée apparaît après toutes les entrées invalidées dont elle dépend.
+    /// Les cycles éventuels sont ajoutés en fin de liste (avec un warning).
+    pub fn invalidate(&self, asset: &str) -> Vec<String> {
+        // 1) Ensemble des assets touchés (parcours inverse)
+        let mut affected = BTreeSet::new();
+        let mut queue = VecDeque::from([asset.to_string()]);
+        while let Some(current) = queue.pop_front() {
+            if !affected.insert(current.clone()) {
+                continue;
+            }
+            for user in self.dependents_of(&current) {
+                queue.push_back(user.to_string());
+            }
+        }
+
+        // 2) Tri topologique (Kahn) restreint aux assets touchés
+        let mut pending: BTreeMap<&str, usize> = affected
+            .iter()
+            .map(|a| {
+                let count = self
+                    .dependencies_of(a)
+                    .filter(|d| affected.contains(*d))
+                    .count();
+                (a.as_str(), count)
+            })
+            .collect();
+
+        let mut ready: VecDeque<&str> = pending
+            .iter()
+            .filter(|(_, count)| **count == 0)
+            .map(|(a, _)| *a)
+            .collect();
+        let mut order = Vec::with_capacity(affected.len());
+
+        while let Some(current) = ready.pop_front() {
+            pending.remove(current);
+            order.push(current.to_string());
+            for user in self.dependents_of(current) {
+                if let Some(count) = pending.get_mut(user) {
+                    *count -= 1;
+                    if *count == 0 {
+                        ready.push_back(user);
+                    }
+                }
+            }
+        }
+
+        if !pending.is_empty() {
+            log::warn!(
+                "Asset dependency cycle detected while invalidating {:?}: {:?}",
+                asset,
+                pending.keys().collect::<Vec<_>>()
+            );
+            order.extend(pending.keys().map(|a| a.to_string()));
+        }
+
+        order
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dependencies.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalidate_orders_dependents_after_dependencies() {
+        let mut graph = AssetGraph::new();
+        graph.add_dependency("level.scene", "hero.prefab");
+        graph.add_dependency("hero.prefab", "hero.material");
+        graph.add_dependency("hero.material", "sprite.wgsl");
+        graph.add_dependency("hero.material", "hero.png");
+        graph.add_dependency("level.scene", "hero.material");
+
+        let order = graph.invalidate("sprite.wgsl");
+        assert_eq!(
+            order,
+            vec!["sprite.wgsl", "hero.material", "hero.prefab", "level.scene"]
+        );
+
+        // hero.png n'est pas touché par un changement du shader
+        assert!(!order.contains(&"hero.png".to_string()));
+    }
+
+    #[test]
+    fn invalidate_survives_cycles() {
+        let mut graph = AssetGraph::new();
+        graph.add_dependency("a", "b");
+        graph.add_dependency("b", "a");
+
+        let order = graph.invalidate("a");
+        assert_eq!(order.len(), 2);
+    }
+
+    #[test]
+    fn clear_dependencies_unlinks_both_directions() {
+        let mut graph = AssetGraph::new();
+        graph.add_dependency("scene", "tex.png");
+        graph.clear_dependencies("scene");
+
+        assert_eq!(graph.invalidate("tex.png"), vec!["tex.png"]);
+        assert!(graph.is_empty());
+    }
+}
@@ -0,0 +1,230 @@
+//! Hot-reload des assets : surveillance (notify) des dossiers du VFS servis par le disque.
+//!
+//! `AssetWatcher::poll_changes` retourne les assets modifiés sur le disque et ce qui en
+//! dépend dans le graphe du loader. L'appelant les recharge selon leur `AssetKind` :
+//! - shaders : `PassManager::reload_shader` recompile et recrée les pipelines concernés (un
+//!   shader invalide est loggé et les passes gardent leurs anciens pipelines) ;
+//! - textures : `Scene::reload_texture` remplace les textures des sprites et des tilesets,
+//!   `PassManager::reload_asset` vide les caches des passes (les bind groups sont recréés
+//!   pour la nouvelle texture) ;
+//! - chunks du monde : `WorldStreamer::reload_file`.
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::mpsc,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result, anyhow};
+use notify::{EventKind, RecursiveMode, Watcher};
+
+use crate::AssetLoader;
+
+/// Type d'un asset, d'après l'extension de son chemin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssetKind {
+    Shader,
+    Texture,
+    Font,
+    Other,
+}
+
+impl AssetKind {
+    pub fn from_path(path: &str) -> Self {
+        let extension = path
+            .rsplit_once('.')
+            .map(|(_, extension)| extension.to_lowercase())
+            .unwrap_or_default();
+        match extension.as_str() {
+            "wgsl" => AssetKind::Shader,
+            "png" | "jpg" | "jpeg" | "bmp" | "tga" | "gif" | "webp" => AssetKind::Texture,
+            "ttf" | "otf" => AssetKind::Font,
+            _ => AssetKind::Other,
+        }
+    }
+}
+
+/// Dossier surveillé : chemin sur le disque et chemin VFS correspondant.
+struct WatchedDir {
+    os_path: PathBuf,
+    vfs_path: String,
+}
+
+/// Surveille les fichiers de dossiers du VFS servis par le disque.
+pub struct AssetWatcher {
+    loader: AssetLoader,
+    watcher: notify::RecommendedWatcher,
+    events: mpsc::Receiver<notify::Result<notify::Event>>,
+    dirs: Vec<WatchedDir>,
+    /// Assets modifiés pas encore retournés, avec la date du dernier événement.
+    pending: HashMap<String, Instant>,
+}
+
+impl AssetWatcher {
+    /// Délai sans nouvel événement avant de recharger : les éditeurs écrivent souvent un
+    /// fichier en plusieurs fois (troncature puis écriture, ou fichier temporaire renommé).
+    pub const DEBOUNCE: Duration = Duration::from_millis(100);
+
+    pub fn new(loader: AssetLoader) -> Result<Self> {
+        let (sender, events) = mpsc::channel();
+        let watcher = notify::recommended_watcher(sender).context("failed to create watcher")?;
+        Ok(Self {
+            loader,
+            watcher,
+            events,
+            dirs: Vec::new(),
+            pending: HashMap::new(),
+        })
+    }
+
+    /// Surveille récursivement le dossier VFS `vfs_dir` (ex: "engine/shaders").
+    pub fn watch(&mut self, vfs_dir: &str) -> Result<()> {
+        let vfs_dir = vfs_dir.trim_end_matches('/');
+        if self.dirs.iter().any(|dir| dir.vfs_path == vfs_dir) {
+            return Ok(());
+        }
+        let os_path = self
+            .loader
+            .vfs()
+            .os_path(vfs_dir)
+            .ok_or_else(|| anyhow!("{:?} is not backed by a directory on disk", vfs_dir))?;
+        // Certains backends (FSEvents) rapportent des chemins canoniques
+        let os_path = os_path.canonicalize().unwrap_or(os_path);
+        self.watcher
+            .watch(&os_path, RecursiveMode::Recursive)
+            .with_context(|| format!("failed to watch {:?}", os_path))?;
+        self.dirs.push(WatchedDir {
+            os_path,
+            vfs_path: vfs_dir.to_string(),
+        });
+        Ok(())
+    }
+
+    /// Surveille chaque mount du VFS servi par le disque, sauf le mount racine ("") qui
+    /// couvrirait tout le dossier de travail. Retourne le nombre de dossiers surveillés.
+    pub fn watch_mounts(&mut self) -> usize {
+        let mut watched = 0;
+        for (prefix, _, _) in self.loader.vfs().debug_list_mounts() {
+            let prefix = prefix.to_string_lossy().replace('\\', "/");
+            if prefix.is_empty() || self.loader.vfs().os_path(&prefix).is_none() {
+                continue;
+            }
+            match self.watch(&prefix) {
+                Ok(()) => watched += 1,
+                Err(e) => log::warn!("Not watching mount {:?}: {:#}", prefix, e),
+            }
+        }
+        watched
+    }
+
+    /// Dossiers VFS surveillés.
+    pub fn watched(&self) -> impl Iterator<Item = &str> {
+        self.dirs.iter().map(|dir| dir.vfs_path.as_str())
+    }
+
+    /// À appeler chaque frame : assets modifiés depuis au moins `DEBOUNCE` et tout ce qui en
+    /// dépend (dépendances en premier), déjà invalidés dans le graphe du loader.
+    pub fn poll_changes(&mut self) -> Vec<String> {
+        let now = Instant::now();
+        while let Ok(event) = self.events.try_recv() {
+            let event = match event {
+                Ok(event) => event,
+                Err(err) => {
+                    log::warn!("Asset watcher error: {}", err);
+                    continue;
+                }
+            };
+            if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                continue;
+            }
+            for path in &event.paths {
+                if let Some(vfs_path) = self.vfs_path(path) {
+                    self.pending.insert(vfs_path, now);
+                }
+            }
+        }
+
+        let ready: Vec<String> = self
+            .pending
+            .iter()
+            .filter(|(_, last)| now.duration_since(**last) >= Self::DEBOUNCE)
+            .map(|(path, _)| path.clone())
+            .collect();
+        let mut reload = Vec::new();
+        for path in ready {
+            self.pending.remove(&path);
+            for asset in self.loader.invalidate(&path) {
+                if !reload.contains(&asset) {
+                    reload.push(asset);
+                }
+            }
+        }
+        reload
+    }
+
+    /// Chemin VFS d'un fichier d'un dossier surveillé (le plus précis si des dossiers sont
+    /// imbriqués). Les fichiers temporaires des éditeurs sont ignorés.
+    fn vfs_path(&self, os_path: &Path) -> Option<String> {
+        let name = os_path.file_name()?.to_string_lossy();
+        if is_temporary_file(&name) || os_path.is_dir() {
+            return None;
+        }
+        self.dirs
+            .iter()
+            .filter_map(|dir| Some((dir, os_path.strip_prefix(&dir.os_path).ok()?)))
+            .min_by_key(|(_, relative)| relative.components().count())
+            .map(|(dir, relative)| join_vfs_path(&dir.vfs_path, relative))
+    }
+}
+
+/// Fichiers d'échange / de sauvegarde des éditeurs (vim, emacs, JetBrains...).
+fn is_temporary_file(name: &str) -> bool {
+    name.starts_with(".#")
+        || name.ends_with('~')
+        || [".swp", ".swx", ".tmp", "___jb_tmp___", "___jb_old___"]
+            .iter()
+            .any(|suffix| name.ends_with(suffix))
+}
+
+/// `dir` + `relative` avec des `/`, quel que soit le séparateur du système.
+fn join_vfs_path(dir: &str, relative: &Path) -> String {
+    let mut path = dir.to_string();
+    for component in relative.components() {
+        if !path.is_empty() {
+            path.push('/');
+        }
+        path.push_str(&component.as_os_str().to_string_lossy());
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relative_paths_map_to_vfs_paths() {
+        let relative = Path::new("lighting").join("lights.wgsl");
+        assert_eq!(
+            join_vfs_path("engine/shaders", &relative),
+            "engine/shaders/lighting/lights.wgsl"
+        );
+        assert_eq!(join_vfs_path("", Path::new("water.wgsl")), "water.wgsl");
+    }
+
+    #[test]
+    fn assets_are_classified_by_extension() {
+        assert_eq!(
+            AssetKind::from_path("engine/shaders/sprite.wgsl"),
+            AssetKind::Shader
+        );
+        assert_eq!(AssetKind::from_path("assets/hero.PNG"), AssetKind::Texture);
+        assert_eq!(AssetKind::from_path("fonts/Noto.ttf"), AssetKind::Font);
+        assert_eq!(AssetKind::from_path("world/0_0.tmx"), AssetKind::Other);
+        assert_eq!(AssetKind::from_path("README"), AssetKind::Other);
+        assert!(is_temporary_file(".sprite.wgsl.swp"));
+        assert!(is_temporary_file("hero.png~"));
+        assert!(!is_temporary_file("hero.png"));
+    }
+}
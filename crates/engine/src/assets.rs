@@ -1,7 +1,14 @@
 use anyhow::{Context, Result, anyhow};
-use std::sync::Arc;
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
 
-use crate::{Texture2D, Vfs};
+use crate::{AssetGraph, PipelineCache, Shader, Texture2D, TextureDescriptor2D, Vfs};
 
 /// AssetLoader : responsable de transformer bytes en resources concrètes.
 /// Exemple courant : charger une `Texture2D` à partir d'un chemin VFS.
@@ -9,37 +16,213 @@ use crate::{Texture2D, Vfs};
 #[derive(Clone)]
 pub struct AssetLoader {
     vfs: Arc<Vfs>,
+    /// Dépendances entre assets, partagées entre tous les clones du loader.
+    graph: Arc<Mutex<AssetGraph>>,
+    /// Pipelines partagés entre les passes, partagés entre tous les clones du loader.
+    pipelines: Arc<Mutex<PipelineCache>>,
+    /// Lectures et écritures en cours (chemin, début), partagées entre tous les clones du
+    /// loader.
+    pending: Arc<Mutex<HashMap<u64, (String, Instant)>>>,
+}
+
+/// Inscrit une lecture ou une écriture dans `AssetLoader::pending` le temps de sa durée de vie.
+struct PendingIo {
+    pending: Arc<Mutex<HashMap<u64, (String, Instant)>>>,
+    id: u64,
+}
+
+impl PendingIo {
+    fn new(loader: &AssetLoader, path: &str) -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        loader
+            .pending
+            .lock()
+            .unwrap()
+            .insert(id, (path.to_string(), Instant::now()));
+        PendingIo {
+            pending: loader.pending.clone(),
+            id,
+        }
+    }
+}
+
+impl Drop for PendingIo {
+    fn drop(&mut self) {
+        self.pending.lock().unwrap().remove(&self.id);
+    }
 }
 
 impl AssetLoader {
     pub fn new(vfs: Arc<Vfs>) -> Self {
-        AssetLoader { vfs }
+        AssetLoader {
+            vfs,
+            graph: Arc::new(Mutex::new(AssetGraph::new())),
+            pipelines: Arc::new(Mutex::new(PipelineCache::new())),
+            pending: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Déclare que l'asset `dependent` référence `dependency` (chemins VFS).
+    pub fn add_dependency(&self, dependent: &str, dependency: &str) {
+        self.graph
+            .lock()
+            .unwrap()
+            .add_dependency(dependent, dependency);
+    }
+
+    /// Liste des assets à recharger (dans l'ordre) quand `path` a changé.
+    pub fn invalidate(&self, path: &str) -> Vec<String> {
+        self.graph.lock().unwrap().invalidate(path)
+    }
+
+    /// VFS utilisé par le loader.
+    pub fn vfs(&self) -> &Arc<Vfs> {
+        &self.vfs
+    }
+
+    /// Accès direct au graphe de dépendances.
+    pub fn graph(&self) -> &Arc<Mutex<AssetGraph>> {
+        &self.graph
     }
 
-    /// Charge les bytes d'un path via le VFS.
+    /// Cache des pipelines de rendu (voir `PipelineCache`).
+    pub fn pipeline_cache(&self) -> &Arc<Mutex<PipelineCache>> {
+        &self.pipelines
+    }
+
+    /// Charge les bytes d'un path via le VFS (ou sa variante localisée, voir `Vfs::set_locale`).
     pub fn load_bytes(&self, path: &str) -> Result<Vec<u8>> {
+        let _scope = tracing::trace_span!("load_bytes", path).entered();
+        let _pending = PendingIo::new(self, path);
+        self.track_localized(path);
         self.vfs.read_bytes(path)
     }
 
+    /// `load_bytes` sans bloquer l'appelant (voir `Vfs::read_bytes_async`).
+    pub async fn load_bytes_async(&self, path: &str) -> Result<Vec<u8>> {
+        let _pending = PendingIo::new(self, path);
+        self.track_localized(path);
+        self.vfs.read_bytes_async(path).await
+    }
+
+    /// Charge un fichier texte (UTF-8) via le VFS.
+    pub fn load_string(&self, path: &str) -> Result<String> {
+        let _scope = tracing::trace_span!("load_string", path).entered();
+        let _pending = PendingIo::new(self, path);
+        self.track_localized(path);
+        self.vfs.read_to_string(path)
+    }
+
+    /// Lectures et écritures en cours (tous threads confondus) et depuis combien de temps,
+    /// les plus anciennes d'abord.
+    pub fn pending_loads(&self) -> Vec<(String, Duration)> {
+        let mut pending: Vec<(String, Instant)> =
+            self.pending.lock().unwrap().values().cloned().collect();
+        pending.sort_by_key(|(_, started)| *started);
+        pending
+            .into_iter()
+            .map(|(path, started)| (path, started.elapsed()))
+            .collect()
+    }
+
+    /// Si `path` est servi par une variante localisée, la modification de ce fichier
+    /// recharge `path` (`invalidate`).
+    fn track_localized(&self, path: &str) {
+        if let Some(variant) = self.vfs.localized_path(path) {
+            self.add_dependency(path, &variant);
+        }
+    }
+
     /// Charge une texture en résolvant les bytes via le VFS puis en appelant
     /// `Texture2D::from_bytes(device, queue, &bytes)`.
     ///
     /// Note: l'appelant doit fournir `device` et `queue`.
+    #[track_caller]
     pub fn load_texture(
         &self,
         path: &str,
         device: &egui_wgpu::wgpu::Device,
         queue: &egui_wgpu::wgpu::Queue,
     ) -> Result<Texture2D> {
+        let _scope = tracing::trace_span!("load_texture", path).entered();
         let bytes = self
             .load_bytes(path)
             .with_context(|| format!("failed to load texture bytes for path {}", path))?;
-        Texture2D::from_bytes(device, queue, &bytes)
-            .map_err(|e| anyhow!(format!("failed to decode image {:?}: {}", path, e)))
+        let mut texture = Texture2D::from_bytes(device, queue, &bytes)
+            .map_err(|e| anyhow!(format!("failed to decode image {:?}: {}", path, e)))?;
+        texture.set_path(path);
+        Ok(texture)
+    }
+
+    /// Comme `load_texture`, sans décodage sRGB (normal maps, textures de données).
+    #[track_caller]
+    pub fn load_texture_linear(
+        &self,
+        path: &str,
+        device: &egui_wgpu::wgpu::Device,
+        queue: &egui_wgpu::wgpu::Queue,
+    ) -> Result<Texture2D> {
+        let _scope = tracing::trace_span!("load_texture", path).entered();
+        let bytes = self
+            .load_bytes(path)
+            .with_context(|| format!("failed to load texture bytes for path {}", path))?;
+        let mut texture = Texture2D::from_bytes_linear(device, queue, &bytes)
+            .map_err(|e| anyhow!(format!("failed to decode image {:?}: {}", path, e)))?;
+        texture.set_path(path);
+        Ok(texture)
+    }
+
+    /// Comme `load_texture`, avec le filtrage, la répétition, le format et les mipmaps de
+    /// `descriptor`.
+    #[track_caller]
+    pub fn load_texture_with(
+        &self,
+        path: &str,
+        device: &egui_wgpu::wgpu::Device,
+        queue: &egui_wgpu::wgpu::Queue,
+        descriptor: &TextureDescriptor2D,
+    ) -> Result<Texture2D> {
+        let _scope = tracing::trace_span!("load_texture", path).entered();
+        let bytes = self
+            .load_bytes(path)
+            .with_context(|| format!("failed to load texture bytes for path {}", path))?;
+        let mut texture = Texture2D::from_bytes_with(device, queue, &bytes, descriptor)
+            .map_err(|e| anyhow!(format!("failed to decode image {:?}: {}", path, e)))?;
+        texture.set_path(path);
+        Ok(texture)
+    }
+
+    /// Charge et compile un shader WGSL via le VFS.
+    pub fn load_shader(&self, path: &str, device: &egui_wgpu::wgpu::Device) -> Result<Shader> {
+        let _scope = tracing::trace_span!("load_shader", path).entered();
+        Ok(Shader::from_vfs(device, &self.vfs, path)?)
+    }
+
+    /// Comme `load_shader`, mais un shader illisible ou invalide est loggé et remplacé par le
+    /// shader de secours (`Shader::from_vfs_or_fallback`).
+    pub fn load_shader_or_fallback(&self, path: &str, device: &egui_wgpu::wgpu::Device) -> Shader {
+        let _scope = tracing::trace_span!("load_shader", path).entered();
+        Shader::from_vfs_or_fallback(device, &self.vfs, path)
     }
 
     /// Ecrit des bytes via le VFS (dans le premier mount writable).
     pub fn write_bytes(&self, path: &str, data: &[u8]) -> Result<()> {
+        let _scope = tracing::trace_span!("write_bytes", path).entered();
+        let _pending = PendingIo::new(self, path);
         self.vfs.write_bytes(path, data)
     }
+
+    /// Attend (au plus `timeout`) la fin des lectures et écritures en cours sur les autres
+    /// threads. Retourne `false` s'il en reste.
+    pub fn wait_idle(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        while !self.pending.lock().unwrap().is_empty() {
+            if Instant::now() >= deadline {
+                return false;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        true
+    }
 }
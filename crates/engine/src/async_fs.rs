@@ -0,0 +1,118 @@
+//! Lectures asynchrones pour les gros assets (musique, vidéo, grands atlas) : ils sont lus
+//! ou streamés sans bloquer le thread de rendu.
+//!
+//! `AsyncFileSystem` est la variante async de `FileSystem`. `Ofs` l'implémente avec tokio,
+//! `MemFs` depuis la mémoire. Le trait n'est pas utilisable en `dyn` : pour lire n'importe
+//! quel mount du VFS, `Vfs::read_bytes_async` exécute la lecture sur le pool bloquant de
+//! tokio. Il faut un runtime tokio dans tous les cas.
+
+use std::{future::Future, io::Cursor, path::Path};
+
+use anyhow::{Context, Result, anyhow};
+use tokio::io::AsyncRead;
+
+use crate::{FileSystem, MemFs, Ofs, Vfs};
+
+/// Filesystem avec des lectures asynchrones.
+pub trait AsyncFileSystem: Send + Sync {
+    /// Flux retourné par `open_read`.
+    type Reader: AsyncRead + Send + Unpin + 'static;
+
+    /// Lit tout le fichier.
+    fn read_bytes(&self, path: &Path) -> impl Future<Output = Result<Vec<u8>>> + Send;
+
+    /// Ouvre le fichier pour le lire par morceaux (ex: `tokio::io::AsyncReadExt::read`),
+    /// sans le charger entièrement en mémoire.
+    fn open_read(&self, path: &Path) -> impl Future<Output = Result<Self::Reader>> + Send;
+}
+
+impl AsyncFileSystem for Ofs {
+    type Reader = tokio::fs::File;
+
+    async fn read_bytes(&self, path: &Path) -> Result<Vec<u8>> {
+        let abs = self.resolve_path(path);
+        tokio::fs::read(&abs)
+            .await
+            .with_context(|| format!("Ofs({}) failed to read {:?}", self.name(), abs))
+    }
+
+    async fn open_read(&self, path: &Path) -> Result<tokio::fs::File> {
+        let abs = self.resolve_path(path);
+        tokio::fs::File::open(&abs)
+            .await
+            .with_context(|| format!("Ofs({}) failed to open {:?}", self.name(), abs))
+    }
+}
+
+/// Les données sont déjà en mémoire : le flux lit une copie du fichier.
+impl AsyncFileSystem for MemFs {
+    type Reader = Cursor<Vec<u8>>;
+
+    async fn read_bytes(&self, path: &Path) -> Result<Vec<u8>> {
+        FileSystem::read_bytes(self, path)
+    }
+
+    async fn open_read(&self, path: &Path) -> Result<Cursor<Vec<u8>>> {
+        FileSystem::read_bytes(self, path).map(Cursor::new)
+    }
+}
+
+impl Vfs {
+    /// `read_bytes` exécuté sur le pool bloquant de tokio : fonctionne pour tous les mounts
+    /// (archives comprises), sans bloquer l'appelant.
+    pub async fn read_bytes_async(&self, path: &str) -> Result<Vec<u8>> {
+        let vfs = self.clone();
+        let owned = path.to_string();
+        tokio::task::spawn_blocking(move || vfs.read_bytes(&owned))
+            .await
+            .map_err(|e| anyhow!("read of vfs path {:?} was cancelled: {}", path, e))?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use tempfile::tempdir;
+    use tokio::io::AsyncReadExt;
+
+    use super::*;
+
+    #[test]
+    fn streams_files_without_blocking() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("music.ogg"), [7u8; 10_000]).unwrap();
+        let ofs = Arc::new(Ofs::new(dir.path(), "Assets"));
+        let vfs = Vfs::new();
+        vfs.mount("assets", ofs.clone(), false);
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let mut reader = ofs.open_read(Path::new("music.ogg")).await.unwrap();
+            let mut chunk = [0u8; 4096];
+            let mut total = 0;
+            loop {
+                let read = reader.read(&mut chunk).await.unwrap();
+                if read == 0 {
+                    break;
+                }
+                total += read;
+            }
+            assert_eq!(total, 10_000);
+
+            let bytes = AsyncFileSystem::read_bytes(&*ofs, Path::new("music.ogg"))
+                .await
+                .unwrap();
+            assert_eq!(bytes.len(), 10_000);
+            assert_eq!(
+                vfs.read_bytes_async("assets/music.ogg").await.unwrap(),
+                bytes
+            );
+            assert!(vfs.read_bytes_async("assets/missing.ogg").await.is_err());
+            assert!(ofs.open_read(Path::new("missing.ogg")).await.is_err());
+        });
+    }
+}
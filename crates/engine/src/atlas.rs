@@ -0,0 +1,353 @@
+use std::{collections::HashMap, sync::Arc};
+
+use anyhow::{Context, Result, anyhow, bail};
+use egui_wgpu::wgpu;
+
+use crate::{AssetLoader, SpriteMesh, Texture2D, Vfs};
+
+/// A named rectangular region of a `TextureAtlas`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AtlasRegion {
+    /// Pixel rectangle inside the atlas texture.
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    /// Same rectangle in normalized coordinates [u0, v0, u1, v1] (see `Sprite::uv`).
+    pub uv: [f32; 4],
+    /// Origin of the frame, relative to its size ([0, 0] = top-left, [0.5, 1] = feet).
+    pub pivot: [f32; 2],
+}
+
+/// Slices a single `Texture2D` into named regions.
+/// Sprites created from the same atlas share one texture (and one bind group), so
+/// `SpritePass` draws all of them with a single instanced call.
+#[derive(Clone)]
+pub struct TextureAtlas {
+    texture: Arc<Texture2D>,
+    regions: HashMap<String, AtlasRegion>,
+    /// Tight meshes of the regions that have one (see `SpriteMesh`).
+    meshes: HashMap<String, Arc<SpriteMesh>>,
+}
+
+impl TextureAtlas {
+    /// Create an empty atlas over `texture`.
+    pub fn new(texture: Arc<Texture2D>) -> Self {
+        Self {
+            texture,
+            regions: HashMap::new(),
+            meshes: HashMap::new(),
+        }
+    }
+
+    /// Slice the texture into a regular grid of `cell_width` x `cell_height` cells.
+    /// Regions are named `"{prefix}{index}"`, row by row starting at the top-left cell.
+    pub fn from_grid(
+        texture: Arc<Texture2D>,
+        prefix: &str,
+        cell_width: u32,
+        cell_height: u32,
+    ) -> Self {
+        let mut atlas = Self::new(texture);
+        atlas.add_grid(prefix, cell_width, cell_height);
+        atlas
+    }
+
+    pub fn texture(&self) -> &Arc<Texture2D> {
+        &self.texture
+    }
+
+    /// Register (or replace) a region given its pixel rectangle.
+    pub fn add_region(&mut self, name: impl Into<String>, x: u32, y: u32, width: u32, height: u32) {
+        let tex_w = self.texture.width.max(1) as f32;
+        let tex_h = self.texture.height.max(1) as f32;
+
+        let region = AtlasRegion {
+            x,
+            y,
+            width,
+            height,
+            uv: [
+                x as f32 / tex_w,
+                y as f32 / tex_h,
+                (x + width) as f32 / tex_w,
+                (y + height) as f32 / tex_h,
+            ],
+            pivot: [0.0, 0.0],
+        };
+        self.regions.insert(name.into(), region);
+    }
+
+    /// Add one region per grid cell (see `from_grid`). Partial cells on the right/bottom
+    /// edges are ignored. Returns the number of regions added.
+    pub fn add_grid(&mut self, prefix: &str, cell_width: u32, cell_height: u32) -> usize {
+        if cell_width == 0 || cell_height == 0 {
+            return 0;
+        }
+
+        let columns = self.texture.width / cell_width;
+        let rows = self.texture.height / cell_height;
+
+        for row in 0..rows {
+            for column in 0..columns {
+                let index = row * columns + column;
+                self.add_region(
+                    format!("{}{}", prefix, index),
+                    column * cell_width,
+                    row * cell_height,
+                    cell_width,
+                    cell_height,
+                );
+            }
+        }
+
+        (columns * rows) as usize
+    }
+
+    pub fn region(&self, name: &str) -> Option<&AtlasRegion> {
+        self.regions.get(name)
+    }
+
+    /// Set the pivot of a region. Returns `false` if there is no such region.
+    pub fn set_pivot(&mut self, name: &str, pivot: [f32; 2]) -> bool {
+        match self.regions.get_mut(name) {
+            Some(region) => {
+                region.pivot = pivot;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Tight mesh of a region, used by `Sprite::from_atlas_region` instead of the quad.
+    pub fn mesh(&self, name: &str) -> Option<&Arc<SpriteMesh>> {
+        self.meshes.get(name)
+    }
+
+    /// Set (or clear) the tight mesh of a region. Returns `false` if there is no such region.
+    pub fn set_mesh(&mut self, name: &str, mesh: Option<Arc<SpriteMesh>>) -> bool {
+        if !self.regions.contains_key(name) {
+            return false;
+        }
+        match mesh {
+            Some(mesh) => self.meshes.insert(name.to_string(), mesh),
+            None => self.meshes.remove(name),
+        };
+        true
+    }
+
+    pub fn remove_region(&mut self, name: &str) -> Option<AtlasRegion> {
+        self.meshes.remove(name);
+        self.regions.remove(name)
+    }
+
+    pub fn regions(&self) -> impl Iterator<Item = (&str, &AtlasRegion)> {
+        self.regions
+            .iter()
+            .map(|(name, region)| (name.as_str(), region))
+    }
+
+    pub fn len(&self) -> usize {
+        self.regions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.regions.is_empty()
+    }
+
+    /// Atlas over `texture` with the regions (and pivots) of `metadata`.
+    pub fn from_metadata(texture: Arc<Texture2D>, metadata: &AtlasMetadata) -> Self {
+        let mut atlas = Self::new(texture);
+        for frame in &metadata.frames {
+            atlas.add_region(&frame.name, frame.x, frame.y, frame.width, frame.height);
+            atlas.set_pivot(&frame.name, frame.pivot);
+            if let Some(mesh) = &frame.mesh {
+                atlas.set_mesh(&frame.name, Some(Arc::new(mesh.clone())));
+            }
+        }
+        atlas
+    }
+
+    /// Load an atlas metadata asset (see `AtlasMetadata`) and its image through the VFS.
+    /// The metadata is registered in the asset graph as depending on the image.
+    pub fn load(
+        loader: &AssetLoader,
+        path: &str,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) -> Result<Self> {
+        let metadata = AtlasMetadata::load(loader.vfs(), path)?;
+        loader.add_dependency(path, &metadata.image);
+        let texture = loader.load_texture(&metadata.image, device, queue)?;
+        Ok(Self::from_metadata(Arc::new(texture), &metadata))
+    }
+}
+
+/// One named frame of an `AtlasMetadata`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AtlasFrame {
+    pub name: String,
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    /// See `AtlasRegion::pivot`.
+    pub pivot: [f32; 2],
+    /// Tight mesh of the frame, if it was generated at import (see `SpriteMesh::from_alpha`).
+    pub mesh: Option<SpriteMesh>,
+}
+
+/// Description of the frames of a sprite sheet, saved next to the image (`.atlas` files,
+/// written by the sprite slicer of the editor).
+///
+/// Text format, one entry per line, frames kept in order (animation order):
+/// ```text
+/// image = assets/sprites/hero.png
+/// frame idle_0 = 0 0 32 32 0.5 1
+/// mesh idle_0 = 0.25 0 0.75 0 0.75 1 0.25 1 | 0 1 2 0 2 3
+/// ```
+/// (`frame <name> = x y width height pivot_x pivot_y`, then optionally
+/// `mesh <name> = <SpriteMesh::encode>` for frames with a tight mesh).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AtlasMetadata {
+    /// VFS path of the sprite sheet.
+    pub image: String,
+    pub frames: Vec<AtlasFrame>,
+}
+
+impl AtlasMetadata {
+    /// File extension of atlas metadata assets.
+    pub const EXTENSION: &str = "atlas";
+
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut metadata = Self::default();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| anyhow!("line {}: expected `key = value`", number + 1))?;
+            let (key, value) = (key.trim(), value.trim());
+
+            if key == "image" {
+                metadata.image = value.to_string();
+                continue;
+            }
+            if let Some(name) = key.strip_prefix("mesh ") {
+                let name = name.trim();
+                let frame = metadata
+                    .frames
+                    .iter_mut()
+                    .find(|frame| frame.name == name)
+                    .ok_or_else(|| {
+                        anyhow!("line {}: mesh of unknown frame {:?}", number + 1, name)
+                    })?;
+                let mesh = SpriteMesh::parse(value)
+                    .with_context(|| format!("line {}: invalid mesh", number + 1))?;
+                frame.mesh = Some(mesh);
+                continue;
+            }
+            let Some(name) = key.strip_prefix("frame ") else {
+                log::warn!("Unknown atlas entry {:?} (line {})", key, number + 1);
+                continue;
+            };
+
+            let fields: Vec<&str> = value.split_whitespace().collect();
+            if fields.len() != 6 {
+                bail!(
+                    "line {}: expected `x y width height pivot_x pivot_y`",
+                    number + 1
+                );
+            }
+            let invalid = || format!("line {}: invalid frame {:?}", number + 1, value);
+            let int = |i: usize| fields[i].parse::<u32>().with_context(invalid);
+            let float = |i: usize| fields[i].parse::<f32>().with_context(invalid);
+            metadata.frames.push(AtlasFrame {
+                name: name.trim().to_string(),
+                x: int(0)?,
+                y: int(1)?,
+                width: int(2)?,
+                height: int(3)?,
+                pivot: [float(4)?, float(5)?],
+                mesh: None,
+            });
+        }
+
+        if metadata.image.is_empty() {
+            bail!("missing `image` entry");
+        }
+        Ok(metadata)
+    }
+
+    pub fn encode(&self) -> String {
+        let mut text = format!("image = {}\n", self.image);
+        for frame in &self.frames {
+            text += &format!(
+                "frame {} = {} {} {} {} {} {}\n",
+                frame.name,
+                frame.x,
+                frame.y,
+                frame.width,
+                frame.height,
+                frame.pivot[0],
+                frame.pivot[1]
+            );
+            if let Some(mesh) = &frame.mesh {
+                text += &format!("mesh {} = {}\n", frame.name, mesh.encode());
+            }
+        }
+        text
+    }
+
+    pub fn load(vfs: &Vfs, path: &str) -> Result<Self> {
+        let text = vfs.read_to_string(path)?;
+        Self::parse(&text).with_context(|| format!("failed to parse atlas {:?}", path))
+    }
+
+    pub fn save(&self, vfs: &Vfs, path: &str) -> Result<()> {
+        vfs.write_bytes(path, self.encode().as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn metadata_roundtrip() {
+        let metadata = AtlasMetadata {
+            image: "assets/sprites/hero.png".to_string(),
+            frames: vec![
+                AtlasFrame {
+                    name: "idle_0".to_string(),
+                    x: 0,
+                    y: 0,
+                    width: 32,
+                    height: 32,
+                    pivot: [0.5, 1.0],
+                    mesh: Some(SpriteMesh::from_convex_polygon(vec![
+                        [0.25, 0.0],
+                        [0.75, 0.0],
+                        [0.75, 1.0],
+                        [0.25, 1.0],
+                    ])),
+                },
+                AtlasFrame {
+                    name: "idle_1".to_string(),
+                    x: 32,
+                    y: 0,
+                    width: 32,
+                    height: 30,
+                    pivot: [0.25, 0.0],
+                    mesh: None,
+                },
+            ],
+        };
+        assert_eq!(AtlasMetadata::parse(&metadata.encode()).unwrap(), metadata);
+        assert!(AtlasMetadata::parse("frame a = 0 0 1 1 0 0").is_err());
+        assert!(AtlasMetadata::parse("image = a.png\nframe a = 0 0 1").is_err());
+        assert!(AtlasMetadata::parse("image = a.png\nmesh a = 0 0 1 0 0 1 | 0 1 2").is_err());
+    }
+}
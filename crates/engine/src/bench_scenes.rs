@@ -0,0 +1,228 @@
+//! Scènes de benchmark du pipeline de sprites, utilisées par `cargo bench` (voir
+//! `benches/sprite_pipeline.rs`) : 10 000 sprites statiques, 1 000 sprites animés, une grande
+//! tilemap et une tempête de particules. Les scènes sont déterministes (graine fixe) pour que les mesures restent
+//! comparables d'un commit à l'autre.
+
+use std::sync::Arc;
+
+use crate::{
+    BlendMode, Camera2D, Rng, Scene, Sprite, SpriteComponent, Texture2D, Tilemap, Tileset,
+    Transform, Vec2, Vec3, Vertex,
+};
+
+/// Scène de benchmark.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BenchScene {
+    /// 10 000 sprites statiques sur une grille, textures, calques et y-sort mélangés.
+    Sprites10k,
+    /// 1 000 sprites qui changent d'image et bougent à chaque frame.
+    AnimatedSprites1k,
+    /// Tilemap de 1024 x 1024 tuiles sur deux calques.
+    LargeTilemap,
+    /// 20 000 particules additives en mouvement.
+    ParticleStorm,
+}
+
+/// Mouvement et animation d'une entité de benchmark (mis à jour par `BenchScene::update`).
+#[derive(Debug, Clone, Copy)]
+struct BenchMotion {
+    origin: Vec2,
+    velocity: Vec2,
+    phase: f32,
+}
+
+impl BenchScene {
+    pub const ALL: [BenchScene; 4] = [
+        BenchScene::Sprites10k,
+        BenchScene::AnimatedSprites1k,
+        BenchScene::LargeTilemap,
+        BenchScene::ParticleStorm,
+    ];
+
+    /// Taille de la zone couverte par les scènes (et du viewport de leur caméra).
+    pub const VIEWPORT: (f32, f32) = (1920.0, 1080.0);
+    /// Colonnes de la planche d'animation (la texture est découpée en autant d'images).
+    const FRAMES: u32 = 8;
+    const SEED: u64 = 0x5EED;
+
+    pub fn name(self) -> &'static str {
+        match self {
+            BenchScene::Sprites10k => "sprites_10k",
+            BenchScene::AnimatedSprites1k => "animated_sprites_1k",
+            BenchScene::LargeTilemap => "large_tilemap",
+            BenchScene::ParticleStorm => "particle_storm",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|scene| scene.name() == name)
+    }
+
+    /// Nouvelle scène remplie (voir `populate`).
+    pub fn build(self, textures: &[Arc<Texture2D>]) -> Scene {
+        let (width, height) = Self::VIEWPORT;
+        let mut scene = Scene::new(self.name().to_string(), Camera2D::new(width, height));
+        self.populate(&mut scene, textures);
+        scene
+    }
+
+    /// Ajoute les entités de la scène. Les sprites utilisent `textures` à tour de rôle : sans
+    /// texture, seule la tilemap est créée (son tileset est alors chargé par la `TilemapPass`).
+    pub fn populate(self, scene: &mut Scene, textures: &[Arc<Texture2D>]) {
+        let mut rng = Rng::new(Self::SEED);
+        let (width, height) = Self::VIEWPORT;
+        let texture = |i: usize| textures.get(i % textures.len().max(1)).cloned();
+
+        match self {
+            BenchScene::Sprites10k => {
+                for i in 0..10_000 {
+                    let Some(texture) = texture(i) else {
+                        return;
+                    };
+                    let (x, y) = ((i % 100) as f32 * 19.2, (i / 100) as f32 * 10.8);
+                    let sprite = Sprite::from_texture(texture)
+                        .with_layer((i % 3) as i32)
+                        .with_y_sort(i % 2 == 0);
+                    scene.spawn_sprite(
+                        format!("sprite_{i}"),
+                        Self::transform(Vec2::new(x, y), 16.0),
+                        sprite,
+                    );
+                }
+            }
+            BenchScene::AnimatedSprites1k => {
+                for i in 0..1_000 {
+                    let Some(texture) = texture(i) else {
+                        return;
+                    };
+                    let origin = Vec2::new(rng.next_f32() * width, rng.next_f32() * height);
+                    let mut sprite = Sprite::from_texture(texture).with_y_sort(true);
+                    sprite.uv = Self::frame_uv(0);
+                    scene.spawn((
+                        Self::transform(origin, 32.0),
+                        SpriteComponent::new(sprite),
+                        BenchMotion {
+                            origin,
+                            velocity: Vec2::new(rng.next_f32() - 0.5, rng.next_f32() - 0.5) * 40.0,
+                            phase: rng.next_f32() * Self::FRAMES as f32,
+                        },
+                    ));
+                }
+            }
+            BenchScene::LargeTilemap => {
+                scene.spawn((Self::large_tilemap(texture(0), &mut rng),));
+            }
+            BenchScene::ParticleStorm => {
+                for i in 0..20_000 {
+                    let Some(texture) = texture(i) else {
+                        return;
+                    };
+                    let origin = Vec2::new(rng.next_f32() * width, rng.next_f32() * height);
+                    let tint = [rng.next_f32(), rng.next_f32(), 1.0, 0.5];
+                    let sprite = Sprite::from_texture(texture)
+                        .with_layer(10)
+                        .with_tint(tint)
+                        .with_blend(BlendMode::Additive);
+                    scene.spawn((
+                        Self::transform(origin, 4.0),
+                        SpriteComponent::new(sprite),
+                        BenchMotion {
+                            origin,
+                            velocity: Vec2::new(rng.next_f32() - 0.5, rng.next_f32() - 0.5) * 600.0,
+                            phase: 0.0,
+                        },
+                    ));
+                }
+            }
+        }
+    }
+
+    /// Place les entités animées à l'instant `time` (secondes) : image de l'animation,
+    /// position (les particules rebouclent dans la zone de la scène).
+    pub fn update(self, scene: &mut Scene, time: f32) {
+        let (width, height) = Self::VIEWPORT;
+        for (_, (transform, component, motion)) in
+            scene
+                .world
+                .query_mut::<(&mut Transform, &mut SpriteComponent, &BenchMotion)>()
+        {
+            let position = motion.origin + motion.velocity * time;
+            let position = match self {
+                BenchScene::ParticleStorm => {
+                    Vec2::new(position.x.rem_euclid(width), position.y.rem_euclid(height))
+                }
+                _ => position,
+            };
+            transform.position = Vec3::new(position.x, position.y, 0.0);
+            if self == BenchScene::AnimatedSprites1k {
+                let frame = (motion.phase + time * 12.0) as u32 % Self::FRAMES;
+                component.sprite.uv = Self::frame_uv(frame);
+            }
+        }
+    }
+
+    /// Tilemap de `LargeTilemap`.
+    fn large_tilemap(texture: Option<Arc<Texture2D>>, rng: &mut Rng) -> Tilemap {
+        let mut map = Tilemap::new(1024, 1024, 16, 16);
+        map.add_tileset(match texture {
+            Some(texture) => Tileset::from_texture("bench", texture, (16, 16)),
+            None => Tileset::new("bench", "bench/tiles.png", (256, 256), (16, 16)),
+        });
+        let tile_count = map.tilesets[0].tile_count.max(1);
+        for layer in 0..2 {
+            let layer = map.add_layer(format!("layer_{layer}"));
+            for y in 0..1024 {
+                for x in 0..1024 {
+                    // Second calque clairsemé, comme un calque de décor
+                    if layer == 1 && !rng.chance(0.2) {
+                        continue;
+                    }
+                    map.set_tile(layer, x, y, 1 + rng.range(0..tile_count));
+                }
+            }
+        }
+        map
+    }
+
+    /// Sprite de `size` pixels à `position`.
+    fn transform(position: Vec2, size: f32) -> Transform {
+        let scale = size / Vertex::QUAD_SIZE;
+        Transform {
+            position: Vec3::new(position.x, position.y, 0.0),
+            scale: Vec3::new(scale, scale, 1.0),
+            ..Default::default()
+        }
+    }
+
+    fn frame_uv(frame: u32) -> [f32; 4] {
+        let width = 1.0 / Self::FRAMES as f32;
+        [frame as f32 * width, 0.0, (frame + 1) as f32 * width, 1.0]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Matrix4;
+
+    use super::*;
+
+    #[test]
+    fn large_tilemap_culls_to_the_camera() {
+        let map = BenchScene::large_tilemap(None, &mut Rng::new(BenchScene::SEED));
+        assert_eq!(map.layers().len(), 2);
+        assert!(
+            map.tile(0, 1023, 1023)
+                .is_some_and(|gid| gid != Tilemap::EMPTY)
+        );
+
+        // Seuls les chunks sous la caméra sont gardés
+        let (width, height) = BenchScene::VIEWPORT;
+        let (min, max) = Camera2D::new(width, height).visible_rect();
+        let visible = map.visible_chunks(&Matrix4::identity(), min.into(), max.into());
+        assert_eq!(visible.len(), 8 * 5);
+        assert_eq!(
+            BenchScene::from_name("particle_storm"),
+            Some(BenchScene::ParticleStorm)
+        );
+    }
+}
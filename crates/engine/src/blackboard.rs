@@ -0,0 +1,378 @@
+//! Blackboard : état global du jeu (score, drapeaux, états de quêtes...) partagé entre les
+//! systèmes et l'interface.
+//!
+//! Les valeurs sont typées (`BlackboardValue`) et chaque modification est enregistrée comme
+//! `BlackboardChange`, pour que le HUD se mette à jour seulement quand une valeur change
+//! (voir `Blackboard::take_changes`). Le blackboard est sauvegardé au format texte, une
+//! entrée par ligne, pour être inclus dans les sauvegardes :
+//!
+//! ```text
+//! score = int 1200
+//! door_open = bool true
+//! speed = float 1.5
+//! quest.main = text Find the key
+//! ```
+
+use std::collections::BTreeMap;
+
+use anyhow::{Context, Result, anyhow, bail};
+
+use crate::Vfs;
+
+/// Valeur d'une entrée du blackboard.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BlackboardValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Text(String),
+}
+
+impl BlackboardValue {
+    /// Nom du type, tel qu'écrit dans le format texte.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            BlackboardValue::Bool(_) => "bool",
+            BlackboardValue::Int(_) => "int",
+            BlackboardValue::Float(_) => "float",
+            BlackboardValue::Text(_) => "text",
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            BlackboardValue::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            BlackboardValue::Int(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Les entiers sont aussi lus comme flottants (ex: barre de progression sur un score).
+    pub fn as_float(&self) -> Option<f64> {
+        match self {
+            BlackboardValue::Float(value) => Some(*value),
+            BlackboardValue::Int(value) => Some(*value as f64),
+            _ => None,
+        }
+    }
+
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            BlackboardValue::Text(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Parse `<type> <valeur>` (ex: `int 3`, `text Hello`).
+    pub fn parse(text: &str) -> Result<Self> {
+        let (type_name, value) = text.split_once(' ').unwrap_or((text, ""));
+        let invalid = || format!("invalid {} value {:?}", type_name, value);
+        Ok(match type_name {
+            "bool" => BlackboardValue::Bool(value.trim().parse().with_context(invalid)?),
+            "int" => BlackboardValue::Int(value.trim().parse().with_context(invalid)?),
+            "float" => BlackboardValue::Float(value.trim().parse().with_context(invalid)?),
+            "text" => BlackboardValue::Text(unescape(value)),
+            other => bail!("unknown value type {:?}", other),
+        })
+    }
+
+    /// Inverse de `parse`.
+    pub fn encode(&self) -> String {
+        match self {
+            BlackboardValue::Bool(value) => format!("bool {}", value),
+            BlackboardValue::Int(value) => format!("int {}", value),
+            BlackboardValue::Float(value) => format!("float {}", value),
+            BlackboardValue::Text(value) => format!("text {}", escape(value)),
+        }
+    }
+}
+
+impl std::fmt::Display for BlackboardValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BlackboardValue::Bool(value) => write!(f, "{}", value),
+            BlackboardValue::Int(value) => write!(f, "{}", value),
+            BlackboardValue::Float(value) => write!(f, "{}", value),
+            BlackboardValue::Text(value) => f.write_str(value),
+        }
+    }
+}
+
+impl From<bool> for BlackboardValue {
+    fn from(value: bool) -> Self {
+        BlackboardValue::Bool(value)
+    }
+}
+
+impl From<i64> for BlackboardValue {
+    fn from(value: i64) -> Self {
+        BlackboardValue::Int(value)
+    }
+}
+
+impl From<i32> for BlackboardValue {
+    fn from(value: i32) -> Self {
+        BlackboardValue::Int(value.into())
+    }
+}
+
+impl From<f64> for BlackboardValue {
+    fn from(value: f64) -> Self {
+        BlackboardValue::Float(value)
+    }
+}
+
+impl From<f32> for BlackboardValue {
+    fn from(value: f32) -> Self {
+        BlackboardValue::Float(value.into())
+    }
+}
+
+impl From<&str> for BlackboardValue {
+    fn from(value: &str) -> Self {
+        BlackboardValue::Text(value.to_string())
+    }
+}
+
+impl From<String> for BlackboardValue {
+    fn from(value: String) -> Self {
+        BlackboardValue::Text(value)
+    }
+}
+
+/// Modification d'une entrée : `old` est `None` pour une création, `new` pour une suppression.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlackboardChange {
+    pub key: String,
+    pub old: Option<BlackboardValue>,
+    pub new: Option<BlackboardValue>,
+}
+
+/// État global clé -> valeur, avec la liste des modifications depuis le dernier
+/// `take_changes`.
+#[derive(Debug, Clone, Default)]
+pub struct Blackboard {
+    values: BTreeMap<String, BlackboardValue>,
+    changes: Vec<BlackboardChange>,
+    /// Incrémenté à chaque modification.
+    revision: u64,
+}
+
+impl Blackboard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&BlackboardValue> {
+        self.values.get(key)
+    }
+
+    pub fn get_bool(&self, key: &str) -> Option<bool> {
+        self.get(key)?.as_bool()
+    }
+
+    pub fn get_int(&self, key: &str) -> Option<i64> {
+        self.get(key)?.as_int()
+    }
+
+    pub fn get_float(&self, key: &str) -> Option<f64> {
+        self.get(key)?.as_float()
+    }
+
+    pub fn get_text(&self, key: &str) -> Option<&str> {
+        self.get(key)?.as_text()
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.values.contains_key(key)
+    }
+
+    /// Définit `key`. Ne produit pas de changement si la valeur est identique.
+    pub fn set(&mut self, key: impl Into<String>, value: impl Into<BlackboardValue>) {
+        let (key, value) = (key.into(), value.into());
+        if self.values.get(&key) == Some(&value) {
+            return;
+        }
+        let old = self.values.insert(key.clone(), value.clone());
+        self.record(key, old, Some(value));
+    }
+
+    /// Ajoute `delta` à l'entier `key` (créé à 0 s'il n'existe pas) et retourne la nouvelle
+    /// valeur. Une valeur d'un autre type est remplacée.
+    pub fn add_int(&mut self, key: &str, delta: i64) -> i64 {
+        let value = self.get_int(key).unwrap_or(0).saturating_add(delta);
+        self.set(key, value);
+        value
+    }
+
+    /// Inverse le booléen `key` (absent = `false`) et retourne la nouvelle valeur.
+    pub fn toggle(&mut self, key: &str) -> bool {
+        let value = !self.get_bool(key).unwrap_or(false);
+        self.set(key, value);
+        value
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<BlackboardValue> {
+        let old = self.values.remove(key)?;
+        self.record(key.to_string(), Some(old.clone()), None);
+        Some(old)
+    }
+
+    /// Supprime toutes les entrées (un changement par entrée).
+    pub fn clear(&mut self) {
+        for (key, old) in std::mem::take(&mut self.values) {
+            self.record(key, Some(old), None);
+        }
+    }
+
+    /// Entrées triées par clé.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &BlackboardValue)> {
+        self.values.iter().map(|(key, value)| (key.as_str(), value))
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Compteur de modifications : comparer avec une valeur retenue permet de savoir si
+    /// quelque chose a changé sans consommer les changements.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Changements depuis le dernier appel, dans l'ordre.
+    pub fn take_changes(&mut self) -> Vec<BlackboardChange> {
+        std::mem::take(&mut self.changes)
+    }
+
+    fn record(&mut self, key: String, old: Option<BlackboardValue>, new: Option<BlackboardValue>) {
+        self.revision += 1;
+        self.changes.push(BlackboardChange { key, old, new });
+    }
+
+    /// Lit le format texte (voir le module). Le résultat ne contient aucun changement.
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut blackboard = Self::new();
+        for (number, line) in text.lines().enumerate() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            // Le texte garde ses espaces de fin : on ne retire que celui après `=`
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| anyhow!("line {}: expected `key = type value`", number + 1))?;
+            let value = value.strip_prefix(' ').unwrap_or(value);
+            let value = BlackboardValue::parse(value)
+                .with_context(|| format!("line {}: {}", number + 1, key.trim()))?;
+            blackboard.values.insert(key.trim().to_string(), value);
+        }
+        Ok(blackboard)
+    }
+
+    pub fn encode(&self) -> String {
+        self.values
+            .iter()
+            .map(|(key, value)| format!("{} = {}\n", key, value.encode()))
+            .collect()
+    }
+
+    /// Remplace le contenu par celui de `path` (chargement d'une sauvegarde) : les
+    /// différences sont enregistrées comme changements, pour rafraîchir le HUD.
+    pub fn load(&mut self, vfs: &Vfs, path: &str) -> Result<()> {
+        let text = vfs.read_to_string(path)?;
+        let loaded = Self::parse(&text).with_context(|| format!("failed to parse {:?}", path))?;
+        let removed: Vec<String> = self
+            .values
+            .keys()
+            .filter(|key| !loaded.values.contains_key(*key))
+            .cloned()
+            .collect();
+        for key in removed {
+            self.remove(&key);
+        }
+        for (key, value) in loaded.values {
+            self.set(key, value);
+        }
+        Ok(())
+    }
+
+    pub fn save(&self, vfs: &Vfs, path: &str) -> Result<()> {
+        vfs.write_bytes(path, self.encode().as_bytes())
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+fn unescape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some(other) => result.push(other),
+            None => result.push('\\'),
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changes_are_recorded_once_per_modification() {
+        let mut blackboard = Blackboard::new();
+        blackboard.set("score", 10);
+        blackboard.set("score", 10);
+        assert_eq!(blackboard.add_int("score", 5), 15);
+        assert!(blackboard.toggle("door_open"));
+        blackboard.remove("door_open");
+
+        let changes = blackboard.take_changes();
+        assert_eq!(changes.len(), 4);
+        assert_eq!(changes[0].old, None);
+        assert_eq!(changes[1].old, Some(BlackboardValue::Int(10)));
+        assert_eq!(changes[1].new, Some(BlackboardValue::Int(15)));
+        assert_eq!(changes[3].new, None);
+        assert_eq!(blackboard.revision(), 4);
+        assert!(blackboard.take_changes().is_empty());
+        assert_eq!(blackboard.get_float("score"), Some(15.0));
+    }
+
+    #[test]
+    fn text_roundtrip() {
+        let mut blackboard = Blackboard::new();
+        blackboard.set("score", 1200);
+        blackboard.set("speed", 1.5);
+        blackboard.set("door_open", true);
+        blackboard.set("quest.main", " Find the key\nthen \\ leave ");
+
+        let text = blackboard.encode();
+        let parsed = Blackboard::parse(&text).unwrap();
+        assert_eq!(
+            parsed.iter().collect::<Vec<_>>(),
+            blackboard.iter().collect::<Vec<_>>()
+        );
+        assert!(parsed.changes.is_empty());
+        assert!(Blackboard::parse("a = vec 1 2").is_err());
+        assert!(Blackboard::parse("a = int x").is_err());
+    }
+}
@@ -0,0 +1,628 @@
+//! Rastérisation des glyphes en couleur, pour le `GlyphAtlas` :
+//!
+//! - images des tables CBDT et sbix (PNG ou BGRA prémultiplié), mises à l'échelle depuis la
+//!   taille de la police la plus proche ;
+//! - calques et dégradés de COLR (v0 et v1), que ttf-parser décrit à un `colr::Painter` :
+//!   chaque contour est rempli par ab_glyph_rasterizer puis fusionné dans une image RGBA.
+//!
+//! Les modes de fusion de COLR v1 qui ne sont pas ceux de Porter-Duff (`Multiply`,
+//! `Screen`…) sont dessinés comme `SourceOver`, et la table `SVG ` n'est pas lue. La couleur
+//! du premier plan (calques sans couleur de palette) est le blanc : le glyphe est gardé dans
+//! l'atlas quelle que soit la couleur du texte.
+
+use rustybuzz::ttf_parser::{
+    self, GlyphId, RasterGlyphImage, RasterImageFormat, RgbaColor, Transform,
+    colr::{ClipBox, ColorStop, CompositeMode, GradientExtend, Paint, Painter},
+};
+
+use crate::glyph_atlas::{Outline, transform_point};
+
+/// Image (couleurs prémultipliées) du glyphe en couleur `id` à `pixels` pixels par em, et
+/// position de son coin haut gauche depuis l'origine du glyphe. `None` si la police n'a pas
+/// d'image ni de calques pour ce glyphe.
+pub fn rasterize_color_glyph(
+    face: &ttf_parser::Face,
+    id: u16,
+    pixels: f32,
+) -> Option<(egui::ColorImage, egui::Vec2)> {
+    let id = GlyphId(id);
+    if face.is_color_glyph(id) {
+        return paint_colr(face, id, pixels);
+    }
+    let strike = pixels.round().clamp(1.0, u16::MAX as f32) as u16;
+    decode_raster(&face.glyph_raster_image(id, strike)?, pixels)
+}
+
+/// Image CBDT ou sbix mise à l'échelle de `pixels` pixels par em.
+fn decode_raster(raster: &RasterGlyphImage, pixels: f32) -> Option<(egui::ColorImage, egui::Vec2)> {
+    let mut image = match raster.format {
+        RasterImageFormat::PNG => {
+            image::load_from_memory_with_format(raster.data, image::ImageFormat::Png)
+                .ok()?
+                .to_rgba8()
+        }
+        RasterImageFormat::BitmapPremulBgra32 => {
+            let mut image = image::RgbaImage::from_raw(
+                raster.width.into(),
+                raster.height.into(),
+                raster.data.to_vec(),
+            )?;
+            for pixel in image.pixels_mut() {
+                pixel.0.swap(0, 2);
+            }
+            // Déjà prémultiplié
+            return Some(scale_raster(raster, &image, pixels));
+        }
+        // Bitmaps monochromes : le contour du glyphe, s'il existe, est utilisé
+        _ => return None,
+    };
+    for pixel in image.pixels_mut() {
+        let [r, g, b, a] = pixel.0;
+        let premultiply = |c: u8| ((c as u16 * a as u16 + 127) / 255) as u8;
+        pixel.0 = [premultiply(r), premultiply(g), premultiply(b), a];
+    }
+    Some(scale_raster(raster, &image, pixels))
+}
+
+fn scale_raster(
+    raster: &RasterGlyphImage,
+    image: &image::RgbaImage,
+    pixels: f32,
+) -> (egui::ColorImage, egui::Vec2) {
+    let scale = pixels / raster.pixels_per_em.max(1) as f32;
+    let size = [
+        (image.width() as f32 * scale).round().max(1.0) as u32,
+        (image.height() as f32 * scale).round().max(1.0) as u32,
+    ];
+    let image = image::imageops::resize(
+        image,
+        size[0],
+        size[1],
+        image::imageops::FilterType::Triangle,
+    );
+    let pixels = image
+        .pixels()
+        .map(|pixel| {
+            let [r, g, b, a] = pixel.0;
+            egui::Color32::from_rgba_premultiplied(r, g, b, a)
+        })
+        .collect();
+    // (x, y) : coin bas gauche de l'image depuis l'origine, y vers le haut
+    let offset = egui::vec2(
+        raster.x as f32 * scale,
+        -(raster.y as f32 + image.height() as f32 / scale) * scale,
+    )
+    .round();
+    (
+        egui::ColorImage::new([size[0] as usize, size[1] as usize], pixels),
+        offset,
+    )
+}
+
+/// Calques COLR du glyphe `id`, peints avec la première palette.
+fn paint_colr(
+    face: &ttf_parser::Face,
+    id: GlyphId,
+    pixels: f32,
+) -> Option<(egui::ColorImage, egui::Vec2)> {
+    let foreground = RgbaColor::new(255, 255, 255, 255);
+    let scale = pixels / face.units_per_em() as f32;
+
+    let mut bounds = Bounds {
+        face,
+        transforms: vec![Transform::new_scale(scale, -scale)],
+        rect: egui::Rect::NOTHING,
+    };
+    face.paint_color_glyph(id, 0, foreground, &mut bounds)?;
+    if !bounds.rect.is_positive() {
+        return None;
+    }
+    let min = bounds.rect.min.floor().to_vec2();
+    let max = bounds.rect.max.ceil().to_vec2();
+    let size = [(max.x - min.x) as usize, (max.y - min.y) as usize];
+
+    let mut canvas = Canvas {
+        face,
+        size,
+        transforms: vec![Transform::new(scale, 0.0, 0.0, -scale, -min.x, -min.y)],
+        outline: None,
+        clips: Vec::new(),
+        layers: vec![(vec![[0.0; 4]; size[0] * size[1]], CompositeMode::SourceOver)],
+    };
+    face.paint_color_glyph(id, 0, foreground, &mut canvas)?;
+    let (image, _) = canvas.layers.swap_remove(0);
+    let pixels = image
+        .into_iter()
+        .map(|color| {
+            let [r, g, b, a] = color.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+            egui::Color32::from_rgba_premultiplied(r, g, b, a)
+        })
+        .collect();
+    Some((egui::ColorImage::new(size, pixels), min))
+}
+
+/// Rectangle (pixels, y vers le bas, depuis l'origine du glyphe) des contours d'un glyphe
+/// COLR.
+struct Bounds<'f, 'a> {
+    face: &'f ttf_parser::Face<'a>,
+    transforms: Vec<Transform>,
+    rect: egui::Rect,
+}
+
+impl<'a> Painter<'a> for Bounds<'_, 'a> {
+    fn outline_glyph(&mut self, glyph_id: GlyphId) {
+        let (Some(bbox), Some(transform)) = (
+            self.face.glyph_bounding_box(glyph_id),
+            self.transforms.last(),
+        ) else {
+            return;
+        };
+        for (x, y) in [
+            (bbox.x_min, bbox.y_min),
+            (bbox.x_max, bbox.y_min),
+            (bbox.x_min, bbox.y_max),
+            (bbox.x_max, bbox.y_max),
+        ] {
+            let [x, y] = transform_point(transform, x.into(), y.into());
+            self.rect.extend_with(egui::pos2(x, y));
+        }
+    }
+
+    fn paint(&mut self, _: Paint<'a>) {}
+
+    fn push_clip(&mut self) {}
+
+    fn push_clip_box(&mut self, _: ClipBox) {}
+
+    fn pop_clip(&mut self) {}
+
+    fn push_layer(&mut self, _: CompositeMode) {}
+
+    fn pop_layer(&mut self) {}
+
+    fn push_transform(&mut self, transform: Transform) {
+        push_transform(&mut self.transforms, transform);
+    }
+
+    fn pop_transform(&mut self) {
+        self.transforms.pop();
+    }
+}
+
+/// Empile `transform`, appliquée avant celles déjà empilées.
+fn push_transform(transforms: &mut Vec<Transform>, transform: Transform) {
+    let top = transforms.last().copied().unwrap_or_default();
+    transforms.push(Transform::combine(top, transform));
+}
+
+/// Image d'un glyphe COLR en cours de dessin. Les couleurs sont en RGBA prémultiplié (0..1).
+struct Canvas<'f, 'a> {
+    face: &'f ttf_parser::Face<'a>,
+    size: [usize; 2],
+    /// Unités de la police -> pixels de l'image, puis transformations de COLR.
+    transforms: Vec<Transform>,
+    /// Couverture du dernier contour, rempli par `paint` (COLR v0).
+    outline: Option<Vec<f32>>,
+    /// Couvertures des découpes empilées, chacune déjà limitée par la précédente.
+    clips: Vec<Vec<f32>>,
+    /// Calques, chacun fusionné avec son mode sur celui du dessous quand il est retiré.
+    layers: Vec<(Vec<[f32; 4]>, CompositeMode)>,
+}
+
+impl Canvas<'_, '_> {
+    fn transform(&self) -> Transform {
+        self.transforms.last().copied().unwrap_or_default()
+    }
+
+    fn push_clip_coverage(&mut self, mut coverage: Vec<f32>) {
+        if let Some(clip) = self.clips.last() {
+            for (value, clip) in coverage.iter_mut().zip(clip) {
+                *value *= clip;
+            }
+        }
+        self.clips.push(coverage);
+    }
+}
+
+impl<'a> Painter<'a> for Canvas<'_, 'a> {
+    fn outline_glyph(&mut self, glyph_id: GlyphId) {
+        let mut outline = Outline::new(self.size, self.transform());
+        self.face.outline_glyph(glyph_id, &mut outline);
+        self.outline = Some(outline.coverage());
+    }
+
+    fn paint(&mut self, paint: Paint<'a>) {
+        let Some(inverse) = invert(&self.transform()) else {
+            return;
+        };
+        let shader = Shader::new(paint, self.face);
+        let width = self.size[0];
+        let Some((layer, _)) = self.layers.last_mut() else {
+            return;
+        };
+        for (index, pixel) in layer.iter_mut().enumerate() {
+            let coverage = self.outline.as_ref().map_or(1.0, |outline| outline[index])
+                * self.clips.last().map_or(1.0, |clip| clip[index]);
+            if coverage <= 0.0 {
+                continue;
+            }
+            let [x, y] = pixel_center(&inverse, width, index);
+            let source = shader.color(x, y).map(|c| c * coverage);
+            *pixel = composite(CompositeMode::SourceOver, source, *pixel);
+        }
+    }
+
+    fn push_clip(&mut self) {
+        // COLR v1 : le contour ne sert plus qu'à découper ce qui est peint dedans
+        let outline = self
+            .outline
+            .take()
+            .unwrap_or_else(|| vec![0.0; self.size[0] * self.size[1]]);
+        self.push_clip_coverage(outline);
+    }
+
+    fn push_clip_box(&mut self, clipbox: ClipBox) {
+        // Le rectangle peut dépasser l'image : il est testé au centre de chaque pixel
+        let Some(inverse) = invert(&self.transform()) else {
+            return self.push_clip_coverage(vec![0.0; self.size[0] * self.size[1]]);
+        };
+        let coverage = (0..self.size[0] * self.size[1])
+            .map(|index| {
+                let [x, y] = pixel_center(&inverse, self.size[0], index);
+                let inside = (clipbox.x_min..=clipbox.x_max).contains(&x)
+                    && (clipbox.y_min..=clipbox.y_max).contains(&y);
+                inside as u8 as f32
+            })
+            .collect();
+        self.push_clip_coverage(coverage);
+    }
+
+    fn pop_clip(&mut self) {
+        self.clips.pop();
+    }
+
+    fn push_layer(&mut self, mode: CompositeMode) {
+        self.layers
+            .push((vec![[0.0; 4]; self.size[0] * self.size[1]], mode));
+    }
+
+    fn pop_layer(&mut self) {
+        // Le premier calque est l'image du glyphe
+        if self.layers.len() > 1
+            && let Some((source, mode)) = self.layers.pop()
+            && let Some((backdrop, _)) = self.layers.last_mut()
+        {
+            for (pixel, source) in backdrop.iter_mut().zip(source) {
+                *pixel = composite(mode, source, *pixel);
+            }
+        }
+    }
+
+    fn push_transform(&mut self, transform: Transform) {
+        push_transform(&mut self.transforms, transform);
+    }
+
+    fn pop_transform(&mut self) {
+        self.transforms.pop();
+    }
+}
+
+/// Centre du pixel `index` d'une image de `width` pixels de large, passé par `inverse` dans
+/// les unités de la police.
+fn pixel_center(inverse: &Transform, width: usize, index: usize) -> [f32; 2] {
+    let x = (index % width) as f32 + 0.5;
+    let y = (index / width) as f32 + 0.5;
+    transform_point(inverse, x, y)
+}
+
+/// Fusion de Porter-Duff de `source` sur `backdrop` (prémultipliés).
+fn composite(mode: CompositeMode, source: [f32; 4], backdrop: [f32; 4]) -> [f32; 4] {
+    let (a, b) = (source[3], backdrop[3]);
+    let (source_factor, backdrop_factor) = match mode {
+        CompositeMode::Clear => (0.0, 0.0),
+        CompositeMode::Source => (1.0, 0.0),
+        CompositeMode::Destination => (0.0, 1.0),
+        CompositeMode::DestinationOver => (1.0 - b, 1.0),
+        CompositeMode::SourceIn => (b, 0.0),
+        CompositeMode::DestinationIn => (0.0, a),
+        CompositeMode::SourceOut => (1.0 - b, 0.0),
+        CompositeMode::DestinationOut => (0.0, 1.0 - a),
+        CompositeMode::SourceAtop => (b, 1.0 - a),
+        CompositeMode::DestinationAtop => (1.0 - b, a),
+        CompositeMode::Xor => (1.0 - b, 1.0 - a),
+        CompositeMode::Plus => (1.0, 1.0),
+        _ => (1.0, 1.0 - a),
+    };
+    std::array::from_fn(|i| (source[i] * source_factor + backdrop[i] * backdrop_factor).min(1.0))
+}
+
+/// Inverse de `transform`, `None` si elle écrase le plan.
+fn invert(transform: &Transform) -> Option<Transform> {
+    let Transform { a, b, c, d, e, f } = *transform;
+    let det = a * d - b * c;
+    if det.abs() < f32::EPSILON {
+        return None;
+    }
+    Some(Transform::new(
+        d / det,
+        -b / det,
+        -c / det,
+        a / det,
+        (c * f - d * e) / det,
+        (b * e - a * f) / det,
+    ))
+}
+
+/// Couleur d'un `Paint` en chaque point (unités de la police).
+enum Shader {
+    Solid([f32; 4]),
+    /// Position sur le dégradé : projection sur `direction` (divisée par sa longueur au
+    /// carré) depuis `start`.
+    Linear {
+        start: [f32; 2],
+        direction: [f32; 2],
+        ramp: Ramp,
+    },
+    /// Dégradé entre deux cercles.
+    Radial {
+        center: [f32; 2],
+        radius: f32,
+        center_delta: [f32; 2],
+        radius_delta: f32,
+        ramp: Ramp,
+    },
+    /// Angles en degrés, sens trigonométrique.
+    Sweep {
+        center: [f32; 2],
+        start_angle: f32,
+        end_angle: f32,
+        ramp: Ramp,
+    },
+}
+
+impl Shader {
+    fn new(paint: Paint, face: &ttf_parser::Face) -> Self {
+        let coords = face.variation_coordinates();
+        match paint {
+            Paint::Solid(color) => Shader::Solid(premultiplied(color)),
+            Paint::LinearGradient(gradient) => {
+                // Le dégradé varie selon p0 -> p1, projeté sur la normale de p0 -> p2
+                let start = [gradient.x0, gradient.y0];
+                let normal = [gradient.y2 - gradient.y0, gradient.x0 - gradient.x2];
+                let delta = [gradient.x1 - gradient.x0, gradient.y1 - gradient.y0];
+                let length = dot(normal, normal);
+                let end = match length > 0.0 {
+                    true => normal.map(|n| n * dot(delta, normal) / length),
+                    false => delta,
+                };
+                let length = dot(end, end).max(f32::EPSILON);
+                Shader::Linear {
+                    start,
+                    direction: end.map(|e| e / length),
+                    ramp: Ramp::new(gradient.stops(0, coords).collect(), gradient.extend),
+                }
+            }
+            Paint::RadialGradient(gradient) => Shader::Radial {
+                center: [gradient.x0, gradient.y0],
+                radius: gradient.r0,
+                center_delta: [gradient.x1 - gradient.x0, gradient.y1 - gradient.y0],
+                radius_delta: gradient.r1 - gradient.r0,
+                ramp: Ramp::new(gradient.stops(0, coords).collect(), gradient.extend),
+            },
+            Paint::SweepGradient(gradient) => Shader::Sweep {
+                center: [gradient.center_x, gradient.center_y],
+                // Unités de COLR : 1.0 = 180°
+                start_angle: gradient.start_angle * 180.0,
+                end_angle: gradient.end_angle * 180.0,
+                ramp: Ramp::new(gradient.stops(0, coords).collect(), gradient.extend),
+            },
+        }
+    }
+
+    fn color(&self, x: f32, y: f32) -> [f32; 4] {
+        match self {
+            Shader::Solid(color) => *color,
+            Shader::Linear {
+                start,
+                direction,
+                ramp,
+            } => ramp.color(dot([x - start[0], y - start[1]], *direction)),
+            Shader::Radial {
+                center,
+                radius,
+                center_delta,
+                radius_delta,
+                ramp,
+            } => {
+                // Plus grand t tel que le point soit sur le cercle (c0 + t dc, r0 + t dr)
+                let point = [x - center[0], y - center[1]];
+                let a = dot(*center_delta, *center_delta) - radius_delta * radius_delta;
+                let b = dot(point, *center_delta) + radius * radius_delta;
+                let c = dot(point, point) - radius * radius;
+                let t = if a.abs() < f32::EPSILON {
+                    (b.abs() > f32::EPSILON).then(|| c / (2.0 * b))
+                } else {
+                    let discriminant = b * b - a * c;
+                    (discriminant >= 0.0).then(|| {
+                        let root = discriminant.sqrt();
+                        [(b + root) / a, (b - root) / a]
+                            .into_iter()
+                            .filter(|t| radius + t * radius_delta >= 0.0)
+                            .fold(f32::NEG_INFINITY, f32::max)
+                    })
+                };
+                match t {
+                    Some(t) if t.is_finite() => ramp.color(t),
+                    _ => [0.0; 4],
+                }
+            }
+            Shader::Sweep {
+                center,
+                start_angle,
+                end_angle,
+                ramp,
+            } => {
+                let angle = (y - center[1])
+                    .atan2(x - center[0])
+                    .to_degrees()
+                    .rem_euclid(360.0);
+                let span = end_angle - start_angle;
+                match (span.abs() > f32::EPSILON, ramp.extend) {
+                    (true, _) => ramp.color((angle - start_angle) / span),
+                    // Angles confondus : arrêt net, ou rien hors de `Pad`
+                    (false, GradientExtend::Pad) => match angle < *start_angle {
+                        true => ramp.color(f32::NEG_INFINITY),
+                        false => ramp.color(f32::INFINITY),
+                    },
+                    (false, _) => [0.0; 4],
+                }
+            }
+        }
+    }
+}
+
+/// Arrêts de couleur d'un dégradé (couleurs non prémultipliées), triés.
+struct Ramp {
+    stops: Vec<(f32, [f32; 4])>,
+    extend: GradientExtend,
+}
+
+impl Ramp {
+    fn new(stops: Vec<ColorStop>, extend: GradientExtend) -> Self {
+        let mut stops: Vec<_> = stops
+            .into_iter()
+            .map(|stop| (stop.stop_offset, unit(stop.color)))
+            .collect();
+        stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Self { stops, extend }
+    }
+
+    /// Couleur (prémultipliée) à la position `t`, répétée ou réfléchie hors des arrêts selon
+    /// `extend`.
+    fn color(&self, t: f32) -> [f32; 4] {
+        let (Some(first), Some(last)) = (self.stops.first(), self.stops.last()) else {
+            return [0.0; 4];
+        };
+        let span = last.0 - first.0;
+        let t = match self.extend {
+            _ if span <= 0.0 => t,
+            GradientExtend::Pad => t,
+            GradientExtend::Repeat => first.0 + ((t - first.0) / span).rem_euclid(1.0) * span,
+            GradientExtend::Reflect => {
+                let u = ((t - first.0) / span).rem_euclid(2.0);
+                first.0 + (1.0 - (u - 1.0).abs()) * span
+            }
+        };
+        let color = match self.stops.iter().position(|stop| stop.0 > t) {
+            None => last.1,
+            Some(0) => first.1,
+            Some(index) => {
+                let (from, to) = (self.stops[index - 1], self.stops[index]);
+                let k = (t - from.0) / (to.0 - from.0);
+                std::array::from_fn(|i| from.1[i] + (to.1[i] - from.1[i]) * k)
+            }
+        };
+        [
+            color[0] * color[3],
+            color[1] * color[3],
+            color[2] * color[3],
+            color[3],
+        ]
+    }
+}
+
+fn unit(color: RgbaColor) -> [f32; 4] {
+    [color.red, color.green, color.blue, color.alpha].map(|c| c as f32 / 255.0)
+}
+
+fn premultiplied(color: RgbaColor) -> [f32; 4] {
+    let [r, g, b, a] = unit(color);
+    [r * a, g * a, b * a, a]
+}
+
+fn dot(a: [f32; 2], b: [f32; 2]) -> f32 {
+    a[0] * b[0] + a[1] * b[1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn color_layers_and_bitmaps_are_rasterized() {
+        let face =
+            ttf_parser::Face::parse(include_bytes!("../../../engine/fonts/DejaVuSans.ttf"), 0)
+                .unwrap();
+        // DejaVu Sans n'a ni COLR ni CBDT
+        let o = face.glyph_index('O').unwrap();
+        assert!(rasterize_color_glyph(&face, o.0, 32.0).is_none());
+
+        // Comme un PaintComposite de COLR v1 : un « O » rouge dont la moitié gauche est
+        // effacée par un rectangle en « DestinationOut »
+        let scale = 32.0 / face.units_per_em() as f32;
+        let mut bounds = Bounds {
+            face: &face,
+            transforms: vec![Transform::new_scale(scale, -scale)],
+            rect: egui::Rect::NOTHING,
+        };
+        bounds.outline_glyph(o);
+        let min = bounds.rect.min.floor();
+        let size = [
+            (bounds.rect.max.x.ceil() - min.x) as usize,
+            (bounds.rect.max.y.ceil() - min.y) as usize,
+        ];
+        let mut canvas = Canvas {
+            face: &face,
+            size,
+            transforms: vec![Transform::new(scale, 0.0, 0.0, -scale, -min.x, -min.y)],
+            outline: None,
+            clips: Vec::new(),
+            layers: vec![(vec![[0.0; 4]; size[0] * size[1]], CompositeMode::SourceOver)],
+        };
+        let bbox = face.glyph_bounding_box(o).unwrap();
+        canvas.push_layer(CompositeMode::SourceOver);
+        canvas.outline_glyph(o);
+        canvas.push_clip();
+        canvas.paint(Paint::Solid(RgbaColor::new(255, 0, 0, 255)));
+        canvas.pop_clip();
+        canvas.push_layer(CompositeMode::DestinationOut);
+        canvas.push_clip_box(ClipBox {
+            x_min: -1000.0,
+            y_min: -1000.0,
+            x_max: (bbox.x_min + bbox.x_max) as f32 / 2.0,
+            y_max: 4000.0,
+        });
+        canvas.paint(Paint::Solid(RgbaColor::new(0, 0, 255, 255)));
+        canvas.pop_clip();
+        canvas.pop_layer();
+        canvas.pop_layer();
+
+        let pixel = |x: usize| canvas.layers[0].0[size[1] / 2 * size[0] + x];
+        let right = pixel(size[0] - 2);
+        assert!(right[3] > 0.9 && right[0] == right[3] && right[2] == 0.0);
+        assert_eq!(pixel(2), [0.0; 4]);
+        assert_eq!(pixel(size[0] / 2), [0.0; 4]);
+
+        // Image CBDT de 4x4 pixels à 8 pixels par em, dessinée à 16 pixels par em
+        let mut png = Vec::new();
+        image::RgbaImage::from_pixel(4, 4, image::Rgba([255, 0, 0, 128]))
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        let raster = RasterGlyphImage {
+            x: 1,
+            y: -2,
+            width: 4,
+            height: 4,
+            pixels_per_em: 8,
+            format: RasterImageFormat::PNG,
+            data: &png,
+        };
+        let (image, offset) = decode_raster(&raster, 16.0).unwrap();
+        assert_eq!(image.size, [8, 8]);
+        assert_eq!(offset, egui::vec2(2.0, -4.0));
+        assert_eq!(
+            image.pixels[0],
+            egui::Color32::from_rgba_premultiplied(128, 0, 0, 128)
+        );
+    }
+}
@@ -0,0 +1,557 @@
+//! Sélecteur de couleur egui : roue HSV, saisie hexadécimale, palettes et pipette.
+//!
+//! Les couleurs éditées sont des `[f32; 4]` RGBA (espace sRGB, alpha non prémultiplié),
+//! le format des champs de teinte. Les palettes sont sauvegardées dans le mount utilisateur
+//! (`ColorPalettes::PATH`) pour être partagées entre projets.
+
+use std::sync::Arc;
+
+use anyhow::{Context, Result, anyhow};
+
+use crate::Vfs;
+
+/// Convertit un RGB (0..1) en HSV (teinte, saturation, valeur dans 0..1).
+pub fn rgb_to_hsv([r, g, b]: [f32; 3]) -> [f32; 3] {
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let delta = max - min;
+
+    let hue = if delta <= f32::EPSILON {
+        0.0
+    } else if max == r {
+        ((g - b) / delta).rem_euclid(6.0) / 6.0
+    } else if max == g {
+        ((b - r) / delta + 2.0) / 6.0
+    } else {
+        ((r - g) / delta + 4.0) / 6.0
+    };
+    let saturation = if max <= f32::EPSILON {
+        0.0
+    } else {
+        delta / max
+    };
+
+    [hue, saturation, max]
+}
+
+/// Convertit un HSV (0..1) en RGB (0..1).
+pub fn hsv_to_rgb([h, s, v]: [f32; 3]) -> [f32; 3] {
+    let h = h.rem_euclid(1.0) * 6.0;
+    let c = v * s;
+    let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+    let m = v - c;
+    let (r, g, b) = match h as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    [r + m, g + m, b + m]
+}
+
+/// Lit une couleur "#RRGGBB" ou "#RRGGBBAA" (le '#' est optionnel).
+pub fn parse_hex_color(text: &str) -> Option<[f32; 4]> {
+    let hex = text.trim().trim_start_matches('#');
+    if !matches!(hex.len(), 6 | 8) || !hex.is_ascii() {
+        return None;
+    }
+
+    let mut color = [1.0; 4];
+    for (i, channel) in color.iter_mut().enumerate().take(hex.len() / 2) {
+        let byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok()?;
+        *channel = byte as f32 / 255.0;
+    }
+    Some(color)
+}
+
+/// Écrit une couleur en "#RRGGBB", ou "#RRGGBBAA" si elle n'est pas opaque.
+pub fn format_hex_color(color: [f32; 4]) -> String {
+    let [r, g, b, a] = color.map(to_byte);
+    if a == 255 {
+        format!("#{:02X}{:02X}{:02X}", r, g, b)
+    } else {
+        format!("#{:02X}{:02X}{:02X}{:02X}", r, g, b, a)
+    }
+}
+
+fn to_byte(channel: f32) -> u8 {
+    (channel.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+fn to_color32(color: [f32; 4]) -> egui::Color32 {
+    let [r, g, b, a] = color.map(to_byte);
+    egui::Color32::from_rgba_unmultiplied(r, g, b, a)
+}
+
+/// Une palette nommée.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorPalette {
+    pub name: String,
+    pub colors: Vec<[f32; 4]>,
+}
+
+/// Ensemble des palettes de l'utilisateur.
+///
+/// Format texte : une palette par ligne, `nom = #RRGGBB #RRGGBBAA ...`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ColorPalettes {
+    pub palettes: Vec<ColorPalette>,
+}
+
+impl ColorPalettes {
+    /// Fichier des palettes, dans le mount utilisateur.
+    pub const PATH: &str = "user/palettes.cfg";
+
+    /// Palette proposée quand l'utilisateur n'en a pas encore.
+    pub fn builtin() -> Self {
+        let colors = [
+            "#000000", "#FFFFFF", "#7F7F7F", "#E53B3B", "#F2994A", "#F2C94C", "#6FCF97", "#2F80ED",
+            "#9B51E0",
+        ];
+        Self {
+            palettes: vec![ColorPalette {
+                name: "Default".to_string(),
+                colors: colors.iter().filter_map(|c| parse_hex_color(c)).collect(),
+            }],
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        self.palettes
+            .iter()
+            .map(|palette| {
+                let colors: Vec<_> = palette
+                    .colors
+                    .iter()
+                    .copied()
+                    .map(format_hex_color)
+                    .collect();
+                format!("{} = {}\n", palette.name, colors.join(" "))
+            })
+            .collect()
+    }
+
+    pub fn decode(text: &str) -> Result<Self> {
+        let palettes = text
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+            .map(|line| {
+                let (name, colors) = line
+                    .split_once('=')
+                    .ok_or_else(|| anyhow!("expected `name = colors`, got {:?}", line))?;
+                let colors = colors
+                    .split_whitespace()
+                    .map(|c| parse_hex_color(c).ok_or_else(|| anyhow!("invalid color {:?}", c)))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(ColorPalette {
+                    name: name.trim().to_string(),
+                    colors,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self { palettes })
+    }
+
+    /// Charge les palettes depuis le VFS, ou `builtin` si le fichier n'existe pas encore.
+    pub fn load(vfs: &Vfs) -> Result<Self> {
+        if !vfs.exists(Self::PATH) {
+            return Ok(Self::builtin());
+        }
+        let text = vfs.read_to_string(Self::PATH)?;
+        Self::decode(&text).with_context(|| format!("failed to parse {:?}", Self::PATH))
+    }
+
+    pub fn save(&self, vfs: &Vfs) -> Result<()> {
+        vfs.write_bytes(Self::PATH, self.encode().as_bytes())
+    }
+}
+
+/// Sélecteur de couleur partagé par tous les champs couleur d'une fenêtre.
+///
+/// `edit` affiche un champ (pastille + code hexadécimal) qui se déplie en éditeur complet :
+/// roue teinte/saturation, curseurs valeur et alpha, palettes et pipette.
+///
+/// La pipette ne lit pas l'écran elle-même : quand elle est active (`eyedropper_active`),
+/// l'hôte échantillonne l'image rendue au clic dans le viewport (ex:
+/// `RenderTarget::read_pixel`) et passe le résultat à `finish_eyedropper`.
+pub struct ColorPicker {
+    palettes: ColorPalettes,
+    selected_palette: usize,
+    new_palette_name: String,
+    vfs: Option<Arc<Vfs>>,
+    /// Champ en attente d'un échantillon de la pipette.
+    eyedropper: Option<egui::Id>,
+    /// Échantillon reçu, appliqué au champ au prochain `edit`.
+    sample: Option<(egui::Id, [f32; 4])>,
+}
+
+impl ColorPicker {
+    const WHEEL_RADIUS: f32 = 70.0;
+    const WHEEL_RINGS: usize = 8;
+    const WHEEL_SEGMENTS: usize = 48;
+    const SWATCH_SIZE: f32 = 16.0;
+
+    /// Sélecteur avec des palettes persistées dans `vfs` (chargées depuis `ColorPalettes::PATH`).
+    pub fn new(vfs: Arc<Vfs>) -> Self {
+        let palettes = ColorPalettes::load(&vfs).unwrap_or_else(|e| {
+            log::error!("Failed to load color palettes: {:#}", e);
+            ColorPalettes::builtin()
+        });
+        Self {
+            vfs: Some(vfs),
+            ..Self::with_palettes(palettes)
+        }
+    }
+
+    /// Sélecteur sans persistance.
+    pub fn with_palettes(palettes: ColorPalettes) -> Self {
+        Self {
+            palettes,
+            selected_palette: 0,
+            new_palette_name: String::new(),
+            vfs: None,
+            eyedropper: None,
+            sample: None,
+        }
+    }
+
+    pub fn palettes(&self) -> &ColorPalettes {
+        &self.palettes
+    }
+
+    /// Vrai si un champ attend un clic de la pipette dans le viewport.
+    pub fn eyedropper_active(&self) -> bool {
+        self.eyedropper.is_some()
+    }
+
+    /// Donne la couleur échantillonnée au champ qui a lancé la pipette.
+    pub fn finish_eyedropper(&mut self, color: [f32; 4]) {
+        if let Some(id) = self.eyedropper.take() {
+            self.sample = Some((id, color));
+        }
+    }
+
+    pub fn cancel_eyedropper(&mut self) {
+        self.eyedropper = None;
+    }
+
+    /// Champ couleur. Retourne `true` si `color` a été modifiée.
+    pub fn edit(
+        &mut self,
+        ui: &mut egui::Ui,
+        id_salt: impl std::hash::Hash,
+        color: &mut [f32; 4],
+    ) -> bool {
+        let id = ui.make_persistent_id(id_salt);
+        let mut changed = false;
+
+        if let Some((sample_id, sample)) = self.sample
+            && sample_id == id
+        {
+            self.sample = None;
+            // La pipette lit une image opaque : on garde l'alpha du champ
+            *color = [sample[0], sample[1], sample[2], color[3]];
+            changed = true;
+        }
+
+        let open_id = id.with("open");
+        let mut open = ui.data(|d| d.get_temp::<bool>(open_id)).unwrap_or(false);
+
+        ui.horizontal(|ui| {
+            let (rect, response) = ui.allocate_exact_size(
+                egui::vec2(Self::SWATCH_SIZE * 2.0, Self::SWATCH_SIZE),
+                egui::Sense::click(),
+            );
+            Self::paint_swatch(ui, rect, *color);
+            if response.clicked() {
+                open = !open;
+            }
+            changed |= Self::hex_field(ui, id, color);
+        });
+
+        if open {
+            ui.indent(id.with("editor"), |ui| {
+                changed |= self.editor(ui, id, color);
+            });
+        }
+
+        ui.data_mut(|d| d.insert_temp(open_id, open));
+        changed
+    }
+
+    fn paint_swatch(ui: &egui::Ui, rect: egui::Rect, color: [f32; 4]) {
+        let painter = ui.painter();
+        // Damier sous les couleurs transparentes
+        if color[3] < 1.0 {
+            let half = rect.height() / 2.0;
+            painter.rect_filled(rect, 2.0, egui::Color32::GRAY);
+            let mut x = rect.left();
+            let mut column = 0;
+            while x < rect.right() {
+                let y = rect.top() + if column % 2 == 0 { 0.0 } else { half };
+                let cell = egui::Rect::from_min_size(egui::pos2(x, y), egui::vec2(half, half))
+                    .intersect(rect);
+                painter.rect_filled(cell, 0.0, egui::Color32::LIGHT_GRAY);
+                x += half;
+                column += 1;
+            }
+        }
+        painter.rect_filled(rect, 2.0, to_color32(color));
+        painter.rect_stroke(
+            rect,
+            2.0,
+            ui.visuals().widgets.noninteractive.bg_stroke,
+            egui::StrokeKind::Inside,
+        );
+    }
+
+    /// Saisie hexadécimale, appliquée quand le champ perd le focus.
+    fn hex_field(ui: &mut egui::Ui, id: egui::Id, color: &mut [f32; 4]) -> bool {
+        let text_id = id.with("hex");
+        let mut text = ui
+            .data(|d| d.get_temp::<String>(text_id))
+            .unwrap_or_else(|| format_hex_color(*color));
+
+        let response = ui.add(egui::TextEdit::singleline(&mut text).desired_width(80.0));
+        let mut changed = false;
+        if response.lost_focus() {
+            if let Some(parsed) = parse_hex_color(&text) {
+                changed = parsed != *color;
+                *color = parsed;
+            }
+            ui.data_mut(|d| d.remove::<String>(text_id));
+        } else if response.has_focus() {
+            ui.data_mut(|d| d.insert_temp(text_id, text));
+        }
+        changed
+    }
+
+    fn editor(&mut self, ui: &mut egui::Ui, id: egui::Id, color: &mut [f32; 4]) -> bool {
+        let mut changed = false;
+
+        // HSV mémorisé : la teinte est perdue en RGB quand la saturation ou la valeur est nulle
+        let hsv_id = id.with("hsv");
+        let rgb = [color[0], color[1], color[2]];
+        let mut hsv = ui
+            .data(|d| d.get_temp::<[f32; 3]>(hsv_id))
+            .filter(|hsv| {
+                let stored = hsv_to_rgb(*hsv);
+                stored
+                    .iter()
+                    .zip(rgb)
+                    .all(|(a, b)| (a - b).abs() < 1.0 / 512.0)
+            })
+            .unwrap_or_else(|| rgb_to_hsv(rgb));
+
+        ui.horizontal(|ui| {
+            let mut hsv_changed = self.wheel(ui, id, &mut hsv);
+            ui.vertical(|ui| {
+                hsv_changed |= ui
+                    .add(egui::Slider::new(&mut hsv[2], 0.0..=1.0).text("V"))
+                    .changed();
+                changed |= ui
+                    .add(egui::Slider::new(&mut color[3], 0.0..=1.0).text("A"))
+                    .changed();
+
+                let picking = self.eyedropper == Some(id);
+                if ui.selectable_label(picking, "Eyedropper").clicked() {
+                    self.eyedropper = if picking { None } else { Some(id) };
+                }
+                if picking {
+                    ui.weak("Click in the viewport to sample");
+                }
+            });
+
+            if hsv_changed {
+                let [r, g, b] = hsv_to_rgb(hsv);
+                *color = [r, g, b, color[3]];
+                changed = true;
+            }
+        });
+        ui.data_mut(|d| d.insert_temp(hsv_id, hsv));
+
+        changed |= self.palette_ui(ui, id, color);
+        changed
+    }
+
+    /// Roue teinte (angle) / saturation (rayon), à la valeur courante.
+    fn wheel(&self, ui: &mut egui::Ui, id: egui::Id, hsv: &mut [f32; 3]) -> bool {
+        let size = egui::Vec2::splat(Self::WHEEL_RADIUS * 2.0);
+        let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+        let response = ui.interact(rect, id.with("wheel"), egui::Sense::click_and_drag());
+        let center = rect.center();
+
+        let mut mesh = egui::Mesh::default();
+        let vertex_color = |h: f32, s: f32| {
+            let [r, g, b] = hsv_to_rgb([h, s, hsv[2]]);
+            to_color32([r, g, b, 1.0])
+        };
+        let point = |h: f32, s: f32| {
+            let angle = h * std::f32::consts::TAU;
+            center + egui::vec2(angle.cos(), -angle.sin()) * s * Self::WHEEL_RADIUS
+        };
+        for ring in 0..Self::WHEEL_RINGS {
+            let s0 = ring as f32 / Self::WHEEL_RINGS as f32;
+            let s1 = (ring + 1) as f32 / Self::WHEEL_RINGS as f32;
+            for segment in 0..Self::WHEEL_SEGMENTS {
+                let h0 = segment as f32 / Self::WHEEL_SEGMENTS as f32;
+                let h1 = (segment + 1) as f32 / Self::WHEEL_SEGMENTS as f32;
+                let base = mesh.vertices.len() as u32;
+                for (h, s) in [(h0, s0), (h1, s0), (h1, s1), (h0, s1)] {
+                    mesh.colored_vertex(point(h, s), vertex_color(h, s));
+                }
+                mesh.add_triangle(base, base + 1, base + 2);
+                mesh.add_triangle(base, base + 2, base + 3);
+            }
+        }
+        ui.painter().add(egui::Shape::mesh(mesh));
+
+        let mut changed = false;
+        if let Some(pos) = response.interact_pointer_pos()
+            && (response.clicked() || response.dragged())
+        {
+            let offset = pos - center;
+            hsv[0] = (-offset.y)
+                .atan2(offset.x)
+                .rem_euclid(std::f32::consts::TAU)
+                / std::f32::consts::TAU;
+            hsv[1] = (offset.length() / Self::WHEEL_RADIUS).min(1.0);
+            changed = true;
+        }
+
+        let marker = point(hsv[0], hsv[1]);
+        let stroke_color = if hsv[2] > 0.5 {
+            egui::Color32::BLACK
+        } else {
+            egui::Color32::WHITE
+        };
+        ui.painter()
+            .circle_stroke(marker, 4.0, egui::Stroke::new(1.5, stroke_color));
+        changed
+    }
+
+    fn palette_ui(&mut self, ui: &mut egui::Ui, id: egui::Id, color: &mut [f32; 4]) -> bool {
+        let mut changed = false;
+        let mut palettes_changed = false;
+
+        ui.horizontal(|ui| {
+            let selected_name = self
+                .palettes
+                .palettes
+                .get(self.selected_palette)
+                .map_or("No palette", |p| p.name.as_str())
+                .to_string();
+            egui::ComboBox::from_id_salt(id.with("palette"))
+                .selected_text(selected_name)
+                .show_ui(ui, |ui| {
+                    for (index, palette) in self.palettes.palettes.iter().enumerate() {
+                        ui.selectable_value(&mut self.selected_palette, index, &palette.name);
+                    }
+                });
+
+            ui.add(
+                egui::TextEdit::singleline(&mut self.new_palette_name)
+                    .hint_text("New palette")
+                    .desired_width(90.0),
+            );
+            let name = self.new_palette_name.trim();
+            if ui
+                .add_enabled(
+                    !name.is_empty() && !name.contains('='),
+                    egui::Button::new("+"),
+                )
+                .clicked()
+            {
+                self.palettes.palettes.push(ColorPalette {
+                    name: name.to_string(),
+                    colors: Vec::new(),
+                });
+                self.selected_palette = self.palettes.palettes.len() - 1;
+                self.new_palette_name.clear();
+                palettes_changed = true;
+            }
+        });
+
+        if let Some(palette) = self.palettes.palettes.get_mut(self.selected_palette) {
+            ui.horizontal_wrapped(|ui| {
+                let mut remove = None;
+                for (index, swatch) in palette.colors.iter().enumerate() {
+                    let (rect, response) = ui.allocate_exact_size(
+                        egui::Vec2::splat(Self::SWATCH_SIZE),
+                        egui::Sense::click(),
+                    );
+                    Self::paint_swatch(ui, rect, *swatch);
+                    if response.clicked() {
+                        *color = *swatch;
+                        changed = true;
+                    }
+                    if response.secondary_clicked() {
+                        remove = Some(index);
+                    }
+                    response.on_hover_text(format_hex_color(*swatch));
+                }
+                if let Some(index) = remove {
+                    palette.colors.remove(index);
+                    palettes_changed = true;
+                }
+                if ui
+                    .small_button("+")
+                    .on_hover_text("Add the current color")
+                    .clicked()
+                {
+                    palette.colors.push(*color);
+                    palettes_changed = true;
+                }
+            });
+            ui.weak("Click: apply, right-click: remove");
+        }
+
+        if palettes_changed
+            && let Some(vfs) = &self.vfs
+            && let Err(e) = self.palettes.save(vfs)
+        {
+            log::error!("Failed to save color palettes: {:#}", e);
+        }
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hsv_roundtrip() {
+        for rgb in [
+            [1.0, 0.0, 0.0],
+            [0.2, 0.6, 0.4],
+            [0.5, 0.5, 0.5],
+            [0.1, 0.2, 0.9],
+        ] {
+            let back = hsv_to_rgb(rgb_to_hsv(rgb));
+            for (a, b) in rgb.iter().zip(back) {
+                assert!((a - b).abs() < 1e-5, "{:?} -> {:?}", rgb, back);
+            }
+        }
+        assert_eq!(rgb_to_hsv([0.0, 1.0, 0.0]), [1.0 / 3.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn hex_and_palettes_roundtrip() {
+        assert_eq!(
+            parse_hex_color("#FF000080"),
+            Some([1.0, 0.0, 0.0, 128.0 / 255.0])
+        );
+        assert_eq!(parse_hex_color("00ff00"), Some([0.0, 1.0, 0.0, 1.0]));
+        assert_eq!(parse_hex_color("#12345"), None);
+        assert_eq!(format_hex_color([1.0, 0.5, 0.0, 1.0]), "#FF8000");
+
+        let palettes = ColorPalettes::builtin();
+        assert_eq!(ColorPalettes::decode(&palettes.encode()).unwrap(), palettes);
+        assert!(ColorPalettes::decode("no separator").is_err());
+    }
+}
@@ -0,0 +1,264 @@
+//! Palette de recherche de l'éditeur (Ctrl+P) : entités, assets et commandes, filtrés par
+//! recherche floue.
+//!
+//! La palette ne connaît pas l'éditeur : l'appelant fournit les entrées (`PaletteEntry`) et
+//! traite la cible choisie (`PaletteTarget`), par exemple en centrant la caméra sur l'entité.
+
+use hecs::Entity;
+
+/// Score de `text` pour la recherche `query` : les caractères de `query` doivent apparaître
+/// dans l'ordre (sans tenir compte de la casse). Les suites de caractères consécutifs et les
+/// débuts de mots (après `_`, `/`, espace, majuscule...) comptent plus. `None` si `text` ne
+/// correspond pas ; une recherche vide correspond à tout avec un score de 0.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let text: Vec<char> = text.chars().collect();
+    let query: Vec<char> = query.chars().filter(|c| !c.is_whitespace()).collect();
+    let Some((&first, rest)) = query.split_first() else {
+        return Some(0);
+    };
+
+    let matches = |wanted: char, i: usize| text[i].to_lowercase().eq(wanted.to_lowercase());
+    let bonus = |i: usize| {
+        let word_start = i == 0
+            || !text[i - 1].is_alphanumeric()
+            || (text[i].is_uppercase() && text[i - 1].is_lowercase());
+        1 + if word_start { 8 } else { 0 }
+    };
+
+    // best[j] : meilleur score avec le caractère courant de la recherche placé en j. Toutes
+    // les positions sont essayées (pas seulement la première occurrence), pour trouver
+    // les débuts de mots plus loin dans le texte.
+    let mut best: Vec<Option<i32>> = (0..text.len())
+        .map(|j| matches(first, j).then(|| bonus(j) - j.min(10) as i32))
+        .collect();
+    for &wanted in rest {
+        let mut next = vec![None; text.len()];
+        // Meilleur score d'une position antérieure non adjacente (caractères sautés)
+        let mut gap: Option<i32> = None;
+        for j in 1..text.len() {
+            if j >= 2 {
+                gap = gap.max(best[j - 2]);
+            }
+            if !matches(wanted, j) {
+                continue;
+            }
+            let consecutive = best[j - 1].map(|score| score + 5);
+            let skipped = gap.map(|score| score - 1);
+            next[j] = consecutive.max(skipped).map(|score| score + bonus(j));
+        }
+        best = next;
+    }
+    best.into_iter().flatten().max()
+}
+
+/// Ce que désigne une entrée de la palette.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PaletteTarget {
+    Entity(Entity),
+    /// Chemin VFS.
+    Asset(String),
+    /// Identifiant de commande, interprété par l'appelant.
+    Command(String),
+}
+
+impl PaletteTarget {
+    fn kind(&self) -> &'static str {
+        match self {
+            PaletteTarget::Entity(_) => "entity",
+            PaletteTarget::Asset(_) => "asset",
+            PaletteTarget::Command(_) => "command",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaletteEntry {
+    /// Texte recherché et affiché.
+    pub label: String,
+    /// Texte secondaire affiché en grisé (chemin, raccourci...).
+    pub detail: String,
+    pub target: PaletteTarget,
+}
+
+impl PaletteEntry {
+    pub fn new(label: impl Into<String>, target: PaletteTarget) -> Self {
+        Self {
+            label: label.into(),
+            detail: String::new(),
+            target,
+        }
+    }
+
+    pub fn detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = detail.into();
+        self
+    }
+}
+
+/// Fenêtre de recherche ouverte par `SHORTCUT`, fermée par Échap ou après un choix.
+#[derive(Debug, Default)]
+pub struct CommandPalette {
+    open: bool,
+    query: String,
+    /// Index dans les résultats filtrés.
+    selected: usize,
+}
+
+impl CommandPalette {
+    pub const SHORTCUT: egui::KeyboardShortcut =
+        egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::P);
+
+    /// Nombre maximal de résultats affichés.
+    pub const MAX_RESULTS: usize = 12;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn open(&mut self) {
+        self.open = true;
+        self.query.clear();
+        self.selected = 0;
+    }
+
+    pub fn close(&mut self) {
+        self.open = false;
+    }
+
+    /// Ouvre / ferme la palette si `SHORTCUT` a été pressé. À appeler à chaque frame, avant
+    /// de construire les entrées (inutiles tant que la palette est fermée).
+    pub fn handle_shortcut(&mut self, ctx: &egui::Context) {
+        if ctx.input_mut(|i| i.consume_shortcut(&Self::SHORTCUT)) {
+            if self.open {
+                self.close();
+            } else {
+                self.open();
+            }
+        }
+    }
+
+    /// Indices des entrées correspondant à `query`, du meilleur score au moins bon (à score
+    /// égal, l'ordre de `entries` est conservé).
+    pub fn filter(query: &str, entries: &[PaletteEntry]) -> Vec<usize> {
+        let mut matches: Vec<(i32, usize)> = entries
+            .iter()
+            .enumerate()
+            .filter_map(|(index, entry)| Some((fuzzy_score(query, &entry.label)?, index)))
+            .collect();
+        matches.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+        matches.into_iter().map(|(_, index)| index).collect()
+    }
+
+    /// Affiche la palette si elle est ouverte. Retourne la cible choisie (Entrée ou clic).
+    pub fn ui(&mut self, ctx: &egui::Context, entries: &[PaletteEntry]) -> Option<PaletteTarget> {
+        if !self.open {
+            return None;
+        }
+        let results = Self::filter(&self.query, entries);
+        let shown = results.len().min(Self::MAX_RESULTS);
+
+        let (up, down, enter, escape) = ctx.input_mut(|i| {
+            (
+                i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::Enter),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::Escape),
+            )
+        });
+        if escape {
+            self.close();
+            return None;
+        }
+        if down && shown > 0 {
+            self.selected = (self.selected + 1) % shown;
+        }
+        if up && shown > 0 {
+            self.selected = (self.selected + shown - 1) % shown;
+        }
+        self.selected = self.selected.min(shown.saturating_sub(1));
+
+        let mut chosen = enter.then(|| results.get(self.selected)).flatten().copied();
+        egui::Area::new(egui::Id::new("command_palette"))
+            .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 48.0))
+            .order(egui::Order::Foreground)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.set_width(420.0);
+                    let response = ui.add(
+                        egui::TextEdit::singleline(&mut self.query)
+                            .hint_text("Search entities, assets and commands")
+                            .desired_width(f32::INFINITY),
+                    );
+                    response.request_focus();
+                    if response.changed() {
+                        self.selected = 0;
+                    }
+                    ui.separator();
+
+                    if results.is_empty() {
+                        ui.label(egui::RichText::new("No match").weak());
+                    }
+                    for (row, &index) in results.iter().take(shown).enumerate() {
+                        let entry = &entries[index];
+                        let clicked = ui
+                            .horizontal(|ui| {
+                                let clicked = ui
+                                    .selectable_label(row == self.selected, &entry.label)
+                                    .clicked();
+                                ui.label(egui::RichText::new(entry.target.kind()).small().weak());
+                                if !entry.detail.is_empty() {
+                                    ui.label(egui::RichText::new(&entry.detail).weak());
+                                }
+                                clicked
+                            })
+                            .inner;
+                        if clicked {
+                            chosen = Some(index);
+                        }
+                    }
+                    if results.len() > shown {
+                        ui.label(
+                            egui::RichText::new(format!("{} more...", results.len() - shown))
+                                .weak(),
+                        );
+                    }
+                });
+            });
+
+        let target = chosen.map(|index| entries[index].target.clone());
+        if target.is_some() {
+            self.close();
+        }
+        target
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzzy_matching_prefers_word_starts() {
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+        assert!(fuzzy_score("xyz", "player").is_none());
+        assert!(fuzzy_score("yalp", "player").is_none());
+        assert!(fuzzy_score("PLR", "player").is_some());
+
+        // "pl" : début de mot et consécutif dans "Player", dispersé dans "sample_list"
+        assert!(fuzzy_score("pl", "Player") > fuzzy_score("pl", "sample_list"));
+        // Le "t" de "tool" (début de mot) est préféré au premier "t" de "sprite"
+        assert!(fuzzy_score("st", "sprite_tool") > fuzzy_score("st", "sprite"));
+
+        let entries = [
+            PaletteEntry::new("sample_list", PaletteTarget::Command("a".into())),
+            PaletteEntry::new("Player", PaletteTarget::Asset("b".into())),
+            PaletteEntry::new("Enemy", PaletteTarget::Command("c".into())),
+        ];
+        assert_eq!(CommandPalette::filter("pl", &entries), [1, 0]);
+        assert_eq!(CommandPalette::filter("", &entries), [0, 1, 2]);
+    }
+}
@@ -53,6 +53,12 @@ impl Camera2D {
         }
     }
 
+    /// Place la caméra pour que `world` soit au centre du viewport.
+    pub fn center_on(&mut self, world: Vec2) {
+        let half = Vec2::new(self.viewport_width, self.viewport_height) / (2.0 * self.zoom);
+        self.position = world - half;
+    }
+
     /// Ajuster le zoom
     pub fn set_zoom(&mut self, zoom: f32) {
         self.zoom = zoom.max(0.1); // Éviter les zooms négatifs ou nuls
@@ -164,6 +170,14 @@ impl Camera2D {
         )
     }
 
+    /// Rectangle du monde visible à l'écran : (coin haut-gauche, coin bas-droit).
+    pub fn visible_rect(&self) -> (Vec2, Vec2) {
+        (
+            self.screen_to_world(0.0, 0.0),
+            self.screen_to_world(self.viewport_width, self.viewport_height),
+        )
+    }
+
     /// Convertir une position monde en position écran (pixels)
     pub fn world_to_screen(&self, world_x: f32, world_y: f32) -> Vec2 {
         Vec2::new(
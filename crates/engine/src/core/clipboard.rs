@@ -0,0 +1,522 @@
+//! Copie profonde d'entités (composants, enfants, références d'assets) et presse-papiers de
+//! l'éditeur : copier / coller / dupliquer, dans la même scène, entre scènes ou entre deux
+//! instances de l'éditeur (via le presse-papiers système, au format texte ci-dessous).
+//!
+//! ```text
+//! gena-entities 1
+//! entity
+//! name = Player
+//! position = 10 20 0
+//! sprite = sprites/player.png
+//! sprite_layer = 10
+//! entity
+//! name = Torch
+//! point_light = 1 0.8 0.5 1 120 40
+//! end
+//! end
+//! ```
+//!
+//! Les textures sont référencées par leur chemin VFS (`Texture2D::path`) : un sprite dont la
+//! texture n'a pas été chargée via l'`AssetLoader` n'est copié qu'en mémoire, comme les
+//! tilemaps et les spawners (non sérialisés).
+
+use std::sync::Arc;
+
+use anyhow::{Context, Result, anyhow, bail};
+use hecs::{Entity, EntityBuilder};
+
+use crate::{
+    BlendMode, Name, NineSlice, PointLight, Scene, Spawner, SpotLight, Sprite, SpriteComponent,
+    Texture2D, Tilemap, Transform, Vec3,
+};
+
+/// Copie d'une entité et de ses descendants, détachée de toute scène.
+/// `Parent` / `Children` / `GlobalTransform` ne sont pas copiés : ils sont recréés par
+/// `Scene::instantiate`.
+#[derive(Clone, Default)]
+pub struct EntitySnapshot {
+    pub name: Option<Name>,
+    pub transform: Option<Transform>,
+    pub sprite: Option<SpriteComponent>,
+    pub point_light: Option<PointLight>,
+    pub spot_light: Option<SpotLight>,
+    pub tilemap: Option<Tilemap>,
+    pub spawner: Option<Spawner>,
+    pub children: Vec<EntitySnapshot>,
+}
+
+impl EntitySnapshot {
+    /// Première ligne du format texte.
+    pub const HEADER: &str = "gena-entities 1";
+
+    /// `true` si `text` ressemble à des entités copiées (et pas à du texte quelconque).
+    pub fn is_entities_text(text: &str) -> bool {
+        text.trim_start().starts_with(Self::HEADER)
+    }
+
+    pub fn encode(entities: &[EntitySnapshot]) -> String {
+        let mut text = format!("{}\n", Self::HEADER);
+        for entity in entities {
+            entity.encode_into(&mut text);
+        }
+        text
+    }
+
+    fn encode_into(&self, text: &mut String) {
+        let vec3 = |v: &Vec3| format!("{} {} {}", v.x, v.y, v.z);
+        let floats = |values: &[f32]| {
+            values
+                .iter()
+                .map(f32::to_string)
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+
+        text.push_str("entity\n");
+        if let Some(name) = &self.name {
+            *text += &format!("name = {}\n", name.as_str());
+        }
+        if let Some(transform) = &self.transform {
+            *text += &format!("position = {}\n", vec3(&transform.position));
+            *text += &format!("rotation = {}\n", vec3(&transform.rotation));
+            *text += &format!("scale = {}\n", vec3(&transform.scale));
+        }
+        if let Some(component) = &self.sprite {
+            let sprite = &component.sprite;
+            match &sprite.texture.path {
+                Some(path) => {
+                    *text += &format!("sprite = {}\n", path);
+                    *text += &format!("sprite_visible = {}\n", component.visible);
+                    *text += &format!("sprite_uv = {}\n", floats(&sprite.uv));
+                    if let Some((width, height)) = sprite.size {
+                        *text += &format!("sprite_size = {} {}\n", width, height);
+                    }
+                    *text += &format!("sprite_layer = {}\n", sprite.layer);
+                    *text += &format!("sprite_y_sort = {}\n", sprite.y_sort);
+                    *text += &format!("sprite_tint = {}\n", floats(&sprite.tint));
+                    if let Some(corners) = &sprite.corner_colors {
+                        *text += &format!(
+                            "sprite_corner_colors = {}\n",
+                            floats(corners.as_flattened())
+                        );
+                    }
+                    *text += &format!("sprite_blend = {}\n", sprite.blend.name());
+                    *text += &format!("sprite_flip = {} {}\n", sprite.flip_x, sprite.flip_y);
+                    if let Some(nine_slice) = &sprite.nine_slice {
+                        let (width, height) = nine_slice.size;
+                        *text += &format!(
+                            "sprite_nine_slice = {} {} {}\n",
+                            floats(&nine_slice.border),
+                            width,
+                            height
+                        );
+                    }
+                    if let Some(path) = sprite.normal_map.as_ref().and_then(|t| t.path.as_ref()) {
+                        *text += &format!("sprite_normal_map = {}\n", path);
+                    }
+                }
+                None => log::warn!("Sprite texture has no asset path, not copied as text"),
+            }
+        }
+        if let Some(light) = &self.point_light {
+            *text += &format!(
+                "point_light = {} {} {} {}\n",
+                floats(&light.color),
+                light.intensity,
+                light.radius,
+                light.height
+            );
+        }
+        if let Some(light) = &self.spot_light {
+            *text += &format!(
+                "spot_light = {} {} {} {} {} {} {}\n",
+                floats(&light.color),
+                light.intensity,
+                light.radius,
+                light.height,
+                light.angle,
+                light.inner_angle,
+                light.outer_angle
+            );
+        }
+        if self.tilemap.is_some() {
+            log::warn!("Tilemaps are only copied within the editor, not as text");
+        }
+        for child in &self.children {
+            child.encode_into(text);
+        }
+        text.push_str("end\n");
+    }
+
+    /// Relit des entités écrites par `encode`. `load_texture(path, linear)` fournit les
+    /// textures référencées (`linear` pour les normal maps), typiquement via un cache autour
+    /// de `AssetLoader::load_texture`.
+    pub fn parse(
+        text: &str,
+        mut load_texture: impl FnMut(&str, bool) -> Result<Arc<Texture2D>>,
+    ) -> Result<Vec<EntitySnapshot>> {
+        let mut lines = text.lines().enumerate();
+        match lines.next() {
+            Some((_, header)) if header.trim() == Self::HEADER => {}
+            _ => bail!("missing `{}` header", Self::HEADER),
+        }
+
+        let mut roots = Vec::new();
+        let mut stack: Vec<EntitySnapshot> = Vec::new();
+        for (number, line) in lines {
+            let line = line.trim();
+            match line {
+                "" => continue,
+                "entity" => {
+                    stack.push(EntitySnapshot::default());
+                    continue;
+                }
+                "end" => {
+                    let entity = stack
+                        .pop()
+                        .ok_or_else(|| anyhow!("line {}: unmatched `end`", number + 1))?;
+                    match stack.last_mut() {
+                        Some(parent) => parent.children.push(entity),
+                        None => roots.push(entity),
+                    }
+                    continue;
+                }
+                _ => {}
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| anyhow!("line {}: expected `key = value`", number + 1))?;
+            let (key, value) = (key.trim(), value.trim());
+            let entity = stack
+                .last_mut()
+                .ok_or_else(|| anyhow!("line {}: {:?} outside of an entity", number + 1, key))?;
+            entity
+                .parse_entry(key, value, &mut load_texture)
+                .with_context(|| format!("line {}: invalid {:?} entry", number + 1, key))?;
+        }
+
+        if !stack.is_empty() {
+            bail!("unterminated entity (missing `end`)");
+        }
+        Ok(roots)
+    }
+
+    fn parse_entry(
+        &mut self,
+        key: &str,
+        value: &str,
+        load_texture: &mut impl FnMut(&str, bool) -> Result<Arc<Texture2D>>,
+    ) -> Result<()> {
+        let transform = || self.transform.unwrap_or_default();
+        match key {
+            "name" => self.name = Some(Name::new(value)),
+            "position" => {
+                let [x, y, z] = floats(value)?;
+                self.transform = Some(Transform {
+                    position: Vec3::new(x, y, z),
+                    ..transform()
+                });
+            }
+            "rotation" => {
+                let [x, y, z] = floats(value)?;
+                self.transform = Some(Transform {
+                    rotation: Vec3::new(x, y, z),
+                    ..transform()
+                });
+            }
+            "scale" => {
+                let [x, y, z] = floats(value)?;
+                self.transform = Some(Transform {
+                    scale: Vec3::new(x, y, z),
+                    ..transform()
+                });
+            }
+            "sprite" => {
+                let texture = load_texture(value, false)?;
+                self.sprite = Some(SpriteComponent::new(Sprite::from_texture(texture)));
+            }
+            "point_light" => {
+                let [r, g, b, intensity, radius, height] = floats(value)?;
+                self.point_light = Some(PointLight {
+                    color: [r, g, b],
+                    intensity,
+                    radius,
+                    height,
+                });
+            }
+            "spot_light" => {
+                let [
+                    r,
+                    g,
+                    b,
+                    intensity,
+                    radius,
+                    height,
+                    angle,
+                    inner_angle,
+                    outer_angle,
+                ] = floats(value)?;
+                self.spot_light = Some(SpotLight {
+                    color: [r, g, b],
+                    intensity,
+                    radius,
+                    height,
+                    angle,
+                    inner_angle,
+                    outer_angle,
+                });
+            }
+            _ => {
+                let Some(property) = key.strip_prefix("sprite_") else {
+                    log::warn!("Unknown entity entry {:?}", key);
+                    return Ok(());
+                };
+                let component = self
+                    .sprite
+                    .as_mut()
+                    .ok_or_else(|| anyhow!("expected after the `sprite` entry"))?;
+                let sprite = &mut component.sprite;
+                match property {
+                    "visible" => component.visible = value.parse()?,
+                    "uv" => sprite.uv = floats(value)?,
+                    "size" => {
+                        let [width, height] = floats(value)?;
+                        sprite.size = Some((width, height));
+                    }
+                    "layer" => sprite.layer = value.parse()?,
+                    "y_sort" => sprite.y_sort = value.parse()?,
+                    "tint" => sprite.tint = floats(value)?,
+                    "corner_colors" => {
+                        let colors: [f32; 16] = floats(value)?;
+                        sprite.corner_colors =
+                            Some([0, 1, 2, 3].map(|i| [0, 1, 2, 3].map(|c| colors[i * 4 + c])));
+                    }
+                    "blend" => {
+                        sprite.blend = BlendMode::from_name(value)
+                            .ok_or_else(|| anyhow!("unknown blend mode {:?}", value))?
+                    }
+                    "flip" => match value.split_whitespace().collect::<Vec<_>>()[..] {
+                        [x, y] => (sprite.flip_x, sprite.flip_y) = (x.parse()?, y.parse()?),
+                        _ => bail!("expected two booleans"),
+                    },
+                    "nine_slice" => {
+                        let [left, top, right, bottom, width, height] = floats(value)?;
+                        sprite.nine_slice =
+                            Some(NineSlice::new([left, top, right, bottom], (width, height)));
+                    }
+                    "normal_map" => sprite.normal_map = Some(load_texture(value, true)?),
+                    _ => log::warn!("Unknown entity entry {:?}", key),
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `N` nombres séparés par des espaces.
+fn floats<const N: usize>(value: &str) -> Result<[f32; N]> {
+    let values = value
+        .split_whitespace()
+        .map(str::parse)
+        .collect::<Result<Vec<f32>, _>>()?;
+    values
+        .try_into()
+        .map_err(|values: Vec<f32>| anyhow!("expected {} numbers, got {}", N, values.len()))
+}
+
+impl Scene {
+    /// Copie profonde de `entity` et de ses descendants (`None` si elle n'existe pas).
+    pub fn snapshot(&self, entity: Entity) -> Option<EntitySnapshot> {
+        if !self.world.contains(entity) {
+            return None;
+        }
+        let world = &self.world;
+        Some(EntitySnapshot {
+            name: world.get::<&Name>(entity).ok().map(|name| (*name).clone()),
+            transform: world.get::<&Transform>(entity).ok().map(|t| *t),
+            sprite: world
+                .get::<&SpriteComponent>(entity)
+                .ok()
+                .map(|sprite| (*sprite).clone()),
+            point_light: world.get::<&PointLight>(entity).ok().map(|l| *l),
+            spot_light: world.get::<&SpotLight>(entity).ok().map(|l| *l),
+            tilemap: world.get::<&Tilemap>(entity).ok().map(|map| (*map).clone()),
+            // La copie repart de zéro (première vague, aucune entité créée)
+            spawner: world.get::<&Spawner>(entity).ok().map(|spawner| {
+                let mut spawner = (*spawner).clone();
+                spawner.reset();
+                spawner
+            }),
+            children: self
+                .children_of(entity)
+                .into_iter()
+                .filter_map(|child| self.snapshot(child))
+                .collect(),
+        })
+    }
+
+    /// Crée une nouvelle entité (et ses descendants) à partir de `snapshot`, attachée sous
+    /// `parent` si fourni.
+    pub fn instantiate(&mut self, snapshot: &EntitySnapshot, parent: Option<Entity>) -> Entity {
+        let mut builder = EntityBuilder::new();
+        if let Some(name) = &snapshot.name {
+            builder.add(name.clone());
+        }
+        if let Some(transform) = snapshot.transform {
+            builder.add(transform);
+        }
+        if let Some(sprite) = &snapshot.sprite {
+            builder.add(sprite.clone());
+        }
+        if let Some(light) = snapshot.point_light {
+            builder.add(light);
+        }
+        if let Some(light) = snapshot.spot_light {
+            builder.add(light);
+        }
+        if let Some(map) = &snapshot.tilemap {
+            builder.add(map.clone());
+        }
+        if let Some(spawner) = &snapshot.spawner {
+            builder.add(spawner.clone());
+        }
+        let entity = self.world.spawn(builder.build());
+
+        if let Some(parent) = parent {
+            self.set_parent(entity, parent);
+        }
+        for child in &snapshot.children {
+            self.instantiate(child, Some(entity));
+        }
+        entity
+    }
+
+    /// Duplique `entity` et ses descendants, sous le même parent.
+    pub fn duplicate(&mut self, entity: Entity) -> Option<Entity> {
+        let snapshot = self.snapshot(entity)?;
+        let parent = self.parent_of(entity);
+        Some(self.instantiate(&snapshot, parent))
+    }
+}
+
+/// Presse-papiers d'entités de l'éditeur. La dernière copie est gardée en mémoire (textures
+/// partagées, tilemaps comprises) ; le texte retourné par `copy` sert pour le presse-papiers
+/// système, et un texte différent (autre instance de l'éditeur) est relu par `paste`.
+#[derive(Default)]
+pub struct EntityClipboard {
+    entities: Vec<EntitySnapshot>,
+    text: String,
+}
+
+impl EntityClipboard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    /// Texte de la dernière copie (`paste` le reconnaît et utilise la copie en mémoire).
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Copie `entities` (un descendant d'une entité déjà copiée est ignoré, il est copié
+    /// avec elle). Retourne le texte à placer dans le presse-papiers système.
+    pub fn copy(&mut self, scene: &Scene, entities: &[Entity]) -> String {
+        self.entities = entities
+            .iter()
+            .filter(|&&entity| {
+                !entities
+                    .iter()
+                    .any(|&other| scene.is_ancestor_of(other, entity))
+            })
+            .filter_map(|&entity| scene.snapshot(entity))
+            .collect();
+        self.text = EntitySnapshot::encode(&self.entities);
+        self.text.clone()
+    }
+
+    /// Colle les entités à la racine de `scene` et retourne les nouvelles racines.
+    /// Sans `text`, ou avec le texte de la dernière copie, la copie en mémoire est utilisée ;
+    /// sinon `text` est relu (voir `EntitySnapshot::parse`).
+    pub fn paste(
+        &self,
+        scene: &mut Scene,
+        text: Option<&str>,
+        load_texture: impl FnMut(&str, bool) -> Result<Arc<Texture2D>>,
+    ) -> Result<Vec<Entity>> {
+        let parsed;
+        let entities = match text {
+            Some(text) if text != self.text => {
+                parsed = EntitySnapshot::parse(text, load_texture)?;
+                &parsed
+            }
+            _ => &self.entities,
+        };
+        Ok(entities
+            .iter()
+            .map(|entity| scene.instantiate(entity, None))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entities_text_roundtrip() {
+        let torch = EntitySnapshot {
+            name: Some(Name::new("Torch")),
+            point_light: Some(PointLight::new([1.0, 0.8, 0.5], 120.0)),
+            ..Default::default()
+        };
+        let player = EntitySnapshot {
+            name: Some(Name::new("Player one")),
+            transform: Some(Transform {
+                position: Vec3::new(10.0, -20.5, 0.0),
+                ..Default::default()
+            }),
+            spot_light: Some(SpotLight::new([0.5; 3], 300.0, 1.5)),
+            children: vec![torch.clone(), torch],
+            ..Default::default()
+        };
+        let text = EntitySnapshot::encode(&[player, EntitySnapshot::default()]);
+        assert!(EntitySnapshot::is_entities_text(&text));
+
+        let parsed = EntitySnapshot::parse(&text, |path, _| bail!("unexpected {}", path)).unwrap();
+        assert_eq!(parsed.len(), 2);
+        let player = &parsed[0];
+        assert_eq!(player.name, Some(Name::new("Player one")));
+        assert_eq!(
+            player.transform.map(|t| t.position),
+            Some(Vec3::new(10.0, -20.5, 0.0))
+        );
+        assert_eq!(player.spot_light.unwrap().angle, 1.5);
+        assert_eq!(player.children.len(), 2);
+        assert_eq!(
+            player.children[1].point_light,
+            Some(PointLight::new([1.0, 0.8, 0.5], 120.0))
+        );
+        assert!(parsed[1].name.is_none() && parsed[1].children.is_empty());
+    }
+
+    #[test]
+    fn rejects_malformed_text() {
+        let load = |_: &str, _: bool| -> Result<Arc<Texture2D>> { bail!("no textures") };
+        assert!(EntitySnapshot::parse("hello", load).is_err());
+        let header = EntitySnapshot::HEADER;
+        assert!(EntitySnapshot::parse(&format!("{header}\nentity\n"), load).is_err());
+        assert!(EntitySnapshot::parse(&format!("{header}\nend\n"), load).is_err());
+        assert!(
+            EntitySnapshot::parse(&format!("{header}\nentity\nsprite_layer = 2\nend"), load)
+                .is_err()
+        );
+        assert!(
+            EntitySnapshot::parse(&format!("{header}\nentity\nsprite = a.png\nend"), load).is_err()
+        );
+    }
+}
@@ -0,0 +1,76 @@
+use hecs::Entity;
+
+use crate::Sprite;
+
+/// Nom lisible d'une entité (debug, éditeur, recherche).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Name(pub String);
+
+impl Name {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self(name.into())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Composant de rendu : l'entité est dessinée par `SpritePass` avec son `Transform`.
+#[derive(Clone)]
+pub struct SpriteComponent {
+    pub sprite: Sprite,
+    pub visible: bool,
+}
+
+impl SpriteComponent {
+    pub fn new(sprite: Sprite) -> Self {
+        Self {
+            sprite,
+            visible: true,
+        }
+    }
+}
+
+impl From<Sprite> for SpriteComponent {
+    fn from(sprite: Sprite) -> Self {
+        Self::new(sprite)
+    }
+}
+
+/// L'entité est dessinée dans le repère de la fenêtre (HUD : barres de vie, icônes) par la
+/// `SpritePass` créée avec `SpritePass::hud`, sans la caméra de la scène. Les autres passes
+/// de sprites, l'éclairage et les contours l'ignorent.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScreenSpace;
+
+/// Élément de HUD (`ScreenSpace`) propre au joueur local d'indice `0` en écran partagé
+/// (voir `LocalPlayers`) : il n'est dessiné que dans la zone de ce joueur. Sans ce
+/// composant, un élément de HUD est dessiné dans la zone de chaque joueur.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PlayerHud(pub usize);
+
+/// Parent d'une entité dans la hiérarchie de la scène.
+/// Géré par `Scene::set_parent` / `Scene::remove_parent` (garde `Children` synchronisé).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Parent(pub Entity);
+
+/// Enfants directs d'une entité, dans l'ordre d'ajout.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Children(pub Vec<Entity>);
+
+/// Surbrillance : la `OutlinePass` dessine un contour de `color` autour du sprite de
+/// l'entité (sélection de l'éditeur, objet avec lequel le joueur peut interagir...).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Highlight {
+    pub color: [f32; 4],
+}
+
+impl Highlight {
+    /// Contour des entités sélectionnées dans l'éditeur.
+    pub const SELECTION: Highlight = Highlight::new([1.0, 0.6, 0.1, 1.0]);
+
+    pub const fn new(color: [f32; 4]) -> Self {
+        Self { color }
+    }
+}
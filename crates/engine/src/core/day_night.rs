@@ -0,0 +1,140 @@
+//! Cycle jour/nuit : l'heure avance avec le temps de jeu et une courbe par canal donne la
+//! teinte globale (lumière ambiante) de chaque heure.
+//!
+//! Posé sur une scène (`Scene::day_night`), le cycle est avancé par `Scene::update` et écrit
+//! l'heure et la teinte dans `Scene::environment` : la `LightingPass` l'utilise comme
+//! lumière ambiante, les shaders de post-process et de météo reçoivent l'heure. Pour la
+//! logique de jeu, `publish` l'écrit dans le `Blackboard`.
+//!
+//! ```ignore
+//! scene.day_night = Some(DayNightCycle::new(600.0).with_time(6.0));
+//! ```
+
+use crate::{Blackboard, Curve, CurveKey, Environment};
+
+/// Heure du jour (0..24) et teinte qui en découle.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DayNightCycle {
+    /// Heure courante, dans [0, 24[.
+    pub time: f32,
+    /// Durée d'une journée complète, en secondes de jeu.
+    pub day_length: f32,
+    /// L'heure n'avance plus (la teinte reste appliquée).
+    pub paused: bool,
+    /// Teinte rouge, verte et bleue en fonction de l'heure (clés de 0 à 24 ; mettre la même
+    /// valeur à 0 et 24 pour que minuit ne saute pas).
+    pub tint: [Curve; 3],
+}
+
+impl DayNightCycle {
+    pub const HOURS_PER_DAY: f32 = 24.0;
+
+    /// Clé du `Blackboard` où `publish` écrit l'heure.
+    pub const BLACKBOARD_KEY: &str = "environment.time_of_day";
+
+    /// Cycle de `day_length` secondes par jour, à midi, avec la teinte par défaut : nuit
+    /// bleutée, aube et crépuscule orangés, jour blanc.
+    pub fn new(day_length: f32) -> Self {
+        // (heure, [r, g, b])
+        const KEYS: [(f32, [f32; 3]); 7] = [
+            (0.0, [0.25, 0.3, 0.5]),
+            (5.0, [0.3, 0.32, 0.5]),
+            (7.0, [1.0, 0.75, 0.55]),
+            (10.0, [1.0, 1.0, 1.0]),
+            (17.0, [1.0, 1.0, 1.0]),
+            (19.5, [0.95, 0.6, 0.45]),
+            (24.0, [0.25, 0.3, 0.5]),
+        ];
+        let channel = |c: usize| {
+            Curve::from_keys(
+                KEYS.iter()
+                    .map(|&(hour, color)| CurveKey::with_tangent(hour, color[c], 0.0)),
+            )
+        };
+
+        Self {
+            time: 12.0,
+            day_length,
+            paused: false,
+            tint: [channel(0), channel(1), channel(2)],
+        }
+    }
+
+    /// Builder-style : heure de départ.
+    pub fn with_time(mut self, hours: f32) -> Self {
+        self.set_time(hours);
+        self
+    }
+
+    /// Change l'heure (ramenée dans [0, 24[).
+    pub fn set_time(&mut self, hours: f32) {
+        self.time = hours.rem_euclid(Self::HOURS_PER_DAY);
+    }
+
+    /// Avance l'heure de `delta_time` secondes de jeu (sauf en pause).
+    pub fn advance(&mut self, delta_time: f32) {
+        if self.paused || self.day_length <= 0.0 {
+            return;
+        }
+        self.set_time(self.time + delta_time * Self::HOURS_PER_DAY / self.day_length);
+    }
+
+    /// Heure courante ramenée dans [0, 1[ (0 = minuit, 0.5 = midi).
+    pub fn normalized_time(&self) -> f32 {
+        self.time / Self::HOURS_PER_DAY
+    }
+
+    /// Teinte à l'heure `hours`.
+    pub fn tint_at(&self, hours: f32) -> [f32; 3] {
+        let hours = hours.rem_euclid(Self::HOURS_PER_DAY);
+        [0, 1, 2].map(|c| self.tint[c].evaluate(hours).max(0.0))
+    }
+
+    /// Teinte à l'heure courante.
+    pub fn current_tint(&self) -> [f32; 3] {
+        self.tint_at(self.time)
+    }
+
+    /// Avance l'heure et l'écrit, avec la teinte, dans `environment`.
+    pub fn update(&mut self, delta_time: f32, environment: &mut Environment) {
+        self.advance(delta_time);
+        environment.time_of_day = self.time;
+        environment.ambient = Some(self.current_tint());
+    }
+
+    /// Écrit l'heure courante dans `blackboard` (`BLACKBOARD_KEY`), pour la logique de jeu
+    /// et le HUD.
+    pub fn publish(&self, blackboard: &mut Blackboard) {
+        blackboard.set(Self::BLACKBOARD_KEY, self.time);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn time_wraps_and_drives_the_tint() {
+        let mut cycle = DayNightCycle::new(240.0).with_time(23.0);
+        // 240 s par jour : 10 s par heure
+        cycle.advance(20.0);
+        assert!((cycle.time - 1.0).abs() < 1e-4);
+
+        let noon = cycle.tint_at(12.0);
+        let midnight = cycle.tint_at(0.0);
+        assert!(noon.iter().all(|&c| (c - 1.0).abs() < 1e-4));
+        assert!(midnight.iter().all(|&c| c < 0.6));
+        assert_eq!(cycle.tint_at(24.0), midnight);
+
+        let mut environment = Environment::default();
+        cycle.paused = true;
+        cycle.update(100.0, &mut environment);
+        assert!((environment.time_of_day - 1.0).abs() < 1e-4);
+        assert_eq!(environment.ambient, Some(cycle.current_tint()));
+
+        let mut blackboard = Blackboard::default();
+        cycle.publish(&mut blackboard);
+        let published = blackboard.get_float(DayNightCycle::BLACKBOARD_KEY).unwrap();
+        assert!((published - 1.0).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,154 @@
+//! Ambiance météo d'une scène : pluie, neige, brouillard et vent.
+//!
+//! Chaque `Scene` porte son `Environment`, dessiné par la `WeatherPass` par-dessus les
+//! sprites : pas de passe à écrire pour l'ambiance courante d'un jeu.
+//!
+//! ```ignore
+//! scene.environment = Environment::rain(0.6).with_wind(-120.0).with_fog(0.2);
+//! ```
+
+/// Couche de pluie ou de neige. Les particules sont générées par le shader (pas d'entités) :
+/// elles suivent le monde quand la caméra bouge, sur plusieurs plans de profondeur.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Precipitation {
+    /// Densité, de 0 (rien) à 1 (averse, tempête de neige).
+    pub intensity: f32,
+    /// Vitesse de chute, en pixels par seconde (plan le plus proche).
+    pub speed: f32,
+    /// Taille des particules en pixels : longueur des gouttes, diamètre des flocons.
+    pub size: f32,
+    /// Couleur RGBA ; l'alpha règle l'opacité de la couche.
+    pub color: [f32; 4],
+}
+
+impl Precipitation {
+    /// Pluie par défaut, d'intensité nulle (voir `Environment::with_rain`).
+    pub const RAIN: Self = Self {
+        intensity: 0.0,
+        speed: 900.0,
+        size: 18.0,
+        color: [0.7, 0.75, 0.85, 0.5],
+    };
+
+    /// Neige par défaut, d'intensité nulle (voir `Environment::with_snow`).
+    pub const SNOW: Self = Self {
+        intensity: 0.0,
+        speed: 60.0,
+        size: 4.0,
+        color: [1.0, 1.0, 1.0, 0.9],
+    };
+
+    pub fn is_active(&self) -> bool {
+        self.intensity > 0.0 && self.color[3] > 0.0
+    }
+}
+
+/// Brouillard : nappes de bruit qui dérivent avec le vent.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fog {
+    /// Opacité maximale des nappes, de 0 (rien) à 1.
+    pub density: f32,
+    /// Taille des nappes en pixels.
+    pub scale: f32,
+    pub color: [f32; 3],
+}
+
+impl Fog {
+    /// Brouillard par défaut, de densité nulle (voir `Environment::with_fog`).
+    pub const NONE: Self = Self {
+        density: 0.0,
+        scale: 300.0,
+        color: [0.8, 0.82, 0.85],
+    };
+
+    pub fn is_active(&self) -> bool {
+        self.density > 0.0
+    }
+}
+
+/// Météo d'une scène. Par défaut : temps clair, la `WeatherPass` ne dessine rien.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Environment {
+    pub rain: Precipitation,
+    pub snow: Precipitation,
+    pub fog: Fog,
+    /// Vent horizontal en pixels par seconde (négatif = vers la gauche) : incline la pluie,
+    /// pousse la neige et le brouillard.
+    pub wind: f32,
+    /// Heure du jour (0..24), transmise aux shaders de météo et de post-process. Mise à jour
+    /// par le `DayNightCycle` de la scène.
+    pub time_of_day: f32,
+    /// Teinte globale de la scène : remplace `LightingPass::ambient` si définie (voir
+    /// `DayNightCycle`).
+    pub ambient: Option<[f32; 3]>,
+}
+
+impl Default for Environment {
+    fn default() -> Self {
+        Self {
+            rain: Precipitation::RAIN,
+            snow: Precipitation::SNOW,
+            fog: Fog::NONE,
+            wind: 0.0,
+            time_of_day: 12.0,
+            ambient: None,
+        }
+    }
+}
+
+impl Environment {
+    /// Pluie d'intensité `intensity` (0..1).
+    pub fn rain(intensity: f32) -> Self {
+        Self::default().with_rain(intensity)
+    }
+
+    /// Neige d'intensité `intensity` (0..1).
+    pub fn snow(intensity: f32) -> Self {
+        Self::default().with_snow(intensity)
+    }
+
+    pub fn with_rain(mut self, intensity: f32) -> Self {
+        self.rain.intensity = intensity.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn with_snow(mut self, intensity: f32) -> Self {
+        self.snow.intensity = intensity.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn with_fog(mut self, density: f32) -> Self {
+        self.fog.density = density.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn with_wind(mut self, wind: f32) -> Self {
+        self.wind = wind;
+        self
+    }
+
+    /// `true` si aucune couche de météo n'est active.
+    pub fn is_clear(&self) -> bool {
+        !self.rain.is_active() && !self.snow.is_active() && !self.fog.is_active()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presets_enable_only_their_layer() {
+        assert!(Environment::default().is_clear());
+
+        let storm = Environment::rain(2.0).with_wind(-150.0);
+        assert_eq!(storm.rain.intensity, 1.0);
+        assert!(!storm.is_clear());
+        assert!(!storm.snow.is_active() && !storm.fog.is_active());
+
+        let mut foggy = Environment::default().with_fog(0.3);
+        assert!(!foggy.is_clear());
+        foggy.fog.density = 0.0;
+        assert!(foggy.is_clear());
+    }
+}
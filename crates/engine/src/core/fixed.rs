@@ -0,0 +1,303 @@
+//! Math déterministe pour les systèmes de simulation (lockstep réseau, replays).
+//!
+//! `Fixed` est un nombre à virgule fixe (Q47.16 sur un `i64`) : toutes ses opérations, y
+//! compris `sqrt` et `sin_cos`, sont en arithmétique entière et donnent le même résultat
+//! bit à bit sur toutes les plateformes, contrairement aux fonctions flottantes de la libm.
+//!
+//! Le code de simulation s'écrit avec `Scalar` (et le trait `Real`) : `f32` par défaut,
+//! `Fixed` quand le projet active la feature `fixed-point` du moteur. Le rendu reste en
+//! `f32` (`Real::to_f32`).
+
+use std::{
+    fmt,
+    ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign},
+};
+
+/// Scalaire des systèmes de simulation, choisi par la feature `fixed-point`.
+#[cfg(feature = "fixed-point")]
+pub type Scalar = Fixed;
+/// Scalaire des systèmes de simulation, choisi par la feature `fixed-point`.
+#[cfg(not(feature = "fixed-point"))]
+pub type Scalar = f32;
+
+/// Nombre à virgule fixe, 16 bits de partie fractionnaire (précision 1/65536).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Fixed(i64);
+
+impl Fixed {
+    pub const FRAC_BITS: u32 = 16;
+    const SCALE: i64 = 1 << Self::FRAC_BITS;
+
+    pub const ZERO: Fixed = Fixed(0);
+    pub const ONE: Fixed = Fixed(Self::SCALE);
+    pub const HALF: Fixed = Fixed(Self::SCALE / 2);
+    pub const PI: Fixed = Fixed(205_887);
+    pub const TAU: Fixed = Fixed(411_775);
+    pub const FRAC_PI_2: Fixed = Fixed(102_944);
+
+    /// Valeur brute (`valeur * 2^16`), pour la sérialisation et le réseau.
+    pub const fn from_bits(bits: i64) -> Self {
+        Fixed(bits)
+    }
+
+    pub const fn to_bits(self) -> i64 {
+        self.0
+    }
+
+    pub const fn from_int(value: i32) -> Self {
+        Fixed((value as i64) << Self::FRAC_BITS)
+    }
+
+    /// Arrondi au plus proche. Déterministe pour une même valeur `f32` : à réserver aux
+    /// données (fichiers, éditeur), pas aux résultats de calculs flottants.
+    pub fn from_f32(value: f32) -> Self {
+        Fixed((value as f64 * Self::SCALE as f64).round() as i64)
+    }
+
+    pub fn to_f32(self) -> f32 {
+        (self.0 as f64 / Self::SCALE as f64) as f32
+    }
+
+    /// Partie entière (arrondi vers -inf).
+    pub const fn to_int(self) -> i64 {
+        self.0 >> Self::FRAC_BITS
+    }
+
+    pub const fn floor(self) -> Self {
+        Fixed(self.0 & !(Self::SCALE - 1))
+    }
+
+    pub const fn ceil(self) -> Self {
+        Fixed((self.0 + Self::SCALE - 1) & !(Self::SCALE - 1))
+    }
+
+    pub const fn abs(self) -> Self {
+        Fixed(self.0.abs())
+    }
+
+    /// Racine carrée (0 pour une valeur négative).
+    pub fn sqrt(self) -> Self {
+        if self.0 <= 0 {
+            return Self::ZERO;
+        }
+        Fixed(((self.0 as u128) << Self::FRAC_BITS).isqrt() as i64)
+    }
+
+    /// Sinus et cosinus (série de Taylor après réduction à [-pi/2, pi/2]).
+    pub fn sin_cos(self) -> (Self, Self) {
+        (self.sin(), (self + Self::FRAC_PI_2).sin())
+    }
+
+    pub fn sin(self) -> Self {
+        // Réduction à [-pi, pi[, puis symétrie sin(pi - x) = sin(x)
+        let mut x = Fixed((self.0 + Self::PI.0).rem_euclid(Self::TAU.0) - Self::PI.0);
+        if x > Self::FRAC_PI_2 {
+            x = Self::PI - x;
+        } else if x < -Self::FRAC_PI_2 {
+            x = -Self::PI - x;
+        }
+        // x (1 - x²/6 (1 - x²/20 (1 - x²/42 (1 - x²/72))))
+        let x2 = x * x;
+        let mut result = Self::ONE;
+        for divisor in [72, 42, 20, 6] {
+            result = Self::ONE - x2 * result / Self::from_int(divisor);
+        }
+        x * result
+    }
+
+    pub fn cos(self) -> Self {
+        (self + Self::FRAC_PI_2).sin()
+    }
+}
+
+impl fmt::Display for Fixed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_f32())
+    }
+}
+
+impl Add for Fixed {
+    type Output = Fixed;
+
+    fn add(self, rhs: Fixed) -> Fixed {
+        Fixed(self.0 + rhs.0)
+    }
+}
+
+impl Sub for Fixed {
+    type Output = Fixed;
+
+    fn sub(self, rhs: Fixed) -> Fixed {
+        Fixed(self.0 - rhs.0)
+    }
+}
+
+impl Mul for Fixed {
+    type Output = Fixed;
+
+    fn mul(self, rhs: Fixed) -> Fixed {
+        Fixed(((self.0 as i128 * rhs.0 as i128) >> Self::FRAC_BITS) as i64)
+    }
+}
+
+impl Div for Fixed {
+    type Output = Fixed;
+
+    /// Panique si `rhs` vaut zéro, comme la division entière.
+    fn div(self, rhs: Fixed) -> Fixed {
+        Fixed((((self.0 as i128) << Self::FRAC_BITS) / rhs.0 as i128) as i64)
+    }
+}
+
+impl Neg for Fixed {
+    type Output = Fixed;
+
+    fn neg(self) -> Fixed {
+        Fixed(-self.0)
+    }
+}
+
+impl AddAssign for Fixed {
+    fn add_assign(&mut self, rhs: Fixed) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for Fixed {
+    fn sub_assign(&mut self, rhs: Fixed) {
+        *self = *self - rhs;
+    }
+}
+
+impl MulAssign for Fixed {
+    fn mul_assign(&mut self, rhs: Fixed) {
+        *self = *self * rhs;
+    }
+}
+
+impl DivAssign for Fixed {
+    fn div_assign(&mut self, rhs: Fixed) {
+        *self = *self / rhs;
+    }
+}
+
+/// Opérations communes à `f32` et `Fixed`, pour écrire un système une seule fois avec
+/// `Scalar`.
+pub trait Real:
+    Copy
+    + PartialOrd
+    + fmt::Debug
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+    + AddAssign
+    + SubAssign
+{
+    const ZERO: Self;
+    const ONE: Self;
+    const TAU: Self;
+
+    fn from_f32(value: f32) -> Self;
+    fn to_f32(self) -> f32;
+    fn from_int(value: i32) -> Self;
+    fn sqrt(self) -> Self;
+    fn sin_cos(self) -> (Self, Self);
+    fn abs(self) -> Self;
+    fn floor(self) -> Self;
+}
+
+impl Real for f32 {
+    const ZERO: Self = 0.0;
+    const ONE: Self = 1.0;
+    const TAU: Self = std::f32::consts::TAU;
+
+    fn from_f32(value: f32) -> Self {
+        value
+    }
+
+    fn to_f32(self) -> f32 {
+        self
+    }
+
+    fn from_int(value: i32) -> Self {
+        value as f32
+    }
+
+    fn sqrt(self) -> Self {
+        f32::sqrt(self)
+    }
+
+    fn sin_cos(self) -> (Self, Self) {
+        f32::sin_cos(self)
+    }
+
+    fn abs(self) -> Self {
+        f32::abs(self)
+    }
+
+    fn floor(self) -> Self {
+        f32::floor(self)
+    }
+}
+
+impl Real for Fixed {
+    const ZERO: Self = Fixed::ZERO;
+    const ONE: Self = Fixed::ONE;
+    const TAU: Self = Fixed::TAU;
+
+    fn from_f32(value: f32) -> Self {
+        Fixed::from_f32(value)
+    }
+
+    fn to_f32(self) -> f32 {
+        Fixed::to_f32(self)
+    }
+
+    fn from_int(value: i32) -> Self {
+        Fixed::from_int(value)
+    }
+
+    fn sqrt(self) -> Self {
+        Fixed::sqrt(self)
+    }
+
+    fn sin_cos(self) -> (Self, Self) {
+        Fixed::sin_cos(self)
+    }
+
+    fn abs(self) -> Self {
+        Fixed::abs(self)
+    }
+
+    fn floor(self) -> Self {
+        Fixed::floor(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_point_operations() {
+        let (a, b) = (Fixed::from_f32(2.5), Fixed::from_int(-4));
+        assert_eq!((a + b).to_f32(), -1.5);
+        assert_eq!((a * b).to_f32(), -10.0);
+        assert_eq!((b / a).to_f32(), -1.5999908);
+        assert_eq!(Fixed::from_f32(-1.25).floor(), Fixed::from_int(-2));
+        assert_eq!(Fixed::from_f32(1.25).ceil(), Fixed::from_int(2));
+        assert_eq!(Fixed::from_int(9).sqrt(), Fixed::from_int(3));
+        assert_eq!(Fixed::from_int(-1).sqrt(), Fixed::ZERO);
+
+        for step in -40..=40 {
+            let angle = step as f32 * 0.2;
+            let (sin, cos) = Fixed::from_f32(angle).sin_cos();
+            assert!((sin.to_f32() - angle.sin()).abs() < 1e-3, "sin({})", angle);
+            assert!((cos.to_f32() - angle.cos()).abs() < 1e-3, "cos({})", angle);
+        }
+        // Résultat figé : doit être identique sur toutes les plateformes
+        assert_eq!(Fixed::from_int(1).sin().to_bits(), 55_147);
+    }
+}
@@ -0,0 +1,129 @@
+//! Hiérarchie parent/enfant des entités et propagation des transforms.
+//!
+//! Une entité enfant hérite de la position/rotation/échelle de son parent :
+//! `GlobalTransform(enfant) = GlobalTransform(parent) * Transform(enfant)`.
+
+use hecs::Entity;
+
+use crate::{Children, GlobalTransform, Mat4, Parent, Scene, Transform};
+
+impl Scene {
+    /// Attache `child` sous `parent` (en le détachant de son ancien parent).
+    /// Refuse (retourne `false`) si l'une des entités n'existe pas ou si cela créerait un cycle.
+    pub fn set_parent(&mut self, child: Entity, parent: Entity) -> bool {
+        if child == parent || !self.world.contains(child) || !self.world.contains(parent) {
+            return false;
+        }
+
+        if self.is_ancestor_of(child, parent) {
+            log::warn!(
+                "set_parent: {:?} is an ancestor of {:?}, refusing to create a cycle",
+                child,
+                parent
+            );
+            return false;
+        }
+
+        self.remove_parent(child);
+
+        let _ = self.world.insert_one(child, Parent(parent));
+
+        let has_children = match self.world.get::<&mut Children>(parent) {
+            Ok(mut children) => {
+                children.0.push(child);
+                true
+            }
+            Err(_) => false,
+        };
+        if !has_children {
+            let _ = self.world.insert_one(parent, Children(vec![child]));
+        }
+
+        true
+    }
+
+    /// Détache `child` de son parent (il redevient une racine). Retourne l'ancien parent.
+    pub fn remove_parent(&mut self, child: Entity) -> Option<Entity> {
+        let Parent(parent) = self.world.remove_one::<Parent>(child).ok()?;
+
+        if let Ok(mut children) = self.world.get::<&mut Children>(parent) {
+            children.0.retain(|&c| c != child);
+        }
+
+        Some(parent)
+    }
+
+    pub fn parent_of(&self, entity: Entity) -> Option<Entity> {
+        self.world.get::<&Parent>(entity).ok().map(|p| p.0)
+    }
+
+    /// Enfants directs de `entity` (copie).
+    pub fn children_of(&self, entity: Entity) -> Vec<Entity> {
+        self.world
+            .get::<&Children>(entity)
+            .map(|c| c.0.clone())
+            .unwrap_or_default()
+    }
+
+    /// `true` si `ancestor` est un parent (direct ou non) de `entity`.
+    pub fn is_ancestor_of(&self, ancestor: Entity, entity: Entity) -> bool {
+        let mut current = self.parent_of(entity);
+        while let Some(parent) = current {
+            if parent == ancestor {
+                return true;
+            }
+            current = self.parent_of(parent);
+        }
+        false
+    }
+
+    /// Détruit `entity` et tous ses descendants, en la détachant de son parent.
+    /// Retourne le nombre d'entités détruites.
+    pub fn despawn_recursive(&mut self, entity: Entity) -> usize {
+        self.remove_parent(entity);
+
+        let mut count = 0;
+        let mut stack = vec![entity];
+        while let Some(current) = stack.pop() {
+            stack.extend(self.children_of(current));
+            if self.world.despawn(current).is_ok() {
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// Recalcule le `GlobalTransform` de toutes les entités ayant un `Transform`,
+    /// en partant des racines (entités sans `Parent`) et en descendant la hiérarchie.
+    pub fn propagate_transforms(&mut self) {
+        let mut stack: Vec<(Entity, Mat4)> = self
+            .world
+            .query::<(&Transform, Option<&Parent>)>()
+            .iter()
+            .filter(|(_, (_, parent))| parent.is_none())
+            .map(|(entity, (transform, _))| (entity, transform.matrix()))
+            .collect();
+
+        while let Some((entity, global)) = stack.pop() {
+            let updated = match self.world.get::<&mut GlobalTransform>(entity) {
+                Ok(mut current) => {
+                    current.0 = global;
+                    true
+                }
+                Err(_) => false,
+            };
+            if !updated {
+                let _ = self.world.insert_one(entity, GlobalTransform(global));
+            }
+
+            for child in self.children_of(entity) {
+                let local = self
+                    .world
+                    .get::<&Transform>(child)
+                    .map(|t| t.matrix())
+                    .unwrap_or_else(|_| Mat4::identity());
+                stack.push((child, global * local));
+            }
+        }
+    }
+}
@@ -1,9 +1,24 @@
 mod camera;
+mod clipboard;
+mod components;
+mod day_night;
+mod environment;
+mod fixed;
+mod hierarchy;
 mod math;
 mod scene;
+mod spawner;
+mod streaming;
 mod transform;
 
 pub use camera::*;
+pub use clipboard::*;
+pub use components::*;
+pub use day_night::*;
+pub use environment::*;
+pub use fixed::*;
 pub use math::*;
 pub use scene::*;
+pub use spawner::*;
+pub use streaming::*;
 pub use transform::*;
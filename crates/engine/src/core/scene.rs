@@ -1,10 +1,26 @@
-use crate::Camera2D;
+use std::sync::Arc;
+
+use crate::{
+    Camera2D, Children, DayNightCycle, Environment, GlobalTransform, Name, Parent, RenderSettings,
+    Sprite, SpriteComponent, Texture2D, Tilemap, Transform,
+};
+use anyhow::Result;
 use egui_wgpu::wgpu;
+use hecs::{DynamicBundle, Entity, World};
 use nalgebra::Vector2;
 
 pub struct Scene {
     pub name: String,
     pub camera: Camera2D,
+    /// Entités de la scène et leurs composants (`Transform`, `SpriteComponent`, `Name`...).
+    /// Utiliser `world.query::<(&A, &B)>()` pour itérer.
+    pub world: World,
+    /// Passes et effets actifs pour cette scène, appliqués par le `PassManager`.
+    pub render_settings: RenderSettings,
+    /// Météo de la scène (pluie, neige, brouillard), dessinée par la `WeatherPass`.
+    pub environment: Environment,
+    /// Cycle jour/nuit avancé par `update`, qui anime `environment`.
+    pub day_night: Option<DayNightCycle>,
 
     // Accumulate raw mouse delta between frames (DeviceEvent)
     mouse_delta: Vector2<f32>,
@@ -15,10 +31,132 @@ impl Scene {
         Self {
             name,
             camera,
+            world: World::new(),
+            render_settings: RenderSettings::default(),
+            environment: Environment::default(),
+            day_night: None,
             mouse_delta: Vector2::new(0.0, 0.0),
         }
     }
 
+    /// Crée une entité avec les composants fournis.
+    pub fn spawn(&mut self, components: impl DynamicBundle) -> Entity {
+        self.world.spawn(components)
+    }
+
+    /// Raccourci : crée une entité nommée, positionnée et dessinée avec `sprite`.
+    pub fn spawn_sprite(
+        &mut self,
+        name: impl Into<String>,
+        transform: Transform,
+        sprite: Sprite,
+    ) -> Entity {
+        self.world
+            .spawn((Name::new(name), transform, SpriteComponent::new(sprite)))
+    }
+
+    /// Détruit une entité et ses descendants (voir `despawn_recursive`).
+    /// Retourne `false` si elle n'existait pas (ou plus).
+    pub fn despawn(&mut self, entity: Entity) -> bool {
+        self.despawn_recursive(entity) > 0
+    }
+
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.world.contains(entity)
+    }
+
+    /// Première entité portant ce `Name`.
+    pub fn find_by_name(&self, name: &str) -> Option<Entity> {
+        self.world
+            .query::<&Name>()
+            .iter()
+            .find(|(_, n)| n.as_str() == name)
+            .map(|(entity, _)| entity)
+    }
+
+    pub fn entity_count(&self) -> u32 {
+        self.world.len()
+    }
+
+    /// Estimation de la mémoire des entités : taille des composants connus du moteur et de
+    /// leurs données (noms, enfants, tuiles). Retourne (octets, nombre d'entités).
+    pub fn memory_estimate(&self) -> (u64, u64) {
+        fn components<T: hecs::Component>(world: &World) -> usize {
+            world.query::<&T>().iter().count() * std::mem::size_of::<T>()
+        }
+
+        let mut bytes = self.world.len() as usize * std::mem::size_of::<Entity>();
+        bytes += components::<Transform>(&self.world);
+        bytes += components::<GlobalTransform>(&self.world);
+        bytes += components::<SpriteComponent>(&self.world);
+        bytes += components::<Parent>(&self.world);
+        bytes += components::<Tilemap>(&self.world);
+        for (_, name) in self.world.query::<&Name>().iter() {
+            bytes += std::mem::size_of::<Name>() + name.as_str().len();
+        }
+        for (_, children) in self.world.query::<&Children>().iter() {
+            bytes +=
+                std::mem::size_of::<Children>() + children.0.len() * std::mem::size_of::<Entity>();
+        }
+        for (_, map) in self.world.query::<&Tilemap>().iter() {
+            bytes += map.heap_size();
+        }
+        (bytes as u64, self.world.len() as u64)
+    }
+
+    /// Supprime toutes les entités.
+    pub fn clear(&mut self) {
+        self.world.clear();
+    }
+
+    /// Remplace les textures chargées depuis `path` (sprites, normal maps et tilesets des
+    /// tilemaps) après sa modification sur le disque. `load(linear)` relit la texture,
+    /// `linear` pour les normal maps ; chaque variante n'est chargée que si elle est utilisée.
+    /// Retourne le nombre de textures remplacées.
+    pub fn reload_texture(
+        &mut self,
+        path: &str,
+        mut load: impl FnMut(bool) -> Result<Arc<Texture2D>>,
+    ) -> Result<usize> {
+        let from_path = |texture: &Arc<Texture2D>| texture.path.as_deref() == Some(path);
+        let mut loaded: [Option<Arc<Texture2D>>; 2] = [None, None];
+        let mut reload = |linear: bool| -> Result<Arc<Texture2D>> {
+            let slot = &mut loaded[linear as usize];
+            if let Some(texture) = slot {
+                return Ok(texture.clone());
+            }
+            let texture = load(linear)?;
+            *slot = Some(texture.clone());
+            Ok(texture)
+        };
+
+        let mut replaced = 0;
+        for (_, component) in self.world.query_mut::<&mut SpriteComponent>() {
+            let sprite = &mut component.sprite;
+            if from_path(&sprite.texture) {
+                sprite.texture = reload(false)?;
+                replaced += 1;
+            }
+            if let Some(normal_map) = &mut sprite.normal_map
+                && from_path(normal_map)
+            {
+                *normal_map = reload(true)?;
+                replaced += 1;
+            }
+        }
+        for (_, map) in self.world.query_mut::<&mut Tilemap>() {
+            for tileset in &mut map.tilesets {
+                if let Some(texture) = &mut tileset.texture
+                    && from_path(texture)
+                {
+                    *texture = reload(false)?;
+                    replaced += 1;
+                }
+            }
+        }
+        Ok(replaced)
+    }
+
     /// Appelé par le handler d'événements bas niveau (DeviceEvent) :
     /// on accumule la delta souris et on retourne rapidement.
     pub fn accumulate_mouse(&mut self, dx: f32, dy: f32) {
@@ -27,7 +165,13 @@ impl Scene {
     }
 
     pub fn update(&mut self, delta_time: f32) {
-        // self.world.update(delta_time);
+        let _scope = tracing::trace_span!("scene_update").entered();
+
+        // 1) Hiérarchie : les enfants héritent du transform de leur parent
+        {
+            let _scope = tracing::trace_span!("propagate_transforms").entered();
+            self.propagate_transforms();
+        }
 
         // 2) Appliquer la souris accumulée à la caméra
         if self.mouse_delta.norm() > 0.0 {
@@ -35,11 +179,17 @@ impl Scene {
             //     .process_mouse(self.mouse_delta.x, self.mouse_delta.y, delta_time);
             self.mouse_delta = Vector2::new(0.0, 0.0);
         }
+
+        // 3) Heure du jour et teinte globale
+        if let Some(cycle) = &mut self.day_night {
+            cycle.update(delta_time, &mut self.environment);
+        }
     }
 
     /// Prépare et upload les buffers GPU qui doivent être faits avant d'enregistrer le pass.
     /// Cette étape peut être faite dans le thread principal avant `render`.
     pub fn prepare_gpu(&mut self, queue: &wgpu::Queue) {
+        let _scope = tracing::trace_span!("prepare_gpu").entered();
         // Ex: upload matrices, instance buffers, vertex buffers dynamiques, textures streaming...
         // self.world.upload_gpu_resources(queue);
         // self.camera.upload_uniforms(queue);
@@ -0,0 +1,694 @@
+//! Spawners de données : tables de spawn pondérées, intervalles, nombre max d'entités en vie,
+//! zones d'apparition et vagues (ennemis, pickups...).
+//!
+//! Les entités sont créées à partir de prefabs (`EntitySnapshot` enregistrés dans une
+//! `PrefabLibrary`) par `Scene::update_spawners`, qui retourne les `SpawnerEvent` de la frame
+//! pour que le jeu réagisse (son, score, fin de niveau...).
+
+use std::collections::{BTreeMap, HashMap};
+
+use hecs::Entity;
+
+use crate::{EntitySnapshot, GlobalTransform, Real, Rng, Scalar, Scene, Transform, Vec2, Vec3};
+
+/// Zone d'apparition, centrée sur la position (globale) du spawner.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SpawnArea {
+    Point,
+    Circle { radius: f32 },
+    Rect { width: f32, height: f32 },
+}
+
+impl SpawnArea {
+    /// Décalage aléatoire (uniforme sur la surface) par rapport au centre. Calculé en
+    /// `Scalar` : identique sur toutes les plateformes avec la feature `fixed-point`.
+    pub fn sample(&self, rng: &mut Rng) -> Vec2 {
+        match *self {
+            SpawnArea::Point => Vec2::zeros(),
+            SpawnArea::Circle { radius } => {
+                // sqrt pour ne pas concentrer les points au centre
+                let distance = Scalar::from_f32(radius) * rng.next_scalar().sqrt();
+                let angle = rng.next_scalar() * <Scalar as Real>::TAU;
+                let (sin, cos) = angle.sin_cos();
+                Vec2::new((cos * distance).to_f32(), (sin * distance).to_f32())
+            }
+            SpawnArea::Rect { width, height } => {
+                let half = Scalar::ONE / Scalar::from_int(2);
+                let x = (rng.next_scalar() - half) * Scalar::from_f32(width);
+                let y = (rng.next_scalar() - half) * Scalar::from_f32(height);
+                Vec2::new(x.to_f32(), y.to_f32())
+            }
+        }
+    }
+}
+
+/// Entrée d'une table de spawn : nom du prefab et poids relatif.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpawnEntry {
+    pub prefab: String,
+    pub weight: f32,
+}
+
+impl SpawnEntry {
+    pub fn new(prefab: impl Into<String>, weight: f32) -> Self {
+        Self {
+            prefab: prefab.into(),
+            weight,
+        }
+    }
+
+    /// Tirage pondéré dans `table` (`None` si elle est vide ou sans poids positif).
+    pub fn pick<'a>(table: &'a [SpawnEntry], rng: &mut Rng) -> Option<&'a str> {
+        let total: f32 = table.iter().map(|entry| entry.weight.max(0.0)).sum();
+        if total <= 0.0 {
+            return None;
+        }
+        let mut target = rng.next_f32() * total;
+        for entry in table.iter().filter(|entry| entry.weight > 0.0) {
+            if target < entry.weight {
+                return Some(&entry.prefab);
+            }
+            target -= entry.weight;
+        }
+        // Arrondi flottant : dernière entrée valide
+        table
+            .iter()
+            .rfind(|entry| entry.weight > 0.0)
+            .map(|entry| entry.prefab.as_str())
+    }
+}
+
+/// Vague : `count` entités tirées de `table`, une toutes les `interval` secondes, après
+/// `delay` secondes. La vague suivante commence quand toutes les entités de celle-ci sont
+/// mortes (détruites).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Wave {
+    pub table: Vec<SpawnEntry>,
+    pub count: u32,
+    pub interval: f32,
+    pub delay: f32,
+}
+
+impl Wave {
+    pub fn new(table: Vec<SpawnEntry>, count: u32, interval: f32) -> Self {
+        Self {
+            table,
+            count,
+            interval,
+            delay: 0.0,
+        }
+    }
+}
+
+/// Événement émis par `Scene::update_spawners`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SpawnerEvent {
+    WaveStarted {
+        spawner: Entity,
+        wave: usize,
+    },
+    Spawned {
+        spawner: Entity,
+        entity: Entity,
+        prefab: String,
+        /// Vague en cours, `None` en mode continu.
+        wave: Option<usize>,
+    },
+    /// Dernière vague terminée (jamais émis avec `loop_waves`).
+    WavesCompleted {
+        spawner: Entity,
+    },
+}
+
+/// Étape calculée par `Spawner::step`, avant création des entités.
+#[derive(Debug, Clone, PartialEq)]
+enum SpawnStep {
+    WaveStarted(usize),
+    Spawn {
+        prefab: String,
+        offset: Vec2,
+        wave: Option<usize>,
+    },
+    Completed,
+}
+
+/// Composant spawner. Sans `waves`, il crée en continu une entité de `table` toutes les
+/// `interval` secondes ; avec des vagues, il les enchaîne (en boucle si `loop_waves`).
+/// `max_alive` (0 = illimité) borne les entités créées encore en vie.
+#[derive(Debug, Clone)]
+pub struct Spawner {
+    pub active: bool,
+    pub table: Vec<SpawnEntry>,
+    pub interval: f32,
+    pub max_alive: u32,
+    pub area: SpawnArea,
+    pub waves: Vec<Wave>,
+    pub loop_waves: bool,
+
+    rng: Rng,
+    timer: f32,
+    wave: usize,
+    spawned_in_wave: u32,
+    wave_started: bool,
+    completed: bool,
+    /// Entités créées par ce spawner et encore vivantes.
+    alive: Vec<Entity>,
+}
+
+impl Spawner {
+    /// Spawner continu de `table`, une entité toutes les `interval` secondes.
+    pub fn new(table: Vec<SpawnEntry>, interval: f32) -> Self {
+        Self {
+            active: true,
+            table,
+            interval,
+            max_alive: 0,
+            area: SpawnArea::Point,
+            waves: Vec::new(),
+            loop_waves: false,
+            rng: Rng::new(0),
+            timer: 0.0,
+            wave: 0,
+            spawned_in_wave: 0,
+            wave_started: false,
+            completed: false,
+            alive: Vec::new(),
+        }
+    }
+
+    /// Spawner de vagues (voir `Wave`).
+    pub fn with_waves(waves: Vec<Wave>) -> Self {
+        Self {
+            waves,
+            ..Self::new(Vec::new(), 1.0)
+        }
+    }
+
+    pub fn with_area(mut self, area: SpawnArea) -> Self {
+        self.area = area;
+        self
+    }
+
+    pub fn with_max_alive(mut self, max_alive: u32) -> Self {
+        self.max_alive = max_alive;
+        self
+    }
+
+    /// Graine des tirages (prefab et position) : même graine, même séquence.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = Rng::new(seed);
+        self
+    }
+
+    /// Vague en cours (`None` en mode continu ou une fois les vagues terminées).
+    pub fn current_wave(&self) -> Option<usize> {
+        (!self.waves.is_empty() && !self.completed).then_some(self.wave)
+    }
+
+    pub fn is_completed(&self) -> bool {
+        self.completed
+    }
+
+    pub fn alive(&self) -> &[Entity] {
+        &self.alive
+    }
+
+    /// Revient au début (première vague, minuteur à zéro) et oublie les entités créées.
+    pub fn reset(&mut self) {
+        self.timer = 0.0;
+        self.wave = 0;
+        self.spawned_in_wave = 0;
+        self.wave_started = false;
+        self.completed = false;
+        self.alive.clear();
+    }
+
+    /// Avance de `delta_time` secondes et retourne les étapes à réaliser. Les entités mortes
+    /// doivent avoir été retirées de `alive` avant.
+    fn step(&mut self, delta_time: f32) -> Vec<SpawnStep> {
+        let mut steps = Vec::new();
+        if !self.active || self.completed {
+            return steps;
+        }
+        self.timer += delta_time;
+
+        if self.waves.is_empty() {
+            let interval = self.interval.max(f32::EPSILON);
+            let table = self.table.clone();
+            while self.timer >= interval {
+                self.timer -= interval;
+                if !self.spawn_step(&table, None, &mut steps) {
+                    // Plein : on attend une place sans accumuler de retard
+                    self.timer = 0.0;
+                    break;
+                }
+            }
+            return steps;
+        }
+
+        // Vagues retirées depuis (éditeur) : on reste sur la dernière
+        self.wave = self.wave.min(self.waves.len() - 1);
+        loop {
+            let wave = &self.waves[self.wave];
+            if !self.wave_started {
+                if self.timer < wave.delay {
+                    break;
+                }
+                self.timer -= wave.delay;
+                self.wave_started = true;
+                // Première entité de la vague immédiatement
+                self.timer = self.timer.max(wave.interval);
+                steps.push(SpawnStep::WaveStarted(self.wave));
+            }
+
+            let wave = self.waves[self.wave].clone();
+            let interval = wave.interval.max(f32::EPSILON);
+            while self.spawned_in_wave < wave.count && self.timer >= interval {
+                self.timer -= interval;
+                if !self.spawn_step(&wave.table, Some(self.wave), &mut steps) {
+                    self.timer = 0.0;
+                    break;
+                }
+                self.spawned_in_wave += 1;
+            }
+
+            // Les entités de cette frame ne sont pas encore dans `alive`
+            let cleared = self.spawned_in_wave >= wave.count
+                && self.alive.is_empty()
+                && !steps
+                    .iter()
+                    .any(|step| matches!(step, SpawnStep::Spawn { .. }));
+            if !cleared {
+                break;
+            }
+            self.wave += 1;
+            self.spawned_in_wave = 0;
+            self.wave_started = false;
+            self.timer = 0.0;
+            if self.wave >= self.waves.len() {
+                if !self.loop_waves {
+                    self.completed = true;
+                    steps.push(SpawnStep::Completed);
+                    break;
+                }
+                self.wave = 0;
+            }
+            // Une vague vide ne doit pas boucler indéfiniment dans la même frame
+            if wave.count == 0 {
+                break;
+            }
+        }
+        steps
+    }
+
+    /// Ajoute une étape de spawn, sauf si `max_alive` est atteint (retourne `false`).
+    fn spawn_step(
+        &mut self,
+        table: &[SpawnEntry],
+        wave: Option<usize>,
+        steps: &mut Vec<SpawnStep>,
+    ) -> bool {
+        let pending = steps
+            .iter()
+            .filter(|step| matches!(step, SpawnStep::Spawn { .. }))
+            .count();
+        if self.max_alive > 0 && self.alive.len() + pending >= self.max_alive as usize {
+            return false;
+        }
+        if let Some(prefab) = SpawnEntry::pick(table, &mut self.rng) {
+            steps.push(SpawnStep::Spawn {
+                prefab: prefab.to_string(),
+                offset: self.area.sample(&mut self.rng),
+                wave,
+            });
+        }
+        true
+    }
+}
+
+impl Spawner {
+    /// Édition du spawner dans l'éditeur ; les prefabs proposés sont ceux de `prefabs`.
+    pub fn ui(&mut self, ui: &mut egui::Ui, prefabs: &PrefabLibrary) {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.active, "Active");
+            if ui.button("Restart").clicked() {
+                self.reset();
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Max alive");
+            ui.add(egui::DragValue::new(&mut self.max_alive));
+            ui.weak("(0 = unlimited)");
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Area");
+            egui::ComboBox::from_id_salt("spawn_area")
+                .selected_text(match self.area {
+                    SpawnArea::Point => "Point",
+                    SpawnArea::Circle { .. } => "Circle",
+                    SpawnArea::Rect { .. } => "Rect",
+                })
+                .show_ui(ui, |ui| {
+                    let area = self.area;
+                    ui.selectable_value(&mut self.area, SpawnArea::Point, "Point");
+                    if ui
+                        .selectable_label(matches!(area, SpawnArea::Circle { .. }), "Circle")
+                        .clicked()
+                    {
+                        self.area = SpawnArea::Circle { radius: 64.0 };
+                    }
+                    if ui
+                        .selectable_label(matches!(area, SpawnArea::Rect { .. }), "Rect")
+                        .clicked()
+                    {
+                        self.area = SpawnArea::Rect {
+                            width: 128.0,
+                            height: 128.0,
+                        };
+                    }
+                });
+            match &mut self.area {
+                SpawnArea::Point => {}
+                SpawnArea::Circle { radius } => {
+                    ui.add(
+                        egui::DragValue::new(radius)
+                            .range(0.0..=f32::MAX)
+                            .prefix("r "),
+                    );
+                }
+                SpawnArea::Rect { width, height } => {
+                    ui.add(egui::DragValue::new(width).range(0.0..=f32::MAX));
+                    ui.add(egui::DragValue::new(height).range(0.0..=f32::MAX));
+                }
+            }
+        });
+        ui.separator();
+
+        let mut use_waves = !self.waves.is_empty();
+        if ui.checkbox(&mut use_waves, "Waves").changed() {
+            self.waves = if use_waves {
+                vec![Wave::new(self.table.clone(), 5, 1.0)]
+            } else {
+                Vec::new()
+            };
+            self.reset();
+        }
+
+        if self.waves.is_empty() {
+            ui.horizontal(|ui| {
+                ui.label("Interval (s)");
+                ui.add(
+                    egui::DragValue::new(&mut self.interval)
+                        .speed(0.05)
+                        .range(0.01..=f32::MAX),
+                );
+            });
+            Self::table_ui(ui, "spawn_table", &mut self.table, prefabs);
+            return;
+        }
+
+        ui.checkbox(&mut self.loop_waves, "Loop waves");
+        if let Some(wave) = self.current_wave() {
+            ui.label(format!(
+                "Wave {} / {}, {} alive",
+                wave + 1,
+                self.waves.len(),
+                self.alive.len()
+            ));
+        } else {
+            ui.label("All waves completed");
+        }
+
+        let mut remove = None;
+        let removable = self.waves.len() > 1;
+        for (index, wave) in self.waves.iter_mut().enumerate() {
+            egui::CollapsingHeader::new(format!("Wave {}", index + 1))
+                .id_salt(("spawn_wave", index))
+                .default_open(true)
+                .show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Count");
+                        ui.add(egui::DragValue::new(&mut wave.count));
+                        ui.label("Interval (s)");
+                        ui.add(
+                            egui::DragValue::new(&mut wave.interval)
+                                .speed(0.05)
+                                .range(0.0..=f32::MAX),
+                        );
+                        ui.label("Delay (s)");
+                        ui.add(
+                            egui::DragValue::new(&mut wave.delay)
+                                .speed(0.05)
+                                .range(0.0..=f32::MAX),
+                        );
+                    });
+                    Self::table_ui(ui, ("spawn_wave_table", index), &mut wave.table, prefabs);
+                    if removable && ui.button("Remove wave").clicked() {
+                        remove = Some(index);
+                    }
+                });
+        }
+        if let Some(index) = remove {
+            self.waves.remove(index);
+            self.reset();
+        }
+        if ui.button("Add wave").clicked() {
+            let last = self.waves.last().cloned();
+            self.waves
+                .push(last.unwrap_or_else(|| Wave::new(Vec::new(), 5, 1.0)));
+        }
+    }
+
+    /// Lignes prefab / poids d'une table de spawn.
+    fn table_ui(
+        ui: &mut egui::Ui,
+        id: impl std::hash::Hash + Copy,
+        table: &mut Vec<SpawnEntry>,
+        prefabs: &PrefabLibrary,
+    ) {
+        let mut remove = None;
+        for (index, entry) in table.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_id_salt((id, index))
+                    .selected_text(entry.prefab.as_str())
+                    .show_ui(ui, |ui| {
+                        for name in prefabs.names() {
+                            if ui.selectable_label(entry.prefab == name, name).clicked() {
+                                entry.prefab = name.to_string();
+                            }
+                        }
+                    });
+                if prefabs.get(&entry.prefab).is_none() {
+                    ui.colored_label(egui::Color32::YELLOW, "missing");
+                }
+                ui.label("Weight");
+                ui.add(
+                    egui::DragValue::new(&mut entry.weight)
+                        .speed(0.1)
+                        .range(0.0..=f32::MAX),
+                );
+                if ui.small_button("x").clicked() {
+                    remove = Some(index);
+                }
+            });
+        }
+        if let Some(index) = remove {
+            table.remove(index);
+        }
+        let first = prefabs.names().next();
+        if ui
+            .add_enabled(first.is_some(), egui::Button::new("Add prefab"))
+            .on_disabled_hover_text("Save an entity as prefab first")
+            .clicked()
+            && let Some(name) = first
+        {
+            table.push(SpawnEntry::new(name, 1.0));
+        }
+    }
+}
+
+/// Prefabs par nom, instanciés par les spawners.
+#[derive(Default, Clone)]
+pub struct PrefabLibrary {
+    prefabs: BTreeMap<String, EntitySnapshot>,
+}
+
+impl PrefabLibrary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, name: impl Into<String>, prefab: EntitySnapshot) {
+        self.prefabs.insert(name.into(), prefab);
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<EntitySnapshot> {
+        self.prefabs.remove(name)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&EntitySnapshot> {
+        self.prefabs.get(name)
+    }
+
+    /// Noms des prefabs, triés.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.prefabs.keys().map(String::as_str)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.prefabs.is_empty()
+    }
+}
+
+impl Scene {
+    /// Fait avancer les `Spawner` de la scène de `delta_time` secondes et instancie leurs
+    /// prefabs (à la racine, à la position globale du spawner plus le décalage de sa zone).
+    /// Un prefab absent de `prefabs` est ignoré avec un avertissement.
+    pub fn update_spawners(
+        &mut self,
+        prefabs: &PrefabLibrary,
+        delta_time: f32,
+    ) -> Vec<SpawnerEvent> {
+        let spawners: Vec<Entity> = self
+            .world
+            .query::<&Spawner>()
+            .iter()
+            .map(|(entity, _)| entity)
+            .collect();
+
+        let mut events = Vec::new();
+        let mut spawned: HashMap<Entity, Vec<Entity>> = HashMap::new();
+        for spawner in spawners {
+            let steps = match self.world.get::<&mut Spawner>(spawner) {
+                Ok(mut component) => {
+                    let world = &self.world;
+                    component.alive.retain(|&entity| world.contains(entity));
+                    component.step(delta_time)
+                }
+                Err(_) => continue,
+            };
+            let origin = self.spawner_origin(spawner);
+
+            for step in steps {
+                match step {
+                    SpawnStep::WaveStarted(wave) => {
+                        events.push(SpawnerEvent::WaveStarted { spawner, wave })
+                    }
+                    SpawnStep::Completed => events.push(SpawnerEvent::WavesCompleted { spawner }),
+                    SpawnStep::Spawn {
+                        prefab,
+                        offset,
+                        wave,
+                    } => {
+                        let Some(snapshot) = prefabs.get(&prefab) else {
+                            log::warn!("Spawner {:?}: unknown prefab {:?}", spawner, prefab);
+                            continue;
+                        };
+                        let entity = self.instantiate(snapshot, None);
+                        let mut transform = snapshot.transform.unwrap_or_default();
+                        transform.position =
+                            Vec3::new(origin.x + offset.x, origin.y + offset.y, origin.z);
+                        let _ = self.world.insert_one(entity, transform);
+
+                        spawned.entry(spawner).or_default().push(entity);
+                        events.push(SpawnerEvent::Spawned {
+                            spawner,
+                            entity,
+                            prefab,
+                            wave,
+                        });
+                    }
+                }
+            }
+        }
+
+        for (spawner, entities) in spawned {
+            if let Ok(mut component) = self.world.get::<&mut Spawner>(spawner) {
+                component.alive.extend(entities);
+            }
+        }
+        events
+    }
+
+    /// Position globale du spawner (locale si la hiérarchie n'a pas été propagée).
+    fn spawner_origin(&self, spawner: Entity) -> Vec3 {
+        if let Ok(global) = self.world.get::<&GlobalTransform>(spawner) {
+            return global.translation();
+        }
+        self.world
+            .get::<&Transform>(spawner)
+            .map(|transform| transform.position)
+            .unwrap_or_else(|_| Vec3::zeros())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spawned(steps: &[SpawnStep]) -> usize {
+        steps
+            .iter()
+            .filter(|step| matches!(step, SpawnStep::Spawn { .. }))
+            .count()
+    }
+
+    #[test]
+    fn continuous_spawner_respects_interval_and_max_alive() {
+        let table = vec![SpawnEntry::new("coin", 1.0), SpawnEntry::new("gem", 0.0)];
+        let mut spawner = Spawner::new(table, 0.5)
+            .with_area(SpawnArea::Circle { radius: 10.0 })
+            .with_max_alive(3);
+
+        assert_eq!(spawned(&spawner.step(0.4)), 0);
+        let steps = spawner.step(0.7);
+        assert_eq!(spawned(&steps), 2);
+        for step in &steps {
+            let SpawnStep::Spawn { prefab, offset, .. } = step else {
+                panic!("unexpected {:?}", step);
+            };
+            assert_eq!(prefab, "coin");
+            assert!(offset.norm() <= 10.0);
+        }
+
+        // 3 en vie : plus rien tant qu'aucune ne meurt
+        spawner.alive = vec![Entity::DANGLING; 3];
+        assert_eq!(spawned(&spawner.step(5.0)), 0);
+        spawner.alive.pop();
+        assert_eq!(spawned(&spawner.step(0.5)), 1);
+    }
+
+    #[test]
+    fn waves_wait_until_cleared() {
+        let mut spawner = Spawner::with_waves(vec![
+            Wave::new(vec![SpawnEntry::new("slime", 1.0)], 2, 1.0),
+            Wave {
+                delay: 2.0,
+                ..Wave::new(vec![SpawnEntry::new("bat", 1.0)], 1, 1.0)
+            },
+        ]);
+
+        let steps = spawner.step(0.0);
+        assert_eq!(steps[0], SpawnStep::WaveStarted(0));
+        assert_eq!(spawned(&steps), 1);
+        assert_eq!(spawned(&spawner.step(1.0)), 1);
+
+        // Vague 0 entièrement créée mais pas encore détruite
+        spawner.alive = vec![Entity::DANGLING; 2];
+        assert!(spawner.step(10.0).is_empty());
+        assert_eq!(spawner.current_wave(), Some(0));
+
+        // Vague 1 après son délai de 2 s
+        spawner.alive.clear();
+        assert!(spawner.step(1.0).is_empty());
+        assert_eq!(spawner.current_wave(), Some(1));
+        assert!(spawner.step(1.5).is_empty());
+        let steps = spawner.step(0.5);
+        assert_eq!(steps[0], SpawnStep::WaveStarted(1));
+        assert_eq!(spawned(&steps), 1);
+
+        assert_eq!(spawner.step(0.0), [SpawnStep::Completed]);
+        assert!(spawner.is_completed() && spawner.step(10.0).is_empty());
+    }
+}
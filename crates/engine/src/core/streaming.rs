@@ -0,0 +1,437 @@
+//! Streaming du monde par proximité de la caméra.
+//!
+//! Le monde est découpé en chunks carrés de `StreamingSettings::chunk_size` unités. Le chunk
+//! (x, y) est décrit par des fichiers d'un dossier du VFS :
+//! - `x_y.entities` : entités au format de `EntitySnapshot::encode`, en coordonnées monde ;
+//! - `x_y.tmx` : tilemap placée au coin du chunk.
+//!
+//! Les chunks proches de la zone visible sont préchargés par un thread (lecture, décodage et
+//! envoi des textures au GPU), puis instanciés dans la scène quand ils entrent dans
+//! `load_distance`. Ils sont détruits au-delà de `unload_distance`, plus grande, pour qu'une
+//! caméra à la frontière ne charge / décharge pas un chunk à chaque frame. Les modifications
+//! faites aux entités d'un chunk ne sont pas sauvegardées au déchargement.
+//!
+//! Le thread est arrêté et joint quand le streamer est détruit, ou pendant l'étape
+//! `JoinWorkers` de l'arrêt du moteur (`register_shutdown`).
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex, mpsc},
+    thread::{self, JoinHandle},
+};
+
+use anyhow::{Context, Result};
+use egui_wgpu::wgpu;
+use hecs::Entity;
+
+use crate::{
+    AssetLoader, EngineHandle, EntitySnapshot, Name, Scene, ShutdownStage, Texture2D, Tilemap,
+    Transform, Vec2, Vec3,
+};
+
+/// Coordonnées d'un chunk dans la grille.
+pub type ChunkCoord = (i32, i32);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StreamingSettings {
+    /// Côté d'un chunk, en unités monde.
+    pub chunk_size: f32,
+    /// Distance (entre la zone visible et le chunk) en deçà de laquelle il est instancié.
+    pub load_distance: f32,
+    /// Distance au-delà de laquelle il est détruit (> `load_distance`).
+    pub unload_distance: f32,
+    /// Distance en deçà de laquelle il est préchargé (>= `load_distance`).
+    pub prefetch_distance: f32,
+}
+
+impl Default for StreamingSettings {
+    fn default() -> Self {
+        Self {
+            chunk_size: 512.0,
+            load_distance: 256.0,
+            unload_distance: 512.0,
+            prefetch_distance: 768.0,
+        }
+    }
+}
+
+impl StreamingSettings {
+    /// Rectangle (min, max) du chunk `coord`.
+    pub fn chunk_rect(&self, (x, y): ChunkCoord) -> (Vec2, Vec2) {
+        let min = Vec2::new(x as f32, y as f32) * self.chunk_size;
+        (min, min + Vec2::new(self.chunk_size, self.chunk_size))
+    }
+
+    /// Chunks qui touchent le rectangle `min`..`max` agrandi de `margin`.
+    pub fn chunks_around(&self, min: Vec2, max: Vec2, margin: f32) -> Vec<ChunkCoord> {
+        let cell = |value: f32| (value / self.chunk_size).floor() as i32;
+        let (x0, x1) = (cell(min.x - margin), cell(max.x + margin));
+        let (y0, y1) = (cell(min.y - margin), cell(max.y + margin));
+        (y0..=y1)
+            .flat_map(|y| (x0..=x1).map(move |x| (x, y)))
+            .collect()
+    }
+}
+
+/// Distance entre deux rectangles (0 s'ils se chevauchent).
+pub fn rect_distance((a_min, a_max): (Vec2, Vec2), (b_min, b_max): (Vec2, Vec2)) -> f32 {
+    let dx = (b_min.x - a_max.x).max(a_min.x - b_max.x).max(0.0);
+    let dy = (b_min.y - a_max.y).max(a_min.y - b_max.y).max(0.0);
+    (dx * dx + dy * dy).sqrt()
+}
+
+/// Chunk d'un fichier du dossier de streaming ("3_-2.tmx" -> (3, -2)).
+pub fn parse_chunk_file(file_name: &str) -> Option<ChunkCoord> {
+    let (stem, extension) = file_name.rsplit_once('.')?;
+    if extension != WorldStreamer::ENTITIES_EXTENSION && extension != "tmx" {
+        return None;
+    }
+    let (x, y) = stem.split_once('_')?;
+    Some((x.parse().ok()?, y.parse().ok()?))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StreamingEvent {
+    Loaded { chunk: ChunkCoord, entities: usize },
+    Unloaded { chunk: ChunkCoord },
+    Failed { chunk: ChunkCoord, error: String },
+}
+
+enum ChunkState {
+    /// Demandé au thread de préchargement.
+    Fetching,
+    /// Préchargé, pas encore dans la scène.
+    Ready(Arc<Vec<EntitySnapshot>>),
+    Loaded {
+        snapshots: Arc<Vec<EntitySnapshot>>,
+        entities: Vec<Entity>,
+    },
+    Failed,
+}
+
+/// Thread de préchargement. `None` dans la file des demandes l'arrête.
+#[derive(Clone)]
+struct StreamingWorker {
+    requests: mpsc::Sender<Option<ChunkCoord>>,
+    thread: Arc<Mutex<Option<JoinHandle<()>>>>,
+}
+
+impl StreamingWorker {
+    /// Arrête le thread (après le chunk en cours) et l'attend. Sans effet s'il est déjà joint.
+    fn stop(&self) {
+        let Some(thread) = self.thread.lock().unwrap().take() else {
+            return;
+        };
+        let _ = self.requests.send(None);
+        if thread.join().is_err() {
+            log::error!("World streaming thread panicked");
+        }
+    }
+}
+
+/// Charge et décharge les chunks d'un dossier du VFS autour de la caméra de la scène.
+pub struct WorldStreamer {
+    dir: String,
+    settings: StreamingSettings,
+    /// Chunks ayant au moins un fichier.
+    available: HashSet<ChunkCoord>,
+    chunks: HashMap<ChunkCoord, ChunkState>,
+    worker: StreamingWorker,
+    results: mpsc::Receiver<(ChunkCoord, Result<Vec<EntitySnapshot>>)>,
+}
+
+impl WorldStreamer {
+    pub const ENTITIES_EXTENSION: &str = "entities";
+
+    /// Liste les chunks de `dir` et démarre le thread de préchargement, qui s'arrête avec
+    /// le streamer. `device` / `queue` servent à créer les textures depuis ce thread.
+    pub fn new(
+        loader: AssetLoader,
+        dir: &str,
+        device: wgpu::Device,
+        queue: wgpu::Queue,
+        settings: StreamingSettings,
+    ) -> Self {
+        let dir = dir.trim_end_matches('/').to_string();
+        let available = loader
+            .vfs()
+            .list_files(&dir)
+            .iter()
+            .filter_map(|path| parse_chunk_file(path.rsplit('/').next().unwrap_or(path)))
+            .collect();
+
+        let (requests, pending) = mpsc::channel::<Option<ChunkCoord>>();
+        let (sender, results) = mpsc::channel();
+        let worker_dir = dir.clone();
+        let thread = thread::spawn(move || {
+            let mut textures = HashMap::new();
+            while let Ok(Some(chunk)) = pending.recv() {
+                let result = load_chunk(
+                    &loader,
+                    &worker_dir,
+                    chunk,
+                    settings,
+                    &device,
+                    &queue,
+                    &mut textures,
+                );
+                if sender.send((chunk, result)).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            dir,
+            settings,
+            available,
+            chunks: HashMap::new(),
+            worker: StreamingWorker {
+                requests,
+                thread: Arc::new(Mutex::new(Some(thread))),
+            },
+            results,
+        }
+    }
+
+    /// Arrête et joint le thread de préchargement pendant l'étape `JoinWorkers` de
+    /// `Engine::shutdown`, même si le streamer est encore vivant à ce moment-là.
+    pub fn register_shutdown(&self, engine: &EngineHandle) {
+        let worker = self.worker.clone();
+        engine.on_shutdown(
+            ShutdownStage::JoinWorkers,
+            format!("world streamer {:?}", self.dir),
+            move || {
+                worker.stop();
+                Ok(())
+            },
+        );
+    }
+
+    pub fn dir(&self) -> &str {
+        &self.dir
+    }
+
+    pub fn settings(&self) -> &StreamingSettings {
+        &self.settings
+    }
+
+    /// Nombre de chunks ayant des fichiers dans le dossier.
+    pub fn available_chunks(&self) -> usize {
+        self.available.len()
+    }
+
+    /// Chunks instanciés dans la scène.
+    pub fn loaded_chunks(&self) -> impl Iterator<Item = ChunkCoord> + '_ {
+        self.chunks
+            .iter()
+            .filter(|(_, state)| matches!(state, ChunkState::Loaded { .. }))
+            .map(|(coord, _)| *coord)
+    }
+
+    pub fn is_loaded(&self, chunk: ChunkCoord) -> bool {
+        matches!(self.chunks.get(&chunk), Some(ChunkState::Loaded { .. }))
+    }
+
+    /// À appeler chaque frame : précharge, instancie et détruit les chunks selon la zone
+    /// visible de `scene.camera`.
+    pub fn update(&mut self, scene: &mut Scene) -> Vec<StreamingEvent> {
+        let mut events = Vec::new();
+        while let Ok((chunk, result)) = self.results.try_recv() {
+            // Un chunk abandonné pendant son chargement n'est plus `Fetching`
+            if !matches!(self.chunks.get(&chunk), Some(ChunkState::Fetching)) {
+                continue;
+            }
+            let state = match result {
+                Ok(snapshots) => ChunkState::Ready(Arc::new(snapshots)),
+                Err(e) => {
+                    log::error!("Failed to stream chunk {:?}: {:#}", chunk, e);
+                    events.push(StreamingEvent::Failed {
+                        chunk,
+                        error: format!("{:#}", e),
+                    });
+                    ChunkState::Failed
+                }
+            };
+            self.chunks.insert(chunk, state);
+        }
+
+        let (a, b) = scene.camera.visible_rect();
+        let view = (a.inf(&b), a.sup(&b));
+        let settings = self.settings;
+        for chunk in settings.chunks_around(view.0, view.1, settings.prefetch_distance) {
+            if self.available.contains(&chunk)
+                && !self.chunks.contains_key(&chunk)
+                && rect_distance(view, settings.chunk_rect(chunk)) <= settings.prefetch_distance
+                && self.worker.requests.send(Some(chunk)).is_ok()
+            {
+                self.chunks.insert(chunk, ChunkState::Fetching);
+            }
+        }
+
+        let forget_distance = settings.prefetch_distance.max(settings.unload_distance);
+        let coords: Vec<ChunkCoord> = self.chunks.keys().copied().collect();
+        for chunk in coords {
+            let distance = rect_distance(view, settings.chunk_rect(chunk));
+            let Some(state) = self.chunks.remove(&chunk) else {
+                continue;
+            };
+            let state = match state {
+                ChunkState::Ready(snapshots) if distance <= settings.load_distance => {
+                    let entities: Vec<Entity> = snapshots
+                        .iter()
+                        .map(|snapshot| scene.instantiate(snapshot, None))
+                        .collect();
+                    events.push(StreamingEvent::Loaded {
+                        chunk,
+                        entities: entities.len(),
+                    });
+                    ChunkState::Loaded {
+                        snapshots,
+                        entities,
+                    }
+                }
+                ChunkState::Loaded {
+                    snapshots,
+                    entities,
+                } if distance > settings.unload_distance => {
+                    for entity in entities {
+                        scene.despawn(entity);
+                    }
+                    events.push(StreamingEvent::Unloaded { chunk });
+                    ChunkState::Ready(snapshots)
+                }
+                state @ ChunkState::Loaded { .. } => state,
+                // Trop loin : les données préchargées sont libérées
+                _ if distance > forget_distance => continue,
+                state => state,
+            };
+            self.chunks.insert(chunk, state);
+        }
+        events
+    }
+
+    /// Recharge le chunk du fichier `path` (chemin VFS, ex: modifié sur le disque) : ses
+    /// entités sont détruites et il sera relu au prochain `update`. Retourne `false` si
+    /// `path` n'est pas un fichier de chunk de ce streamer.
+    pub fn reload_file(&mut self, path: &str, scene: &mut Scene) -> bool {
+        let Some((dir, file_name)) = path.rsplit_once('/') else {
+            return false;
+        };
+        if dir != self.dir {
+            return false;
+        }
+        let Some(chunk) = parse_chunk_file(file_name) else {
+            return false;
+        };
+        self.available.insert(chunk);
+        if let Some(ChunkState::Loaded { entities, .. }) = self.chunks.remove(&chunk) {
+            for entity in entities {
+                scene.despawn(entity);
+            }
+        }
+        true
+    }
+
+    /// Détruit les entités de tous les chunks chargés et oublie les chunks préchargés.
+    pub fn unload_all(&mut self, scene: &mut Scene) {
+        for (_, state) in self.chunks.drain() {
+            if let ChunkState::Loaded { entities, .. } = state {
+                for entity in entities {
+                    scene.despawn(entity);
+                }
+            }
+        }
+    }
+}
+
+impl Drop for WorldStreamer {
+    fn drop(&mut self) {
+        self.worker.stop();
+    }
+}
+
+/// Lit les fichiers du chunk (thread de préchargement). `textures` évite de recharger une
+/// texture partagée par plusieurs chunks.
+fn load_chunk(
+    loader: &AssetLoader,
+    dir: &str,
+    chunk: ChunkCoord,
+    settings: StreamingSettings,
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    textures: &mut HashMap<(String, bool), Arc<Texture2D>>,
+) -> Result<Vec<EntitySnapshot>> {
+    let (x, y) = chunk;
+    let base = format!("{}/{}_{}", dir, x, y);
+    let mut snapshots = Vec::new();
+
+    let entities_path = format!("{}.{}", base, WorldStreamer::ENTITIES_EXTENSION);
+    if loader.vfs().exists(&entities_path) {
+        let text = loader.load_string(&entities_path)?;
+        let load_texture = |path: &str, linear: bool| -> Result<Arc<Texture2D>> {
+            if let Some(texture) = textures.get(&(path.to_string(), linear)) {
+                return Ok(texture.clone());
+            }
+            let texture = Arc::new(if linear {
+                loader.load_texture_linear(path, device, queue)?
+            } else {
+                loader.load_texture(path, device, queue)?
+            });
+            textures.insert((path.to_string(), linear), texture.clone());
+            Ok(texture)
+        };
+        snapshots = EntitySnapshot::parse(&text, load_texture)
+            .with_context(|| format!("failed to parse {:?}", entities_path))?;
+    }
+
+    let map_path = format!("{}.tmx", base);
+    if loader.vfs().exists(&map_path) {
+        let map = Tilemap::load_tmx(loader, &map_path)?;
+        let (min, _) = settings.chunk_rect(chunk);
+        snapshots.push(EntitySnapshot {
+            name: Some(Name::new(format!("Chunk {}_{}", x, y))),
+            transform: Some(Transform {
+                position: Vec3::new(min.x, min.y, 0.0),
+                ..Default::default()
+            }),
+            tilemap: Some(map),
+            ..Default::default()
+        });
+    }
+    Ok(snapshots)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunks_are_selected_by_distance_to_the_view() {
+        assert_eq!(parse_chunk_file("3_-2.tmx"), Some((3, -2)));
+        assert_eq!(parse_chunk_file("0_0.entities"), Some((0, 0)));
+        assert_eq!(parse_chunk_file("0_0.png"), None);
+        assert_eq!(parse_chunk_file("readme.tmx"), None);
+
+        let settings = StreamingSettings {
+            chunk_size: 100.0,
+            ..Default::default()
+        };
+        assert_eq!(
+            settings.chunk_rect((-1, 2)),
+            (Vec2::new(-100.0, 200.0), Vec2::new(0.0, 300.0))
+        );
+
+        let view = (Vec2::new(10.0, 10.0), Vec2::new(90.0, 90.0));
+        assert_eq!(rect_distance(view, settings.chunk_rect((0, 0))), 0.0);
+        assert_eq!(rect_distance(view, settings.chunk_rect((1, 0))), 10.0);
+        assert_eq!(rect_distance(view, settings.chunk_rect((-2, 0))), 110.0);
+        let corner = rect_distance(view, settings.chunk_rect((1, 1)));
+        assert!((corner - 200f32.sqrt()).abs() < 1e-4);
+
+        let around = settings.chunks_around(view.0, view.1, 20.0);
+        assert_eq!(around.len(), 9);
+        assert!(around.contains(&(-1, -1)) && around.contains(&(1, 1)));
+        assert_eq!(settings.chunks_around(view.0, view.1, 0.0), [(0, 0)]);
+    }
+}
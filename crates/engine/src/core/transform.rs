@@ -1,4 +1,4 @@
-use crate::{Mat4, Vec3};
+use crate::{Mat4, Vec2, Vec3};
 
 #[derive(Debug, Clone, Copy)]
 pub struct Transform {
@@ -27,4 +27,166 @@ impl Transform {
 
         translation * rotation_y * rotation_x * rotation_z * scale
     }
+
+    /// Matrice de dessin d'une entité : monde (`global`) si la hiérarchie a été propagée,
+    /// locale sinon, suivie de sa déformation `affine` éventuelle.
+    pub fn draw_matrix(&self, global: Option<&GlobalTransform>, affine: Option<&Affine2D>) -> Mat4 {
+        let model = global
+            .map(GlobalTransform::matrix)
+            .unwrap_or_else(|| self.matrix());
+        affine.map_or(model, |affine| model * affine.matrix())
+    }
+}
+
+/// Déformation 2D affine quelconque (matrice 2x3) appliquée au sprite d'une entité après
+/// son `Transform` : cisaillement, écrasement, retournement de carte en faux 3D, ombre
+/// projetée... Elle agit dans l'espace local du quad (0..`Vertex::QUAD_SIZE` pixels) et
+/// n'est pas héritée par les enfants.
+///
+/// ```ignore
+/// // Retournement de carte autour de son centre, à mi-course
+/// let flip = Affine2D::card_flip(0.5).around(Vec2::new(50.0, 50.0));
+/// scene.world.insert_one(card, flip)?;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Affine2D {
+    /// Lignes de la matrice : `[[a, c, tx], [b, d, ty]]`, soit
+    /// `x' = a * x + c * y + tx` et `y' = b * x + d * y + ty`.
+    pub rows: [[f32; 3]; 2],
+}
+
+impl Default for Affine2D {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Affine2D {
+    pub const IDENTITY: Self = Self {
+        rows: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0]],
+    };
+
+    pub const fn new(rows: [[f32; 3]; 2]) -> Self {
+        Self { rows }
+    }
+
+    pub fn translation(offset: Vec2) -> Self {
+        Self::new([[1.0, 0.0, offset.x], [0.0, 1.0, offset.y]])
+    }
+
+    pub fn scale(x: f32, y: f32) -> Self {
+        Self::new([[x, 0.0, 0.0], [0.0, y, 0.0]])
+    }
+
+    /// Cisaillement : `x` (radians) penche les verticales, `y` les horizontales.
+    pub fn skew(x: f32, y: f32) -> Self {
+        Self::new([[1.0, x.tan(), 0.0], [y.tan(), 1.0, 0.0]])
+    }
+
+    /// Écrasement / étirement à aire constante : `amount` > 0 écrase (plus large, moins
+    /// haut), < 0 étire.
+    pub fn squash(amount: f32) -> Self {
+        let width = (1.0 + amount).max(0.01);
+        Self::scale(width, 1.0 / width)
+    }
+
+    /// Retournement de carte en faux 3D autour de l'axe vertical : `progress` de 0 (face)
+    /// à 1 (dos, miroir) ; à 0.5 la carte est vue par la tranche.
+    pub fn card_flip(progress: f32) -> Self {
+        Self::scale((progress * std::f32::consts::PI).cos(), 1.0)
+    }
+
+    /// Ombre projetée au sol : aplatie verticalement de `height` (0..1) et penchée de
+    /// `lean` radians, à placer autour des pieds du sprite avec `around`.
+    pub fn shadow(height: f32, lean: f32) -> Self {
+        Self::scale(1.0, height).then(Self::skew(lean, 0.0))
+    }
+
+    /// `self` puis `next`.
+    pub fn then(self, next: Self) -> Self {
+        let [[a, c, tx], [b, d, ty]] = self.rows;
+        let [[na, nc, ntx], [nb, nd, nty]] = next.rows;
+        Self::new([
+            [na * a + nc * b, na * c + nc * d, na * tx + nc * ty + ntx],
+            [nb * a + nd * b, nb * c + nd * d, nb * tx + nd * ty + nty],
+        ])
+    }
+
+    /// La même déformation, avec `pivot` (espace local) comme point fixe.
+    pub fn around(self, pivot: Vec2) -> Self {
+        Self::translation(-pivot)
+            .then(self)
+            .then(Self::translation(pivot))
+    }
+
+    pub fn transform_point(&self, point: Vec2) -> Vec2 {
+        let [[a, c, tx], [b, d, ty]] = self.rows;
+        Vec2::new(
+            a * point.x + c * point.y + tx,
+            b * point.x + d * point.y + ty,
+        )
+    }
+
+    /// Matrice 4x4 équivalente (z inchangé), à multiplier à droite de la matrice du modèle.
+    pub fn matrix(&self) -> Mat4 {
+        let [[a, c, tx], [b, d, ty]] = self.rows;
+        Mat4::new(
+            a, c, 0.0, tx, //
+            b, d, 0.0, ty, //
+            0.0, 0.0, 1.0, 0.0, //
+            0.0, 0.0, 0.0, 1.0,
+        )
+    }
+}
+
+/// Transformation monde d'une entité, calculée chaque frame par
+/// `Scene::propagate_transforms` (parent * local). Ne pas modifier à la main :
+/// éditer le `Transform` local à la place.
+#[derive(Debug, Clone, Copy)]
+pub struct GlobalTransform(pub Mat4);
+
+impl Default for GlobalTransform {
+    fn default() -> Self {
+        Self(Mat4::identity())
+    }
+}
+
+impl GlobalTransform {
+    pub fn matrix(&self) -> Mat4 {
+        self.0
+    }
+
+    /// Position monde (colonne de translation).
+    pub fn translation(&self) -> Vec3 {
+        Vec3::new(self.0[(0, 3)], self.0[(1, 3)], self.0[(2, 3)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Vector4;
+
+    use super::*;
+
+    #[test]
+    fn affine_composes_like_its_matrix() {
+        let pivot = Vec2::new(50.0, 50.0);
+        let flip = Affine2D::card_flip(1.0).around(pivot);
+        let mirrored = flip.transform_point(Vec2::new(0.0, 10.0));
+        assert!((mirrored - Vec2::new(100.0, 10.0)).norm() < 1e-4);
+        assert!((flip.transform_point(pivot) - pivot).norm() < 1e-4);
+
+        let deform = Affine2D::skew(0.3, 0.0)
+            .then(Affine2D::squash(0.5))
+            .then(Affine2D::translation(Vec2::new(4.0, -2.0)));
+        let point = Vec2::new(12.0, 30.0);
+        let expected = deform.transform_point(point);
+        let moved = deform.matrix() * Vector4::new(point.x, point.y, 0.0, 1.0);
+        assert!((Vec2::new(moved.x, moved.y) - expected).norm() < 1e-4);
+
+        let transform = Transform::default();
+        let model = transform.draw_matrix(None, Some(&deform));
+        assert!((model - deform.matrix()).norm() < 1e-6);
+        assert_eq!(transform.draw_matrix(None, None), Mat4::identity());
+    }
 }
@@ -0,0 +1,511 @@
+//! Courbes 1D (valeur en fonction du temps) et leur éditeur egui.
+//!
+//! Une `Curve` est une suite de clés (temps, valeur, tangentes) interpolées en Hermite
+//! cubique. Elle sert de profil générique : easing, lissage de caméra, paramètres qui
+//! évoluent dans le temps... Le format texte (`encode`/`decode`) permet de la stocker
+//! comme asset via le VFS.
+
+use anyhow::{Context, Result, anyhow};
+
+use crate::Vfs;
+
+/// Une clé de la courbe. Les tangentes sont des pentes (valeur / temps).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CurveKey {
+    pub time: f32,
+    pub value: f32,
+    /// Pente à gauche de la clé.
+    pub in_tangent: f32,
+    /// Pente à droite de la clé.
+    pub out_tangent: f32,
+}
+
+impl CurveKey {
+    pub fn new(time: f32, value: f32) -> Self {
+        Self {
+            time,
+            value,
+            in_tangent: 0.0,
+            out_tangent: 0.0,
+        }
+    }
+
+    /// Clé avec la même pente des deux côtés (tangente continue).
+    pub fn with_tangent(time: f32, value: f32, tangent: f32) -> Self {
+        Self {
+            time,
+            value,
+            in_tangent: tangent,
+            out_tangent: tangent,
+        }
+    }
+}
+
+/// Courbes prédéfinies, proposées par l'éditeur.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CurvePreset {
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+    Constant,
+}
+
+impl CurvePreset {
+    pub const ALL: [CurvePreset; 5] = [
+        CurvePreset::Linear,
+        CurvePreset::EaseIn,
+        CurvePreset::EaseOut,
+        CurvePreset::EaseInOut,
+        CurvePreset::Constant,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CurvePreset::Linear => "Linear",
+            CurvePreset::EaseIn => "Ease in",
+            CurvePreset::EaseOut => "Ease out",
+            CurvePreset::EaseInOut => "Ease in-out",
+            CurvePreset::Constant => "Constant",
+        }
+    }
+
+    /// Courbe de 0 à 1 sur [0, 1] (constante à 1 pour `Constant`).
+    pub fn curve(self) -> Curve {
+        let keys = match self {
+            CurvePreset::Linear => [
+                CurveKey::with_tangent(0.0, 0.0, 1.0),
+                CurveKey::with_tangent(1.0, 1.0, 1.0),
+            ],
+            CurvePreset::EaseIn => [
+                CurveKey::with_tangent(0.0, 0.0, 0.0),
+                CurveKey::with_tangent(1.0, 1.0, 2.0),
+            ],
+            CurvePreset::EaseOut => [
+                CurveKey::with_tangent(0.0, 0.0, 2.0),
+                CurveKey::with_tangent(1.0, 1.0, 0.0),
+            ],
+            CurvePreset::EaseInOut => [CurveKey::new(0.0, 0.0), CurveKey::new(1.0, 1.0)],
+            CurvePreset::Constant => [CurveKey::new(0.0, 1.0), CurveKey::new(1.0, 1.0)],
+        };
+        Curve::from_keys(keys)
+    }
+}
+
+/// Courbe à clés triées par temps. Hors de l'intervalle des clés, la valeur est bornée
+/// à celle de la première / dernière clé. Une courbe vide vaut 0.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Curve {
+    keys: Vec<CurveKey>,
+}
+
+impl Curve {
+    /// En-tête du format texte (une clé par ligne : `time value in_tangent out_tangent`).
+    const HEADER: &str = "curve v1";
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_keys(keys: impl IntoIterator<Item = CurveKey>) -> Self {
+        let mut curve = Self {
+            keys: keys.into_iter().collect(),
+        };
+        curve.sort();
+        curve
+    }
+
+    pub fn keys(&self) -> &[CurveKey] {
+        &self.keys
+    }
+
+    fn sort(&mut self) {
+        self.keys.sort_by(|a, b| a.time.total_cmp(&b.time));
+    }
+
+    /// Ajoute une clé et retourne son index (après tri).
+    pub fn add_key(&mut self, key: CurveKey) -> usize {
+        let index = self.keys.partition_point(|k| k.time <= key.time);
+        self.keys.insert(index, key);
+        index
+    }
+
+    /// Remplace la clé `index` et retourne son nouvel index (le temps a pu changer).
+    pub fn set_key(&mut self, index: usize, key: CurveKey) -> usize {
+        self.keys.remove(index);
+        self.add_key(key)
+    }
+
+    pub fn remove_key(&mut self, index: usize) -> Option<CurveKey> {
+        (index < self.keys.len()).then(|| self.keys.remove(index))
+    }
+
+    /// Valeur de la courbe au temps `time`.
+    pub fn evaluate(&self, time: f32) -> f32 {
+        let (Some(first), Some(last)) = (self.keys.first(), self.keys.last()) else {
+            return 0.0;
+        };
+        if time <= first.time {
+            return first.value;
+        }
+        if time >= last.time {
+            return last.value;
+        }
+
+        let next = self.keys.partition_point(|k| k.time <= time);
+        let (k0, k1) = (&self.keys[next - 1], &self.keys[next]);
+        let dt = k1.time - k0.time;
+        if dt <= f32::EPSILON {
+            return k1.value;
+        }
+
+        // Hermite cubique
+        let s = (time - k0.time) / dt;
+        let s2 = s * s;
+        let s3 = s2 * s;
+        let h00 = 2.0 * s3 - 3.0 * s2 + 1.0;
+        let h10 = s3 - 2.0 * s2 + s;
+        let h01 = -2.0 * s3 + 3.0 * s2;
+        let h11 = s3 - s2;
+
+        h00 * k0.value + h10 * dt * k0.out_tangent + h01 * k1.value + h11 * dt * k1.in_tangent
+    }
+
+    /// Sérialise la courbe au format texte.
+    pub fn encode(&self) -> String {
+        let mut text = format!("{}\n", Self::HEADER);
+        for key in &self.keys {
+            text.push_str(&format!(
+                "{} {} {} {}\n",
+                key.time, key.value, key.in_tangent, key.out_tangent
+            ));
+        }
+        text
+    }
+
+    pub fn decode(text: &str) -> Result<Self> {
+        let mut lines = text
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty() && !l.starts_with('#'));
+
+        if lines.next() != Some(Self::HEADER) {
+            return Err(anyhow!("missing {:?} header", Self::HEADER));
+        }
+
+        let keys = lines
+            .map(|line| {
+                let fields = line
+                    .split_whitespace()
+                    .map(str::parse::<f32>)
+                    .collect::<Result<Vec<_>, _>>()
+                    .with_context(|| format!("invalid curve key {:?}", line))?;
+                match fields[..] {
+                    [time, value, in_tangent, out_tangent] => Ok(CurveKey {
+                        time,
+                        value,
+                        in_tangent,
+                        out_tangent,
+                    }),
+                    _ => Err(anyhow!("expected 4 numbers per curve key, got {:?}", line)),
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self::from_keys(keys))
+    }
+
+    /// Charge une courbe depuis le VFS (ex: "assets/curves/camera_smoothing.curve").
+    pub fn load(vfs: &Vfs, path: &str) -> Result<Self> {
+        let text = vfs.read_to_string(path)?;
+        Self::decode(&text).with_context(|| format!("failed to parse curve {:?}", path))
+    }
+
+    /// Écrit la courbe dans le VFS (mount writable).
+    pub fn save(&self, vfs: &Vfs, path: &str) -> Result<()> {
+        vfs.write_bytes(path, self.encode().as_bytes())
+    }
+}
+
+/// Éditeur egui réutilisable pour une `Curve`.
+///
+/// - glisser une clé la déplace, double-cliquer dans le graphe ajoute une clé ;
+/// - clic droit sur une clé la supprime ;
+/// - la clé sélectionnée affiche ses poignées de tangentes (glissables) ;
+/// - le menu "Preset" remplace la courbe.
+pub struct CurveEditor {
+    id: egui::Id,
+    time_range: (f32, f32),
+    value_range: (f32, f32),
+    height: f32,
+}
+
+impl CurveEditor {
+    const KEY_RADIUS: f32 = 4.0;
+    /// Longueur (pixels) des poignées de tangentes.
+    const HANDLE_LENGTH: f32 = 30.0;
+    const SAMPLES: usize = 96;
+
+    pub fn new(id_salt: impl std::hash::Hash) -> Self {
+        Self {
+            id: egui::Id::new(id_salt),
+            time_range: (0.0, 1.0),
+            value_range: (0.0, 1.0),
+            height: 160.0,
+        }
+    }
+
+    /// Intervalle de temps affiché (défaut : 0..1).
+    pub fn time_range(mut self, min: f32, max: f32) -> Self {
+        self.time_range = (min, max);
+        self
+    }
+
+    /// Intervalle de valeurs affiché (défaut : 0..1).
+    pub fn value_range(mut self, min: f32, max: f32) -> Self {
+        self.value_range = (min, max);
+        self
+    }
+
+    pub fn height(mut self, height: f32) -> Self {
+        self.height = height;
+        self
+    }
+
+    /// Dessine l'éditeur. Retourne `true` si la courbe a été modifiée.
+    pub fn show(&self, ui: &mut egui::Ui, curve: &mut Curve) -> bool {
+        let mut changed = false;
+        let selected_id = self.id.with("selected");
+        let mut selected: Option<usize> = ui.data(|d| d.get_temp(selected_id)).flatten();
+
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_salt(self.id.with("preset"))
+                .selected_text("Preset")
+                .show_ui(ui, |ui| {
+                    for preset in CurvePreset::ALL {
+                        if ui.selectable_label(false, preset.name()).clicked() {
+                            let mut preset_curve = preset.curve();
+                            self.fit_preset(&mut preset_curve);
+                            *curve = preset_curve;
+                            selected = None;
+                            changed = true;
+                        }
+                    }
+                });
+            ui.weak("Double-click: add key, right-click: remove");
+        });
+
+        let size = egui::vec2(ui.available_width(), self.height);
+        let (response, painter) = ui.allocate_painter(size, egui::Sense::click());
+        let rect = response.rect;
+        let visuals = ui.visuals().clone();
+
+        painter.rect_filled(rect, 2.0, visuals.extreme_bg_color);
+        for i in 1..4 {
+            let f = i as f32 / 4.0;
+            let stroke = egui::Stroke::new(1.0, visuals.faint_bg_color);
+            painter.hline(rect.x_range(), egui::lerp(rect.y_range(), f), stroke);
+            painter.vline(egui::lerp(rect.x_range(), f), rect.y_range(), stroke);
+        }
+
+        // Ajout d'une clé
+        if response.double_clicked()
+            && let Some(pos) = response.interact_pointer_pos()
+        {
+            let (time, value) = self.screen_to_curve(rect, pos);
+            let tangent = self.slope_at(curve, time);
+            selected = Some(curve.add_key(CurveKey::with_tangent(time, value, tangent)));
+            changed = true;
+        }
+
+        // Clés (itération par index : l'ordre change quand une clé est déplacée)
+        let mut index = 0;
+        while index < curve.keys.len() {
+            let key = curve.keys[index];
+            let center = self.curve_to_screen(rect, key.time, key.value);
+            let key_rect =
+                egui::Rect::from_center_size(center, egui::Vec2::splat(Self::KEY_RADIUS * 3.0));
+            let key_response = ui.interact(
+                key_rect,
+                self.id.with(("key", index)),
+                egui::Sense::click_and_drag(),
+            );
+
+            if key_response.secondary_clicked() {
+                curve.remove_key(index);
+                selected = None;
+                changed = true;
+                continue;
+            }
+            if key_response.clicked() || key_response.drag_started() {
+                selected = Some(index);
+            }
+            if key_response.dragged()
+                && let Some(pos) = key_response.interact_pointer_pos()
+            {
+                let (time, value) = self.screen_to_curve(rect, pos);
+                let new_index = curve.set_key(index, CurveKey { time, value, ..key });
+                selected = Some(new_index);
+                changed = true;
+            }
+            index += 1;
+        }
+
+        // Tangentes de la clé sélectionnée
+        if let Some(index) = selected.filter(|&i| i < curve.keys.len()) {
+            let key = curve.keys[index];
+            let center = self.curve_to_screen(rect, key.time, key.value);
+
+            for (side, slope) in [(-1.0, key.in_tangent), (1.0, key.out_tangent)] {
+                let handle = center + self.handle_offset(rect, slope) * side;
+                painter.line_segment(
+                    [center, handle],
+                    egui::Stroke::new(1.0, visuals.weak_text_color()),
+                );
+                painter.circle_filled(handle, Self::KEY_RADIUS * 0.75, visuals.weak_text_color());
+
+                let handle_rect =
+                    egui::Rect::from_center_size(handle, egui::Vec2::splat(Self::KEY_RADIUS * 3.0));
+                let handle_response = ui.interact(
+                    handle_rect,
+                    self.id.with(("tangent", index, side as i32)),
+                    egui::Sense::drag(),
+                );
+                if handle_response.dragged()
+                    && let Some(pos) = handle_response.interact_pointer_pos()
+                {
+                    let slope = self.screen_slope(rect, (pos - center) * side);
+                    let key = &mut curve.keys[index];
+                    if side < 0.0 {
+                        key.in_tangent = slope;
+                    } else {
+                        key.out_tangent = slope;
+                    }
+                    changed = true;
+                }
+            }
+        }
+
+        // Courbe
+        let (t0, t1) = self.time_range;
+        let points = (0..=Self::SAMPLES)
+            .map(|i| {
+                let time = egui::lerp(t0..=t1, i as f32 / Self::SAMPLES as f32);
+                self.curve_to_screen(rect, time, curve.evaluate(time))
+            })
+            .collect();
+        painter.line(points, egui::Stroke::new(2.0, visuals.selection.bg_fill));
+
+        for (index, key) in curve.keys.iter().enumerate() {
+            let color = if selected == Some(index) {
+                visuals.strong_text_color()
+            } else {
+                visuals.text_color()
+            };
+            painter.circle_filled(
+                self.curve_to_screen(rect, key.time, key.value),
+                Self::KEY_RADIUS,
+                color,
+            );
+        }
+
+        ui.data_mut(|d| d.insert_temp(selected_id, selected));
+        changed
+    }
+
+    /// Ramène une courbe prédéfinie (0..1 x 0..1) aux intervalles de l'éditeur.
+    fn fit_preset(&self, curve: &mut Curve) {
+        let (t0, t1) = self.time_range;
+        let (v0, v1) = self.value_range;
+        let slope_scale = (v1 - v0) / (t1 - t0).max(f32::EPSILON);
+        for key in &mut curve.keys {
+            key.time = t0 + key.time * (t1 - t0);
+            key.value = v0 + key.value * (v1 - v0);
+            key.in_tangent *= slope_scale;
+            key.out_tangent *= slope_scale;
+        }
+    }
+
+    /// Pente de la courbe à `time` (différence finie), pour une nouvelle clé sans cassure.
+    fn slope_at(&self, curve: &Curve, time: f32) -> f32 {
+        let h = (self.time_range.1 - self.time_range.0)
+            .abs()
+            .max(f32::EPSILON)
+            * 1e-3;
+        (curve.evaluate(time + h) - curve.evaluate(time - h)) / (2.0 * h)
+    }
+
+    fn curve_to_screen(&self, rect: egui::Rect, time: f32, value: f32) -> egui::Pos2 {
+        let (t0, t1) = self.time_range;
+        let (v0, v1) = self.value_range;
+        egui::pos2(
+            egui::remap(time, t0..=t1, rect.x_range()),
+            egui::remap(value, v0..=v1, rect.bottom()..=rect.top()),
+        )
+    }
+
+    fn screen_to_curve(&self, rect: egui::Rect, pos: egui::Pos2) -> (f32, f32) {
+        let (t0, t1) = self.time_range;
+        let (v0, v1) = self.value_range;
+        let pos = rect.clamp(pos);
+        (
+            egui::remap(pos.x, rect.x_range(), t0..=t1),
+            egui::remap(pos.y, rect.bottom()..=rect.top(), v0..=v1),
+        )
+    }
+
+    /// Pixels par unité de temps / de valeur.
+    fn scale(&self, rect: egui::Rect) -> egui::Vec2 {
+        let (t0, t1) = self.time_range;
+        let (v0, v1) = self.value_range;
+        egui::vec2(
+            rect.width() / (t1 - t0).abs().max(f32::EPSILON),
+            rect.height() / (v1 - v0).abs().max(f32::EPSILON),
+        )
+    }
+
+    /// Vecteur écran (vers la droite) d'une poignée de pente `slope`.
+    fn handle_offset(&self, rect: egui::Rect, slope: f32) -> egui::Vec2 {
+        let scale = self.scale(rect);
+        egui::vec2(scale.x, -slope * scale.y).normalized() * Self::HANDLE_LENGTH
+    }
+
+    /// Pente correspondant à un vecteur écran (orienté vers la droite).
+    fn screen_slope(&self, rect: egui::Rect, offset: egui::Vec2) -> f32 {
+        let scale = self.scale(rect);
+        let dx = offset.x.max(1.0) / scale.x;
+        let dy = -offset.y / scale.y;
+        dy / dx
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evaluate_interpolates_and_clamps() {
+        let linear = CurvePreset::Linear.curve();
+        assert!((linear.evaluate(0.25) - 0.25).abs() < 1e-5);
+        assert_eq!(linear.evaluate(-1.0), 0.0);
+        assert_eq!(linear.evaluate(2.0), 1.0);
+
+        let ease = CurvePreset::EaseInOut.curve();
+        assert!(ease.evaluate(0.1) < 0.1);
+        assert!((ease.evaluate(0.5) - 0.5).abs() < 1e-5);
+
+        assert_eq!(Curve::new().evaluate(0.5), 0.0);
+    }
+
+    #[test]
+    fn encode_decode_roundtrip() {
+        let mut curve = CurvePreset::EaseOut.curve();
+        curve.add_key(CurveKey::with_tangent(0.5, 0.8, -0.25));
+
+        let decoded = Curve::decode(&curve.encode()).unwrap();
+        assert_eq!(decoded, curve);
+        assert_eq!(decoded.keys()[1].time, 0.5);
+
+        assert!(Curve::decode("0 0 0 0").is_err());
+    }
+}
@@ -3,6 +3,14 @@ use std::time::{Duration, Instant};
 pub struct DeltaTimer {
     last_frame_time: Instant,
     delta_time: f32,
+    /// Durée réelle de la frame, sans la borne appliquée à `delta_time`.
+    frame_time: Duration,
+    /// Temps de jeu écoulé pendant la frame : `delta_time` ralenti par `time_scale`, nul
+    /// pendant un hit-stop.
+    scaled_delta_time: f32,
+    time_scale: f32,
+    /// Temps réel restant du hit-stop en cours (secondes).
+    hit_stop: f32,
     frame_count: u64,
     fps_timer: Instant,
     fps: f32,
@@ -14,6 +22,10 @@ impl DeltaTimer {
         Self {
             last_frame_time: now,
             delta_time: 0.0,
+            frame_time: Duration::ZERO,
+            scaled_delta_time: 0.0,
+            time_scale: 1.0,
+            hit_stop: 0.0,
             frame_count: 0,
             fps_timer: now,
             fps: 0.0,
@@ -24,8 +36,10 @@ impl DeltaTimer {
         let current_time = Instant::now();
         let duration = current_time.duration_since(self.last_frame_time);
 
+        self.frame_time = duration;
         self.delta_time = duration.as_secs_f32();
         self.delta_time = self.delta_time.min(1.0 / 30.0);
+        self.advance_scaled(self.delta_time);
 
         self.last_frame_time = current_time;
         self.frame_count += 1;
@@ -39,11 +53,71 @@ impl DeltaTimer {
         self.delta_time
     }
 
+    /// Calcule le temps de jeu de la frame : le hit-stop consomme le temps réel en premier,
+    /// le reste est ralenti par `time_scale`.
+    fn advance_scaled(&mut self, delta_time: f32) {
+        let frozen = delta_time.min(self.hit_stop);
+        self.hit_stop -= frozen;
+        self.scaled_delta_time = (delta_time - frozen) * self.time_scale;
+    }
+
+    /// Temps réel de la frame (UI, caméra de l'éditeur, effets d'écran).
     pub fn delta_time(&self) -> f32 {
         self.delta_time
     }
 
+    /// Durée réelle de la frame, non bornée (statistiques, voir `FrameStats::record_timer`).
+    pub fn frame_time(&self) -> Duration {
+        self.frame_time
+    }
+
+    /// Temps de jeu de la frame (simulation, animations).
+    pub fn scaled_delta_time(&self) -> f32 {
+        self.scaled_delta_time
+    }
+
+    pub fn time_scale(&self) -> f32 {
+        self.time_scale
+    }
+
+    /// Ralentit (< 1) ou accélère (> 1) le temps de jeu.
+    pub fn set_time_scale(&mut self, time_scale: f32) {
+        self.time_scale = time_scale.max(0.0);
+    }
+
+    /// Fige le temps de jeu pendant `duration` de temps réel (impact, coup critique). Un
+    /// hit-stop déjà en cours est prolongé s'il est plus court, pas cumulé.
+    pub fn hit_stop(&mut self, duration: Duration) {
+        self.hit_stop = self.hit_stop.max(duration.as_secs_f32());
+    }
+
+    pub fn is_hit_stopped(&self) -> bool {
+        self.hit_stop > 0.0
+    }
+
     pub fn fps(&self) -> f32 {
         self.fps
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hit_stop_freezes_scaled_time() {
+        let mut timer = DeltaTimer::new();
+        timer.set_time_scale(0.5);
+        timer.hit_stop(Duration::from_millis(30));
+        timer.hit_stop(Duration::from_millis(10));
+
+        timer.advance_scaled(0.02);
+        assert_eq!(timer.scaled_delta_time(), 0.0);
+        assert!(timer.is_hit_stopped());
+
+        // Fin du hit-stop en cours de frame : seul le reste compte, ralenti
+        timer.advance_scaled(0.02);
+        assert!((timer.scaled_delta_time() - 0.005).abs() < 1e-6);
+        assert!(!timer.is_hit_stopped());
+    }
+}
@@ -1,9 +1,15 @@
 use std::{
     path::{Path, PathBuf},
-    sync::Arc,
+    sync::{Arc, Mutex},
+    time::Duration,
 };
 
-use crate::{AssetLoader, Vfs};
+use anyhow::Result;
+
+use crate::{
+    AssetLoader, Blackboard, ModManager, Profiler, ShutdownHooks, ShutdownStage, Vfs,
+    WindowManager, report_live_resources,
+};
 
 /// Engine: structure principale du moteur, contenant le VFS, l'AssetLoader et un cache simple.
 ///
@@ -12,6 +18,35 @@ use crate::{AssetLoader, Vfs};
 pub struct Engine {
     pub vfs: Arc<Vfs>,
     pub loader: AssetLoader,
+    pub mods: Arc<Mutex<ModManager>>,
+    /// État global du jeu (score, drapeaux, quêtes), partagé entre les fenêtres.
+    pub blackboard: Arc<Mutex<Blackboard>>,
+    /// Hooks exécutés par `shutdown`.
+    pub shutdown_hooks: Arc<Mutex<ShutdownHooks>>,
+}
+
+/// Poignée légère (clonable) vers les subsystèmes partagés du moteur, donnée aux fenêtres
+/// à leur création (voir `WindowFactory`).
+#[derive(Clone)]
+pub struct EngineHandle {
+    pub vfs: Arc<Vfs>,
+    pub loader: AssetLoader,
+    pub mods: Arc<Mutex<ModManager>>,
+    /// État global du jeu (score, drapeaux, quêtes), partagé entre les fenêtres.
+    pub blackboard: Arc<Mutex<Blackboard>>,
+    pub shutdown_hooks: Arc<Mutex<ShutdownHooks>>,
+}
+
+impl EngineHandle {
+    /// Voir `Engine::on_shutdown`.
+    pub fn on_shutdown(
+        &self,
+        stage: ShutdownStage,
+        name: impl Into<String>,
+        hook: impl FnOnce() -> Result<()> + Send + 'static,
+    ) {
+        self.shutdown_hooks.lock().unwrap().add(stage, name, hook);
+    }
 }
 
 impl Default for Engine {
@@ -21,25 +56,114 @@ impl Default for Engine {
         // vfs.mount_os("engine", PathBuf::from("engine"), "Engine", false);
 
         let loader = AssetLoader::new(vfs.clone());
-        Engine { vfs, loader }
+        let mods = Arc::new(Mutex::new(ModManager::new("mods", vfs.clone())));
+        Engine {
+            vfs,
+            loader,
+            mods,
+            blackboard: Arc::new(Mutex::new(Blackboard::new())),
+            shutdown_hooks: Arc::new(Mutex::new(ShutdownHooks::new())),
+        }
     }
 }
 
 impl Engine {
     pub const NAME: &str = "Gena";
+    /// Archive montée sous "assets" si elle existe (voir `pack_directory`).
+    pub const ASSETS_ARCHIVE: &str = "assets.pak";
+    /// Attente maximale des lectures / écritures d'assets en cours à l'arrêt.
+    pub const SHUTDOWN_IO_TIMEOUT: Duration = Duration::from_secs(2);
 
     pub fn init(&mut self) {
         log::info!("Starting engine...");
 
+        // Le jeu a pu installer son propre subscriber `tracing` : il y ajoute `Profiler::layer`
+        if Profiler::install().is_err() {
+            log::info!("A tracing subscriber is already set, the profiler uses its layer if added");
+        }
+
         self.vfs
             .mount_os("engine", PathBuf::from("engine"), "Engine", false);
 
+        // Jeu livré : assets packés (`app pack`), sous le dossier qui peut encore les remplacer
+        if Path::new(Self::ASSETS_ARCHIVE).exists()
+            && let Err(e) = self
+                .vfs
+                .mount_zip("assets", Self::ASSETS_ARCHIVE, "Assets archive")
+        {
+            log::error!("Failed to mount {}: {:#}", Self::ASSETS_ARCHIVE, e);
+        }
+
         self.vfs
             .mount_os("assets", PathBuf::from("assets"), "Assets", true);
 
+        // Données de l'utilisateur, partagées entre projets (palettes, préférences...)
+        self.vfs
+            .mount_os("user", PathBuf::from("user"), "User", true);
+
+        // Mods : montés au-dessus des assets de base, dans l'ordre de chargement.
+        {
+            let mut mods = self.mods.lock().unwrap();
+            match mods.discover() {
+                Ok(()) => mods.apply(),
+                Err(e) => log::error!("Failed to discover mods: {:#}", e),
+            }
+        }
+
         log::info!("Engine initialization complete.");
     }
 
+    /// Poignée partagée vers les subsystèmes (VFS, loader, mods, blackboard).
+    pub fn handle(&self) -> EngineHandle {
+        EngineHandle {
+            vfs: self.vfs.clone(),
+            loader: self.loader.clone(),
+            mods: self.mods.clone(),
+            blackboard: self.blackboard.clone(),
+            shutdown_hooks: self.shutdown_hooks.clone(),
+        }
+    }
+
+    /// Enregistre un hook exécuté par `shutdown` pendant `stage` (sauvegarde à écrire,
+    /// son à couper, thread à joindre...).
+    pub fn on_shutdown(
+        &self,
+        stage: ShutdownStage,
+        name: impl Into<String>,
+        hook: impl FnOnce() -> Result<()> + Send + 'static,
+    ) {
+        self.shutdown_hooks.lock().unwrap().add(stage, name, hook);
+    }
+
+    /// Arrête le moteur dans l'ordre (voir le module `shutdown`) : écritures, audio,
+    /// threads, ressources GPU des fenêtres puis fenêtres et surfaces. Sans effet après le
+    /// premier appel.
+    pub fn shutdown(&self, windows: &mut WindowManager) {
+        let mut hooks = {
+            let mut shared = self.shutdown_hooks.lock().unwrap();
+            if !shared.begin() {
+                return;
+            }
+            shared.take()
+        };
+        log::info!("Shutting down engine...");
+
+        hooks.run_stage(ShutdownStage::FlushWrites);
+        if !self.loader.wait_idle(Self::SHUTDOWN_IO_TIMEOUT) {
+            log::warn!(
+                "Asset I/O still pending at shutdown: {:?}",
+                self.loader.pending_loads()
+            );
+        }
+        hooks.run_stage(ShutdownStage::StopAudio);
+        hooks.run_stage(ShutdownStage::JoinWorkers);
+        windows.shutdown();
+        hooks.run_stage(ShutdownStage::Final);
+
+        report_live_resources("shutdown", 0);
+        log::info!("Engine shutdown complete.");
+    }
+
     /// Mount an OS directory for the given prefix. `writable` controls whether writes go here.
     pub fn mount_os(
         &self,
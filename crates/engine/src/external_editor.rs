@@ -0,0 +1,271 @@
+//! Ouverture d'assets texte (scripts, shaders, scènes) dans un éditeur externe.
+//!
+//! La commande vient des préférences (`EditorPreferences::external_editor`, ex:
+//! `code --goto {path}`). Les fichiers ouverts sont surveillés (date de modification,
+//! par polling) : quand l'un change, `poll_changes` invalide l'asset dans le graphe de
+//! dépendances et retourne la liste des assets à recharger, dans l'ordre.
+
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+    time::{Duration, Instant, SystemTime},
+};
+
+use anyhow::{Context, Result, anyhow};
+
+use crate::AssetLoader;
+
+/// Découpe un modèle de commande en programme + arguments, `{path}` étant remplacé par
+/// `path`. Les arguments peuvent être entourés de guillemets doubles. Sans `{path}`, le
+/// chemin est ajouté en dernier argument. Un modèle vide utilise l'ouverture par défaut
+/// du système.
+pub fn expand_editor_command(template: &str, path: &Path) -> Result<Vec<String>> {
+    let template = template.trim();
+    if template.is_empty() {
+        return Ok(default_open_command(path));
+    }
+
+    let path = path.to_string_lossy();
+    let mut args = Vec::new();
+    let mut current: Option<String> = None;
+    let mut quoted = false;
+    for c in template.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                current.get_or_insert_with(String::new);
+            }
+            c if c.is_whitespace() && !quoted => args.extend(current.take()),
+            c => current.get_or_insert_with(String::new).push(c),
+        }
+    }
+    if quoted {
+        return Err(anyhow!(
+            "unterminated quote in editor command {:?}",
+            template
+        ));
+    }
+    args.extend(current);
+
+    if !args.iter().any(|arg| arg.contains("{path}")) {
+        args.push("{path}".to_string());
+    }
+    Ok(args
+        .into_iter()
+        .map(|arg| arg.replace("{path}", &path))
+        .collect())
+}
+
+fn default_open_command(path: &Path) -> Vec<String> {
+    let path = path.to_string_lossy().into_owned();
+    if cfg!(target_os = "windows") {
+        vec!["cmd".into(), "/C".into(), "start".into(), "".into(), path]
+    } else if cfg!(target_os = "macos") {
+        vec!["open".into(), path]
+    } else {
+        vec!["xdg-open".into(), path]
+    }
+}
+
+struct WatchedFile {
+    vfs_path: String,
+    os_path: PathBuf,
+    modified: Option<SystemTime>,
+}
+
+/// Lance l'éditeur externe et surveille les fichiers ouverts.
+pub struct ExternalEditor {
+    loader: AssetLoader,
+    watched: Vec<WatchedFile>,
+    last_poll: Option<Instant>,
+}
+
+impl ExternalEditor {
+    /// Intervalle minimal entre deux vérifications des dates de modification.
+    pub const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+    pub fn new(loader: AssetLoader) -> Self {
+        Self {
+            loader,
+            watched: Vec::new(),
+            last_poll: None,
+        }
+    }
+
+    /// Ouvre `vfs_path` avec la commande `template` et le surveille.
+    pub fn open(&mut self, template: &str, vfs_path: &str) -> Result<()> {
+        let os_path = self.watch(vfs_path)?;
+        let args = expand_editor_command(template, &os_path)?;
+        let (program, args) = args
+            .split_first()
+            .ok_or_else(|| anyhow!("empty editor command"))?;
+        Command::new(program)
+            .args(args)
+            .spawn()
+            .with_context(|| format!("failed to start external editor {:?}", program))?;
+        log::info!("Opened {:?} in external editor ({})", vfs_path, program);
+        Ok(())
+    }
+
+    /// Surveille `vfs_path` sans l'ouvrir. Retourne son chemin sur le disque.
+    pub fn watch(&mut self, vfs_path: &str) -> Result<PathBuf> {
+        let os_path = self
+            .loader
+            .vfs()
+            .os_path(vfs_path)
+            .ok_or_else(|| anyhow!("{:?} is not backed by a file on disk", vfs_path))?;
+
+        if !self.watched.iter().any(|w| w.vfs_path == vfs_path) {
+            self.watched.push(WatchedFile {
+                vfs_path: vfs_path.to_string(),
+                modified: Self::modified(&os_path),
+                os_path: os_path.clone(),
+            });
+        }
+        Ok(os_path)
+    }
+
+    pub fn unwatch(&mut self, vfs_path: &str) {
+        self.watched.retain(|w| w.vfs_path != vfs_path);
+    }
+
+    /// Chemins VFS surveillés.
+    pub fn watched(&self) -> impl Iterator<Item = &str> {
+        self.watched.iter().map(|w| w.vfs_path.as_str())
+    }
+
+    fn modified(path: &Path) -> Option<SystemTime> {
+        std::fs::metadata(path).and_then(|m| m.modified()).ok()
+    }
+
+    /// À appeler chaque frame : au plus une fois par `POLL_INTERVAL`, compare les dates de
+    /// modification et retourne les assets à recharger (fichiers modifiés et tout ce qui en
+    /// dépend, dépendances en premier), déjà invalidés dans le graphe du loader.
+    pub fn poll_changes(&mut self) -> Vec<String> {
+        let now = Instant::now();
+        if self
+            .last_poll
+            .is_some_and(|last| now.duration_since(last) < Self::POLL_INTERVAL)
+        {
+            return Vec::new();
+        }
+        self.last_poll = Some(now);
+        self.check_now()
+    }
+
+    /// Comme `poll_changes`, sans limite de fréquence.
+    pub fn check_now(&mut self) -> Vec<String> {
+        let mut reload = Vec::new();
+        for file in &mut self.watched {
+            let modified = Self::modified(&file.os_path);
+            if modified == file.modified {
+                continue;
+            }
+            file.modified = modified;
+            for asset in self.loader.invalidate(&file.vfs_path) {
+                if !reload.contains(&asset) {
+                    reload.push(asset);
+                }
+            }
+        }
+        reload
+    }
+
+    /// Liste des fichiers surveillés et champ d'ouverture d'un fichier par son chemin VFS.
+    pub fn ui(&mut self, ui: &mut egui::Ui, template: &str) {
+        let path_id = ui.make_persistent_id("external_editor_path");
+        let mut path = ui
+            .data(|d| d.get_temp::<String>(path_id))
+            .unwrap_or_default();
+
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut path)
+                    .hint_text("assets/shaders/water.wgsl")
+                    .desired_width(220.0),
+            );
+            if ui
+                .add_enabled(
+                    !path.trim().is_empty(),
+                    egui::Button::new("Open in external editor"),
+                )
+                .clicked()
+                && let Err(e) = self.open(template, path.trim())
+            {
+                log::error!("{:#}", e);
+            }
+        });
+        ui.data_mut(|d| d.insert_temp(path_id, path));
+
+        ui.separator();
+        if self.watched.is_empty() {
+            ui.weak("No file opened");
+        }
+        let mut unwatch = None;
+        for file in &self.watched {
+            ui.horizontal(|ui| {
+                ui.label(&file.vfs_path);
+                if ui.small_button("Stop watching").clicked() {
+                    unwatch = Some(file.vfs_path.clone());
+                }
+            });
+        }
+        if let Some(path) = unwatch {
+            self.unwatch(&path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::Vfs;
+
+    #[test]
+    fn expands_command_templates() {
+        let path = Path::new("/game/assets/my shader.wgsl");
+        assert_eq!(
+            expand_editor_command("code --goto {path}", path).unwrap(),
+            ["code", "--goto", "/game/assets/my shader.wgsl"]
+        );
+        assert_eq!(
+            expand_editor_command(r#""C:\Program Files\Editor\edit.exe" -n"#, path).unwrap(),
+            [
+                r"C:\Program Files\Editor\edit.exe",
+                "-n",
+                "/game/assets/my shader.wgsl"
+            ]
+        );
+        assert!(expand_editor_command("\"unterminated {path}", path).is_err());
+        assert!(!expand_editor_command("", path).unwrap().is_empty());
+    }
+
+    #[test]
+    fn modified_files_invalidate_their_dependents() {
+        let dir = tempdir().unwrap();
+        let shader = dir.path().join("sprite.wgsl");
+        std::fs::write(&shader, "// v1").unwrap();
+
+        let vfs = Arc::new(Vfs::new());
+        vfs.mount_os("assets", dir.path(), "Assets", true);
+        let loader = AssetLoader::new(vfs);
+        loader.add_dependency("assets/hero.material", "assets/sprite.wgsl");
+
+        let mut editor = ExternalEditor::new(loader);
+        editor.watch("assets/sprite.wgsl").unwrap();
+        assert!(editor.check_now().is_empty());
+
+        let file = std::fs::File::options().write(true).open(&shader).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(10))
+            .unwrap();
+        assert_eq!(
+            editor.check_now(),
+            ["assets/sprite.wgsl", "assets/hero.material"]
+        );
+        assert!(editor.check_now().is_empty());
+    }
+}
@@ -0,0 +1,274 @@
+//! Budgets de temps par phase de la frame (mise à jour, préparation GPU, passes de rendu,
+//! soumission, présentation). `FrameBudgets` additionne le temps de chaque phase pendant la frame et, à
+//! la fin, produit un `BudgetExceeded` pour chaque phase au-dessus de son budget : de quoi
+//! repérer une régression (un upload de sprites qui grossit sans limite...) bien avant
+//! qu'elle ne devienne un à-coup signalé par le `FrameWatchdog`.
+//!
+//! `Window::handle_redraw` ouvre et ferme la frame et mesure la soumission et la présentation ;
+//! `PassManager` ajoute le temps des passes. La mise à jour et la préparation GPU sont
+//! mesurées par la fenêtre qui les fait (`record` / `measure`). Les événements sont gardés
+//! jusqu'à `drain_events` ; le log est écrit au plus une fois toutes les `LOG_INTERVAL`
+//! frames par phase.
+
+use std::{
+    collections::VecDeque,
+    fmt,
+    time::{Duration, Instant},
+};
+
+/// Phase de la frame avec un budget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FramePhase {
+    /// Mise à jour de la scène et de la simulation.
+    Update,
+    /// Uploads GPU faits avant les passes (`Scene::prepare_gpu`).
+    PrepareGpu,
+    /// Encodage des passes de rendu (`PassManager`).
+    Passes,
+    /// Soumission des commandes à la queue (`Queue::submit`).
+    Submit,
+    /// Présentation de la surface (`SurfaceTexture::present`).
+    Present,
+}
+
+impl FramePhase {
+    pub const ALL: [FramePhase; 5] = [
+        FramePhase::Update,
+        FramePhase::PrepareGpu,
+        FramePhase::Passes,
+        FramePhase::Submit,
+        FramePhase::Present,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            FramePhase::Update => "update",
+            FramePhase::PrepareGpu => "prepare_gpu",
+            FramePhase::Passes => "passes",
+            FramePhase::Submit => "submit",
+            FramePhase::Present => "present",
+        }
+    }
+
+    /// Budget par défaut, pour tenir 60 FPS avec de la marge.
+    pub fn default_budget(self) -> Duration {
+        match self {
+            FramePhase::Update => Duration::from_millis(4),
+            FramePhase::PrepareGpu => Duration::from_millis(2),
+            FramePhase::Passes => Duration::from_millis(6),
+            FramePhase::Submit => Duration::from_millis(2),
+            FramePhase::Present => Duration::from_millis(4),
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Phase qui a dépassé son budget pendant une frame.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BudgetExceeded {
+    /// Numéro de la frame depuis la création de `FrameBudgets`.
+    pub frame: u64,
+    pub phase: FramePhase,
+    pub duration: Duration,
+    pub budget: Duration,
+    /// Partie la plus lente de la phase (la passe la plus longue...), si elle est connue.
+    pub worst: Option<(String, Duration)>,
+    /// Nombre de frames consécutives au-dessus du budget, celle-ci comprise.
+    pub consecutive: u32,
+}
+
+impl fmt::Display for BudgetExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ms = |duration: Duration| duration.as_secs_f64() * 1000.0;
+        write!(
+            f,
+            "Frame {}: {} took {:.2} ms (budget {:.2} ms)",
+            self.frame,
+            self.phase.name(),
+            ms(self.duration),
+            ms(self.budget)
+        )?;
+        if let Some((name, duration)) = &self.worst {
+            write!(f, ", slowest: {} {:.2} ms", name, ms(*duration))?;
+        }
+        if self.consecutive > 1 {
+            write!(f, ", {} frames in a row", self.consecutive)?;
+        }
+        Ok(())
+    }
+}
+
+/// Temps des phases de la frame et leurs budgets.
+pub struct FrameBudgets {
+    pub enabled: bool,
+    /// Budget de chaque phase (indice : `FramePhase`), `None` : pas de budget.
+    budgets: [Option<Duration>; 5],
+    frame: u64,
+    timings: [Duration; 5],
+    worst: [Option<(String, Duration)>; 5],
+    consecutive: [u32; 5],
+    exceeded: [u64; 5],
+    /// Frame du dernier warning de chaque phase.
+    last_logged: [Option<u64>; 5],
+    events: VecDeque<BudgetExceeded>,
+}
+
+impl Default for FrameBudgets {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FrameBudgets {
+    /// Intervalle minimal (frames) entre deux warnings d'une même phase.
+    pub const LOG_INTERVAL: u64 = 120;
+    /// Au-delà, les événements les plus anciens non lus sont perdus.
+    pub const MAX_EVENTS: usize = 256;
+
+    /// Budgets par défaut (`FramePhase::default_budget`).
+    pub fn new() -> Self {
+        Self {
+            enabled: true,
+            budgets: FramePhase::ALL.map(|phase| Some(phase.default_budget())),
+            frame: 0,
+            timings: [Duration::ZERO; 5],
+            worst: Default::default(),
+            consecutive: [0; 5],
+            exceeded: [0; 5],
+            last_logged: [None; 5],
+            events: VecDeque::new(),
+        }
+    }
+
+    pub fn budget(&self, phase: FramePhase) -> Option<Duration> {
+        self.budgets[phase.index()]
+    }
+
+    /// Change le budget de `phase` (`None` : la phase n'est plus surveillée).
+    pub fn set_budget(&mut self, phase: FramePhase, budget: Option<Duration>) {
+        self.budgets[phase.index()] = budget;
+    }
+
+    pub fn begin_frame(&mut self) {
+        self.frame += 1;
+        self.timings = [Duration::ZERO; 5];
+        self.worst = Default::default();
+    }
+
+    /// Ajoute `duration` au temps de `phase` pendant cette frame.
+    pub fn record(&mut self, phase: FramePhase, duration: Duration) {
+        self.timings[phase.index()] += duration;
+    }
+
+    /// Comme `record`, pour la partie `name` de la phase (gardée si c'est la plus lente).
+    pub fn record_part(&mut self, phase: FramePhase, name: &str, duration: Duration) {
+        self.record(phase, duration);
+        let worst = &mut self.worst[phase.index()];
+        if worst
+            .as_ref()
+            .is_none_or(|(_, slowest)| duration > *slowest)
+        {
+            *worst = Some((name.to_owned(), duration));
+        }
+    }
+
+    /// Exécute `f` et ajoute son temps à `phase`.
+    pub fn measure<R>(&mut self, phase: FramePhase, f: impl FnOnce() -> R) -> R {
+        let started = Instant::now();
+        let result = f();
+        self.record(phase, started.elapsed());
+        result
+    }
+
+    /// Temps de `phase` pendant la frame en cours (ou la dernière terminée).
+    pub fn timing(&self, phase: FramePhase) -> Duration {
+        self.timings[phase.index()]
+    }
+
+    /// Ferme la frame : compare chaque phase à son budget et retourne le nombre de
+    /// dépassements (les événements sont à lire avec `drain_events`).
+    pub fn end_frame(&mut self) -> usize {
+        if !self.enabled {
+            return 0;
+        }
+        let mut count = 0;
+        for phase in FramePhase::ALL {
+            let i = phase.index();
+            let duration = self.timings[i];
+            let Some(budget) = self.budgets[i].filter(|budget| duration > *budget) else {
+                self.consecutive[i] = 0;
+                continue;
+            };
+            self.consecutive[i] += 1;
+            self.exceeded[i] += 1;
+            count += 1;
+            let event = BudgetExceeded {
+                frame: self.frame,
+                phase,
+                duration,
+                budget,
+                worst: self.worst[i].clone(),
+                consecutive: self.consecutive[i],
+            };
+            if self.last_logged[i].is_none_or(|logged| self.frame - logged >= Self::LOG_INTERVAL) {
+                self.last_logged[i] = Some(self.frame);
+                log::warn!("{}", event);
+            }
+            if self.events.len() == Self::MAX_EVENTS {
+                self.events.pop_front();
+            }
+            self.events.push_back(event);
+        }
+        count
+    }
+
+    /// Dépassements depuis le dernier appel, les plus anciens d'abord.
+    pub fn drain_events(&mut self) -> Vec<BudgetExceeded> {
+        self.events.drain(..).collect()
+    }
+
+    /// Nombre de frames où `phase` a dépassé son budget.
+    pub fn exceeded_count(&self, phase: FramePhase) -> u64 {
+        self.exceeded[phase.index()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn phases_over_budget_emit_events() {
+        let mut budgets = FrameBudgets::new();
+        budgets.set_budget(FramePhase::Present, None);
+        budgets.begin_frame();
+        budgets.record(FramePhase::Update, Duration::from_millis(1));
+        budgets.record(FramePhase::Present, Duration::from_millis(50));
+        assert_eq!(budgets.end_frame(), 0);
+
+        for _ in 0..2 {
+            budgets.begin_frame();
+            budgets.record_part(FramePhase::Passes, "sprites", Duration::from_millis(5));
+            budgets.record_part(FramePhase::Passes, "lighting", Duration::from_millis(2));
+            budgets.record(FramePhase::Update, Duration::from_millis(5));
+            assert_eq!(budgets.end_frame(), 2);
+        }
+        assert_eq!(budgets.timing(FramePhase::Passes), Duration::from_millis(7));
+
+        let events = budgets.drain_events();
+        assert_eq!(events.len(), 4);
+        let passes = &events[3];
+        assert_eq!((passes.frame, passes.phase), (3, FramePhase::Passes));
+        assert_eq!(passes.consecutive, 2);
+        assert_eq!(
+            passes.worst,
+            Some(("sprites".to_owned(), Duration::from_millis(5)))
+        );
+        assert!(passes.to_string().contains("slowest: sprites 5.00 ms"));
+        assert_eq!(budgets.exceeded_count(FramePhase::Update), 2);
+        assert!(budgets.drain_events().is_empty());
+    }
+}
@@ -0,0 +1,220 @@
+//! Statistiques de frame : historique du temps CPU des frames (alimenté par le `DeltaTimer`),
+//! percentiles 1% / 99%, nombre de draw calls et d'instances dessinées (comptés par les passes
+//! de rendu via `record_draw`).
+//!
+//! `WindowState::frame_stats` est remis à zéro au début de chaque redraw ; l'overlay egui
+//! (`overlay`) est dessiné par-dessus l'UI quand `FrameStats::overlay` est actif.
+
+use std::{collections::VecDeque, time::Duration};
+
+use crate::DeltaTimer;
+
+/// Compteurs de rendu d'une frame.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DrawCounts {
+    pub draw_calls: u32,
+    pub instances: u32,
+}
+
+/// Historique des temps de frame et compteurs de rendu.
+#[derive(Debug, Clone)]
+pub struct FrameStats {
+    /// Temps CPU des dernières frames (ms), du plus ancien au plus récent.
+    frame_times: VecDeque<f32>,
+    capacity: usize,
+    /// Compteurs de la frame en cours.
+    current: DrawCounts,
+    /// Compteurs de la dernière frame terminée.
+    last: DrawCounts,
+    /// Affiche l'overlay (voir `overlay`).
+    pub overlay: bool,
+}
+
+impl Default for FrameStats {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_HISTORY)
+    }
+}
+
+impl FrameStats {
+    /// Nombre de frames gardées dans l'historique.
+    pub const DEFAULT_HISTORY: usize = 240;
+
+    pub fn new(history: usize) -> Self {
+        Self {
+            frame_times: VecDeque::with_capacity(history),
+            capacity: history.max(1),
+            current: DrawCounts::default(),
+            last: DrawCounts::default(),
+            overlay: false,
+        }
+    }
+
+    /// Début d'une frame : les compteurs de la frame précédente deviennent `draw_counts`.
+    pub fn begin_frame(&mut self) {
+        self.last = std::mem::take(&mut self.current);
+    }
+
+    /// Ajoute le temps d'une frame à l'historique.
+    pub fn record_frame_time(&mut self, frame_time: Duration) {
+        if self.frame_times.len() == self.capacity {
+            self.frame_times.pop_front();
+        }
+        self.frame_times
+            .push_back(frame_time.as_secs_f32() * 1000.0);
+    }
+
+    /// Ajoute le temps de la frame mesuré par `timer` (non borné, contrairement à
+    /// `DeltaTimer::delta_time`).
+    pub fn record_timer(&mut self, timer: &DeltaTimer) {
+        self.record_frame_time(timer.frame_time());
+    }
+
+    /// Un draw call de `instances` instances pendant cette frame.
+    pub fn record_draw(&mut self, instances: u32) {
+        self.current.draw_calls += 1;
+        self.current.instances += instances;
+    }
+
+    /// Compteurs de la dernière frame terminée.
+    pub fn draw_counts(&self) -> DrawCounts {
+        self.last
+    }
+
+    /// Temps des frames de l'historique (ms).
+    pub fn frame_times(&self) -> impl Iterator<Item = f32> + '_ {
+        self.frame_times.iter().copied()
+    }
+
+    /// Temps de la dernière frame (ms).
+    pub fn last_frame_time(&self) -> Option<f32> {
+        self.frame_times.back().copied()
+    }
+
+    /// Temps moyen des frames de l'historique (ms).
+    pub fn average(&self) -> Option<f32> {
+        if self.frame_times.is_empty() {
+            return None;
+        }
+        Some(self.frame_times.iter().sum::<f32>() / self.frame_times.len() as f32)
+    }
+
+    /// Temps de frame au percentile `percent` (0..100) de l'historique (ms, rang le plus
+    /// proche).
+    pub fn percentile(&self, percent: f32) -> Option<f32> {
+        if self.frame_times.is_empty() {
+            return None;
+        }
+        let mut sorted: Vec<f32> = self.frame_times.iter().copied().collect();
+        sorted.sort_by(f32::total_cmp);
+        let rank = (percent.clamp(0.0, 100.0) / 100.0 * sorted.len() as f32).ceil() as usize;
+        Some(sorted[rank.saturating_sub(1)])
+    }
+
+    /// Frames les plus rapides : percentile 1%.
+    pub fn p1(&self) -> Option<f32> {
+        self.percentile(1.0)
+    }
+
+    /// Frames les plus lentes : percentile 99%.
+    pub fn p99(&self) -> Option<f32> {
+        self.percentile(99.0)
+    }
+
+    /// Dessine l'overlay (valeurs et graphe des temps de frame) dans un coin de l'écran,
+    /// si `overlay` est actif.
+    pub fn overlay(&self, ctx: &egui::Context) {
+        if !self.overlay {
+            return;
+        }
+        egui::Area::new(egui::Id::new("frame_stats_overlay"))
+            .anchor(egui::Align2::RIGHT_TOP, egui::vec2(-8.0, 8.0))
+            .order(egui::Order::Foreground)
+            .interactable(false)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| self.ui(ui));
+            });
+    }
+
+    /// Valeurs et graphe des temps de frame.
+    pub fn ui(&self, ui: &mut egui::Ui) {
+        let ms = |value: Option<f32>| value.map_or("-".to_owned(), |v| format!("{:.2} ms", v));
+        let counts = self.draw_counts();
+        egui::Grid::new("frame_stats")
+            .num_columns(2)
+            .show(ui, |ui| {
+                for (label, value) in [
+                    ("Frame", ms(self.last_frame_time())),
+                    ("Average", ms(self.average())),
+                    ("1%", ms(self.p1())),
+                    ("99%", ms(self.p99())),
+                    ("Draw calls", counts.draw_calls.to_string()),
+                    ("Instances", counts.instances.to_string()),
+                ] {
+                    ui.label(label);
+                    ui.monospace(value);
+                    ui.end_row();
+                }
+            });
+
+        let (response, painter) =
+            ui.allocate_painter(egui::vec2(200.0, 60.0), egui::Sense::hover());
+        let rect = response.rect;
+        painter.rect_filled(rect, 2.0, egui::Color32::from_black_alpha(120));
+
+        // Échelle : au moins 33 ms (30 FPS), pour que les frames normales restent lisibles
+        let max = self.p99().unwrap_or(0.0).max(33.3);
+        let step = rect.width() / self.capacity.max(2) as f32;
+        let points: Vec<egui::Pos2> = self
+            .frame_times()
+            .enumerate()
+            .map(|(i, time)| {
+                egui::pos2(
+                    rect.left() + i as f32 * step,
+                    rect.bottom() - (time / max).min(1.0) * rect.height(),
+                )
+            })
+            .collect();
+        // Repère des 16,7 ms (60 FPS)
+        let target_y = rect.bottom() - (16.7 / max) * rect.height();
+        painter.hline(
+            rect.x_range(),
+            target_y,
+            egui::Stroke::new(1.0, egui::Color32::DARK_GREEN),
+        );
+        painter.add(egui::Shape::line(
+            points,
+            egui::Stroke::new(1.0, egui::Color32::LIGHT_YELLOW),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentiles_and_draw_counts() {
+        let mut stats = FrameStats::new(100);
+        for ms in 1..=150 {
+            stats.record_frame_time(Duration::from_millis(ms));
+        }
+        // Seules les 100 dernières frames (51..150 ms) sont gardées
+        assert_eq!(stats.frame_times().count(), 100);
+        assert!((stats.p1().unwrap() - 51.0).abs() < 1e-3);
+        assert!((stats.p99().unwrap() - 149.0).abs() < 1e-3);
+        assert!((stats.average().unwrap() - 100.5).abs() < 1e-3);
+
+        stats.record_draw(10);
+        stats.record_draw(1);
+        assert_eq!(stats.draw_counts(), DrawCounts::default());
+        stats.begin_frame();
+        assert_eq!(
+            stats.draw_counts(),
+            DrawCounts {
+                draw_calls: 2,
+                instances: 11
+            }
+        );
+    }
+}
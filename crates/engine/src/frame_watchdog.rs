@@ -0,0 +1,184 @@
+//! Surveillance des frames longues : quand une frame dépasse le seuil (250 ms par défaut),
+//! `FrameWatchdog` écrit dans le log un rapport avec les scopes mesurés de la frame et les
+//! chargements d'assets en cours. De quoi retrouver l'origine d'un à-coup signalé par un joueur.
+//!
+//! Les scopes sont les spans `tracing` de la frame (étapes du redraw, mise à jour de la scène,
+//! passes de rendu...), lus dans le profileur (`Profiler::current_scopes`) : le watchdog en
+//! demande la capture tant qu'il est actif. Sans le layer du profileur dans le subscriber, le
+//! rapport n'a que la durée de la frame et les chargements. `Window::handle_redraw` ouvre et
+//! ferme la frame.
+
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+
+use crate::{AssetLoader, ProfileRecord, Profiler};
+
+/// Diagnostic d'une frame trop longue.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameReport {
+    /// Numéro de la frame depuis la création du watchdog.
+    pub frame: u64,
+    pub duration: Duration,
+    pub threshold: Duration,
+    /// Scopes du thread de la frame, par ordre de début.
+    pub scopes: Vec<ProfileRecord>,
+    /// Chargements d'assets en cours à la fin de la frame, avec leur durée.
+    pub pending_loads: Vec<(String, Duration)>,
+}
+
+impl fmt::Display for FrameReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ms = |duration: Duration| duration.as_secs_f64() * 1000.0;
+        writeln!(
+            f,
+            "Frame {} took {:.1} ms (threshold {:.0} ms)",
+            self.frame,
+            ms(self.duration),
+            ms(self.threshold)
+        )?;
+        writeln!(f, "  scopes:")?;
+        for scope in &self.scopes {
+            writeln!(
+                f,
+                "    {:indent$}{} {:.2} ms (at {:.2} ms)",
+                "",
+                scope.label(),
+                ms(scope.duration),
+                ms(scope.start),
+                indent = scope.depth as usize * 2
+            )?;
+        }
+        write!(f, "  pending asset loads: {}", self.pending_loads.len())?;
+        for (path, duration) in &self.pending_loads {
+            write!(f, "\n    {} ({:.0} ms)", path, ms(*duration))?;
+        }
+        Ok(())
+    }
+}
+
+/// Mesure les frames et signale celles qui dépassent `threshold`.
+pub struct FrameWatchdog {
+    pub threshold: Duration,
+    pub enabled: bool,
+    frame: u64,
+    frame_start: Option<Instant>,
+    loader: Option<AssetLoader>,
+    last_report: Option<FrameReport>,
+    hitches: u64,
+}
+
+impl Default for FrameWatchdog {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_THRESHOLD)
+    }
+}
+
+impl FrameWatchdog {
+    pub const DEFAULT_THRESHOLD: Duration = Duration::from_millis(250);
+
+    pub fn new(threshold: Duration) -> Self {
+        Self {
+            threshold,
+            enabled: true,
+            frame: 0,
+            frame_start: None,
+            loader: None,
+            last_report: None,
+            hitches: 0,
+        }
+    }
+
+    /// Les rapports listeront les chargements en cours de `loader` (`AssetLoader::pending_loads`).
+    pub fn watch_loader(&mut self, loader: AssetLoader) {
+        self.loader = Some(loader);
+    }
+
+    /// À appeler après `Profiler::new_frame`, pour que les scopes capturés soient ceux de la frame.
+    pub fn begin_frame(&mut self) {
+        Profiler::set_capturing(self.enabled);
+        self.frame += 1;
+        self.frame_start = Some(Instant::now());
+    }
+
+    /// Ferme la frame. Si elle a dépassé le seuil, le rapport est écrit dans le log (warning),
+    /// gardé dans `last_report` et retourné.
+    pub fn end_frame(&mut self) -> Option<&FrameReport> {
+        let duration = self.frame_start.take()?.elapsed();
+        if !self.enabled || duration <= self.threshold {
+            return None;
+        }
+        let thread = std::thread::current();
+        let thread = thread.name().unwrap_or("unnamed");
+        let report = FrameReport {
+            frame: self.frame,
+            duration,
+            threshold: self.threshold,
+            scopes: Profiler::current_scopes()
+                .into_iter()
+                .filter(|scope| scope.thread == thread)
+                .collect(),
+            pending_loads: self
+                .loader
+                .as_ref()
+                .map(AssetLoader::pending_loads)
+                .unwrap_or_default(),
+        };
+        log::warn!("{}", report);
+        self.hitches += 1;
+        self.last_report = Some(report);
+        self.last_report.as_ref()
+    }
+
+    /// Dernier rapport de frame trop longue.
+    pub fn last_report(&self) -> Option<&FrameReport> {
+        self.last_report.as_ref()
+    }
+
+    /// Nombre de frames qui ont dépassé le seuil.
+    pub fn hitches(&self) -> u64 {
+        self.hitches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing_subscriber::layer::SubscriberExt;
+
+    use super::*;
+
+    #[test]
+    fn reports_only_long_frames() {
+        let _lock = crate::profiler::TEST_LOCK.lock().unwrap();
+        let subscriber = tracing_subscriber::registry().with(Profiler::layer());
+        tracing::subscriber::with_default(subscriber, || {
+            let mut watchdog = FrameWatchdog::new(Duration::from_millis(20));
+            Profiler::new_frame();
+            watchdog.begin_frame();
+            drop(tracing::trace_span!("update").entered());
+            assert!(watchdog.end_frame().is_none());
+
+            Profiler::new_frame();
+            watchdog.begin_frame();
+            {
+                let _update = tracing::trace_span!("update").entered();
+                let _physics = tracing::trace_span!("physics", step = "broad").entered();
+                std::thread::sleep(Duration::from_millis(30));
+            }
+            let report = watchdog.end_frame().unwrap().clone();
+            Profiler::set_capturing(false);
+
+            assert_eq!(report.frame, 2);
+            let labels: Vec<(String, u32)> = report
+                .scopes
+                .iter()
+                .map(|scope| (scope.label(), scope.depth))
+                .collect();
+            assert!(labels.contains(&("update".to_owned(), 0)));
+            assert!(labels.contains(&("physics: broad".to_owned(), 1)));
+            assert!(report.to_string().contains("  physics: broad"));
+            assert_eq!(watchdog.hitches(), 1);
+        });
+    }
+}
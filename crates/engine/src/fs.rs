@@ -1,9 +1,43 @@
 use std::{
-    path::{Path, PathBuf},
-    sync::Arc,
+    collections::{BTreeMap, BTreeSet, HashSet},
+    io::Write,
+    path::{Component, Path, PathBuf},
+    sync::{Arc, Mutex, RwLock},
+    time::SystemTime,
 };
 
-use anyhow::{Context, Result, anyhow};
+use anyhow::{Context, Result, anyhow, bail};
+
+/// Entrée d'un dossier (voir `FileSystem::read_dir` et `Vfs::read_dir`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirEntry {
+    /// Nom du fichier ou du dossier, sans son dossier parent.
+    pub name: String,
+    /// Chemin séparé par `/` : relatif à la racine du filesystem, ou chemin VFS complet
+    /// pour `Vfs::read_dir`.
+    pub path: String,
+    pub is_dir: bool,
+}
+
+/// Métadonnées d'un fichier ou d'un dossier.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Metadata {
+    /// Taille en octets (0 pour un dossier).
+    pub size: u64,
+    /// Date de dernière modification, si le filesystem la connaît.
+    pub modified: Option<SystemTime>,
+    pub is_dir: bool,
+}
+
+impl Metadata {
+    fn dir() -> Self {
+        Metadata {
+            size: 0,
+            modified: None,
+            is_dir: true,
+        }
+    }
+}
 
 /// Trait minimal pour un filesystem (peut être monté dans le VFS).
 /// Tous les chemins passés aux méthodes sont relatifs au "root" du filesystem.
@@ -17,11 +51,82 @@ pub trait FileSystem: Send + Sync + 'static {
     /// Ecrit des bytes dans un fichier (crée les dossiers parents si nécessaire).
     fn write_bytes(&self, path: &Path, data: &[u8]) -> Result<()>;
 
+    /// Ajoute des bytes à la fin d'un fichier, créé s'il n'existe pas.
+    /// Par défaut : relit le fichier et le réécrit en entier.
+    fn append_bytes(&self, path: &Path, data: &[u8]) -> Result<()> {
+        let mut content = match self.exists(path) {
+            true => self.read_bytes(path)?,
+            false => Vec::new(),
+        };
+        content.extend_from_slice(data);
+        self.write_bytes(path, &content)
+    }
+
     /// Vérifie si un chemin existe dans ce filesystem.
     fn exists(&self, path: &Path) -> bool;
 
     /// Nom (pour debug).
     fn name(&self) -> &str;
+
+    /// Chemin sur le disque du fichier, si ce filesystem en a un (ouverture dans un
+    /// éditeur externe, détection des modifications).
+    fn os_path(&self, _path: &Path) -> Option<PathBuf> {
+        None
+    }
+
+    /// Fichiers sous le dossier `dir` (récursivement), relatifs à la racine du filesystem.
+    /// Par défaut : aucun (filesystem non listable).
+    fn list_files(&self, _dir: &Path) -> Vec<PathBuf> {
+        Vec::new()
+    }
+
+    /// Contenu direct du dossier `dir` (fichiers et sous-dossiers), trié par nom.
+    /// Par défaut : déduit de `list_files` (les dossiers vides n'apparaissent pas).
+    fn read_dir(&self, dir: &Path) -> Vec<DirEntry> {
+        let mut entries: BTreeMap<String, bool> = BTreeMap::new();
+        for file in self.list_files(dir) {
+            let Ok(relative) = file.strip_prefix(dir) else {
+                continue;
+            };
+            let mut components = relative.components();
+            let Some(first) = components.next() else {
+                continue;
+            };
+            let is_dir = components.next().is_some();
+            *entries
+                .entry(first.as_os_str().to_string_lossy().into_owned())
+                .or_default() |= is_dir;
+        }
+        entries
+            .into_iter()
+            .map(|(name, is_dir)| DirEntry {
+                path: path_key(&dir.join(&name)),
+                name,
+                is_dir,
+            })
+            .collect()
+    }
+
+    /// Taille, date de modification et type du chemin. Par défaut : le fichier est lu pour
+    /// connaître sa taille, un chemin existant illisible est considéré comme un dossier.
+    fn metadata(&self, path: &Path) -> Result<Metadata> {
+        if !self.exists(path) {
+            bail!("{} has no file {:?}", self.name(), path);
+        }
+        Ok(match self.read_bytes(path) {
+            Ok(bytes) => Metadata {
+                size: bytes.len() as u64,
+                modified: None,
+                is_dir: false,
+            },
+            Err(_) => Metadata::dir(),
+        })
+    }
+
+    /// Supprime un fichier (ou un dossier vide). Par défaut : non supporté.
+    fn remove(&self, path: &Path) -> Result<()> {
+        bail!("{} does not support removing {:?}", self.name(), path)
+    }
 }
 
 /// Implementation basique qui mappe vers le système de fichiers OS.
@@ -29,6 +134,10 @@ pub trait FileSystem: Send + Sync + 'static {
 pub struct Ofs {
     root: PathBuf,
     name: String,
+    /// Voir `case_insensitive`.
+    case_insensitive: bool,
+    /// Chemins déjà signalés comme trouvés à la casse près (un warning par chemin).
+    case_warnings: Mutex<HashSet<PathBuf>>,
 }
 
 impl Ofs {
@@ -38,19 +147,155 @@ impl Ofs {
         Ofs {
             root: root.into(),
             name: name.into(),
+            case_insensitive: false,
+            case_warnings: Mutex::new(HashSet::new()),
         }
     }
 
+    /// Un chemin absent retombe sur le fichier dont le nom ne diffère que par la casse
+    /// ("UI/Title.png" pour "ui/title.png"), avec un warning : un projet écrit sous Windows
+    /// reste lisible sous Linux. `find_case_mismatches` liste ces références à corriger.
+    pub fn case_insensitive(mut self, enabled: bool) -> Self {
+        self.case_insensitive = enabled;
+        self
+    }
+
     /// Résout un chemin relatif en chemin absolu sur le FS.
-    fn resolve_path(&self, rel: &Path) -> PathBuf {
+    pub(crate) fn resolve_path(&self, rel: &Path) -> PathBuf {
         if rel.is_absolute() {
-            rel.to_path_buf()
-        } else {
-            self.root.join(rel)
+            return rel.to_path_buf();
         }
+        let abs = self.root.join(rel);
+        if self.case_insensitive
+            && !abs.exists()
+            && let Some(found) = case_insensitive_path(&self.root, rel)
+        {
+            if self.case_warnings.lock().unwrap().insert(rel.to_path_buf()) {
+                log::warn!(
+                    "Ofs({}): {:?} only exists as {:?} (case differs, fails on case-sensitive systems)",
+                    self.name,
+                    rel,
+                    found.strip_prefix(&self.root).unwrap_or(&found)
+                );
+            }
+            return found;
+        }
+        abs
     }
 }
 
+/// Chemin existant sous `root` qui correspond à `rel` en ignorant la casse de chaque
+/// composant (la casse exacte est préférée quand elle existe).
+fn case_insensitive_path(root: &Path, rel: &Path) -> Option<PathBuf> {
+    let mut current = root.to_path_buf();
+    for component in rel.components() {
+        let Component::Normal(part) = component else {
+            current.push(component);
+            continue;
+        };
+        let exact = current.join(part);
+        if exact.exists() {
+            current = exact;
+            continue;
+        }
+        let wanted = part.to_string_lossy().to_lowercase();
+        let entry = std::fs::read_dir(&current)
+            .ok()?
+            .flatten()
+            .find(|entry| entry.file_name().to_string_lossy().to_lowercase() == wanted)?;
+        current.push(entry.file_name());
+    }
+    Some(current)
+}
+
+/// Référence d'un asset dont la casse diffère du fichier sur le disque.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaseMismatch {
+    /// Fichier qui contient la référence (relatif au dossier vérifié).
+    pub file: String,
+    /// Chemin tel qu'écrit dans `file`.
+    pub reference: String,
+    /// Fichier correspondant sur le disque (relatif au dossier vérifié).
+    pub actual: String,
+}
+
+/// Cherche dans les fichiers texte de `dir` (scènes, cartes, shaders...) les chemins qui ne
+/// désignent un fichier de `dir` qu'en ignorant la casse. Un chemin est essayé relatif à
+/// `dir`, sans son premier dossier (préfixe VFS, ex: "assets/") et relatif au fichier qui
+/// le contient. Résultat trié par fichier.
+pub fn find_case_mismatches(dir: impl AsRef<Path>) -> Result<Vec<CaseMismatch>> {
+    /// Les fichiers plus gros (textures, sons) ne sont pas lus.
+    const MAX_TEXT_SIZE: u64 = 4 * 1024 * 1024;
+
+    let dir = dir.as_ref();
+    if !dir.is_dir() {
+        bail!("{:?} is not a directory", dir);
+    }
+    let ofs = Ofs::new(dir, "case_check");
+    let files: BTreeSet<String> = ofs
+        .list_files(Path::new(""))
+        .iter()
+        .map(|path| path_key(path))
+        .collect();
+    let lowercase: BTreeMap<String, &String> = files
+        .iter()
+        .map(|file| (file.to_lowercase(), file))
+        .collect();
+    let extensions: HashSet<String> = files
+        .iter()
+        .filter_map(|file| Some(file.rsplit_once('.')?.1.to_lowercase()))
+        .collect();
+
+    let mut mismatches = Vec::new();
+    for file in &files {
+        let path = dir.join(file);
+        if std::fs::metadata(&path).is_ok_and(|m| m.len() > MAX_TEXT_SIZE) {
+            continue;
+        }
+        // Fichiers binaires : pas de l'UTF-8
+        let Ok(text) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        let parent = file.rsplit_once('/').map_or("", |(parent, _)| parent);
+        let mut seen = HashSet::new();
+        let tokens = text.split(|c: char| {
+            c.is_whitespace()
+                || matches!(
+                    c,
+                    '"' | '\'' | '(' | ')' | '[' | ']' | ',' | '=' | '<' | '>'
+                )
+        });
+        for reference in tokens {
+            let is_path = reference
+                .rsplit_once('.')
+                .is_some_and(|(_, extension)| extensions.contains(&extension.to_lowercase()));
+            if !is_path || !seen.insert(reference) {
+                continue;
+            }
+            let reference_path = Path::new(reference);
+            let candidates = [
+                path_key(reference_path),
+                path_key(&reference_path.components().skip(1).collect::<PathBuf>()),
+                path_key(&Path::new(parent).join(reference_path)),
+            ];
+            if candidates.iter().any(|candidate| files.contains(candidate)) {
+                continue;
+            }
+            if let Some(actual) = candidates
+                .iter()
+                .find_map(|candidate| lowercase.get(&candidate.to_lowercase()))
+            {
+                mismatches.push(CaseMismatch {
+                    file: file.clone(),
+                    reference: reference.to_string(),
+                    actual: (*actual).clone(),
+                });
+            }
+        }
+    }
+    Ok(mismatches)
+}
+
 impl FileSystem for Ofs {
     fn read_to_string(&self, path: &Path) -> Result<String> {
         let abs = self.resolve_path(path);
@@ -78,6 +323,24 @@ impl FileSystem for Ofs {
         Ok(())
     }
 
+    fn append_bytes(&self, path: &Path, data: &[u8]) -> Result<()> {
+        let abs = self.resolve_path(path);
+        if let Some(parent) = abs.parent() {
+            std::fs::create_dir_all(parent).with_context(|| {
+                format!(
+                    "Ofs({}) failed to create parent directories for {:?}",
+                    self.name, abs
+                )
+            })?;
+        }
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&abs)
+            .and_then(|mut file| file.write_all(data))
+            .with_context(|| format!("Ofs({}) failed to append to {:?}", self.name, abs))
+    }
+
     fn exists(&self, path: &Path) -> bool {
         let abs = self.resolve_path(path);
         abs.exists()
@@ -86,6 +349,209 @@ impl FileSystem for Ofs {
     fn name(&self) -> &str {
         &self.name
     }
+
+    fn os_path(&self, path: &Path) -> Option<PathBuf> {
+        Some(self.resolve_path(path))
+    }
+
+    fn list_files(&self, dir: &Path) -> Vec<PathBuf> {
+        let mut files = Vec::new();
+        let mut pending = vec![dir.to_path_buf()];
+        while let Some(current) = pending.pop() {
+            let Ok(entries) = std::fs::read_dir(self.resolve_path(&current)) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let path = current.join(entry.file_name());
+                match entry.file_type() {
+                    Ok(kind) if kind.is_dir() => pending.push(path),
+                    Ok(_) => files.push(path),
+                    Err(_) => {}
+                }
+            }
+        }
+        files
+    }
+
+    fn read_dir(&self, dir: &Path) -> Vec<DirEntry> {
+        let Ok(entries) = std::fs::read_dir(self.resolve_path(dir)) else {
+            return Vec::new();
+        };
+        let mut entries: Vec<DirEntry> = entries
+            .flatten()
+            .map(|entry| {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                DirEntry {
+                    path: path_key(&dir.join(&name)),
+                    is_dir: entry.file_type().is_ok_and(|kind| kind.is_dir()),
+                    name,
+                }
+            })
+            .collect();
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+        entries
+    }
+
+    fn metadata(&self, path: &Path) -> Result<Metadata> {
+        let abs = self.resolve_path(path);
+        let metadata = std::fs::metadata(&abs)
+            .with_context(|| format!("Ofs({}) failed to stat {:?}", self.name, abs))?;
+        Ok(Metadata {
+            size: if metadata.is_dir() { 0 } else { metadata.len() },
+            modified: metadata.modified().ok(),
+            is_dir: metadata.is_dir(),
+        })
+    }
+
+    fn remove(&self, path: &Path) -> Result<()> {
+        let abs = self.resolve_path(path);
+        if abs.is_dir() {
+            std::fs::remove_dir(&abs)
+        } else {
+            std::fs::remove_file(&abs)
+        }
+        .with_context(|| format!("Ofs({}) failed to remove {:?}", self.name, abs))
+    }
+}
+
+/// Clé d'un chemin dans un filesystem sans dossiers réels (`MemFs`, `ZipFs`) : composants
+/// séparés par `/`, sans `.` ni `/` de tête.
+pub(crate) fn path_key(path: &Path) -> String {
+    let parts: Vec<_> = path
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => Some(part.to_string_lossy()),
+            _ => None,
+        })
+        .collect();
+    parts.join("/")
+}
+
+/// Filesystem en mémoire : tests, contenu généré (procgen) ou buffers pas encore
+/// sauvegardés de l'éditeur, montés comme n'importe quel autre filesystem.
+pub struct MemFs {
+    files: RwLock<BTreeMap<String, MemFile>>,
+    name: String,
+}
+
+struct MemFile {
+    data: Vec<u8>,
+    modified: SystemTime,
+}
+
+impl MemFs {
+    pub fn new(name: impl Into<String>) -> Self {
+        MemFs {
+            files: RwLock::new(BTreeMap::new()),
+            name: name.into(),
+        }
+    }
+
+    /// Ajoute ou remplace un fichier.
+    pub fn insert(&self, path: impl AsRef<Path>, data: impl Into<Vec<u8>>) {
+        let file = MemFile {
+            data: data.into(),
+            modified: SystemTime::now(),
+        };
+        let key = path_key(path.as_ref());
+        self.files.write().unwrap().insert(key, file);
+    }
+
+    /// Supprime un fichier et retourne son contenu.
+    pub fn take(&self, path: impl AsRef<Path>) -> Option<Vec<u8>> {
+        let file = self
+            .files
+            .write()
+            .unwrap()
+            .remove(&path_key(path.as_ref()))?;
+        Some(file.data)
+    }
+
+    /// Nombre de fichiers.
+    pub fn len(&self) -> usize {
+        self.files.read().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.read().unwrap().is_empty()
+    }
+}
+
+impl FileSystem for MemFs {
+    fn read_to_string(&self, path: &Path) -> Result<String> {
+        let bytes = self.read_bytes(path)?;
+        String::from_utf8(bytes)
+            .with_context(|| format!("MemFs({}) {:?} is not valid UTF-8", self.name, path))
+    }
+
+    fn read_bytes(&self, path: &Path) -> Result<Vec<u8>> {
+        let key = path_key(path);
+        self.files
+            .read()
+            .unwrap()
+            .get(&key)
+            .map(|file| file.data.clone())
+            .ok_or_else(|| anyhow!("MemFs({}) has no file {:?}", self.name, key))
+    }
+
+    fn write_bytes(&self, path: &Path, data: &[u8]) -> Result<()> {
+        self.insert(path, data);
+        Ok(())
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        let key = path_key(path);
+        let files = self.files.read().unwrap();
+        if key.is_empty() || files.contains_key(&key) {
+            return true;
+        }
+        // Dossier : au moins un fichier en dessous
+        let dir = format!("{}/", key);
+        files
+            .range(dir.clone()..)
+            .next()
+            .is_some_and(|(file, _)| file.starts_with(&dir))
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn list_files(&self, dir: &Path) -> Vec<PathBuf> {
+        let dir = path_key(dir);
+        let prefix = if dir.is_empty() {
+            String::new()
+        } else {
+            format!("{}/", dir)
+        };
+        self.files
+            .read()
+            .unwrap()
+            .range(prefix.clone()..)
+            .take_while(|(file, _)| file.starts_with(&prefix))
+            .map(|(file, _)| PathBuf::from(file))
+            .collect()
+    }
+
+    fn metadata(&self, path: &Path) -> Result<Metadata> {
+        if let Some(file) = self.files.read().unwrap().get(&path_key(path)) {
+            return Ok(Metadata {
+                size: file.data.len() as u64,
+                modified: Some(file.modified),
+                is_dir: false,
+            });
+        }
+        if self.exists(path) {
+            return Ok(Metadata::dir());
+        }
+        bail!("MemFs({}) has no file {:?}", self.name, path)
+    }
+
+    fn remove(&self, path: &Path) -> Result<()> {
+        self.take(path)
+            .map(|_| ())
+            .ok_or_else(|| anyhow!("MemFs({}) has no file {:?}", self.name, path))
+    }
 }
 
 /// Mount point utilisé par le VFS.
@@ -95,6 +561,8 @@ struct Mount {
     prefix: PathBuf,
     fs: Arc<dyn FileSystem>,
     writable: bool,
+    /// Les mounts de plus haute priorité sont consultés en premier.
+    priority: i32,
 }
 
 impl Mount {
@@ -107,6 +575,17 @@ impl Mount {
         path.starts_with(&self.prefix)
     }
 
+    /// Résolution de `path` par ce mount (voir `Vfs::resolve`).
+    fn resolved(&self, path: &Path) -> ResolvedPath {
+        ResolvedPath {
+            mount: self.fs.name().to_string(),
+            prefix: self.prefix.clone(),
+            relative: self.relative_path(path),
+            writable: self.writable,
+            priority: self.priority,
+        }
+    }
+
     /// Retourne le chemin relatif à donner au filesystem : strip_prefix(prefix).
     fn relative_path<'a>(&self, path: &'a Path) -> PathBuf {
         if self.prefix.as_os_str().is_empty() {
@@ -120,11 +599,43 @@ impl Mount {
     }
 }
 
+/// Stratégie de résolution d'un chemin en lecture quand plusieurs mounts matchent.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ResolveMode {
+    /// Le mount le plus prioritaire qui contient réellement le fichier gagne ; les fichiers
+    /// absents retombent sur les mounts de plus basse priorité (overrides de mods).
+    #[default]
+    FirstExisting,
+    /// Le mount le plus prioritaire qui matche le préfixe gagne, même si le fichier n'y
+    /// existe pas.
+    TopMount,
+}
+
+/// Mount qui sert un chemin VFS (voir `Vfs::resolve`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedPath {
+    /// Nom du filesystem (`FileSystem::name`).
+    pub mount: String,
+    pub prefix: PathBuf,
+    /// Chemin donné au filesystem (sans le préfixe du mount).
+    pub relative: PathBuf,
+    pub writable: bool,
+    pub priority: i32,
+}
+
 /// Virtual File System (collection de mounts).
-/// Priorité : le dernier mount ajouté a la priorité la plus haute.
+/// Priorité : la `priority` la plus haute d'abord ; à priorité égale, le dernier mount
+/// ajouté gagne.
+///
+/// Avec une langue (`set_locale`), les lectures de `assets/ui/title.png` servent
+/// `assets/ui/title.fr.png` s'il existe (`localized_path`).
 #[derive(Clone)]
 pub struct Vfs {
     mounts: Arc<std::sync::Mutex<Vec<Mount>>>,
+    resolve_mode: Arc<std::sync::Mutex<ResolveMode>>,
+    locale: Arc<std::sync::Mutex<Option<String>>>,
+    /// Voir `set_case_insensitive`.
+    case_insensitive: Arc<std::sync::atomic::AtomicBool>,
 }
 
 impl Vfs {
@@ -132,20 +643,119 @@ impl Vfs {
     pub fn new() -> Self {
         Vfs {
             mounts: Arc::new(std::sync::Mutex::new(Vec::new())),
+            resolve_mode: Arc::new(std::sync::Mutex::new(ResolveMode::default())),
+            locale: Arc::new(std::sync::Mutex::new(None)),
+            case_insensitive: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+        }
+    }
+
+    /// Change la stratégie de résolution des lectures (voir `ResolveMode`).
+    pub fn set_resolve_mode(&self, mode: ResolveMode) {
+        *self.resolve_mode.lock().unwrap() = mode;
+    }
+
+    pub fn resolve_mode(&self) -> ResolveMode {
+        *self.resolve_mode.lock().unwrap()
+    }
+
+    /// Les `Ofs` montés ensuite par `mount_os` ignorent la casse des chemins absents (voir
+    /// `Ofs::case_insensitive`). À activer avant `Engine::init`.
+    pub fn set_case_insensitive(&self, enabled: bool) {
+        self.case_insensitive
+            .store(enabled, std::sync::atomic::Ordering::Relaxed);
+    }
+
+    /// Langue des variantes localisées ("fr", "pt-BR"...), `None` pour les désactiver.
+    pub fn set_locale(&self, locale: Option<&str>) {
+        *self.locale.lock().unwrap() = locale.map(|l| l.to_lowercase().replace('_', "-"));
+    }
+
+    /// Langue courante, normalisée en minuscules ("pt-br").
+    pub fn locale(&self) -> Option<String> {
+        self.locale.lock().unwrap().clone()
+    }
+
+    /// Variante localisée de `path` qui sert ses lectures : `title.pt-br.png`, sinon
+    /// `title.pt.png`. `None` sans langue ou si aucune variante n'existe.
+    pub fn localized_path(&self, path: &str) -> Option<String> {
+        let variants = self.localized_variants(Path::new(path));
+        let mode = self.resolve_mode();
+        let mounts = self.mounts.lock().unwrap();
+        let (_, _, variant) = Self::serving_mount(&mounts, Path::new(path), &variants, mode)?;
+        variant.map(|index| variants[index].to_string_lossy().replace('\\', "/"))
+    }
+
+    /// Chemins candidats pour la langue courante, du plus précis au plus général.
+    fn localized_variants(&self, path: &Path) -> Vec<PathBuf> {
+        let Some(locale) = self.locale() else {
+            return Vec::new();
+        };
+        let (Some(stem), Some(name)) = (path.file_stem(), path.file_name()) else {
+            return Vec::new();
+        };
+        let stem = stem.to_string_lossy();
+        let extension = path.extension().map(|e| e.to_string_lossy());
+        let mut tags = vec![locale.as_str()];
+        if let Some((language, _)) = locale.split_once('-') {
+            tags.push(language);
         }
+        tags.into_iter()
+            .map(|tag| {
+                let file = match &extension {
+                    Some(extension) => format!("{stem}.{tag}.{extension}"),
+                    None => format!("{}.{tag}", name.to_string_lossy()),
+                };
+                path.with_file_name(file)
+            })
+            .collect()
     }
 
+    /// Priorité des mounts du moteur et du jeu (`mount`, `mount_os`...).
+    pub const DEFAULT_PRIORITY: i32 = 0;
+    /// Priorité des mods (`ModManager::apply`) : au-dessus des assets même montés après.
+    pub const MOD_PRIORITY: i32 = 100;
+
     /// Monte un filesystem sur un `prefix` (ex: "assets", "engine", "" pour catch-all).
     /// `prefix` est un chemin relatif (pas de leading slash de convention).
     /// Si `writable == true`, les opérations d'écriture pourront utiliser ce mount.
     pub fn mount(&self, prefix: impl AsRef<Path>, fs: Arc<dyn FileSystem>, writable: bool) {
+        self.mount_with_priority(prefix, fs, writable, Self::DEFAULT_PRIORITY);
+    }
+
+    /// Comme `mount`, avec une priorité explicite (voir `Vfs`).
+    pub fn mount_with_priority(
+        &self,
+        prefix: impl AsRef<Path>,
+        fs: Arc<dyn FileSystem>,
+        writable: bool,
+        priority: i32,
+    ) {
         let mount = Mount {
             prefix: prefix.as_ref().to_path_buf(),
             fs,
             writable,
+            priority,
         };
         let mut mounts = self.mounts.lock().unwrap();
-        mounts.push(mount);
+        // Gardés triés par priorité croissante, ordre d'ajout à priorité égale
+        let index = mounts.partition_point(|m| m.priority <= priority);
+        mounts.insert(index, mount);
+    }
+
+    /// Change la priorité des mounts dont le filesystem s'appelle `name`. Ils passent
+    /// au-dessus des mounts de même priorité. Retourne `false` si aucun mount ne correspond.
+    pub fn set_priority(&self, name: &str, priority: i32) -> bool {
+        let mut mounts = self.mounts.lock().unwrap();
+        let (moved, kept): (Vec<Mount>, Vec<Mount>) =
+            mounts.drain(..).partition(|m| m.fs.name() == name);
+        *mounts = kept;
+        let found = !moved.is_empty();
+        for mut mount in moved {
+            mount.priority = priority;
+            let index = mounts.partition_point(|m| m.priority <= priority);
+            mounts.insert(index, mount);
+        }
+        found
     }
 
     /// Monte un Ofs facilement (convenience).
@@ -156,36 +766,132 @@ impl Vfs {
         name: impl Into<String>,
         writable: bool,
     ) {
-        let os = Ofs::new(root, name);
+        let case_insensitive = self
+            .case_insensitive
+            .load(std::sync::atomic::Ordering::Relaxed);
+        let os = Ofs::new(root, name).case_insensitive(case_insensitive);
         self.mount(prefix, Arc::new(os), writable);
     }
 
+    /// Monte un `MemFs` vide et le retourne pour y ajouter des fichiers.
+    pub fn mount_memory(
+        &self,
+        prefix: impl AsRef<Path>,
+        name: impl Into<String>,
+        writable: bool,
+    ) -> Arc<MemFs> {
+        let fs = Arc::new(MemFs::new(name));
+        self.mount(prefix, fs.clone(), writable);
+        fs
+    }
+
+    /// Monte une archive zip en lecture seule (voir `ZipFs`).
+    pub fn mount_zip(
+        &self,
+        prefix: impl AsRef<Path>,
+        archive: impl AsRef<Path>,
+        name: impl Into<String>,
+    ) -> Result<()> {
+        let zip = crate::ZipFs::open(archive, name)?;
+        self.mount(prefix, Arc::new(zip), false);
+        Ok(())
+    }
+
     /// Unmount par prefix (supprime toutes les correspondances exactes).
     pub fn unmount(&self, prefix: impl AsRef<Path>) {
         let mut mounts = self.mounts.lock().unwrap();
         mounts.retain(|m| m.prefix != prefix.as_ref());
     }
 
-    /// Résout le premier mount (ordre priorité) qui matche le chemin passé.
-    /// Retourne (fs, relative_path, writable) si trouvé.
-    fn resolve_mount_for(&self, path: &Path) -> Option<(Arc<dyn FileSystem>, PathBuf, bool)> {
+    /// Unmount tous les mounts dont le nom du filesystem (`FileSystem::name`) satisfait `predicate`.
+    pub fn unmount_where(&self, predicate: impl Fn(&str) -> bool) {
+        let mut mounts = self.mounts.lock().unwrap();
+        mounts.retain(|m| !predicate(m.fs.name()));
+    }
+
+    /// Mount qui sert `path` en lecture, selon le `ResolveMode` (voir `resolve_mount_for`).
+    pub fn resolve(&self, path: &str) -> Option<ResolvedPath> {
+        self.resolve_mount_for(Path::new(path))
+            .map(|(_, resolved)| resolved)
+    }
+
+    /// Résout le mount (ordre priorité) qui sert le chemin passé, selon le `ResolveMode`.
+    /// Dans chaque mount, une variante localisée existante (voir `localized_path`) passe avant
+    /// le chemin lui-même. En `FirstExisting`, si aucun mount ne contient le fichier, le mount
+    /// le plus prioritaire qui matche est retourné (pour que l'erreur de lecture soit
+    /// parlante).
+    fn resolve_mount_for(&self, path: &Path) -> Option<(Arc<dyn FileSystem>, ResolvedPath)> {
+        let variants = self.localized_variants(path);
+        let mode = self.resolve_mode();
         let mounts = self.mounts.lock().unwrap();
-        for m in mounts.iter().rev() {
-            if m.matches(path) {
-                let rel = m.relative_path(path);
-                return Some((m.fs.clone(), rel, m.writable));
+        Self::serving_mount(&mounts, path, &variants, mode).map(|(fs, resolved, _)| (fs, resolved))
+    }
+
+    /// Mount qui sert `path`, et la variante lue (indice dans `variants`). Les mounts sont
+    /// essayés du plus prioritaire au moins prioritaire : un `title.png` de mod passe avant le
+    /// `title.fr.png` du jeu de base.
+    fn serving_mount(
+        mounts: &[Mount],
+        path: &Path,
+        variants: &[PathBuf],
+        mode: ResolveMode,
+    ) -> Option<(Arc<dyn FileSystem>, ResolvedPath, Option<usize>)> {
+        let mut top = None;
+        for m in mounts.iter().rev().filter(|m| m.matches(path)) {
+            let variant = variants.iter().enumerate().find_map(|(index, variant)| {
+                let resolved = m.resolved(variant);
+                m.fs.exists(&resolved.relative).then_some((index, resolved))
+            });
+            if let Some((index, resolved)) = variant {
+                return Some((m.fs.clone(), resolved, Some(index)));
             }
+            let resolved = m.resolved(path);
+            if mode == ResolveMode::TopMount || m.fs.exists(&resolved.relative) {
+                return Some((m.fs.clone(), resolved, None));
+            }
+            top.get_or_insert((m.fs.clone(), resolved, None));
         }
-        None
+        top
+    }
+
+    /// Lit le fichier dans tous les mounts qui le contiennent (ordre: basse -> haute priorité),
+    /// avec le nom du filesystem d'origine. Utile pour fusionner des fichiers fournis par
+    /// plusieurs mods (listes, tables...) plutôt que de les remplacer.
+    pub fn read_all_matches(&self, path: &str) -> Result<Vec<(String, Vec<u8>)>> {
+        let pathp = Path::new(path);
+        let candidates: Vec<(Arc<dyn FileSystem>, PathBuf)> = {
+            let mounts = self.mounts.lock().unwrap();
+            mounts
+                .iter()
+                .filter(|m| m.matches(pathp))
+                .map(|m| (m.fs.clone(), m.relative_path(pathp)))
+                .collect()
+        };
+
+        let mut matches = Vec::new();
+        for (fs, rel) in candidates {
+            if !fs.exists(&rel) {
+                continue;
+            }
+            let data = fs.read_bytes(&rel).with_context(|| {
+                format!(
+                    "failed to read bytes from vfs path {:?} (fs {:?})",
+                    path,
+                    fs.name()
+                )
+            })?;
+            matches.push((fs.name().to_string(), data));
+        }
+        Ok(matches)
     }
 
     /// Lit des bytes depuis le VFS.
     /// Le `path` est une chaîne de style "prefix/..." ou ""-prefixed selon vos mounts.
     pub fn read_bytes(&self, path: &str) -> Result<Vec<u8>> {
         let pathp = Path::new(path);
-        if let Some((fs, rel, _writable)) = self.resolve_mount_for(pathp) {
+        if let Some((fs, resolved)) = self.resolve_mount_for(pathp) {
             return fs
-                .read_bytes(&rel)
+                .read_bytes(&resolved.relative)
                 .with_context(|| format!("failed to read bytes from vfs path {:?}", path));
         }
         Err(anyhow!("no mount found for path {:?}", path))
@@ -194,9 +900,9 @@ impl Vfs {
     /// Lis un fichier en tant que string.
     pub fn read_to_string(&self, path: &str) -> Result<String> {
         let pathp = Path::new(path);
-        if let Some((fs, rel, _writable)) = self.resolve_mount_for(pathp) {
+        if let Some((fs, resolved)) = self.resolve_mount_for(pathp) {
             return fs
-                .read_to_string(&rel)
+                .read_to_string(&resolved.relative)
                 .with_context(|| format!("failed to read string from vfs path {:?}", path));
         }
         Err(anyhow!("no mount found for path {:?}", path))
@@ -221,15 +927,146 @@ impl Vfs {
         Err(anyhow!("no writable mount found for path {:?}", path))
     }
 
+    /// Ajoute des bytes à la fin d'un fichier du premier mount writable qui matche le chemin.
+    pub fn append_bytes(&self, path: &str, data: &[u8]) -> Result<()> {
+        let pathp = Path::new(path);
+        let mounts = self.mounts.lock().unwrap();
+        for m in mounts.iter().rev() {
+            if m.matches(pathp) && m.writable {
+                let rel = m.relative_path(pathp);
+                return m.fs.append_bytes(&rel, data).with_context(|| {
+                    format!(
+                        "failed to append bytes to vfs path {:?} (mount {:?})",
+                        path, m.prefix
+                    )
+                });
+            }
+        }
+        Err(anyhow!("no writable mount found for path {:?}", path))
+    }
+
     /// Vérifie si un chemin existe dans le VFS (via le premier mount qui matche).
     pub fn exists(&self, path: &str) -> bool {
         let pathp = Path::new(path);
-        if let Some((fs, rel, _)) = self.resolve_mount_for(pathp) {
-            return fs.exists(&rel);
+        if let Some((fs, resolved)) = self.resolve_mount_for(pathp) {
+            return fs.exists(&resolved.relative);
         }
         false
     }
 
+    /// Chemin sur le disque qui sert `path` (voir `FileSystem::os_path`).
+    pub fn os_path(&self, path: &str) -> Option<PathBuf> {
+        let (fs, resolved) = self.resolve_mount_for(Path::new(path))?;
+        fs.os_path(&resolved.relative)
+    }
+
+    /// Chemins VFS des fichiers sous `dir` ("" = tous les mounts), triés et sans doublon
+    /// (un fichier remplacé par un mod n'apparaît qu'une fois).
+    pub fn list_files(&self, dir: &str) -> Vec<String> {
+        let dir = Path::new(dir.trim_end_matches('/'));
+        let mounts: Vec<(PathBuf, Arc<dyn FileSystem>)> = {
+            let mounts = self.mounts.lock().unwrap();
+            mounts
+                .iter()
+                .map(|m| (m.prefix.clone(), m.fs.clone()))
+                .collect()
+        };
+
+        let mut files = std::collections::BTreeSet::new();
+        for (prefix, fs) in mounts {
+            // Dossier demandé dans ce mount, ou mount entier s'il est sous le dossier demandé
+            let rel = if dir.starts_with(&prefix) {
+                dir.strip_prefix(&prefix)
+                    .unwrap_or(Path::new(""))
+                    .to_path_buf()
+            } else if prefix.starts_with(dir) {
+                PathBuf::new()
+            } else {
+                continue;
+            };
+            for file in fs.list_files(&rel) {
+                let path = prefix.join(file);
+                let parts: Vec<_> = path
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                    .collect();
+                files.insert(parts.join("/"));
+            }
+        }
+        files.into_iter().collect()
+    }
+
+    /// Contenu direct du dossier VFS `dir` ("" = racine) : union des mounts qui se
+    /// superposent (un mod qui ajoute un fichier le fait apparaître à côté des assets de
+    /// base), triée par nom. Les mounts situés sous `dir` apparaissent comme des dossiers.
+    pub fn read_dir(&self, dir: &str) -> Vec<DirEntry> {
+        let dir = Path::new(dir.trim_end_matches('/'));
+        let mounts: Vec<(PathBuf, Arc<dyn FileSystem>)> = {
+            let mounts = self.mounts.lock().unwrap();
+            mounts
+                .iter()
+                .map(|m| (m.prefix.clone(), m.fs.clone()))
+                .collect()
+        };
+
+        let mut entries: BTreeMap<String, bool> = BTreeMap::new();
+        for (prefix, fs) in mounts {
+            if let Ok(rel) = dir.strip_prefix(&prefix) {
+                for entry in fs.read_dir(rel) {
+                    *entries.entry(entry.name).or_default() |= entry.is_dir;
+                }
+            } else if let Ok(rest) = prefix.strip_prefix(dir)
+                && let Some(first) = rest.components().next()
+            {
+                entries.insert(first.as_os_str().to_string_lossy().into_owned(), true);
+            }
+        }
+        entries
+            .into_iter()
+            .map(|(name, is_dir)| DirEntry {
+                path: path_key(&dir.join(&name)),
+                name,
+                is_dir,
+            })
+            .collect()
+    }
+
+    /// Métadonnées du chemin VFS `path`, depuis le mount qui le sert (voir `ResolveMode`).
+    /// Les dossiers parents de mounts (ex: "" ou "mods") sont des dossiers.
+    pub fn metadata(&self, path: &str) -> Result<Metadata> {
+        let pathp = Path::new(path.trim_end_matches('/'));
+        if let Some((fs, resolved)) = self.resolve_mount_for(pathp)
+            && let Ok(metadata) = fs.metadata(&resolved.relative)
+        {
+            return Ok(metadata);
+        }
+        let mounts = self.mounts.lock().unwrap();
+        if mounts.iter().any(|m| m.prefix.starts_with(pathp)) {
+            return Ok(Metadata::dir());
+        }
+        Err(anyhow!("no file at vfs path {:?}", path))
+    }
+
+    /// Supprime `path` du mount writable le plus prioritaire qui le contient.
+    pub fn remove(&self, path: &str) -> Result<()> {
+        let pathp = Path::new(path);
+        let mounts = self.mounts.lock().unwrap();
+        for m in mounts.iter().rev() {
+            if m.matches(pathp) && m.writable {
+                let rel = m.relative_path(pathp);
+                if m.fs.exists(&rel) {
+                    return m.fs.remove(&rel).with_context(|| {
+                        format!(
+                            "failed to remove vfs path {:?} (mount {:?})",
+                            path, m.prefix
+                        )
+                    });
+                }
+            }
+        }
+        Err(anyhow!("no writable mount contains {:?}", path))
+    }
+
     /// Retourne les informations de debug sur les mounts (ordre: basse -> haute priorité).
     pub fn debug_list_mounts(&self) -> Vec<(PathBuf, String, bool)> {
         let mounts = self.mounts.lock().unwrap();
@@ -270,6 +1107,136 @@ mod tests {
         assert_eq!(got, "abc");
     }
 
+    #[test]
+    fn list_files_across_mounts() {
+        let dir = tempdir().unwrap();
+        let (base, overrides) = (dir.path().join("base"), dir.path().join("mod"));
+        std::fs::create_dir_all(base.join("sprites")).unwrap();
+        std::fs::create_dir_all(&overrides).unwrap();
+        std::fs::write(base.join("sprites/player.png"), "").unwrap();
+        std::fs::write(base.join("level.tmx"), "").unwrap();
+        std::fs::write(overrides.join("level.tmx"), "").unwrap();
+
+        let vfs = Vfs::new();
+        vfs.mount_os("assets", base, "base", false);
+        vfs.mount_os("assets", overrides, "mod", false);
+
+        assert_eq!(
+            vfs.list_files(""),
+            ["assets/level.tmx", "assets/sprites/player.png"]
+        );
+        assert_eq!(
+            vfs.list_files("assets/sprites"),
+            ["assets/sprites/player.png"]
+        );
+        assert!(vfs.list_files("engine").is_empty());
+    }
+
+    #[test]
+    fn memory_fs_is_a_regular_mount() {
+        let vfs = Vfs::new();
+        let generated = vfs.mount_memory("generated", "Generated", true);
+        generated.insert("levels/cave.tmx", "<map/>");
+
+        vfs.write_bytes("generated/levels/dungeon.tmx", b"<map></map>")
+            .unwrap();
+        assert_eq!(generated.len(), 2);
+        assert_eq!(
+            vfs.read_to_string("generated/levels/cave.tmx").unwrap(),
+            "<map/>"
+        );
+        assert!(vfs.exists("generated/levels"));
+        assert!(!vfs.exists("generated/levels/missing.tmx"));
+        assert!(vfs.read_bytes("generated/levels/missing.tmx").is_err());
+        assert_eq!(
+            vfs.list_files("generated"),
+            ["generated/levels/cave.tmx", "generated/levels/dungeon.tmx"]
+        );
+        assert_eq!(
+            vfs.debug_list_mounts(),
+            [(PathBuf::from("generated"), "Generated".to_string(), true)]
+        );
+
+        assert!(generated.take("levels/cave.tmx").is_some());
+        assert!(!vfs.exists("generated/levels/cave.tmx"));
+    }
+
+    #[test]
+    fn read_dir_metadata_and_remove() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("sprites/empty")).unwrap();
+        std::fs::write(dir.path().join("sprites/player.png"), "png").unwrap();
+        std::fs::write(dir.path().join("level.tmx"), "<map/>").unwrap();
+
+        let vfs = Vfs::new();
+        vfs.mount_os("assets", dir.path(), "Assets", true);
+        let generated = vfs.mount_memory("assets/generated", "Generated", true);
+        generated.insert("cave.tmx", "<map></map>");
+
+        let names = |entries: Vec<DirEntry>| -> Vec<(String, bool)> {
+            entries.into_iter().map(|e| (e.path, e.is_dir)).collect()
+        };
+        assert_eq!(names(vfs.read_dir("")), [("assets".to_string(), true)]);
+        assert_eq!(
+            names(vfs.read_dir("assets")),
+            [
+                ("assets/generated".to_string(), true),
+                ("assets/level.tmx".to_string(), false),
+                ("assets/sprites".to_string(), true),
+            ]
+        );
+        assert_eq!(
+            names(vfs.read_dir("assets/sprites")),
+            [
+                ("assets/sprites/empty".to_string(), true),
+                ("assets/sprites/player.png".to_string(), false),
+            ]
+        );
+
+        let metadata = vfs.metadata("assets/level.tmx").unwrap();
+        assert_eq!((metadata.size, metadata.is_dir), (6, false));
+        assert!(metadata.modified.is_some());
+        assert!(vfs.metadata("assets/sprites").unwrap().is_dir);
+        assert!(vfs.metadata("").unwrap().is_dir);
+        assert_eq!(vfs.metadata("assets/generated/cave.tmx").unwrap().size, 11);
+        assert!(vfs.metadata("assets/missing.png").is_err());
+
+        vfs.remove("assets/generated/cave.tmx").unwrap();
+        vfs.remove("assets/sprites/player.png").unwrap();
+        assert!(generated.is_empty());
+        assert!(!dir.path().join("sprites/player.png").exists());
+        assert!(vfs.remove("assets/missing.png").is_err());
+    }
+
+    #[test]
+    fn explicit_priorities_and_resolve() {
+        let vfs = Vfs::new();
+        let overlay = Arc::new(MemFs::new("mod:hd"));
+        overlay.insert("hero.png", "hd");
+        overlay.insert("extra.png", "extra");
+        vfs.mount_with_priority("assets", overlay, false, Vfs::MOD_PRIORITY);
+        // Monté après, mais de priorité plus basse
+        vfs.mount_memory("assets", "Assets", true)
+            .insert("hero.png", "base");
+
+        assert_eq!(vfs.read_to_string("assets/hero.png").unwrap(), "hd");
+        let resolved = vfs.resolve("assets/hero.png").unwrap();
+        assert_eq!(resolved.mount, "mod:hd");
+        assert_eq!(resolved.relative, PathBuf::from("hero.png"));
+        assert_eq!(
+            (resolved.writable, resolved.priority),
+            (false, Vfs::MOD_PRIORITY)
+        );
+        let names: Vec<String> = vfs.read_dir("assets").into_iter().map(|e| e.name).collect();
+        assert_eq!(names, ["extra.png", "hero.png"]);
+
+        assert!(vfs.set_priority("Assets", Vfs::MOD_PRIORITY));
+        assert_eq!(vfs.read_to_string("assets/hero.png").unwrap(), "base");
+        assert_eq!(vfs.resolve("assets/extra.png").unwrap().mount, "mod:hd");
+        assert!(!vfs.set_priority("missing", 0));
+        assert!(vfs.resolve("engine/sprite.wgsl").is_none());
+    }
+
     #[test]
     fn mount_priority() {
         // mount A then B; B should win because last mounted
@@ -287,6 +1254,32 @@ mod tests {
         assert_eq!(s, "from_b");
     }
 
+    #[test]
+    fn missing_files_fall_through_to_lower_mounts() {
+        let dir_a = tempdir().unwrap();
+        let dir_b = tempdir().unwrap();
+        std::fs::write(dir_a.path().join("x.txt"), "from_a").unwrap();
+        std::fs::write(dir_a.path().join("y.txt"), "base_only").unwrap();
+        std::fs::write(dir_b.path().join("x.txt"), "from_b").unwrap();
+
+        let vfs = Arc::new(Vfs::new());
+        vfs.mount_os("common", dir_a.path(), "A", false);
+        vfs.mount_os("common", dir_b.path(), "B", false);
+
+        assert_eq!(vfs.read_to_string("common/x.txt").unwrap(), "from_b");
+        assert_eq!(vfs.read_to_string("common/y.txt").unwrap(), "base_only");
+        assert!(vfs.exists("common/y.txt"));
+
+        let all = vfs.read_all_matches("common/x.txt").unwrap();
+        let names: Vec<_> = all.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["A", "B"]);
+        assert!(vfs.read_all_matches("common/z.txt").unwrap().is_empty());
+
+        // Ancien comportement : le mount le plus prioritaire gagne toujours
+        vfs.set_resolve_mode(ResolveMode::TopMount);
+        assert!(vfs.read_to_string("common/y.txt").is_err());
+    }
+
     #[test]
     fn engine_basic_flow() {
         let dir = tempdir().unwrap();
@@ -306,4 +1299,100 @@ mod tests {
         engine.loader.write_bytes("game/b.txt", b"xyz").unwrap();
         assert_eq!(std::fs::read_to_string(root.join("b.txt")).unwrap(), "xyz");
     }
+
+    #[test]
+    fn localized_variants_replace_reads() {
+        let vfs = Vfs::new();
+        let assets = vfs.mount_memory("assets", "Assets", false);
+        assets.insert("ui/title.png", "title");
+        assets.insert("ui/title.fr.png", "titre");
+        assets.insert("ui/title.pt-br.png", "título");
+        assets.insert("ui/credits", "credits");
+        assets.insert("ui/credits.fr", "générique");
+
+        assert_eq!(vfs.localized_path("assets/ui/title.png"), None);
+        assert_eq!(vfs.read_to_string("assets/ui/title.png").unwrap(), "title");
+
+        vfs.set_locale(Some("fr_CA"));
+        assert_eq!(
+            vfs.localized_path("assets/ui/title.png").as_deref(),
+            Some("assets/ui/title.fr.png")
+        );
+        assert_eq!(vfs.read_to_string("assets/ui/title.png").unwrap(), "titre");
+        assert_eq!(
+            vfs.read_to_string("assets/ui/credits").unwrap(),
+            "générique"
+        );
+
+        vfs.set_locale(Some("pt-BR"));
+        assert_eq!(vfs.read_to_string("assets/ui/title.png").unwrap(), "título");
+        assert_eq!(vfs.read_to_string("assets/ui/credits").unwrap(), "credits");
+
+        // Le chargeur recharge l'asset de base quand sa variante change
+        let loader = crate::AssetLoader::new(Arc::new(vfs));
+        loader.load_bytes("assets/ui/title.png").unwrap();
+        assert!(
+            loader
+                .invalidate("assets/ui/title.pt-br.png")
+                .contains(&"assets/ui/title.png".to_string())
+        );
+    }
+
+    #[test]
+    fn mod_overrides_beat_lower_localized_variants() {
+        let vfs = Vfs::new();
+        let base = vfs.mount_memory("assets", "Base", false);
+        base.insert("ui/title.png", "title");
+        base.insert("ui/title.fr.png", "titre");
+        let module = Arc::new(MemFs::new("Mod"));
+        module.insert("ui/title.png", "mod title");
+        vfs.mount_with_priority("assets", module.clone(), false, Vfs::MOD_PRIORITY);
+        vfs.set_locale(Some("fr"));
+
+        // Le mod remplace le fichier, variantes comprises
+        assert_eq!(
+            vfs.read_to_string("assets/ui/title.png").unwrap(),
+            "mod title"
+        );
+        assert_eq!(vfs.localized_path("assets/ui/title.png"), None);
+
+        // Sa propre variante passe avant son fichier
+        module.insert("ui/title.fr.png", "titre du mod");
+        assert_eq!(
+            vfs.read_to_string("assets/ui/title.png").unwrap(),
+            "titre du mod"
+        );
+        assert_eq!(
+            vfs.localized_path("assets/ui/title.png").as_deref(),
+            Some("assets/ui/title.fr.png")
+        );
+    }
+
+    #[test]
+    fn case_insensitive_ofs_and_mismatch_report() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("UI")).unwrap();
+        std::fs::write(dir.path().join("UI/Title.png"), "png").unwrap();
+        std::fs::write(
+            dir.path().join("menu.scene"),
+            "sprite = \"assets/ui/title.png\"\nicon = \"UI/Title.png\"",
+        )
+        .unwrap();
+
+        let vfs = Vfs::new();
+        vfs.set_case_insensitive(true);
+        vfs.mount_os("assets", dir.path(), "Assets", false);
+        assert_eq!(vfs.read_to_string("assets/ui/title.png").unwrap(), "png");
+        assert!(!vfs.exists("assets/ui/missing.png"));
+
+        let mismatches = find_case_mismatches(dir.path()).unwrap();
+        assert_eq!(
+            mismatches,
+            [CaseMismatch {
+                file: "menu.scene".to_string(),
+                reference: "assets/ui/title.png".to_string(),
+                actual: "UI/Title.png".to_string(),
+            }]
+        );
+    }
 }
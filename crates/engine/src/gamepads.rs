@@ -0,0 +1,338 @@
+//! Lecture des manettes avec gilrs. `Gamepads` relève les événements des manettes branchées
+//! et les traduit en `GamepadEvent` ; `WindowManager::update_gamepads`, appelé par la boucle
+//! d'événements avant les redraws, les passe à la fenêtre active
+//! (`WindowState::handle_gamepad_event`) : boutons dans son `Input`, positions brutes des axes
+//! avec `Input::set_axis`, que `Input::axis_value` passe par les `InputSettings` (zone morte,
+//! sensibilité, courbe).
+//!
+//! Toutes les manettes alimentent le même `Input`. Les événements de la frame restent
+//! lisibles (`WindowState::gamepad_events`) pour ce qui distingue les manettes : joueurs
+//! locaux (`LocalPlayers::handle_gamepad_event`) et invites de commande
+//! (`InputPrompts::on_gamepad_button`, avec la famille de la manette).
+//!
+//! L'axe vertical des sticks est inversé par rapport à gilrs : positif vers le bas, comme à
+//! l'écran et dans `InputMap::editor`.
+//!
+//! Dans l'autre sens, `Gamepads::rumble` joue les vibrations demandées à la fenêtre active
+//! (`Haptics`) avec le retour de force de gilrs.
+
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use gilrs::{
+    Axis, Button, EventType, Gilrs,
+    ff::{Effect, EffectBuilder, Repeat},
+};
+
+use crate::{GamepadAxis, GamepadButton, GamepadFamily, HapticRequest, Input, InputButton};
+
+/// Identifiant d'une manette, attribué par `Gamepads` (gilrs).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GamepadId(pub u32);
+
+/// Événement d'une manette.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GamepadEvent {
+    Connected {
+        gamepad: GamepadId,
+        family: GamepadFamily,
+    },
+    Disconnected {
+        gamepad: GamepadId,
+    },
+    Button {
+        gamepad: GamepadId,
+        family: GamepadFamily,
+        button: GamepadButton,
+        pressed: bool,
+    },
+    /// Position brute d'un axe (-1..1, 0..1 pour les gâchettes).
+    Axis {
+        gamepad: GamepadId,
+        axis: GamepadAxis,
+        value: f32,
+    },
+}
+
+impl GamepadEvent {
+    pub fn gamepad(&self) -> GamepadId {
+        match *self {
+            GamepadEvent::Connected { gamepad, .. }
+            | GamepadEvent::Disconnected { gamepad }
+            | GamepadEvent::Button { gamepad, .. }
+            | GamepadEvent::Axis { gamepad, .. } => gamepad,
+        }
+    }
+
+    /// Applique l'événement à `input`. Une manette débranchée relâche les boutons et recentre
+    /// les axes de manette.
+    pub fn apply(&self, input: &mut Input) {
+        match *self {
+            GamepadEvent::Button {
+                button, pressed, ..
+            } => match pressed {
+                true => input.press(InputButton::Gamepad(button)),
+                false => input.release(InputButton::Gamepad(button)),
+            },
+            GamepadEvent::Axis { axis, value, .. } => input.set_axis(axis, value),
+            GamepadEvent::Disconnected { .. } => {
+                for button in GamepadButton::ALL {
+                    input.release(InputButton::Gamepad(button));
+                }
+                for axis in GamepadAxis::ALL {
+                    input.set_axis(axis, 0.0);
+                }
+            }
+            GamepadEvent::Connected { .. } => {}
+        }
+    }
+
+    /// Événement correspondant à un événement gilrs, s'il concerne un bouton ou un axe connu.
+    fn from_gilrs(gamepad: GamepadId, family: GamepadFamily, event: EventType) -> Option<Self> {
+        match event {
+            EventType::Connected => Some(GamepadEvent::Connected { gamepad, family }),
+            EventType::Disconnected => Some(GamepadEvent::Disconnected { gamepad }),
+            EventType::ButtonPressed(button, _) => Self::from_button(gamepad, family, button, true),
+            EventType::ButtonReleased(button, _) => {
+                Self::from_button(gamepad, family, button, false)
+            }
+            EventType::ButtonChanged(button, value, _) => {
+                Self::from_trigger(gamepad, button, value)
+            }
+            EventType::AxisChanged(axis, value, _) => Self::from_axis(gamepad, axis, value),
+            _ => None,
+        }
+    }
+
+    fn from_button(
+        gamepad: GamepadId,
+        family: GamepadFamily,
+        button: Button,
+        pressed: bool,
+    ) -> Option<Self> {
+        Some(GamepadEvent::Button {
+            gamepad,
+            family,
+            button: gamepad_button(button)?,
+            pressed,
+        })
+    }
+
+    /// Position d'une gâchette analogique, qui sert d'axe.
+    fn from_trigger(gamepad: GamepadId, button: Button, value: f32) -> Option<Self> {
+        let axis = match button {
+            Button::LeftTrigger2 => GamepadAxis::LeftTrigger,
+            Button::RightTrigger2 => GamepadAxis::RightTrigger,
+            _ => return None,
+        };
+        Some(GamepadEvent::Axis {
+            gamepad,
+            axis,
+            value,
+        })
+    }
+
+    fn from_axis(gamepad: GamepadId, axis: Axis, value: f32) -> Option<Self> {
+        let (axis, value) = match axis {
+            Axis::LeftStickX => (GamepadAxis::LeftStickX, value),
+            Axis::LeftStickY => (GamepadAxis::LeftStickY, -value),
+            Axis::RightStickX => (GamepadAxis::RightStickX, value),
+            Axis::RightStickY => (GamepadAxis::RightStickY, -value),
+            _ => return None,
+        };
+        Some(GamepadEvent::Axis {
+            gamepad,
+            axis,
+            value,
+        })
+    }
+}
+
+/// Bouton gilrs -> bouton du moteur (les deux nomment les boutons par position).
+fn gamepad_button(button: Button) -> Option<GamepadButton> {
+    Some(match button {
+        Button::South => GamepadButton::South,
+        Button::East => GamepadButton::East,
+        Button::West => GamepadButton::West,
+        Button::North => GamepadButton::North,
+        Button::LeftTrigger => GamepadButton::LeftBumper,
+        Button::RightTrigger => GamepadButton::RightBumper,
+        Button::LeftTrigger2 => GamepadButton::LeftTrigger,
+        Button::RightTrigger2 => GamepadButton::RightTrigger,
+        Button::Select => GamepadButton::Select,
+        Button::Start => GamepadButton::Start,
+        Button::LeftThumb => GamepadButton::LeftStick,
+        Button::RightThumb => GamepadButton::RightStick,
+        Button::DPadUp => GamepadButton::DPadUp,
+        Button::DPadDown => GamepadButton::DPadDown,
+        Button::DPadLeft => GamepadButton::DPadLeft,
+        Button::DPadRight => GamepadButton::DPadRight,
+        _ => return None,
+    })
+}
+
+/// Manettes branchées, lues avec gilrs.
+pub struct Gamepads {
+    /// `None` si gilrs n'a pas pu s'initialiser : aucune manette n'est lue.
+    gilrs: Option<Gilrs>,
+    connected: HashMap<GamepadId, GamepadFamily>,
+    /// Vibrations en cours, arrêtées par gilrs quand leur `Effect` est détruit : gardées
+    /// jusqu'à l'instant de fin.
+    effects: Vec<(Effect, Instant)>,
+}
+
+impl Default for Gamepads {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Gamepads {
+    pub fn new() -> Self {
+        let gilrs = match Gilrs::new() {
+            Ok(gilrs) => Some(gilrs),
+            Err(gilrs::Error::NotImplemented(_)) => {
+                log::warn!("Gamepads are not supported on this platform");
+                None
+            }
+            Err(e) => {
+                log::warn!("Failed to initialize gamepads: {}", e);
+                None
+            }
+        };
+        // Les manettes déjà branchées ne produisent pas d'événement `Connected`
+        let connected = gilrs
+            .iter()
+            .flat_map(|gilrs| gilrs.gamepads())
+            .map(|(id, gamepad)| (Self::id(id), GamepadFamily::from_name(gamepad.name())))
+            .collect();
+        Self {
+            gilrs,
+            connected,
+            effects: Vec::new(),
+        }
+    }
+
+    fn id(id: gilrs::GamepadId) -> GamepadId {
+        GamepadId(usize::from(id) as u32)
+    }
+
+    /// Événements reçus depuis l'appel précédent.
+    pub fn poll(&mut self) -> Vec<GamepadEvent> {
+        let Some(gilrs) = &mut self.gilrs else {
+            return Vec::new();
+        };
+        let mut events = Vec::new();
+        while let Some(event) = gilrs.next_event() {
+            let gamepad = Self::id(event.id);
+            let family = *self
+                .connected
+                .entry(gamepad)
+                .or_insert_with(|| GamepadFamily::from_name(gilrs.gamepad(event.id).name()));
+            if event.event == EventType::Disconnected {
+                self.connected.remove(&gamepad);
+            }
+            events.extend(GamepadEvent::from_gilrs(gamepad, family, event.event));
+        }
+        events
+    }
+
+    /// Joue une vibration sur sa manette, ou sur toutes (`gamepad: None`). Les manettes sans
+    /// retour de force sont ignorées.
+    pub fn rumble(&mut self, request: HapticRequest) {
+        let Some(gilrs) = &mut self.gilrs else {
+            return;
+        };
+        let now = Instant::now();
+        self.effects.retain(|(_, end)| *end > now);
+
+        let gamepads: Vec<gilrs::GamepadId> = gilrs
+            .gamepads()
+            .filter(|(id, gamepad)| {
+                gamepad.is_ff_supported()
+                    && request.gamepad.is_none_or(|target| Self::id(*id) == target)
+            })
+            .map(|(id, _)| id)
+            .collect();
+        if gamepads.is_empty() {
+            return;
+        }
+
+        let rumble = request.rumble;
+        let mut builder = EffectBuilder::new();
+        for effect in rumble.base_effects() {
+            builder.add_effect(effect);
+        }
+        let effect = builder
+            .gamepads(&gamepads)
+            .repeat(Repeat::For(rumble.play_for()))
+            .finish(gilrs)
+            .and_then(|effect| effect.play().map(|_| effect));
+        match effect {
+            // Marge pour que gilrs ait joué le dernier pas avant la destruction
+            Ok(effect) => self
+                .effects
+                .push((effect, now + rumble.duration + Duration::from_millis(100))),
+            Err(e) => log::warn!("Failed to play gamepad rumble: {}", e),
+        }
+    }
+
+    /// Manettes branchées et leur famille.
+    pub fn connected(&self) -> impl Iterator<Item = (GamepadId, GamepadFamily)> + '_ {
+        self.connected
+            .iter()
+            .map(|(&gamepad, &family)| (gamepad, family))
+    }
+
+    pub fn family(&self, gamepad: GamepadId) -> Option<GamepadFamily> {
+        self.connected.get(&gamepad).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gilrs_events_drive_the_input() {
+        let pad = GamepadId(0);
+        let family = GamepadFamily::PlayStation;
+        let events: Vec<GamepadEvent> = [
+            GamepadEvent::from_button(pad, family, Button::South, true),
+            GamepadEvent::from_button(pad, family, Button::LeftTrigger, true),
+            GamepadEvent::from_trigger(pad, Button::RightTrigger2, 0.8),
+            GamepadEvent::from_axis(pad, Axis::LeftStickY, 0.6),
+            GamepadEvent::from_axis(pad, Axis::LeftStickX, 0.1),
+            GamepadEvent::from_button(pad, family, Button::Mode, true),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        assert_eq!(events.len(), 5);
+        assert_eq!(
+            events[0],
+            GamepadEvent::Button {
+                gamepad: pad,
+                family,
+                button: GamepadButton::South,
+                pressed: true,
+            }
+        );
+
+        let mut input = Input::default();
+        for event in &events {
+            event.apply(&mut input);
+        }
+        assert!(input.is_down(InputButton::Gamepad(GamepadButton::LeftBumper)));
+        assert!((input.axis_value(GamepadAxis::RightTrigger) - 0.75).abs() < 1e-6);
+        // Stick vers le haut : négatif ; dans la zone morte : 0
+        assert!((input.axis_value(GamepadAxis::LeftStickY) + 0.5).abs() < 1e-6);
+        assert_eq!(input.axis_value(GamepadAxis::LeftStickX), 0.0);
+
+        GamepadEvent::Disconnected { gamepad: pad }.apply(&mut input);
+        assert!(!input.is_down(InputButton::Gamepad(GamepadButton::South)));
+        assert_eq!(input.axis_value(GamepadAxis::LeftStickY), 0.0);
+    }
+}
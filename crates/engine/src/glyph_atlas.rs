@@ -0,0 +1,455 @@
+//! Atlas des glyphes du texte mis en forme (`ShapedText`). Chaque glyphe est rastérisé
+//! (contours lus par ttf-parser, remplis par ab_glyph_rasterizer) à sa taille en pixels la
+//! première fois qu'il est dessiné, puis copié dans une texture egui. Un texte est dessiné
+//! en un seul maillage texturé.
+//!
+//! Les glyphes en couleur (emoji des tables COLR, CBDT ou sbix, voir
+//! `rasterize_color_glyph`) sont rangés dans la même texture avec leurs couleurs : ils ne
+//! prennent que l'opacité de la couleur du texte.
+//!
+//! Les glyphes sont rangés en étagères, dans des pages (une texture de `SIZE`² chacune) :
+//! quand la page en cours est pleine, une nouvelle est créée et les glyphes déjà rangés ne
+//! bougent pas. Un texte est dessiné en un maillage par page qu'il utilise. Au-delà de
+//! `MAX_PAGES`, l'atlas est vidé et les glyphes sont rastérisés de nouveau à mesure qu'ils
+//! sont dessinés : un texte déjà ajouté à la frame peut alors être faux pendant cette frame.
+
+use std::collections::HashMap;
+
+use ab_glyph_rasterizer::{Point, Rasterizer, point};
+use egui::epaint::AlphaFromCoverage;
+use rustybuzz::ttf_parser;
+
+use crate::{FontGlyph, ShapedGlyph, ShapedText, TextShaper, rasterize_color_glyph};
+
+/// Inclinaison de l'italique (décalage horizontal par point de hauteur).
+const ITALIC_SKEW: f32 = 0.2;
+
+/// Glyphe rangé dans l'atlas.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AtlasGlyph {
+    /// Page (texture) du glyphe, voir `GlyphAtlas::texture_id`.
+    pub page: usize,
+    pub uv: egui::Rect,
+    /// Rectangle du glyphe (pixels) depuis son origine sur la ligne de base.
+    pub rect: egui::Rect,
+    /// Image en couleur, dessinée sans la couleur du texte.
+    pub colored: bool,
+}
+
+/// Apparence d'un glyphe dessiné.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GlyphStyle {
+    pub color: egui::Color32,
+    /// Décalage (points) depuis la position du glyphe dans le texte.
+    pub offset: egui::Vec2,
+    pub italic: bool,
+}
+
+impl GlyphStyle {
+    pub fn new(color: egui::Color32) -> Self {
+        Self {
+            color,
+            offset: egui::Vec2::ZERO,
+            italic: false,
+        }
+    }
+}
+
+/// Glyphes rastérisés, dans des textures egui créées à mesure que les pages se remplissent.
+#[derive(Clone, Default)]
+pub struct GlyphAtlas {
+    pages: Vec<egui::TextureHandle>,
+    /// Par glyphe et taille en pixels par em (bits du flottant). `None` : glyphe sans contour
+    /// (espace) ou trop grand pour l'atlas.
+    glyphs: HashMap<(FontGlyph, u32), Option<AtlasGlyph>>,
+    /// Page en cours de remplissage.
+    page: usize,
+    /// Coin de la place suivante dans la page et hauteur de l'étagère en cours.
+    cursor: [usize; 2],
+    shelf_height: usize,
+    /// Opacité selon la couverture des pixels, celle du style egui.
+    coverage: AlphaFromCoverage,
+}
+
+impl GlyphAtlas {
+    /// Côté d'une page (pixels).
+    pub const SIZE: usize = 1024;
+    /// Au-delà, l'atlas est vidé plutôt que de créer une page de plus.
+    pub const MAX_PAGES: usize = 8;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Texture de la page `page`, si elle a déjà été créée.
+    pub fn texture_id(&self, page: usize) -> Option<egui::TextureId> {
+        self.pages.get(page).map(egui::TextureHandle::id)
+    }
+
+    /// Nombre de pages créées.
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Nombre de glyphes rastérisés.
+    pub fn len(&self) -> usize {
+        self.glyphs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.glyphs.is_empty()
+    }
+
+    /// Oublie les glyphes, à faire quand les polices du `TextShaper` changent. Les textures
+    /// sont gardées et réécrites.
+    pub fn clear(&mut self) {
+        self.glyphs.clear();
+        self.page = 0;
+        self.cursor = [0, 0];
+        self.shelf_height = 0;
+    }
+
+    /// Texture de la page en cours, créée au besoin.
+    fn texture(&mut self, ctx: &egui::Context) -> &mut egui::TextureHandle {
+        while self.pages.len() <= self.page {
+            let name = format!("glyph_atlas_{}", self.pages.len());
+            self.pages.push(ctx.load_texture(
+                name,
+                egui::ColorImage::filled([Self::SIZE; 2], egui::Color32::TRANSPARENT),
+                egui::TextureOptions::LINEAR,
+            ));
+        }
+        &mut self.pages[self.page]
+    }
+
+    /// Glyphe `glyph` à `pixels` pixels par em, rastérisé au premier appel.
+    pub fn glyph(
+        &mut self,
+        ctx: &egui::Context,
+        shaper: &TextShaper,
+        glyph: FontGlyph,
+        pixels: f32,
+    ) -> Option<AtlasGlyph> {
+        let coverage = ctx.style().visuals.text_alpha_from_coverage;
+        if coverage != self.coverage {
+            self.clear();
+            self.coverage = coverage;
+        }
+        let key = (glyph, pixels.to_bits());
+        if let Some(entry) = self.glyphs.get(&key) {
+            return *entry;
+        }
+        let entry = shaper.face(glyph.font).and_then(|face| {
+            match rasterize_color_glyph(&face, glyph.id, pixels) {
+                Some((image, offset)) => self.insert(ctx, image, offset, true),
+                None => rasterize(&face, glyph.id, pixels, coverage)
+                    .and_then(|(image, offset)| self.insert(ctx, image, offset, false)),
+            }
+        });
+        self.glyphs.insert(key, entry);
+        entry
+    }
+
+    /// Copie `image` dans la texture. Chaque image est suivie d'un pixel transparent à droite
+    /// et en bas, pour que le filtrage ne mélange pas deux glyphes voisins.
+    fn insert(
+        &mut self,
+        ctx: &egui::Context,
+        image: egui::ColorImage,
+        offset: egui::Vec2,
+        colored: bool,
+    ) -> Option<AtlasGlyph> {
+        let [width, height] = image.size;
+        let padded = [width + 1, height + 1];
+        if padded[0] > Self::SIZE || padded[1] > Self::SIZE {
+            return None;
+        }
+        if self.cursor[0] + padded[0] > Self::SIZE {
+            self.cursor = [0, self.cursor[1] + self.shelf_height];
+            self.shelf_height = 0;
+        }
+        if self.cursor[1] + padded[1] > Self::SIZE {
+            match self.page + 1 < Self::MAX_PAGES {
+                true => self.page += 1,
+                false => {
+                    log::debug!("Glyph atlas is full, clearing it");
+                    self.page = 0;
+                    self.glyphs.clear();
+                }
+            }
+            self.cursor = [0, 0];
+            self.shelf_height = 0;
+        }
+
+        let mut pixels = vec![egui::Color32::TRANSPARENT; padded[0] * padded[1]];
+        for (row, source) in image.pixels.chunks_exact(width).enumerate() {
+            pixels[row * padded[0]..row * padded[0] + width].copy_from_slice(source);
+        }
+        let position = self.cursor;
+        self.texture(ctx).set_partial(
+            position,
+            egui::ColorImage::new(padded, pixels),
+            egui::TextureOptions::LINEAR,
+        );
+        self.cursor[0] += padded[0];
+        self.shelf_height = self.shelf_height.max(padded[1]);
+
+        let texel = 1.0 / Self::SIZE as f32;
+        Some(AtlasGlyph {
+            page: self.page,
+            uv: egui::Rect::from_min_size(
+                egui::pos2(position[0] as f32, position[1] as f32) * texel,
+                egui::vec2(width as f32, height as f32) * texel,
+            ),
+            rect: egui::Rect::from_min_size(
+                offset.to_pos2(),
+                egui::vec2(width as f32, height as f32),
+            ),
+            colored,
+        })
+    }
+
+    /// Ajoute le glyphe `glyph` d'un texte dessiné à `origin` au maillage de sa page dans
+    /// `meshes` (un par page, créés au besoin). L'origine du glyphe est arrondie au pixel, pour
+    /// des contours nets. Un glyphe en couleur ne prend que l'opacité de `style.color`.
+    pub fn add_glyph(
+        &mut self,
+        ctx: &egui::Context,
+        shaper: &TextShaper,
+        meshes: &mut Vec<egui::Mesh>,
+        origin: egui::Pos2,
+        glyph: &ShapedGlyph,
+        style: GlyphStyle,
+    ) {
+        let Some(id) = glyph.glyph else {
+            return;
+        };
+        let pixels_per_point = ctx.pixels_per_point();
+        let Some(entry) = self.glyph(ctx, shaper, id, glyph.size * pixels_per_point) else {
+            return;
+        };
+        let baseline = origin + glyph.pos.to_vec2() + style.offset;
+        let pixel = (baseline.to_vec2() * pixels_per_point).round();
+        let rect = egui::Rect::from_min_max(
+            ((pixel + entry.rect.min.to_vec2()) / pixels_per_point).to_pos2(),
+            ((pixel + entry.rect.max.to_vec2()) / pixels_per_point).to_pos2(),
+        );
+        let skew = |y: f32| match style.italic {
+            true => (baseline.y - y) * ITALIC_SKEW,
+            false => 0.0,
+        };
+        let color = match entry.colored {
+            true => egui::Color32::from_white_alpha(style.color.a()),
+            false => style.color,
+        };
+
+        while meshes.len() <= entry.page {
+            meshes.push(egui::Mesh::with_texture(self.pages[meshes.len()].id()));
+        }
+        let mesh = &mut meshes[entry.page];
+        let first = mesh.vertices.len() as u32;
+        for (pos, uv) in [
+            (rect.left_top(), entry.uv.left_top()),
+            (rect.right_top(), entry.uv.right_top()),
+            (rect.right_bottom(), entry.uv.right_bottom()),
+            (rect.left_bottom(), entry.uv.left_bottom()),
+        ] {
+            mesh.vertices.push(egui::epaint::Vertex {
+                pos: egui::pos2(pos.x + skew(pos.y), pos.y),
+                uv,
+                color,
+            });
+        }
+        mesh.add_triangle(first, first + 1, first + 2);
+        mesh.add_triangle(first, first + 2, first + 3);
+    }
+
+    /// Dessine `text` à `origin`. `style` donne l'apparence de chaque glyphe (avec son indice
+    /// dans `text.glyphs`) ; les objets ne sont pas dessinés.
+    pub fn paint(
+        &mut self,
+        painter: &egui::Painter,
+        shaper: &TextShaper,
+        origin: egui::Pos2,
+        text: &ShapedText,
+        mut style: impl FnMut(usize, &ShapedGlyph) -> GlyphStyle,
+    ) {
+        let ctx = painter.ctx().clone();
+        let mut meshes = Vec::new();
+        for (index, glyph) in text.glyphs.iter().enumerate() {
+            if glyph.glyph.is_some() {
+                let glyph_style = style(index, glyph);
+                self.add_glyph(&ctx, shaper, &mut meshes, origin, glyph, glyph_style);
+            }
+        }
+        for mesh in meshes.into_iter().filter(|mesh| !mesh.is_empty()) {
+            painter.add(egui::Shape::mesh(mesh));
+        }
+    }
+}
+
+/// Image (couverture en blanc) du glyphe `id` à `pixels` pixels par em, et position de son
+/// coin haut gauche depuis l'origine du glyphe. `None` pour un glyphe sans contour.
+fn rasterize(
+    face: &ttf_parser::Face,
+    id: u16,
+    pixels: f32,
+    coverage: AlphaFromCoverage,
+) -> Option<(egui::ColorImage, egui::Vec2)> {
+    let id = ttf_parser::GlyphId(id);
+    let scale = pixels / face.units_per_em() as f32;
+    let bounds = face.glyph_bounding_box(id)?;
+    let min = egui::vec2(
+        (bounds.x_min as f32 * scale).floor(),
+        (-bounds.y_max as f32 * scale).floor(),
+    );
+    let max = egui::vec2(
+        (bounds.x_max as f32 * scale).ceil(),
+        (-bounds.y_min as f32 * scale).ceil(),
+    );
+    let size = [
+        (max.x - min.x).max(1.0) as usize,
+        (max.y - min.y).max(1.0) as usize,
+    ];
+    let transform = ttf_parser::Transform::new(scale, 0.0, 0.0, -scale, -min.x, -min.y);
+    let mut outline = Outline::new(size, transform);
+    face.outline_glyph(id, &mut outline)?;
+
+    let pixels = outline
+        .coverage()
+        .into_iter()
+        .map(|alpha| coverage.color_from_coverage(alpha))
+        .collect();
+    Some((egui::ColorImage::new(size, pixels), min))
+}
+
+/// Applique `transform` au point (`x`, `y`).
+pub(crate) fn transform_point(transform: &ttf_parser::Transform, x: f32, y: f32) -> [f32; 2] {
+    [
+        transform.a * x + transform.c * y + transform.e,
+        transform.b * x + transform.d * y + transform.f,
+    ]
+}
+
+/// Contour d'un glyphe, placé par `transform` en pixels depuis le coin haut gauche de son
+/// image (y vers le bas).
+pub(crate) struct Outline {
+    rasterizer: Rasterizer,
+    transform: ttf_parser::Transform,
+    start: Point,
+    last: Point,
+}
+
+impl Outline {
+    pub(crate) fn new(size: [usize; 2], transform: ttf_parser::Transform) -> Self {
+        Self {
+            rasterizer: Rasterizer::new(size[0], size[1]),
+            transform,
+            start: point(0.0, 0.0),
+            last: point(0.0, 0.0),
+        }
+    }
+
+    /// Couverture (0..1) de chaque pixel, ligne par ligne.
+    pub(crate) fn coverage(self) -> Vec<f32> {
+        let (width, height) = self.rasterizer.dimensions();
+        let mut coverage = vec![0.0; width * height];
+        self.rasterizer.for_each_pixel_2d(|x, y, alpha| {
+            coverage[y as usize * width + x as usize] = alpha.min(1.0);
+        });
+        coverage
+    }
+
+    fn point(&self, x: f32, y: f32) -> Point {
+        let [x, y] = transform_point(&self.transform, x, y);
+        point(x, y)
+    }
+}
+
+impl ttf_parser::OutlineBuilder for Outline {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.start = self.point(x, y);
+        self.last = self.start;
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        let to = self.point(x, y);
+        self.rasterizer.draw_line(self.last, to);
+        self.last = to;
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        let to = self.point(x, y);
+        self.rasterizer.draw_quad(self.last, self.point(x1, y1), to);
+        self.last = to;
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        let to = self.point(x, y);
+        self.rasterizer
+            .draw_cubic(self.last, self.point(x1, y1), self.point(x2, y2), to);
+        self.last = to;
+    }
+
+    fn close(&mut self) {
+        if self.last != self.start {
+            self.rasterizer.draw_line(self.last, self.start);
+        }
+        self.last = self.start;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn full_pages_keep_earlier_glyphs() {
+        let ctx = egui::Context::default();
+        let mut atlas = GlyphAtlas::new();
+        // 101 pixels avec la marge : 10 glyphes par étagère, 100 par page
+        let glyph = |index: usize| {
+            egui::ColorImage::filled([100, 100], egui::Color32::from_gray(index as u8))
+        };
+        let mut entries = Vec::new();
+        let output = ctx.run(egui::RawInput::default(), |ctx| {
+            for index in 0..150 {
+                let entry = atlas.insert(ctx, glyph(index), egui::Vec2::ZERO, false);
+                entries.push(entry.unwrap());
+            }
+        });
+        assert_eq!(atlas.page_count(), 2);
+        assert_eq!((entries[99].page, entries[100].page), (0, 1));
+
+        // Rejoue les envois de textures, dans l'ordre
+        let mut textures: HashMap<egui::TextureId, egui::ColorImage> = HashMap::new();
+        for (id, delta) in output.textures_delta.set {
+            let egui::ImageData::Color(image) = delta.image;
+            let Some([x, y]) = delta.pos else {
+                textures.insert(id, (*image).clone());
+                continue;
+            };
+            let texture = textures.get_mut(&id).unwrap();
+            for (row, pixels) in image.pixels.chunks_exact(image.size[0]).enumerate() {
+                let start = (y + row) * texture.size[0] + x;
+                texture.pixels[start..start + pixels.len()].copy_from_slice(pixels);
+            }
+        }
+
+        for (index, entry) in entries.iter().enumerate() {
+            let texture = &textures[&atlas.texture_id(entry.page).unwrap()];
+            let min = (entry.uv.min.to_vec2() * GlyphAtlas::SIZE as f32).round();
+            let max = (entry.uv.max.to_vec2() * GlyphAtlas::SIZE as f32).round();
+            assert_eq!(max - min, egui::vec2(100.0, 100.0));
+            for [x, y] in [[min.x, min.y], [max.x - 1.0, max.y - 1.0]] {
+                let pixel = texture.pixels[y as usize * GlyphAtlas::SIZE + x as usize];
+                assert_eq!(
+                    pixel,
+                    egui::Color32::from_gray(index as u8),
+                    "glyph {}",
+                    index
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,241 @@
+//! Vibrations des manettes. Le jeu demande une vibration à la fenêtre
+//! (`WindowState::haptics`, ex: `haptics.play(HapticPreset::Impact)`) ;
+//! `WindowManager::update_gamepads` relève ces demandes et les joue avec le retour de force
+//! de gilrs (`Gamepads::rumble`), sur les manettes qui le supportent.
+//!
+//! Une vibration pilote les deux moteurs des manettes (modèle XInput) : le gros moteur, basse
+//! fréquence, pour les chocs ; le petit, haute fréquence, pour les sensations fines. Son
+//! enveloppe monte depuis 0 pendant `attack` et redescend à 0 pendant `fade`.
+
+use std::time::Duration;
+
+use gilrs::ff::{BaseEffect, BaseEffectType, Envelope, Replay, Ticks};
+
+use crate::GamepadId;
+
+/// Vibration des deux moteurs d'une manette.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rumble {
+    /// Intensité du gros moteur (basse fréquence), 0..1.
+    pub strong: f32,
+    /// Intensité du petit moteur (haute fréquence), 0..1.
+    pub weak: f32,
+    pub duration: Duration,
+    /// Montée depuis 0 au début de la vibration.
+    pub attack: Duration,
+    /// Descente vers 0 à la fin de la vibration.
+    pub fade: Duration,
+}
+
+impl Rumble {
+    /// Pas de temps du retour de force de gilrs : les durées y sont arrondies.
+    const TICK_MS: u32 = 50;
+
+    pub fn new(strong: f32, weak: f32, duration: Duration) -> Self {
+        Self {
+            strong,
+            weak,
+            duration,
+            attack: Duration::ZERO,
+            fade: Duration::ZERO,
+        }
+    }
+
+    pub fn attack(mut self, attack: Duration) -> Self {
+        self.attack = attack;
+        self
+    }
+
+    pub fn fade(mut self, fade: Duration) -> Self {
+        self.fade = fade;
+        self
+    }
+
+    /// Intensités multipliées par `factor` (voir `Haptics::intensity`).
+    pub fn scaled(self, factor: f32) -> Self {
+        Self {
+            strong: (self.strong * factor).clamp(0.0, 1.0),
+            weak: (self.weak * factor).clamp(0.0, 1.0),
+            ..self
+        }
+    }
+
+    fn ticks(duration: Duration) -> u32 {
+        (duration.as_millis() as u32).div_ceil(Self::TICK_MS)
+    }
+
+    /// Durée de la vibration, en pas de gilrs (au moins un).
+    pub(crate) fn play_for(&self) -> Ticks {
+        Ticks::from_ms(Self::ticks(self.duration).max(1) * Self::TICK_MS)
+    }
+
+    /// Effets gilrs des deux moteurs. L'attaque et la descente sont raccourcies si elles ne
+    /// tiennent pas dans la durée (gilrs les veut strictement plus courtes).
+    pub(crate) fn base_effects(&self) -> [BaseEffect; 2] {
+        let length = Self::ticks(self.duration).max(1);
+        let (mut attack, mut fade) = (Self::ticks(self.attack), Self::ticks(self.fade));
+        while attack + fade >= length && attack + fade > 0 {
+            match attack > fade {
+                true => attack -= 1,
+                false => fade -= 1,
+            }
+        }
+        let envelope = Envelope {
+            attack_length: Ticks::from_ms(attack * Self::TICK_MS),
+            attack_level: 0.0,
+            fade_length: Ticks::from_ms(fade * Self::TICK_MS),
+            fade_level: 0.0,
+        };
+        let scheduling = Replay {
+            play_for: self.play_for(),
+            ..Default::default()
+        };
+        let magnitude = |intensity: f32| (intensity.clamp(0.0, 1.0) * u16::MAX as f32) as u16;
+        [
+            BaseEffect {
+                kind: BaseEffectType::Strong {
+                    magnitude: magnitude(self.strong),
+                },
+                scheduling,
+                envelope,
+            },
+            BaseEffect {
+                kind: BaseEffectType::Weak {
+                    magnitude: magnitude(self.weak),
+                },
+                scheduling,
+                envelope,
+            },
+        ]
+    }
+}
+
+/// Vibrations toutes faites.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HapticPreset {
+    /// Petit retour de sélection (menu, ramassage).
+    Tick,
+    /// Coup reçu ou donné.
+    Impact,
+    /// Explosion proche : forte, avec une longue descente.
+    Explosion,
+    /// Moteur ou grondement continu, sur le petit moteur.
+    Engine,
+}
+
+impl HapticPreset {
+    pub const ALL: [HapticPreset; 4] = [
+        HapticPreset::Tick,
+        HapticPreset::Impact,
+        HapticPreset::Explosion,
+        HapticPreset::Engine,
+    ];
+
+    pub fn rumble(self) -> Rumble {
+        let ms = Duration::from_millis;
+        match self {
+            HapticPreset::Tick => Rumble::new(0.0, 0.4, ms(40)),
+            HapticPreset::Impact => Rumble::new(0.8, 0.5, ms(180)).fade(ms(80)),
+            HapticPreset::Explosion => Rumble::new(1.0, 0.7, ms(700)).attack(ms(30)).fade(ms(450)),
+            HapticPreset::Engine => Rumble::new(0.2, 0.35, ms(1000))
+                .attack(ms(200))
+                .fade(ms(200)),
+        }
+    }
+}
+
+/// Vibration demandée, pour une manette ou pour toutes (`gamepad: None`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HapticRequest {
+    pub gamepad: Option<GamepadId>,
+    pub rumble: Rumble,
+}
+
+/// Demandes de vibration d'une fenêtre, jouées par `WindowManager::update_gamepads`.
+#[derive(Debug, Clone)]
+pub struct Haptics {
+    /// Réglage du joueur : coupé, aucune demande n'est gardée.
+    pub enabled: bool,
+    /// Multiplicateur des intensités (réglage du joueur), 0..1.
+    pub intensity: f32,
+    requests: Vec<HapticRequest>,
+}
+
+impl Default for Haptics {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            intensity: 1.0,
+            requests: Vec::new(),
+        }
+    }
+}
+
+impl Haptics {
+    /// Joue `preset` sur toutes les manettes.
+    pub fn play(&mut self, preset: HapticPreset) {
+        self.rumble(None, preset.rumble());
+    }
+
+    /// Joue `preset` sur une manette (joueur local...).
+    pub fn play_on(&mut self, gamepad: GamepadId, preset: HapticPreset) {
+        self.rumble(Some(gamepad), preset.rumble());
+    }
+
+    /// Vibration sur mesure, sur une manette ou sur toutes (`None`).
+    pub fn rumble(&mut self, gamepad: Option<GamepadId>, rumble: Rumble) {
+        if !self.enabled || self.intensity <= 0.0 {
+            return;
+        }
+        self.requests.push(HapticRequest {
+            gamepad,
+            rumble: rumble.scaled(self.intensity),
+        });
+    }
+
+    /// Demandes en attente, retirées de la file.
+    pub fn take_requests(&mut self) -> Vec<HapticRequest> {
+        std::mem::take(&mut self.requests)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requests_are_scaled_and_envelopes_fit() {
+        let mut haptics = Haptics {
+            intensity: 0.5,
+            ..Default::default()
+        };
+        haptics.play(HapticPreset::Impact);
+        haptics.play_on(GamepadId(2), HapticPreset::Tick);
+        let requests = haptics.take_requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].gamepad, None);
+        assert!((requests[0].rumble.strong - 0.4).abs() < 1e-6);
+        assert_eq!(requests[1].gamepad, Some(GamepadId(2)));
+        assert!(haptics.take_requests().is_empty());
+
+        haptics.enabled = false;
+        haptics.play(HapticPreset::Explosion);
+        assert!(haptics.take_requests().is_empty());
+
+        // Attaque + descente plus longues que la vibration : raccourcies
+        let rumble = Rumble::new(1.0, 0.0, Duration::from_millis(100))
+            .attack(Duration::from_millis(80))
+            .fade(Duration::from_millis(80));
+        let [strong, weak] = rumble.base_effects();
+        assert_eq!(
+            strong.kind,
+            BaseEffectType::Strong {
+                magnitude: u16::MAX
+            }
+        );
+        assert_eq!(weak.kind, BaseEffectType::Weak { magnitude: 0 });
+        let envelope = strong.envelope;
+        assert!(envelope.attack_length + envelope.fade_length < strong.scheduling.play_for);
+        assert_eq!(strong.scheduling.play_for, rumble.play_for());
+    }
+}
@@ -0,0 +1,494 @@
+//! HUD déclaratif : des widgets (textes, barres de progression) liés au `Blackboard` ou à la
+//! scène, avec conditions de visibilité. Le HUD est décrit une fois ; `Hud::ui` le dessine
+//! chaque frame (egui) et ne recalcule les valeurs liées au blackboard que lorsqu'il a changé.
+//!
+//! ```ignore
+//! let mut hud = Hud::new();
+//! hud.add(HudWidget::text("score", "Score: {score}").anchor(egui::Align2::RIGHT_TOP));
+//! hud.add(
+//!     HudWidget::progress("health", Binding::key("health"), Binding::key("health_max"))
+//!         .visible_if(Condition::IsTrue(Binding::key("alive"))),
+//! );
+//! ```
+
+use std::sync::Arc;
+
+use crate::{
+    Blackboard, BlackboardValue, GlyphStyle, MarkupIcons, MarkupText, Scene, TextLayoutCache,
+};
+
+/// Lecture de scène utilisée par `Binding::Scene`.
+pub type SceneBinding = Arc<dyn Fn(&Scene) -> Option<BlackboardValue> + Send + Sync>;
+
+/// Source d'une valeur affichée par un widget.
+#[derive(Clone)]
+pub enum Binding {
+    Constant(BlackboardValue),
+    /// Entrée du blackboard.
+    Key(String),
+    /// Valeur lue dans la scène (ECS), réévaluée à chaque frame.
+    Scene(SceneBinding),
+}
+
+impl Binding {
+    pub fn key(key: impl Into<String>) -> Self {
+        Binding::Key(key.into())
+    }
+
+    pub fn constant(value: impl Into<BlackboardValue>) -> Self {
+        Binding::Constant(value.into())
+    }
+
+    /// Ex: `Binding::scene(|scene| Some((scene.entity_count() as i64).into()))`.
+    pub fn scene(read: impl Fn(&Scene) -> Option<BlackboardValue> + Send + Sync + 'static) -> Self {
+        Binding::Scene(Arc::new(read))
+    }
+
+    /// Valeur courante ; les liaisons de scène valent `None` sans scène.
+    pub fn resolve(
+        &self,
+        blackboard: &Blackboard,
+        scene: Option<&Scene>,
+    ) -> Option<BlackboardValue> {
+        match self {
+            Binding::Constant(value) => Some(value.clone()),
+            Binding::Key(key) => blackboard.get(key).cloned(),
+            Binding::Scene(read) => scene.and_then(|scene| read(scene)),
+        }
+    }
+
+    fn is_dynamic(&self) -> bool {
+        matches!(self, Binding::Scene(_))
+    }
+}
+
+/// Condition de visibilité d'un widget.
+#[derive(Clone)]
+pub enum Condition {
+    /// Booléen vrai, nombre non nul ou texte non vide.
+    IsTrue(Binding),
+    Equals(Binding, BlackboardValue),
+    Above(Binding, f64),
+    Below(Binding, f64),
+    Not(Box<Condition>),
+}
+
+impl Condition {
+    pub fn evaluate(&self, blackboard: &Blackboard, scene: Option<&Scene>) -> bool {
+        let number = |binding: &Binding| binding.resolve(blackboard, scene)?.as_float();
+        match self {
+            Condition::IsTrue(binding) => match binding.resolve(blackboard, scene) {
+                Some(BlackboardValue::Bool(value)) => value,
+                Some(BlackboardValue::Int(value)) => value != 0,
+                Some(BlackboardValue::Float(value)) => value != 0.0,
+                Some(BlackboardValue::Text(value)) => !value.is_empty(),
+                None => false,
+            },
+            Condition::Equals(binding, expected) => {
+                binding.resolve(blackboard, scene).as_ref() == Some(expected)
+            }
+            Condition::Above(binding, limit) => number(binding).is_some_and(|v| v > *limit),
+            Condition::Below(binding, limit) => number(binding).is_some_and(|v| v < *limit),
+            Condition::Not(condition) => !condition.evaluate(blackboard, scene),
+        }
+    }
+
+    fn is_dynamic(&self) -> bool {
+        match self {
+            Condition::IsTrue(binding)
+            | Condition::Equals(binding, _)
+            | Condition::Above(binding, _)
+            | Condition::Below(binding, _) => binding.is_dynamic(),
+            Condition::Not(condition) => condition.is_dynamic(),
+        }
+    }
+}
+
+/// Contenu d'un widget.
+#[derive(Clone)]
+pub enum WidgetKind {
+    /// Texte où `{clé}` est remplacé par la valeur du blackboard (`{{` / `}}` pour des
+    /// accolades).
+    Text(String),
+    /// Valeur liée affichée telle quelle.
+    Value(Binding),
+    /// Barre remplie à `value / max`, avec `label` (modèle comme `Text`) par-dessus.
+    Progress {
+        value: Binding,
+        max: Binding,
+        label: Option<String>,
+    },
+}
+
+/// Widget du HUD, placé par rapport à un coin / bord de l'écran.
+#[derive(Clone)]
+pub struct HudWidget {
+    pub id: String,
+    pub kind: WidgetKind,
+    pub anchor: egui::Align2,
+    /// Décalage depuis l'ancre, en points.
+    pub offset: [f32; 2],
+    pub visible: Option<Condition>,
+    /// Largeur des barres de progression.
+    pub width: f32,
+    /// Le texte (hors barres de progression) contient des balises `MarkupText`.
+    pub markup: bool,
+}
+
+impl HudWidget {
+    pub fn new(id: impl Into<String>, kind: WidgetKind) -> Self {
+        Self {
+            id: id.into(),
+            kind,
+            anchor: egui::Align2::LEFT_TOP,
+            offset: [8.0, 8.0],
+            visible: None,
+            width: 160.0,
+            markup: false,
+        }
+    }
+
+    pub fn text(id: impl Into<String>, template: impl Into<String>) -> Self {
+        Self::new(id, WidgetKind::Text(template.into()))
+    }
+
+    pub fn value(id: impl Into<String>, binding: Binding) -> Self {
+        Self::new(id, WidgetKind::Value(binding))
+    }
+
+    pub fn progress(id: impl Into<String>, value: Binding, max: Binding) -> Self {
+        Self::new(
+            id,
+            WidgetKind::Progress {
+                value,
+                max,
+                label: None,
+            },
+        )
+    }
+
+    pub fn anchor(mut self, anchor: egui::Align2) -> Self {
+        self.anchor = anchor;
+        self
+    }
+
+    pub fn offset(mut self, x: f32, y: f32) -> Self {
+        self.offset = [x, y];
+        self
+    }
+
+    pub fn visible_if(mut self, condition: Condition) -> Self {
+        self.visible = Some(condition);
+        self
+    }
+
+    /// Interprète les balises du texte (`[color=...]`, `[wave]`, `[icon=...]`, voir
+    /// `MarkupText`), après le remplacement des `{clé}`.
+    pub fn markup(mut self) -> Self {
+        self.markup = true;
+        self
+    }
+
+    /// Texte des barres de progression (modèle, voir `WidgetKind::Text`).
+    pub fn label(mut self, template: impl Into<String>) -> Self {
+        if let WidgetKind::Progress { label, .. } = &mut self.kind {
+            *label = Some(template.into());
+        }
+        self
+    }
+
+    /// Le widget dépend de la scène : il est réévalué à chaque frame.
+    fn is_dynamic(&self) -> bool {
+        let kind = match &self.kind {
+            WidgetKind::Text(_) => false,
+            WidgetKind::Value(binding) => binding.is_dynamic(),
+            WidgetKind::Progress { value, max, .. } => value.is_dynamic() || max.is_dynamic(),
+        };
+        kind || self.visible.as_ref().is_some_and(Condition::is_dynamic)
+    }
+
+    fn resolve(
+        &self,
+        blackboard: &Blackboard,
+        scene: Option<&Scene>,
+        icons: Option<&MarkupIcons>,
+    ) -> ResolvedWidget {
+        let visible = self
+            .visible
+            .as_ref()
+            .is_none_or(|condition| condition.evaluate(blackboard, scene));
+        let (text, fraction) = match &self.kind {
+            WidgetKind::Text(template) => (format_template(template, blackboard), None),
+            WidgetKind::Value(binding) => (
+                binding
+                    .resolve(blackboard, scene)
+                    .map(|value| value.to_string())
+                    .unwrap_or_default(),
+                None,
+            ),
+            WidgetKind::Progress { value, max, label } => {
+                let value = value.resolve(blackboard, scene).and_then(|v| v.as_float());
+                let max = max.resolve(blackboard, scene).and_then(|v| v.as_float());
+                let fraction = match (value, max) {
+                    (Some(value), Some(max)) if max > 0.0 => (value / max).clamp(0.0, 1.0),
+                    _ => 0.0,
+                };
+                let text = label
+                    .as_ref()
+                    .map(|label| format_template(label, blackboard))
+                    .unwrap_or_default();
+                (text, Some(fraction as f32))
+            }
+        };
+        let markup = (self.markup && fraction.is_none()).then(|| {
+            let mut markup = MarkupText::parse(&text);
+            if let Some(icons) = icons {
+                markup.replace_emoji(|name| icons.icon(name).is_some());
+            }
+            markup
+        });
+        ResolvedWidget {
+            visible,
+            text,
+            fraction,
+            markup,
+        }
+    }
+}
+
+/// Valeurs affichées d'un widget, gardées entre deux changements du blackboard.
+#[derive(Debug, Clone, PartialEq)]
+struct ResolvedWidget {
+    visible: bool,
+    text: String,
+    fraction: Option<f32>,
+    markup: Option<MarkupText>,
+}
+
+/// Ensemble de widgets liés à l'état du jeu.
+#[derive(Clone, Default)]
+pub struct Hud {
+    widgets: Vec<HudWidget>,
+    resolved: Vec<ResolvedWidget>,
+    /// Révision du blackboard des valeurs de `resolved` (`None` : à recalculer).
+    revision: Option<u64>,
+    /// Mise en page des textes, refaite seulement quand un texte change.
+    text_layouts: TextLayoutCache,
+    /// Atlas des balises `[icon=...]`.
+    icons: Option<MarkupIcons>,
+}
+
+impl Hud {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, widget: HudWidget) {
+        self.widgets.push(widget);
+        self.revision = None;
+    }
+
+    pub fn remove(&mut self, id: &str) -> Option<HudWidget> {
+        let index = self.widgets.iter().position(|widget| widget.id == id)?;
+        self.revision = None;
+        Some(self.widgets.remove(index))
+    }
+
+    /// Atlas des balises `[icon=...]` et des emoji des widgets à balises.
+    pub fn set_icons(&mut self, icons: Option<MarkupIcons>) {
+        self.icons = icons;
+        self.revision = None;
+    }
+
+    pub fn widgets(&self) -> &[HudWidget] {
+        &self.widgets
+    }
+
+    /// Met à jour les valeurs affichées : tout est recalculé si le blackboard a changé,
+    /// seuls les widgets liés à la scène sinon.
+    fn refresh(&mut self, blackboard: &Blackboard, scene: Option<&Scene>) {
+        if self.revision != Some(blackboard.revision()) {
+            self.resolved = self
+                .widgets
+                .iter()
+                .map(|widget| widget.resolve(blackboard, scene, self.icons.as_ref()))
+                .collect();
+            self.revision = Some(blackboard.revision());
+            return;
+        }
+        for (widget, resolved) in self.widgets.iter().zip(&mut self.resolved) {
+            if widget.is_dynamic() {
+                *resolved = widget.resolve(blackboard, scene, self.icons.as_ref());
+            }
+        }
+    }
+
+    /// Dessine le HUD par-dessus l'écran.
+    pub fn ui(&mut self, ctx: &egui::Context, blackboard: &Blackboard, scene: &Scene) {
+        self.refresh(blackboard, Some(scene));
+        let style = ctx.style();
+        let (label_font, bar_font) = (
+            egui::TextStyle::Body.resolve(&style),
+            egui::TextStyle::Button.resolve(&style),
+        );
+
+        for (widget, resolved) in self.widgets.iter().zip(&self.resolved) {
+            if !resolved.visible {
+                continue;
+            }
+            let [x, y] = widget.offset;
+            // Décalage vers l'intérieur de l'écran, quel que soit le coin
+            let inward = egui::vec2(
+                match widget.anchor.x() {
+                    egui::Align::Max => -x,
+                    _ => x,
+                },
+                match widget.anchor.y() {
+                    egui::Align::Max => -y,
+                    _ => y,
+                },
+            );
+            egui::Area::new(egui::Id::new(("hud", &widget.id)))
+                .anchor(widget.anchor, inward)
+                .interactable(false)
+                .show(ctx, |ui| match (resolved.fraction, &resolved.markup) {
+                    (Some(fraction), _) => {
+                        let bar = egui::ProgressBar::new(fraction).desired_width(widget.width);
+                        // Lecteurs d'écran : libellé (l'id si la barre n'a pas de texte) et
+                        // valeur, qu'egui ne renseigne pas pour une barre de progression
+                        let label = match resolved.text.is_empty() {
+                            true => widget.id.as_str(),
+                            false => resolved.text.as_str(),
+                        };
+                        let response = ui.add(bar);
+                        response.widget_info(|| {
+                            let mut info = egui::WidgetInfo::labeled(
+                                egui::WidgetType::ProgressIndicator,
+                                true,
+                                label,
+                            );
+                            info.value = Some(fraction as f64);
+                            info
+                        });
+                        if !resolved.text.is_empty() {
+                            // Texte mis en forme dessiné par-dessus la barre, placé et coloré
+                            // comme le sien
+                            let text = self.text_layouts.layout(
+                                ctx,
+                                &resolved.text,
+                                &bar_font,
+                                f32::INFINITY,
+                            );
+                            let origin = response.rect.left_center()
+                                + egui::vec2(ui.spacing().item_spacing.x, -text.size.y * 0.5);
+                            let visuals = ui.visuals();
+                            let color = visuals
+                                .override_text_color
+                                .unwrap_or(visuals.selection.stroke.color);
+                            let painter = ui.painter().with_clip_rect(response.rect);
+                            self.text_layouts
+                                .paint(&painter, origin, &text, |_, _| GlyphStyle::new(color));
+                        }
+                    }
+                    (None, Some(markup)) => {
+                        markup.show(
+                            ui,
+                            &mut self.text_layouts,
+                            &label_font,
+                            egui::Color32::WHITE,
+                            self.icons.as_ref(),
+                        );
+                    }
+                    (None, None) => {
+                        self.text_layouts.label(
+                            ui,
+                            &resolved.text,
+                            &label_font,
+                            egui::Color32::WHITE,
+                        );
+                    }
+                });
+        }
+        self.text_layouts.end_frame();
+    }
+}
+
+/// Remplace les `{clé}` de `template` par les valeurs du blackboard (vide si absente).
+pub fn format_template(template: &str, blackboard: &Blackboard) -> String {
+    let mut result = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find(['{', '}']) {
+        result.push_str(&rest[..start]);
+        let brace = &rest[start..];
+        if brace.starts_with("{{") || brace.starts_with("}}") {
+            result.push_str(&brace[..1]);
+            rest = &brace[2..];
+            continue;
+        }
+        match (brace.starts_with('{'), brace.find('}')) {
+            (true, Some(end)) => {
+                if let Some(value) = blackboard.get(brace[1..end].trim()) {
+                    result.push_str(&value.to_string());
+                }
+                rest = &brace[end + 1..];
+            }
+            _ => {
+                result.push_str(&brace[..1]);
+                rest = &brace[1..];
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn templates_read_the_blackboard() {
+        let mut blackboard = Blackboard::new();
+        blackboard.set("score", 1200);
+        blackboard.set("name", "Ana");
+        assert_eq!(
+            format_template("{name}: {score} pts {{x}} {missing}", &blackboard),
+            "Ana: 1200 pts {x} "
+        );
+        assert_eq!(format_template("a } b {", &blackboard), "a } b {");
+    }
+
+    #[test]
+    fn widgets_refresh_when_the_blackboard_changes() {
+        let mut blackboard = Blackboard::new();
+        blackboard.set("health", 50);
+        blackboard.set("health_max", 200);
+        let mut hud = Hud::new();
+        hud.add(
+            HudWidget::progress("health", Binding::key("health"), Binding::key("health_max"))
+                .label("{health} HP")
+                .visible_if(Condition::Not(Box::new(Condition::IsTrue(Binding::key(
+                    "dead",
+                ))))),
+        );
+        hud.add(
+            HudWidget::text("boss", "Boss!")
+                .visible_if(Condition::Above(Binding::key("wave"), 4.0)),
+        );
+
+        hud.refresh(&blackboard, None);
+        assert_eq!(hud.resolved[0].fraction, Some(0.25));
+        assert_eq!(hud.resolved[0].text, "50 HP");
+        assert!(hud.resolved[0].visible && !hud.resolved[1].visible);
+
+        blackboard.set("health", 400);
+        blackboard.set("wave", 5);
+        hud.refresh(&blackboard, None);
+        assert_eq!(hud.resolved[0].fraction, Some(1.0));
+        assert!(hud.resolved[1].visible);
+
+        blackboard.set("dead", true);
+        hud.refresh(&blackboard, None);
+        assert!(!hud.resolved[0].visible);
+    }
+}
@@ -0,0 +1,616 @@
+//! Pipeline d'import des assets : chaque asset source (image, atlas...) produit un artefact
+//! traité, écrit dans `ImportPipeline::ARTIFACT_DIR`, et un fichier `.meta` posé à côté de
+//! la source (`AssetMeta`).
+//!
+//! Le `.meta` garde l'identifiant stable de l'asset (UUID, conservé quand l'asset est
+//! réimporté), ses réglages d'import (filtrage, répétition, sRGB, mipmaps, atlas) et les hashes de la source et
+//! de ses dépendances lors du dernier import :
+//! ```text
+//! uuid = 3f2a8c1e-4b7d-4e0f-9a51-6c2d8e7b1f04
+//! filter = nearest
+//! wrap = clamp
+//! srgb = true
+//! mipmaps = false
+//! atlas = assets/sprites/characters.atlas
+//! source = 9c1f0e2d3b4a5968
+//! settings = 0a1b2c3d4e5f6071
+//! dependency assets/sprites/hero.png = 77e3a1c05b2d9f48
+//! ```
+//! Modifier la source, une dépendance ou les réglages du `.meta` rend l'asset périmé :
+//! `ImportPipeline::reimport_changed` (appelé par l'éditeur avec les changements de
+//! l'`AssetWatcher`) le réimporte.
+
+use std::{io::Cursor, sync::Arc};
+
+use anyhow::{Context, Result, anyhow, bail};
+use egui_wgpu::wgpu;
+use uuid::Uuid;
+
+use crate::{AssetLoader, AtlasMetadata, Texture2D, TextureDescriptor2D, Vfs};
+
+/// Hash 64 bits (FNV-1a) du contenu d'un fichier : stable d'une exécution et d'une version
+/// du compilateur à l'autre, contrairement à `DefaultHasher`.
+pub fn content_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Filtrage d'une texture importée.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TextureFilter {
+    /// Pixels nets (pixel art).
+    #[default]
+    Nearest,
+    Linear,
+}
+
+impl TextureFilter {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TextureFilter::Nearest => "nearest",
+            TextureFilter::Linear => "linear",
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Self> {
+        match text {
+            "nearest" => Ok(TextureFilter::Nearest),
+            "linear" => Ok(TextureFilter::Linear),
+            _ => bail!("unknown filter {:?} (expected nearest or linear)", text),
+        }
+    }
+
+    pub fn to_wgpu(self) -> wgpu::FilterMode {
+        match self {
+            TextureFilter::Nearest => wgpu::FilterMode::Nearest,
+            TextureFilter::Linear => wgpu::FilterMode::Linear,
+        }
+    }
+}
+
+/// Répétition d'une texture importée hors de [0, 1].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TextureWrap {
+    #[default]
+    Clamp,
+    Repeat,
+    /// Répétée en miroir une fois sur deux.
+    Mirror,
+}
+
+impl TextureWrap {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TextureWrap::Clamp => "clamp",
+            TextureWrap::Repeat => "repeat",
+            TextureWrap::Mirror => "mirror",
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Self> {
+        match text {
+            "clamp" => Ok(TextureWrap::Clamp),
+            "repeat" => Ok(TextureWrap::Repeat),
+            "mirror" => Ok(TextureWrap::Mirror),
+            _ => bail!("unknown wrap {:?} (expected clamp, repeat or mirror)", text),
+        }
+    }
+
+    pub fn to_wgpu(self) -> wgpu::AddressMode {
+        match self {
+            TextureWrap::Clamp => wgpu::AddressMode::ClampToEdge,
+            TextureWrap::Repeat => wgpu::AddressMode::Repeat,
+            TextureWrap::Mirror => wgpu::AddressMode::MirrorRepeat,
+        }
+    }
+}
+
+/// Réglages d'import d'un asset, modifiables dans son `.meta`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportSettings {
+    pub filter: TextureFilter,
+    pub wrap: TextureWrap,
+    /// Texels décodés en sRGB ; `false` pour les textures de données (normal maps).
+    pub srgb: bool,
+    /// Génère les mipmaps de la texture.
+    pub mipmaps: bool,
+    /// Atlas (`.atlas`) dont l'image fait partie : il est réimporté avec elle.
+    pub atlas: Option<String>,
+}
+
+impl Default for ImportSettings {
+    fn default() -> Self {
+        Self {
+            filter: TextureFilter::Nearest,
+            wrap: TextureWrap::Clamp,
+            srgb: true,
+            mipmaps: false,
+            atlas: None,
+        }
+    }
+}
+
+impl ImportSettings {
+    /// Hash des réglages, comparé à celui du dernier import.
+    pub fn hash(&self) -> u64 {
+        let atlas = self.atlas.as_deref().unwrap_or("");
+        let settings = format!(
+            "{} {} {} {} {}",
+            self.filter.as_str(),
+            self.wrap.as_str(),
+            self.srgb,
+            self.mipmaps,
+            atlas
+        );
+        content_hash(settings.as_bytes())
+    }
+
+    /// Création de la texture avec ces réglages.
+    pub fn texture_descriptor(&self) -> TextureDescriptor2D {
+        TextureDescriptor2D::new()
+            .with_filter(self.filter.to_wgpu())
+            .with_address_mode(self.wrap.to_wgpu())
+            .with_srgb(self.srgb)
+            .with_mipmaps(self.mipmaps)
+    }
+}
+
+/// Sidecar `<asset>.meta` d'un asset importé (format : voir le module).
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssetMeta {
+    /// Identifiant stable de l'asset, nom de son artefact.
+    pub uuid: Uuid,
+    pub settings: ImportSettings,
+    /// Hash de la source au dernier import (`None` : jamais importé).
+    pub source_hash: Option<u64>,
+    /// Hash des réglages au dernier import.
+    pub settings_hash: Option<u64>,
+    /// Dépendances (chemins VFS) et leur hash au dernier import.
+    pub dependencies: Vec<(String, u64)>,
+}
+
+impl Default for AssetMeta {
+    fn default() -> Self {
+        Self {
+            uuid: Uuid::new_v4(),
+            settings: ImportSettings::default(),
+            source_hash: None,
+            settings_hash: None,
+            dependencies: Vec::new(),
+        }
+    }
+}
+
+impl AssetMeta {
+    /// Extension des fichiers de métadonnées.
+    pub const EXTENSION: &str = "meta";
+
+    /// Chemin du `.meta` de l'asset `asset`.
+    pub fn path_of(asset: &str) -> String {
+        format!("{}.{}", asset, Self::EXTENSION)
+    }
+
+    /// Asset décrit par le `.meta` `path`, `None` si `path` n'est pas un `.meta`.
+    pub fn asset_of(path: &str) -> Option<&str> {
+        path.strip_suffix(Self::EXTENSION)?.strip_suffix('.')
+    }
+
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut uuid = None;
+        let mut meta = Self::default();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| anyhow!("line {}: expected `key = value`", number + 1))?;
+            let (key, value) = (key.trim(), value.trim());
+            let context = || format!("line {}: invalid {}", number + 1, key);
+            let hash = |value: &str| u64::from_str_radix(value, 16).with_context(context);
+
+            match key {
+                "uuid" => uuid = Some(Uuid::parse_str(value).with_context(context)?),
+                "filter" => {
+                    meta.settings.filter = TextureFilter::parse(value).with_context(context)?
+                }
+                "wrap" => meta.settings.wrap = TextureWrap::parse(value).with_context(context)?,
+                "srgb" => meta.settings.srgb = value.parse().with_context(context)?,
+                "mipmaps" => meta.settings.mipmaps = value.parse().with_context(context)?,
+                "atlas" => meta.settings.atlas = Some(value.to_string()),
+                "source" => meta.source_hash = Some(hash(value)?),
+                "settings" => meta.settings_hash = Some(hash(value)?),
+                _ => {
+                    let Some(dependency) = key.strip_prefix("dependency ") else {
+                        log::warn!("Unknown meta entry {:?} (line {})", key, number + 1);
+                        continue;
+                    };
+                    meta.dependencies
+                        .push((dependency.trim().to_string(), hash(value)?));
+                }
+            }
+        }
+
+        meta.uuid = uuid.ok_or_else(|| anyhow!("missing `uuid` entry"))?;
+        Ok(meta)
+    }
+
+    pub fn encode(&self) -> String {
+        let mut text = format!(
+            "uuid = {}\nfilter = {}\nwrap = {}\nsrgb = {}\nmipmaps = {}\n",
+            self.uuid,
+            self.settings.filter.as_str(),
+            self.settings.wrap.as_str(),
+            self.settings.srgb,
+            self.settings.mipmaps
+        );
+        if let Some(atlas) = &self.settings.atlas {
+            text += &format!("atlas = {}\n", atlas);
+        }
+        if let Some(hash) = self.source_hash {
+            text += &format!("source = {:016x}\n", hash);
+        }
+        if let Some(hash) = self.settings_hash {
+            text += &format!("settings = {:016x}\n", hash);
+        }
+        for (dependency, hash) in &self.dependencies {
+            text += &format!("dependency {} = {:016x}\n", dependency, hash);
+        }
+        text
+    }
+
+    /// `.meta` de l'asset `asset`, `None` s'il n'en a pas encore.
+    pub fn load(vfs: &Vfs, asset: &str) -> Result<Option<Self>> {
+        let path = Self::path_of(asset);
+        if !vfs.exists(&path) {
+            return Ok(None);
+        }
+        let text = vfs.read_to_string(&path)?;
+        Self::parse(&text)
+            .map(Some)
+            .with_context(|| format!("failed to parse {:?}", path))
+    }
+
+    pub fn save(&self, vfs: &Vfs, asset: &str) -> Result<()> {
+        vfs.write_bytes(&Self::path_of(asset), self.encode().as_bytes())
+    }
+}
+
+/// Résultat d'un import.
+pub struct ImportedAsset {
+    /// Contenu de l'artefact.
+    pub artifact: Vec<u8>,
+    /// Assets lus pour produire l'artefact (chemins VFS) : les modifier le réimporte.
+    pub dependencies: Vec<String>,
+}
+
+/// Transforme un type d'asset source en artefact.
+pub trait Importer: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// Extensions (en minuscules, sans point) des sources traitées.
+    fn extensions(&self) -> &[&str];
+
+    fn import(
+        &self,
+        path: &str,
+        source: &[u8],
+        settings: &ImportSettings,
+        vfs: &Vfs,
+    ) -> Result<ImportedAsset>;
+}
+
+/// Images : décodées puis réencodées en PNG RGBA8, que `Texture2D::from_bytes` lit sans
+/// conversion quel que soit le format source.
+pub struct TextureImporter;
+
+impl Importer for TextureImporter {
+    fn name(&self) -> &str {
+        "texture"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["png", "jpg", "jpeg", "bmp", "tga", "gif", "webp"]
+    }
+
+    fn import(
+        &self,
+        path: &str,
+        source: &[u8],
+        _settings: &ImportSettings,
+        _vfs: &Vfs,
+    ) -> Result<ImportedAsset> {
+        let image = image::load_from_memory(source)
+            .with_context(|| format!("failed to decode image {:?}", path))?
+            .to_rgba8();
+        let mut artifact = Vec::new();
+        image
+            .write_to(&mut Cursor::new(&mut artifact), image::ImageFormat::Png)
+            .with_context(|| format!("failed to encode {:?}", path))?;
+        Ok(ImportedAsset {
+            artifact,
+            dependencies: Vec::new(),
+        })
+    }
+}
+
+/// Atlas (`AtlasMetadata`) : validés et normalisés ; l'image du sprite sheet est une
+/// dépendance.
+pub struct AtlasImporter;
+
+impl Importer for AtlasImporter {
+    fn name(&self) -> &str {
+        "atlas"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &[AtlasMetadata::EXTENSION]
+    }
+
+    fn import(
+        &self,
+        path: &str,
+        source: &[u8],
+        _settings: &ImportSettings,
+        vfs: &Vfs,
+    ) -> Result<ImportedAsset> {
+        let text =
+            std::str::from_utf8(source).with_context(|| format!("{:?} is not UTF-8", path))?;
+        let metadata = AtlasMetadata::parse(text)
+            .with_context(|| format!("failed to parse atlas {:?}", path))?;
+        if !vfs.exists(&metadata.image) {
+            bail!(
+                "atlas {:?} references missing image {:?}",
+                path,
+                metadata.image
+            );
+        }
+        Ok(ImportedAsset {
+            artifact: metadata.encode().into_bytes(),
+            dependencies: vec![metadata.image],
+        })
+    }
+}
+
+/// Importe les assets avec l'`Importer` de leur extension et tient leurs `.meta` à jour.
+/// Les dépendances des imports sont déclarées dans le graphe du loader : l'`AssetWatcher`
+/// retourne un asset quand une de ses dépendances change.
+pub struct ImportPipeline {
+    loader: AssetLoader,
+    importers: Vec<Arc<dyn Importer>>,
+}
+
+impl ImportPipeline {
+    /// Dossier VFS des artefacts, nommés d'après l'UUID de leur asset (dans le mount
+    /// writable des assets du projet).
+    pub const ARTIFACT_DIR: &str = "assets/.imported";
+
+    /// Pipeline avec les importers de textures et d'atlas.
+    pub fn new(loader: AssetLoader) -> Self {
+        let mut pipeline = Self {
+            loader,
+            importers: Vec::new(),
+        };
+        pipeline.register(TextureImporter);
+        pipeline.register(AtlasImporter);
+        pipeline
+    }
+
+    /// Ajoute un importer ; il remplace les précédents pour les extensions qu'il traite.
+    pub fn register(&mut self, importer: impl Importer + 'static) {
+        self.importers.insert(0, Arc::new(importer));
+    }
+
+    /// Importer de l'asset `path`, d'après son extension.
+    pub fn importer_for(&self, path: &str) -> Option<&dyn Importer> {
+        let (_, extension) = path.rsplit_once('.')?;
+        let extension = extension.to_lowercase();
+        self.importers
+            .iter()
+            .find(|importer| importer.extensions().contains(&extension.as_str()))
+            .map(|importer| importer.as_ref())
+    }
+
+    /// Chemin VFS de l'artefact d'un asset.
+    pub fn artifact_path(meta: &AssetMeta) -> String {
+        format!("{}/{}", Self::ARTIFACT_DIR, meta.uuid)
+    }
+
+    /// `true` si l'asset `path` a un importer et que son artefact manque ou est périmé
+    /// (source, réglages ou dépendance modifiés depuis le dernier import).
+    pub fn needs_import(&self, path: &str) -> Result<bool> {
+        if self.importer_for(path).is_none() {
+            return Ok(false);
+        }
+        let vfs = self.loader.vfs();
+        let Some(meta) = AssetMeta::load(vfs, path)? else {
+            return Ok(true);
+        };
+        if meta.settings_hash != Some(meta.settings.hash())
+            || meta.source_hash != Some(content_hash(&vfs.read_bytes(path)?))
+            || !vfs.exists(&Self::artifact_path(&meta))
+        {
+            return Ok(true);
+        }
+        Ok(meta.dependencies.iter().any(|(dependency, hash)| {
+            vfs.read_bytes(dependency)
+                .is_ok_and(|bytes| content_hash(&bytes) != *hash)
+                || !vfs.exists(dependency)
+        }))
+    }
+
+    /// Importe `path` : écrit son artefact et son `.meta` (créé au premier import, l'UUID
+    /// et les réglages existants sont gardés).
+    pub fn import(&self, path: &str) -> Result<AssetMeta> {
+        let importer = self
+            .importer_for(path)
+            .ok_or_else(|| anyhow!("no importer for {:?}", path))?;
+        let vfs = self.loader.vfs();
+        let mut meta = AssetMeta::load(vfs, path)?.unwrap_or_default();
+        let source = vfs.read_bytes(path)?;
+
+        let imported = importer
+            .import(path, &source, &meta.settings, vfs)
+            .with_context(|| format!("{} importer failed on {:?}", importer.name(), path))?;
+        vfs.write_bytes(&Self::artifact_path(&meta), &imported.artifact)?;
+
+        meta.source_hash = Some(content_hash(&source));
+        meta.settings_hash = Some(meta.settings.hash());
+        meta.dependencies = imported
+            .dependencies
+            .iter()
+            .map(|dependency| {
+                let bytes = vfs.read_bytes(dependency)?;
+                Ok((dependency.clone(), content_hash(&bytes)))
+            })
+            .collect::<Result<_>>()?;
+        meta.save(vfs, path)?;
+
+        for (dependency, _) in &meta.dependencies {
+            self.loader.add_dependency(path, dependency);
+        }
+        if let Some(atlas) = &meta.settings.atlas {
+            self.loader.add_dependency(atlas, path);
+        }
+        Ok(meta)
+    }
+
+    /// Importe les assets périmés sous `dir` ("" = tous les mounts). Retourne les assets
+    /// importés ; les échecs sont loggés.
+    pub fn import_dir(&self, dir: &str) -> Vec<String> {
+        let files = self.loader.vfs().list_files(dir);
+        self.reimport_changed(&files)
+    }
+
+    /// À appeler avec les changements de l'`AssetWatcher` : réimporte les assets modifiés
+    /// (ou dont le `.meta` a été modifié) s'ils sont périmés, dans l'ordre. Retourne les
+    /// assets réimportés ; les échecs sont loggés.
+    pub fn reimport_changed(&self, changed: &[String]) -> Vec<String> {
+        let mut imported: Vec<String> = Vec::new();
+        for path in changed {
+            let asset = AssetMeta::asset_of(path).unwrap_or(path);
+            if imported.iter().any(|done| done == asset) {
+                continue;
+            }
+            let result = self
+                .needs_import(asset)
+                .and_then(|stale| stale.then(|| self.import(asset)).transpose());
+            match result {
+                Ok(Some(_)) => imported.push(asset.to_string()),
+                Ok(None) => {}
+                Err(e) => log::error!("Failed to import {:?}: {:#}", asset, e),
+            }
+        }
+        imported
+    }
+
+    /// Charge la texture importée de `path` avec ses réglages (voir
+    /// `ImportSettings::texture_descriptor`). Sans
+    /// artefact (asset jamais importé), la source est chargée avec les réglages par défaut.
+    #[track_caller]
+    pub fn load_texture(
+        &self,
+        path: &str,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) -> Result<Texture2D> {
+        let vfs = self.loader.vfs();
+        let meta = AssetMeta::load(vfs, path)?;
+        let artifact = meta
+            .as_ref()
+            .map(Self::artifact_path)
+            .filter(|artifact| vfs.exists(artifact));
+        let settings = meta.map(|meta| meta.settings).unwrap_or_default();
+
+        let bytes = vfs.read_bytes(artifact.as_deref().unwrap_or(path))?;
+        let mut texture =
+            Texture2D::from_bytes_with(device, queue, &bytes, &settings.texture_descriptor())
+                .map_err(|e| anyhow!("failed to decode image {:?}: {}", path, e))?;
+        texture.set_path(path);
+        Ok(texture)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn meta_roundtrip_and_settings_hash() {
+        let meta = AssetMeta {
+            settings: ImportSettings {
+                filter: TextureFilter::Linear,
+                wrap: TextureWrap::Mirror,
+                srgb: false,
+                mipmaps: true,
+                atlas: Some("assets/sprites/characters.atlas".to_string()),
+            },
+            source_hash: Some(content_hash(b"hero")),
+            settings_hash: Some(7),
+            dependencies: vec![("assets/sprites/hero.png".to_string(), u64::MAX)],
+            ..AssetMeta::default()
+        };
+        assert_eq!(AssetMeta::parse(&meta.encode()).unwrap(), meta);
+        assert!(AssetMeta::parse("filter = nearest").is_err());
+        assert!(AssetMeta::parse("uuid = 0\n").is_err());
+
+        assert_ne!(meta.settings.hash(), ImportSettings::default().hash());
+        let descriptor = meta.settings.texture_descriptor();
+        assert_eq!(descriptor.address_mode, wgpu::AddressMode::MirrorRepeat);
+        assert_eq!(descriptor.format(), wgpu::TextureFormat::Rgba8Unorm);
+        assert_eq!(descriptor.mip_level_count(256, 64), 9);
+        assert_eq!(descriptor.mip_level_count(1, 1), 1);
+        assert_eq!(TextureDescriptor2D::new().mip_level_count(256, 64), 1);
+        assert_eq!(content_hash(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(
+            AssetMeta::asset_of(&AssetMeta::path_of("assets/hero.png")),
+            Some("assets/hero.png")
+        );
+        assert_eq!(AssetMeta::asset_of("assets/hero.png"), None);
+    }
+
+    #[test]
+    fn stale_assets_are_reimported() {
+        let png = |color: u8| {
+            let image = image::RgbaImage::from_pixel(2, 2, image::Rgba([color, 0, 0, 255]));
+            let mut bytes = Vec::new();
+            image
+                .write_to(&mut Cursor::new(&mut bytes), image::ImageFormat::Png)
+                .unwrap();
+            bytes
+        };
+        let vfs = Arc::new(Vfs::new());
+        let files = vfs.mount_memory("", "memory", true);
+        files.insert("hero.png", png(255));
+        files.insert(
+            "hero.atlas",
+            "image = hero.png\nframe idle = 0 0 2 2 0.5 1\n",
+        );
+        let pipeline = ImportPipeline::new(AssetLoader::new(vfs.clone()));
+
+        let all = vec!["hero.atlas".to_string(), "hero.png".to_string()];
+        assert_eq!(pipeline.reimport_changed(&all), all);
+        assert!(pipeline.reimport_changed(&all).is_empty());
+        let uuid = AssetMeta::load(&vfs, "hero.png").unwrap().unwrap().uuid;
+
+        // Réglages modifiés dans le .meta : réimporté avec le même UUID
+        let mut meta = AssetMeta::load(&vfs, "hero.png").unwrap().unwrap();
+        meta.settings.filter = TextureFilter::Linear;
+        meta.save(&vfs, "hero.png").unwrap();
+        assert_eq!(
+            pipeline.reimport_changed(&[AssetMeta::path_of("hero.png")]),
+            ["hero.png"]
+        );
+        assert_eq!(
+            AssetMeta::load(&vfs, "hero.png").unwrap().unwrap().uuid,
+            uuid
+        );
+
+        // L'atlas dépend de son image
+        files.insert("hero.png", png(0));
+        assert!(pipeline.needs_import("hero.atlas").unwrap());
+        assert!(!pipeline.needs_import("hero.atlas.meta").unwrap());
+    }
+}
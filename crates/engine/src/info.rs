@@ -0,0 +1,157 @@
+use egui_wgpu::wgpu;
+
+use crate::Engine;
+
+/// Informations sur le moteur et le GPU utilisé, capturées à la création du device.
+///
+/// Permet au jeu d'adapter ses réglages de qualité (limites, features) et à l'utilisateur
+/// de remonter sa configuration matérielle lors d'un rapport de bug.
+#[derive(Debug, Clone)]
+pub struct EngineInfo {
+    pub engine_name: &'static str,
+    pub engine_version: &'static str,
+    /// Nom, backend, driver, type de device...
+    pub adapter: wgpu::AdapterInfo,
+    /// Features supportées par l'adapter.
+    pub features: wgpu::Features,
+    /// Limites supportées par l'adapter.
+    pub limits: wgpu::Limits,
+    /// Formats supportés par la surface de la fenêtre.
+    pub surface_formats: Vec<wgpu::TextureFormat>,
+    /// Present modes supportés par la surface de la fenêtre.
+    pub present_modes: Vec<wgpu::PresentMode>,
+}
+
+impl EngineInfo {
+    /// Interroge l'adapter et les capacités de la surface.
+    pub fn query(adapter: &wgpu::Adapter, caps: &wgpu::SurfaceCapabilities) -> Self {
+        Self {
+            engine_name: Engine::NAME,
+            engine_version: env!("CARGO_PKG_VERSION"),
+            adapter: adapter.get_info(),
+            features: adapter.features(),
+            limits: adapter.limits(),
+            surface_formats: caps.formats.clone(),
+            present_modes: caps.present_modes.clone(),
+        }
+    }
+
+    /// Résumé sur une ligne, pratique pour les logs et les rapports de bug.
+    pub fn summary(&self) -> String {
+        format!(
+            "{} {} | {} ({:?}, {:?}) | driver: {} {}",
+            self.engine_name,
+            self.engine_version,
+            self.adapter.name,
+            self.adapter.backend,
+            self.adapter.device_type,
+            self.adapter.driver,
+            self.adapter.driver_info,
+        )
+    }
+
+    /// Dessine le contenu du panneau "About GPU".
+    pub fn ui(&self, ui: &mut egui::Ui) {
+        egui::Grid::new("engine_info_grid")
+            .num_columns(2)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label("Engine");
+                ui.label(format!("{} {}", self.engine_name, self.engine_version));
+                ui.end_row();
+
+                ui.label("Adapter");
+                ui.label(&self.adapter.name);
+                ui.end_row();
+
+                ui.label("Backend");
+                ui.label(format!("{:?}", self.adapter.backend));
+                ui.end_row();
+
+                ui.label("Device type");
+                ui.label(format!("{:?}", self.adapter.device_type));
+                ui.end_row();
+
+                ui.label("Vendor / device");
+                ui.label(format!(
+                    "{:#06x} / {:#06x}",
+                    self.adapter.vendor, self.adapter.device
+                ));
+                ui.end_row();
+
+                ui.label("Driver");
+                ui.label(format!(
+                    "{} {}",
+                    self.adapter.driver, self.adapter.driver_info
+                ));
+                ui.end_row();
+            });
+
+        ui.collapsing("Limits", |ui| {
+            egui::Grid::new("engine_info_limits")
+                .num_columns(2)
+                .striped(true)
+                .show(ui, |ui| {
+                    let limits = &self.limits;
+                    let rows: [(&str, u64); 8] = [
+                        (
+                            "max_texture_dimension_2d",
+                            limits.max_texture_dimension_2d as u64,
+                        ),
+                        (
+                            "max_texture_array_layers",
+                            limits.max_texture_array_layers as u64,
+                        ),
+                        ("max_bind_groups", limits.max_bind_groups as u64),
+                        (
+                            "max_sampled_textures_per_shader_stage",
+                            limits.max_sampled_textures_per_shader_stage as u64,
+                        ),
+                        ("max_vertex_buffers", limits.max_vertex_buffers as u64),
+                        ("max_vertex_attributes", limits.max_vertex_attributes as u64),
+                        ("max_buffer_size", limits.max_buffer_size),
+                        (
+                            "max_uniform_buffer_binding_size",
+                            limits.max_uniform_buffer_binding_size as u64,
+                        ),
+                    ];
+
+                    for (name, value) in rows {
+                        ui.label(name);
+                        ui.label(value.to_string());
+                        ui.end_row();
+                    }
+                });
+        });
+
+        ui.collapsing("Features", |ui| {
+            for (name, _) in self.features.iter_names() {
+                ui.label(name);
+            }
+        });
+
+        ui.collapsing("Surface formats", |ui| {
+            for format in &self.surface_formats {
+                ui.label(format!("{:?}", format));
+            }
+        });
+
+        ui.collapsing("Present modes", |ui| {
+            for mode in &self.present_modes {
+                ui.label(format!("{:?}", mode));
+            }
+        });
+
+        if ui.button("Copy to clipboard").clicked() {
+            ui.ctx().copy_text(self.summary());
+        }
+    }
+
+    /// Fenêtre egui "About GPU". `open` contrôle sa visibilité.
+    pub fn show_window(&self, ctx: &egui::Context, open: &mut bool) {
+        egui::Window::new("About GPU")
+            .open(open)
+            .resizable(true)
+            .show(ctx, |ui| self.ui(ui));
+    }
+}
@@ -0,0 +1,375 @@
+//! Actions d'entrée nommées : le jeu interroge "move_up" ou "zoom_in" plutôt que des touches.
+//! `InputMap` associe chaque action à des touches, boutons de souris, boutons et axes de
+//! manette ; `Input` garde l'état des périphériques (tenu par `WindowState`) et répond aux
+//! requêtes par action pour la frame en cours.
+//!
+//! ```ignore
+//! let input = window_state.input();
+//! let dx = input.axis("move_left", "move_right");
+//! if input.just_pressed("jump") { ... }
+//! ```
+//!
+//! `Input::end_frame` est appelé une fois par frame (fin de `WindowState::end_frame_and_draw`)
+//! pour que `just_pressed` / `just_released` ne durent qu'une frame.
+
+use std::collections::{HashMap, HashSet};
+
+use winit::{event::MouseButton, keyboard::KeyCode};
+
+use crate::{GamepadButton, InputButton, InputSettings};
+
+/// Axe analogique de manette.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GamepadAxis {
+    LeftStickX,
+    LeftStickY,
+    RightStickX,
+    RightStickY,
+    LeftTrigger,
+    RightTrigger,
+}
+
+impl GamepadAxis {
+    pub const ALL: [GamepadAxis; 6] = [
+        GamepadAxis::LeftStickX,
+        GamepadAxis::LeftStickY,
+        GamepadAxis::RightStickX,
+        GamepadAxis::RightStickY,
+        GamepadAxis::LeftTrigger,
+        GamepadAxis::RightTrigger,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            GamepadAxis::LeftStickX => "left_stick_x",
+            GamepadAxis::LeftStickY => "left_stick_y",
+            GamepadAxis::RightStickX => "right_stick_x",
+            GamepadAxis::RightStickY => "right_stick_y",
+            GamepadAxis::LeftTrigger => "left_trigger",
+            GamepadAxis::RightTrigger => "right_trigger",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|axis| axis.name() == name)
+    }
+}
+
+/// Demi-axe associé à une action : la valeur de l'action est la partie positive (ou
+/// négative) de l'axe, après ses réglages (zone morte, courbe... voir `InputSettings`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AxisBinding {
+    pub axis: GamepadAxis,
+    /// `true` : valeurs positives de l'axe (droite, bas, gâchette enfoncée).
+    pub positive: bool,
+}
+
+/// Associations action -> entrées.
+#[derive(Debug, Clone, Default)]
+pub struct InputMap {
+    buttons: HashMap<String, Vec<InputButton>>,
+    axes: HashMap<String, Vec<AxisBinding>>,
+}
+
+impl InputMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Actions de l'éditeur : déplacement de la caméra (ZQSD / WASD physiques, flèches,
+    /// stick gauche) et zoom.
+    pub fn editor() -> Self {
+        let mut map = Self::new();
+        for (action, key, arrow, button, positive) in [
+            (
+                "move_up",
+                KeyCode::KeyW,
+                KeyCode::ArrowUp,
+                GamepadButton::DPadUp,
+                false,
+            ),
+            (
+                "move_down",
+                KeyCode::KeyS,
+                KeyCode::ArrowDown,
+                GamepadButton::DPadDown,
+                true,
+            ),
+            (
+                "move_left",
+                KeyCode::KeyA,
+                KeyCode::ArrowLeft,
+                GamepadButton::DPadLeft,
+                false,
+            ),
+            (
+                "move_right",
+                KeyCode::KeyD,
+                KeyCode::ArrowRight,
+                GamepadButton::DPadRight,
+                true,
+            ),
+        ] {
+            map.bind(action, InputButton::Key(key));
+            map.bind(action, InputButton::Key(arrow));
+            map.bind(action, InputButton::Gamepad(button));
+            let axis = match action {
+                "move_up" | "move_down" => GamepadAxis::LeftStickY,
+                _ => GamepadAxis::LeftStickX,
+            };
+            map.bind_axis(action, axis, positive);
+        }
+        map.bind("zoom_in", InputButton::Key(KeyCode::Equal));
+        map.bind("zoom_in", InputButton::Gamepad(GamepadButton::RightBumper));
+        map.bind("zoom_out", InputButton::Key(KeyCode::Minus));
+        map.bind("zoom_out", InputButton::Gamepad(GamepadButton::LeftBumper));
+        map
+    }
+
+    pub fn bind(&mut self, action: impl Into<String>, button: InputButton) {
+        let buttons = self.buttons.entry(action.into()).or_default();
+        if !buttons.contains(&button) {
+            buttons.push(button);
+        }
+    }
+
+    /// Associe un demi-axe de manette à `action`.
+    pub fn bind_axis(&mut self, action: impl Into<String>, axis: GamepadAxis, positive: bool) {
+        self.axes
+            .entry(action.into())
+            .or_default()
+            .push(AxisBinding { axis, positive });
+    }
+
+    /// Retire toutes les entrées de `action`.
+    pub fn unbind(&mut self, action: &str) {
+        self.buttons.remove(action);
+        self.axes.remove(action);
+    }
+
+    pub fn bindings(&self, action: &str) -> &[InputButton] {
+        self.buttons.get(action).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn axis_bindings(&self, action: &str) -> &[AxisBinding] {
+        self.axes.get(action).map_or(&[], Vec::as_slice)
+    }
+
+    /// Noms des actions (non triés).
+    pub fn actions(&self) -> impl Iterator<Item = &str> {
+        let axes_only = self
+            .axes
+            .keys()
+            .filter(|action| !self.buttons.contains_key(*action));
+        self.buttons.keys().chain(axes_only).map(String::as_str)
+    }
+}
+
+/// État des périphériques et actions, frame par frame.
+#[derive(Debug, Clone, Default)]
+pub struct Input {
+    pub map: InputMap,
+    /// Zones mortes, sensibilité et courbes des axes et de la souris.
+    pub settings: InputSettings,
+    down: HashSet<InputButton>,
+    /// Entrées enfoncées à la fin de la frame précédente.
+    previous: HashSet<InputButton>,
+    axes: HashMap<GamepadAxis, f32>,
+    previous_axes: HashMap<GamepadAxis, f32>,
+}
+
+impl Input {
+    /// Valeur au-delà de laquelle une action analogique compte comme pressée.
+    pub const PRESS_THRESHOLD: f32 = 0.5;
+
+    pub fn new(map: InputMap) -> Self {
+        Self {
+            map,
+            ..Default::default()
+        }
+    }
+
+    pub fn press(&mut self, button: InputButton) {
+        self.down.insert(button);
+    }
+
+    pub fn release(&mut self, button: InputButton) {
+        self.down.remove(&button);
+    }
+
+    pub fn press_key(&mut self, key: KeyCode) {
+        self.press(InputButton::Key(key));
+    }
+
+    pub fn release_key(&mut self, key: KeyCode) {
+        self.release(InputButton::Key(key));
+    }
+
+    pub fn press_mouse_button(&mut self, button: MouseButton) {
+        self.press(InputButton::Mouse(button));
+    }
+
+    pub fn release_mouse_button(&mut self, button: MouseButton) {
+        self.release(InputButton::Mouse(button));
+    }
+
+    /// Position brute d'un axe de manette (-1..1, 0..1 pour les gâchettes), avant ses
+    /// réglages.
+    pub fn set_axis(&mut self, axis: GamepadAxis, value: f32) {
+        self.axes.insert(axis, value.clamp(-1.0, 1.0));
+    }
+
+    /// Relâche tout (perte du focus, capture de la souris rendue).
+    pub fn release_all(&mut self) {
+        self.down.clear();
+        self.axes.clear();
+    }
+
+    /// Fin de la frame : l'état courant devient l'état précédent.
+    pub fn end_frame(&mut self) {
+        self.previous.clone_from(&self.down);
+        self.previous_axes.clone_from(&self.axes);
+    }
+
+    pub fn is_down(&self, button: InputButton) -> bool {
+        self.down.contains(&button)
+    }
+
+    /// `button` enfoncé pendant cette frame (sans passer par une action).
+    pub fn button_just_pressed(&self, button: InputButton) -> bool {
+        self.down.contains(&button) && !self.previous.contains(&button)
+    }
+
+    /// `button` relâché pendant cette frame.
+    pub fn button_just_released(&self, button: InputButton) -> bool {
+        !self.down.contains(&button) && self.previous.contains(&button)
+    }
+
+    pub fn key_just_pressed(&self, key: KeyCode) -> bool {
+        self.button_just_pressed(InputButton::Key(key))
+    }
+
+    pub fn key_just_released(&self, key: KeyCode) -> bool {
+        self.button_just_released(InputButton::Key(key))
+    }
+
+    /// Valeur de `action` (0..1) : 1 si une de ses touches est enfoncée, sinon la plus forte
+    /// de ses positions d'axe. 0 pour une action inconnue.
+    pub fn value(&self, action: &str) -> f32 {
+        self.action_value(action, &self.down, &self.axes)
+    }
+
+    /// Position réglée d'un axe (zone morte, courbe, sensibilité, inversion).
+    pub fn axis_value(&self, axis: GamepadAxis) -> f32 {
+        let raw = self.axes.get(&axis).copied().unwrap_or(0.0);
+        self.settings.axis(axis).apply(raw)
+    }
+
+    /// Mouvement de souris brut (pixels) avec les réglages de la souris.
+    pub fn mouse_delta(&self, raw: (f32, f32)) -> (f32, f32) {
+        self.settings.mouse_delta(raw)
+    }
+
+    fn action_value(
+        &self,
+        action: &str,
+        down: &HashSet<InputButton>,
+        axes: &HashMap<GamepadAxis, f32>,
+    ) -> f32 {
+        if self
+            .map
+            .bindings(action)
+            .iter()
+            .any(|button| down.contains(button))
+        {
+            return 1.0;
+        }
+        self.map
+            .axis_bindings(action)
+            .iter()
+            .map(|binding| {
+                let raw = axes.get(&binding.axis).copied().unwrap_or(0.0);
+                let value = self.settings.axis(binding.axis).apply(raw);
+                let value = if binding.positive { value } else { -value };
+                value.max(0.0)
+            })
+            .fold(0.0, f32::max)
+    }
+
+    fn was_pressed(&self, action: &str) -> bool {
+        self.action_value(action, &self.previous, &self.previous_axes) >= Self::PRESS_THRESHOLD
+    }
+
+    pub fn pressed(&self, action: &str) -> bool {
+        self.value(action) >= Self::PRESS_THRESHOLD
+    }
+
+    /// Pressée pendant cette frame (et pas à la précédente).
+    pub fn just_pressed(&self, action: &str) -> bool {
+        self.pressed(action) && !self.was_pressed(action)
+    }
+
+    /// Relâchée pendant cette frame.
+    pub fn just_released(&self, action: &str) -> bool {
+        !self.pressed(action) && self.was_pressed(action)
+    }
+
+    /// `positive - negative` (-1..1), ex: `axis("move_left", "move_right")`.
+    pub fn axis(&self, negative: &str, positive: &str) -> f32 {
+        self.value(positive) - self.value(negative)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn actions_combine_buttons_and_axes() {
+        let mut input = Input::new(InputMap::editor());
+        input.press_key(KeyCode::KeyD);
+        assert!(input.pressed("move_right"));
+        assert!(input.just_pressed("move_right"));
+        assert_eq!(input.axis("move_left", "move_right"), 1.0);
+
+        input.end_frame();
+        assert!(input.pressed("move_right"));
+        assert!(!input.just_pressed("move_right"));
+
+        input.release_key(KeyCode::KeyD);
+        assert!(input.just_released("move_right"));
+        input.end_frame();
+        assert!(!input.just_released("move_right"));
+
+        // Stick : zone morte puis valeur remise à l'échelle
+        input.set_axis(GamepadAxis::LeftStickY, -0.1);
+        assert_eq!(input.value("move_up"), 0.0);
+        input.set_axis(GamepadAxis::LeftStickY, -0.6);
+        assert!((input.value("move_up") - 0.5).abs() < 1e-6);
+        assert!(input.just_pressed("move_up"));
+        assert_eq!(input.value("move_down"), 0.0);
+
+        input
+            .map
+            .bind("fire", InputButton::Mouse(MouseButton::Left));
+        input.press_mouse_button(MouseButton::Left);
+        assert!(input.pressed("fire"));
+        assert!(!input.pressed("unknown"));
+    }
+
+    #[test]
+    fn raw_keys_have_edges() {
+        let mut input = Input::default();
+        input.press_key(KeyCode::Space);
+        assert!(input.key_just_pressed(KeyCode::Space));
+        // Répétition du clavier : toujours enfoncée, plus "just pressed"
+        input.end_frame();
+        input.press_key(KeyCode::Space);
+        assert!(!input.key_just_pressed(KeyCode::Space));
+
+        input.release_key(KeyCode::Space);
+        assert!(input.key_just_released(KeyCode::Space));
+        input.end_frame();
+        assert!(!input.key_just_released(KeyCode::Space));
+    }
+}
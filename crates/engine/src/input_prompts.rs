@@ -0,0 +1,476 @@
+//! Invites de commande ("Appuyez sur [A] pour sauter") adaptées au périphérique actif.
+//!
+//! Le jeu associe des actions logiques ("jump", "interact") à des touches et à des boutons de
+//! manette. `InputPrompts` retient le dernier périphérique utilisé et retourne, pour une
+//! action, le bouton à afficher et son glyphe dans l'atlas du périphérique
+//! (`engine/glyphs/<jeu>.atlas`). Changer de périphérique en cours de partie change les
+//! invites au prochain appel.
+//!
+//! Les atlas Xbox, PlayStation et Switch livrés avec le moteur sont des glyphes provisoires
+//! (une pastille par bouton, à la couleur de la famille) : un jeu les remplace en montant
+//! ses propres atlas au même chemin. Il n'y a pas d'atlas clavier : les touches sont
+//! affichées par leur nom.
+//!
+//! L'appelant signale les boutons pressés avec `on_gamepad_button`, par exemple depuis les
+//! `GamepadEvent::Button` de `WindowState::gamepad_events`, qui donnent la famille de la
+//! manette.
+
+use std::{collections::HashMap, sync::Arc};
+
+use anyhow::Result;
+use egui_wgpu::wgpu;
+use winit::{event::MouseButton, keyboard::KeyCode};
+
+use crate::{AssetLoader, AtlasMetadata, AtlasRegion, InputMap, TextureAtlas};
+
+/// Famille de manette, qui détermine les glyphes (A/B/X/Y, croix/rond...).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GamepadFamily {
+    Xbox,
+    PlayStation,
+    Switch,
+}
+
+impl GamepadFamily {
+    /// Famille d'après le nom rapporté par le pilote. Xbox par défaut (XInput).
+    pub fn from_name(name: &str) -> Self {
+        let name = name.to_lowercase();
+        let has = |words: &[&str]| words.iter().any(|word| name.contains(word));
+        if has(&[
+            "playstation",
+            "dualshock",
+            "dualsense",
+            "ps3",
+            "ps4",
+            "ps5",
+            "sony",
+        ]) {
+            GamepadFamily::PlayStation
+        } else if has(&["nintendo", "switch", "joy-con", "pro controller"]) {
+            GamepadFamily::Switch
+        } else {
+            GamepadFamily::Xbox
+        }
+    }
+}
+
+/// Périphérique d'entrée actif.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InputDevice {
+    Keyboard,
+    Gamepad(GamepadFamily),
+}
+
+impl InputDevice {
+    /// Nom du jeu de glyphes (`engine/glyphs/<nom>.atlas`).
+    pub fn glyph_set(self) -> &'static str {
+        match self {
+            InputDevice::Keyboard => "keyboard",
+            InputDevice::Gamepad(GamepadFamily::Xbox) => "xbox",
+            InputDevice::Gamepad(GamepadFamily::PlayStation) => "playstation",
+            InputDevice::Gamepad(GamepadFamily::Switch) => "switch",
+        }
+    }
+
+    pub const ALL: [InputDevice; 4] = [
+        InputDevice::Keyboard,
+        InputDevice::Gamepad(GamepadFamily::Xbox),
+        InputDevice::Gamepad(GamepadFamily::PlayStation),
+        InputDevice::Gamepad(GamepadFamily::Switch),
+    ];
+}
+
+/// Bouton de manette, par position (le bouton "sud" est A sur Xbox, croix sur PlayStation,
+/// B sur Switch).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GamepadButton {
+    South,
+    East,
+    West,
+    North,
+    LeftBumper,
+    RightBumper,
+    LeftTrigger,
+    RightTrigger,
+    Select,
+    Start,
+    LeftStick,
+    RightStick,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+impl GamepadButton {
+    pub const ALL: [GamepadButton; 16] = [
+        GamepadButton::South,
+        GamepadButton::East,
+        GamepadButton::West,
+        GamepadButton::North,
+        GamepadButton::LeftBumper,
+        GamepadButton::RightBumper,
+        GamepadButton::LeftTrigger,
+        GamepadButton::RightTrigger,
+        GamepadButton::Select,
+        GamepadButton::Start,
+        GamepadButton::LeftStick,
+        GamepadButton::RightStick,
+        GamepadButton::DPadUp,
+        GamepadButton::DPadDown,
+        GamepadButton::DPadLeft,
+        GamepadButton::DPadRight,
+    ];
+
+    /// Nom de la région du glyphe, commun à tous les atlas de manettes.
+    pub fn region_name(self) -> &'static str {
+        match self {
+            GamepadButton::South => "south",
+            GamepadButton::East => "east",
+            GamepadButton::West => "west",
+            GamepadButton::North => "north",
+            GamepadButton::LeftBumper => "left_bumper",
+            GamepadButton::RightBumper => "right_bumper",
+            GamepadButton::LeftTrigger => "left_trigger",
+            GamepadButton::RightTrigger => "right_trigger",
+            GamepadButton::Select => "select",
+            GamepadButton::Start => "start",
+            GamepadButton::LeftStick => "left_stick",
+            GamepadButton::RightStick => "right_stick",
+            GamepadButton::DPadUp => "dpad_up",
+            GamepadButton::DPadDown => "dpad_down",
+            GamepadButton::DPadLeft => "dpad_left",
+            GamepadButton::DPadRight => "dpad_right",
+        }
+    }
+
+    /// Texte du bouton pour `family`, utilisé quand le glyphe manque.
+    pub fn label(self, family: GamepadFamily) -> &'static str {
+        use GamepadButton::*;
+        use GamepadFamily::*;
+        match (self, family) {
+            (South, Xbox) | (East, Switch) => "A",
+            (East, Xbox) | (South, Switch) => "B",
+            (West, Xbox) | (North, Switch) => "X",
+            (North, Xbox) | (West, Switch) => "Y",
+            (South, PlayStation) => "Cross",
+            (East, PlayStation) => "Circle",
+            (West, PlayStation) => "Square",
+            (North, PlayStation) => "Triangle",
+            (LeftBumper, Xbox) => "LB",
+            (RightBumper, Xbox) => "RB",
+            (LeftTrigger, Xbox) => "LT",
+            (RightTrigger, Xbox) => "RT",
+            (LeftBumper, PlayStation) => "L1",
+            (RightBumper, PlayStation) => "R1",
+            (LeftTrigger, PlayStation) => "L2",
+            (RightTrigger, PlayStation) => "R2",
+            (LeftBumper, Switch) => "L",
+            (RightBumper, Switch) => "R",
+            (LeftTrigger, Switch) => "ZL",
+            (RightTrigger, Switch) => "ZR",
+            (Select, Xbox) => "View",
+            (Start, Xbox) => "Menu",
+            (Select, PlayStation) => "Share",
+            (Start, PlayStation) => "Options",
+            (Select, Switch) => "-",
+            (Start, Switch) => "+",
+            (LeftStick, PlayStation) => "L3",
+            (RightStick, PlayStation) => "R3",
+            (LeftStick, _) => "LS",
+            (RightStick, _) => "RS",
+            (DPadUp, _) => "Up",
+            (DPadDown, _) => "Down",
+            (DPadLeft, _) => "Left",
+            (DPadRight, _) => "Right",
+        }
+    }
+}
+
+/// Touche ou bouton associé à une action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InputButton {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    Gamepad(GamepadButton),
+}
+
+impl InputButton {
+    fn matches(self, device: InputDevice) -> bool {
+        matches!(
+            (self, device),
+            (
+                InputButton::Key(_) | InputButton::Mouse(_),
+                InputDevice::Keyboard
+            ) | (InputButton::Gamepad(_), InputDevice::Gamepad(_))
+        )
+    }
+
+    /// Nom de la région du glyphe : `KeyCode` sans préfixe pour le clavier ("W", "Space").
+    pub fn region_name(self) -> String {
+        match self {
+            InputButton::Key(key) => key_label(key),
+            InputButton::Mouse(button) => format!("mouse_{}", mouse_label(button).to_lowercase()),
+            InputButton::Gamepad(button) => button.region_name().to_string(),
+        }
+    }
+
+    pub fn label(self, device: InputDevice) -> String {
+        match (self, device) {
+            (InputButton::Gamepad(button), InputDevice::Gamepad(family)) => {
+                button.label(family).to_string()
+            }
+            (InputButton::Gamepad(button), InputDevice::Keyboard) => {
+                button.label(GamepadFamily::Xbox).to_string()
+            }
+            (InputButton::Key(key), _) => key_label(key),
+            (InputButton::Mouse(button), _) => format!("Mouse {}", mouse_label(button)),
+        }
+    }
+}
+
+/// "KeyW" -> "W", "Digit1" -> "1", "ArrowUp" -> "Up" ; les autres noms sont gardés.
+fn key_label(key: KeyCode) -> String {
+    let name = format!("{:?}", key);
+    ["Key", "Digit", "Arrow"]
+        .iter()
+        .find_map(|prefix| name.strip_prefix(prefix).filter(|rest| !rest.is_empty()))
+        .map(str::to_string)
+        .unwrap_or(name)
+}
+
+/// "Left", "Right", "Middle", "Back", "Forward", "4"...
+fn mouse_label(button: MouseButton) -> String {
+    match button {
+        MouseButton::Other(index) => index.to_string(),
+        button => format!("{:?}", button),
+    }
+}
+
+/// Invite à afficher pour une action.
+#[derive(Clone)]
+pub struct InputPrompt {
+    pub button: InputButton,
+    /// Texte de la touche ("Space", "A", "Cross").
+    pub label: String,
+    /// Atlas du périphérique et région du glyphe, si l'atlas est chargé et la contient.
+    pub glyph: Option<(Arc<TextureAtlas>, AtlasRegion)>,
+}
+
+/// Actions logiques, glyphes par périphérique et périphérique actif.
+#[derive(Clone)]
+pub struct InputPrompts {
+    bindings: HashMap<String, Vec<InputButton>>,
+    glyphs: HashMap<InputDevice, Arc<TextureAtlas>>,
+    active: InputDevice,
+}
+
+impl Default for InputPrompts {
+    fn default() -> Self {
+        Self {
+            bindings: HashMap::new(),
+            glyphs: HashMap::new(),
+            active: InputDevice::Keyboard,
+        }
+    }
+}
+
+impl InputPrompts {
+    /// Dossier VFS des atlas de glyphes.
+    pub const GLYPHS_DIR: &str = "engine/glyphs";
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Associe `button` à `action` (une action peut avoir une touche et un bouton de manette,
+    /// ou plusieurs de chaque : la première du périphérique actif est affichée).
+    pub fn bind(&mut self, action: impl Into<String>, button: InputButton) {
+        let buttons = self.bindings.entry(action.into()).or_default();
+        if !buttons.contains(&button) {
+            buttons.push(button);
+        }
+    }
+
+    pub fn unbind(&mut self, action: &str) {
+        self.bindings.remove(action);
+    }
+
+    pub fn bindings(&self, action: &str) -> &[InputButton] {
+        self.bindings.get(action).map_or(&[], Vec::as_slice)
+    }
+
+    /// Reprend les touches et boutons des actions de `map` (les axes n'ont pas d'invite).
+    pub fn bind_map(&mut self, map: &InputMap) {
+        for action in map.actions() {
+            for &button in map.bindings(action) {
+                self.bind(action, button);
+            }
+        }
+    }
+
+    pub fn set_glyphs(&mut self, device: InputDevice, atlas: Arc<TextureAtlas>) {
+        self.glyphs.insert(device, atlas);
+    }
+
+    /// Charge les atlas de `GLYPHS_DIR` présents dans le VFS. Retourne leur nombre.
+    pub fn load_glyphs(
+        &mut self,
+        loader: &AssetLoader,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) -> Result<usize> {
+        let mut loaded = 0;
+        for input_device in InputDevice::ALL {
+            let path = format!(
+                "{}/{}.{}",
+                Self::GLYPHS_DIR,
+                input_device.glyph_set(),
+                AtlasMetadata::EXTENSION
+            );
+            if !loader.vfs().exists(&path) {
+                continue;
+            }
+            let atlas = TextureAtlas::load(loader, &path, device, queue)?;
+            self.set_glyphs(input_device, Arc::new(atlas));
+            loaded += 1;
+        }
+        Ok(loaded)
+    }
+
+    pub fn active_device(&self) -> InputDevice {
+        self.active
+    }
+
+    /// Change le périphérique actif. Retourne true s'il a changé.
+    pub fn set_active_device(&mut self, device: InputDevice) -> bool {
+        std::mem::replace(&mut self.active, device) != device
+    }
+
+    /// À appeler sur chaque touche pressée : repasse les invites au clavier.
+    pub fn on_key_pressed(&mut self, _key: KeyCode) -> bool {
+        self.set_active_device(InputDevice::Keyboard)
+    }
+
+    /// À appeler sur chaque bouton de manette pressé.
+    pub fn on_gamepad_button(&mut self, family: GamepadFamily, _button: GamepadButton) -> bool {
+        self.set_active_device(InputDevice::Gamepad(family))
+    }
+
+    /// Invite de `action` pour le périphérique actif. `None` si l'action n'a aucun bouton
+    /// sur ce périphérique.
+    pub fn prompt(&self, action: &str) -> Option<InputPrompt> {
+        let button = self
+            .bindings(action)
+            .iter()
+            .copied()
+            .find(|button| button.matches(self.active))?;
+        let glyph = self.glyphs.get(&self.active).and_then(|atlas| {
+            let region = *atlas.region(&button.region_name())?;
+            Some((atlas.clone(), region))
+        });
+        Some(InputPrompt {
+            button,
+            label: button.label(self.active),
+            glyph,
+        })
+    }
+
+    /// Remplace les `{action}` de `template` par le texte de leur touche, entre crochets
+    /// ("Press {jump} to jump" -> "Press [Space] to jump"). Les actions inconnues sont
+    /// gardées telles quelles.
+    pub fn format(&self, template: &str) -> String {
+        let mut result = String::with_capacity(template.len());
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            result.push_str(&rest[..start]);
+            let Some(end) = rest[start..].find('}') else {
+                rest = &rest[start..];
+                break;
+            };
+            let action = &rest[start + 1..start + end];
+            match self.prompt(action) {
+                Some(prompt) => {
+                    result.push('[');
+                    result.push_str(&prompt.label);
+                    result.push(']');
+                }
+                None => result.push_str(&rest[start..=start + end]),
+            }
+            rest = &rest[start + end + 1..];
+        }
+        result.push_str(rest);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prompts_follow_the_active_device() {
+        assert_eq!(
+            GamepadFamily::from_name("Sony DualSense Wireless Controller"),
+            GamepadFamily::PlayStation
+        );
+        assert_eq!(
+            GamepadFamily::from_name("Nintendo Switch Pro Controller"),
+            GamepadFamily::Switch
+        );
+        assert_eq!(
+            GamepadFamily::from_name("Generic X-Box pad"),
+            GamepadFamily::Xbox
+        );
+
+        let mut prompts = InputPrompts::new();
+        prompts.bind("jump", InputButton::Key(KeyCode::Space));
+        prompts.bind("jump", InputButton::Gamepad(GamepadButton::South));
+        prompts.bind("walk", InputButton::Key(KeyCode::KeyW));
+
+        assert_eq!(
+            prompts.format("Press {jump} to jump"),
+            "Press [Space] to jump"
+        );
+        assert_eq!(prompts.prompt("walk").unwrap().label, "W");
+        assert!(prompts.prompt("walk").unwrap().glyph.is_none());
+
+        assert!(prompts.on_gamepad_button(GamepadFamily::PlayStation, GamepadButton::East));
+        assert!(!prompts.on_gamepad_button(GamepadFamily::PlayStation, GamepadButton::South));
+        assert_eq!(
+            prompts.format("Press {jump} to jump"),
+            "Press [Cross] to jump"
+        );
+        assert!(prompts.prompt("walk").is_none());
+        assert_eq!(prompts.format("{walk} {unknown"), "{walk} {unknown");
+
+        prompts.on_gamepad_button(GamepadFamily::Switch, GamepadButton::South);
+        assert_eq!(prompts.prompt("jump").unwrap().label, "B");
+        prompts.on_key_pressed(KeyCode::KeyA);
+        assert_eq!(prompts.active_device(), InputDevice::Keyboard);
+    }
+
+    #[test]
+    fn shipped_gamepad_atlases_have_every_button() {
+        let atlases = [
+            include_str!("../../../engine/glyphs/xbox.atlas"),
+            include_str!("../../../engine/glyphs/playstation.atlas"),
+            include_str!("../../../engine/glyphs/switch.atlas"),
+        ];
+        for text in atlases {
+            let metadata = AtlasMetadata::parse(text).unwrap();
+            assert!(metadata.image.starts_with(InputPrompts::GLYPHS_DIR));
+            for button in GamepadButton::ALL {
+                assert!(
+                    metadata
+                        .frames
+                        .iter()
+                        .any(|frame| frame.name == button.region_name()),
+                    "missing {:?} in {}",
+                    button,
+                    metadata.image
+                );
+            }
+        }
+    }
+}
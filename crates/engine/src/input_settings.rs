@@ -0,0 +1,310 @@
+//! Réglages des entrées analogiques, propres à l'utilisateur : zone morte, sensibilité et
+//! courbe de réponse de chaque axe de manette et des mouvements de souris. Les valeurs
+//! brutes varient beaucoup d'un périphérique à l'autre (sticks qui dérivent, souris à
+//! haute résolution).
+//!
+//! `Input` les applique à la lecture : `Input::axis_value` (et les actions liées à un axe)
+//! sur les positions brutes des sticks et gâchettes, que `Gamepads` relève chaque frame ;
+//! `Input::mouse_delta` sur les mouvements de souris.
+//!
+//! Stockés dans le mount utilisateur (`InputSettings::PATH`) au format `clé = valeur`,
+//! comme les préférences de l'éditeur : `left_stick_x.dead_zone = 0.15`.
+
+use std::collections::HashMap;
+
+use anyhow::{Context, Result, anyhow};
+
+use crate::{GamepadAxis, Vfs};
+
+/// Réponse d'un axe à l'amplitude (0..1) au-delà de la zone morte.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResponseCurve {
+    Linear,
+    /// Plus de précision près du centre.
+    Quadratic,
+    Cubic,
+}
+
+impl ResponseCurve {
+    pub const ALL: [ResponseCurve; 3] = [
+        ResponseCurve::Linear,
+        ResponseCurve::Quadratic,
+        ResponseCurve::Cubic,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ResponseCurve::Linear => "linear",
+            ResponseCurve::Quadratic => "quadratic",
+            ResponseCurve::Cubic => "cubic",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|curve| curve.name() == name)
+    }
+
+    pub fn apply(self, amount: f32) -> f32 {
+        match self {
+            ResponseCurve::Linear => amount,
+            ResponseCurve::Quadratic => amount * amount,
+            ResponseCurve::Cubic => amount * amount * amount,
+        }
+    }
+}
+
+/// Réglages d'un axe.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AxisSettings {
+    /// Amplitude ignorée autour du centre (0..1 pour les sticks, pixels pour la souris).
+    pub dead_zone: f32,
+    pub sensitivity: f32,
+    pub curve: ResponseCurve,
+    pub invert: bool,
+}
+
+impl AxisSettings {
+    /// Réglages par défaut d'un stick ou d'une gâchette.
+    pub const STICK: AxisSettings = AxisSettings {
+        dead_zone: 0.2,
+        sensitivity: 1.0,
+        curve: ResponseCurve::Linear,
+        invert: false,
+    };
+    /// Réglages par défaut de la souris (aucune transformation).
+    pub const MOUSE: AxisSettings = AxisSettings {
+        dead_zone: 0.0,
+        sensitivity: 1.0,
+        curve: ResponseCurve::Linear,
+        invert: false,
+    };
+    /// Déplacement (pixels) qui vaut 1 pour la courbe de réponse de la souris : en dessous,
+    /// une courbe quadratique ralentit les petits mouvements, au-dessus elle les accélère.
+    pub const MOUSE_CURVE_SCALE: f32 = 10.0;
+
+    /// Valeur d'un axe de manette (-1..1) : 0 dans la zone morte, puis remise à l'échelle
+    /// jusqu'à la butée, courbe et sensibilité.
+    pub fn apply(&self, value: f32) -> f32 {
+        let dead_zone = self.dead_zone.clamp(0.0, 0.99);
+        let amount = ((value.abs() - dead_zone) / (1.0 - dead_zone)).clamp(0.0, 1.0);
+        let value = self.curve.apply(amount) * self.sensitivity * self.sign(value);
+        value.clamp(-1.0, 1.0)
+    }
+
+    /// Déplacement de souris (pixels), sans borne.
+    pub fn apply_delta(&self, delta: f32) -> f32 {
+        let amount = (delta.abs() - self.dead_zone.max(0.0)).max(0.0) / Self::MOUSE_CURVE_SCALE;
+        self.curve.apply(amount) * Self::MOUSE_CURVE_SCALE * self.sensitivity * self.sign(delta)
+    }
+
+    fn sign(&self, value: f32) -> f32 {
+        match (value < 0.0) != self.invert {
+            true => -1.0,
+            false => 1.0,
+        }
+    }
+}
+
+/// Réglages des axes de manette et de la souris.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InputSettings {
+    axes: HashMap<GamepadAxis, AxisSettings>,
+    pub mouse_x: AxisSettings,
+    pub mouse_y: AxisSettings,
+}
+
+impl Default for InputSettings {
+    fn default() -> Self {
+        Self {
+            axes: GamepadAxis::ALL
+                .into_iter()
+                .map(|axis| (axis, AxisSettings::STICK))
+                .collect(),
+            mouse_x: AxisSettings::MOUSE,
+            mouse_y: AxisSettings::MOUSE,
+        }
+    }
+}
+
+impl InputSettings {
+    /// Fichier des réglages, dans le mount utilisateur.
+    pub const PATH: &str = "user/input.cfg";
+
+    pub fn axis(&self, axis: GamepadAxis) -> &AxisSettings {
+        self.axes.get(&axis).unwrap_or(&AxisSettings::STICK)
+    }
+
+    pub fn axis_mut(&mut self, axis: GamepadAxis) -> &mut AxisSettings {
+        self.axes.entry(axis).or_insert(AxisSettings::STICK)
+    }
+
+    /// Mouvement de souris brut (pixels) -> mouvement réglé.
+    pub fn mouse_delta(&self, (dx, dy): (f32, f32)) -> (f32, f32) {
+        (self.mouse_x.apply_delta(dx), self.mouse_y.apply_delta(dy))
+    }
+
+    /// Réglages de `name` ("left_stick_x", "mouse_y"...).
+    fn named_mut(&mut self, name: &str) -> Option<&mut AxisSettings> {
+        match name {
+            "mouse_x" => Some(&mut self.mouse_x),
+            "mouse_y" => Some(&mut self.mouse_y),
+            _ => GamepadAxis::from_name(name).map(|axis| self.axis_mut(axis)),
+        }
+    }
+
+    fn named(&self) -> Vec<(&'static str, &AxisSettings)> {
+        let mut named: Vec<(&'static str, &AxisSettings)> = GamepadAxis::ALL
+            .into_iter()
+            .map(|axis| (axis.name(), self.axis(axis)))
+            .collect();
+        named.push(("mouse_x", &self.mouse_x));
+        named.push(("mouse_y", &self.mouse_y));
+        named
+    }
+
+    /// Parse les réglages. Les clés absentes gardent leur valeur par défaut.
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut settings = Self::default();
+
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| anyhow!("line {}: expected `key = value`", number + 1))?;
+            let (key, value) = (key.trim(), value.trim());
+            let invalid = || format!("line {}: invalid {} {:?}", number + 1, key, value);
+
+            let Some((axis, field)) = key
+                .split_once('.')
+                .and_then(|(name, field)| Some((settings.named_mut(name)?, field)))
+            else {
+                log::warn!("Unknown input setting {:?} (line {})", key, number + 1);
+                continue;
+            };
+            match field {
+                "dead_zone" => axis.dead_zone = value.parse().with_context(invalid)?,
+                "sensitivity" => axis.sensitivity = value.parse().with_context(invalid)?,
+                "curve" => {
+                    axis.curve =
+                        ResponseCurve::from_name(value).ok_or_else(|| anyhow!(invalid()))?
+                }
+                "invert" => axis.invert = value.parse().with_context(invalid)?,
+                other => log::warn!("Unknown input setting {:?} (line {})", other, number + 1),
+            }
+        }
+
+        Ok(settings)
+    }
+
+    pub fn encode(&self) -> String {
+        let mut text = String::new();
+        for (name, axis) in self.named() {
+            text.push_str(&format!(
+                "{name}.dead_zone = {}\n{name}.sensitivity = {}\n{name}.curve = {}\n\
+                 {name}.invert = {}\n",
+                axis.dead_zone,
+                axis.sensitivity,
+                axis.curve.name(),
+                axis.invert,
+            ));
+        }
+        text
+    }
+
+    /// Charge les réglages, ou les valeurs par défaut si le fichier n'existe pas encore.
+    pub fn load(vfs: &Vfs) -> Result<Self> {
+        if !vfs.exists(Self::PATH) {
+            return Ok(Self::default());
+        }
+        let text = vfs.read_to_string(Self::PATH)?;
+        Self::parse(&text).with_context(|| format!("failed to parse {:?}", Self::PATH))
+    }
+
+    pub fn save(&self, vfs: &Vfs) -> Result<()> {
+        vfs.write_bytes(Self::PATH, self.encode().as_bytes())
+    }
+
+    /// Formulaire des réglages. Retourne `true` si une valeur a changé.
+    pub fn ui(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut changed = false;
+
+        egui::Grid::new("input_settings")
+            .num_columns(5)
+            .striped(true)
+            .show(ui, |ui| {
+                for header in ["Axis", "Dead zone", "Sensitivity", "Curve", "Invert"] {
+                    ui.strong(header);
+                }
+                ui.end_row();
+
+                let names: Vec<&'static str> = self.named().into_iter().map(|(n, _)| n).collect();
+                for name in names {
+                    let Some(axis) = self.named_mut(name) else {
+                        continue;
+                    };
+                    let max_dead_zone = if name.starts_with("mouse") { 20.0 } else { 0.9 };
+                    ui.label(name);
+                    changed |= ui
+                        .add(
+                            egui::DragValue::new(&mut axis.dead_zone)
+                                .range(0.0..=max_dead_zone)
+                                .speed(0.01),
+                        )
+                        .changed();
+                    changed |= ui
+                        .add(egui::Slider::new(&mut axis.sensitivity, 0.1..=5.0))
+                        .changed();
+                    egui::ComboBox::from_id_salt(("input_curve", name))
+                        .selected_text(axis.curve.name())
+                        .show_ui(ui, |ui| {
+                            for curve in ResponseCurve::ALL {
+                                changed |= ui
+                                    .selectable_value(&mut axis.curve, curve, curve.name())
+                                    .changed();
+                            }
+                        });
+                    changed |= ui.checkbox(&mut axis.invert, "").changed();
+                    ui.end_row();
+                }
+            });
+
+        if ui.button("Reset input settings").clicked() {
+            *self = Self::default();
+            changed = true;
+        }
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn curves_and_roundtrip() {
+        let stick = AxisSettings::STICK;
+        assert_eq!(stick.apply(0.1), 0.0);
+        assert!((stick.apply(-0.6) + 0.5).abs() < 1e-6);
+        let quadratic = AxisSettings {
+            curve: ResponseCurve::Quadratic,
+            invert: true,
+            ..stick
+        };
+        assert!((quadratic.apply(0.6) + 0.25).abs() < 1e-6);
+        assert_eq!(AxisSettings::MOUSE.apply_delta(-7.0), -7.0);
+
+        let mut settings = InputSettings::default();
+        settings.axis_mut(GamepadAxis::LeftStickX).dead_zone = 0.1;
+        settings.mouse_y = AxisSettings {
+            sensitivity: 2.0,
+            curve: ResponseCurve::Cubic,
+            ..AxisSettings::MOUSE
+        };
+        assert_eq!(InputSettings::parse(&settings.encode()).unwrap(), settings);
+        assert!(InputSettings::parse("mouse_x.curve = wobbly").is_err());
+    }
+}
@@ -1,29 +1,113 @@
+mod achievements;
+mod analytics;
+mod asset_graph;
+mod asset_watcher;
 mod assets;
+mod async_fs;
+mod atlas;
+mod bench_scenes;
+mod blackboard;
+mod color_glyph;
+mod color_picker;
+mod command_palette;
 mod core;
+mod curve;
 mod delta_timer;
 mod engine;
+mod external_editor;
+mod frame_budget;
+mod frame_stats;
+mod frame_watchdog;
 mod fs;
+mod gamepads;
+mod glyph_atlas;
 mod gpu;
+mod haptics;
+mod hud;
+mod import;
+mod info;
+mod input_map;
+mod input_prompts;
+mod input_settings;
+mod local_players;
+mod memory;
+mod mods;
+mod preferences;
+mod procgen;
+mod profiler;
 mod renderer;
+mod replay;
 mod resources;
+mod rewind;
+mod rich_text;
 mod shader;
+mod shutdown;
 mod sprite;
+mod sprite_mesh;
+mod sprite_slicer;
+mod text_layout;
+mod text_shaping;
 mod texture;
+mod texture_array;
+mod tilemap;
 mod uniforms;
 mod vertex;
 mod window;
+mod zip_fs;
 
+pub use achievements::*;
+pub use analytics::*;
+pub use asset_graph::*;
+pub use asset_watcher::*;
 pub use assets::*;
+pub use async_fs::*;
+pub use atlas::*;
+pub use bench_scenes::*;
+pub use blackboard::*;
+pub use color_glyph::*;
+pub use color_picker::*;
+pub use command_palette::*;
 pub use core::*;
+pub use curve::*;
 pub use delta_timer::*;
 pub use engine::*;
+pub use external_editor::*;
+pub use frame_budget::*;
+pub use frame_stats::*;
+pub use frame_watchdog::*;
 pub use fs::*;
+pub use gamepads::*;
+pub use glyph_atlas::*;
 pub use gpu::*;
+pub use haptics::*;
+pub use hud::*;
+pub use import::*;
+pub use info::*;
+pub use input_map::*;
+pub use input_prompts::*;
+pub use input_settings::*;
+pub use local_players::*;
+pub use memory::*;
+pub use mods::*;
+pub use preferences::*;
+pub use procgen::*;
+pub use profiler::*;
 pub use renderer::*;
+pub use replay::*;
 pub use resources::*;
+pub use rewind::*;
+pub use rich_text::*;
 pub use shader::*;
+pub use shutdown::*;
 pub use sprite::*;
+pub use sprite_mesh::*;
+pub use sprite_slicer::*;
+pub use text_layout::*;
+pub use text_shaping::*;
 pub use texture::*;
+pub use texture_array::*;
+pub use tilemap::*;
 pub use uniforms::*;
 pub use vertex::*;
 pub use window::*;
+pub use zip_fs::*;
@@ -0,0 +1,350 @@
+//! Multijoueur local en écran partagé. `LocalPlayers` tient les joueurs : chacun a sa caméra,
+//! son `Input` (alimenté par le périphérique qui lui est assigné : clavier / souris ou une
+//! manette précise), son calque de HUD et sa zone de la fenêtre (`Viewport`), calculée par
+//! le `SplitLayout`.
+//!
+//! Le rendu passe par `PassManager::execute_players` : les passes qui dessinent la scène
+//! (`RenderPass::per_viewport`) s'exécutent une fois par joueur avec sa caméra, limitées à sa
+//! zone ; les entités `PlayerHud` ne sont dessinées que dans le HUD de leur joueur.
+
+use egui_wgpu::wgpu;
+
+use crate::{
+    Camera2D, GamepadAxis, GamepadEvent, GamepadId, HudLayer, Input, InputButton, InputMap,
+};
+
+/// Périphérique d'entrée assignable à un joueur.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PlayerDevice {
+    /// Clavier et souris (un seul joueur à la fois).
+    KeyboardMouse,
+    Gamepad(GamepadId),
+}
+
+/// Zone de la fenêtre, en pixels physiques.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Viewport {
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        x >= self.x && y >= self.y && x < self.x + self.width && y < self.y + self.height
+    }
+
+    /// Limite le dessin de `rpass` à cette zone.
+    pub fn apply(&self, rpass: &mut wgpu::RenderPass) {
+        rpass.set_viewport(
+            self.x,
+            self.y,
+            self.width.max(1.0),
+            self.height.max(1.0),
+            0.0,
+            1.0,
+        );
+    }
+}
+
+/// Vue d'un joueur pendant le rendu (voir `PassContext::viewport`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlayerView {
+    /// Indice du joueur dans `LocalPlayers`.
+    pub player: usize,
+    pub viewport: Viewport,
+    pub hud: HudLayer,
+}
+
+/// Découpage de la fenêtre entre les joueurs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SplitLayout {
+    /// Côte à côte à deux joueurs, grille 2 x 2 à trois ou quatre.
+    #[default]
+    Auto,
+    /// Une colonne par joueur.
+    Columns,
+    /// Une ligne par joueur.
+    Rows,
+}
+
+impl SplitLayout {
+    /// Zones des `count` joueurs dans une fenêtre `width` x `height`.
+    pub fn viewports(self, count: usize, width: f32, height: f32) -> Vec<Viewport> {
+        let (columns, rows) = match (self, count) {
+            (_, 0) => return Vec::new(),
+            (SplitLayout::Columns, n) => (n, 1),
+            (SplitLayout::Rows, n) => (1, n),
+            (SplitLayout::Auto, n) if n <= 2 => (n, 1),
+            (SplitLayout::Auto, n) => {
+                let columns = (n as f32).sqrt().ceil() as usize;
+                (columns, n.div_ceil(columns))
+            }
+        };
+        let (cell_width, cell_height) = (width / columns as f32, height / rows as f32);
+        (0..count)
+            .map(|i| {
+                let (column, row) = (i % columns, i / columns);
+                Viewport::new(
+                    column as f32 * cell_width,
+                    row as f32 * cell_height,
+                    cell_width,
+                    cell_height,
+                )
+            })
+            .collect()
+    }
+}
+
+/// Joueur local.
+pub struct LocalPlayer {
+    pub camera: Camera2D,
+    /// Entrées du joueur, alimentées par `device`.
+    pub input: Input,
+    /// Calque du HUD du joueur, dans sa zone.
+    pub hud: HudLayer,
+    device: Option<PlayerDevice>,
+    viewport: Viewport,
+}
+
+impl LocalPlayer {
+    pub fn device(&self) -> Option<PlayerDevice> {
+        self.device
+    }
+
+    pub fn viewport(&self) -> Viewport {
+        self.viewport
+    }
+}
+
+/// Joueurs locaux, leurs périphériques et leurs zones de la fenêtre.
+pub struct LocalPlayers {
+    players: Vec<LocalPlayer>,
+    layout: SplitLayout,
+    window_size: (f32, f32),
+}
+
+impl LocalPlayers {
+    pub fn new(width: f32, height: f32) -> Self {
+        Self {
+            players: Vec::new(),
+            layout: SplitLayout::Auto,
+            window_size: (width, height),
+        }
+    }
+
+    /// Ajoute un joueur avec ses actions `map`. Retourne son indice.
+    pub fn add_player(&mut self, map: InputMap) -> usize {
+        self.players.push(LocalPlayer {
+            camera: Camera2D::new(1.0, 1.0),
+            input: Input::new(map),
+            hud: HudLayer::default(),
+            device: None,
+            viewport: Viewport::new(0.0, 0.0, 1.0, 1.0),
+        });
+        self.update_viewports();
+        self.players.len() - 1
+    }
+
+    /// Retire le joueur `player` ; les suivants sont décalés d'un indice.
+    pub fn remove_player(&mut self, player: usize) -> Option<LocalPlayer> {
+        if player >= self.players.len() {
+            return None;
+        }
+        let removed = self.players.remove(player);
+        self.update_viewports();
+        Some(removed)
+    }
+
+    pub fn len(&self) -> usize {
+        self.players.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.players.is_empty()
+    }
+
+    pub fn players(&self) -> &[LocalPlayer] {
+        &self.players
+    }
+
+    pub fn player(&self, player: usize) -> Option<&LocalPlayer> {
+        self.players.get(player)
+    }
+
+    pub fn player_mut(&mut self, player: usize) -> Option<&mut LocalPlayer> {
+        self.players.get_mut(player)
+    }
+
+    pub fn layout(&self) -> SplitLayout {
+        self.layout
+    }
+
+    pub fn set_layout(&mut self, layout: SplitLayout) {
+        self.layout = layout;
+        self.update_viewports();
+    }
+
+    /// Taille de la fenêtre (appeler lors du resize) : zones et caméras suivent.
+    pub fn resize(&mut self, width: f32, height: f32) {
+        self.window_size = (width, height);
+        self.update_viewports();
+    }
+
+    fn update_viewports(&mut self) {
+        let (width, height) = self.window_size;
+        let viewports = self.layout.viewports(self.players.len(), width, height);
+        for (player, viewport) in self.players.iter_mut().zip(viewports) {
+            player.viewport = viewport;
+            player
+                .camera
+                .set_viewport_size(viewport.width, viewport.height);
+        }
+    }
+
+    /// Assigne `device` au joueur `player` (ou à personne avec `None`). Le périphérique est
+    /// retiré au joueur qui l'avait, dont les entrées sont relâchées.
+    pub fn assign_device(&mut self, player: usize, device: Option<PlayerDevice>) {
+        for (index, other) in self.players.iter_mut().enumerate() {
+            let new_device = match index == player {
+                true => device,
+                false if device.is_some() && other.device == device => None,
+                false => continue,
+            };
+            if other.device != new_device {
+                other.input.release_all();
+                other.device = new_device;
+            }
+        }
+    }
+
+    /// Joueur qui utilise `device`.
+    pub fn player_for_device(&self, device: PlayerDevice) -> Option<usize> {
+        self.players.iter().position(|p| p.device == Some(device))
+    }
+
+    /// Transmet un bouton de `device` à son joueur. Retourne `false` si aucun joueur n'a ce
+    /// périphérique (par exemple pour proposer à une nouvelle manette de rejoindre la partie).
+    pub fn handle_button(
+        &mut self,
+        device: PlayerDevice,
+        button: InputButton,
+        pressed: bool,
+    ) -> bool {
+        let Some(player) = self.player_for_device(device) else {
+            return false;
+        };
+        let input = &mut self.players[player].input;
+        match pressed {
+            true => input.press(button),
+            false => input.release(button),
+        }
+        true
+    }
+
+    /// Transmet la position d'un axe de `device` à son joueur.
+    pub fn handle_axis(&mut self, device: PlayerDevice, axis: GamepadAxis, value: f32) -> bool {
+        let Some(player) = self.player_for_device(device) else {
+            return false;
+        };
+        self.players[player].input.set_axis(axis, value);
+        true
+    }
+
+    /// Transmet un événement de manette (`WindowState::gamepad_events`) au joueur qui a cette
+    /// manette. Retourne `false` si aucun joueur ne l'a.
+    pub fn handle_gamepad_event(&mut self, event: &GamepadEvent) -> bool {
+        let Some(player) = self.player_for_device(PlayerDevice::Gamepad(event.gamepad())) else {
+            return false;
+        };
+        event.apply(&mut self.players[player].input);
+        true
+    }
+
+    /// Joueur dont la zone contient la position (`x`, `y`) de la fenêtre.
+    pub fn player_at(&self, x: f32, y: f32) -> Option<usize> {
+        self.players.iter().position(|p| p.viewport.contains(x, y))
+    }
+
+    /// Fin de la frame pour les entrées de tous les joueurs (voir `Input::end_frame`).
+    pub fn end_frame(&mut self) {
+        for player in &mut self.players {
+            player.input.end_frame();
+        }
+    }
+
+    /// Vues des joueurs pour le rendu.
+    pub fn views(&self) -> impl Iterator<Item = (&Camera2D, PlayerView)> {
+        self.players.iter().enumerate().map(|(index, player)| {
+            (
+                &player.camera,
+                PlayerView {
+                    player: index,
+                    viewport: player.viewport,
+                    hud: player.hud,
+                },
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use winit::keyboard::KeyCode;
+
+    use super::*;
+
+    #[test]
+    fn devices_route_to_their_player() {
+        let mut players = LocalPlayers::new(1280.0, 720.0);
+        let first = players.add_player(InputMap::editor());
+        let second = players.add_player(InputMap::editor());
+        assert_eq!(
+            players.player(second).unwrap().viewport(),
+            Viewport::new(640.0, 0.0, 640.0, 720.0)
+        );
+        assert_eq!(players.player(first).unwrap().camera.viewport_width, 640.0);
+
+        let pad = PlayerDevice::Gamepad(GamepadId(3));
+        players.assign_device(first, Some(PlayerDevice::KeyboardMouse));
+        players.assign_device(second, Some(pad));
+        assert!(players.handle_button(
+            PlayerDevice::KeyboardMouse,
+            InputButton::Key(KeyCode::KeyD),
+            true
+        ));
+        assert!(players.handle_axis(pad, GamepadAxis::LeftStickX, -1.0));
+        assert!(!players.handle_axis(
+            PlayerDevice::Gamepad(GamepadId(4)),
+            GamepadAxis::LeftStickX,
+            1.0
+        ));
+        assert!(players.player(first).unwrap().input.pressed("move_right"));
+        assert!(players.player(second).unwrap().input.pressed("move_left"));
+        assert!(!players.player(second).unwrap().input.pressed("move_right"));
+
+        // La manette passe au premier joueur : le second la perd et ses entrées sont relâchées
+        players.assign_device(first, Some(pad));
+        assert_eq!(players.player_for_device(pad), Some(first));
+        assert_eq!(players.player(second).unwrap().device(), None);
+        assert!(!players.player(second).unwrap().input.pressed("move_left"));
+
+        players.add_player(InputMap::editor());
+        assert_eq!(
+            SplitLayout::Auto.viewports(3, 100.0, 100.0)[2],
+            Viewport::new(0.0, 50.0, 50.0, 50.0)
+        );
+        assert_eq!(players.player_at(75.0 * 12.8, 500.0), None);
+        assert_eq!(players.player_at(10.0, 500.0), Some(2));
+    }
+}
@@ -0,0 +1,416 @@
+//! Suivi de la mémoire des principales allocations du moteur, par catégorie.
+//!
+//! Les ressources GPU (textures, cibles de rendu, buffers d'instances) portent un
+//! `TrackedMemory` : leur taille est comptée à la création et retirée au drop. Une
+//! catégorie qui ne fait que monter signale une fuite, par exemple des textures gardées par
+//! un cache de bind groups. Les entités de la scène sont estimées à la demande
+//! (`Scene::memory_estimate`, puis `set_memory_gauge`).
+//!
+//! `MemoryPanel` affiche la répartition et le maximum atteint par catégorie.
+//!
+//! Détection de fuites : chaque `TrackedMemory` vivant est enregistré avec un libellé et
+//! son site de création (`#[track_caller]`). `begin_resource_generation` marque un point
+//! (chargement d'une scène, bouton "Mark" du panneau) ; `report_live_resources` logge ce qui
+//! a été créé depuis et vit encore. Les caches des passes sont purgés à la frame suivante :
+//! faire le rapport d'un déchargement de scène après au moins une frame. L'application fait
+//! un rapport complet à l'arrêt.
+
+use std::{
+    collections::BTreeMap,
+    panic::Location,
+    sync::{
+        Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+/// Catégorie d'allocation suivie.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MemoryCategory {
+    /// `Texture2D` et `TextureArray`.
+    Textures,
+    /// `RenderTarget` (couleur et depth).
+    RenderTargets,
+    /// Buffers d'instances des sprites et des chunks de tilemap, meshes des sprites.
+    InstanceBuffers,
+    /// Composants des entités de la scène (estimation).
+    Entities,
+    /// Bind groups des caches des passes (comptés, sans taille).
+    BindGroups,
+}
+
+impl MemoryCategory {
+    pub const ALL: [MemoryCategory; 5] = [
+        MemoryCategory::Textures,
+        MemoryCategory::RenderTargets,
+        MemoryCategory::InstanceBuffers,
+        MemoryCategory::Entities,
+        MemoryCategory::BindGroups,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            MemoryCategory::Textures => "Textures",
+            MemoryCategory::RenderTargets => "Render targets",
+            MemoryCategory::InstanceBuffers => "Instance buffers",
+            MemoryCategory::Entities => "Entities",
+            MemoryCategory::BindGroups => "Bind groups",
+        }
+    }
+
+    fn counters(self) -> &'static Counters {
+        &COUNTERS[self as usize]
+    }
+}
+
+struct Counters {
+    bytes: AtomicU64,
+    peak: AtomicU64,
+    allocations: AtomicU64,
+}
+
+impl Counters {
+    const fn new() -> Self {
+        Self {
+            bytes: AtomicU64::new(0),
+            peak: AtomicU64::new(0),
+            allocations: AtomicU64::new(0),
+        }
+    }
+}
+
+static COUNTERS: [Counters; MemoryCategory::ALL.len()] =
+    [const { Counters::new() }; MemoryCategory::ALL.len()];
+
+/// État d'une catégorie.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryStats {
+    pub category: MemoryCategory,
+    /// Octets actuellement alloués.
+    pub bytes: u64,
+    /// Maximum atteint depuis le lancement (ou `reset_memory_peaks`).
+    pub peak: u64,
+    /// Nombre d'allocations vivantes.
+    pub allocations: u64,
+}
+
+/// État de toutes les catégories, dans l'ordre de `MemoryCategory::ALL`.
+pub fn memory_stats() -> Vec<MemoryStats> {
+    MemoryCategory::ALL
+        .iter()
+        .map(|&category| {
+            let counters = category.counters();
+            MemoryStats {
+                category,
+                bytes: counters.bytes.load(Ordering::Relaxed),
+                peak: counters.peak.load(Ordering::Relaxed),
+                allocations: counters.allocations.load(Ordering::Relaxed),
+            }
+        })
+        .collect()
+}
+
+/// Ramène le maximum de chaque catégorie à sa valeur actuelle.
+pub fn reset_memory_peaks() {
+    for category in MemoryCategory::ALL {
+        let counters = category.counters();
+        counters
+            .peak
+            .store(counters.bytes.load(Ordering::Relaxed), Ordering::Relaxed);
+    }
+}
+
+/// Remplace la valeur d'une catégorie estimée périodiquement plutôt que suivie allocation
+/// par allocation (ex: `MemoryCategory::Entities`).
+pub fn set_memory_gauge(category: MemoryCategory, bytes: u64, allocations: u64) {
+    let counters = category.counters();
+    counters.bytes.store(bytes, Ordering::Relaxed);
+    counters.allocations.store(allocations, Ordering::Relaxed);
+    counters.peak.fetch_max(bytes, Ordering::Relaxed);
+}
+
+/// Ressource suivie encore vivante (voir `live_resources`).
+#[derive(Debug, Clone)]
+pub struct LiveResource {
+    pub category: MemoryCategory,
+    pub bytes: u64,
+    /// Libellé (chemin de la texture, nom de la cible...), vide si inconnu.
+    pub label: String,
+    /// Site de création : premier appelant hors des fonctions `#[track_caller]`.
+    pub location: &'static Location<'static>,
+    /// Génération courante à la création (voir `begin_resource_generation`).
+    pub generation: u64,
+}
+
+static LIVE: Mutex<BTreeMap<u64, LiveResource>> = Mutex::new(BTreeMap::new());
+static NEXT_RESOURCE_ID: AtomicU64 = AtomicU64::new(1);
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Commence une nouvelle génération et la retourne : les ressources créées à partir de
+/// maintenant en font partie. À appeler au chargement d'une scène.
+pub fn begin_resource_generation() -> u64 {
+    GENERATION.fetch_add(1, Ordering::Relaxed) + 1
+}
+
+/// Ressources vivantes créées depuis la génération `since` (0 = toutes), les plus
+/// anciennes en premier.
+pub fn live_resources(since: u64) -> Vec<LiveResource> {
+    LIVE.lock()
+        .unwrap()
+        .values()
+        .filter(|resource| resource.generation >= since)
+        .cloned()
+        .collect()
+}
+
+/// Logge (en warning) les ressources créées depuis la génération `since` encore vivantes,
+/// regroupées par site de création. `context` décrit le moment ("scene unload",
+/// "shutdown"...). Retourne le nombre de ressources.
+pub fn report_live_resources(context: &str, since: u64) -> usize {
+    let resources = live_resources(since);
+    if resources.is_empty() {
+        log::info!("No GPU resource leaked at {}", context);
+        return 0;
+    }
+    let mut sites: BTreeMap<String, Vec<&LiveResource>> = BTreeMap::new();
+    for resource in &resources {
+        sites
+            .entry(resource.location.to_string())
+            .or_default()
+            .push(resource);
+    }
+    log::warn!(
+        "{} GPU resource(s) still alive at {}:",
+        resources.len(),
+        context
+    );
+    for (site, resources) in sites {
+        let bytes: u64 = resources.iter().map(|resource| resource.bytes).sum();
+        let labels: Vec<&str> = resources
+            .iter()
+            .map(|resource| resource.label.as_str())
+            .filter(|label| !label.is_empty())
+            .collect();
+        log::warn!(
+            "  {} x {} ({}) created at {} {:?}",
+            resources.len(),
+            resources[0].category.label(),
+            format_bytes(bytes),
+            site,
+            labels
+        );
+    }
+    resources.len()
+}
+
+/// Taille d'une allocation, comptée dans sa catégorie tant que la valeur existe.
+#[derive(Debug, Default)]
+pub struct TrackedMemory {
+    category: Option<MemoryCategory>,
+    bytes: u64,
+    /// Clé dans `LIVE` (0 pour `TrackedMemory::default()`).
+    id: u64,
+}
+
+impl TrackedMemory {
+    #[track_caller]
+    pub fn new(category: MemoryCategory, bytes: u64) -> Self {
+        let counters = category.counters();
+        let total = counters.bytes.fetch_add(bytes, Ordering::Relaxed) + bytes;
+        counters.allocations.fetch_add(1, Ordering::Relaxed);
+        counters.peak.fetch_max(total, Ordering::Relaxed);
+
+        let id = NEXT_RESOURCE_ID.fetch_add(1, Ordering::Relaxed);
+        let resource = LiveResource {
+            category,
+            bytes,
+            label: String::new(),
+            location: Location::caller(),
+            generation: GENERATION.load(Ordering::Relaxed),
+        };
+        LIVE.lock().unwrap().insert(id, resource);
+        Self {
+            category: Some(category),
+            bytes,
+            id,
+        }
+    }
+
+    /// Comme `new`, avec un libellé affiché dans les rapports de fuite.
+    #[track_caller]
+    pub fn labeled(category: MemoryCategory, bytes: u64, label: impl Into<String>) -> Self {
+        let memory = Self::new(category, bytes);
+        memory.set_label(label);
+        memory
+    }
+
+    pub fn set_label(&self, label: impl Into<String>) {
+        if let Some(resource) = LIVE.lock().unwrap().get_mut(&self.id) {
+            resource.label = label.into();
+        }
+    }
+
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+}
+
+impl Drop for TrackedMemory {
+    fn drop(&mut self) {
+        if let Some(category) = self.category {
+            let counters = category.counters();
+            counters.bytes.fetch_sub(self.bytes, Ordering::Relaxed);
+            counters.allocations.fetch_sub(1, Ordering::Relaxed);
+            LIVE.lock().unwrap().remove(&self.id);
+        }
+    }
+}
+
+/// "1.5 MiB", "512 B"...
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+/// Fenêtre de répartition de la mémoire suivie et des ressources vivantes.
+#[derive(Debug, Default)]
+pub struct MemoryPanel {
+    /// Génération marquée depuis le panneau : seules les ressources créées depuis sont
+    /// listées (0 = toutes).
+    mark: u64,
+}
+
+impl MemoryPanel {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        let stats = memory_stats();
+        let total: u64 = stats.iter().map(|stats| stats.bytes).sum();
+        ui.label(format!("Tracked: {}", format_bytes(total)));
+        ui.separator();
+
+        egui::Grid::new("memory_panel")
+            .num_columns(4)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.strong("Category");
+                ui.strong("Current");
+                ui.strong("Peak");
+                ui.strong("Count");
+                ui.end_row();
+                for stats in &stats {
+                    let fraction = match total {
+                        0 => 0.0,
+                        total => stats.bytes as f32 / total as f32,
+                    };
+                    ui.add(
+                        egui::ProgressBar::new(fraction)
+                            .desired_width(140.0)
+                            .text(stats.category.label()),
+                    );
+                    ui.label(format_bytes(stats.bytes));
+                    ui.label(format_bytes(stats.peak));
+                    ui.label(stats.allocations.to_string());
+                    ui.end_row();
+                }
+            });
+
+        ui.separator();
+        if ui.button("Reset peaks").clicked() {
+            reset_memory_peaks();
+        }
+
+        ui.separator();
+        let resources = live_resources(self.mark);
+        ui.horizontal(|ui| {
+            if ui
+                .button("Mark")
+                .on_hover_text("Only list resources created from now on")
+                .clicked()
+            {
+                self.mark = begin_resource_generation();
+            }
+            if ui.button("Log live resources").clicked() {
+                report_live_resources("memory panel", self.mark);
+            }
+        });
+        egui::CollapsingHeader::new(format!("Live resources ({})", resources.len()))
+            .id_salt("memory_panel_live")
+            .show(ui, |ui| {
+                egui::ScrollArea::vertical()
+                    .max_height(240.0)
+                    .show(ui, |ui| {
+                        for resource in resources.iter().rev() {
+                            let label = match resource.label.as_str() {
+                                "" => resource.category.label(),
+                                label => label,
+                            };
+                            ui.label(format!(
+                                "{} ({}) - {}",
+                                label,
+                                format_bytes(resource.bytes),
+                                resource.location
+                            ));
+                        }
+                    });
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tracked_memory_is_released_on_drop() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(3 * 1024 * 1024), "3.0 MiB");
+
+        // Seul ce test utilise les buffers d'instances (pas de GPU dans les tests)
+        let category = MemoryCategory::InstanceBuffers;
+        let stats = || memory_stats()[category as usize];
+        let before = stats();
+        let first = TrackedMemory::new(category, 1000);
+        let second = TrackedMemory::new(category, 24);
+        assert_eq!(stats().bytes, before.bytes + 1024);
+        assert_eq!(stats().allocations, before.allocations + 2);
+        drop(first);
+        drop(second);
+        assert_eq!(stats().bytes, before.bytes);
+        assert!(stats().peak >= before.bytes + 1024);
+
+        drop(TrackedMemory::default());
+        assert_eq!(stats().allocations, before.allocations);
+    }
+
+    #[test]
+    fn live_resources_report_their_creation_site() {
+        let generation = begin_resource_generation();
+        let memory = TrackedMemory::labeled(MemoryCategory::BindGroups, 0, "test_bind_group");
+        let live: Vec<_> = live_resources(generation)
+            .into_iter()
+            .filter(|resource| resource.label == "test_bind_group")
+            .collect();
+        assert_eq!(live.len(), 1);
+        assert_eq!(live[0].location.file(), file!());
+
+        drop(memory);
+        assert!(
+            live_resources(generation)
+                .iter()
+                .all(|resource| resource.label != "test_bind_group")
+        );
+    }
+}
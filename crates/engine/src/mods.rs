@@ -0,0 +1,780 @@
+//! Support des mods : découverte dans un dossier `mods/`, manifestes, ordre de chargement
+//! et montage dans le VFS au-dessus des assets de base.
+//!
+//! - Chaque sous-dossier de `mods/` est un mod, de même que chaque archive `.zip` / `.pak`
+//!   (montée avec `ZipFs`, son contenu est celui du dossier).
+//! - Un mod peut fournir un manifeste `mod.cfg` (voir `ModManifest`) : id, nom, version,
+//!   dépendances et priorité de chargement par défaut. Sans manifeste, l'id est le nom du dossier.
+//! - L'ordre de chargement choisi par l'utilisateur est stocké dans `mods/load_order.txt` :
+//!   un id par ligne, préfixé par `!` si le mod est désactivé, `#` pour les commentaires.
+//!   Les mods absents du fichier sont ajoutés selon leur `load_order` puis leur id.
+//! - Un mod est toujours chargé après ses dépendances ; un mod dont une dépendance manque
+//!   ou est désactivée n'est pas monté (voir `ModManager::issues`).
+//! - Les mods actifs sont montés sur le préfixe `assets` dans l'ordre de chargement :
+//!   le dernier mod chargé a la priorité la plus haute (voir `Vfs`). Un fichier absent d'un
+//!   mod retombe sur les mods précédents puis sur les assets de base (`ResolveMode::FirstExisting`).
+//!   Les fichiers fournis par plusieurs mods actifs sont listés par `ModManager::conflicts`.
+
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{Context, Result, anyhow};
+
+use crate::{FileSystem, Ofs, Vfs, ZipFs};
+
+/// Origine d'un mod sur disque.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModSource {
+    Directory(PathBuf),
+    Archive(PathBuf),
+}
+
+impl ModSource {
+    pub fn path(&self) -> &Path {
+        match self {
+            ModSource::Directory(path) | ModSource::Archive(path) => path,
+        }
+    }
+}
+
+/// Manifeste d'un mod (`mod.cfg` à la racine du mod).
+///
+/// Format : une entrée `clé = valeur` par ligne, `#` pour les commentaires.
+/// ```text
+/// id = better_trees
+/// name = Better Trees
+/// version = 1.2.0
+/// dependencies = core_textures, hd_ui
+/// load_order = 10
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModManifest {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    /// Ids des mods qui doivent être chargés (et actifs) avant celui-ci.
+    pub dependencies: Vec<String>,
+    /// Priorité par défaut quand le mod n'est pas encore dans `load_order.txt` :
+    /// les valeurs basses sont chargées en premier.
+    pub load_order: i32,
+}
+
+impl ModManifest {
+    pub const FILE_NAME: &str = "mod.cfg";
+
+    /// Manifeste implicite d'un mod qui n'en fournit pas.
+    pub fn with_id(id: impl Into<String>) -> Self {
+        let id = id.into();
+        Self {
+            name: id.clone(),
+            id,
+            version: String::from("0.0.0"),
+            dependencies: Vec::new(),
+            load_order: 0,
+        }
+    }
+
+    /// Parse un manifeste. `default_id` est utilisé si le fichier ne précise pas d'id.
+    pub fn parse(text: &str, default_id: &str) -> Result<Self> {
+        let mut manifest = Self::with_id(default_id);
+        let mut name = None;
+
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| anyhow!("line {}: expected `key = value`", number + 1))?;
+            let value = value.trim();
+
+            match key.trim() {
+                "id" if !value.is_empty() => manifest.id = value.to_string(),
+                "name" => name = Some(value.to_string()),
+                "version" => manifest.version = value.to_string(),
+                "dependencies" => {
+                    manifest.dependencies = value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|d| !d.is_empty())
+                        .map(str::to_string)
+                        .collect();
+                }
+                "load_order" => {
+                    manifest.load_order = value.parse().with_context(|| {
+                        format!("line {}: invalid load_order {:?}", number + 1, value)
+                    })?;
+                }
+                other => log::warn!("Unknown mod manifest key {:?} (line {})", other, number + 1),
+            }
+        }
+
+        manifest.name = name.unwrap_or_else(|| manifest.id.clone());
+        Ok(manifest)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ModInfo {
+    pub manifest: ModManifest,
+    pub source: ModSource,
+    pub enabled: bool,
+}
+
+impl ModInfo {
+    pub fn id(&self) -> &str {
+        &self.manifest.id
+    }
+}
+
+/// Problème détecté lors de la résolution des mods.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModIssue {
+    /// Le manifeste n'a pas pu être lu : le mod est chargé avec un manifeste implicite.
+    InvalidManifest { id: String, error: String },
+    /// Deux mods déclarent le même id : seul le premier est gardé.
+    DuplicateId { id: String, path: PathBuf },
+    /// Une dépendance n'est pas installée : le mod n'est pas monté.
+    MissingDependency { id: String, dependency: String },
+    /// Une dépendance est installée mais inactive : le mod n'est pas monté.
+    InactiveDependency { id: String, dependency: String },
+    /// Dépendances circulaires entre ces mods.
+    DependencyCycle(Vec<String>),
+}
+
+impl fmt::Display for ModIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ModIssue::InvalidManifest { id, error } => {
+                write!(f, "{}: invalid manifest ({})", id, error)
+            }
+            ModIssue::DuplicateId { id, path } => {
+                write!(f, "{}: duplicate mod id, {:?} ignored", id, path)
+            }
+            ModIssue::MissingDependency { id, dependency } => {
+                write!(f, "{}: missing dependency {:?}", id, dependency)
+            }
+            ModIssue::InactiveDependency { id, dependency } => {
+                write!(f, "{}: dependency {:?} is not active", id, dependency)
+            }
+            ModIssue::DependencyCycle(ids) => {
+                write!(f, "dependency cycle between {}", ids.join(", "))
+            }
+        }
+    }
+}
+
+/// Fichier fourni par plusieurs mods actifs : le dernier de `mods` (ordre de chargement) gagne.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModConflict {
+    /// Chemin relatif à la racine des mods (= relatif au préfixe `assets`).
+    pub path: String,
+    pub mods: Vec<String>,
+}
+
+impl ModConflict {
+    pub fn winner(&self) -> &str {
+        self.mods.last().map(String::as_str).unwrap_or_default()
+    }
+}
+
+pub struct ModManager {
+    root: PathBuf,
+    vfs: Arc<Vfs>,
+    /// Mods découverts, dans l'ordre de chargement.
+    mods: Vec<ModInfo>,
+    /// Ids des mods effectivement montés par le dernier `check`/`apply`.
+    active: HashSet<String>,
+    issues: Vec<ModIssue>,
+    conflicts: Vec<ModConflict>,
+}
+
+impl ModManager {
+    /// Préfixe VFS sur lequel les mods sont montés (au-dessus des assets de base).
+    pub const MOUNT_PREFIX: &str = "assets";
+    /// Nom du fichier d'ordre de chargement, dans le dossier des mods.
+    pub const LOAD_ORDER_FILE: &str = "load_order.txt";
+    const MOUNT_NAME_PREFIX: &str = "mod:";
+    const ARCHIVE_EXTENSIONS: [&str; 2] = ["zip", "pak"];
+
+    pub fn new(root: impl Into<PathBuf>, vfs: Arc<Vfs>) -> Self {
+        Self {
+            root: root.into(),
+            vfs,
+            mods: Vec::new(),
+            active: HashSet::new(),
+            issues: Vec::new(),
+            conflicts: Vec::new(),
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Mods connus, dans l'ordre de chargement.
+    pub fn mods(&self) -> &[ModInfo] {
+        &self.mods
+    }
+
+    pub fn get(&self, id: &str) -> Option<&ModInfo> {
+        self.mods.iter().find(|m| m.id() == id)
+    }
+
+    /// `true` si le mod est activé et que toutes ses dépendances sont actives.
+    pub fn is_active(&self, id: &str) -> bool {
+        self.active.contains(id)
+    }
+
+    /// Problèmes détectés par le dernier `discover`/`check`.
+    pub fn issues(&self) -> &[ModIssue] {
+        &self.issues
+    }
+
+    /// Fichiers fournis par plusieurs mods actifs (dernier `discover`/`check`).
+    pub fn conflicts(&self) -> &[ModConflict] {
+        &self.conflicts
+    }
+
+    /// Scanne le dossier des mods, lit les manifestes puis applique l'ordre de `load_order.txt`.
+    /// Les mods absents du fichier sont ajoutés à la fin (par `load_order` puis id), activés.
+    /// Un dossier `mods/` inexistant donne simplement une liste vide.
+    pub fn discover(&mut self) -> Result<()> {
+        let mut found: Vec<ModInfo> = Vec::new();
+        let mut issues = Vec::new();
+
+        if self.root.is_dir() {
+            let entries = std::fs::read_dir(&self.root)
+                .with_context(|| format!("failed to read mods directory {:?}", self.root))?;
+
+            let mut paths = entries
+                .map(|entry| entry.map(|e| e.path()))
+                .collect::<std::io::Result<Vec<_>>>()
+                .with_context(|| format!("failed to read mods directory {:?}", self.root))?;
+            paths.sort();
+
+            for path in paths {
+                let Some(stem) = path.file_stem().and_then(|s| s.to_str()) else {
+                    continue;
+                };
+
+                let source = if path.is_dir() {
+                    ModSource::Directory(path.clone())
+                } else if path
+                    .extension()
+                    .and_then(|e| e.to_str())
+                    .is_some_and(|e| Self::ARCHIVE_EXTENSIONS.contains(&e))
+                {
+                    ModSource::Archive(path.clone())
+                } else {
+                    continue;
+                };
+
+                let manifest = match Self::read_manifest(&source, stem) {
+                    Ok(manifest) => manifest,
+                    Err(e) => {
+                        issues.push(ModIssue::InvalidManifest {
+                            id: stem.to_string(),
+                            error: format!("{:#}", e),
+                        });
+                        ModManifest::with_id(stem)
+                    }
+                };
+
+                if found.iter().any(|m| m.id() == manifest.id) {
+                    issues.push(ModIssue::DuplicateId {
+                        id: manifest.id,
+                        path,
+                    });
+                    continue;
+                }
+
+                found.push(ModInfo {
+                    manifest,
+                    source,
+                    enabled: true,
+                });
+            }
+        }
+
+        found.sort_by(|a, b| (a.manifest.load_order, a.id()).cmp(&(b.manifest.load_order, b.id())));
+
+        // Ordre de chargement persistant
+        let mut ordered = Vec::with_capacity(found.len());
+        for (id, enabled) in self.read_load_order() {
+            if let Some(index) = found.iter().position(|m| m.id() == id) {
+                let mut info = found.remove(index);
+                info.enabled = enabled;
+                ordered.push(info);
+            }
+        }
+        ordered.extend(found);
+
+        self.mods = ordered;
+        self.check();
+        self.issues.splice(0..0, issues);
+        Ok(())
+    }
+
+    fn read_manifest(source: &ModSource, default_id: &str) -> Result<ModManifest> {
+        let text = match source {
+            ModSource::Directory(dir) => {
+                let path = dir.join(ModManifest::FILE_NAME);
+                if !path.is_file() {
+                    return Ok(ModManifest::with_id(default_id));
+                }
+                std::fs::read_to_string(&path)
+                    .with_context(|| format!("failed to read {:?}", path))?
+            }
+            ModSource::Archive(path) => {
+                let archive = ZipFs::open(path, default_id)?;
+                let manifest = Path::new(ModManifest::FILE_NAME);
+                if !archive.exists(manifest) {
+                    return Ok(ModManifest::with_id(default_id));
+                }
+                archive.read_to_string(manifest)?
+            }
+        };
+        ModManifest::parse(&text, default_id)
+    }
+
+    fn load_order_path(&self) -> PathBuf {
+        self.root.join(Self::LOAD_ORDER_FILE)
+    }
+
+    /// Lit `load_order.txt` : liste de (id, enabled).
+    fn read_load_order(&self) -> Vec<(String, bool)> {
+        let Ok(text) = std::fs::read_to_string(self.load_order_path()) else {
+            return Vec::new();
+        };
+
+        text.lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+            .map(|l| match l.strip_prefix('!') {
+                Some(id) => (id.trim().to_string(), false),
+                None => (l.to_string(), true),
+            })
+            .collect()
+    }
+
+    /// Écrit l'ordre de chargement courant dans `load_order.txt`.
+    pub fn save_load_order(&self) -> Result<()> {
+        let mut text =
+            String::from("# Mod load order: last entry wins. Prefix with '!' to disable.\n");
+        for info in &self.mods {
+            if !info.enabled {
+                text.push('!');
+            }
+            text.push_str(info.id());
+            text.push('\n');
+        }
+
+        std::fs::create_dir_all(&self.root)
+            .with_context(|| format!("failed to create mods directory {:?}", self.root))?;
+        std::fs::write(self.load_order_path(), text)
+            .with_context(|| format!("failed to write {:?}", self.load_order_path()))
+    }
+
+    pub fn set_enabled(&mut self, id: &str, enabled: bool) -> bool {
+        match self.mods.iter_mut().find(|m| m.id() == id) {
+            Some(info) => {
+                info.enabled = enabled;
+                self.check();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Déplace un mod dans l'ordre de chargement (index borné à la liste).
+    /// Les dépendances restent prioritaires : un mod ne peut pas passer avant elles.
+    pub fn move_to(&mut self, id: &str, index: usize) -> bool {
+        let Some(current) = self.mods.iter().position(|m| m.id() == id) else {
+            return false;
+        };
+        let info = self.mods.remove(current);
+        let index = index.min(self.mods.len());
+        self.mods.insert(index, info);
+        self.check();
+        true
+    }
+
+    /// Réordonne les mods pour que chacun suive ses dépendances (tri stable), puis recalcule
+    /// les mods actifs, les problèmes et les conflits de fichiers.
+    pub fn check(&mut self) {
+        self.issues.clear();
+        self.sort_by_dependencies();
+
+        self.active.clear();
+        for info in &self.mods {
+            if !info.enabled {
+                continue;
+            }
+
+            let mut loadable = true;
+            for dependency in &info.manifest.dependencies {
+                if self.active.contains(dependency) {
+                    continue;
+                }
+                loadable = false;
+                let issue = if self.mods.iter().any(|m| m.id() == dependency) {
+                    ModIssue::InactiveDependency {
+                        id: info.id().to_string(),
+                        dependency: dependency.clone(),
+                    }
+                } else {
+                    ModIssue::MissingDependency {
+                        id: info.id().to_string(),
+                        dependency: dependency.clone(),
+                    }
+                };
+                self.issues.push(issue);
+            }
+
+            if loadable {
+                self.active.insert(info.id().to_string());
+            }
+        }
+
+        self.conflicts = self.find_conflicts();
+    }
+
+    /// Tri topologique stable : à dépendances satisfaites, l'ordre courant est conservé.
+    /// Les dépendances inconnues sont ignorées ; les cycles restent en fin de liste.
+    fn sort_by_dependencies(&mut self) {
+        let ids: HashSet<String> = self.mods.iter().map(|m| m.id().to_string()).collect();
+        let mut remaining = std::mem::take(&mut self.mods);
+        let mut placed: HashSet<String> = HashSet::new();
+
+        while !remaining.is_empty() {
+            let ready = remaining.iter().position(|m| {
+                m.manifest
+                    .dependencies
+                    .iter()
+                    .all(|d| placed.contains(d) || !ids.contains(d))
+            });
+
+            match ready {
+                Some(index) => {
+                    let info = remaining.remove(index);
+                    placed.insert(info.id().to_string());
+                    self.mods.push(info);
+                }
+                None => {
+                    let cycle = remaining.iter().map(|m| m.id().to_string()).collect();
+                    log::warn!("Mod dependency cycle: {:?}", cycle);
+                    self.issues.push(ModIssue::DependencyCycle(cycle));
+                    self.mods.append(&mut remaining);
+                }
+            }
+        }
+    }
+
+    /// Fichiers fournis par plus d'un mod actif.
+    fn find_conflicts(&self) -> Vec<ModConflict> {
+        let mut providers: BTreeMap<String, Vec<String>> = BTreeMap::new();
+
+        for info in self.mods.iter().filter(|m| self.active.contains(m.id())) {
+            let mut files = Vec::new();
+            let listed = match &info.source {
+                ModSource::Directory(dir) => collect_files(dir, dir, &mut files),
+                ModSource::Archive(path) => ZipFs::open(path, info.id()).map(|archive| {
+                    files.extend(
+                        archive
+                            .list_files(Path::new(""))
+                            .iter()
+                            .map(|file| file.to_string_lossy().into_owned()),
+                    );
+                }),
+            };
+            if let Err(e) = listed {
+                log::warn!("Failed to list files of mod {:?}: {:#}", info.id(), e);
+            }
+            for file in files {
+                if file == ModManifest::FILE_NAME {
+                    continue;
+                }
+                providers
+                    .entry(file)
+                    .or_default()
+                    .push(info.id().to_string());
+            }
+        }
+
+        providers
+            .into_iter()
+            .filter(|(_, mods)| mods.len() > 1)
+            .map(|(path, mods)| ModConflict { path, mods })
+            .collect()
+    }
+
+    /// (Re)monte les mods actifs dans le VFS, dans l'ordre de chargement.
+    /// Les montages de mods précédents sont retirés d'abord.
+    pub fn apply(&mut self) {
+        self.check();
+
+        self.vfs
+            .unmount_where(|name| name.starts_with(Self::MOUNT_NAME_PREFIX));
+
+        for issue in &self.issues {
+            log::warn!("Mod issue: {}", issue);
+        }
+        for conflict in &self.conflicts {
+            log::info!(
+                "Mod conflict on {:?}: {:?} (winner: {:?})",
+                conflict.path,
+                conflict.mods,
+                conflict.winner()
+            );
+        }
+
+        for info in self.mods.iter().filter(|m| self.active.contains(m.id())) {
+            let name = format!("{}{}", Self::MOUNT_NAME_PREFIX, info.id());
+            let fs: Arc<dyn FileSystem> = match &info.source {
+                ModSource::Directory(path) => Arc::new(Ofs::new(path.clone(), name)),
+                ModSource::Archive(path) => match ZipFs::open(path, name) {
+                    Ok(archive) => Arc::new(archive),
+                    Err(e) => {
+                        log::warn!("Failed to mount mod {:?}: {:#}", info.id(), e);
+                        continue;
+                    }
+                },
+            };
+            self.vfs
+                .mount_with_priority(Self::MOUNT_PREFIX, fs, false, Vfs::MOD_PRIORITY);
+            log::info!(
+                "Mounted mod {:?} {} from {:?}",
+                info.id(),
+                info.manifest.version,
+                info.source.path()
+            );
+        }
+    }
+
+    /// Liste éditable des mods (activer/désactiver, réordonner), avec les problèmes
+    /// de dépendances et les conflits de fichiers.
+    /// Retourne `true` si l'utilisateur a demandé d'appliquer les changements
+    /// (ils sont alors sauvegardés et remontés).
+    pub fn ui(&mut self, ui: &mut egui::Ui) -> bool {
+        if self.mods.is_empty() {
+            ui.label(format!("No mods found in {:?}", self.root));
+        }
+
+        let mut changed = false;
+        let mut move_request: Option<(usize, usize)> = None;
+        let count = self.mods.len();
+
+        for (index, info) in self.mods.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                changed |= ui
+                    .checkbox(&mut info.enabled, &info.manifest.name)
+                    .changed();
+                ui.weak(&info.manifest.version);
+                if matches!(info.source, ModSource::Archive(_)) {
+                    ui.weak("(archive)");
+                }
+                if info.enabled && !self.active.contains(&info.manifest.id) {
+                    ui.colored_label(ui.visuals().warn_fg_color, "inactive");
+                }
+                if ui.add_enabled(index > 0, egui::Button::new("⬆")).clicked() {
+                    move_request = Some((index, index - 1));
+                }
+                if ui
+                    .add_enabled(index + 1 < count, egui::Button::new("⬇"))
+                    .clicked()
+                {
+                    move_request = Some((index, index + 1));
+                }
+            });
+        }
+
+        if let Some((from, to)) = move_request {
+            self.mods.swap(from, to);
+            changed = true;
+        }
+        if changed {
+            self.check();
+        }
+
+        if !self.issues.is_empty() {
+            ui.separator();
+            for issue in &self.issues {
+                ui.colored_label(ui.visuals().warn_fg_color, issue.to_string());
+            }
+        }
+
+        if !self.conflicts.is_empty() {
+            ui.collapsing(format!("Conflicts ({})", self.conflicts.len()), |ui| {
+                for conflict in &self.conflicts {
+                    ui.label(format!(
+                        "{}: {} (uses {})",
+                        conflict.path,
+                        conflict.mods.join(", "),
+                        conflict.winner()
+                    ));
+                }
+            });
+        }
+
+        ui.separator();
+
+        let mut applied = false;
+        ui.horizontal(|ui| {
+            if ui.button("Rescan").clicked()
+                && let Err(e) = self.discover()
+            {
+                log::error!("Failed to scan mods: {:#}", e);
+            }
+            if ui.button("Apply").clicked() {
+                if let Err(e) = self.save_load_order() {
+                    log::error!("Failed to save mod load order: {:#}", e);
+                }
+                self.apply();
+                applied = true;
+            }
+        });
+
+        applied
+    }
+}
+
+/// Liste récursivement les fichiers de `dir`, en chemins relatifs à `root` (séparateur `/`).
+fn collect_files(root: &Path, dir: &Path, out: &mut Vec<String>) -> Result<()> {
+    for entry in std::fs::read_dir(dir).with_context(|| format!("failed to read {:?}", dir))? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(root, &path, out)?;
+        } else if let Ok(rel) = path.strip_prefix(root) {
+            let rel: Vec<_> = rel
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect();
+            out.push(rel.join("/"));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+
+    fn write_files(files: &[(PathBuf, &str)]) {
+        for (path, content) in files {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        }
+    }
+
+    #[test]
+    fn discover_orders_and_mounts_mods_above_base_assets() {
+        let dir = tempdir().unwrap();
+        let base = dir.path().join("base");
+        let mods = dir.path().join("mods");
+        write_files(&[
+            (base.join("title.txt"), "base"),
+            (mods.join("alpha").join("title.txt"), "alpha"),
+            (mods.join("beta").join("title.txt"), "beta"),
+            (mods.join("load_order.txt"), "beta\nalpha\n"),
+        ]);
+
+        let vfs = Arc::new(Vfs::new());
+        vfs.mount_os("assets", &base, "Assets", false);
+
+        let mut manager = ModManager::new(&mods, vfs.clone());
+        manager.discover().unwrap();
+        let ids: Vec<_> = manager.mods().iter().map(ModInfo::id).collect();
+        assert_eq!(ids, ["beta", "alpha"]);
+
+        manager.apply();
+        assert_eq!(vfs.read_to_string("assets/title.txt").unwrap(), "alpha");
+        assert_eq!(manager.conflicts().len(), 1);
+        assert_eq!(manager.conflicts()[0].winner(), "alpha");
+
+        // Désactiver alpha : beta prend le relais, et l'ordre est persisté
+        manager.set_enabled("alpha", false);
+        manager.save_load_order().unwrap();
+        manager.apply();
+        assert_eq!(vfs.read_to_string("assets/title.txt").unwrap(), "beta");
+        assert!(manager.conflicts().is_empty());
+
+        let mut reloaded = ModManager::new(&mods, vfs);
+        reloaded.discover().unwrap();
+        assert!(!reloaded.mods()[1].enabled);
+    }
+
+    #[test]
+    fn archive_mods_are_mounted_with_their_manifest() {
+        let dir = tempdir().unwrap();
+        let source = dir.path().join("source");
+        let mods = dir.path().join("mods");
+        write_files(&[
+            (source.join("mod.cfg"), "id = packed\nversion = 2.0.0\n"),
+            (source.join("title.txt"), "packed"),
+            (mods.join("loose").join("title.txt"), "loose"),
+        ]);
+        crate::pack_directory(&source, mods.join("z_packed.pak")).unwrap();
+
+        let vfs = Arc::new(Vfs::new());
+        let mut manager = ModManager::new(&mods, vfs.clone());
+        manager.discover().unwrap();
+        let packed = manager.get("packed").unwrap();
+        assert_eq!(packed.manifest.version, "2.0.0");
+        assert!(matches!(packed.source, ModSource::Archive(_)));
+
+        manager.apply();
+        assert_eq!(vfs.read_to_string("assets/title.txt").unwrap(), "packed");
+        assert_eq!(manager.conflicts().len(), 1);
+        assert_eq!(manager.conflicts()[0].mods, ["loose", "packed"]);
+    }
+
+    #[test]
+    fn dependencies_are_loaded_first_and_required() {
+        let dir = tempdir().unwrap();
+        let mods = dir.path().join("mods");
+        write_files(&[
+            (
+                mods.join("a_addon").join("mod.cfg"),
+                "id = addon\nversion = 1.0.0\ndependencies = core\n",
+            ),
+            (mods.join("core").join("mod.cfg"), "load_order = 5\n"),
+            (
+                mods.join("orphan").join("mod.cfg"),
+                "dependencies = not_installed\n",
+            ),
+        ]);
+
+        let mut manager = ModManager::new(&mods, Arc::new(Vfs::new()));
+        manager.discover().unwrap();
+
+        let ids: Vec<_> = manager.mods().iter().map(ModInfo::id).collect();
+        assert_eq!(ids, ["orphan", "core", "addon"]);
+        assert_eq!(manager.get("addon").unwrap().manifest.version, "1.0.0");
+        assert!(manager.is_active("addon"));
+        assert!(!manager.is_active("orphan"));
+        assert_eq!(
+            manager.issues(),
+            [ModIssue::MissingDependency {
+                id: "orphan".into(),
+                dependency: "not_installed".into(),
+            }]
+        );
+
+        // Désactiver la dépendance désactive l'addon
+        manager.set_enabled("core", false);
+        assert!(!manager.is_active("addon"));
+        assert!(manager.issues().contains(&ModIssue::InactiveDependency {
+            id: "addon".into(),
+            dependency: "core".into(),
+        }));
+    }
+}
@@ -0,0 +1,228 @@
+//! Préférences de l'éditeur, propres à l'utilisateur (pas au projet).
+//!
+//! Stockées dans le mount utilisateur (`EditorPreferences::PATH`) au format `clé = valeur`,
+//! comme les manifestes de mods.
+
+use anyhow::{Context, Result, anyhow};
+
+use crate::Vfs;
+
+/// Thème de l'interface egui.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditorTheme {
+    Dark,
+    Light,
+}
+
+impl EditorTheme {
+    pub const ALL: [EditorTheme; 2] = [EditorTheme::Dark, EditorTheme::Light];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            EditorTheme::Dark => "dark",
+            EditorTheme::Light => "light",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|theme| theme.name() == name)
+    }
+
+    pub fn visuals(self) -> egui::Visuals {
+        match self {
+            EditorTheme::Dark => egui::Visuals::dark(),
+            EditorTheme::Light => egui::Visuals::light(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EditorPreferences {
+    /// Intervalle de sauvegarde automatique, en secondes (0 = désactivée).
+    pub autosave_interval: u32,
+    pub theme: EditorTheme,
+    /// Vitesse de déplacement de la caméra de l'éditeur (pixels par seconde).
+    pub camera_speed: f32,
+    /// Multiplicateur des mouvements de souris quand la caméra capture la souris.
+    pub mouse_sensitivity: f32,
+    /// Taille par défaut d'une case de la grille (pixels).
+    pub grid_size: f32,
+    /// Grille affichée par défaut à l'ouverture d'une scène.
+    pub grid_visible: bool,
+    /// Commande d'ouverture des scripts / shaders dans un éditeur externe.
+    /// `{path}` est remplacé par le chemin du fichier (ex: "code {path}").
+    pub external_editor: String,
+}
+
+impl Default for EditorPreferences {
+    fn default() -> Self {
+        Self {
+            autosave_interval: 300,
+            theme: EditorTheme::Dark,
+            camera_speed: 500.0,
+            mouse_sensitivity: 1.0,
+            grid_size: 32.0,
+            grid_visible: true,
+            external_editor: String::new(),
+        }
+    }
+}
+
+impl EditorPreferences {
+    /// Fichier des préférences, dans le mount utilisateur.
+    pub const PATH: &str = "user/preferences.cfg";
+
+    /// Parse les préférences. Les clés absentes gardent leur valeur par défaut.
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut prefs = Self::default();
+
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| anyhow!("line {}: expected `key = value`", number + 1))?;
+            let (key, value) = (key.trim(), value.trim());
+            let invalid = || format!("line {}: invalid {} {:?}", number + 1, key, value);
+
+            match key {
+                "autosave_interval" => {
+                    prefs.autosave_interval = value.parse().with_context(invalid)?
+                }
+                "theme" => {
+                    prefs.theme = EditorTheme::from_name(value).ok_or_else(|| anyhow!(invalid()))?
+                }
+                "camera_speed" => prefs.camera_speed = value.parse().with_context(invalid)?,
+                "mouse_sensitivity" => {
+                    prefs.mouse_sensitivity = value.parse().with_context(invalid)?
+                }
+                "grid_size" => prefs.grid_size = value.parse().with_context(invalid)?,
+                "grid_visible" => prefs.grid_visible = value.parse().with_context(invalid)?,
+                "external_editor" => prefs.external_editor = value.to_string(),
+                other => log::warn!("Unknown preference {:?} (line {})", other, number + 1),
+            }
+        }
+
+        Ok(prefs)
+    }
+
+    pub fn encode(&self) -> String {
+        format!(
+            "autosave_interval = {}\ntheme = {}\ncamera_speed = {}\nmouse_sensitivity = {}\n\
+             grid_size = {}\ngrid_visible = {}\nexternal_editor = {}\n",
+            self.autosave_interval,
+            self.theme.name(),
+            self.camera_speed,
+            self.mouse_sensitivity,
+            self.grid_size,
+            self.grid_visible,
+            self.external_editor,
+        )
+    }
+
+    /// Charge les préférences, ou les valeurs par défaut si le fichier n'existe pas encore.
+    pub fn load(vfs: &Vfs) -> Result<Self> {
+        if !vfs.exists(Self::PATH) {
+            return Ok(Self::default());
+        }
+        let text = vfs.read_to_string(Self::PATH)?;
+        Self::parse(&text).with_context(|| format!("failed to parse {:?}", Self::PATH))
+    }
+
+    pub fn save(&self, vfs: &Vfs) -> Result<()> {
+        vfs.write_bytes(Self::PATH, self.encode().as_bytes())
+    }
+
+    /// Formulaire des préférences. Retourne `true` si une valeur a changé.
+    pub fn ui(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut changed = false;
+
+        egui::Grid::new("editor_preferences")
+            .num_columns(2)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label("Theme");
+                egui::ComboBox::from_id_salt("preferences_theme")
+                    .selected_text(self.theme.name())
+                    .show_ui(ui, |ui| {
+                        for theme in EditorTheme::ALL {
+                            changed |= ui
+                                .selectable_value(&mut self.theme, theme, theme.name())
+                                .changed();
+                        }
+                    });
+                ui.end_row();
+
+                ui.label("Autosave (s, 0 = off)");
+                changed |= ui
+                    .add(egui::DragValue::new(&mut self.autosave_interval).range(0..=3600))
+                    .changed();
+                ui.end_row();
+
+                ui.label("Camera speed");
+                changed |= ui
+                    .add(egui::DragValue::new(&mut self.camera_speed).range(1.0..=10_000.0))
+                    .changed();
+                ui.end_row();
+
+                ui.label("Mouse sensitivity");
+                changed |= ui
+                    .add(egui::Slider::new(&mut self.mouse_sensitivity, 0.1..=5.0))
+                    .changed();
+                ui.end_row();
+
+                ui.label("Grid size");
+                changed |= ui
+                    .add(egui::DragValue::new(&mut self.grid_size).range(1.0..=1024.0))
+                    .changed();
+                ui.end_row();
+
+                ui.label("Show grid");
+                changed |= ui.checkbox(&mut self.grid_visible, "").changed();
+                ui.end_row();
+
+                ui.label("External editor");
+                changed |= ui
+                    .add(
+                        egui::TextEdit::singleline(&mut self.external_editor)
+                            .hint_text("code {path}"),
+                    )
+                    .changed();
+                ui.end_row();
+            });
+
+        if ui.button("Reset to defaults").clicked() {
+            *self = Self::default();
+            changed = true;
+        }
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_parse_roundtrip() {
+        let prefs = EditorPreferences {
+            autosave_interval: 60,
+            theme: EditorTheme::Light,
+            camera_speed: 250.0,
+            mouse_sensitivity: 0.5,
+            grid_size: 16.0,
+            grid_visible: false,
+            external_editor: "code --goto {path}".to_string(),
+        };
+        assert_eq!(EditorPreferences::parse(&prefs.encode()).unwrap(), prefs);
+
+        let partial = EditorPreferences::parse("# comment\ntheme = light\n").unwrap();
+        assert_eq!(partial.theme, EditorTheme::Light);
+        assert_eq!(partial.grid_size, EditorPreferences::default().grid_size);
+
+        assert!(EditorPreferences::parse("theme = purple").is_err());
+    }
+}
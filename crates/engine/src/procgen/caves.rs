@@ -0,0 +1,76 @@
+use super::{CellGrid, Rng};
+
+/// Cave generation with a cellular automaton: the grid is filled with random walls, then
+/// smoothed `steps` times (a floor cell with `birth_limit` wall neighbours or more becomes a
+/// wall, a wall with fewer than `survival_limit` becomes floor). The border is always wall.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CaveGenerator {
+    /// Probability of a wall in the initial fill.
+    pub fill: f32,
+    pub steps: u32,
+    pub birth_limit: u32,
+    pub survival_limit: u32,
+}
+
+impl Default for CaveGenerator {
+    fn default() -> Self {
+        Self {
+            fill: 0.45,
+            steps: 5,
+            birth_limit: 5,
+            survival_limit: 4,
+        }
+    }
+}
+
+impl CaveGenerator {
+    pub fn generate(&self, width: u32, height: u32, rng: &mut Rng) -> CellGrid {
+        let border = |x: u32, y: u32| x == 0 || y == 0 || x + 1 >= width || y + 1 >= height;
+
+        let mut grid = CellGrid::new(width, height, true);
+        for y in 0..height {
+            for x in 0..width {
+                grid.set_wall(x, y, border(x, y) || rng.chance(self.fill));
+            }
+        }
+
+        for _ in 0..self.steps {
+            let previous = grid.clone();
+            for y in 0..height {
+                for x in 0..width {
+                    let neighbours = previous.wall_neighbours(x, y);
+                    let wall = if previous.is_wall(x as i64, y as i64) {
+                        neighbours >= self.survival_limit
+                    } else {
+                        neighbours >= self.birth_limit
+                    };
+                    grid.set_wall(x, y, border(x, y) || wall);
+                }
+            }
+        }
+        grid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn caves_are_walled_and_deterministic() {
+        let generator = CaveGenerator::default();
+        let grid = generator.generate(40, 30, &mut Rng::new(3));
+        assert_eq!(grid, generator.generate(40, 30, &mut Rng::new(3)));
+        assert_ne!(grid, generator.generate(40, 30, &mut Rng::new(4)));
+
+        for x in 0..40 {
+            assert!(grid.is_wall(x, 0) && grid.is_wall(x, 29));
+        }
+        for y in 0..30 {
+            assert!(grid.is_wall(0, y) && grid.is_wall(39, y));
+        }
+        // Ni tout plein ni tout vide
+        let floor = grid.floor_count();
+        assert!(floor > 100 && floor < 38 * 28);
+    }
+}
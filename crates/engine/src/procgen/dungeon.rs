@@ -0,0 +1,152 @@
+use super::{CellGrid, Rng};
+
+/// Rectangular room of a `Dungeon`, in cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Room {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Room {
+    pub fn center(&self) -> (u32, u32) {
+        (self.x + self.width / 2, self.y + self.height / 2)
+    }
+
+    /// Whether the rooms overlap or are less than `margin` cells apart.
+    pub fn intersects(&self, other: &Room, margin: u32) -> bool {
+        self.x < other.x + other.width + margin
+            && other.x < self.x + self.width + margin
+            && self.y < other.y + other.height + margin
+            && other.y < self.y + self.height + margin
+    }
+
+    pub fn contains(&self, x: u32, y: u32) -> bool {
+        (self.x..self.x + self.width).contains(&x) && (self.y..self.y + self.height).contains(&y)
+    }
+}
+
+/// Result of `DungeonGenerator::generate`: the carved grid and its rooms, in the order they
+/// are connected (each room has a corridor to the previous one).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dungeon {
+    pub grid: CellGrid,
+    pub rooms: Vec<Room>,
+}
+
+/// Room and corridor dungeons: rooms of random size are placed without overlapping, then
+/// joined by L-shaped corridors.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DungeonGenerator {
+    /// Maximum number of rooms (fewer when the map is too small).
+    pub room_count: u32,
+    /// Side of a room, walls excluded.
+    pub min_room_size: u32,
+    pub max_room_size: u32,
+    /// Placement tries before giving up on more rooms.
+    pub attempts: u32,
+}
+
+impl Default for DungeonGenerator {
+    fn default() -> Self {
+        Self {
+            room_count: 12,
+            min_room_size: 4,
+            max_room_size: 10,
+            attempts: 200,
+        }
+    }
+}
+
+impl DungeonGenerator {
+    pub fn generate(&self, width: u32, height: u32, rng: &mut Rng) -> Dungeon {
+        let mut grid = CellGrid::new(width, height, true);
+        let mut rooms: Vec<Room> = Vec::new();
+        let min = self.min_room_size.max(1);
+        let max = self.max_room_size.max(min);
+
+        for _ in 0..self.attempts {
+            if rooms.len() as u32 >= self.room_count {
+                break;
+            }
+            let room_width = rng.range(min..max + 1);
+            let room_height = rng.range(min..max + 1);
+            // Une cellule de mur au moins sur chaque bord de la carte
+            if room_width + 2 > width || room_height + 2 > height {
+                continue;
+            }
+            let room = Room {
+                x: rng.range(1..width - room_width),
+                y: rng.range(1..height - room_height),
+                width: room_width,
+                height: room_height,
+            };
+            if rooms.iter().any(|other| room.intersects(other, 1)) {
+                continue;
+            }
+
+            for y in room.y..room.y + room.height {
+                for x in room.x..room.x + room.width {
+                    grid.set_wall(x, y, false);
+                }
+            }
+            if let Some(previous) = rooms.last() {
+                Self::carve_corridor(&mut grid, previous.center(), room.center(), rng.chance(0.5));
+            }
+            rooms.push(room);
+        }
+
+        Dungeon { grid, rooms }
+    }
+
+    /// Carve a one cell wide corridor from `from` to `to`, horizontal leg first or not.
+    fn carve_corridor(
+        grid: &mut CellGrid,
+        (x0, y0): (u32, u32),
+        (x1, y1): (u32, u32),
+        horizontal_first: bool,
+    ) {
+        let corner = if horizontal_first { (x1, y0) } else { (x0, y1) };
+        for ((ax, ay), (bx, by)) in [((x0, y0), corner), (corner, (x1, y1))] {
+            for x in ax.min(bx)..=ax.max(bx) {
+                for y in ay.min(by)..=ay.max(by) {
+                    grid.set_wall(x, y, false);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dungeon_rooms_are_separate_and_connected() {
+        let dungeon = DungeonGenerator::default().generate(80, 50, &mut Rng::new(11));
+        assert!(dungeon.rooms.len() >= 4);
+        for (i, room) in dungeon.rooms.iter().enumerate() {
+            for other in &dungeon.rooms[i + 1..] {
+                assert!(!room.intersects(other, 1));
+            }
+        }
+
+        // Toutes les cellules de sol sont atteignables depuis la première salle
+        let grid = &dungeon.grid;
+        let (width, height) = grid.size();
+        let mut seen = vec![false; (width * height) as usize];
+        let mut stack = vec![dungeon.rooms[0].center()];
+        let mut reached = 0;
+        while let Some((x, y)) = stack.pop() {
+            let index = (y * width + x) as usize;
+            if grid.is_wall(x as i64, y as i64) || seen[index] {
+                continue;
+            }
+            seen[index] = true;
+            reached += 1;
+            stack.extend([(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)]);
+        }
+        assert_eq!(reached, grid.floor_count());
+    }
+}
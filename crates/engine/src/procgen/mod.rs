@@ -0,0 +1,186 @@
+//! Procedural generation: seeded noise heightmaps and biomes, cellular automata caves and
+//! room / corridor dungeons, all producing plain grids written into a `Tilemap`.
+//!
+//! Every generator is deterministic for a given seed (`Rng` is self-contained, no platform
+//! randomness), so game code can regenerate the same level from a stored seed.
+
+mod caves;
+mod dungeon;
+mod noise;
+
+pub use caves::*;
+pub use dungeon::*;
+pub use noise::*;
+
+use std::ops::Range;
+
+use crate::{Fixed, Scalar, Tilemap};
+
+/// Small seeded pseudo-random generator (SplitMix64).
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        Self::mix(self.state)
+    }
+
+    /// Uniform in `0.0..1.0`.
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Uniform in `0..1` with 16 bits of precision, computed without floats.
+    pub fn next_fixed(&mut self) -> Fixed {
+        Fixed::from_bits((self.next_u64() >> (64 - Fixed::FRAC_BITS)) as i64)
+    }
+
+    /// Uniform in `0..1` as the simulation `Scalar` (`next_f32` or `next_fixed`, depending
+    /// on the `fixed-point` feature).
+    pub fn next_scalar(&mut self) -> Scalar {
+        #[cfg(feature = "fixed-point")]
+        return self.next_fixed();
+        #[cfg(not(feature = "fixed-point"))]
+        return self.next_f32();
+    }
+
+    /// Uniform in `range` (`range.start` when it is empty).
+    pub fn range(&mut self, range: Range<u32>) -> u32 {
+        if range.end <= range.start {
+            return range.start;
+        }
+        range.start + (self.next_u64() % u64::from(range.end - range.start)) as u32
+    }
+
+    /// `true` with probability `probability`.
+    pub fn chance(&mut self, probability: f32) -> bool {
+        self.next_f32() < probability
+    }
+
+    /// Hash of a seed and a cell, e.g. to give each chunk of an endless map its own `Rng`.
+    pub fn hash(seed: u64, x: i64, y: i64) -> u64 {
+        let mut hash = Self::mix(seed ^ 0x51_7CC1_B727_220A);
+        hash = Self::mix(hash ^ x as u64);
+        Self::mix(hash ^ (y as u64).rotate_left(32))
+    }
+
+    fn mix(mut z: u64) -> u64 {
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+/// Grid of wall / floor cells produced by `CaveGenerator` and `DungeonGenerator`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CellGrid {
+    width: u32,
+    height: u32,
+    walls: Vec<bool>,
+}
+
+impl CellGrid {
+    /// Grid filled with walls (`wall = true`) or floor.
+    pub fn new(width: u32, height: u32, wall: bool) -> Self {
+        Self {
+            width,
+            height,
+            walls: vec![wall; (width * height) as usize],
+        }
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// Whether (`x`, `y`) is a wall; cells outside the grid count as walls.
+    pub fn is_wall(&self, x: i64, y: i64) -> bool {
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+            return true;
+        }
+        self.walls[(y as u32 * self.width + x as u32) as usize]
+    }
+
+    pub fn set_wall(&mut self, x: u32, y: u32, wall: bool) {
+        if x < self.width && y < self.height {
+            self.walls[(y * self.width + x) as usize] = wall;
+        }
+    }
+
+    /// Number of walls among the 8 neighbours of (`x`, `y`).
+    pub fn wall_neighbours(&self, x: u32, y: u32) -> u32 {
+        let (x, y) = (x as i64, y as i64);
+        let mut count = 0;
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                if (dx, dy) != (0, 0) && self.is_wall(x + dx, y + dy) {
+                    count += 1;
+                }
+            }
+        }
+        count
+    }
+
+    pub fn floor_count(&self) -> usize {
+        self.walls.iter().filter(|&&wall| !wall).count()
+    }
+
+    /// Cells as tiles: `floor_gid` / `wall_gid` (`Tilemap::EMPTY` to leave a cell empty),
+    /// row by row, with their coordinates.
+    pub fn tiles(&self, floor_gid: u32, wall_gid: u32) -> impl Iterator<Item = (u32, u32, u32)> {
+        let width = self.width.max(1);
+        self.walls.iter().enumerate().map(move |(index, &wall)| {
+            let index = index as u32;
+            let gid = if wall { wall_gid } else { floor_gid };
+            (index % width, index / width, gid)
+        })
+    }
+
+    /// Write the grid into `layer` of `map` (see `tiles`), and with `collision` mark the
+    /// walls `Tilemap::COLLISION_SOLID` (and clear the floor). Cells outside the map are
+    /// skipped.
+    pub fn apply(
+        &self,
+        map: &mut Tilemap,
+        layer: usize,
+        floor_gid: u32,
+        wall_gid: u32,
+        collision: bool,
+    ) {
+        for ((x, y, gid), &wall) in self.tiles(floor_gid, wall_gid).zip(&self.walls) {
+            map.set_tile(layer, x, y, gid);
+            if collision {
+                map.set_collision(x, y, if wall { Tilemap::COLLISION_SOLID } else { 0 });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rng_is_deterministic() {
+        let (mut a, mut b) = (Rng::new(42), Rng::new(42));
+        let first: Vec<u64> = (0..8).map(|_| a.next_u64()).collect();
+        assert_eq!(first, (0..8).map(|_| b.next_u64()).collect::<Vec<_>>());
+        let mut other = Rng::new(43);
+        assert_ne!(first, (0..8).map(|_| other.next_u64()).collect::<Vec<_>>());
+
+        for _ in 0..1000 {
+            let value = a.next_f32();
+            assert!((0.0..1.0).contains(&value));
+            assert!((3..7).contains(&a.range(3..7)));
+        }
+        assert_eq!(a.range(5..5), 5);
+        assert_ne!(Rng::hash(1, 2, 3), Rng::hash(1, 3, 2));
+    }
+}
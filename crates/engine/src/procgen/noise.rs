@@ -0,0 +1,168 @@
+use super::Rng;
+use crate::Tilemap;
+
+/// Seeded 2D gradient noise (Perlin style): smooth values in about `-1.0..1.0`, varying
+/// over a distance of 1.
+#[derive(Debug, Clone, Copy)]
+pub struct Noise {
+    seed: u64,
+}
+
+impl Noise {
+    pub fn new(seed: u64) -> Self {
+        Self { seed }
+    }
+
+    pub fn get(&self, x: f32, y: f32) -> f32 {
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let (ix, iy) = (x0 as i64, y0 as i64);
+
+        let dot = |cx: i64, cy: i64, dx: f32, dy: f32| {
+            let angle = (Rng::hash(self.seed, cx, cy) >> 40) as f32 / (1u64 << 24) as f32
+                * std::f32::consts::TAU;
+            angle.cos() * dx + angle.sin() * dy
+        };
+        // Courbe de lissage 6t^5 - 15t^4 + 10t^3
+        let fade = |t: f32| t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+        let lerp = |a: f32, b: f32, t: f32| a + (b - a) * t;
+
+        let (u, v) = (fade(fx), fade(fy));
+        let top = lerp(dot(ix, iy, fx, fy), dot(ix + 1, iy, fx - 1.0, fy), u);
+        let bottom = lerp(
+            dot(ix, iy + 1, fx, fy - 1.0),
+            dot(ix + 1, iy + 1, fx - 1.0, fy - 1.0),
+            u,
+        );
+        // Gradients unitaires : l'amplitude max est ~0.707, ramenée vers 1
+        lerp(top, bottom, v) * std::f32::consts::SQRT_2
+    }
+
+    /// Fractal noise: `octaves` layers of noise, each `lacunarity` times finer and `gain`
+    /// times weaker than the previous one, normalized back to about `-1.0..1.0`.
+    pub fn fbm(&self, x: f32, y: f32, octaves: u32, lacunarity: f32, gain: f32) -> f32 {
+        let (mut sum, mut total) = (0.0, 0.0);
+        let (mut frequency, mut amplitude) = (1.0, 1.0);
+        for octave in 0..octaves.max(1) {
+            // Décalage par octave pour ne pas superposer les mêmes motifs à l'origine
+            let offset = octave as f32 * 17.31;
+            sum += self.get(x * frequency + offset, y * frequency - offset) * amplitude;
+            total += amplitude;
+            frequency *= lacunarity;
+            amplitude *= gain;
+        }
+        sum / total
+    }
+}
+
+/// Grid of heights in `0.0..=1.0`, e.g. for terrain elevation or moisture.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Heightmap {
+    width: u32,
+    height: u32,
+    values: Vec<f32>,
+}
+
+impl Heightmap {
+    /// Fractal noise sampled every cell, `scale` being the size of the features in cells,
+    /// then stretched to cover the whole `0..1` range.
+    pub fn generate(width: u32, height: u32, seed: u64, scale: f32, octaves: u32) -> Self {
+        let noise = Noise::new(seed);
+        let scale = scale.max(f32::EPSILON);
+        let mut values: Vec<f32> = (0..width * height)
+            .map(|index| {
+                let (x, y) = ((index % width) as f32, (index / width) as f32);
+                noise.fbm(x / scale, y / scale, octaves, 2.0, 0.5)
+            })
+            .collect();
+
+        let min = values.iter().copied().fold(f32::INFINITY, f32::min);
+        let max = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+        let range = (max - min).max(f32::EPSILON);
+        for value in &mut values {
+            *value = (*value - min) / range;
+        }
+        Self {
+            width,
+            height,
+            values,
+        }
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> Option<f32> {
+        (x < self.width && y < self.height).then(|| self.values[(y * self.width + x) as usize])
+    }
+
+    /// Gid of each cell (row by row, with coordinates) picked by `Biome::pick`, using
+    /// `moisture` (same size) as the second axis when given. Cells matching no biome get
+    /// `Tilemap::EMPTY`.
+    pub fn biome_tiles<'a>(
+        &'a self,
+        moisture: Option<&'a Heightmap>,
+        biomes: &'a [Biome],
+    ) -> impl Iterator<Item = (u32, u32, u32)> + 'a {
+        (0..self.width * self.height).map(move |index| {
+            let (x, y) = (index % self.width, index / self.width);
+            let height = self.values[index as usize];
+            let moisture = moisture.and_then(|m| m.get(x, y)).unwrap_or(0.0);
+            let gid = Biome::pick(biomes, height, moisture).map_or(Tilemap::EMPTY, |b| b.gid);
+            (x, y, gid)
+        })
+    }
+}
+
+/// Tile used where the height (and moisture) stay under the biome's limits.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Biome {
+    pub name: String,
+    pub max_height: f32,
+    pub max_moisture: f32,
+    pub gid: u32,
+}
+
+impl Biome {
+    /// Biome for heights up to `max_height`, whatever the moisture.
+    pub fn new(name: impl Into<String>, max_height: f32, gid: u32) -> Self {
+        Self {
+            name: name.into(),
+            max_height,
+            max_moisture: 1.0,
+            gid,
+        }
+    }
+
+    /// First biome of `biomes` (listed from the lowest / driest) whose limits contain
+    /// `height` and `moisture`.
+    pub fn pick(biomes: &[Biome], height: f32, moisture: f32) -> Option<&Biome> {
+        biomes
+            .iter()
+            .find(|biome| height <= biome.max_height && moisture <= biome.max_moisture)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn heightmap_is_smooth_and_normalized() {
+        let map = Heightmap::generate(64, 48, 7, 16.0, 4);
+        assert_eq!(map, Heightmap::generate(64, 48, 7, 16.0, 4));
+        let values: Vec<f32> = (0..48)
+            .flat_map(|y| (0..64).map(move |x| (x, y)))
+            .map(|(x, y)| map.get(x, y).unwrap())
+            .collect();
+        assert!(values.iter().all(|v| (0.0..=1.0).contains(v)));
+        assert!(values.contains(&0.0) && values.contains(&1.0));
+        // Cellules voisines proches à cette échelle
+        assert!((map.get(10, 10).unwrap() - map.get(11, 10).unwrap()).abs() < 0.2);
+
+        let biomes = [Biome::new("water", 0.4, 1), Biome::new("land", 1.0, 2)];
+        let tiles: Vec<u32> = map.biome_tiles(None, &biomes).map(|t| t.2).collect();
+        assert!(tiles.contains(&1) && tiles.contains(&2));
+    }
+}
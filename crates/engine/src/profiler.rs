@@ -0,0 +1,594 @@
+//! Profileur CPU intégré, sur `tracing`. Le code à mesurer ouvre un span
+//! (`let _span = tracing::trace_span!("scene_update").entered();`), avec au besoin un champ
+//! qui le précise (`trace_span!("pass", pass = name)`). Le layer du profileur
+//! (`Profiler::layer`, installé par `Profiler::install`) range chaque span terminé dans la
+//! frame en cours (`Profiler::new_frame`, appelé au début de chaque redraw) et garde les
+//! dernières frames ; le `ProfilerPanel` ne fait que les afficher : temps des frames, flame
+//! graph et scopes les plus coûteux.
+//!
+//! Le moteur mesure le redraw et ses étapes, la mise à jour de la scène, chaque passe de rendu
+//! et les chargements d'assets ; les spans des dépendances (winit...) sont enregistrés aussi.
+//! Le `FrameWatchdog` lit aussi les scopes de la frame en cours (`Profiler::current_scopes`) :
+//! il demande leur capture (`Profiler::set_capturing`) sans remplir l'historique.
+//! Ni actif ni en capture, le filtre du layer refuse les spans : le registre de
+//! `tracing_subscriber` les crée encore, mais le layer ne les mesure pas. Le filtre est propre
+//! au layer pour ne pas couper les spans des autres layers du jeu. L'imbrication est suivie par
+//! thread : un span entré ne doit pas traverser un `.await`.
+
+use std::{
+    cell::Cell,
+    collections::{BTreeMap, VecDeque},
+    fmt,
+    sync::{
+        Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use tracing::{
+    Metadata, Subscriber,
+    field::{Field, Visit},
+    span::{Attributes, Id},
+    subscriber::{Interest, SetGlobalDefaultError},
+};
+use tracing_subscriber::{
+    Layer,
+    layer::{Context, Filter, SubscriberExt},
+    registry::LookupSpan,
+};
+
+/// Scope (span `tracing`) terminé.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProfileRecord {
+    pub name: &'static str,
+    /// Précision sur le scope (premier champ du span : nom de la passe, chemin de l'asset...).
+    pub detail: Option<String>,
+    pub thread: String,
+    /// Niveau d'imbrication dans son thread (0 : scope de premier niveau).
+    pub depth: u32,
+    /// Début, depuis le début de la frame.
+    pub start: Duration,
+    pub duration: Duration,
+}
+
+impl ProfileRecord {
+    /// `name` suivi du détail, s'il y en a un.
+    pub fn label(&self) -> String {
+        match &self.detail {
+            Some(detail) => format!("{}: {}", self.name, detail),
+            None => self.name.to_owned(),
+        }
+    }
+}
+
+/// Frame enregistrée par le profileur.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProfiledFrame {
+    /// Numéro de la frame depuis l'activation du profileur.
+    pub index: u64,
+    pub duration: Duration,
+    /// Scopes de la frame, par ordre de début.
+    pub scopes: Vec<ProfileRecord>,
+}
+
+/// Scope terminé, en attente de la fin de sa frame.
+#[derive(Clone)]
+struct PendingScope {
+    record: ProfileRecord,
+    started: Instant,
+}
+
+struct ProfilerState {
+    frame: u64,
+    frame_start: Option<Instant>,
+    scopes: Vec<PendingScope>,
+    history: VecDeque<ProfiledFrame>,
+}
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static CAPTURING: AtomicBool = AtomicBool::new(false);
+static STATE: Mutex<ProfilerState> = Mutex::new(ProfilerState {
+    frame: 0,
+    frame_start: None,
+    scopes: Vec::new(),
+    history: VecDeque::new(),
+});
+
+/// L'état du profileur est global : les tests qui l'utilisent passent l'un après l'autre.
+#[cfg(test)]
+pub(crate) static TEST_LOCK: Mutex<()> = Mutex::new(());
+
+thread_local! {
+    static DEPTH: Cell<u32> = const { Cell::new(0) };
+}
+
+/// Extension d'un span entré : début et niveau d'imbrication.
+struct Entered {
+    started: Instant,
+    depth: u32,
+}
+
+/// Extension d'un span qui a des champs : la valeur du premier.
+struct Detail(String);
+
+/// Garde la valeur du premier champ d'un span.
+#[derive(Default)]
+struct DetailVisitor(Option<String>);
+
+impl Visit for DetailVisitor {
+    fn record_str(&mut self, _field: &Field, value: &str) {
+        self.0.get_or_insert_with(|| value.to_owned());
+    }
+
+    fn record_debug(&mut self, _field: &Field, value: &dyn fmt::Debug) {
+        self.0.get_or_insert_with(|| format!("{:?}", value));
+    }
+}
+
+/// Layer `tracing` qui enregistre les spans dans la frame en cours.
+struct ProfilerLayer;
+
+impl<S> Layer<S> for ProfilerLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut visitor = DetailVisitor::default();
+        attrs.record(&mut visitor);
+        if let Some(detail) = visitor.0
+            && let Some(span) = ctx.span(id)
+        {
+            span.extensions_mut().insert(Detail(detail));
+        }
+    }
+
+    fn on_enter(&self, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let depth = DEPTH.with(|depth| depth.replace(depth.get() + 1));
+        span.extensions_mut().replace(Entered {
+            started: Instant::now(),
+            depth,
+        });
+    }
+
+    fn on_exit(&self, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let Some(entered) = span.extensions_mut().remove::<Entered>() else {
+            return;
+        };
+        DEPTH.with(|cell| cell.set(entered.depth));
+        if !Profiler::is_recording() {
+            return;
+        }
+        let thread = std::thread::current();
+        let record = ProfileRecord {
+            name: span.name(),
+            detail: span
+                .extensions()
+                .get::<Detail>()
+                .map(|detail| detail.0.clone()),
+            thread: thread.name().unwrap_or("unnamed").to_owned(),
+            depth: entered.depth,
+            start: Duration::ZERO,
+            duration: entered.started.elapsed(),
+        };
+        let mut state = STATE.lock().unwrap();
+        if state.scopes.len() < Profiler::MAX_SCOPES_PER_FRAME {
+            state.scopes.push(PendingScope {
+                record,
+                started: entered.started,
+            });
+        }
+    }
+}
+
+/// Filtre du layer : les spans, seulement quand le profileur est actif ou en capture. Décidé à
+/// chaque span (`Interest::sometimes`), puisque le profileur peut être activé à tout moment.
+struct ProfilerFilter;
+
+impl<S> Filter<S> for ProfilerFilter {
+    fn enabled(&self, metadata: &Metadata<'_>, _: &Context<'_, S>) -> bool {
+        metadata.is_span() && Profiler::is_recording()
+    }
+
+    fn callsite_enabled(&self, metadata: &'static Metadata<'static>) -> Interest {
+        match metadata.is_span() {
+            true => Interest::sometimes(),
+            false => Interest::never(),
+        }
+    }
+}
+
+/// Contrôle du profileur (état global, partagé par tous les threads).
+pub struct Profiler;
+
+impl Profiler {
+    /// Nombre de frames gardées.
+    pub const HISTORY: usize = 300;
+    /// Au-delà, les scopes de la frame sont ignorés.
+    pub const MAX_SCOPES_PER_FRAME: usize = 4096;
+
+    /// Layer `tracing` du profileur, à ajouter au subscriber du jeu s'il en installe un.
+    pub fn layer<S>() -> impl Layer<S>
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+    {
+        ProfilerLayer.with_filter(ProfilerFilter)
+    }
+
+    /// Installe un subscriber global réduit au layer du profileur. Échoue si un subscriber
+    /// est déjà installé (le jeu y ajoute alors `Profiler::layer`).
+    pub fn install() -> Result<(), SetGlobalDefaultError> {
+        tracing::subscriber::set_global_default(tracing_subscriber::registry().with(Self::layer()))
+    }
+
+    pub fn is_enabled() -> bool {
+        ENABLED.load(Ordering::Relaxed)
+    }
+
+    /// Active ou coupe l'enregistrement. Les frames déjà enregistrées restent consultables.
+    pub fn set_enabled(enabled: bool) {
+        if ENABLED.swap(enabled, Ordering::Relaxed) == enabled {
+            return;
+        }
+        let mut state = STATE.lock().unwrap();
+        state.frame_start = None;
+        state.scopes.clear();
+    }
+
+    /// Capture les scopes de la frame en cours même profileur coupé, sans les garder dans
+    /// l'historique (`current_scopes`).
+    pub fn set_capturing(capturing: bool) {
+        CAPTURING.store(capturing, Ordering::Relaxed);
+    }
+
+    /// Actif ou en capture : le layer mesure les spans.
+    fn is_recording() -> bool {
+        Self::is_enabled() || CAPTURING.load(Ordering::Relaxed)
+    }
+
+    /// Termine la frame en cours (ses scopes passent dans l'historique si le profileur est
+    /// actif) et en commence une.
+    pub fn new_frame() {
+        if !Self::is_recording() {
+            return;
+        }
+        let now = Instant::now();
+        let mut state = STATE.lock().unwrap();
+        let scopes = std::mem::take(&mut state.scopes);
+        if let Some(frame_start) = state.frame_start.replace(now)
+            && Self::is_enabled()
+        {
+            let frame = ProfiledFrame {
+                index: state.frame,
+                duration: now - frame_start,
+                scopes: Self::records(scopes, frame_start),
+            };
+            if state.history.len() == Self::HISTORY {
+                state.history.pop_front();
+            }
+            state.history.push_back(frame);
+        }
+        state.frame += 1;
+    }
+
+    /// Scopes déjà terminés de la frame en cours, par ordre de début.
+    pub fn current_scopes() -> Vec<ProfileRecord> {
+        let state = STATE.lock().unwrap();
+        let Some(frame_start) = state.frame_start else {
+            return Vec::new();
+        };
+        Self::records(state.scopes.clone(), frame_start)
+    }
+
+    fn records(scopes: Vec<PendingScope>, frame_start: Instant) -> Vec<ProfileRecord> {
+        let mut records: Vec<ProfileRecord> = scopes
+            .into_iter()
+            .map(|scope| ProfileRecord {
+                start: scope.started.saturating_duration_since(frame_start),
+                ..scope.record
+            })
+            .collect();
+        records.sort_by_key(|record| (record.start, record.depth));
+        records
+    }
+
+    /// Numéro et durée des frames de l'historique, de la plus ancienne à la plus récente.
+    pub fn frame_durations() -> Vec<(u64, Duration)> {
+        let state = STATE.lock().unwrap();
+        state
+            .history
+            .iter()
+            .map(|frame| (frame.index, frame.duration))
+            .collect()
+    }
+
+    /// Frame `index`, si elle est encore dans l'historique.
+    pub fn frame(index: u64) -> Option<ProfiledFrame> {
+        let state = STATE.lock().unwrap();
+        state
+            .history
+            .iter()
+            .find(|frame| frame.index == index)
+            .cloned()
+    }
+
+    /// Dernière frame terminée.
+    pub fn latest_frame() -> Option<ProfiledFrame> {
+        STATE.lock().unwrap().history.back().cloned()
+    }
+
+    /// Vide l'historique.
+    pub fn clear() {
+        STATE.lock().unwrap().history.clear();
+    }
+}
+
+/// Temps cumulé d'un scope (même nom et même détail) dans une frame.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScopeTotal {
+    pub label: String,
+    pub total: Duration,
+    pub max: Duration,
+    pub count: u32,
+}
+
+impl ProfiledFrame {
+    /// Temps cumulé de chaque scope, les plus coûteux d'abord.
+    pub fn totals(&self) -> Vec<ScopeTotal> {
+        let mut totals: BTreeMap<String, ScopeTotal> = BTreeMap::new();
+        for scope in &self.scopes {
+            let label = scope.label();
+            let total = totals.entry(label.clone()).or_insert(ScopeTotal {
+                label,
+                total: Duration::ZERO,
+                max: Duration::ZERO,
+                count: 0,
+            });
+            total.total += scope.duration;
+            total.max = total.max.max(scope.duration);
+            total.count += 1;
+        }
+        let mut totals: Vec<ScopeTotal> = totals.into_values().collect();
+        totals.sort_by_key(|total| std::cmp::Reverse(total.total));
+        totals
+    }
+}
+
+/// Fenêtre du profileur : temps des frames (cliquer une barre pour figer cette frame),
+/// flame graph et scopes les plus coûteux de la frame affichée.
+#[derive(Default)]
+pub struct ProfilerPanel {
+    /// Frame figée ; `None` : suit la dernière frame.
+    selected: Option<u64>,
+}
+
+impl ProfilerPanel {
+    /// Hauteur d'une ligne du flame graph.
+    const ROW_HEIGHT: f32 = 18.0;
+    const TOP_SCOPES: usize = 20;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        let ms = |duration: Duration| duration.as_secs_f32() * 1000.0;
+
+        ui.horizontal(|ui| {
+            let mut enabled = Profiler::is_enabled();
+            if ui.checkbox(&mut enabled, "Record").changed() {
+                Profiler::set_enabled(enabled);
+            }
+            if ui.button("Clear").clicked() {
+                Profiler::clear();
+                self.selected = None;
+            }
+            if self.selected.is_some() && ui.button("Live").clicked() {
+                self.selected = None;
+            }
+        });
+
+        self.frames_ui(ui, &Profiler::frame_durations());
+
+        let frame = match self.selected {
+            Some(index) => Profiler::frame(index),
+            None => Profiler::latest_frame(),
+        };
+        let Some(frame) = frame else {
+            ui.label("No frame recorded.");
+            return;
+        };
+        ui.label(format!(
+            "Frame {}: {:.2} ms, {} scopes",
+            frame.index,
+            ms(frame.duration),
+            frame.scopes.len()
+        ));
+        ui.separator();
+        Self::flame_graph_ui(ui, &frame);
+        ui.separator();
+
+        egui::Grid::new("profiler_top_scopes")
+            .num_columns(4)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.strong("Scope");
+                ui.strong("Total");
+                ui.strong("Max");
+                ui.strong("Count");
+                ui.end_row();
+                for total in frame.totals().into_iter().take(Self::TOP_SCOPES) {
+                    ui.label(total.label);
+                    ui.monospace(format!("{:.3} ms", ms(total.total)));
+                    ui.monospace(format!("{:.3} ms", ms(total.max)));
+                    ui.label(total.count.to_string());
+                    ui.end_row();
+                }
+            });
+    }
+
+    /// Barres des temps de frame ; un clic fige la frame.
+    fn frames_ui(&mut self, ui: &mut egui::Ui, frames: &[(u64, Duration)]) {
+        let (response, painter) =
+            ui.allocate_painter(egui::vec2(ui.available_width(), 60.0), egui::Sense::click());
+        let rect = response.rect;
+        painter.rect_filled(rect, 2.0, egui::Color32::from_black_alpha(120));
+        if frames.is_empty() {
+            return;
+        }
+
+        // Échelle : au moins 33 ms (30 FPS)
+        let max = frames
+            .iter()
+            .map(|(_, duration)| duration.as_secs_f32() * 1000.0)
+            .fold(33.3, f32::max);
+        let step = rect.width() / Profiler::HISTORY as f32;
+        for (i, (index, duration)) in frames.iter().enumerate() {
+            let height = (duration.as_secs_f32() * 1000.0 / max) * rect.height();
+            let bar = egui::Rect::from_min_max(
+                egui::pos2(rect.left() + i as f32 * step, rect.bottom() - height),
+                egui::pos2(rect.left() + (i + 1) as f32 * step, rect.bottom()),
+            );
+            let color = match self.selected == Some(*index) {
+                true => egui::Color32::WHITE,
+                false if duration.as_secs_f32() > 1.0 / 60.0 => egui::Color32::LIGHT_RED,
+                false => egui::Color32::LIGHT_GREEN,
+            };
+            painter.rect_filled(
+                bar.shrink2(egui::vec2(step.min(2.0) * 0.25, 0.0)),
+                0.0,
+                color,
+            );
+        }
+
+        if response.clicked()
+            && let Some(pos) = response.interact_pointer_pos()
+        {
+            let i = ((pos.x - rect.left()) / step) as usize;
+            self.selected = frames.get(i).map(|(index, _)| *index);
+        }
+    }
+
+    /// Scopes de la frame sur l'axe du temps, un groupe de lignes par thread et une ligne par
+    /// niveau d'imbrication.
+    fn flame_graph_ui(ui: &mut egui::Ui, frame: &ProfiledFrame) {
+        let mut threads: Vec<(&str, u32)> = Vec::new();
+        for scope in &frame.scopes {
+            match threads.iter_mut().find(|(name, _)| *name == scope.thread) {
+                Some((_, depth)) => *depth = (*depth).max(scope.depth),
+                None => threads.push((&scope.thread, scope.depth)),
+            }
+        }
+        let rows: u32 = threads.iter().map(|(_, depth)| depth + 1).sum();
+        let (response, painter) = ui.allocate_painter(
+            egui::vec2(ui.available_width(), rows.max(1) as f32 * Self::ROW_HEIGHT),
+            egui::Sense::hover(),
+        );
+        let rect = response.rect;
+        let scale = rect.width() / frame.duration.as_secs_f32().max(1e-6);
+        let font = egui::FontId::monospace(11.0);
+        let mut hovered = None;
+
+        for scope in &frame.scopes {
+            let row: u32 = threads
+                .iter()
+                .take_while(|(name, _)| *name != scope.thread)
+                .map(|(_, depth)| depth + 1)
+                .sum::<u32>()
+                + scope.depth;
+            let left = rect.left() + scope.start.as_secs_f32() * scale;
+            let width = (scope.duration.as_secs_f32() * scale).max(1.0);
+            let top = rect.top() + row as f32 * Self::ROW_HEIGHT;
+            let bar = egui::Rect::from_min_size(
+                egui::pos2(left, top),
+                egui::vec2(width.min(rect.right() - left), Self::ROW_HEIGHT - 1.0),
+            );
+            // Couleur stable par nom de scope
+            let hash = scope
+                .name
+                .bytes()
+                .fold(0u32, |h, b| h.wrapping_mul(31) + b as u32);
+            let color = egui::Color32::from_rgb(
+                100 + (hash % 120) as u8,
+                100 + (hash / 120 % 120) as u8,
+                160,
+            );
+            painter.rect_filled(bar, 1.0, color);
+            if bar.width() > 40.0 {
+                painter.with_clip_rect(bar).text(
+                    bar.left_center() + egui::vec2(3.0, 0.0),
+                    egui::Align2::LEFT_CENTER,
+                    scope.label(),
+                    font.clone(),
+                    egui::Color32::BLACK,
+                );
+            }
+            if response.hover_pos().is_some_and(|pos| bar.contains(pos)) {
+                hovered = Some(scope);
+            }
+        }
+
+        if let Some(scope) = hovered {
+            response.on_hover_text(format!(
+                "{} ({})\n{:.3} ms at {:.3} ms",
+                scope.label(),
+                scope.thread,
+                scope.duration.as_secs_f64() * 1000.0,
+                scope.start.as_secs_f64() * 1000.0
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nested_spans_are_recorded_per_frame() {
+        let _lock = TEST_LOCK.lock().unwrap();
+        let subscriber = tracing_subscriber::registry().with(Profiler::layer());
+        tracing::subscriber::with_default(subscriber, || {
+            Profiler::set_enabled(true);
+            Profiler::new_frame();
+            {
+                let _outer = tracing::trace_span!("outer").entered();
+                let _inner = tracing::trace_span!("inner", asset = "detail").entered();
+            }
+            Profiler::new_frame();
+
+            // D'autres tests peuvent charger des assets en parallèle : ne garder que ce thread
+            let thread = std::thread::current().name().unwrap().to_owned();
+            let frame = Profiler::latest_frame().unwrap();
+            let scopes: Vec<&ProfileRecord> = frame
+                .scopes
+                .iter()
+                .filter(|scope| scope.thread == thread)
+                .collect();
+            assert_eq!(scopes.len(), 2);
+            assert_eq!((scopes[0].name, scopes[0].depth), ("outer", 0));
+            assert_eq!(
+                (scopes[1].label().as_str(), scopes[1].depth),
+                ("inner: detail", 1)
+            );
+            assert!(scopes[0].duration >= scopes[1].duration);
+            assert!(frame.totals().iter().any(|total| total.label == "outer"));
+
+            Profiler::set_enabled(false);
+            drop(tracing::trace_span!("ignored").entered());
+            let state = STATE.lock().unwrap();
+            assert!(
+                state
+                    .scopes
+                    .iter()
+                    .all(|scope| scope.record.name != "ignored")
+            );
+        });
+    }
+}
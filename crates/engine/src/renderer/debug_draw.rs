@@ -0,0 +1,489 @@
+//! Dessin immédiat de formes de debug (lignes, rectangles, cercles, polygones) en
+//! coordonnées monde : boîtes de collision, chemins, gizmos de l'éditeur...
+//!
+//! Le code de jeu pousse des `DebugShape` dans un `DebugDraw` (clonable, partageable entre
+//! threads) ; la `ShapePass` les dessine par-dessus la scène. Une forme sans durée de vie
+//! n'est dessinée qu'une frame, les autres restent jusqu'à expiration.
+
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use bytemuck::{Pod, Zeroable};
+use egui_wgpu::wgpu;
+use wgpu::util::DeviceExt;
+
+use crate::{
+    AssetLoader, PassContext, RenderPass, Shader, Uniforms, Vec2, catch_validation_errors,
+};
+
+/// Géométrie d'une `DebugShape`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ShapeKind {
+    Line {
+        from: Vec2,
+        to: Vec2,
+    },
+    Rect {
+        min: Vec2,
+        max: Vec2,
+    },
+    Circle {
+        center: Vec2,
+        radius: f32,
+    },
+    /// Polygone fermé ; rempli en éventail, il doit donc être convexe.
+    Polygon {
+        points: Vec<Vec2>,
+    },
+}
+
+/// Une forme à dessiner, construite avec `DebugShape::line`, `rect`... puis configurée
+/// (`color`, `thickness`, `filled`, `lifetime`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct DebugShape {
+    pub kind: ShapeKind,
+    pub color: [f32; 4],
+    /// Épaisseur des contours, en unités monde.
+    pub thickness: f32,
+    /// Remplie (ignoré pour les lignes).
+    pub filled: bool,
+    /// Durée d'affichage ; `None` = une seule frame.
+    pub lifetime: Option<Duration>,
+}
+
+impl DebugShape {
+    /// Nombre de segments des cercles.
+    pub const CIRCLE_SEGMENTS: usize = 32;
+
+    fn new(kind: ShapeKind) -> Self {
+        Self {
+            kind,
+            color: [0.0, 1.0, 0.0, 1.0],
+            thickness: 1.0,
+            filled: false,
+            lifetime: None,
+        }
+    }
+
+    pub fn line(from: Vec2, to: Vec2) -> Self {
+        Self::new(ShapeKind::Line { from, to })
+    }
+
+    pub fn rect(min: Vec2, max: Vec2) -> Self {
+        Self::new(ShapeKind::Rect { min, max })
+    }
+
+    pub fn circle(center: Vec2, radius: f32) -> Self {
+        Self::new(ShapeKind::Circle { center, radius })
+    }
+
+    pub fn polygon(points: Vec<Vec2>) -> Self {
+        Self::new(ShapeKind::Polygon { points })
+    }
+
+    pub fn color(mut self, color: [f32; 4]) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn thickness(mut self, thickness: f32) -> Self {
+        self.thickness = thickness;
+        self
+    }
+
+    pub fn filled(mut self) -> Self {
+        self.filled = true;
+        self
+    }
+
+    /// Garder la forme affichée pendant `seconds` secondes.
+    pub fn lifetime(mut self, seconds: f32) -> Self {
+        self.lifetime = Some(Duration::from_secs_f32(seconds.max(0.0)));
+        self
+    }
+
+    /// Contour de la forme (points successifs, fermé sauf pour une ligne).
+    fn outline(&self) -> (Vec<Vec2>, bool) {
+        match &self.kind {
+            ShapeKind::Line { from, to } => (vec![*from, *to], false),
+            ShapeKind::Rect { min, max } => (
+                vec![*min, Vec2::new(max.x, min.y), *max, Vec2::new(min.x, max.y)],
+                true,
+            ),
+            ShapeKind::Circle { center, radius } => {
+                let points = (0..Self::CIRCLE_SEGMENTS)
+                    .map(|i| {
+                        let angle = i as f32 / Self::CIRCLE_SEGMENTS as f32 * std::f32::consts::TAU;
+                        center + Vec2::new(angle.cos(), angle.sin()) * *radius
+                    })
+                    .collect();
+                (points, true)
+            }
+            ShapeKind::Polygon { points } => (points.clone(), true),
+        }
+    }
+
+    /// Triangles de la forme (3 sommets par triangle), ajoutés à `out`.
+    pub fn tessellate(&self, out: &mut Vec<ShapeVertex>) {
+        let (points, closed) = self.outline();
+        let vertex = |position: Vec2| ShapeVertex {
+            position: position.into(),
+            color: self.color,
+        };
+
+        if self.filled && closed {
+            for i in 1..points.len().saturating_sub(1) {
+                out.extend([points[0], points[i], points[i + 1]].map(vertex));
+            }
+            return;
+        }
+
+        let segments = if closed {
+            points.len()
+        } else {
+            points.len().saturating_sub(1)
+        };
+        let half = self.thickness * 0.5;
+        for i in 0..segments {
+            let (a, b) = (points[i], points[(i + 1) % points.len()]);
+            let direction = b - a;
+            let Some(direction) = direction.try_normalize(f32::EPSILON) else {
+                continue;
+            };
+            // Prolongé d'une demi-épaisseur pour que les coins se rejoignent
+            let (a, b) = (a - direction * half, b + direction * half);
+            let normal = Vec2::new(-direction.y, direction.x) * half;
+            let quad = [a + normal, b + normal, b - normal, a - normal];
+            out.extend([quad[0], quad[1], quad[2], quad[0], quad[2], quad[3]].map(vertex));
+        }
+    }
+}
+
+#[derive(Debug)]
+struct QueuedShape {
+    shape: DebugShape,
+    /// `None` = retirée après la prochaine frame.
+    expires: Option<Instant>,
+}
+
+/// File de formes de debug partagée : les clones d'un `DebugDraw` poussent dans la même
+/// file, dessinée par la `ShapePass` créée avec.
+#[derive(Debug, Clone, Default)]
+pub struct DebugDraw {
+    shapes: Arc<Mutex<Vec<QueuedShape>>>,
+}
+
+impl DebugDraw {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&self, shape: DebugShape) {
+        let expires = shape.lifetime.map(|lifetime| Instant::now() + lifetime);
+        self.shapes
+            .lock()
+            .unwrap()
+            .push(QueuedShape { shape, expires });
+    }
+
+    pub fn line(&self, from: Vec2, to: Vec2, color: [f32; 4]) {
+        self.add(DebugShape::line(from, to).color(color));
+    }
+
+    pub fn rect(&self, min: Vec2, max: Vec2, color: [f32; 4]) {
+        self.add(DebugShape::rect(min, max).color(color));
+    }
+
+    pub fn circle(&self, center: Vec2, radius: f32, color: [f32; 4]) {
+        self.add(DebugShape::circle(center, radius).color(color));
+    }
+
+    pub fn polygon(&self, points: Vec<Vec2>, color: [f32; 4]) {
+        self.add(DebugShape::polygon(points).color(color));
+    }
+
+    /// Nombre de formes en attente.
+    pub fn len(&self) -> usize {
+        self.shapes.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&self) {
+        self.shapes.lock().unwrap().clear();
+    }
+
+    /// Sommets des formes à dessiner à l'instant `now`, puis retire les formes d'une frame
+    /// et celles expirées.
+    pub fn take_frame(&self, now: Instant) -> Vec<ShapeVertex> {
+        let mut shapes = self.shapes.lock().unwrap();
+        shapes.retain(|queued| queued.expires.is_none_or(|expires| expires > now));
+        let mut vertices = Vec::new();
+        for queued in shapes.iter() {
+            queued.shape.tessellate(&mut vertices);
+        }
+        shapes.retain(|queued| queued.expires.is_some());
+        vertices
+    }
+}
+
+/// Sommet d'une forme tessellée.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable)]
+pub struct ShapeVertex {
+    pub position: [f32; 2],
+    pub color: [f32; 4],
+}
+
+impl ShapeVertex {
+    pub fn layout<'a>() -> wgpu::VertexBufferLayout<'a> {
+        const ATTRIBUTES: [wgpu::VertexAttribute; 2] =
+            wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x4];
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<ShapeVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &ATTRIBUTES,
+        }
+    }
+}
+
+/// Passe dessinant les formes d'un `DebugDraw` par-dessus la scène (après les sprites,
+/// avant l'UI egui).
+pub struct ShapePass {
+    debug: DebugDraw,
+    pipeline: wgpu::RenderPipeline,
+    /// Gardés pour recréer le pipeline quand le shader est rechargé.
+    pipeline_layout: wgpu::PipelineLayout,
+    target_format: wgpu::TextureFormat,
+    uniform_buffer: wgpu::Buffer,
+    uniform_bind_group: wgpu::BindGroup,
+    /// Buffer de sommets réutilisé tant qu'il est assez grand.
+    vertex_buffer: Option<wgpu::Buffer>,
+}
+
+impl ShapePass {
+    pub const SHADER_PATH: &str = "engine/shaders/shape.wgsl";
+
+    pub fn new(
+        device: &wgpu::Device,
+        target_format: wgpu::TextureFormat,
+        loader: &AssetLoader,
+        debug: DebugDraw,
+    ) -> Result<Self> {
+        let shader = loader.load_shader_or_fallback(Self::SHADER_PATH, device);
+
+        let uniform_bind_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("shape_uniform_bind_group_layout"),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+            });
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("shape_uniform_buffer"),
+            size: std::mem::size_of::<Uniforms>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let uniform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("shape_uniform_bind_group"),
+            layout: &uniform_bind_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("shape_pipeline_layout"),
+            bind_group_layouts: &[&uniform_bind_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = Self::create_pipeline(device, &shader, &pipeline_layout, target_format);
+
+        Ok(Self {
+            debug,
+            pipeline,
+            pipeline_layout,
+            target_format,
+            uniform_buffer,
+            uniform_bind_group,
+            vertex_buffer: None,
+        })
+    }
+
+    fn create_pipeline(
+        device: &wgpu::Device,
+        shader: &Shader,
+        pipeline_layout: &wgpu::PipelineLayout,
+        target_format: wgpu::TextureFormat,
+    ) -> wgpu::RenderPipeline {
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("shape_pipeline"),
+            layout: Some(pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: shader.module(),
+                entry_point: Some("vs_main"),
+                buffers: &[ShapeVertex::layout()],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: shader.module(),
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: target_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        })
+    }
+
+    /// File dessinée par la passe (à cloner pour dessiner depuis le code de jeu).
+    pub fn debug_draw(&self) -> &DebugDraw {
+        &self.debug
+    }
+}
+
+impl RenderPass for ShapePass {
+    fn name(&self) -> &str {
+        "shape_pass"
+    }
+
+    fn after(&self) -> &[&str] {
+        &["sprite_pass"]
+    }
+
+    fn before(&self) -> &[&str] {
+        &["egui_pass"]
+    }
+
+    fn reload_shader(
+        &mut self,
+        path: &str,
+        device: &wgpu::Device,
+        loader: &AssetLoader,
+    ) -> Result<bool> {
+        if path != Self::SHADER_PATH {
+            return Ok(false);
+        }
+        let shader = loader.load_shader(path, device)?;
+        self.pipeline = catch_validation_errors(device, || {
+            Self::create_pipeline(device, &shader, &self.pipeline_layout, self.target_format)
+        })?;
+        Ok(true)
+    }
+
+    fn execute(&mut self, ctx: &mut PassContext) {
+        let vertices = self.debug.take_frame(Instant::now());
+        if vertices.is_empty() {
+            return;
+        }
+
+        let uniforms = Uniforms {
+            model_view_proj: ctx.camera.view_projection_matrix().into(),
+        };
+        ctx.queue
+            .write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniforms]));
+
+        let bytes: &[u8] = bytemuck::cast_slice(&vertices);
+        match &self.vertex_buffer {
+            Some(buffer) if buffer.size() >= bytes.len() as wgpu::BufferAddress => {
+                ctx.queue.write_buffer(buffer, 0, bytes);
+            }
+            _ => {
+                self.vertex_buffer = Some(ctx.window_state.device.create_buffer_init(
+                    &wgpu::util::BufferInitDescriptor {
+                        label: Some("shape_vertices"),
+                        contents: bytes,
+                        usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+                    },
+                ));
+            }
+        }
+        let Some(buffer) = &self.vertex_buffer else {
+            return;
+        };
+
+        let mut rpass = ctx.encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("shape_render_pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: ctx.target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            occlusion_query_set: None,
+            timestamp_writes: None,
+        });
+        rpass.set_pipeline(&self.pipeline);
+        rpass.set_bind_group(0, &self.uniform_bind_group, &[]);
+        rpass.set_vertex_buffer(0, buffer.slice(..bytes.len() as wgpu::BufferAddress));
+        rpass.draw(0..vertices.len() as u32, 0..1);
+        ctx.window_state.frame_stats.record_draw(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tessellates_outlines_and_fills() {
+        let mut vertices = Vec::new();
+        DebugShape::line(Vec2::new(0.0, 0.0), Vec2::new(10.0, 0.0))
+            .thickness(2.0)
+            .tessellate(&mut vertices);
+        assert_eq!(vertices.len(), 6);
+        // Ligne prolongée d'une demi-épaisseur à chaque bout
+        let xs: Vec<f32> = vertices.iter().map(|v| v.position[0]).collect();
+        assert_eq!(xs.iter().cloned().fold(f32::INFINITY, f32::min), -1.0);
+        assert_eq!(xs.iter().cloned().fold(f32::NEG_INFINITY, f32::max), 11.0);
+
+        vertices.clear();
+        DebugShape::rect(Vec2::new(0.0, 0.0), Vec2::new(4.0, 4.0)).tessellate(&mut vertices);
+        assert_eq!(vertices.len(), 4 * 6);
+
+        vertices.clear();
+        DebugShape::circle(Vec2::zeros(), 5.0)
+            .filled()
+            .tessellate(&mut vertices);
+        assert_eq!(vertices.len(), (DebugShape::CIRCLE_SEGMENTS - 2) * 3);
+    }
+
+    #[test]
+    fn one_frame_shapes_are_dropped_after_drawing() {
+        let debug = DebugDraw::new();
+        let now = Instant::now();
+        debug.line(Vec2::zeros(), Vec2::new(1.0, 1.0), [1.0; 4]);
+        debug.add(DebugShape::circle(Vec2::zeros(), 1.0).lifetime(1.0));
+        assert_eq!(debug.clone().len(), 2);
+
+        assert!(!debug.take_frame(now).is_empty());
+        assert_eq!(debug.len(), 1);
+        assert!(!debug.take_frame(now).is_empty());
+        assert!(debug.take_frame(now + Duration::from_secs(2)).is_empty());
+        assert!(debug.is_empty());
+    }
+}
@@ -0,0 +1,153 @@
+//! Ordonnancement des passes (mini render graph).
+//!
+//! Chaque passe peut déclarer :
+//! - des contraintes nommées : `before("egui_pass")`, `after("sprite_pass")` ;
+//! - des attachments lus / écrits : une passe qui lit un attachment s'exécute après
+//!   les passes qui l'écrivent (sans le lire elles-mêmes).
+//!
+//! L'ordre résultant est un tri topologique stable : à contraintes égales, l'ordre
+//! d'insertion est conservé. Les noms inconnus sont ignorés (la passe peut être ajoutée plus tard).
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// Description d'une passe pour le calcul d'ordre.
+#[derive(Debug, Clone, Default)]
+pub struct PassNode {
+    pub name: String,
+    pub before: Vec<String>,
+    pub after: Vec<String>,
+    pub reads: Vec<String>,
+    pub writes: Vec<String>,
+}
+
+/// Calcule l'ordre d'exécution (indices dans `nodes`).
+/// En cas de cycle, retourne en erreur les noms des passes impliquées.
+pub fn resolve_pass_order(nodes: &[PassNode]) -> Result<Vec<usize>, Vec<String>> {
+    let count = nodes.len();
+    let mut edges: Vec<Vec<usize>> = vec![Vec::new(); count];
+    let mut in_degree = vec![0usize; count];
+
+    let mut add_edge = |from: usize, to: usize| {
+        if from != to && !edges[from].contains(&to) {
+            edges[from].push(to);
+            in_degree[to] += 1;
+        }
+    };
+
+    let indices_named = |name: &str| -> Vec<usize> {
+        nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, n)| n.name == name)
+            .map(|(i, _)| i)
+            .collect()
+    };
+
+    for (i, node) in nodes.iter().enumerate() {
+        for name in &node.before {
+            for j in indices_named(name) {
+                add_edge(i, j);
+            }
+        }
+        for name in &node.after {
+            for j in indices_named(name) {
+                add_edge(j, i);
+            }
+        }
+        // Producteurs -> consommateurs
+        for attachment in &node.reads {
+            for (j, writer) in nodes.iter().enumerate() {
+                if writer.writes.contains(attachment) && !writer.reads.contains(attachment) {
+                    add_edge(j, i);
+                }
+            }
+        }
+    }
+
+    // Kahn avec une file de priorité sur l'indice d'insertion (tri stable)
+    let mut ready: BinaryHeap<Reverse<usize>> = (0..count)
+        .filter(|&i| in_degree[i] == 0)
+        .map(Reverse)
+        .collect();
+    let mut order = Vec::with_capacity(count);
+
+    while let Some(Reverse(i)) = ready.pop() {
+        order.push(i);
+        for &j in &edges[i] {
+            in_degree[j] -= 1;
+            if in_degree[j] == 0 {
+                ready.push(Reverse(j));
+            }
+        }
+    }
+
+    if order.len() != count {
+        let cyclic = (0..count)
+            .filter(|&i| in_degree[i] > 0)
+            .map(|i| nodes[i].name.clone())
+            .collect();
+        return Err(cyclic);
+    }
+
+    Ok(order)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(name: &str) -> PassNode {
+        PassNode {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn names(nodes: &[PassNode], order: &[usize]) -> Vec<String> {
+        order.iter().map(|&i| nodes[i].name.clone()).collect()
+    }
+
+    #[test]
+    fn keeps_insertion_order_without_constraints() {
+        let nodes = vec![node("a"), node("b"), node("c")];
+        let order = resolve_pass_order(&nodes).unwrap();
+        assert_eq!(names(&nodes, &order), ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn plugin_pass_inserted_before_ui() {
+        let mut debug = node("debug_pass");
+        debug.before.push("egui_pass".into());
+        let nodes = vec![node("sprite_pass"), node("egui_pass"), debug];
+
+        let order = resolve_pass_order(&nodes).unwrap();
+        assert_eq!(
+            names(&nodes, &order),
+            ["sprite_pass", "debug_pass", "egui_pass"]
+        );
+    }
+
+    #[test]
+    fn readers_run_after_writers() {
+        let mut post = node("post");
+        post.reads.push("scene_color".into());
+        let mut sprites = node("sprites");
+        sprites.writes.push("scene_color".into());
+        let nodes = vec![post, sprites];
+
+        let order = resolve_pass_order(&nodes).unwrap();
+        assert_eq!(names(&nodes, &order), ["sprites", "post"]);
+    }
+
+    #[test]
+    fn reports_cycles() {
+        let mut a = node("a");
+        a.before.push("b".into());
+        let mut b = node("b");
+        b.before.push("a".into());
+
+        let err = resolve_pass_order(&[a, b]).unwrap_err();
+        assert_eq!(err, ["a", "b"]);
+    }
+}
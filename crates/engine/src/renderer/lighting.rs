@@ -0,0 +1,717 @@
+//! Éclairage 2D : lumières ponctuelles / spots posées comme composants de la scène, normal
+//! maps optionnelles par sprite (`Sprite::normal_map`) et `LightingPass`, qui multiplie la
+//! scène déjà dessinée par la lumière reçue (ambiante + lumières).
+//!
+//! La passe travaille en trois temps :
+//! 1. les normal maps des sprites sont dessinées dans un normal buffer (normale « plate »
+//!    ailleurs) ;
+//! 2. chaque lumière est accumulée (blending additif) dans un light buffer initialisé à la
+//!    couleur ambiante ;
+//! 3. le light buffer est composé sur la cible (blending multiplicatif).
+
+use std::{collections::HashMap, ops::Range, sync::Arc};
+
+use anyhow::Result;
+use bytemuck::{Pod, Zeroable};
+use egui_wgpu::wgpu;
+use wgpu::util::DeviceExt;
+
+use crate::{
+    Affine2D, AssetLoader, BatchKey, GlobalTransform, InstanceData, Mat4, PassContext, RenderPass,
+    RenderTarget, ScreenSpace, Shader, SpriteComponent, SpriteDraw, SpriteRenderer, Texture2D,
+    TrackedMemory, Transform, Vec2, catch_validation_errors,
+};
+
+/// Lumière ponctuelle, placée à la position monde de l'entité (`GlobalTransform`, sinon
+/// `Transform`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PointLight {
+    pub color: [f32; 3],
+    pub intensity: f32,
+    /// Distance (unités monde) à laquelle la lumière s'éteint.
+    pub radius: f32,
+    /// Hauteur de la lumière au-dessus du plan : plus elle est basse, plus les normal
+    /// maps donnent du relief.
+    pub height: f32,
+}
+
+impl Default for PointLight {
+    fn default() -> Self {
+        Self {
+            color: [1.0; 3],
+            intensity: 1.0,
+            radius: 200.0,
+            height: 50.0,
+        }
+    }
+}
+
+impl PointLight {
+    pub fn new(color: [f32; 3], radius: f32) -> Self {
+        Self {
+            color,
+            radius,
+            ..Default::default()
+        }
+    }
+}
+
+/// Spot : comme `PointLight`, limité à un cône orienté par `angle` (tourné avec l'entité).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpotLight {
+    pub color: [f32; 3],
+    pub intensity: f32,
+    pub radius: f32,
+    pub height: f32,
+    /// Direction du cône en radians (0 = +x, y vers le bas de l'écran).
+    pub angle: f32,
+    /// Demi-angle (radians) à l'intérieur duquel l'éclairage est plein.
+    pub inner_angle: f32,
+    /// Demi-angle (radians) au-delà duquel il est nul ; fondu entre les deux.
+    pub outer_angle: f32,
+}
+
+impl Default for SpotLight {
+    fn default() -> Self {
+        Self {
+            color: [1.0; 3],
+            intensity: 1.0,
+            radius: 300.0,
+            height: 50.0,
+            angle: 0.0,
+            inner_angle: 20f32.to_radians(),
+            outer_angle: 30f32.to_radians(),
+        }
+    }
+}
+
+impl SpotLight {
+    pub fn new(color: [f32; 3], radius: f32, angle: f32) -> Self {
+        Self {
+            color,
+            radius,
+            angle,
+            ..Default::default()
+        }
+    }
+}
+
+/// Données d'une lumière envoyées au GPU (une instance par lumière, voir `lights.wgsl`).
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable)]
+pub struct LightInstance {
+    pub position: [f32; 2],
+    pub radius: f32,
+    pub height: f32,
+    /// Couleur multipliée par l'intensité (alpha inutilisé).
+    pub color: [f32; 4],
+    /// Direction unitaire du cône (spots).
+    pub direction: [f32; 2],
+    /// Cosinus des demi-angles extérieur / intérieur du cône ; `[-2, -1]` pour une
+    /// lumière ponctuelle (toujours dans le cône).
+    pub cone: [f32; 2],
+}
+
+impl LightInstance {
+    /// Lumière ponctuelle à l'origine de `model`.
+    pub fn point(light: &PointLight, model: &Mat4) -> Self {
+        Self {
+            position: Self::origin(model),
+            radius: light.radius,
+            height: light.height,
+            color: Self::color(light.color, light.intensity),
+            direction: [1.0, 0.0],
+            cone: [-2.0, -1.0],
+        }
+    }
+
+    /// Spot à l'origine de `model`, sa direction étant tournée par `model`.
+    pub fn spot(light: &SpotLight, model: &Mat4) -> Self {
+        let local = Vec2::new(light.angle.cos(), light.angle.sin());
+        let direction = model.fixed_view::<2, 2>(0, 0) * local;
+        let direction = direction.try_normalize(f32::EPSILON).unwrap_or(local);
+        let outer = light.outer_angle.max(light.inner_angle);
+        Self {
+            position: Self::origin(model),
+            radius: light.radius,
+            height: light.height,
+            color: Self::color(light.color, light.intensity),
+            direction: [direction.x, direction.y],
+            cone: [outer.cos(), light.inner_angle.cos()],
+        }
+    }
+
+    fn origin(model: &Mat4) -> [f32; 2] {
+        [model[(0, 3)], model[(1, 3)]]
+    }
+
+    fn color([r, g, b]: [f32; 3], intensity: f32) -> [f32; 4] {
+        [r * intensity, g * intensity, b * intensity, 1.0]
+    }
+
+    pub fn layout<'a>() -> wgpu::VertexBufferLayout<'a> {
+        const ATTRIBUTES: [wgpu::VertexAttribute; 6] = wgpu::vertex_attr_array![
+            0 => Float32x2,
+            1 => Float32,
+            2 => Float32,
+            3 => Float32x4,
+            4 => Float32x2,
+            5 => Float32x2
+        ];
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<LightInstance>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &ATTRIBUTES,
+        }
+    }
+}
+
+/// Uniforms de `lights.wgsl`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+struct LightUniforms {
+    view_proj: [[f32; 4]; 4],
+    screen_size: [f32; 2],
+    _padding: [f32; 2],
+}
+
+/// Passe d'éclairage 2D, à placer après `SpritePass` (voir la doc du module).
+/// Sans lumière, la scène est simplement multipliée par `ambient`.
+pub struct LightingPass {
+    /// Lumière reçue partout (noir = seules les lumières éclairent, blanc = pas d'effet).
+    /// `Environment::ambient` la remplace quand la scène en définit une (cycle jour/nuit).
+    pub ambient: [f32; 3],
+    /// Dessine les normal maps (format `NORMAL_FORMAT`).
+    normal_renderer: SpriteRenderer,
+    normal_buffer: RenderTarget,
+    light_buffer: RenderTarget,
+    /// Layout texture + sampler partagé par les deux buffers.
+    buffer_bind_layout: wgpu::BindGroupLayout,
+    normal_bind_group: wgpu::BindGroup,
+    light_bind_group: wgpu::BindGroup,
+    /// Un bind group par normal map (clé : pointeur de l'`Arc`, gardé avec lui).
+    normal_maps: HashMap<usize, (Arc<Texture2D>, wgpu::BindGroup, TrackedMemory)>,
+    light_pipeline: wgpu::RenderPipeline,
+    light_uniform_buffer: wgpu::Buffer,
+    light_uniform_bind_group: wgpu::BindGroup,
+    /// Buffer d'instances de lumières réutilisé tant qu'il est assez grand.
+    light_instances: Option<wgpu::Buffer>,
+    composite_pipeline: wgpu::RenderPipeline,
+    /// Layouts et format gardés pour recréer les pipelines quand un shader est rechargé.
+    light_pipeline_layout: wgpu::PipelineLayout,
+    composite_pipeline_layout: wgpu::PipelineLayout,
+    target_format: wgpu::TextureFormat,
+}
+
+impl LightingPass {
+    pub const LIGHTS_SHADER_PATH: &str = "engine/shaders/lighting/lights.wgsl";
+    pub const COMPOSITE_SHADER_PATH: &str = "engine/shaders/lighting/composite.wgsl";
+    /// Normales encodées en `0..1` : pas de conversion sRGB.
+    pub const NORMAL_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+    /// Flottant pour que les lumières qui se recouvrent dépassent 1 sans saturer.
+    pub const LIGHT_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+    /// Normale « plate » (face à la caméra) des pixels sans normal map.
+    const FLAT_NORMAL: wgpu::Color = wgpu::Color {
+        r: 0.5,
+        g: 0.5,
+        b: 1.0,
+        a: 1.0,
+    };
+
+    pub fn new(
+        device: &wgpu::Device,
+        target_format: wgpu::TextureFormat,
+        loader: &AssetLoader,
+    ) -> Result<Self> {
+        let normal_renderer = SpriteRenderer::new(device, Self::NORMAL_FORMAT, loader)?;
+        let normal_buffer =
+            RenderTarget::new(device, "normal_buffer", 1, 1, Self::NORMAL_FORMAT, false);
+        let light_buffer =
+            RenderTarget::new(device, "light_buffer", 1, 1, Self::LIGHT_FORMAT, false);
+        let buffer_bind_layout = SpriteRenderer::create_texture_bind_layout(
+            device,
+            "lighting_buffer_bind_group_layout",
+            wgpu::TextureViewDimension::D2,
+        );
+
+        // ========================================================================
+        // Accumulation des lumières : @group(0) = uniforms, @group(1) = normal buffer
+        // ========================================================================
+        let lights_shader = loader.load_shader_or_fallback(Self::LIGHTS_SHADER_PATH, device);
+        let uniform_bind_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("light_uniform_bind_group_layout"),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+            });
+        let light_uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("light_uniform_buffer"),
+            size: std::mem::size_of::<LightUniforms>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let light_uniform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("light_uniform_bind_group"),
+            layout: &uniform_bind_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: light_uniform_buffer.as_entire_binding(),
+            }],
+        });
+        let light_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("light_pipeline_layout"),
+                bind_group_layouts: &[&uniform_bind_layout, &buffer_bind_layout],
+                push_constant_ranges: &[],
+            });
+        let light_pipeline =
+            Self::create_light_pipeline(device, &lights_shader, &light_pipeline_layout);
+
+        // ========================================================================
+        // Composition : cible = cible * light buffer (alpha de la cible conservé)
+        // ========================================================================
+        let composite_shader = loader.load_shader_or_fallback(Self::COMPOSITE_SHADER_PATH, device);
+        let composite_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("light_composite_pipeline_layout"),
+                bind_group_layouts: &[&buffer_bind_layout],
+                push_constant_ranges: &[],
+            });
+        let composite_pipeline = Self::create_composite_pipeline(
+            device,
+            &composite_shader,
+            &composite_pipeline_layout,
+            target_format,
+        );
+
+        Ok(Self {
+            ambient: [0.3; 3],
+            normal_bind_group: normal_buffer.create_bind_group(device, &buffer_bind_layout),
+            light_bind_group: light_buffer.create_bind_group(device, &buffer_bind_layout),
+            normal_renderer,
+            normal_buffer,
+            light_buffer,
+            buffer_bind_layout,
+            normal_maps: HashMap::new(),
+            light_pipeline,
+            light_uniform_buffer,
+            light_uniform_bind_group,
+            light_instances: None,
+            light_pipeline_layout,
+            composite_pipeline,
+            composite_pipeline_layout,
+            target_format,
+        })
+    }
+
+    fn create_light_pipeline(
+        device: &wgpu::Device,
+        shader: &Shader,
+        layout: &wgpu::PipelineLayout,
+    ) -> wgpu::RenderPipeline {
+        let additive = wgpu::BlendComponent {
+            src_factor: wgpu::BlendFactor::One,
+            dst_factor: wgpu::BlendFactor::One,
+            operation: wgpu::BlendOperation::Add,
+        };
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("light_pipeline"),
+            layout: Some(layout),
+            vertex: wgpu::VertexState {
+                module: shader.module(),
+                entry_point: Some("vs_main"),
+                buffers: &[LightInstance::layout()],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: shader.module(),
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: Self::LIGHT_FORMAT,
+                    blend: Some(wgpu::BlendState {
+                        color: additive,
+                        alpha: additive,
+                    }),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        })
+    }
+
+    fn create_composite_pipeline(
+        device: &wgpu::Device,
+        shader: &Shader,
+        layout: &wgpu::PipelineLayout,
+        target_format: wgpu::TextureFormat,
+    ) -> wgpu::RenderPipeline {
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("light_composite_pipeline"),
+            layout: Some(layout),
+            vertex: wgpu::VertexState {
+                module: shader.module(),
+                entry_point: Some("vs_main"),
+                buffers: &[],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: shader.module(),
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: target_format,
+                    blend: Some(wgpu::BlendState {
+                        color: wgpu::BlendComponent {
+                            src_factor: wgpu::BlendFactor::Dst,
+                            dst_factor: wgpu::BlendFactor::Zero,
+                            operation: wgpu::BlendOperation::Add,
+                        },
+                        alpha: wgpu::BlendComponent {
+                            src_factor: wgpu::BlendFactor::Zero,
+                            dst_factor: wgpu::BlendFactor::One,
+                            operation: wgpu::BlendOperation::Add,
+                        },
+                    }),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        })
+    }
+
+    /// Redimensionne les buffers à la taille de la fenêtre (et refait leurs bind groups).
+    fn ensure_size(&mut self, device: &wgpu::Device, width: u32, height: u32) {
+        if self.normal_buffer.resize(device, width, height) {
+            self.normal_bind_group = self
+                .normal_buffer
+                .create_bind_group(device, &self.buffer_bind_layout);
+        }
+        if self.light_buffer.resize(device, width, height) {
+            self.light_bind_group = self
+                .light_buffer
+                .create_bind_group(device, &self.buffer_bind_layout);
+        }
+    }
+
+    /// Instances des normal maps de la scène, dans l'ordre de `SpritePass`, regroupées par
+    /// normal map.
+    fn collect_normals(
+        &mut self,
+        ctx: &PassContext,
+    ) -> (Vec<InstanceData>, Vec<(usize, Range<u32>)>) {
+        let device = &ctx.window_state.device;
+        let mut draws: Vec<SpriteDraw> = Vec::new();
+        for (_entity, (transform, global, affine, component, screen_space)) in ctx
+            .scene
+            .world
+            .query::<(
+                &Transform,
+                Option<&GlobalTransform>,
+                Option<&Affine2D>,
+                &SpriteComponent,
+                Option<&ScreenSpace>,
+            )>()
+            .iter()
+        {
+            let sprite = &component.sprite;
+            // Les sprites du HUD ne sont pas éclairés
+            let visible = component.visible && screen_space.is_none();
+            let Some(normal_map) = sprite.normal_map.as_ref().filter(|_| visible) else {
+                continue;
+            };
+            let key = Arc::as_ptr(normal_map) as usize;
+            self.normal_maps.entry(key).or_insert_with(|| {
+                (
+                    normal_map.clone(),
+                    normal_map.create_bind_group(device, &self.normal_renderer.texture_bind_layout),
+                    normal_map.track_bind_group(),
+                )
+            });
+            let model = transform.draw_matrix(global, affine);
+            draws.extend(SpriteDraw::for_sprite(
+                sprite,
+                (BatchKey::Texture(key), 0),
+                model,
+                draws.len(),
+            ));
+        }
+        draws.sort_by(SpriteDraw::cmp_draw_order);
+
+        let mut instances = Vec::with_capacity(draws.len());
+        let mut batches: Vec<(usize, Range<u32>)> = Vec::new();
+        for draw in draws {
+            let BatchKey::Texture(key) = draw.key else {
+                continue;
+            };
+            // La teinte ne colore pas les normales, seule son alpha compte (idem pour les
+            // couleurs des coins, RGBA8 : alpha dans l'octet de poids fort)
+            let mut instance = draw.instance;
+            instance.tint = [1.0, 1.0, 1.0, instance.tint[3]];
+            instance.corner_colors = instance.corner_colors.map(|color| color | 0x00ff_ffff);
+            let index = instances.len() as u32;
+            instances.push(instance);
+            match batches.last_mut() {
+                Some((last, range)) if *last == key => range.end = index + 1,
+                _ => batches.push((key, index..index + 1)),
+            }
+        }
+        self.normal_maps
+            .retain(|key, _| batches.iter().any(|(used, _)| used == key));
+        (instances, batches)
+    }
+
+    fn collect_lights(ctx: &PassContext) -> Vec<LightInstance> {
+        let world = &ctx.scene.world;
+        let model = |transform: Option<&Transform>, global: Option<&GlobalTransform>| {
+            global
+                .map(GlobalTransform::matrix)
+                .or_else(|| transform.map(Transform::matrix))
+                .unwrap_or_else(Mat4::identity)
+        };
+
+        let mut lights: Vec<LightInstance> = world
+            .query::<(&PointLight, Option<&Transform>, Option<&GlobalTransform>)>()
+            .iter()
+            .map(|(_, (light, transform, global))| {
+                LightInstance::point(light, &model(transform, global))
+            })
+            .collect();
+        lights.extend(
+            world
+                .query::<(&SpotLight, Option<&Transform>, Option<&GlobalTransform>)>()
+                .iter()
+                .map(|(_, (light, transform, global))| {
+                    LightInstance::spot(light, &model(transform, global))
+                }),
+        );
+        lights.retain(|light| light.radius > 0.0);
+        lights
+    }
+}
+
+impl RenderPass for LightingPass {
+    fn name(&self) -> &str {
+        "lighting_pass"
+    }
+
+    fn after(&self) -> &[&str] {
+        &["sprite_pass"]
+    }
+
+    fn before(&self) -> &[&str] {
+        &["shape_pass", "egui_pass"]
+    }
+
+    fn reload_shader(
+        &mut self,
+        path: &str,
+        device: &wgpu::Device,
+        loader: &AssetLoader,
+    ) -> Result<bool> {
+        match path {
+            Self::LIGHTS_SHADER_PATH => {
+                let shader = loader.load_shader(path, device)?;
+                self.light_pipeline = catch_validation_errors(device, || {
+                    Self::create_light_pipeline(device, &shader, &self.light_pipeline_layout)
+                })?;
+                Ok(true)
+            }
+            Self::COMPOSITE_SHADER_PATH => {
+                let shader = loader.load_shader(path, device)?;
+                self.composite_pipeline = catch_validation_errors(device, || {
+                    Self::create_composite_pipeline(
+                        device,
+                        &shader,
+                        &self.composite_pipeline_layout,
+                        self.target_format,
+                    )
+                })?;
+                Ok(true)
+            }
+            _ => self.normal_renderer.reload_shader(path, device, loader),
+        }
+    }
+
+    fn execute(&mut self, ctx: &mut PassContext) {
+        let (width, height) = (
+            ctx.window_state.config.width,
+            ctx.window_state.config.height,
+        );
+        self.ensure_size(&ctx.window_state.device, width, height);
+        let view_proj = ctx.camera.view_projection_matrix();
+
+        // 1. Normal buffer
+        let (instances, batches) = self.collect_normals(ctx);
+        self.normal_renderer.update_transform(ctx.queue, view_proj);
+        self.normal_renderer
+            .ensure_instance_capacity(&ctx.window_state.device, instances.len());
+        if !instances.is_empty() {
+            ctx.queue.write_buffer(
+                &self.normal_renderer.instance_buffer,
+                0,
+                bytemuck::cast_slice(&instances),
+            );
+        }
+        {
+            let mut rpass = ctx.encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("normal_render_pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &self.normal_buffer.view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(Self::FLAT_NORMAL),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                occlusion_query_set: None,
+                timestamp_writes: None,
+            });
+            for (key, range) in batches {
+                if let Some((_, bind_group, _)) = self.normal_maps.get(&key) {
+                    ctx.window_state.frame_stats.record_draw(range.len() as u32);
+                    self.normal_renderer.draw_instances_from(
+                        &mut rpass,
+                        bind_group,
+                        &self.normal_renderer.instance_buffer,
+                        range,
+                    );
+                }
+            }
+        }
+
+        // 2. Light buffer : ambiante + lumières
+        let lights = Self::collect_lights(ctx);
+        let uniforms = LightUniforms {
+            view_proj: view_proj.into(),
+            screen_size: [
+                self.normal_buffer.width as f32,
+                self.normal_buffer.height as f32,
+            ],
+            _padding: [0.0; 2],
+        };
+        ctx.queue.write_buffer(
+            &self.light_uniform_buffer,
+            0,
+            bytemuck::cast_slice(&[uniforms]),
+        );
+        if !lights.is_empty() {
+            let bytes: &[u8] = bytemuck::cast_slice(&lights);
+            match &self.light_instances {
+                Some(buffer) if buffer.size() >= bytes.len() as wgpu::BufferAddress => {
+                    ctx.queue.write_buffer(buffer, 0, bytes);
+                }
+                _ => {
+                    self.light_instances = Some(ctx.window_state.device.create_buffer_init(
+                        &wgpu::util::BufferInitDescriptor {
+                            label: Some("light_instances"),
+                            contents: bytes,
+                            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+                        },
+                    ));
+                }
+            }
+        }
+        {
+            let ambient = ctx.scene.environment.ambient.unwrap_or(self.ambient);
+            let [r, g, b] = ambient.map(f64::from);
+            let mut rpass = ctx.encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("light_render_pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &self.light_buffer.view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color { r, g, b, a: 1.0 }),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                occlusion_query_set: None,
+                timestamp_writes: None,
+            });
+            if let Some(buffer) = self.light_instances.as_ref().filter(|_| !lights.is_empty()) {
+                rpass.set_pipeline(&self.light_pipeline);
+                rpass.set_bind_group(0, &self.light_uniform_bind_group, &[]);
+                rpass.set_bind_group(1, &self.normal_bind_group, &[]);
+                rpass.set_vertex_buffer(0, buffer.slice(..));
+                rpass.draw(0..6, 0..lights.len() as u32);
+                ctx.window_state
+                    .frame_stats
+                    .record_draw(lights.len() as u32);
+            }
+        }
+
+        // 3. Composition sur la cible
+        let mut rpass = ctx.encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("light_composite_render_pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: ctx.target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            occlusion_query_set: None,
+            timestamp_writes: None,
+        });
+        rpass.set_pipeline(&self.composite_pipeline);
+        rpass.set_bind_group(0, &self.light_bind_group, &[]);
+        rpass.draw(0..3, 0..1);
+        ctx.window_state.frame_stats.record_draw(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Vec3;
+
+    #[test]
+    fn point_lights_are_never_clipped_by_the_cone() {
+        let light = PointLight {
+            intensity: 2.0,
+            ..PointLight::new([1.0, 0.5, 0.0], 64.0)
+        };
+        let instance =
+            LightInstance::point(&light, &Mat4::new_translation(&Vec3::new(10.0, 20.0, 0.0)));
+        assert_eq!(instance.position, [10.0, 20.0]);
+        assert_eq!(instance.color, [2.0, 1.0, 0.0, 1.0]);
+        // cos(angle) >= -1 > cône extérieur : toujours pleinement éclairé
+        assert!(instance.cone[0] < instance.cone[1] && instance.cone[1] <= -1.0);
+    }
+
+    #[test]
+    fn spot_direction_follows_the_entity_rotation() {
+        let light = SpotLight::new([1.0; 3], 100.0, 0.0);
+        let model = Transform {
+            rotation: Vec3::new(0.0, 0.0, std::f32::consts::FRAC_PI_2),
+            scale: Vec3::new(3.0, 3.0, 1.0),
+            ..Default::default()
+        }
+        .matrix();
+        let instance = LightInstance::spot(&light, &model);
+        assert!(instance.direction[0].abs() < 1e-5);
+        assert!((instance.direction[1] - 1.0).abs() < 1e-5);
+        // Cône extérieur plus large : son cosinus est plus petit
+        assert!(instance.cone[0] < instance.cone[1]);
+    }
+}
@@ -0,0 +1,197 @@
+//! Génération des mipmaps sur le GPU : chaque niveau est dessiné à partir du précédent
+//! (`mipmap.wgsl`). Utilisé par `Texture2D::from_bytes_with` quand le descripteur demande
+//! des mipmaps ; les sprites et tilemaps vus de loin (zoom arrière) ne scintillent plus.
+
+use std::collections::HashMap;
+
+use egui_wgpu::wgpu;
+
+use crate::Shader;
+
+/// Pipelines de génération des mipmaps, un par format de texture.
+pub struct MipmapGenerator {
+    shader: Shader,
+    bind_layout: wgpu::BindGroupLayout,
+    pipeline_layout: wgpu::PipelineLayout,
+    sampler: wgpu::Sampler,
+    pipelines: HashMap<wgpu::TextureFormat, wgpu::RenderPipeline>,
+}
+
+impl MipmapGenerator {
+    /// Les textures sont créées sans `AssetLoader` : le shader est embarqué dans le binaire.
+    const SHADER_SOURCE: &str = include_str!("../../../../engine/shaders/mipmap.wgsl");
+
+    pub fn new(device: &wgpu::Device) -> Self {
+        let shader = Shader::from_source(device, "mipmap_shader", Self::SHADER_SOURCE);
+        let bind_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("mipmap_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("mipmap_pipeline_layout"),
+            bind_group_layouts: &[&bind_layout],
+            push_constant_ranges: &[],
+        });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("mipmap_sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        Self {
+            shader,
+            bind_layout,
+            pipeline_layout,
+            sampler,
+            pipelines: HashMap::new(),
+        }
+    }
+
+    /// `true` si les mipmaps d'une texture de ce format peuvent être générées (format
+    /// filtrable et utilisable comme cible de rendu).
+    pub fn supports(format: wgpu::TextureFormat) -> bool {
+        matches!(
+            format,
+            wgpu::TextureFormat::Rgba8Unorm
+                | wgpu::TextureFormat::Rgba8UnormSrgb
+                | wgpu::TextureFormat::Bgra8Unorm
+                | wgpu::TextureFormat::Bgra8UnormSrgb
+                | wgpu::TextureFormat::Rgba16Float
+        )
+    }
+
+    /// Enregistre dans `encoder` le calcul des niveaux 1.. de `texture` à partir du niveau
+    /// 0. La texture doit avoir les usages `TEXTURE_BINDING` et `RENDER_ATTACHMENT`, et un
+    /// format accepté par `supports` (sinon rien n'est fait).
+    pub fn generate(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        texture: &wgpu::Texture,
+    ) {
+        let format = texture.format();
+        if texture.mip_level_count() <= 1 || !Self::supports(format) {
+            return;
+        }
+        let (shader, layout) = (&self.shader, &self.pipeline_layout);
+        let pipeline = self
+            .pipelines
+            .entry(format)
+            .or_insert_with(|| Self::create_pipeline(device, shader, layout, format));
+
+        let level_view = |level: u32| {
+            texture.create_view(&wgpu::TextureViewDescriptor {
+                label: Some("mipmap_level_view"),
+                base_mip_level: level,
+                mip_level_count: Some(1),
+                ..Default::default()
+            })
+        };
+        for level in 1..texture.mip_level_count() {
+            let (source, target) = (level_view(level - 1), level_view(level));
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("mipmap_bind_group"),
+                layout: &self.bind_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(&source),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&self.sampler),
+                    },
+                ],
+            });
+
+            let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("mipmap_render_pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &target,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                occlusion_query_set: None,
+                timestamp_writes: None,
+            });
+            rpass.set_pipeline(pipeline);
+            rpass.set_bind_group(0, &bind_group, &[]);
+            rpass.draw(0..3, 0..1);
+        }
+    }
+
+    fn create_pipeline(
+        device: &wgpu::Device,
+        shader: &Shader,
+        layout: &wgpu::PipelineLayout,
+        format: wgpu::TextureFormat,
+    ) -> wgpu::RenderPipeline {
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("mipmap_pipeline"),
+            layout: Some(layout),
+            vertex: wgpu::VertexState {
+                module: shader.module(),
+                entry_point: Some("vs_main"),
+                buffers: &[],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: shader.module(),
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn embedded_shader_is_valid() {
+        crate::validate_wgsl("mipmap.wgsl", MipmapGenerator::SHADER_SOURCE).unwrap();
+        assert!(MipmapGenerator::supports(
+            wgpu::TextureFormat::Rgba8UnormSrgb
+        ));
+        assert!(!MipmapGenerator::supports(
+            wgpu::TextureFormat::Depth32Float
+        ));
+    }
+}
@@ -1,5 +1,27 @@
+mod debug_draw;
+mod graph;
+mod lighting;
+mod mipmaps;
+mod outline;
 mod passes;
+mod pipeline_cache;
+mod post_process;
+mod screen_flash;
+mod settings;
+mod target;
 mod traits;
+mod weather;
 
+pub use debug_draw::*;
+pub use graph::*;
+pub use lighting::*;
+pub use mipmaps::*;
+pub use outline::*;
 pub use passes::*;
+pub use pipeline_cache::*;
+pub use post_process::*;
+pub use screen_flash::*;
+pub use settings::*;
+pub use target::*;
 pub use traits::*;
+pub use weather::*;
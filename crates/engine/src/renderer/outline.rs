@@ -0,0 +1,437 @@
+//! Contour des entités surlignées (`Highlight`) : sélection de l'éditeur, objets avec
+//! lesquels le joueur peut interagir...
+//!
+//! Deux étapes : les sprites des entités surlignées sont dessinés dans un masque (couleur
+//! du contour là où le sprite est opaque), puis le masque est dilaté de `width` pixels et
+//! composé sur la cible, à l'extérieur des sprites seulement. Le contour passe par-dessus
+//! ce qui cache l'entité, pour qu'une sélection reste visible.
+
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Range,
+    sync::Arc,
+};
+
+use anyhow::Result;
+use bytemuck::{Pod, Zeroable};
+use egui_wgpu::wgpu;
+
+use crate::{
+    Affine2D, AssetLoader, BatchKey, GlobalTransform, Highlight, InstanceData, PassContext,
+    RenderPass, RenderTarget, ScreenSpace, Shader, SpriteComponent, SpriteDraw, SpriteRenderer,
+    Texture2D, TrackedMemory, Transform, Vertex, catch_validation_errors,
+};
+
+/// Paramètres du shader de composition (`OutlineParams` dans `composite.wgsl`).
+#[repr(C)]
+#[derive(Copy, Clone, Pod, Zeroable)]
+struct OutlineParams {
+    width: f32,
+    _padding: [f32; 3],
+}
+
+/// Passe de contour : lit les entités `Highlight` de la scène et dessine leur contour
+/// dans `ctx.target`. S'exécute après les sprites et l'éclairage, avant l'UI.
+pub struct OutlinePass {
+    /// Buffers d'instances, quad et uniforms, partagés avec le pipeline du masque.
+    renderer: SpriteRenderer,
+    mask_pipeline: wgpu::RenderPipeline,
+    mask_pipeline_layout: wgpu::PipelineLayout,
+    composite_pipeline: wgpu::RenderPipeline,
+    composite_pipeline_layout: wgpu::PipelineLayout,
+    composite_bind_layout: wgpu::BindGroupLayout,
+    params_buffer: wgpu::Buffer,
+    target_format: wgpu::TextureFormat,
+    /// Masque à la taille de la fenêtre, créé au premier contour.
+    mask: Option<RenderTarget>,
+    /// Un bind group par texture surlignée (clé : pointeur de l'`Arc<Texture2D>`).
+    bind_groups: HashMap<usize, (Arc<Texture2D>, wgpu::BindGroup, TrackedMemory)>,
+    /// Épaisseur du contour en pixels (au plus `MAX_WIDTH`).
+    pub width: f32,
+}
+
+impl OutlinePass {
+    pub const MASK_SHADER_PATH: &str = "engine/shaders/outline/mask.wgsl";
+    pub const COMPOSITE_SHADER_PATH: &str = "engine/shaders/outline/composite.wgsl";
+
+    const MASK_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+
+    /// Épaisseur maximale : la dilatation lit (2 * MAX_WIDTH + 1)² pixels du masque.
+    pub const MAX_WIDTH: f32 = 8.0;
+    pub const DEFAULT_WIDTH: f32 = 2.0;
+
+    pub fn new(
+        device: &wgpu::Device,
+        target_format: wgpu::TextureFormat,
+        loader: &AssetLoader,
+    ) -> Result<Self> {
+        let renderer = SpriteRenderer::new(device, Self::MASK_FORMAT, loader)?;
+
+        let mask_shader = loader.load_shader_or_fallback(Self::MASK_SHADER_PATH, device);
+        let mask_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("outline_mask_pipeline_layout"),
+            bind_group_layouts: &[&renderer.uniform_bind_layout, &renderer.texture_bind_layout],
+            push_constant_ranges: &[],
+        });
+        let mask_pipeline = Self::create_mask_pipeline(device, &mask_shader, &mask_pipeline_layout);
+
+        let composite_bind_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("outline_composite_bind_group_layout"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            multisampled: false,
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                ],
+            });
+        let composite_shader = loader.load_shader_or_fallback(Self::COMPOSITE_SHADER_PATH, device);
+        let composite_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("outline_composite_pipeline_layout"),
+                bind_group_layouts: &[&composite_bind_layout],
+                push_constant_ranges: &[],
+            });
+        let composite_pipeline = Self::create_composite_pipeline(
+            device,
+            &composite_shader,
+            &composite_pipeline_layout,
+            target_format,
+        );
+
+        let params_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("outline_params"),
+            size: std::mem::size_of::<OutlineParams>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        Ok(Self {
+            renderer,
+            mask_pipeline,
+            mask_pipeline_layout,
+            composite_pipeline,
+            composite_pipeline_layout,
+            composite_bind_layout,
+            params_buffer,
+            target_format,
+            mask: None,
+            bind_groups: HashMap::new(),
+            width: Self::DEFAULT_WIDTH,
+        })
+    }
+
+    fn create_mask_pipeline(
+        device: &wgpu::Device,
+        shader: &Shader,
+        layout: &wgpu::PipelineLayout,
+    ) -> wgpu::RenderPipeline {
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("outline_mask_pipeline"),
+            layout: Some(layout),
+            vertex: wgpu::VertexState {
+                module: shader.module(),
+                entry_point: Some("vs_main"),
+                buffers: &[Vertex::layout(), InstanceData::layout()],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: shader.module(),
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: Self::MASK_FORMAT,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        })
+    }
+
+    fn create_composite_pipeline(
+        device: &wgpu::Device,
+        shader: &Shader,
+        layout: &wgpu::PipelineLayout,
+        target_format: wgpu::TextureFormat,
+    ) -> wgpu::RenderPipeline {
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("outline_composite_pipeline"),
+            layout: Some(layout),
+            vertex: wgpu::VertexState {
+                module: shader.module(),
+                entry_point: Some("vs_main"),
+                buffers: &[],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: shader.module(),
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: target_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        })
+    }
+
+    /// Redimensionne le masque à la taille de la fenêtre.
+    fn ensure_mask(&mut self, device: &wgpu::Device, width: u32, height: u32) {
+        match &mut self.mask {
+            Some(mask) => {
+                mask.resize(device, width, height);
+            }
+            None => {
+                self.mask = Some(RenderTarget::new(
+                    device,
+                    "outline_mask",
+                    width,
+                    height,
+                    Self::MASK_FORMAT,
+                    false,
+                ));
+            }
+        }
+    }
+}
+
+impl RenderPass for OutlinePass {
+    fn name(&self) -> &str {
+        "outline_pass"
+    }
+
+    fn after(&self) -> &[&str] {
+        &["sprite_pass", "tilemap_pass", "lighting_pass"]
+    }
+
+    fn before(&self) -> &[&str] {
+        &["shape_pass", "egui_pass"]
+    }
+
+    fn reload_shader(
+        &mut self,
+        path: &str,
+        device: &wgpu::Device,
+        loader: &AssetLoader,
+    ) -> Result<bool> {
+        match path {
+            Self::MASK_SHADER_PATH => {
+                let shader = loader.load_shader(path, device)?;
+                self.mask_pipeline = catch_validation_errors(device, || {
+                    Self::create_mask_pipeline(device, &shader, &self.mask_pipeline_layout)
+                })?;
+                Ok(true)
+            }
+            Self::COMPOSITE_SHADER_PATH => {
+                let shader = loader.load_shader(path, device)?;
+                self.composite_pipeline = catch_validation_errors(device, || {
+                    Self::create_composite_pipeline(
+                        device,
+                        &shader,
+                        &self.composite_pipeline_layout,
+                        self.target_format,
+                    )
+                })?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    fn execute(&mut self, ctx: &mut PassContext) {
+        let device = &ctx.window_state.device;
+
+        let mut draws: Vec<SpriteDraw> = Vec::new();
+        for (_entity, (transform, global, affine, component, highlight, screen_space)) in ctx
+            .scene
+            .world
+            .query::<(
+                &Transform,
+                Option<&GlobalTransform>,
+                Option<&Affine2D>,
+                &SpriteComponent,
+                &Highlight,
+                Option<&ScreenSpace>,
+            )>()
+            .iter()
+        {
+            // Le masque est dessiné avec la caméra de la scène : pas de contour pour le HUD
+            if !component.visible || screen_space.is_some() {
+                continue;
+            }
+            let sprite = &component.sprite;
+            let key = Arc::as_ptr(&sprite.texture) as usize;
+            self.bind_groups.entry(key).or_insert_with(|| {
+                (
+                    sprite.texture.clone(),
+                    sprite.create_bind_group(device, &self.renderer.texture_bind_layout),
+                    sprite.texture.track_bind_group(),
+                )
+            });
+            let model = transform.draw_matrix(global, affine);
+            let batch = (BatchKey::Texture(key), 0);
+            for mut draw in SpriteDraw::for_sprite(sprite, batch, model, draws.len()) {
+                draw.instance.tint = highlight.color;
+                draws.push(draw);
+            }
+        }
+
+        // Rien à surligner : pas de masque ni de composition
+        if draws.is_empty() {
+            self.bind_groups.clear();
+            return;
+        }
+
+        // Même ordre que la `SpritePass` : un sprite surligné devant un autre le recouvre
+        draws.sort_by(SpriteDraw::cmp_draw_order);
+        let used: HashSet<BatchKey> = draws.iter().map(|draw| draw.key).collect();
+        self.bind_groups
+            .retain(|key, _| used.contains(&BatchKey::Texture(*key)));
+
+        let mut instances: Vec<InstanceData> = Vec::with_capacity(draws.len());
+        let mut batches: Vec<(usize, Range<u32>)> = Vec::new();
+        for draw in draws {
+            let BatchKey::Texture(key) = draw.key else {
+                continue;
+            };
+            let index = instances.len() as u32;
+            instances.push(draw.instance);
+            match batches.last_mut() {
+                Some((batch, range)) if *batch == key => range.end = index + 1,
+                _ => batches.push((key, index..index + 1)),
+            }
+        }
+
+        self.renderer
+            .ensure_instance_capacity(&ctx.window_state.device, instances.len());
+        ctx.queue.write_buffer(
+            &self.renderer.instance_buffer,
+            0,
+            bytemuck::cast_slice(&instances),
+        );
+        self.renderer
+            .update_transform(ctx.queue, ctx.camera.view_projection_matrix());
+
+        let (width, height) = (
+            ctx.window_state.config.width,
+            ctx.window_state.config.height,
+        );
+        self.ensure_mask(&ctx.window_state.device, width, height);
+        let Some(mask) = &self.mask else {
+            return;
+        };
+
+        // 1. Masque : couleur du contour là où les sprites surlignés sont opaques
+        {
+            let mut rpass = ctx.encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("outline_mask_render_pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &mask.view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                occlusion_query_set: None,
+                timestamp_writes: None,
+            });
+            for (key, range) in batches {
+                let (_texture, bind_group, _) = &self.bind_groups[&key];
+                ctx.window_state.frame_stats.record_draw(range.len() as u32);
+                self.renderer.draw_with(
+                    &mut rpass,
+                    &self.mask_pipeline,
+                    bind_group,
+                    &self.renderer.instance_buffer,
+                    range,
+                );
+            }
+        }
+
+        // 2. Dilatation du masque, composée sur la cible
+        let params = OutlineParams {
+            width: self.width.clamp(0.0, Self::MAX_WIDTH),
+            _padding: [0.0; 3],
+        };
+        ctx.queue
+            .write_buffer(&self.params_buffer, 0, bytemuck::bytes_of(&params));
+        let bind_group = ctx
+            .window_state
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("outline_composite_bind_group"),
+                layout: &self.composite_bind_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(&mask.view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: self.params_buffer.as_entire_binding(),
+                    },
+                ],
+            });
+
+        let mut rpass = ctx.encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("outline_composite_render_pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: ctx.target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            occlusion_query_set: None,
+            timestamp_writes: None,
+        });
+        rpass.set_pipeline(&self.composite_pipeline);
+        rpass.set_bind_group(0, &bind_group, &[]);
+        rpass.draw(0..3, 0..1);
+        ctx.window_state.frame_stats.record_draw(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn composite_shader_matches_max_width() {
+        let source = include_str!("../../../../engine/shaders/outline/composite.wgsl");
+        let expected = format!("const MAX_WIDTH: i32 = {};", OutlinePass::MAX_WIDTH as i32);
+        assert!(source.contains(&expected));
+        assert_eq!(std::mem::size_of::<OutlineParams>(), 16);
+    }
+}
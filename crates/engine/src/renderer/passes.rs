@@ -1,25 +1,48 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
 use egui_wgpu::wgpu;
 use wgpu::{CommandEncoder, Queue, TextureView};
 use winit::window::Window;
 
+use crate::AssetLoader;
 use crate::Camera2D;
+use crate::FramePhase;
+use crate::LocalPlayers;
+use crate::PassNode;
+use crate::PlayerView;
+use crate::RenderSettings;
+use crate::RenderTarget;
+use crate::Scene;
 use crate::WindowState;
+use crate::resolve_pass_order;
 
 /// Contexte fourni à chaque pass lors de l'exécution.
-/// Contient des références vers les ressources par-frame (encoder, target, queue, camera).
+/// Contient des références vers les ressources par-frame (encoder, target, queue, camera, scene).
 /// Expose également la `winit::window::Window` et le `WindowState` afin que les passes
 /// (par exemple une passe qui dessine l'UI via egui) puissent interagir avec la fenêtre
 /// et l'état associé.
 pub struct PassContext<'a> {
     pub encoder: &'a mut CommandEncoder,
+    /// Vue couleur à dessiner : la swapchain, ou la `RenderTarget` assignée à la passe.
     pub target: &'a TextureView,
+    /// Depth buffer de la cible, si elle en a un (jamais pour la swapchain pour l'instant).
+    pub depth: Option<&'a TextureView>,
     pub queue: &'a Queue,
     pub camera: &'a Camera2D,
+    /// Scène courante (entités à dessiner).
+    pub scene: &'a Scene,
     /// Référence immuable à la winit Window (utile pour egui / platform output).
     pub window: &'a Window,
     /// Référence mutable au WindowState pour la frame courante.
     /// Permet d'accéder à `egui_renderer`, `queue`, `device`, etc. depuis une passe.
     pub window_state: &'a mut WindowState,
+    /// Vue du joueur en écran partagé (`PassManager::execute_players`) : la passe limite son
+    /// dessin à `viewport` (`Viewport::apply`). `None` : toute la cible.
+    pub viewport: Option<PlayerView>,
 }
 
 /// Trait simple et ergonomique pour une passe de rendu.
@@ -35,40 +58,398 @@ pub trait RenderPass {
 
     /// Execute the pass for the current frame. `ctx` contains encoder/target/queue/camera.
     /// A pass is free to begin one or more `RenderPass`es via `ctx.encoder.begin_render_pass(...)`.
-    fn execute(&self, ctx: &mut PassContext);
+    fn execute(&mut self, ctx: &mut PassContext);
+
+    /// Noms des passes qui doivent s'exécuter après celle-ci. Par défaut : aucune.
+    fn before(&self) -> &[&str] {
+        &[]
+    }
+
+    /// Noms des passes qui doivent s'exécuter avant celle-ci. Par défaut : aucune.
+    fn after(&self) -> &[&str] {
+        &[]
+    }
+
+    /// Attachments lus par la passe : elle s'exécute après les passes qui les écrivent.
+    fn reads(&self) -> &[&str] {
+        &[]
+    }
+
+    /// Attachments écrits par la passe.
+    fn writes(&self) -> &[&str] {
+        &[]
+    }
+
+    /// Recrée les pipelines qui utilisent le shader `path` (chemin VFS) après sa
+    /// modification. Retourne `true` si la passe utilise ce shader. En cas d'erreur, la
+    /// passe doit garder ses pipelines actuels. Par défaut : aucun shader rechargeable.
+    fn reload_shader(
+        &mut self,
+        _path: &str,
+        _device: &wgpu::Device,
+        _loader: &AssetLoader,
+    ) -> Result<bool> {
+        Ok(false)
+    }
+
+    /// Oublie ce que la passe a chargé depuis l'asset `path` (chemin VFS) après sa
+    /// modification, pour le relire au prochain usage. Retourne `true` si la passe l'utilisait.
+    /// Par défaut : aucun asset en cache.
+    fn reload_asset(&mut self, _path: &str) -> bool {
+        false
+    }
+
+    /// Applique les réglages de la scène (ex: effets de post-process actifs). L'activation
+    /// de la passe elle-même est gérée par le `PassManager`. Par défaut : no-op.
+    fn apply_settings(&mut self, _settings: &RenderSettings) {}
+
+    /// La passe dessine la scène vue par une caméra : en écran partagé, elle s'exécute une
+    /// fois par joueur (`PassManager::execute_players`). Par défaut : une seule fois, sur
+    /// toute la cible.
+    fn per_viewport(&self) -> bool {
+        false
+    }
+}
+
+struct PassEntry {
+    pass: Box<dyn RenderPass + Send + Sync>,
+    /// Contraintes ajoutées via `PassOrdering` (en plus de celles déclarées par la passe).
+    before: Vec<String>,
+    after: Vec<String>,
+    /// Cible hors-écran ; `None` = la cible du `PassContext` (swapchain).
+    target: Option<Arc<Mutex<RenderTarget>>>,
+    /// Passe exécutée (voir `PassManager::set_enabled` et `RenderSettings`).
+    enabled: bool,
+}
+
+impl PassEntry {
+    fn node(&self) -> PassNode {
+        let to_strings = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+
+        let mut before = to_strings(self.pass.before());
+        before.extend(self.before.iter().cloned());
+        let mut after = to_strings(self.pass.after());
+        after.extend(self.after.iter().cloned());
+
+        PassNode {
+            name: self.pass.name().to_string(),
+            before,
+            after,
+            reads: to_strings(self.pass.reads()),
+            writes: to_strings(self.pass.writes()),
+        }
+    }
 }
 
-/// Gestionnaire de passes. Garde les passes dans un vecteur et les exécute dans l'ordre.
+/// Retourné par `PassManager::add` pour déclarer où placer la passe.
+/// Exemple : `passes.add(DebugPass::new()).before("egui_pass");`
+pub struct PassOrdering<'a> {
+    entry: &'a mut PassEntry,
+}
+
+impl PassOrdering<'_> {
+    /// La passe s'exécutera avant la passe nommée `name`.
+    pub fn before(self, name: impl Into<String>) -> Self {
+        self.entry.before.push(name.into());
+        self
+    }
+
+    /// La passe s'exécutera après la passe nommée `name`.
+    pub fn after(self, name: impl Into<String>) -> Self {
+        self.entry.after.push(name.into());
+        self
+    }
+
+    /// La passe dessinera dans `target` au lieu de la swapchain.
+    pub fn target(self, target: Arc<Mutex<RenderTarget>>) -> Self {
+        self.entry.target = Some(target);
+        self
+    }
+}
+
+/// Gestionnaire de passes. Les passes sont exécutées dans l'ordre d'insertion, sauf
+/// contraintes d'ordre (`before`/`after`) ou dépendances d'attachments (voir `resolve_pass_order`).
 pub struct PassManager {
-    passes: Vec<Box<dyn RenderPass + Send + Sync>>,
+    passes: Vec<PassEntry>,
+    /// Ordre d'exécution résolu (indices dans `passes`), recalculé quand `dirty`.
+    order: Vec<usize>,
+    dirty: bool,
+    /// Derniers réglages de scène appliqués ; `execute_all` réapplique quand ils changent.
+    settings: Option<RenderSettings>,
 }
 
 impl PassManager {
     pub fn new() -> Self {
-        Self { passes: Vec::new() }
+        Self {
+            passes: Vec::new(),
+            order: Vec::new(),
+            dirty: false,
+            settings: None,
+        }
+    }
+
+    pub fn add<P: RenderPass + Send + Sync + 'static>(&mut self, pass: P) -> PassOrdering<'_> {
+        self.passes.push(PassEntry {
+            pass: Box::new(pass),
+            before: Vec::new(),
+            after: Vec::new(),
+            target: None,
+            enabled: true,
+        });
+        self.dirty = true;
+        // La nouvelle passe doit aussi suivre les réglages de la scène
+        self.settings = None;
+
+        PassOrdering {
+            entry: self.passes.last_mut().unwrap(),
+        }
     }
 
-    pub fn add<P: RenderPass + Send + Sync + 'static>(&mut self, pass: P) {
-        self.passes.push(Box::new(pass));
+    /// Retire la (les) passe(s) nommée(s) `name`. Retourne `true` si une passe a été retirée.
+    pub fn remove(&mut self, name: &str) -> bool {
+        let len = self.passes.len();
+        self.passes.retain(|e| e.pass.name() != name);
+        self.dirty = true;
+        self.passes.len() != len
+    }
+
+    /// Change la cible de la (des) passe(s) nommée(s) `name` (`None` = swapchain).
+    /// Retourne `true` si une passe correspond.
+    pub fn set_target(&mut self, name: &str, target: Option<Arc<Mutex<RenderTarget>>>) -> bool {
+        let mut found = false;
+        for entry in self.passes.iter_mut().filter(|e| e.pass.name() == name) {
+            entry.target = target.clone();
+            found = true;
+        }
+        found
     }
 
     pub fn clear(&mut self) {
         self.passes.clear();
+        self.order.clear();
+        self.dirty = false;
+        self.settings = None;
+    }
+
+    /// Active ou désactive la (les) passe(s) nommée(s) `name`, jusqu'aux prochains réglages
+    /// de scène appliqués. Retourne `true` si une passe correspond.
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) -> bool {
+        let mut found = false;
+        for entry in self.passes.iter_mut().filter(|e| e.pass.name() == name) {
+            entry.enabled = enabled;
+            found = true;
+        }
+        found
+    }
+
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.passes
+            .iter()
+            .any(|e| e.pass.name() == name && e.enabled)
+    }
+
+    /// Active les passes selon `settings` et les transmet aux passes (effets de
+    /// post-process). Appelé par `execute_all` quand les réglages de la scène changent.
+    pub fn apply_settings(&mut self, settings: &RenderSettings) {
+        for entry in &mut self.passes {
+            entry.enabled = settings.is_pass_enabled(entry.pass.name());
+            entry.pass.apply_settings(settings);
+        }
+        self.settings = Some(settings.clone());
+    }
+
+    /// Noms des passes dans leur ordre d'exécution.
+    pub fn ordered_names(&mut self) -> Vec<&str> {
+        self.resolve_order();
+        self.order
+            .iter()
+            .map(|&i| self.passes[i].pass.name())
+            .collect()
+    }
+
+    /// Recalcule l'ordre d'exécution si des passes ont été ajoutées/retirées.
+    /// En cas de cycle, l'ordre d'insertion est utilisé (avec une erreur dans le log).
+    fn resolve_order(&mut self) {
+        if !self.dirty {
+            return;
+        }
+
+        let nodes: Vec<PassNode> = self.passes.iter().map(PassEntry::node).collect();
+        self.order = match resolve_pass_order(&nodes) {
+            Ok(order) => order,
+            Err(cycle) => {
+                log::error!(
+                    "Render pass ordering cycle between {:?}; falling back to insertion order",
+                    cycle
+                );
+                (0..self.passes.len()).collect()
+            }
+        };
+        self.dirty = false;
     }
 
     /// Appel de `prepare` pour toutes les passes (par ex. lors de l'initialisation ou après resize).
     pub fn prepare_all(&mut self, device: &wgpu::Device, queue: &Queue) {
-        for p in &mut self.passes {
-            p.prepare(device, queue);
+        self.resolve_order();
+        for &i in &self.order {
+            self.passes[i].pass.prepare(device, queue);
         }
     }
 
-    /// Execute toutes les passes dans l'ordre. Le caller doit fournir un `PassContext`.
-    pub fn execute_all(&self, ctx: &mut PassContext) {
-        for p in &self.passes {
+    /// Recharge le shader `path` dans toutes les passes qui l'utilisent. Les erreurs de
+    /// compilation sont loggées (la passe garde son ancien pipeline) : un shader invalide ne
+    /// fait pas planter le jeu. Retourne le nombre de passes rechargées.
+    pub fn reload_shader(
+        &mut self,
+        path: &str,
+        device: &wgpu::Device,
+        loader: &AssetLoader,
+    ) -> usize {
+        // Les pipelines partagés sont recréés une fois, par la première passe qui les demande
+        loader
+            .pipeline_cache()
+            .lock()
+            .unwrap()
+            .invalidate_shader(path);
+        let mut reloaded = 0;
+        for entry in &mut self.passes {
+            match entry.pass.reload_shader(path, device, loader) {
+                Ok(true) => {
+                    log::info!("Reloaded shader {:?} in {}", path, entry.pass.name());
+                    reloaded += 1;
+                }
+                Ok(false) => {}
+                Err(err) => log::error!(
+                    "Failed to reload shader {:?} in {}: {:#}",
+                    path,
+                    entry.pass.name(),
+                    err
+                ),
+            }
+        }
+        reloaded
+    }
+
+    /// Signale aux passes que l'asset `path` a changé (voir `RenderPass::reload_asset`).
+    /// Retourne le nombre de passes qui l'utilisaient.
+    pub fn reload_asset(&mut self, path: &str) -> usize {
+        let mut reloaded = 0;
+        for entry in &mut self.passes {
+            if entry.pass.reload_asset(path) {
+                reloaded += 1;
+            }
+        }
+        reloaded
+    }
+
+    /// Execute toutes les passes actives dans l'ordre. Le caller doit fournir un `PassContext`.
+    /// Les passes qui ont une `RenderTarget` reçoivent un contexte qui pointe vers elle.
+    /// Les `RenderSettings` de la scène sont appliqués d'abord s'ils ont changé.
+    /// Le temps CPU de chaque passe est transmis au `FrameWatchdog` de la fenêtre.
+    pub fn execute_all(&mut self, ctx: &mut PassContext) {
+        self.begin_execute(ctx);
+        for &i in &self.order {
             // éventuel logging :
             // log::debug!("Executing pass: {}", p.name());
-            p.execute(ctx);
+            let entry = &mut self.passes[i];
+            if entry.enabled {
+                Self::execute_entry(entry, ctx);
+            }
+        }
+    }
+
+    /// Comme `execute_all`, en écran partagé : les passes `per_viewport` s'exécutent une fois
+    /// par joueur de `players`, avec sa caméra et sa vue (`PassContext::viewport`) ; les autres
+    /// une seule fois. Les passes réécrivent leurs buffers (`queue.write_buffer`) à chaque
+    /// exécution : les commandes encodées sont donc soumises entre deux joueurs.
+    pub fn execute_players(&mut self, ctx: &mut PassContext, players: &LocalPlayers) {
+        if players.is_empty() {
+            self.execute_all(ctx);
+            return;
+        }
+        self.begin_execute(ctx);
+        for &i in &self.order {
+            let entry = &mut self.passes[i];
+            if !entry.enabled {
+                continue;
+            }
+            if !entry.pass.per_viewport() {
+                Self::execute_entry(entry, ctx);
+                continue;
+            }
+            for (index, (camera, view)) in players.views().enumerate() {
+                if index > 0 {
+                    Self::flush(ctx);
+                }
+                let mut player_ctx = PassContext {
+                    encoder: &mut *ctx.encoder,
+                    target: ctx.target,
+                    depth: ctx.depth,
+                    queue: ctx.queue,
+                    camera,
+                    scene: ctx.scene,
+                    window: ctx.window,
+                    window_state: &mut *ctx.window_state,
+                    viewport: Some(view),
+                };
+                Self::execute_entry(entry, &mut player_ctx);
+            }
+        }
+    }
+
+    /// Réglages de la scène et ordre des passes, avant l'exécution.
+    fn begin_execute(&mut self, ctx: &PassContext) {
+        if self.settings.as_ref() != Some(&ctx.scene.render_settings) {
+            self.apply_settings(&ctx.scene.render_settings);
         }
+        self.resolve_order();
+    }
+
+    /// Soumet les commandes encodées jusqu'ici et continue dans un nouvel encoder.
+    fn flush(ctx: &mut PassContext) {
+        let encoder =
+            ctx.window_state
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("Split screen Encoder"),
+                });
+        let previous = std::mem::replace(ctx.encoder, encoder);
+        ctx.queue.submit(Some(previous.finish()));
+    }
+
+    fn execute_entry(entry: &mut PassEntry, ctx: &mut PassContext) {
+        let _scope = tracing::trace_span!("pass", pass = entry.pass.name()).entered();
+        let started = Instant::now();
+        let Some(target) = &entry.target else {
+            entry.pass.execute(ctx);
+            Self::record_pass_time(ctx, entry.pass.name(), started.elapsed());
+            return;
+        };
+
+        // Les vues sont clonées (handles wgpu) pour ne pas garder le lock pendant la passe.
+        let (view, depth) = {
+            let target = target.lock().unwrap();
+            (target.view.clone(), target.depth_view().cloned())
+        };
+        let mut target_ctx = PassContext {
+            encoder: &mut *ctx.encoder,
+            target: &view,
+            depth: depth.as_ref(),
+            queue: ctx.queue,
+            camera: ctx.camera,
+            scene: ctx.scene,
+            window: ctx.window,
+            window_state: &mut *ctx.window_state,
+            viewport: ctx.viewport,
+        };
+        entry.pass.execute(&mut target_ctx);
+        Self::record_pass_time(ctx, entry.pass.name(), started.elapsed());
+    }
+
+    /// Temps CPU d'une passe, pour le budget des passes.
+    fn record_pass_time(ctx: &mut PassContext, name: &str, duration: Duration) {
+        ctx.window_state
+            .frame_budgets
+            .record_part(FramePhase::Passes, name, duration);
     }
 }
@@ -0,0 +1,172 @@
+//! Cache de `wgpu::RenderPipeline`, partagé par les passes via l'`AssetLoader`.
+//!
+//! Deux passes (ou deux fenêtres sur le même device) qui demandent un pipeline de même
+//! configuration reçoivent le même objet au lieu d'en recompiler un. Au rechargement d'un
+//! shader, `invalidate_shader` retire les pipelines qui l'utilisent : la première passe
+//! les recrée, les suivantes les retrouvent dans le cache.
+
+use std::collections::HashMap;
+
+use egui_wgpu::wgpu;
+
+/// Copie possédée (hashable) d'un `wgpu::VertexBufferLayout`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct VertexLayoutKey {
+    pub array_stride: wgpu::BufferAddress,
+    pub step_mode: wgpu::VertexStepMode,
+    pub attributes: Vec<wgpu::VertexAttribute>,
+}
+
+impl VertexLayoutKey {
+    pub fn from_layouts(layouts: &[wgpu::VertexBufferLayout]) -> Vec<Self> {
+        layouts
+            .iter()
+            .map(|layout| Self {
+                array_stride: layout.array_stride,
+                step_mode: layout.step_mode,
+                attributes: layout.attributes.to_vec(),
+            })
+            .collect()
+    }
+}
+
+/// Configuration d'un pipeline : shader, formats des attachments, blending, buffers de
+/// sommets et constantes surchargées. Le device en fait partie, les pipelines n'étant pas
+/// partageables entre devices.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PipelineKey {
+    device: wgpu::Device,
+    /// Chemin VFS (ou label) du shader, voir `Shader::label`.
+    pub shader: String,
+    pub format: wgpu::TextureFormat,
+    pub depth_format: Option<wgpu::TextureFormat>,
+    pub blend: Option<wgpu::BlendState>,
+    pub vertex_layouts: Vec<VertexLayoutKey>,
+    /// Constantes `override` (nom, bits du `f64`).
+    pub constants: Vec<(String, u64)>,
+}
+
+impl PipelineKey {
+    pub fn new(
+        device: &wgpu::Device,
+        shader: impl Into<String>,
+        format: wgpu::TextureFormat,
+    ) -> Self {
+        Self {
+            device: device.clone(),
+            shader: shader.into(),
+            format,
+            depth_format: None,
+            blend: None,
+            vertex_layouts: Vec::new(),
+            constants: Vec::new(),
+        }
+    }
+
+    pub fn depth_format(mut self, depth_format: Option<wgpu::TextureFormat>) -> Self {
+        self.depth_format = depth_format;
+        self
+    }
+
+    pub fn blend(mut self, blend: wgpu::BlendState) -> Self {
+        self.blend = Some(blend);
+        self
+    }
+
+    pub fn vertex_layouts(mut self, layouts: &[wgpu::VertexBufferLayout]) -> Self {
+        self.vertex_layouts = VertexLayoutKey::from_layouts(layouts);
+        self
+    }
+
+    pub fn constant(mut self, name: impl Into<String>, value: f64) -> Self {
+        self.constants.push((name.into(), value.to_bits()));
+        self
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct PipelineCache {
+    pipelines: HashMap<PipelineKey, wgpu::RenderPipeline>,
+}
+
+impl PipelineCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, key: &PipelineKey) -> Option<&wgpu::RenderPipeline> {
+        self.pipelines.get(key)
+    }
+
+    /// Pipeline de `key`, créé par `create` s'il n'est pas en cache. Une erreur de création
+    /// (ex: `catch_validation_errors` lors d'un rechargement) n'est pas mise en cache.
+    pub fn get_or_try_create<E>(
+        &mut self,
+        key: PipelineKey,
+        create: impl FnOnce() -> Result<wgpu::RenderPipeline, E>,
+    ) -> Result<wgpu::RenderPipeline, E> {
+        if let Some(pipeline) = self.pipelines.get(&key) {
+            return Ok(pipeline.clone());
+        }
+        let pipeline = create()?;
+        self.pipelines.insert(key, pipeline.clone());
+        Ok(pipeline)
+    }
+
+    pub fn get_or_create(
+        &mut self,
+        key: PipelineKey,
+        create: impl FnOnce() -> wgpu::RenderPipeline,
+    ) -> wgpu::RenderPipeline {
+        self.pipelines.entry(key).or_insert_with(create).clone()
+    }
+
+    /// Retire les pipelines du shader `shader`. Retourne leur nombre.
+    pub fn invalidate_shader(&mut self, shader: &str) -> usize {
+        let len = self.pipelines.len();
+        self.pipelines.retain(|key, _| key.shader != shader);
+        len - self.pipelines.len()
+    }
+
+    /// Retire les pipelines d'un device (ex: fenêtre fermée).
+    pub fn remove_device(&mut self, device: &wgpu::Device) {
+        self.pipelines.retain(|key, _| &key.device != device);
+    }
+
+    pub fn len(&self) -> usize {
+        self.pipelines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pipelines.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.pipelines.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vertex_layout_keys_compare_by_content() {
+        let attributes = wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x4];
+        let layout = |step_mode| wgpu::VertexBufferLayout {
+            array_stride: 24,
+            step_mode,
+            attributes: &attributes,
+        };
+        let vertex = VertexLayoutKey::from_layouts(&[layout(wgpu::VertexStepMode::Vertex)]);
+        assert_eq!(
+            vertex,
+            VertexLayoutKey::from_layouts(&[layout(wgpu::VertexStepMode::Vertex)])
+        );
+        assert_ne!(
+            vertex,
+            VertexLayoutKey::from_layouts(&[layout(wgpu::VertexStepMode::Instance)])
+        );
+        assert_eq!(vertex[0].attributes.len(), 2);
+    }
+}
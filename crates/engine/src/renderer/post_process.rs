@@ -0,0 +1,436 @@
+//! Chaîne de post-process : effets plein écran appliqués à une `RenderTarget`.
+//!
+//! La scène est dessinée dans une cible hors-écran (ex: `SpritePass` avec
+//! `PassOrdering::target`), puis `PostProcessPass` applique les effets actifs dans l'ordre
+//! choisi, en alternant (ping-pong) entre deux textures intermédiaires. Le dernier effet
+//! écrit dans la cible du `PassContext` (la swapchain).
+//!
+//! ```ignore
+//! let post = PostProcessPass::builder(scene_color.clone())
+//!     .with(PostEffect::bloom(0.8, 0.6))
+//!     .with(PostEffect::vignette(0.4))
+//!     .with(PostEffect::gamma(1.1))
+//!     .build(&device, surface_format, &loader)?;
+//! passes.add(post);
+//! ```
+//!
+//! Un effet est un shader WGSL qui ne définit que `fs_main` : le prélude
+//! `engine/shaders/post/common.wgsl` (triangle plein écran, texture d'entrée, paramètres)
+//! lui est préfixé.
+
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result};
+use bytemuck::{Pod, Zeroable};
+use egui_wgpu::wgpu;
+
+use crate::{
+    AssetLoader, PassContext, RenderPass, RenderSettings, RenderTarget, ScreenFlash, Shader,
+};
+
+/// Paramètres envoyés au shader d'effet (`PostParams` dans `common.wgsl`).
+#[repr(C)]
+#[derive(Copy, Clone, Pod, Zeroable)]
+struct PostParams {
+    values: [[f32; 4]; 2],
+    texel_size: [f32; 2],
+    /// `Environment::time_of_day` de la scène.
+    time_of_day: f32,
+    _padding: f32,
+}
+
+/// Un effet de la chaîne : shader + paramètres, activable à chaud.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PostEffect {
+    pub name: String,
+    /// Chemin VFS du shader d'effet.
+    pub shader_path: String,
+    /// Paramètres libres, lus par le shader dans `params.values`.
+    pub params: [[f32; 4]; 2],
+    pub enabled: bool,
+}
+
+impl PostEffect {
+    /// Prélude préfixé à chaque shader d'effet.
+    pub const COMMON_SHADER_PATH: &str = "engine/shaders/post/common.wgsl";
+    const COPY_SHADER_PATH: &str = "engine/shaders/post/copy.wgsl";
+
+    /// Effet utilisateur. `shader_path` doit définir `fs_main(in: VSOut) -> @location(0) vec4<f32>`.
+    pub fn custom(
+        name: impl Into<String>,
+        shader_path: impl Into<String>,
+        params: [[f32; 4]; 2],
+    ) -> Self {
+        Self {
+            name: name.into(),
+            shader_path: shader_path.into(),
+            params,
+            enabled: true,
+        }
+    }
+
+    /// Halo autour des zones dont la luminance dépasse `threshold`.
+    pub fn bloom(threshold: f32, intensity: f32) -> Self {
+        Self::custom(
+            "bloom",
+            "engine/shaders/post/bloom.wgsl",
+            [[threshold, intensity, 2.0, 0.0], [0.0; 4]],
+        )
+    }
+
+    /// Assombrit les bords ; `strength` entre 0 (aucun effet) et 1.
+    pub fn vignette(strength: f32) -> Self {
+        Self::custom(
+            "vignette",
+            "engine/shaders/post/vignette.wgsl",
+            [[strength, 0.75, 0.45, 0.0], [0.0; 4]],
+        )
+    }
+
+    /// Correction gamma (1.0 = aucun effet).
+    pub fn gamma(gamma: f32) -> Self {
+        Self::custom(
+            "gamma",
+            "engine/shaders/post/gamma.wgsl",
+            [[gamma, 0.0, 0.0, 0.0], [0.0; 4]],
+        )
+    }
+
+    /// Exposition (EV), contraste et saturation (0, 1, 1 = aucun effet).
+    pub fn color_grading(exposure: f32, contrast: f32, saturation: f32) -> Self {
+        Self::custom(
+            "color_grading",
+            "engine/shaders/post/color_grading.wgsl",
+            [[exposure, contrast, saturation, 0.0], [1.0, 1.0, 1.0, 0.0]],
+        )
+    }
+
+    /// Mélange l'image vers une couleur, piloté chaque frame par `ScreenFlash::apply`.
+    /// Sans effet tant qu'aucun flash n'est en cours.
+    pub fn flash() -> Self {
+        Self::custom(
+            ScreenFlash::EFFECT_NAME,
+            "engine/shaders/post/flash.wgsl",
+            [[0.0; 4]; 2],
+        )
+    }
+
+    /// Teinte multiplicative de `color_grading`.
+    pub fn with_tint(mut self, r: f32, g: f32, b: f32) -> Self {
+        self.params[1] = [r, g, b, 0.0];
+        self
+    }
+}
+
+/// Pipeline compilé d'un effet.
+struct EffectPipeline {
+    pipeline: wgpu::RenderPipeline,
+    params_buffer: wgpu::Buffer,
+}
+
+/// Construit un `PostProcessPass` : les effets sont appliqués dans l'ordre des appels à `with`.
+pub struct PostProcessBuilder {
+    source: Arc<Mutex<RenderTarget>>,
+    effects: Vec<PostEffect>,
+}
+
+impl PostProcessBuilder {
+    pub fn with(mut self, effect: PostEffect) -> Self {
+        self.effects.push(effect);
+        self
+    }
+
+    /// Compile les effets. `format` est le format de la cible finale ; la source doit avoir
+    /// le même format (voir `WindowState::create_render_target`).
+    pub fn build(
+        self,
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        loader: &AssetLoader,
+    ) -> Result<PostProcessPass> {
+        let common = loader
+            .load_string(PostEffect::COMMON_SHADER_PATH)
+            .with_context(|| {
+                format!(
+                    "failed to load post-process prelude {:?}",
+                    PostEffect::COMMON_SHADER_PATH
+                )
+            })?;
+
+        let bind_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("post_process_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("post_process_pipeline_layout"),
+            bind_group_layouts: &[&bind_layout],
+            push_constant_ranges: &[],
+        });
+
+        let compile = |path: &str| -> Result<EffectPipeline> {
+            let source = loader
+                .load_string(path)
+                .with_context(|| format!("failed to load post-process effect {:?}", path))?;
+            loader.add_dependency(path, PostEffect::COMMON_SHADER_PATH);
+
+            // Un effet invalide est dessiné en magenta (voir `Shader::fallback`)
+            let shader =
+                Shader::compile_or_fallback(device, path, &format!("{}\n{}", common, source));
+            let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(path),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: shader.module(),
+                    entry_point: Some("vs_main"),
+                    buffers: &[],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: shader.module(),
+                    entry_point: Some("fs_main"),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format,
+                        blend: None,
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview: None,
+                cache: None,
+            });
+
+            let params_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("post_process_params"),
+                size: std::mem::size_of::<PostParams>() as wgpu::BufferAddress,
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+
+            Ok(EffectPipeline {
+                pipeline,
+                params_buffer,
+            })
+        };
+
+        let copy = compile(PostEffect::COPY_SHADER_PATH)?;
+        let effects = self
+            .effects
+            .into_iter()
+            .map(|effect| compile(&effect.shader_path).map(|pipeline| (effect, pipeline)))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(PostProcessPass {
+            source: self.source,
+            effects,
+            copy,
+            bind_layout,
+            ping_pong: None,
+            format,
+        })
+    }
+}
+
+/// Passe de post-process : lit `source`, applique les effets actifs, écrit dans `ctx.target`.
+/// S'exécute après `sprite_pass` et avant `egui_pass` (l'UI n'est pas post-traitée).
+pub struct PostProcessPass {
+    source: Arc<Mutex<RenderTarget>>,
+    effects: Vec<(PostEffect, EffectPipeline)>,
+    /// Recopie simple utilisée quand aucun effet n'est actif.
+    copy: EffectPipeline,
+    bind_layout: wgpu::BindGroupLayout,
+    /// Textures intermédiaires, (re)créées à la taille de la source.
+    ping_pong: Option<[RenderTarget; 2]>,
+    format: wgpu::TextureFormat,
+}
+
+impl PostProcessPass {
+    pub fn builder(source: Arc<Mutex<RenderTarget>>) -> PostProcessBuilder {
+        PostProcessBuilder {
+            source,
+            effects: Vec::new(),
+        }
+    }
+
+    /// Effets dans leur ordre d'application.
+    pub fn effects(&self) -> impl Iterator<Item = &PostEffect> {
+        self.effects.iter().map(|(effect, _)| effect)
+    }
+
+    /// Accès à un effet par nom (activer/désactiver, régler ses paramètres).
+    pub fn effect_mut(&mut self, name: &str) -> Option<&mut PostEffect> {
+        self.effects
+            .iter_mut()
+            .map(|(effect, _)| effect)
+            .find(|effect| effect.name == name)
+    }
+
+    /// Réglages des effets (cases à cocher + paramètres bruts).
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        for (effect, _) in &mut self.effects {
+            ui.checkbox(&mut effect.enabled, &effect.name);
+            ui.indent(&effect.name, |ui| {
+                for value in effect.params.iter_mut().flatten() {
+                    ui.add(egui::DragValue::new(value).speed(0.01));
+                }
+            });
+        }
+    }
+
+    fn ensure_ping_pong(&mut self, device: &wgpu::Device, width: u32, height: u32) {
+        match &mut self.ping_pong {
+            Some(targets) => {
+                for target in targets {
+                    target.resize(device, width, height);
+                }
+            }
+            None => {
+                let create =
+                    |label| RenderTarget::new(device, label, width, height, self.format, false);
+                self.ping_pong = Some([create("post_process_ping"), create("post_process_pong")]);
+            }
+        }
+    }
+
+    fn draw(
+        &self,
+        ctx: &mut PassContext,
+        effect: &EffectPipeline,
+        params: [[f32; 4]; 2],
+        input: &RenderTarget,
+        output: &wgpu::TextureView,
+    ) {
+        let uniforms = PostParams {
+            values: params,
+            texel_size: [1.0 / input.width as f32, 1.0 / input.height as f32],
+            time_of_day: ctx.scene.environment.time_of_day,
+            _padding: 0.0,
+        };
+        ctx.queue
+            .write_buffer(&effect.params_buffer, 0, bytemuck::bytes_of(&uniforms));
+
+        let bind_group = ctx
+            .window_state
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("post_process_bind_group"),
+                layout: &self.bind_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(&input.view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&input.sampler),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: effect.params_buffer.as_entire_binding(),
+                    },
+                ],
+            });
+
+        let mut rpass = ctx.encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("post_process_render_pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: output,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            occlusion_query_set: None,
+            timestamp_writes: None,
+        });
+        rpass.set_pipeline(&effect.pipeline);
+        rpass.set_bind_group(0, &bind_group, &[]);
+        rpass.draw(0..3, 0..1);
+        ctx.window_state.frame_stats.record_draw(1);
+    }
+}
+
+impl RenderPass for PostProcessPass {
+    fn name(&self) -> &str {
+        "post_process_pass"
+    }
+
+    fn after(&self) -> &[&str] {
+        &["sprite_pass"]
+    }
+
+    fn before(&self) -> &[&str] {
+        &["egui_pass"]
+    }
+
+    /// Active / désactive les effets listés dans `settings.post_effects`.
+    fn apply_settings(&mut self, settings: &RenderSettings) {
+        for (name, &enabled) in &settings.post_effects {
+            if let Some(effect) = self.effect_mut(name) {
+                effect.enabled = enabled;
+            }
+        }
+    }
+
+    fn execute(&mut self, ctx: &mut PassContext) {
+        let target = ctx.target;
+        let source = self.source.clone();
+        let source = source.lock().unwrap();
+
+        let active: Vec<usize> = (0..self.effects.len())
+            .filter(|&i| self.effects[i].0.enabled)
+            .collect();
+
+        let Some((&last, chain)) = active.split_last() else {
+            self.draw(ctx, &self.copy, [[0.0; 4]; 2], &source, target);
+            return;
+        };
+
+        self.ensure_ping_pong(&ctx.window_state.device, source.width, source.height);
+        let targets = self.ping_pong.as_ref().unwrap();
+
+        // Ping-pong : source -> A -> B -> A ... -> cible finale
+        let mut input: &RenderTarget = &source;
+        for (step, &i) in chain.iter().enumerate() {
+            let output = &targets[step % 2];
+            let (effect, pipeline) = &self.effects[i];
+            self.draw(ctx, pipeline, effect.params, input, &output.view);
+            input = output;
+        }
+
+        let (effect, pipeline) = &self.effects[last];
+        self.draw(ctx, pipeline, effect.params, input, target);
+    }
+}
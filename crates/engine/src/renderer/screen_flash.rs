@@ -0,0 +1,140 @@
+//! Flashs et teintes plein écran (impact, dégâts, soin) : des impulsions de couleur qui
+//! s'estompent, combinées en une seule couleur appliquée par l'effet `PostEffect::flash`.
+//!
+//! ```ignore
+//! // Construction : l'effet en fin de chaîne
+//! let post = PostProcessPass::builder(scene_color).with(PostEffect::flash()).build(...)?;
+//! // Gameplay
+//! flash.flash([1.0, 1.0, 1.0], 0.15);
+//! // Chaque frame (temps réel : le flash continue pendant un hit-stop)
+//! flash.update(delta_timer.delta_time());
+//! flash.apply(&mut post);
+//! ```
+
+/// Une impulsion : `color[3]` est l'intensité au départ, qui décroît jusqu'à 0.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FlashPulse {
+    pub color: [f32; 4],
+    /// Durée totale (secondes).
+    pub duration: f32,
+    /// Temps pendant lequel l'impulsion reste à pleine intensité avant de s'estomper.
+    pub hold: f32,
+    pub elapsed: f32,
+}
+
+impl FlashPulse {
+    /// Intensité courante (0 une fois terminée).
+    pub fn intensity(&self) -> f32 {
+        let fade = (self.duration - self.hold).max(f32::EPSILON);
+        let t = ((self.elapsed - self.hold) / fade).clamp(0.0, 1.0);
+        match self.elapsed >= self.duration {
+            true => 0.0,
+            false => self.color[3] * (1.0 - t),
+        }
+    }
+}
+
+/// Impulsions en cours.
+#[derive(Debug, Clone, Default)]
+pub struct ScreenFlash {
+    pulses: Vec<FlashPulse>,
+}
+
+impl ScreenFlash {
+    /// Nom de l'effet de post-process piloté par `apply`.
+    pub const EFFECT_NAME: &str = "flash";
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Flash de `color` à pleine intensité qui s'estompe en `duration` secondes.
+    pub fn flash(&mut self, color: [f32; 3], duration: f32) {
+        self.pulse(FlashPulse {
+            color: [color[0], color[1], color[2], 1.0],
+            duration,
+            hold: 0.0,
+            elapsed: 0.0,
+        });
+    }
+
+    /// Teinte de `color` à `strength` (0..1), maintenue `hold` secondes puis estompée
+    /// jusqu'à `duration`.
+    pub fn tint(&mut self, color: [f32; 3], strength: f32, hold: f32, duration: f32) {
+        self.pulse(FlashPulse {
+            color: [color[0], color[1], color[2], strength.clamp(0.0, 1.0)],
+            duration: duration.max(hold),
+            hold,
+            elapsed: 0.0,
+        });
+    }
+
+    pub fn pulse(&mut self, pulse: FlashPulse) {
+        self.pulses.push(pulse);
+    }
+
+    pub fn clear(&mut self) {
+        self.pulses.clear();
+    }
+
+    pub fn is_active(&self) -> bool {
+        !self.pulses.is_empty()
+    }
+
+    /// Fait avancer les impulsions de `delta_time` secondes et retire celles qui sont finies.
+    pub fn update(&mut self, delta_time: f32) {
+        for pulse in &mut self.pulses {
+            pulse.elapsed += delta_time;
+        }
+        self.pulses.retain(|pulse| pulse.elapsed < pulse.duration);
+    }
+
+    /// Couleur (rgb) et intensité (a) combinées : chaque impulsion recouvre les précédentes
+    /// selon son intensité.
+    pub fn color(&self) -> [f32; 4] {
+        // Composition "over" en couleur prémultipliée
+        let (mut color, mut alpha) = ([0.0f32; 3], 0.0f32);
+        for pulse in &self.pulses {
+            let intensity = pulse.intensity();
+            for (channel, pulse_channel) in color.iter_mut().zip(pulse.color) {
+                *channel = *channel * (1.0 - intensity) + pulse_channel * intensity;
+            }
+            alpha = alpha * (1.0 - intensity) + intensity;
+        }
+        match alpha > 0.0 {
+            true => [color[0] / alpha, color[1] / alpha, color[2] / alpha, alpha],
+            false => [0.0; 4],
+        }
+    }
+
+    /// Écrit la couleur courante dans l'effet `EFFECT_NAME` de `post` (sans effet si la
+    /// chaîne ne le contient pas).
+    pub fn apply(&self, post: &mut crate::PostProcessPass) {
+        if let Some(effect) = post.effect_mut(Self::EFFECT_NAME) {
+            effect.params[0] = self.color();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pulses_fade_and_combine() {
+        let mut flash = ScreenFlash::new();
+        flash.flash([1.0, 1.0, 1.0], 0.2);
+        flash.tint([1.0, 0.0, 0.0], 0.5, 0.1, 0.3);
+        assert_eq!(flash.color(), [1.0, 0.5, 0.5, 1.0]);
+
+        // Le flash est à mi-course, la teinte encore maintenue
+        flash.update(0.1);
+        let [r, g, _, a] = flash.color();
+        assert!((r - 1.0).abs() < 1e-6 && (g - 1.0 / 3.0).abs() < 1e-6);
+        assert!((a - 0.75).abs() < 1e-6);
+
+        flash.update(0.25);
+        assert!(!flash.is_active());
+        assert_eq!(flash.color(), [0.0; 4]);
+    }
+}
@@ -0,0 +1,93 @@
+//! Réglages de rendu propres à une scène : passes et effets de post-process actifs.
+//!
+//! Chaque `Scene` porte ses `RenderSettings`. Le `PassManager` les applique dès qu'il
+//! exécute une scène dont les réglages diffèrent des derniers appliqués (changement de
+//! scène, réglages modifiés en jeu) : pas de liste de passes à refaire à la main.
+//!
+//! ```ignore
+//! // Menu : l'UI seule
+//! menu.render_settings = RenderSettings::only(["egui_pass"]);
+//! // Gameplay : toutes les passes, bloom activé
+//! level.render_settings = RenderSettings::default().with_effect("bloom", true);
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Passes et effets de post-process actifs pour une scène. Par défaut : toutes les passes,
+/// effets inchangés.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RenderSettings {
+    /// Seules ces passes s'exécutent (par nom, ex: "egui_pass") ; `None` = toutes.
+    pub enabled_passes: Option<BTreeSet<String>>,
+    /// Passes qui ne s'exécutent pas, même si elles sont dans `enabled_passes`.
+    pub disabled_passes: BTreeSet<String>,
+    /// Effets de post-process forcés actifs ou inactifs (par nom, ex: "bloom"). Les effets
+    /// absents gardent leur état actuel.
+    pub post_effects: BTreeMap<String, bool>,
+}
+
+impl RenderSettings {
+    /// Seulement les passes `names`.
+    pub fn only<S: Into<String>>(names: impl IntoIterator<Item = S>) -> Self {
+        Self {
+            enabled_passes: Some(names.into_iter().map(Into::into).collect()),
+            ..Self::default()
+        }
+    }
+
+    /// Désactive la passe `name`.
+    pub fn without(mut self, name: impl Into<String>) -> Self {
+        self.disabled_passes.insert(name.into());
+        self
+    }
+
+    /// Active ou désactive l'effet de post-process `name`.
+    pub fn with_effect(mut self, name: impl Into<String>, enabled: bool) -> Self {
+        self.post_effects.insert(name.into(), enabled);
+        self
+    }
+
+    pub fn is_pass_enabled(&self, name: &str) -> bool {
+        self.enabled_passes
+            .as_ref()
+            .is_none_or(|passes| passes.contains(name))
+            && !self.disabled_passes.contains(name)
+    }
+
+    /// Active ou désactive la passe `name` (l'ajoute à `enabled_passes` si la liste existe).
+    pub fn set_pass_enabled(&mut self, name: &str, enabled: bool) {
+        if enabled {
+            self.disabled_passes.remove(name);
+            if let Some(passes) = &mut self.enabled_passes {
+                passes.insert(name.to_string());
+            }
+        } else {
+            self.disabled_passes.insert(name.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn passes_are_filtered_by_name() {
+        let all = RenderSettings::default();
+        assert!(all.is_pass_enabled("lighting_pass"));
+
+        let mut menu = RenderSettings::only(["egui_pass", "sprite_pass"]).without("sprite_pass");
+        assert!(menu.is_pass_enabled("egui_pass"));
+        assert!(!menu.is_pass_enabled("sprite_pass"));
+        assert!(!menu.is_pass_enabled("lighting_pass"));
+
+        menu.set_pass_enabled("lighting_pass", true);
+        menu.set_pass_enabled("sprite_pass", true);
+        assert!(menu.is_pass_enabled("lighting_pass"));
+        assert!(menu.is_pass_enabled("sprite_pass"));
+        assert_ne!(menu, all);
+
+        let level = RenderSettings::default().with_effect("bloom", true);
+        assert_eq!(level.post_effects.get("bloom"), Some(&true));
+    }
+}
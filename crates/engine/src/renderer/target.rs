@@ -0,0 +1,276 @@
+use anyhow::{Result, anyhow};
+use egui_wgpu::wgpu;
+
+use crate::{MemoryCategory, TrackedMemory};
+
+/// Texture de rendu hors-écran (couleur + depth optionnel).
+///
+/// Une passe peut la cibler à la place de la swapchain (voir `PassOrdering::target`),
+/// puis la texture peut être échantillonnée par une autre passe (post-process) ou affichée
+/// dans egui (viewport de l'éditeur, voir `EguiRenderer::register_render_target`).
+pub struct RenderTarget {
+    pub texture: wgpu::Texture,
+    pub view: wgpu::TextureView,
+    pub sampler: wgpu::Sampler,
+    depth: Option<(wgpu::Texture, wgpu::TextureView)>,
+    pub format: wgpu::TextureFormat,
+    pub width: u32,
+    pub height: u32,
+    label: String,
+    memory: TrackedMemory,
+}
+
+impl RenderTarget {
+    /// Format utilisé pour le depth buffer optionnel.
+    pub const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+    /// Crée une cible de `width` x `height` pixels (au minimum 1x1).
+    #[track_caller]
+    pub fn new(
+        device: &wgpu::Device,
+        label: impl Into<String>,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+        with_depth: bool,
+    ) -> Self {
+        let label = label.into();
+        let width = width.max(1);
+        let height = height.max(1);
+
+        let (texture, view) = Self::create_color(device, &label, width, height, format);
+        let depth = with_depth.then(|| Self::create_depth(device, &label, width, height));
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some(&format!("{}_sampler", label)),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        Self {
+            texture,
+            view,
+            sampler,
+            depth,
+            format,
+            width,
+            height,
+            memory: Self::track(&label, width, height, format, with_depth),
+            label,
+        }
+    }
+
+    /// Taille des textures de la cible (couleur et depth) pour le suivi mémoire.
+    #[track_caller]
+    fn track(
+        label: &str,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+        with_depth: bool,
+    ) -> TrackedMemory {
+        let pixels = width as u64 * height as u64;
+        let color = format.block_copy_size(None).unwrap_or(4) as u64;
+        let depth = if with_depth { 4 } else { 0 };
+        TrackedMemory::labeled(
+            MemoryCategory::RenderTargets,
+            pixels * (color + depth),
+            label,
+        )
+    }
+
+    fn create_color(
+        device: &wgpu::Device,
+        label: &str,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+    ) -> (wgpu::Texture, wgpu::TextureView) {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        (texture, view)
+    }
+
+    pub(crate) fn create_depth(
+        device: &wgpu::Device,
+        label: &str,
+        width: u32,
+        height: u32,
+    ) -> (wgpu::Texture, wgpu::TextureView) {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(&format!("{}_depth", label)),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: Self::DEPTH_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        (texture, view)
+    }
+
+    /// Vue du depth buffer, si la cible en a un.
+    pub fn depth_view(&self) -> Option<&wgpu::TextureView> {
+        self.depth.as_ref().map(|(_, view)| view)
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// Recrée les textures à la nouvelle taille. Retourne `false` si la taille n'a pas changé.
+    /// Les bind groups / textures egui qui référencent l'ancienne vue doivent être mis à jour.
+    #[track_caller]
+    pub fn resize(&mut self, device: &wgpu::Device, width: u32, height: u32) -> bool {
+        let width = width.max(1);
+        let height = height.max(1);
+        if (width, height) == (self.width, self.height) {
+            return false;
+        }
+
+        (self.texture, self.view) =
+            Self::create_color(device, &self.label, width, height, self.format);
+        if self.depth.is_some() {
+            self.depth = Some(Self::create_depth(device, &self.label, width, height));
+        }
+        self.width = width;
+        self.height = height;
+        self.memory = Self::track(
+            &self.label,
+            width,
+            height,
+            self.format,
+            self.depth.is_some(),
+        );
+        true
+    }
+
+    /// Lit la couleur du pixel (`x`, `y`) de la cible (RGBA, 0..1, espace du format).
+    /// Lecture GPU bloquante : réservée aux outils (pipette de l'éditeur), pas au rendu.
+    pub fn read_pixel(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        x: u32,
+        y: u32,
+    ) -> Result<[f32; 4]> {
+        use wgpu::TextureFormat as F;
+        let bgra = match self.format {
+            F::Rgba8Unorm | F::Rgba8UnormSrgb => false,
+            F::Bgra8Unorm | F::Bgra8UnormSrgb => true,
+            format => return Err(anyhow!("cannot read pixels of a {:?} target", format)),
+        };
+        if x >= self.width || y >= self.height {
+            return Err(anyhow!(
+                "pixel ({}, {}) outside of a {}x{} target",
+                x,
+                y,
+                self.width,
+                self.height
+            ));
+        }
+
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(&format!("{}_readback", self.label)),
+            size: wgpu::COPY_BYTES_PER_ROW_ALIGNMENT as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("read_pixel"),
+        });
+        encoder.copy_texture_to_buffer(
+            wgpu::TexelCopyTextureInfo {
+                texture: &self.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d { x, y, z: 0 },
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::TexelCopyBufferInfo {
+                buffer: &buffer,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT),
+                    rows_per_image: Some(1),
+                },
+            },
+            wgpu::Extent3d {
+                width: 1,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+        );
+        queue.submit(Some(encoder.finish()));
+
+        let slice = buffer.slice(..4);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        device.poll(wgpu::PollType::Wait)?;
+        receiver.recv()??;
+
+        let bytes = {
+            let data = slice.get_mapped_range();
+            [data[0], data[1], data[2], data[3]]
+        };
+        buffer.unmap();
+
+        let rgba = if bgra {
+            [bytes[2], bytes[1], bytes[0], bytes[3]]
+        } else {
+            bytes
+        };
+        Ok(rgba.map(|channel| channel as f32 / 255.0))
+    }
+
+    /// Bind group (binding 0 = vue couleur, binding 1 = sampler), même layout que
+    /// `Texture2D::create_bind_group`, pour échantillonner la cible dans une autre passe.
+    pub fn create_bind_group(
+        &self,
+        device: &wgpu::Device,
+        bind_group_layout: &wgpu::BindGroupLayout,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some(&format!("{}_bind_group", self.label)),
+            layout: bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&self.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+            ],
+        })
+    }
+}
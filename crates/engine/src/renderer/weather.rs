@@ -0,0 +1,233 @@
+//! Météo : dessine l'`Environment` de la scène (pluie, neige, brouillard) par-dessus les
+//! sprites, avec un seul shader plein écran (`weather.wgsl`). Rien n'est dessiné par temps
+//! clair.
+
+use std::time::Instant;
+
+use anyhow::Result;
+use bytemuck::{Pod, Zeroable};
+use egui_wgpu::wgpu;
+
+use crate::{AssetLoader, Environment, PassContext, RenderPass, Shader, catch_validation_errors};
+
+/// Paramètres du shader (`WeatherParams` dans `weather.wgsl`).
+#[repr(C)]
+#[derive(Copy, Clone, Pod, Zeroable)]
+struct WeatherParams {
+    screen: [f32; 4],
+    camera: [f32; 4],
+    rain: [f32; 4],
+    rain_color: [f32; 4],
+    snow: [f32; 4],
+    snow_color: [f32; 4],
+    fog: [f32; 4],
+    fog_color: [f32; 4],
+}
+
+impl WeatherParams {
+    fn new(environment: &Environment, ctx: &PassContext, time: f32) -> Self {
+        let camera = ctx.camera;
+        let (rain, snow, fog) = (environment.rain, environment.snow, environment.fog);
+        Self {
+            screen: [
+                ctx.window_state.config.width as f32,
+                ctx.window_state.config.height as f32,
+                time,
+                camera.zoom,
+            ],
+            camera: [
+                camera.position.x,
+                camera.position.y,
+                environment.wind,
+                environment.time_of_day,
+            ],
+            rain: [rain.intensity, rain.speed, rain.size, 0.0],
+            rain_color: rain.color,
+            snow: [snow.intensity, snow.speed, snow.size, 0.0],
+            snow_color: snow.color,
+            fog: [fog.density, fog.scale, 0.0, 0.0],
+            fog_color: [fog.color[0], fog.color[1], fog.color[2], 1.0],
+        }
+    }
+}
+
+/// Passe météo : lit `ctx.scene.environment` et le compose sur `ctx.target`. S'exécute
+/// après les sprites et l'éclairage (la pluie n'est pas éclairée), avant les contours et
+/// l'UI.
+pub struct WeatherPass {
+    pipeline: wgpu::RenderPipeline,
+    pipeline_layout: wgpu::PipelineLayout,
+    bind_group: wgpu::BindGroup,
+    params_buffer: wgpu::Buffer,
+    target_format: wgpu::TextureFormat,
+    /// Origine du temps des animations.
+    started: Instant,
+}
+
+impl WeatherPass {
+    pub const SHADER_PATH: &str = "engine/shaders/weather.wgsl";
+
+    pub fn new(
+        device: &wgpu::Device,
+        target_format: wgpu::TextureFormat,
+        loader: &AssetLoader,
+    ) -> Result<Self> {
+        let bind_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("weather_bind_group_layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("weather_pipeline_layout"),
+            bind_group_layouts: &[&bind_layout],
+            push_constant_ranges: &[],
+        });
+
+        let shader = loader.load_shader_or_fallback(Self::SHADER_PATH, device);
+        let pipeline = Self::create_pipeline(device, &shader, &pipeline_layout, target_format);
+
+        let params_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("weather_params"),
+            size: std::mem::size_of::<WeatherParams>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("weather_bind_group"),
+            layout: &bind_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: params_buffer.as_entire_binding(),
+            }],
+        });
+
+        Ok(Self {
+            pipeline,
+            pipeline_layout,
+            bind_group,
+            params_buffer,
+            target_format,
+            started: Instant::now(),
+        })
+    }
+
+    fn create_pipeline(
+        device: &wgpu::Device,
+        shader: &Shader,
+        layout: &wgpu::PipelineLayout,
+        target_format: wgpu::TextureFormat,
+    ) -> wgpu::RenderPipeline {
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("weather_pipeline"),
+            layout: Some(layout),
+            vertex: wgpu::VertexState {
+                module: shader.module(),
+                entry_point: Some("vs_main"),
+                buffers: &[],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: shader.module(),
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: target_format,
+                    // Le shader sort des couleurs prémultipliées
+                    blend: Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        })
+    }
+}
+
+impl RenderPass for WeatherPass {
+    fn name(&self) -> &str {
+        "weather_pass"
+    }
+
+    fn after(&self) -> &[&str] {
+        &["sprite_pass", "tilemap_pass", "lighting_pass"]
+    }
+
+    fn before(&self) -> &[&str] {
+        &["outline_pass", "shape_pass", "egui_pass"]
+    }
+
+    fn reload_shader(
+        &mut self,
+        path: &str,
+        device: &wgpu::Device,
+        loader: &AssetLoader,
+    ) -> Result<bool> {
+        if path != Self::SHADER_PATH {
+            return Ok(false);
+        }
+        let shader = loader.load_shader(path, device)?;
+        self.pipeline = catch_validation_errors(device, || {
+            Self::create_pipeline(device, &shader, &self.pipeline_layout, self.target_format)
+        })?;
+        Ok(true)
+    }
+
+    fn execute(&mut self, ctx: &mut PassContext) {
+        let environment = &ctx.scene.environment;
+        if environment.is_clear() {
+            return;
+        }
+
+        let params = WeatherParams::new(environment, ctx, self.started.elapsed().as_secs_f32());
+        ctx.queue
+            .write_buffer(&self.params_buffer, 0, bytemuck::bytes_of(&params));
+
+        let mut rpass = ctx.encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("weather_render_pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: ctx.target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            occlusion_query_set: None,
+            timestamp_writes: None,
+        });
+        rpass.set_pipeline(&self.pipeline);
+        rpass.set_bind_group(0, &self.bind_group, &[]);
+        rpass.draw(0..3, 0..1);
+        ctx.window_state.frame_stats.record_draw(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn params_match_shader_layout() {
+        let source = include_str!("../../../../engine/shaders/weather.wgsl");
+        let fields = source
+            .split("struct WeatherParams {")
+            .nth(1)
+            .and_then(|rest| rest.split("};").next())
+            .unwrap()
+            .matches(": vec4<f32>")
+            .count();
+        assert_eq!(std::mem::size_of::<WeatherParams>(), fields * 16);
+    }
+}
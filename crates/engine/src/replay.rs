@@ -0,0 +1,560 @@
+//! Enregistrement et visionneuse de replays (fenêtre de l'éditeur).
+//!
+//! Un `Replay` est une suite de frames à fréquence fixe : entrées pressées et position des
+//! entités nommées. Il est sauvegardé en texte (`.replay`) :
+//!
+//! ```text
+//! replay 60
+//! tick 0
+//! input Space
+//! entity 4294967297 12.5 -3 Player
+//! tick 1
+//! ...
+//! ```
+//!
+//! La `ReplayViewer` charge un replay, le parcourt avec une timeline, dessine les
+//! trajectoires des entités avec le `DebugDraw` et exporte une plage de frames en CSV.
+
+use std::{ops::Range, sync::Arc};
+
+use anyhow::{Context, Result, anyhow, bail};
+
+use crate::{DebugDraw, DebugShape, GlobalTransform, Name, Scene, Transform, Vec2, Vfs};
+
+/// Position d'une entité dans une frame.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayEntity {
+    /// `Entity::to_bits` au moment de l'enregistrement.
+    pub id: u64,
+    pub name: String,
+    pub position: Vec2,
+}
+
+/// État enregistré à un tick.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReplayFrame {
+    pub tick: u64,
+    /// Entrées pressées pendant le tick (ex: noms de `KeyCode`).
+    pub inputs: Vec<String>,
+    pub entities: Vec<ReplayEntity>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Replay {
+    /// Ticks par seconde.
+    pub tick_rate: f32,
+    pub frames: Vec<ReplayFrame>,
+}
+
+impl Replay {
+    pub const EXTENSION: &str = "replay";
+
+    pub fn new(tick_rate: f32) -> Self {
+        Self {
+            tick_rate,
+            frames: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Ajoute une frame avec les entités nommées de `scene` (position globale si elle est
+    /// calculée, locale sinon).
+    pub fn capture(&mut self, scene: &Scene, inputs: Vec<String>) {
+        let tick = self.frames.last().map_or(0, |frame| frame.tick + 1);
+        let mut entities: Vec<ReplayEntity> = scene
+            .world
+            .query::<(&Name, &Transform, Option<&GlobalTransform>)>()
+            .iter()
+            .map(|(entity, (name, transform, global))| ReplayEntity {
+                id: entity.to_bits().get(),
+                name: name.0.clone(),
+                position: match global {
+                    Some(global) => global.translation().xy(),
+                    None => transform.position.xy(),
+                },
+            })
+            .collect();
+        entities.sort_by_key(|entity| entity.id);
+        self.frames.push(ReplayFrame {
+            tick,
+            inputs,
+            entities,
+        });
+    }
+
+    /// Entités présentes dans au moins une frame (id, nom), triées par id.
+    pub fn entities(&self) -> Vec<(u64, String)> {
+        let mut entities: Vec<(u64, String)> = Vec::new();
+        for entity in self.frames.iter().flat_map(|frame| &frame.entities) {
+            if let Err(index) = entities.binary_search_by_key(&entity.id, |(id, _)| *id) {
+                entities.insert(index, (entity.id, entity.name.clone()));
+            }
+        }
+        entities
+    }
+
+    /// Positions de l'entité `id` sur les frames `frames` (les frames où elle est absente
+    /// sont sautées).
+    pub fn trajectory(&self, id: u64, frames: Range<usize>) -> Vec<Vec2> {
+        let end = frames.end.min(self.frames.len());
+        let start = frames.start.min(end);
+        self.frames[start..end]
+            .iter()
+            .filter_map(|frame| frame.entities.iter().find(|entity| entity.id == id))
+            .map(|entity| entity.position)
+            .collect()
+    }
+
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+
+        let tick_rate = match lines.next() {
+            Some((_, header)) => header
+                .strip_prefix("replay ")
+                .and_then(|rate| rate.trim().parse::<f32>().ok())
+                .filter(|rate| *rate > 0.0)
+                .ok_or_else(|| anyhow!("expected \"replay <tick rate>\", got {:?}", header))?,
+            None => bail!("empty replay"),
+        };
+        let mut replay = Replay::new(tick_rate);
+
+        for (number, line) in lines {
+            let (keyword, rest) = line.split_once(' ').unwrap_or((line, ""));
+            replay
+                .parse_line(keyword, rest.trim())
+                .with_context(|| format!("line {}", number))?;
+        }
+        Ok(replay)
+    }
+
+    fn parse_line(&mut self, keyword: &str, rest: &str) -> Result<()> {
+        if keyword == "tick" {
+            self.frames.push(ReplayFrame {
+                tick: rest.parse().context("invalid tick")?,
+                ..Default::default()
+            });
+            return Ok(());
+        }
+        let frame = self
+            .frames
+            .last_mut()
+            .ok_or_else(|| anyhow!("{:?} before the first tick", keyword))?;
+        match keyword {
+            "input" => frame.inputs.push(rest.to_string()),
+            "entity" => {
+                let mut fields = rest.splitn(4, ' ');
+                let mut next = |what| fields.next().ok_or_else(|| anyhow!("missing {}", what));
+                let id = next("id")?.parse().context("invalid id")?;
+                let x = next("x")?.parse().context("invalid x")?;
+                let y = next("y")?.parse().context("invalid y")?;
+                frame.entities.push(ReplayEntity {
+                    id,
+                    name: fields.next().unwrap_or_default().to_string(),
+                    position: Vec2::new(x, y),
+                });
+            }
+            _ => bail!("unknown keyword {:?}", keyword),
+        }
+        Ok(())
+    }
+
+    pub fn encode(&self) -> String {
+        let mut text = format!("replay {}\n", self.tick_rate);
+        for frame in &self.frames {
+            text.push_str(&format!("tick {}\n", frame.tick));
+            for input in &frame.inputs {
+                text.push_str(&format!("input {}\n", input));
+            }
+            for entity in &frame.entities {
+                text.push_str(&format!(
+                    "entity {} {} {} {}\n",
+                    entity.id, entity.position.x, entity.position.y, entity.name
+                ));
+            }
+        }
+        text
+    }
+
+    pub fn load(vfs: &Vfs, path: &str) -> Result<Self> {
+        let text = vfs.read_to_string(path)?;
+        Self::parse(&text).with_context(|| format!("failed to parse replay {:?}", path))
+    }
+
+    pub fn save(&self, vfs: &Vfs, path: &str) -> Result<()> {
+        vfs.write_bytes(path, self.encode().as_bytes())
+    }
+
+    /// Frames `frames` en CSV : une ligne par entité et par tick
+    /// (`tick,id,name,x,y,inputs`, les entrées séparées par `|`).
+    pub fn export_csv(&self, frames: Range<usize>) -> String {
+        let mut csv = String::from("tick,id,name,x,y,inputs\n");
+        let end = frames.end.min(self.frames.len());
+        for frame in &self.frames[frames.start.min(end)..end] {
+            let inputs = frame.inputs.join("|");
+            for entity in &frame.entities {
+                csv.push_str(&format!(
+                    "{},{},\"{}\",{},{},{}\n",
+                    frame.tick,
+                    entity.id,
+                    entity.name.replace('"', "\"\""),
+                    entity.position.x,
+                    entity.position.y,
+                    inputs
+                ));
+            }
+        }
+        csv
+    }
+}
+
+/// Outil de l'éditeur : enregistre la scène, rejoue un replay avec une timeline et dessine
+/// les trajectoires.
+pub struct ReplayViewer {
+    vfs: Arc<Vfs>,
+    /// Chemin VFS du replay (chargement / sauvegarde).
+    path: String,
+    replay: Option<Replay>,
+    /// Index de la frame affichée.
+    frame: usize,
+    playing: bool,
+    /// Vitesse de lecture (1 = temps réel).
+    speed: f32,
+    /// Temps écoulé depuis la dernière frame lue / enregistrée.
+    elapsed: f32,
+    /// Nombre de frames de trajectoire dessinées avant la frame affichée (0 : tout).
+    trail: usize,
+    selected: Option<u64>,
+    recording: Option<Replay>,
+    export_path: String,
+    export_range: (usize, usize),
+    status: String,
+}
+
+impl ReplayViewer {
+    /// Fréquence d'enregistrement.
+    pub const TICK_RATE: f32 = 30.0;
+
+    pub fn new(vfs: Arc<Vfs>) -> Self {
+        Self {
+            vfs,
+            path: format!("replays/session.{}", Replay::EXTENSION),
+            replay: None,
+            frame: 0,
+            playing: false,
+            speed: 1.0,
+            elapsed: 0.0,
+            trail: 60,
+            selected: None,
+            recording: None,
+            export_path: "replays/export.csv".to_string(),
+            export_range: (0, 0),
+            status: String::new(),
+        }
+    }
+
+    pub fn replay(&self) -> Option<&Replay> {
+        self.replay.as_ref()
+    }
+
+    pub fn current_frame(&self) -> Option<&ReplayFrame> {
+        self.replay.as_ref()?.frames.get(self.frame)
+    }
+
+    pub fn open(&mut self, path: &str) -> Result<()> {
+        let replay = Replay::load(&self.vfs, path)?;
+        self.path = path.to_string();
+        self.set_replay(replay);
+        Ok(())
+    }
+
+    pub fn set_replay(&mut self, replay: Replay) {
+        self.export_range = (0, replay.len().saturating_sub(1));
+        self.replay = Some(replay);
+        self.frame = 0;
+        self.playing = false;
+        self.elapsed = 0.0;
+        self.selected = None;
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    pub fn start_recording(&mut self) {
+        self.recording = Some(Replay::new(Self::TICK_RATE));
+        self.elapsed = 0.0;
+    }
+
+    /// Arrête l'enregistrement, sauvegarde le replay dans `path` et l'ouvre.
+    pub fn stop_recording(&mut self) -> Result<()> {
+        let Some(replay) = self.recording.take() else {
+            return Ok(());
+        };
+        replay.save(&self.vfs, &self.path)?;
+        self.set_replay(replay);
+        Ok(())
+    }
+
+    /// À appeler chaque frame : enregistre la scène à `TICK_RATE` si l'enregistrement est
+    /// en cours, avance la lecture sinon.
+    pub fn update(&mut self, delta_time: f32, scene: &Scene, inputs: impl FnOnce() -> Vec<String>) {
+        if let Some(recording) = &mut self.recording {
+            self.elapsed += delta_time;
+            let period = 1.0 / recording.tick_rate;
+            if self.elapsed >= period || recording.is_empty() {
+                self.elapsed = (self.elapsed - period).max(0.0) % period;
+                recording.capture(scene, inputs());
+            }
+            return;
+        }
+        let Some(replay) = &self.replay else {
+            return;
+        };
+        if !self.playing || replay.is_empty() {
+            return;
+        }
+        self.elapsed += delta_time * self.speed;
+        let period = 1.0 / replay.tick_rate;
+        while self.elapsed >= period {
+            self.elapsed -= period;
+            if self.frame + 1 >= replay.len() {
+                self.playing = false;
+                self.elapsed = 0.0;
+                break;
+            }
+            self.frame += 1;
+        }
+    }
+
+    /// Dessine les trajectoires jusqu'à la frame affichée et la position courante des
+    /// entités. L'entité sélectionnée est dessinée en entier et en surbrillance.
+    pub fn draw(&self, debug_draw: &DebugDraw) {
+        let (Some(replay), Some(frame)) = (&self.replay, self.current_frame()) else {
+            return;
+        };
+        let end = self.frame + 1;
+        let start = match self.trail {
+            0 => 0,
+            trail => end.saturating_sub(trail),
+        };
+        for entity in &frame.entities {
+            let selected = self.selected == Some(entity.id);
+            let (color, range) = match selected {
+                true => ([1.0, 0.85, 0.2, 1.0], 0..replay.len()),
+                false => ([0.3, 0.8, 1.0, 0.6], start..end),
+            };
+            let points = replay.trajectory(entity.id, range);
+            for segment in points.windows(2) {
+                debug_draw.line(segment[0], segment[1], color);
+            }
+            debug_draw.add(
+                DebugShape::circle(entity.position, if selected { 6.0 } else { 4.0 })
+                    .color(color)
+                    .filled(),
+            );
+        }
+    }
+
+    /// Exporte les frames `export_range` (incluses) dans `export_path`.
+    pub fn export(&self) -> Result<usize> {
+        let replay = self
+            .replay
+            .as_ref()
+            .ok_or_else(|| anyhow!("no replay loaded"))?;
+        let (first, last) = self.export_range;
+        let range = first.min(last)..first.max(last) + 1;
+        let count = range.len().min(replay.len().saturating_sub(range.start));
+        self.vfs
+            .write_bytes(&self.export_path, replay.export_csv(range).as_bytes())?;
+        Ok(count)
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Replay");
+            ui.text_edit_singleline(&mut self.path);
+            if ui.button("Open").clicked() {
+                let path = self.path.clone();
+                self.status = match self.open(&path) {
+                    Ok(()) => format!(
+                        "Loaded {} frames",
+                        self.replay.as_ref().map_or(0, Replay::len)
+                    ),
+                    Err(e) => format!("{:#}", e),
+                };
+            }
+            let record_label = if self.is_recording() {
+                "Stop recording"
+            } else {
+                "Record"
+            };
+            if ui.button(record_label).clicked() {
+                if self.is_recording() {
+                    self.status = match self.stop_recording() {
+                        Ok(()) => format!("Saved {}", self.path),
+                        Err(e) => format!("{:#}", e),
+                    };
+                } else {
+                    self.start_recording();
+                    self.status = "Recording...".to_string();
+                }
+            }
+        });
+        if let Some(recording) = &self.recording {
+            ui.label(format!("Recorded frames: {}", recording.len()));
+        }
+        if !self.status.is_empty() {
+            ui.label(egui::RichText::new(&self.status).weak());
+        }
+
+        let Some(replay) = &self.replay else {
+            ui.label("No replay loaded");
+            return;
+        };
+        if replay.is_empty() {
+            ui.label("Empty replay");
+            return;
+        }
+        let last = replay.len() - 1;
+        let entities = replay.entities();
+        ui.separator();
+
+        // Timeline
+        ui.horizontal(|ui| {
+            if ui.button("|<").clicked() {
+                self.frame = 0;
+            }
+            if ui.button("<").clicked() {
+                self.frame = self.frame.saturating_sub(1);
+            }
+            if ui
+                .button(if self.playing { "Pause" } else { "Play" })
+                .clicked()
+            {
+                if !self.playing && self.frame == last {
+                    self.frame = 0;
+                }
+                self.playing = !self.playing;
+            }
+            if ui.button(">").clicked() {
+                self.frame = (self.frame + 1).min(last);
+            }
+            if ui.button(">|").clicked() {
+                self.frame = last;
+            }
+            ui.add(
+                egui::DragValue::new(&mut self.speed)
+                    .range(0.05..=8.0)
+                    .speed(0.05)
+                    .suffix("x"),
+            );
+        });
+        let timeline = ui.add(
+            egui::Slider::new(&mut self.frame, 0..=last)
+                .text("frame")
+                .clamping(egui::SliderClamping::Always),
+        );
+        if timeline.dragged() {
+            self.playing = false;
+        }
+        if let Some(frame) = replay.frames.get(self.frame) {
+            ui.label(format!(
+                "Tick {} ({:.2}s)",
+                frame.tick,
+                frame.tick as f32 / replay.tick_rate
+            ));
+            ui.label(format!(
+                "Inputs: {}",
+                if frame.inputs.is_empty() {
+                    "-".to_string()
+                } else {
+                    frame.inputs.join(", ")
+                }
+            ));
+        }
+        ui.add(egui::Slider::new(&mut self.trail, 0..=600).text("trail (0 = all)"));
+
+        ui.separator();
+        egui::ScrollArea::vertical()
+            .max_height(160.0)
+            .show(ui, |ui| {
+                for (id, name) in &entities {
+                    let selected = self.selected == Some(*id);
+                    if ui
+                        .selectable_label(selected, format!("{} ({})", name, id))
+                        .clicked()
+                    {
+                        self.selected = if selected { None } else { Some(*id) };
+                    }
+                }
+            });
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label("Export frames");
+            ui.add(egui::DragValue::new(&mut self.export_range.0).range(0..=last));
+            ui.label("to");
+            ui.add(egui::DragValue::new(&mut self.export_range.1).range(0..=last));
+        });
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut self.export_path);
+            if ui.button("Export CSV").clicked() {
+                self.status = match self.export() {
+                    Ok(count) => format!("Exported {} frames to {}", count, self.export_path),
+                    Err(e) => format!("{:#}", e),
+                };
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replays_round_trip_and_give_trajectories() {
+        let text = "replay 30\n\
+                    tick 0\n\
+                    input Space\n\
+                    entity 7 0 0 Player One\n\
+                    tick 1\n\
+                    entity 7 1.5 -2 Player One\n\
+                    entity 9 4 4 Enemy\n\
+                    tick 2\n\
+                    entity 7 3 -4 Player One\n";
+        let replay = Replay::parse(text).unwrap();
+        assert_eq!(replay.tick_rate, 30.0);
+        assert_eq!(replay.len(), 3);
+        assert_eq!(replay.frames[0].inputs, ["Space"]);
+        assert_eq!(replay.frames[1].entities[0].name, "Player One");
+        assert_eq!(Replay::parse(&replay.encode()).unwrap(), replay);
+
+        assert_eq!(
+            replay.entities(),
+            [(7, "Player One".to_string()), (9, "Enemy".to_string())]
+        );
+        assert_eq!(
+            replay.trajectory(7, 1..10),
+            [Vec2::new(1.5, -2.0), Vec2::new(3.0, -4.0)]
+        );
+        assert_eq!(replay.trajectory(9, 0..3).len(), 1);
+
+        let csv = replay.export_csv(0..1);
+        assert_eq!(csv.lines().nth(1), Some("0,7,\"Player One\",0,0,Space"));
+
+        assert!(Replay::parse("replay 30\nentity 1 0 0 A").is_err());
+        assert!(Replay::parse("tick 0").is_err());
+    }
+}
@@ -0,0 +1,280 @@
+//! Rembobinage de la simulation pour le débogage.
+//!
+//! Le `RewindBuffer` garde les `capacity` derniers ticks de la scène (copies profondes des
+//! entités, comme le presse-papiers) à fréquence fixe. Quand un bug apparaît, on met en
+//! pause et on recule / avance tick par tick : la scène est restaurée à chaque pas. Reprendre
+//! repart du tick affiché (les ticks suivants sont oubliés).
+//!
+//! Les entités restaurées sont recréées : les `Entity` gardées ailleurs (sélection de
+//! l'éditeur...) ne sont plus valides, et les spawners repartent de leur première vague.
+
+use std::collections::VecDeque;
+
+use hecs::Entity;
+
+use crate::{EntitySnapshot, Parent, Scene};
+
+/// État de la scène à un tick.
+#[derive(Clone)]
+pub struct SceneSnapshot {
+    pub tick: u64,
+    /// Entités racines (sans `Parent`) avec leurs descendants.
+    pub entities: Vec<EntitySnapshot>,
+}
+
+impl SceneSnapshot {
+    pub fn capture(scene: &Scene, tick: u64) -> Self {
+        let roots: Vec<Entity> = scene
+            .world
+            .query::<Option<&Parent>>()
+            .iter()
+            .filter(|(_, parent)| parent.is_none())
+            .map(|(entity, _)| entity)
+            .collect();
+        Self {
+            tick,
+            entities: roots
+                .into_iter()
+                .filter_map(|entity| scene.snapshot(entity))
+                .collect(),
+        }
+    }
+
+    /// Remplace toutes les entités de `scene` par celles du snapshot.
+    pub fn restore(&self, scene: &mut Scene) {
+        scene.clear();
+        for entity in &self.entities {
+            scene.instantiate(entity, None);
+        }
+        scene.propagate_transforms();
+    }
+
+    /// Format texte du presse-papiers d'entités (voir `EntitySnapshot::encode`).
+    pub fn encode(&self) -> String {
+        EntitySnapshot::encode(&self.entities)
+    }
+}
+
+/// Historique circulaire des derniers ticks de la scène, avec sa fenêtre de débogage.
+pub struct RewindBuffer {
+    capacity: usize,
+    tick_rate: f32,
+    frames: VecDeque<SceneSnapshot>,
+    next_tick: u64,
+    elapsed: f32,
+    /// Index dans `frames` du tick affiché, si la simulation est en pause.
+    paused_at: Option<usize>,
+}
+
+impl RewindBuffer {
+    /// Ticks gardés par défaut : 4 secondes à `TICK_RATE`.
+    pub const DEFAULT_CAPACITY: usize = 120;
+    pub const TICK_RATE: f32 = 30.0;
+
+    pub fn new(capacity: usize, tick_rate: f32) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            tick_rate,
+            frames: VecDeque::new(),
+            next_tick: 0,
+            elapsed: 0.0,
+            paused_at: None,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Ticks gardés, du plus ancien au plus récent.
+    pub fn frames(&self) -> impl Iterator<Item = &SceneSnapshot> {
+        self.frames.iter()
+    }
+
+    /// `true` pendant l'inspection : la simulation (spawners, gameplay) doit être figée.
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    /// Tick affiché pendant la pause.
+    pub fn current(&self) -> Option<&SceneSnapshot> {
+        self.frames.get(self.paused_at?)
+    }
+
+    /// Enregistre un tick (le plus ancien est oublié au-delà de `capacity`).
+    pub fn record(&mut self, scene: &Scene) {
+        self.push(SceneSnapshot::capture(scene, self.next_tick));
+    }
+
+    fn push(&mut self, snapshot: SceneSnapshot) {
+        if self.frames.len() >= self.capacity {
+            self.frames.pop_front();
+        }
+        self.next_tick = snapshot.tick + 1;
+        self.frames.push_back(snapshot);
+    }
+
+    /// À appeler chaque frame : enregistre la scène à `tick_rate`, sauf en pause.
+    pub fn update(&mut self, delta_time: f32, scene: &Scene) {
+        if self.is_paused() {
+            return;
+        }
+        self.elapsed += delta_time;
+        let period = 1.0 / self.tick_rate;
+        if self.elapsed >= period || self.frames.is_empty() {
+            self.elapsed = (self.elapsed - period).max(0.0) % period;
+            self.record(scene);
+        }
+    }
+
+    /// Met en pause sur le dernier tick enregistré (sans toucher à la scène).
+    pub fn pause(&mut self) {
+        if !self.frames.is_empty() {
+            self.paused_at = Some(self.frames.len() - 1);
+        }
+    }
+
+    /// Affiche le tick `index` de l'historique (borné) : met en pause et restaure la scène.
+    pub fn seek(&mut self, index: usize, scene: &mut Scene) {
+        let Some(last) = self.frames.len().checked_sub(1) else {
+            return;
+        };
+        let index = index.min(last);
+        self.paused_at = Some(index);
+        self.frames[index].restore(scene);
+    }
+
+    /// Recule (`delta < 0`) ou avance de `delta` ticks.
+    pub fn step(&mut self, delta: isize, scene: &mut Scene) {
+        let current = self
+            .paused_at
+            .unwrap_or(self.frames.len().saturating_sub(1));
+        self.seek(current.saturating_add_signed(delta), scene);
+    }
+
+    /// Reprend la simulation depuis le tick affiché : les ticks suivants sont oubliés.
+    pub fn resume(&mut self) {
+        if let Some(index) = self.paused_at.take() {
+            self.frames.truncate(index + 1);
+            if let Some(frame) = self.frames.back() {
+                self.next_tick = frame.tick + 1;
+            }
+            self.elapsed = 0.0;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.frames.clear();
+        self.paused_at = None;
+        self.elapsed = 0.0;
+    }
+
+    /// Fenêtre de débogage. Retourne `true` si la scène a été restaurée (les `Entity`
+    /// gardées par l'appelant ne sont plus valides).
+    pub fn ui(&mut self, ui: &mut egui::Ui, scene: &mut Scene) -> bool {
+        let mut restored = false;
+        ui.horizontal(|ui| {
+            if self.is_paused() {
+                if ui.button("Resume").clicked() {
+                    self.resume();
+                }
+            } else if ui
+                .add_enabled(!self.frames.is_empty(), egui::Button::new("Pause"))
+                .clicked()
+            {
+                self.pause();
+            }
+            let paused = self.is_paused();
+            if ui
+                .add_enabled(!self.frames.is_empty(), egui::Button::new("<"))
+                .on_hover_text("Step back one tick")
+                .clicked()
+            {
+                self.step(-1, scene);
+                restored = true;
+            }
+            if ui
+                .add_enabled(paused, egui::Button::new(">"))
+                .on_hover_text("Step forward one tick")
+                .clicked()
+            {
+                self.step(1, scene);
+                restored = true;
+            }
+            if ui.button("Clear").clicked() {
+                self.clear();
+            }
+        });
+
+        if let Some(last) = self.frames.len().checked_sub(1) {
+            let mut index = self.paused_at.unwrap_or(last);
+            let slider = egui::Slider::new(&mut index, 0..=last).text("tick");
+            if ui.add(slider).changed() {
+                self.seek(index, scene);
+                restored = true;
+            }
+        }
+
+        match self.current() {
+            Some(frame) => {
+                ui.label(format!(
+                    "Tick {} ({} root entities)",
+                    frame.tick,
+                    frame.entities.len()
+                ));
+                if ui
+                    .button("Copy entities")
+                    .on_hover_text("Copy this tick in the entity clipboard format")
+                    .clicked()
+                {
+                    ui.ctx().copy_text(frame.encode());
+                }
+            }
+            None => {
+                ui.label(format!(
+                    "Recording: {} / {} ticks at {} Hz",
+                    self.frames.len(),
+                    self.capacity,
+                    self.tick_rate
+                ));
+            }
+        }
+        restored
+    }
+}
+
+impl Default for RewindBuffer {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_CAPACITY, Self::TICK_RATE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn history_is_bounded_and_resume_drops_later_ticks() {
+        let mut buffer = RewindBuffer::new(3, 10.0);
+        for tick in 0..5 {
+            buffer.push(SceneSnapshot {
+                tick,
+                entities: Vec::new(),
+            });
+        }
+        assert_eq!(buffer.len(), 3);
+        buffer.pause();
+        assert_eq!(buffer.current().map(|frame| frame.tick), Some(4));
+
+        buffer.paused_at = Some(1);
+        buffer.resume();
+        assert!(!buffer.is_paused());
+        let ticks: Vec<u64> = buffer.frames().map(|frame| frame.tick).collect();
+        assert_eq!(ticks, [2, 3]);
+        assert_eq!(buffer.next_tick, 4);
+    }
+}
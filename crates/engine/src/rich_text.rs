@@ -0,0 +1,516 @@
+//! Texte enrichi : balises légères dans les textes du HUD et des dialogues pour colorer,
+//! graisser ou animer une partie du texte, ou y insérer une icône de l'atlas.
+//!
+//! ```text
+//! [color=#ffcc00]Or[/color] : [b]120[/b]  [icon=coin]  [wave]Bravo ![/wave]
+//! ```
+//!
+//! Balises : `[color=#rrggbb]` (ou `#rrggbbaa`, ou un nom : `red`, `gold`...), `[b]`, `[i]`,
+//! `[wave]`, `[shake]`, fermées par `[/nom]` ou `[/]` (dernière balise ouverte), et
+//! `[icon=région]`. `[[` écrit un `[`. Une balise inconnue reste dans le texte telle quelle.
+//!
+//! Le texte est mis en forme par `TextShaper` (arabe lié, texte bidirectionnel) : les
+//! parties gardent leur style quel que soit leur ordre d'affichage. Les emoji sont dessinés
+//! en couleur si une police d'emoji en couleur est enregistrée (`COLOR_EMOJI_FAMILY`), sinon
+//! avec les emoji monochromes d'egui ; `replace_emoji` peut aussi remplacer ceux qui ont une
+//! région dans l'atlas des icônes (planche Twemoji / Noto Emoji, voir `emoji_region`).
+//!
+//! Le gras utilise la famille de police `BOLD_FAMILY` si elle est enregistrée dans egui
+//! (sinon le texte reste normal) ; l'italique est penché au dessin.
+
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::Arc,
+};
+
+use crate::{GlyphStyle, TextLayoutCache, TextRun, TextureAtlas};
+
+/// Caractère d'une icône dans le texte mis en forme.
+const OBJECT: char = '\u{fffc}';
+
+/// Animation d'une partie du texte, caractère par caractère.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TextEffect {
+    /// Les caractères ondulent verticalement.
+    Wave,
+    /// Les caractères tremblent au hasard.
+    Shake,
+}
+
+impl TextEffect {
+    /// Décalage du caractère `index` au temps `time` (secondes), pour une police de `size`
+    /// points.
+    pub fn offset(self, index: usize, time: f64, size: f32) -> egui::Vec2 {
+        match self {
+            TextEffect::Wave => {
+                let phase = time * 6.0 - index as f64 * 0.6;
+                egui::vec2(0.0, phase.sin() as f32 * size * 0.15)
+            }
+            TextEffect::Shake => {
+                // Nouvelle position 20 fois par seconde
+                let mut hasher = DefaultHasher::new();
+                (index, (time * 20.0) as u64).hash(&mut hasher);
+                let hash = hasher.finish();
+                let unit = |bits: u64| (bits & 0xffff) as f32 / 0xffff as f32 * 2.0 - 1.0;
+                egui::vec2(unit(hash), unit(hash >> 16)) * size * 0.08
+            }
+        }
+    }
+}
+
+/// Style d'une partie du texte.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SpanStyle {
+    /// `None` : couleur par défaut du texte.
+    pub color: Option<egui::Color32>,
+    pub bold: bool,
+    pub italic: bool,
+    pub effect: Option<TextEffect>,
+}
+
+/// Partie d'un texte enrichi.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MarkupSpan {
+    Text {
+        text: String,
+        style: SpanStyle,
+    },
+    /// Icône (région d'atlas, voir `MarkupIcons`), de la hauteur du texte.
+    Icon {
+        name: String,
+        style: SpanStyle,
+    },
+}
+
+/// Texte découpé en parties de même style.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MarkupText {
+    pub spans: Vec<MarkupSpan>,
+}
+
+/// Famille de police utilisée par `[b]`, à enregistrer dans les `FontDefinitions` egui.
+pub const BOLD_FAMILY: &str = "bold";
+
+impl MarkupText {
+    /// Découpe `source` selon ses balises. Ne peut pas échouer : les balises inconnues ou mal
+    /// formées sont gardées comme texte, les balises non fermées durent jusqu'à la fin.
+    pub fn parse(source: &str) -> Self {
+        let mut markup = Self::default();
+        // Balises ouvertes (nom, style à l'intérieur)
+        let mut stack: Vec<(String, SpanStyle)> = Vec::new();
+        let mut text = String::new();
+        let mut rest = source;
+
+        while let Some(start) = rest.find('[') {
+            text.push_str(&rest[..start]);
+            let tag = &rest[start..];
+            if let Some(after) = tag.strip_prefix("[[") {
+                text.push('[');
+                rest = after;
+                continue;
+            }
+            let Some(end) = tag.find(']') else {
+                text.push('[');
+                rest = &tag[1..];
+                continue;
+            };
+            let (inner, after) = (&tag[1..end], &tag[end + 1..]);
+            let style = stack.last().map(|(_, style)| *style).unwrap_or_default();
+
+            if let Some(name) = inner.strip_prefix('/') {
+                let open = match name.trim() {
+                    "" => stack.len().checked_sub(1),
+                    name => stack.iter().rposition(|(open, _)| open == name),
+                };
+                if let Some(index) = open {
+                    markup.push_text(&mut text, style);
+                    stack.truncate(index);
+                    rest = after;
+                    continue;
+                }
+            } else if let Some(name) = inner.strip_prefix("icon=") {
+                markup.push_text(&mut text, style);
+                markup.spans.push(MarkupSpan::Icon {
+                    name: name.trim().to_string(),
+                    style,
+                });
+                rest = after;
+                continue;
+            } else if let Some((name, inner_style)) = Self::open_tag(inner, style) {
+                markup.push_text(&mut text, style);
+                stack.push((name.to_string(), inner_style));
+                rest = after;
+                continue;
+            }
+
+            // Balise inconnue : gardée comme texte
+            text.push('[');
+            rest = &tag[1..];
+        }
+        text.push_str(rest);
+        let style = stack.last().map(|(_, style)| *style).unwrap_or_default();
+        markup.push_text(&mut text, style);
+        markup
+    }
+
+    /// Nom et style d'une balise ouvrante (`None` si inconnue).
+    fn open_tag(inner: &str, mut style: SpanStyle) -> Option<(&str, SpanStyle)> {
+        let (name, value) = match inner.split_once('=') {
+            Some((name, value)) => (name.trim(), Some(value.trim())),
+            None => (inner.trim(), None),
+        };
+        match (name, value) {
+            ("color", Some(value)) => style.color = Some(parse_color(value)?),
+            ("b", None) => style.bold = true,
+            ("i", None) => style.italic = true,
+            ("wave", None) => style.effect = Some(TextEffect::Wave),
+            ("shake", None) => style.effect = Some(TextEffect::Shake),
+            _ => return None,
+        }
+        Some((name, style))
+    }
+
+    fn push_text(&mut self, text: &mut String, style: SpanStyle) {
+        if text.is_empty() {
+            return;
+        }
+        let text = std::mem::take(text);
+        // Parties consécutives de même style fusionnées
+        if let Some(MarkupSpan::Text {
+            text: previous,
+            style: previous_style,
+        }) = self.spans.last_mut()
+            && *previous_style == style
+        {
+            previous.push_str(&text);
+            return;
+        }
+        self.spans.push(MarkupSpan::Text { text, style });
+    }
+
+    /// Texte sans balises ni icônes (lecteurs d'écran, presse-papiers).
+    pub fn plain_text(&self) -> String {
+        self.spans
+            .iter()
+            .filter_map(|span| match span {
+                MarkupSpan::Text { text, .. } => Some(text.as_str()),
+                MarkupSpan::Icon { .. } => None,
+            })
+            .collect()
+    }
+
+    /// Remplace les emoji du texte par des icônes, pour ceux dont la région (voir
+    /// `emoji_region`) existe selon `has_icon`. Les séquences les plus longues (drapeaux,
+    /// couleurs de peau, séquences ZWJ) sont essayées d'abord. Les emoji ne prennent pas la
+    /// couleur du texte.
+    pub fn replace_emoji(&mut self, has_icon: impl Fn(&str) -> bool) {
+        /// Plus longue séquence essayée (famille ZWJ à 4 personnes + sélecteurs).
+        const MAX_SEQUENCE: usize = 11;
+
+        let mut spans = Vec::with_capacity(self.spans.len());
+        for span in self.spans.drain(..) {
+            let MarkupSpan::Text { text, style } = span else {
+                spans.push(span);
+                continue;
+            };
+            let chars: Vec<(usize, char)> = text.char_indices().collect();
+            let (mut start, mut index) = (0, 0);
+            while index < chars.len() {
+                let emoji = (!chars[index].1.is_ascii())
+                    .then(|| {
+                        (1..=MAX_SEQUENCE.min(chars.len() - index))
+                            .rev()
+                            .find_map(|len| {
+                                let end = chars.get(index + len).map_or(text.len(), |c| c.0);
+                                let name = emoji_region(&text[chars[index].0..end]);
+                                has_icon(&name).then_some((len, name))
+                            })
+                    })
+                    .flatten();
+                let Some((len, name)) = emoji else {
+                    index += 1;
+                    continue;
+                };
+                let offset = chars[index].0;
+                if start < offset {
+                    spans.push(MarkupSpan::Text {
+                        text: text[start..offset].to_string(),
+                        style,
+                    });
+                }
+                let style = SpanStyle {
+                    color: None,
+                    ..style
+                };
+                spans.push(MarkupSpan::Icon { name, style });
+                index += len;
+                start = chars.get(index).map_or(text.len(), |c| c.0);
+            }
+            if start < text.len() {
+                spans.push(MarkupSpan::Text {
+                    text: text[start..].to_string(),
+                    style,
+                });
+            }
+        }
+        self.spans = spans;
+    }
+
+    pub fn has_effects(&self) -> bool {
+        self.spans.iter().any(|span| span.style().effect.is_some())
+    }
+
+    /// Texte à mettre en forme et ses parties : une par partie du texte enrichi, les icônes
+    /// étant des objets (U+FFFC) de leur largeur.
+    fn runs(
+        &self,
+        ctx: &egui::Context,
+        font: &egui::FontId,
+        icons: Option<&MarkupIcons>,
+    ) -> (String, Vec<TextRun>) {
+        let mut text = String::new();
+        let mut runs = Vec::with_capacity(self.spans.len());
+        for span in &self.spans {
+            match span {
+                MarkupSpan::Text { text: part, style } => {
+                    text.push_str(part);
+                    runs.push(TextRun::new(text.len(), family(ctx, font, *style)));
+                }
+                MarkupSpan::Icon { name, .. } => {
+                    let width = icons
+                        .and_then(|icons| icons.icon(name))
+                        .map_or(font.size, |(_, aspect)| font.size * aspect);
+                    text.push(OBJECT);
+                    runs.push(TextRun::object(text.len(), width));
+                }
+            }
+        }
+        (text, runs)
+    }
+
+    /// Dessine le texte dans `ui` (mise en forme gardée dans `cache`), coupé à la largeur
+    /// disponible. `icons` : atlas des balises `[icon=...]`, qui laissent un blanc sans atlas
+    /// ou si la région n'existe pas.
+    pub fn show(
+        &self,
+        ui: &mut egui::Ui,
+        cache: &mut TextLayoutCache,
+        font: &egui::FontId,
+        color: egui::Color32,
+        icons: Option<&MarkupIcons>,
+    ) -> egui::Response {
+        let ctx = ui.ctx().clone();
+        let (text, runs) = self.runs(&ctx, font, icons);
+        let shaped = cache.layout_runs(&ctx, &text, &runs, font.size, ui.available_width());
+        let (rect, response) = ui.allocate_exact_size(shaped.size, egui::Sense::hover());
+        let painter = ui.painter();
+        let time = ctx.input(|input| input.time);
+        // Les caractères animés bougent dans l'ordre d'affichage
+        let offset = |index: usize, style: SpanStyle| {
+            style.effect.map_or(egui::Vec2::ZERO, |effect| {
+                effect.offset(index, time, font.size)
+            })
+        };
+
+        cache.paint(painter, rect.min, &shaped, |index, glyph| {
+            let style = self.spans[glyph.run].style();
+            GlyphStyle {
+                color: style.color.unwrap_or(color),
+                offset: offset(index, style),
+                italic: style.italic,
+            }
+        });
+
+        for (index, glyph) in shaped.glyphs.iter().enumerate() {
+            if let MarkupSpan::Icon { name, style } = &self.spans[glyph.run]
+                && let Some(icons) = icons
+                && let Some((uv, _)) = icons.icon(name)
+            {
+                let row = shaped.rows[glyph.row].rect;
+                let size = egui::vec2(glyph.advance, font.size);
+                let min = rect.min
+                    + egui::vec2(glyph.pos.x, row.center().y - size.y * 0.5)
+                    + offset(index, *style);
+                painter.image(
+                    icons.texture,
+                    egui::Rect::from_min_size(min, size),
+                    uv,
+                    style.color.unwrap_or(egui::Color32::WHITE),
+                );
+            }
+        }
+
+        if self.has_effects() {
+            ctx.request_repaint();
+        }
+        response
+    }
+}
+
+impl MarkupSpan {
+    pub fn style(&self) -> SpanStyle {
+        match self {
+            MarkupSpan::Text { style, .. } | MarkupSpan::Icon { style, .. } => *style,
+        }
+    }
+}
+
+/// Famille de police d'une partie du texte.
+fn family(ctx: &egui::Context, font: &egui::FontId, style: SpanStyle) -> egui::FontFamily {
+    if style.bold {
+        let bold = egui::FontFamily::Name(BOLD_FAMILY.into());
+        if ctx.fonts(|fonts| fonts.families().contains(&bold)) {
+            return bold;
+        }
+    }
+    font.family.clone()
+}
+
+/// Couleur `#rrggbb`, `#rrggbbaa` ou nommée.
+fn parse_color(value: &str) -> Option<egui::Color32> {
+    if let Some(hex) = value.strip_prefix('#') {
+        let channel = |index: usize| u8::from_str_radix(hex.get(index..index + 2)?, 16).ok();
+        return match hex.len() {
+            6 => Some(egui::Color32::from_rgb(
+                channel(0)?,
+                channel(2)?,
+                channel(4)?,
+            )),
+            8 => Some(egui::Color32::from_rgba_unmultiplied(
+                channel(0)?,
+                channel(2)?,
+                channel(4)?,
+                channel(6)?,
+            )),
+            _ => None,
+        };
+    }
+    Some(match value.to_lowercase().as_str() {
+        "white" => egui::Color32::WHITE,
+        "black" => egui::Color32::BLACK,
+        "gray" | "grey" => egui::Color32::GRAY,
+        "red" => egui::Color32::RED,
+        "green" => egui::Color32::GREEN,
+        "blue" => egui::Color32::BLUE,
+        "yellow" => egui::Color32::YELLOW,
+        "gold" => egui::Color32::GOLD,
+        "orange" => egui::Color32::ORANGE,
+        _ => return None,
+    })
+}
+
+/// Nom de la région d'atlas d'un emoji : ses points de code en hexadécimal, séparés par
+/// des tirets, sans sélecteur de variante (U+FE0F), comme les fichiers de Twemoji
+/// ("1f600", "1f44d-1f3fd", "1f1eb-1f1f7").
+pub fn emoji_region(sequence: &str) -> String {
+    sequence
+        .chars()
+        .filter(|&c| c != '\u{fe0f}')
+        .map(|c| format!("{:x}", c as u32))
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Icônes des balises `[icon=...]` : régions d'un atlas dont la texture est enregistrée
+/// auprès du renderer egui (`egui_wgpu::Renderer::register_native_texture`).
+#[derive(Clone)]
+pub struct MarkupIcons {
+    pub texture: egui::TextureId,
+    pub atlas: Arc<TextureAtlas>,
+}
+
+impl MarkupIcons {
+    pub fn new(texture: egui::TextureId, atlas: Arc<TextureAtlas>) -> Self {
+        Self { texture, atlas }
+    }
+
+    /// UV de la région `name` et son rapport largeur / hauteur.
+    pub fn icon(&self, name: &str) -> Option<(egui::Rect, f32)> {
+        let region = self.atlas.region(name)?;
+        let [u0, v0, u1, v1] = region.uv;
+        let aspect = region.width as f32 / region.height.max(1) as f32;
+        Some((
+            egui::Rect::from_min_max(egui::pos2(u0, v0), egui::pos2(u1, v1)),
+            aspect,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tags_split_the_text_into_styled_spans() {
+        let markup =
+            MarkupText::parse("[color=#ff0000]Or [b]120[/b][/color] [icon=coin] [wave]ok[/] [[x]");
+        let red = Some(egui::Color32::from_rgb(255, 0, 0));
+        let text = |text: &str, style: SpanStyle| MarkupSpan::Text {
+            text: text.to_string(),
+            style,
+        };
+        assert_eq!(
+            markup.spans,
+            vec![
+                text(
+                    "Or ",
+                    SpanStyle {
+                        color: red,
+                        ..Default::default()
+                    }
+                ),
+                text(
+                    "120",
+                    SpanStyle {
+                        color: red,
+                        bold: true,
+                        ..Default::default()
+                    }
+                ),
+                text(" ", SpanStyle::default()),
+                MarkupSpan::Icon {
+                    name: "coin".to_string(),
+                    style: SpanStyle::default(),
+                },
+                text(" ", SpanStyle::default()),
+                text(
+                    "ok",
+                    SpanStyle {
+                        effect: Some(TextEffect::Wave),
+                        ..Default::default()
+                    }
+                ),
+                text(" [x]", SpanStyle::default()),
+            ]
+        );
+        assert!(markup.has_effects());
+
+        // Emoji de l'atlas, séquence la plus longue d'abord ; les autres restent du texte
+        let mut markup = MarkupText::parse("[color=red]GG 👍🏽 ❤️é[/color]");
+        assert_eq!(emoji_region("❤️"), "2764");
+        markup.replace_emoji(|name| ["1f44d", "1f44d-1f3fd", "2764"].contains(&name));
+        let icon = |name: &str| MarkupSpan::Icon {
+            name: name.to_string(),
+            style: SpanStyle::default(),
+        };
+        let red = SpanStyle {
+            color: Some(egui::Color32::RED),
+            ..Default::default()
+        };
+        assert_eq!(
+            markup.spans,
+            vec![
+                text("GG ", red),
+                icon("1f44d-1f3fd"),
+                text(" ", red),
+                icon("2764"),
+                text("é", red),
+            ]
+        );
+
+        // Balises inconnues et fermetures orphelines gardées comme texte
+        let markup = MarkupText::parse("[size=3]a[/b] [color=nope]");
+        assert_eq!(markup.plain_text(), "[size=3]a[/b] [color=nope]");
+        assert!(!markup.has_effects());
+    }
+}
@@ -1,22 +1,185 @@
+use anyhow::{Result, bail};
 use egui_wgpu::wgpu;
+use wgpu::naga;
+
+use crate::Vfs;
+
+/// Erreur de chargement / compilation d'un shader. Les erreurs naga contiennent le
+/// diagnostic complet (fichier, ligne, extrait de la source).
+#[derive(Debug, thiserror::Error)]
+pub enum ShaderError {
+    #[error("failed to read shader {path:?}: {message}")]
+    Read { path: String, message: String },
+    #[error("failed to parse shader {label:?}:\n{diagnostic}")]
+    Parse { label: String, diagnostic: String },
+    #[error("invalid shader {label:?}:\n{diagnostic}")]
+    Validation { label: String, diagnostic: String },
+    /// Refusé par wgpu malgré la validation naga (ex: fonctionnalité non supportée).
+    #[error("shader {label:?} rejected by the device: {message}")]
+    Device { label: String, message: String },
+}
 
 pub struct Shader {
     shader: wgpu::ShaderModule,
+    label: String,
 }
 
 impl Shader {
-    pub fn from_wgsl(device: &wgpu::Device, label: &str, path: &str) -> Self {
-        let shader_source = std::fs::read_to_string(path).unwrap();
+    /// Label du shader de secours (voir `Shader::fallback`).
+    pub const FALLBACK_LABEL: &str = "fallback_shader";
+
+    /// Source du shader de secours : un triangle plein écran magenta, sans entrée de sommet
+    /// ni bind group, donc compatible avec n'importe quel pipeline utilisant les points
+    /// d'entrée `vs_main` / `fs_main`.
+    pub const FALLBACK_SOURCE: &str = "\
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    return vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+    return vec4<f32>(1.0, 0.0, 1.0, 1.0);
+}
+";
+
+    /// Charge un shader WGSL depuis le disque (hors VFS).
+    pub fn from_wgsl(device: &wgpu::Device, label: &str, path: &str) -> Result<Self, ShaderError> {
+        let shader_source = std::fs::read_to_string(path).map_err(|err| ShaderError::Read {
+            path: path.to_string(),
+            message: err.to_string(),
+        })?;
+
+        Self::compile(device, label, &shader_source)
+    }
+
+    /// Charge un shader WGSL via le VFS (ex: "engine/shaders/sprite.wgsl").
+    /// Le chemin VFS sert aussi de label pour le debug.
+    pub fn from_vfs(device: &wgpu::Device, vfs: &Vfs, path: &str) -> Result<Self, ShaderError> {
+        let shader_source = Self::read_vfs(vfs, path)?;
+
+        Self::compile(device, path, &shader_source)
+    }
+
+    /// Comme `from_vfs`, mais une erreur (lecture ou compilation) est loggée et remplacée par
+    /// le shader de secours (voir `compile_or_fallback`).
+    pub fn from_vfs_or_fallback(device: &wgpu::Device, vfs: &Vfs, path: &str) -> Self {
+        match Self::read_vfs(vfs, path) {
+            Ok(source) => Self::compile_or_fallback(device, path, &source),
+            Err(err) => Self::fallback_after(device, path, err),
+        }
+    }
+
+    fn read_vfs(vfs: &Vfs, path: &str) -> Result<String, ShaderError> {
+        vfs.read_to_string(path).map_err(|err| ShaderError::Read {
+            path: path.to_string(),
+            message: format!("{:#}", err),
+        })
+    }
+
+    /// Comme `from_source`, mais la source est d'abord validée : une erreur de compilation
+    /// est retournée (message naga avec la ligne fautive) au lieu de faire paniquer wgpu.
+    pub fn compile(device: &wgpu::Device, label: &str, source: &str) -> Result<Self, ShaderError> {
+        validate_wgsl(label, source)?;
+        catch_validation_errors(device, || Self::from_source(device, label, source)).map_err(
+            |err| ShaderError::Device {
+                label: label.to_string(),
+                message: format!("{:#}", err),
+            },
+        )
+    }
 
+    /// Comme `compile`, mais une erreur est loggée et remplacée par le shader de secours :
+    /// l'objet est dessiné en magenta au lieu d'arrêter l'application. Le shader de secours
+    /// garde `label`, pour que ses pipelines en cache soient invalidés quand le fichier est
+    /// corrigé (`PipelineCache::invalidate_shader`).
+    pub fn compile_or_fallback(device: &wgpu::Device, label: &str, source: &str) -> Self {
+        Self::compile(device, label, source)
+            .unwrap_or_else(|err| Self::fallback_after(device, label, err))
+    }
+
+    fn fallback_after(device: &wgpu::Device, label: &str, err: ShaderError) -> Self {
+        log::error!("{}; using the fallback shader", err);
+        Self::from_source(device, label, Self::FALLBACK_SOURCE)
+    }
+
+    /// Shader de secours magenta (voir `FALLBACK_SOURCE`).
+    pub fn fallback(device: &wgpu::Device) -> Self {
+        Self::from_source(device, Self::FALLBACK_LABEL, Self::FALLBACK_SOURCE)
+    }
+
+    /// Compile un shader WGSL à partir de sa source, sans validation préalable : une source
+    /// invalide fait paniquer wgpu (voir `compile`).
+    pub fn from_source(device: &wgpu::Device, label: &str, source: &str) -> Self {
         let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some(label),
-            source: wgpu::ShaderSource::Wgsl(shader_source.into()),
+            source: wgpu::ShaderSource::Wgsl(source.into()),
         });
 
-        Self { shader }
+        Self {
+            shader,
+            label: label.to_string(),
+        }
     }
 
     pub fn module(&self) -> &wgpu::ShaderModule {
         &self.shader
     }
+
+    /// Label de création : le chemin VFS pour `from_vfs` (clé de `PipelineCache`).
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+}
+
+/// Parse et valide une source WGSL avec naga (le compilateur utilisé par wgpu).
+pub fn validate_wgsl(label: &str, source: &str) -> Result<(), ShaderError> {
+    let module = naga::front::wgsl::parse_str(source).map_err(|err| ShaderError::Parse {
+        label: label.to_string(),
+        diagnostic: err.emit_to_string_with_path(source, label),
+    })?;
+    naga::valid::Validator::new(
+        naga::valid::ValidationFlags::all(),
+        naga::valid::Capabilities::all(),
+    )
+    .validate(&module)
+    .map_err(|err| ShaderError::Validation {
+        label: label.to_string(),
+        diagnostic: err.emit_to_string_with_path(source, label),
+    })?;
+    Ok(())
+}
+
+/// Exécute `create` dans un error scope de validation : les erreurs wgpu (ex: pipeline qui ne
+/// correspond plus au shader) sont retournées au lieu de passer par le handler global, qui
+/// panique. Utilisé pour recréer des pipelines à chaud.
+pub fn catch_validation_errors<T>(device: &wgpu::Device, create: impl FnOnce() -> T) -> Result<T> {
+    device.push_error_scope(wgpu::ErrorFilter::Validation);
+    let value = create();
+    if let Some(err) = pollster::block_on(device.pop_error_scope()) {
+        bail!("{}", err);
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_wgsl_is_reported() {
+        let valid = "@fragment fn fs_main() -> @location(0) vec4<f32> { return vec4<f32>(1.0); }";
+        assert!(validate_wgsl("valid.wgsl", valid).is_ok());
+
+        assert!(validate_wgsl(Shader::FALLBACK_LABEL, Shader::FALLBACK_SOURCE).is_ok());
+
+        let err = validate_wgsl("broken.wgsl", "fn main() { let x: f32 = ; }").unwrap_err();
+        assert!(matches!(err, ShaderError::Parse { .. }));
+        assert!(err.to_string().contains("broken.wgsl"));
+        // Type correct, mais sortie de fragment sans `@location` : rejeté par le validateur
+        let source = "@fragment fn fs_main() -> vec4<f32> { return vec4<f32>(1.0); }";
+        let err = validate_wgsl("unbound.wgsl", source).unwrap_err();
+        assert!(matches!(err, ShaderError::Validation { .. }));
+    }
 }
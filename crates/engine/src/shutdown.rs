@@ -0,0 +1,130 @@
+//! Arrêt ordonné du moteur (`Engine::shutdown`). Les subsystèmes enregistrent des hooks
+//! (`Engine::on_shutdown`) dans une étape ; les étapes s'exécutent dans l'ordre :
+//!
+//! 1. `FlushWrites` : écritures en attente (sauvegardes, `Analytics::register_shutdown`),
+//!    puis attente des lectures / écritures en cours de l'`AssetLoader` ;
+//! 2. `StopAudio` ;
+//! 3. `JoinWorkers` : threads de travail (`WorldStreamer::register_shutdown`...) ;
+//! 4. les fenêtres libèrent leurs ressources GPU (`Window::on_shutdown`), puis sont détruites
+//!    avec leur surface ;
+//! 5. `Final` : dernier nettoyage, puis rapport des ressources GPU encore vivantes.
+//!
+//! Un hook en erreur est loggé sans interrompre l'arrêt.
+
+use anyhow::Result;
+
+/// Étape de l'arrêt, dans l'ordre d'exécution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ShutdownStage {
+    FlushWrites,
+    StopAudio,
+    JoinWorkers,
+    /// Après la destruction des fenêtres (device et surfaces).
+    Final,
+}
+
+type ShutdownHook = Box<dyn FnOnce() -> Result<()> + Send>;
+
+/// Hooks d'arrêt, exécutés une seule fois.
+#[derive(Default)]
+pub struct ShutdownHooks {
+    hooks: Vec<(ShutdownStage, String, ShutdownHook)>,
+    done: bool,
+}
+
+impl ShutdownHooks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ajoute un hook ; ceux d'une même étape s'exécutent dans l'ordre d'ajout.
+    pub fn add(
+        &mut self,
+        stage: ShutdownStage,
+        name: impl Into<String>,
+        hook: impl FnOnce() -> Result<()> + Send + 'static,
+    ) {
+        self.hooks.push((stage, name.into(), Box::new(hook)));
+    }
+
+    /// Exécute (et retire) les hooks de `stage`. Retourne le nombre de hooks en erreur.
+    pub fn run_stage(&mut self, stage: ShutdownStage) -> usize {
+        let (run, keep): (Vec<_>, Vec<_>) = std::mem::take(&mut self.hooks)
+            .into_iter()
+            .partition(|(hook_stage, _, _)| *hook_stage == stage);
+        self.hooks = keep;
+        let mut failed = 0;
+        for (_, name, hook) in run {
+            log::info!("Shutdown ({:?}): {}", stage, name);
+            if let Err(e) = hook() {
+                log::error!("Shutdown hook {:?} failed: {:#}", name, e);
+                failed += 1;
+            }
+        }
+        failed
+    }
+
+    /// Marque l'arrêt comme commencé. Retourne `false` s'il l'était déjà.
+    pub fn begin(&mut self) -> bool {
+        !std::mem::replace(&mut self.done, true)
+    }
+
+    /// Retire les hooks (l'arrêt reste marqué comme commencé) : `Engine::shutdown` les
+    /// exécute sans garder le verrou, les hooks peuvent donc utiliser le moteur. Les hooks
+    /// ajoutés pendant l'arrêt ne sont pas exécutés.
+    pub fn take(&mut self) -> ShutdownHooks {
+        ShutdownHooks {
+            hooks: std::mem::take(&mut self.hooks),
+            done: self.done,
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    pub fn len(&self) -> usize {
+        self.hooks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hooks.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use anyhow::anyhow;
+
+    use super::*;
+
+    #[test]
+    fn stages_run_in_order_once() {
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let mut hooks = ShutdownHooks::new();
+        for (stage, name) in [
+            (ShutdownStage::JoinWorkers, "workers"),
+            (ShutdownStage::FlushWrites, "save"),
+            (ShutdownStage::FlushWrites, "analytics"),
+        ] {
+            let order = order.clone();
+            hooks.add(stage, name, move || {
+                order.lock().unwrap().push(name);
+                Ok(())
+            });
+        }
+        hooks.add(ShutdownStage::StopAudio, "audio", || {
+            Err(anyhow!("no device"))
+        });
+
+        assert!(hooks.begin());
+        assert!(!hooks.begin());
+        assert_eq!(hooks.run_stage(ShutdownStage::FlushWrites), 0);
+        assert_eq!(hooks.run_stage(ShutdownStage::StopAudio), 1);
+        assert_eq!(hooks.run_stage(ShutdownStage::JoinWorkers), 0);
+        assert_eq!(*order.lock().unwrap(), ["save", "analytics", "workers"]);
+        assert!(hooks.is_empty());
+    }
+}
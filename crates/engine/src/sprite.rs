@@ -1,22 +1,60 @@
-use std::sync::Arc;
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    ops::Range,
+    sync::{Arc, Mutex},
+};
 
+use anyhow::Result;
 use bytemuck::{Pod, Zeroable};
 use egui_wgpu::wgpu;
-use nalgebra::Matrix4;
+use nalgebra::{Matrix4, Vector3, Vector4};
 use wgpu::util::DeviceExt;
 
-use crate::{PassContext, RenderPass, Shader, Texture2D, TextureHandle, Uniforms, Vertex};
+use crate::{
+    Affine2D, AssetLoader, DrawCounts, GlobalTransform, MemoryCategory, PassContext, PipelineCache,
+    PipelineKey, PlayerHud, RenderPass, RenderTarget, Scene, ScreenSpace, Shader, SpriteComponent,
+    SpriteMesh, Texture2D, TextureArray, TextureAtlas, TextureHandle, TrackedMemory, Transform,
+    Uniforms, Vec2, Vertex, catch_validation_errors,
+};
 
 /// Per-instance data uploaded to the GPU for instanced draws.
 #[repr(C)]
 #[derive(Copy, Clone, Pod, Zeroable)]
 pub struct InstanceData {
     pub model: [[f32; 4]; 4],
+    /// UV sub-rectangle [u0, v0, u1, v1] sampled by this instance (see `Sprite::uv`).
+    pub uv_rect: [f32; 4],
+    /// Depth written when the renderer has a depth buffer (see `SpriteRenderer::layer_depth`).
+    pub depth: f32,
+    /// Layer of the texture array sampled by this instance (array batches only, see
+    /// `SpritePass::enable_texture_arrays`).
+    pub texture_layer: u32,
+    /// RGBA multiplier applied to the sampled texel (see `Sprite::tint`).
+    pub tint: [f32; 4],
+    /// Colors of the quad corners (top-left, top-right, bottom-right, bottom-left) packed as
+    /// RGBA8 (see `pack_color`), interpolated across the quad and multiplied with `tint`.
+    pub corner_colors: [u32; 4],
 }
 
 impl InstanceData {
+    /// `corner_colors` of an instance without gradient.
+    pub const WHITE_CORNERS: [u32; 4] = [u32::MAX; 4];
+
+    /// Pack an RGBA color (components clamped to [0, 1]) as read by WGSL's
+    /// `unpack4x8unorm`: red in the lowest byte.
+    pub fn pack_color(color: [f32; 4]) -> u32 {
+        color
+            .iter()
+            .enumerate()
+            .map(|(i, c)| ((c.clamp(0.0, 1.0) * 255.0).round() as u32) << (8 * i))
+            .sum()
+    }
+
     pub fn layout<'a>() -> wgpu::VertexBufferLayout<'a> {
-        // A mat4 is 4 vec4 attributes. We expose them as locations 2..5.
+        // A mat4 is 4 vec4 attributes. We expose them as locations 2..5, then the UV rect at 6,
+        // the depth at 7, the texture array layer at 8, the tint at 9 and the corner colors
+        // at 10.
         wgpu::VertexBufferLayout {
             array_stride: std::mem::size_of::<InstanceData>() as wgpu::BufferAddress,
             step_mode: wgpu::VertexStepMode::Instance,
@@ -45,13 +83,210 @@ impl InstanceData {
                     shader_location: 5,
                     format: wgpu::VertexFormat::Float32x4,
                 },
+                // uv rect
+                wgpu::VertexAttribute {
+                    offset: (std::mem::size_of::<[f32; 4]>() * 4) as wgpu::BufferAddress,
+                    shader_location: 6,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                // depth
+                wgpu::VertexAttribute {
+                    offset: (std::mem::size_of::<[f32; 4]>() * 5) as wgpu::BufferAddress,
+                    shader_location: 7,
+                    format: wgpu::VertexFormat::Float32,
+                },
+                // texture array layer
+                wgpu::VertexAttribute {
+                    offset: (std::mem::size_of::<[f32; 4]>() * 5 + std::mem::size_of::<f32>())
+                        as wgpu::BufferAddress,
+                    shader_location: 8,
+                    format: wgpu::VertexFormat::Uint32,
+                },
+                // tint
+                wgpu::VertexAttribute {
+                    offset: (std::mem::size_of::<[f32; 4]>() * 5 + std::mem::size_of::<f32>() * 2)
+                        as wgpu::BufferAddress,
+                    shader_location: 9,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                // corner colors
+                wgpu::VertexAttribute {
+                    offset: (std::mem::size_of::<[f32; 4]>() * 6 + std::mem::size_of::<f32>() * 2)
+                        as wgpu::BufferAddress,
+                    shader_location: 10,
+                    format: wgpu::VertexFormat::Uint32x4,
+                },
             ],
         }
     }
 }
 
+/// Nine-slice rendering: the corners keep their size, the edges stretch along one axis and
+/// the center along both, so panels and speech bubbles can be resized without distortion.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NineSlice {
+    /// Border insets in texture pixels: [left, top, right, bottom].
+    pub border: [f32; 4],
+    /// Drawn size in world units. The sprite's transform should not be scaled (the scale
+    /// would stretch the corners too); its translation / rotation still apply.
+    pub size: (f32, f32),
+}
+
+/// One of the 9 quads of a nine-slice sprite.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NineSliceCell {
+    /// Position and size in the sprite's local space: [x, y, width, height].
+    pub rect: [f32; 4],
+    /// UV sub-rectangle [u0, v0, u1, v1].
+    pub uv: [f32; 4],
+}
+
+impl NineSlice {
+    pub fn new(border: [f32; 4], size: (f32, f32)) -> Self {
+        Self { border, size }
+    }
+
+    /// Split `size` and the `uv` region of a `texture_size` texture into the 9 cells
+    /// (row by row, top-left first). Borders larger than the size shrink proportionally;
+    /// empty cells (zero border) are skipped. Flips mirror the cells and their UVs.
+    pub fn cells(
+        &self,
+        uv: [f32; 4],
+        texture_size: (u32, u32),
+        flip_x: bool,
+        flip_y: bool,
+    ) -> Vec<NineSliceCell> {
+        let [left, top, right, bottom] = self.border.map(|b| b.max(0.0));
+        let (width, height) = (self.size.0.max(0.0), self.size.1.max(0.0));
+        let [u0, v0, u1, v1] = uv;
+        let (texture_width, texture_height) =
+            (texture_size.0.max(1) as f32, texture_size.1.max(1) as f32);
+
+        // Bornes des 3 colonnes / lignes, à l'écran et dans la texture
+        let fit = |a: f32, b: f32, total: f32| {
+            let scale = if a + b > total { total / (a + b) } else { 1.0 };
+            [0.0, a * scale, total - b * scale, total]
+        };
+        let xs = fit(left, right, width);
+        let ys = fit(top, bottom, height);
+        let us = [
+            u0,
+            u0 + left / texture_width,
+            u1 - right / texture_width,
+            u1,
+        ];
+        let vs = [
+            v0,
+            v0 + top / texture_height,
+            v1 - bottom / texture_height,
+            v1,
+        ];
+
+        let mut cells = Vec::with_capacity(9);
+        for row in 0..3 {
+            for column in 0..3 {
+                let (cell_width, cell_height) =
+                    (xs[column + 1] - xs[column], ys[row + 1] - ys[row]);
+                if cell_width <= 0.0 || cell_height <= 0.0 {
+                    continue;
+                }
+
+                let (mut x, mut y) = (xs[column], ys[row]);
+                let mut cell_uv = [us[column], vs[row], us[column + 1], vs[row + 1]];
+                if flip_x {
+                    x = width - x - cell_width;
+                    cell_uv.swap(0, 2);
+                }
+                if flip_y {
+                    y = height - y - cell_height;
+                    cell_uv.swap(1, 3);
+                }
+                cells.push(NineSliceCell {
+                    rect: [x, y, cell_width, cell_height],
+                    uv: cell_uv,
+                });
+            }
+        }
+        cells
+    }
+}
+
+/// How a sprite's color is combined with the target. `SpriteRenderer` keeps one pipeline
+/// per mode; sprites of different modes never share a batch.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum BlendMode {
+    /// Regular transparency (straight alpha).
+    #[default]
+    Alpha,
+    /// Color added to the target, weighted by alpha: glows, fire, particles.
+    Additive,
+    /// Target multiplied by the color: shadows, tinted glass. Transparent texels should be
+    /// white, as alpha does not weaken the effect.
+    Multiply,
+    /// Transparency for textures whose color is already multiplied by alpha.
+    Premultiplied,
+}
+
+impl BlendMode {
+    pub const ALL: [BlendMode; 4] = [
+        BlendMode::Alpha,
+        BlendMode::Additive,
+        BlendMode::Multiply,
+        BlendMode::Premultiplied,
+    ];
+
+    /// Lowercase name, as written in entity clipboard text.
+    pub fn name(self) -> &'static str {
+        match self {
+            BlendMode::Alpha => "alpha",
+            BlendMode::Additive => "additive",
+            BlendMode::Multiply => "multiply",
+            BlendMode::Premultiplied => "premultiplied",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|mode| mode.name() == name)
+    }
+
+    /// Blend state of the pipeline drawing this mode. Except for `Alpha` and
+    /// `Premultiplied`, the target alpha is left unchanged.
+    pub fn blend_state(self) -> wgpu::BlendState {
+        let keep_alpha = wgpu::BlendComponent {
+            src_factor: wgpu::BlendFactor::Zero,
+            dst_factor: wgpu::BlendFactor::One,
+            operation: wgpu::BlendOperation::Add,
+        };
+        match self {
+            BlendMode::Alpha => wgpu::BlendState::ALPHA_BLENDING,
+            BlendMode::Premultiplied => wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING,
+            BlendMode::Additive => wgpu::BlendState {
+                color: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::SrcAlpha,
+                    dst_factor: wgpu::BlendFactor::One,
+                    operation: wgpu::BlendOperation::Add,
+                },
+                alpha: keep_alpha,
+            },
+            BlendMode::Multiply => wgpu::BlendState {
+                color: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::Dst,
+                    dst_factor: wgpu::BlendFactor::Zero,
+                    operation: wgpu::BlendOperation::Add,
+                },
+                alpha: keep_alpha,
+            },
+        }
+    }
+
+    /// Index of the mode in `ALL`.
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
 /// Sprite descriptor referencing a `Texture2D`.
-/// Keeps per-sprite metadata (for now minimal; can be extended: uv rect, tint, pivot, etc.).
+/// Keeps per-sprite metadata (uv rect, tint, flips, draw order...).
 #[derive(Clone)]
 pub struct Sprite {
     pub texture: Arc<Texture2D>,
@@ -60,6 +295,33 @@ pub struct Sprite {
     pub uv: [f32; 4],
     /// Optional logical size override (if you want sprites to have different logical size than texture)
     pub size: Option<(f32, f32)>,
+    /// Draw layer: higher layers are drawn on top of lower ones (e.g. ground 0, characters 10, UI 100).
+    pub layer: i32,
+    /// Within its layer, sort by the bottom edge ("feet") of the sprite: sprites lower on
+    /// screen are drawn in front. Sprites without `y_sort` keep their submission order and
+    /// are drawn before the y-sorted ones of the same layer.
+    pub y_sort: bool,
+    /// RGBA color multiplied with the texture (white = unchanged). Used for damage flashes,
+    /// team colors, fades...
+    pub tint: [f32; 4],
+    /// Colors of the corners (top-left, top-right, bottom-right, bottom-left) interpolated
+    /// across the sprite and multiplied with `tint`: gradients on UI panels, stylized
+    /// lighting. `None` = white.
+    pub corner_colors: Option<[[f32; 4]; 4]>,
+    /// How the sprite is blended with what is already drawn (additive for glows, particles...).
+    pub blend: BlendMode,
+    /// Mirror the sprite horizontally / vertically (the UV rect is flipped, the quad is not;
+    /// a tight `mesh` is mirrored instead).
+    pub flip_x: bool,
+    pub flip_y: bool,
+    /// Draw the sprite as a stretchable nine-slice instead of a single quad.
+    pub nine_slice: Option<NineSlice>,
+    /// Tangent-space normal map lit by the `LightingPass` (same layout as `texture`, loaded
+    /// with `Texture2D::from_bytes_linear`). Its alpha should match the sprite's.
+    pub normal_map: Option<Arc<Texture2D>>,
+    /// Draw this mesh instead of the full quad (see `SpriteMesh`), to skip the transparent
+    /// parts of large images. Ignored for nine-slice sprites.
+    pub mesh: Option<Arc<SpriteMesh>>,
 }
 
 impl Sprite {
@@ -69,7 +331,126 @@ impl Sprite {
             texture,
             uv: [0.0, 0.0, 1.0, 1.0],
             size: None,
+            layer: 0,
+            y_sort: false,
+            tint: [1.0; 4],
+            corner_colors: None,
+            blend: BlendMode::Alpha,
+            flip_x: false,
+            flip_y: false,
+            nine_slice: None,
+            normal_map: None,
+            mesh: None,
+        }
+    }
+
+    /// Builder-style setter for `layer`.
+    pub fn with_layer(mut self, layer: i32) -> Self {
+        self.layer = layer;
+        self
+    }
+
+    /// Builder-style setter for `y_sort`.
+    pub fn with_y_sort(mut self, y_sort: bool) -> Self {
+        self.y_sort = y_sort;
+        self
+    }
+
+    /// Builder-style setter for `tint`.
+    pub fn with_tint(mut self, tint: [f32; 4]) -> Self {
+        self.tint = tint;
+        self
+    }
+
+    /// Builder-style setter for `corner_colors`.
+    pub fn with_corner_colors(mut self, corners: [[f32; 4]; 4]) -> Self {
+        self.corner_colors = Some(corners);
+        self
+    }
+
+    /// Vertical gradient from `top` to `bottom` (see `corner_colors`).
+    pub fn with_vertical_gradient(self, top: [f32; 4], bottom: [f32; 4]) -> Self {
+        self.with_corner_colors([top, top, bottom, bottom])
+    }
+
+    /// Horizontal gradient from `left` to `right` (see `corner_colors`).
+    pub fn with_horizontal_gradient(self, left: [f32; 4], right: [f32; 4]) -> Self {
+        self.with_corner_colors([left, right, right, left])
+    }
+
+    /// Color of the gradient at `[x, y]` in the sprite's quad ([0, 0] = top-left,
+    /// [1, 1] = bottom-right), before flips.
+    pub fn corner_color_at(&self, point: [f32; 2]) -> [f32; 4] {
+        match &self.corner_colors {
+            Some(corners) => gradient_color(corners, point),
+            None => [1.0; 4],
+        }
+    }
+
+    /// Builder-style setter for `blend`.
+    pub fn with_blend(mut self, blend: BlendMode) -> Self {
+        self.blend = blend;
+        self
+    }
+
+    /// Builder-style setter for `flip_x` / `flip_y`.
+    pub fn with_flip(mut self, flip_x: bool, flip_y: bool) -> Self {
+        self.flip_x = flip_x;
+        self.flip_y = flip_y;
+        self
+    }
+
+    /// Builder-style setter for `normal_map`.
+    pub fn with_normal_map(mut self, normal_map: Arc<Texture2D>) -> Self {
+        self.normal_map = Some(normal_map);
+        self
+    }
+
+    /// Builder-style setter for `mesh`.
+    pub fn with_mesh(mut self, mesh: Arc<SpriteMesh>) -> Self {
+        self.mesh = Some(mesh);
+        self
+    }
+
+    /// Builder-style setter for `nine_slice`: `border` insets in texture pixels
+    /// ([left, top, right, bottom]), drawn at `size` world units.
+    pub fn with_nine_slice(mut self, border: [f32; 4], size: (f32, f32)) -> Self {
+        self.nine_slice = Some(NineSlice::new(border, size));
+        self
+    }
+
+    /// UV rect actually sampled: `uv` with the flips applied.
+    pub fn flipped_uv(&self) -> [f32; 4] {
+        let [mut u0, mut v0, mut u1, mut v1] = self.uv;
+        if self.flip_x {
+            std::mem::swap(&mut u0, &mut u1);
+        }
+        if self.flip_y {
+            std::mem::swap(&mut v0, &mut v1);
         }
+        [u0, v0, u1, v1]
+    }
+
+    /// Create a sprite from a named region of a `TextureAtlas`, with the region's tight mesh
+    /// if it has one. The sprite shares the atlas texture, so all its regions batch into one
+    /// draw call (as long as they use the same mesh).
+    pub fn from_atlas_region(atlas: &TextureAtlas, name: &str) -> Option<Self> {
+        let region = atlas.region(name)?;
+        Some(Self {
+            texture: atlas.texture().clone(),
+            uv: region.uv,
+            size: Some((region.width as f32, region.height as f32)),
+            layer: 0,
+            y_sort: false,
+            tint: [1.0; 4],
+            corner_colors: None,
+            blend: BlendMode::Alpha,
+            flip_x: false,
+            flip_y: false,
+            nine_slice: None,
+            normal_map: None,
+            mesh: atlas.mesh(name).cloned(),
+        })
     }
 
     /// Convenience: load texture from file and wrap in a Sprite.
@@ -105,14 +486,40 @@ impl Sprite {
     pub fn texture_size(&self) -> (u32, u32) {
         (self.texture.width, self.texture.height)
     }
+
+    /// The mesh actually drawn instead of the quad: `mesh`, unless the sprite is a nine-slice.
+    pub(crate) fn tight_mesh(&self) -> Option<&Arc<SpriteMesh>> {
+        self.mesh.as_ref().filter(|_| self.nine_slice.is_none())
+    }
 }
 
 // ============================================================================
 // SpriteRenderer (unchanged behavior - still owns pipeline, instance buffer, etc.)
 // ============================================================================
 
+/// Vertices and indices of a tight mesh in the mesh buffers of a `SpriteRenderer`.
+#[derive(Clone)]
+struct MeshRange {
+    base_vertex: i32,
+    indices: Range<u32>,
+}
+
+/// Tight meshes drawn by a `SpriteRenderer` (see `SpriteRenderer::add_mesh`), packed into
+/// one vertex and one index buffer.
+#[derive(Default)]
+struct SpriteMeshes {
+    /// Keyed by `Arc<SpriteMesh>` pointer; the `Arc` is kept so the key cannot be reused by
+    /// another mesh while registered.
+    ranges: HashMap<usize, (Arc<SpriteMesh>, MeshRange)>,
+    /// Vertex and index buffers, `None` while no mesh has any triangle.
+    buffers: Option<(wgpu::Buffer, wgpu::Buffer, TrackedMemory)>,
+    /// Meshes were added or removed since the buffers were built.
+    dirty: bool,
+}
+
 pub struct SpriteRenderer {
-    pub pipeline: wgpu::RenderPipeline,
+    /// One pipeline per `BlendMode`, in `BlendMode::ALL` order.
+    pipelines: Vec<wgpu::RenderPipeline>,
     pub texture_bind_layout: wgpu::BindGroupLayout, // @group(1) - texture + sampler
     pub uniform_bind_layout: wgpu::BindGroupLayout, // @group(0) - uniforms
     pub uniform_buffer: wgpu::Buffer,
@@ -123,10 +530,56 @@ pub struct SpriteRenderer {
     // Instance buffer for batching
     pub instance_buffer: wgpu::Buffer,
     pub instance_capacity: usize,
+    instance_memory: TrackedMemory,
+
+    /// Format of the depth attachment the pipeline tests/writes, if any.
+    depth_format: Option<wgpu::TextureFormat>,
+    target_format: wgpu::TextureFormat,
+
+    /// Pipelines (one per `BlendMode`) and @group(1) layout sampling a `TextureArray`
+    /// (see `enable_texture_arrays`).
+    array_pipeline: Option<(Vec<wgpu::RenderPipeline>, wgpu::BindGroupLayout)>,
+
+    meshes: SpriteMeshes,
 }
 
 impl SpriteRenderer {
-    pub fn new(device: &wgpu::Device, target_format: wgpu::TextureFormat) -> Self {
+    /// Number of instances the instance buffer can hold before its first growth.
+    pub const INITIAL_INSTANCE_CAPACITY: usize = 1024;
+
+    /// Path of the built-in sprite shader, resolved through the engine VFS.
+    pub const SHADER_PATH: &str = "engine/shaders/sprite.wgsl";
+
+    /// Path of the texture-array variant of the sprite shader.
+    pub const ARRAY_SHADER_PATH: &str = "engine/shaders/sprite_array.wgsl";
+
+    /// Alpha below which fragments are discarded when a depth buffer is used, so the
+    /// transparent parts of a sprite do not hide what is behind them.
+    pub const DEPTH_ALPHA_CUTOFF: f64 = 0.5;
+
+    /// Layers are clamped to this range when converted to depth.
+    pub const MIN_DEPTH_LAYER: i32 = -1024;
+    pub const MAX_DEPTH_LAYER: i32 = 1023;
+
+    #[track_caller]
+    pub fn new(
+        device: &wgpu::Device,
+        target_format: wgpu::TextureFormat,
+        loader: &AssetLoader,
+    ) -> Result<Self> {
+        Self::with_depth_format(device, target_format, None, loader)
+    }
+
+    /// Like `new`, with an optional depth attachment of format `depth_format`.
+    /// With depth, the pipeline tests and writes the depth of each sprite's layer
+    /// (`LessEqual`: higher layers win, equal layers keep painter order) and discards
+    /// fragments under `DEPTH_ALPHA_CUTOFF`.
+    pub fn with_depth_format(
+        device: &wgpu::Device,
+        target_format: wgpu::TextureFormat,
+        depth_format: Option<wgpu::TextureFormat>,
+        loader: &AssetLoader,
+    ) -> Result<Self> {
         // ========================================================================
         // BIND GROUP 0 : Uniforms (matrice de transformation)
         // ========================================================================
@@ -148,75 +601,25 @@ impl SpriteRenderer {
         // ========================================================================
         // BIND GROUP 1 : Texture + Sampler
         // ========================================================================
-        let texture_bind_layout =
-            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                label: Some("texture_bind_group_layout"),
-                entries: &[
-                    wgpu::BindGroupLayoutEntry {
-                        binding: 0,
-                        visibility: wgpu::ShaderStages::FRAGMENT,
-                        ty: wgpu::BindingType::Texture {
-                            multisampled: false,
-                            view_dimension: wgpu::TextureViewDimension::D2,
-                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
-                        },
-                        count: None,
-                    },
-                    wgpu::BindGroupLayoutEntry {
-                        binding: 1,
-                        visibility: wgpu::ShaderStages::FRAGMENT,
-                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
-                        count: None,
-                    },
-                ],
-            });
-
-        // Shader
-        let shader = Shader::from_wgsl(
+        let texture_bind_layout = Self::create_texture_bind_layout(
             device,
-            "sprite_shader",
-            r"C:\Users\bubbl\Desktop\gena\assets\shader.wgsl",
+            "texture_bind_group_layout",
+            wgpu::TextureViewDimension::D2,
         );
 
-        // ========================================================================
-        // PIPELINE LAYOUT : Déclare les 2 bind groups dans l'ORDRE
-        // @group(0) = uniforms, @group(1) = texture
-        // ========================================================================
-        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-            label: Some("sprite_pipeline_layout"),
-            bind_group_layouts: &[
-                &uniform_bind_layout, // @group(0)
-                &texture_bind_layout, // @group(1)
-            ],
-            push_constant_ranges: &[],
-        });
+        // Shader
+        let shader = loader.load_shader_or_fallback(Self::SHADER_PATH, device);
 
-        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("sprite_pipeline"),
-            layout: Some(&pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &shader.module(),
-                entry_point: Some("vs_main"),
-                // include instance attributes as a second buffer
-                buffers: &[Vertex::layout(), InstanceData::layout()],
-                compilation_options: wgpu::PipelineCompilationOptions::default(),
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &shader.module(),
-                entry_point: Some("fs_main"),
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: target_format,
-                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-                compilation_options: wgpu::PipelineCompilationOptions::default(),
-            }),
-            primitive: wgpu::PrimitiveState::default(),
-            depth_stencil: None,
-            multisample: wgpu::MultisampleState::default(),
-            multiview: None,
-            cache: None,
-        });
+        let pipelines = Self::create_pipelines(
+            device,
+            loader.pipeline_cache(),
+            "sprite_pipeline",
+            &shader,
+            &uniform_bind_layout,
+            &texture_bind_layout,
+            target_format,
+            depth_format,
+        );
 
         // ========================================================================
         // Créer le buffer d'uniforms et son bind group
@@ -259,18 +662,14 @@ impl SpriteRenderer {
         });
 
         // ========================================================================
-        // Instance buffer (start with a reasonable default capacity)
+        // Instance buffer (start with a reasonable default capacity, grows on demand)
         // ========================================================================
-        let instance_capacity = 1024usize;
-        let empty_instances = vec![InstanceData::zeroed(); instance_capacity];
-        let instance_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("instance_buffer"),
-            contents: bytemuck::cast_slice(&empty_instances),
-            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
-        });
+        let instance_capacity = Self::INITIAL_INSTANCE_CAPACITY;
+        let (instance_buffer, instance_memory) =
+            Self::create_instance_buffer(device, instance_capacity);
 
-        Self {
-            pipeline,
+        Ok(Self {
+            pipelines,
             texture_bind_layout,
             uniform_bind_layout,
             quad_vertex,
@@ -279,31 +678,492 @@ impl SpriteRenderer {
             uniform_bind_group,
             instance_buffer,
             instance_capacity,
+            instance_memory,
+            depth_format,
+            target_format,
+            array_pipeline: None,
+            meshes: SpriteMeshes::default(),
+        })
+    }
+
+    /// @group(1) layout: binding 0 = texture of dimension `view_dimension`, binding 1 = sampler.
+    pub(crate) fn create_texture_bind_layout(
+        device: &wgpu::Device,
+        label: &str,
+        view_dimension: wgpu::TextureViewDimension,
+    ) -> wgpu::BindGroupLayout {
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some(label),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        })
+    }
+
+    /// One pipeline per `BlendMode`, in `BlendMode::ALL` order, shared through `cache` with
+    /// the other renderers using the same configuration.
+    #[allow(clippy::too_many_arguments)]
+    fn create_pipelines(
+        device: &wgpu::Device,
+        cache: &Mutex<PipelineCache>,
+        label: &str,
+        shader: &Shader,
+        uniform_bind_layout: &wgpu::BindGroupLayout,
+        texture_bind_layout: &wgpu::BindGroupLayout,
+        target_format: wgpu::TextureFormat,
+        depth_format: Option<wgpu::TextureFormat>,
+    ) -> Vec<wgpu::RenderPipeline> {
+        BlendMode::ALL
+            .into_iter()
+            .map(|blend| {
+                let key = PipelineKey::new(device, shader.label(), target_format)
+                    .depth_format(depth_format)
+                    .blend(blend.blend_state())
+                    .vertex_layouts(&[Vertex::layout(), InstanceData::layout()])
+                    .constant("ALPHA_CUTOFF", Self::alpha_cutoff(depth_format));
+                cache.lock().unwrap().get_or_create(key, || {
+                    Self::create_pipeline(
+                        device,
+                        &format!("{}_{}", label, blend.name()),
+                        shader,
+                        uniform_bind_layout,
+                        texture_bind_layout,
+                        target_format,
+                        depth_format,
+                        blend,
+                    )
+                })
+            })
+            .collect()
+    }
+
+    fn alpha_cutoff(depth_format: Option<wgpu::TextureFormat>) -> f64 {
+        if depth_format.is_some() {
+            Self::DEPTH_ALPHA_CUTOFF
+        } else {
+            0.0
         }
     }
 
-    /// Dessiner des sprites (instanced). `instance_count` indique combien d'instances seront dessinées
-    /// à partir de la `instance_buffer` (commençant à 0).
+    #[allow(clippy::too_many_arguments)]
+    fn create_pipeline(
+        device: &wgpu::Device,
+        label: &str,
+        shader: &Shader,
+        uniform_bind_layout: &wgpu::BindGroupLayout,
+        texture_bind_layout: &wgpu::BindGroupLayout,
+        target_format: wgpu::TextureFormat,
+        depth_format: Option<wgpu::TextureFormat>,
+        blend: BlendMode,
+    ) -> wgpu::RenderPipeline {
+        // ========================================================================
+        // PIPELINE LAYOUT : Déclare les 2 bind groups dans l'ORDRE
+        // @group(0) = uniforms, @group(1) = texture
+        // ========================================================================
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some(&format!("{}_layout", label)),
+            bind_group_layouts: &[
+                uniform_bind_layout, // @group(0)
+                texture_bind_layout, // @group(1)
+            ],
+            push_constant_ranges: &[],
+        });
+
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(label),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: shader.module(),
+                entry_point: Some("vs_main"),
+                // include instance attributes as a second buffer
+                buffers: &[Vertex::layout(), InstanceData::layout()],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: shader.module(),
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: target_format,
+                    blend: Some(blend.blend_state()),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions {
+                    constants: &[("ALPHA_CUTOFF", Self::alpha_cutoff(depth_format))],
+                    ..Default::default()
+                },
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: depth_format.map(|format| wgpu::DepthStencilState {
+                format,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        })
+    }
+
+    /// Build the texture-array pipeline (`ARRAY_SHADER_PATH`), used by `draw_array_instanced`.
+    /// Does nothing if it already exists.
+    pub fn enable_texture_arrays(
+        &mut self,
+        device: &wgpu::Device,
+        loader: &AssetLoader,
+    ) -> Result<()> {
+        if self.array_pipeline.is_some() {
+            return Ok(());
+        }
+
+        let layout = Self::create_texture_bind_layout(
+            device,
+            "texture_array_bind_group_layout",
+            wgpu::TextureViewDimension::D2Array,
+        );
+        let shader = loader.load_shader_or_fallback(Self::ARRAY_SHADER_PATH, device);
+        let pipelines = Self::create_pipelines(
+            device,
+            loader.pipeline_cache(),
+            "sprite_array_pipeline",
+            &shader,
+            &self.uniform_bind_layout,
+            &layout,
+            self.target_format,
+            self.depth_format,
+        );
+        self.array_pipeline = Some((pipelines, layout));
+        Ok(())
+    }
+
+    /// Rebuild the pipelines using the shader at `path` (`SHADER_PATH` or, once enabled,
+    /// `ARRAY_SHADER_PATH`) after it changed on disk. Returns `false` if this renderer does
+    /// not use it. On error the current pipelines are kept.
+    pub fn reload_shader(
+        &mut self,
+        path: &str,
+        device: &wgpu::Device,
+        loader: &AssetLoader,
+    ) -> Result<bool> {
+        if path == Self::SHADER_PATH {
+            let shader = loader.load_shader(path, device)?;
+            self.pipelines = catch_validation_errors(device, || {
+                Self::create_pipelines(
+                    device,
+                    loader.pipeline_cache(),
+                    "sprite_pipeline",
+                    &shader,
+                    &self.uniform_bind_layout,
+                    &self.texture_bind_layout,
+                    self.target_format,
+                    self.depth_format,
+                )
+            })
+            .inspect_err(|_| Self::forget_pipelines(loader, path))?;
+            return Ok(true);
+        }
+
+        let Some((pipelines, layout)) = self
+            .array_pipeline
+            .as_mut()
+            .filter(|_| path == Self::ARRAY_SHADER_PATH)
+        else {
+            return Ok(false);
+        };
+        let shader = loader.load_shader(path, device)?;
+        *pipelines = catch_validation_errors(device, || {
+            Self::create_pipelines(
+                device,
+                loader.pipeline_cache(),
+                "sprite_array_pipeline",
+                &shader,
+                &self.uniform_bind_layout,
+                layout,
+                self.target_format,
+                self.depth_format,
+            )
+        })
+        .inspect_err(|_| Self::forget_pipelines(loader, path))?;
+        Ok(true)
+    }
+
+    /// Pipelines created inside a failed error scope are invalid: keep them out of the cache.
+    fn forget_pipelines(loader: &AssetLoader, shader: &str) {
+        loader
+            .pipeline_cache()
+            .lock()
+            .unwrap()
+            .invalidate_shader(shader);
+    }
+
+    /// @group(1) layout of the texture-array pipeline, once `enable_texture_arrays` was called.
+    pub fn array_texture_bind_layout(&self) -> Option<&wgpu::BindGroupLayout> {
+        self.array_pipeline.as_ref().map(|(_, layout)| layout)
+    }
+
+    /// Pipeline drawing `blend` sprites from single textures.
+    pub fn pipeline(&self, blend: BlendMode) -> &wgpu::RenderPipeline {
+        &self.pipelines[blend.index()]
+    }
+
+    pub fn depth_format(&self) -> Option<wgpu::TextureFormat> {
+        self.depth_format
+    }
+
+    /// Depth of a sprite layer in [0, 1]: higher layers are closer (smaller depth).
+    pub fn layer_depth(layer: i32) -> f32 {
+        let layer = layer.clamp(Self::MIN_DEPTH_LAYER, Self::MAX_DEPTH_LAYER);
+        let steps = (Self::MAX_DEPTH_LAYER - Self::MIN_DEPTH_LAYER + 1) as f32;
+        1.0 - ((layer - Self::MIN_DEPTH_LAYER) as f32 + 0.5) / steps
+    }
+
+    /// Allocate an instance buffer able to hold `capacity` `InstanceData` entries.
+    #[track_caller]
+    fn create_instance_buffer(
+        device: &wgpu::Device,
+        capacity: usize,
+    ) -> (wgpu::Buffer, TrackedMemory) {
+        let size = (capacity * std::mem::size_of::<InstanceData>()) as wgpu::BufferAddress;
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("instance_buffer"),
+            size,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        (
+            buffer,
+            TrackedMemory::labeled(MemoryCategory::InstanceBuffers, size, "sprite_instances"),
+        )
+    }
+
+    /// Make sure the instance buffer can hold at least `required` instances.
+    /// The capacity is doubled until it fits and the GPU buffer is recreated; previous
+    /// contents are discarded, so call this before uploading the frame's instances.
+    /// Returns `true` if the buffer was reallocated.
+    #[track_caller]
+    pub fn ensure_instance_capacity(&mut self, device: &wgpu::Device, required: usize) -> bool {
+        if required <= self.instance_capacity {
+            return false;
+        }
+
+        let mut capacity = self.instance_capacity.max(1);
+        while capacity < required {
+            capacity *= 2;
+        }
+
+        log::debug!(
+            "Growing sprite instance buffer from {} to {} instances",
+            self.instance_capacity,
+            capacity
+        );
+
+        (self.instance_buffer, self.instance_memory) =
+            Self::create_instance_buffer(device, capacity);
+        self.instance_capacity = capacity;
+        true
+    }
+
+    /// Key of `mesh` in the renderer (see `add_mesh`).
+    pub fn mesh_key(mesh: &Arc<SpriteMesh>) -> usize {
+        Arc::as_ptr(mesh) as usize
+    }
+
+    /// Register a tight mesh so instances can be drawn with it instead of the quad (see
+    /// `draw_instanced`). It is uploaded by the next `upload_meshes`. Returns its key.
+    pub fn add_mesh(&mut self, mesh: &Arc<SpriteMesh>) -> usize {
+        let key = Self::mesh_key(mesh);
+        self.meshes.ranges.entry(key).or_insert_with(|| {
+            self.meshes.dirty = true;
+            let range = MeshRange {
+                base_vertex: 0,
+                indices: 0..0,
+            };
+            (mesh.clone(), range)
+        });
+        key
+    }
+
+    /// Forget the meshes whose key does not pass `keep` (e.g. no longer drawn).
+    pub fn retain_meshes(&mut self, mut keep: impl FnMut(usize) -> bool) {
+        let count = self.meshes.ranges.len();
+        self.meshes.ranges.retain(|key, _| keep(*key));
+        self.meshes.dirty |= self.meshes.ranges.len() != count;
+    }
+
+    /// Rebuild the mesh buffers if meshes were added or removed since the last call.
+    /// Call it after registering the frame's meshes and before drawing them.
+    pub fn upload_meshes(&mut self, device: &wgpu::Device) {
+        if !std::mem::take(&mut self.meshes.dirty) {
+            return;
+        }
+
+        let mut vertices: Vec<Vertex> = Vec::new();
+        let mut indices: Vec<u16> = Vec::new();
+        for (mesh, range) in self.meshes.ranges.values_mut() {
+            let start = indices.len() as u32;
+            *range = MeshRange {
+                base_vertex: vertices.len() as i32,
+                indices: start..start + mesh.indices.len() as u32,
+            };
+            vertices.extend(mesh.vertices.iter().map(|&uv| Vertex::from_uv(uv)));
+            indices.extend_from_slice(&mesh.indices);
+        }
+
+        self.meshes.buffers = (!indices.is_empty()).then(|| {
+            let vertex = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("sprite_mesh_vertex"),
+                contents: bytemuck::cast_slice(&vertices),
+                usage: wgpu::BufferUsages::VERTEX,
+            });
+            let index = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("sprite_mesh_index"),
+                contents: bytemuck::cast_slice(&indices),
+                usage: wgpu::BufferUsages::INDEX,
+            });
+            let memory = TrackedMemory::labeled(
+                MemoryCategory::InstanceBuffers,
+                vertex.size() + index.size(),
+                "sprite_meshes",
+            );
+            (vertex, index, memory)
+        });
+    }
+
+    /// Dessiner des sprites (instanced) avec le mode de fusion `blend`. `instances` indique
+    /// la plage d'instances de `instance_buffer` à dessiner, `mesh` le mesh ajusté
+    /// (`add_mesh`) à dessiner à la place du quad.
     pub fn draw_instanced<'a>(
         &'a self,
         rpass: &mut wgpu::RenderPass<'a>,
+        blend: BlendMode,
         texture_bind_group: &'a wgpu::BindGroup,
-        instance_count: u32,
+        mesh: Option<usize>,
+        instances: Range<u32>,
     ) {
-        rpass.set_pipeline(&self.pipeline);
-        rpass.set_vertex_buffer(0, self.quad_vertex.slice(..));
-        rpass.set_vertex_buffer(1, self.instance_buffer.slice(..));
-        rpass.set_index_buffer(self.quad_index.slice(..), wgpu::IndexFormat::Uint16);
+        self.draw_geometry(
+            rpass,
+            self.pipeline(blend),
+            texture_bind_group,
+            &self.instance_buffer,
+            mesh,
+            instances,
+        );
+    }
+
+    /// Comme `draw_instanced` (fusion alpha), en lisant les instances dans `instance_buffer`
+    /// plutôt que dans le buffer du renderer (ex: buffers par chunk de `TilemapPass`).
+    pub fn draw_instances_from<'a>(
+        &'a self,
+        rpass: &mut wgpu::RenderPass<'a>,
+        texture_bind_group: &'a wgpu::BindGroup,
+        instance_buffer: &'a wgpu::Buffer,
+        instances: Range<u32>,
+    ) {
+        self.draw_with(
+            rpass,
+            self.pipeline(BlendMode::Alpha),
+            texture_bind_group,
+            instance_buffer,
+            instances,
+        );
+    }
+
+    /// Comme `draw_instanced`, avec un bind group de `TextureArray` : chaque instance
+    /// échantillonne sa couche (`InstanceData::texture_layer`).
+    /// Ne dessine rien si `enable_texture_arrays` n'a pas été appelé.
+    pub fn draw_array_instanced<'a>(
+        &'a self,
+        rpass: &mut wgpu::RenderPass<'a>,
+        blend: BlendMode,
+        array_bind_group: &'a wgpu::BindGroup,
+        mesh: Option<usize>,
+        instances: Range<u32>,
+    ) {
+        if let Some((pipelines, _)) = &self.array_pipeline {
+            self.draw_geometry(
+                rpass,
+                &pipelines[blend.index()],
+                array_bind_group,
+                &self.instance_buffer,
+                mesh,
+                instances,
+            );
+        }
+    }
+
+    /// Draw `instances` of `instance_buffer` with `pipeline`, which must use the renderer's
+    /// bind group layouts and vertex layouts (e.g. the outline mask of `OutlinePass`).
+    pub(crate) fn draw_with<'a>(
+        &'a self,
+        rpass: &mut wgpu::RenderPass<'a>,
+        pipeline: &'a wgpu::RenderPipeline,
+        texture_bind_group: &'a wgpu::BindGroup,
+        instance_buffer: &'a wgpu::Buffer,
+        instances: Range<u32>,
+    ) {
+        self.draw_geometry(
+            rpass,
+            pipeline,
+            texture_bind_group,
+            instance_buffer,
+            None,
+            instances,
+        );
+    }
+
+    /// Like `draw_with`, with the registered mesh `mesh` instead of the quad. Meshes that
+    /// are not registered fall back to the quad; empty meshes draw nothing.
+    fn draw_geometry<'a>(
+        &'a self,
+        rpass: &mut wgpu::RenderPass<'a>,
+        pipeline: &'a wgpu::RenderPipeline,
+        texture_bind_group: &'a wgpu::BindGroup,
+        instance_buffer: &'a wgpu::Buffer,
+        mesh: Option<usize>,
+        instances: Range<u32>,
+    ) {
+        let (vertex, index, base_vertex, indices) =
+            match mesh.and_then(|key| self.meshes.ranges.get(&key)) {
+                Some((_, range)) if range.indices.is_empty() => return,
+                Some((_, range)) => match &self.meshes.buffers {
+                    Some((vertex, index, _)) => {
+                        (vertex, index, range.base_vertex, range.indices.clone())
+                    }
+                    None => return,
+                },
+                None => (&self.quad_vertex, &self.quad_index, 0, 0..6),
+            };
+
+        rpass.set_pipeline(pipeline);
+        rpass.set_vertex_buffer(0, vertex.slice(..));
+        rpass.set_vertex_buffer(1, instance_buffer.slice(..));
+        rpass.set_index_buffer(index.slice(..), wgpu::IndexFormat::Uint16);
 
         // IMPORTANT : bind les 2 groupes dans l'ordre
         rpass.set_bind_group(0, &self.uniform_bind_group, &[]); // @group(0) = uniforms
         rpass.set_bind_group(1, texture_bind_group, &[]); // @group(1) = texture
 
-        if instance_count == 0 {
+        if instances.is_empty() {
             return;
         }
 
-        rpass.draw_indexed(0..6, 0, 0..instance_count);
+        rpass.draw_indexed(indices, base_vertex, instances);
     }
 
     /// Mettre à jour la matrice de transformation
@@ -319,42 +1179,655 @@ impl SpriteRenderer {
 // 4. SPRITE PASS - Une passe concrète qui utilise SpriteRenderer
 // ============================================================================
 
-/// Passe de rendu pour afficher des sprites
+/// Texture arrays of a `SpritePass`, keyed by texture size, with their bind group.
+type TextureArrays = HashMap<(u32, u32), (TextureArray, wgpu::BindGroup)>;
+
+/// One instanced draw: what it binds, how it blends, the mesh it draws (`None` for the
+/// quad) and its range in the frame's instance buffer.
+type SpriteBatch = (BatchKey, BlendMode, Option<usize>, Range<u32>);
+
+/// What a batch binds at @group(1).
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub(crate) enum BatchKey {
+    /// Bind group of a single texture (see `SpritePass::texture_key`).
+    Texture(usize),
+    /// Texture array holding the textures of this size (see `SpritePass::enable_texture_arrays`).
+    Array(u32, u32),
+}
+
+/// Bilinear interpolation of `corners` (top-left, top-right, bottom-right, bottom-left) at
+/// `[x, y]` ([0, 0] = top-left corner).
+fn gradient_color(corners: &[[f32; 4]; 4], [x, y]: [f32; 2]) -> [f32; 4] {
+    let [top_left, top_right, bottom_right, bottom_left] = *corners;
+    let mix = |a: [f32; 4], b: [f32; 4], t: f32| [0, 1, 2, 3].map(|i| a[i] + (b[i] - a[i]) * t);
+    mix(
+        mix(top_left, top_right, x),
+        mix(bottom_left, bottom_right, x),
+        y,
+    )
+}
+
+/// `InstanceData::corner_colors` of the part `[x, y, width, height]` of a quad (normalized,
+/// see `gradient_color`).
+fn packed_corner_colors(
+    corners: Option<&[[f32; 4]; 4]>,
+    [x, y, width, height]: [f32; 4],
+) -> [u32; 4] {
+    let Some(corners) = corners else {
+        return InstanceData::WHITE_CORNERS;
+    };
+    [
+        [x, y],
+        [x + width, y],
+        [x + width, y + height],
+        [x, y + height],
+    ]
+    .map(|point| InstanceData::pack_color(gradient_color(corners, point)))
+}
+
+/// One sprite instance of the frame, with the keys used to order it.
+#[derive(Clone, Copy)]
+pub(crate) struct SpriteDraw {
+    layer: i32,
+    /// World-space y of the sprite's bottom edge, for y-sorted sprites only.
+    feet_y: Option<f32>,
+    /// Submission order, used as the final tie-breaker.
+    order: usize,
+    pub(crate) key: BatchKey,
+    pub(crate) blend: BlendMode,
+    /// Key of the sprite's tight mesh (see `SpriteRenderer::add_mesh`), `None` for the quad.
+    pub(crate) mesh: Option<usize>,
+    pub(crate) instance: InstanceData,
+}
+
+impl SpriteDraw {
+    fn new(
+        sprite: &Sprite,
+        (key, texture_layer): (BatchKey, u32),
+        model: Matrix4<f32>,
+        order: usize,
+    ) -> Self {
+        // A mesh is not symmetric like the quad: mirror it rather than flipping the UV rect
+        let mesh = sprite.tight_mesh();
+        let mut corner_colors =
+            packed_corner_colors(sprite.corner_colors.as_ref(), [0.0, 0.0, 1.0, 1.0]);
+        let (model, uv_rect) = match mesh {
+            Some(_) if sprite.flip_x || sprite.flip_y => {
+                // The gradient stays in place: mirror the corners along with the mesh
+                if sprite.flip_x {
+                    corner_colors.swap(0, 1);
+                    corner_colors.swap(2, 3);
+                }
+                if sprite.flip_y {
+                    corner_colors.swap(0, 3);
+                    corner_colors.swap(1, 2);
+                }
+                (
+                    model * Self::quad_mirror(sprite.flip_x, sprite.flip_y),
+                    sprite.uv,
+                )
+            }
+            _ => (model, sprite.flipped_uv()),
+        };
+
+        let feet_y = sprite.y_sort.then(|| {
+            // Lowest on-screen point of the transformed quad (y grows downwards)
+            Vertex::quad_vertices()
+                .iter()
+                .map(|v| {
+                    let [x, y] = v.position();
+                    (model * Vector4::new(x, y, 0.0, 1.0))[1]
+                })
+                .fold(f32::NEG_INFINITY, f32::max)
+        });
+
+        Self {
+            layer: sprite.layer,
+            feet_y,
+            order,
+            key,
+            blend: sprite.blend,
+            mesh: mesh.map(SpriteRenderer::mesh_key),
+            instance: InstanceData {
+                model: model.into(),
+                uv_rect,
+                depth: SpriteRenderer::layer_depth(sprite.layer),
+                texture_layer,
+                tint: sprite.tint,
+                corner_colors,
+            },
+        }
+    }
+
+    /// Mirror of the unit quad around its center.
+    fn quad_mirror(flip_x: bool, flip_y: bool) -> Matrix4<f32> {
+        let sign = |flip: bool| if flip { -1.0 } else { 1.0 };
+        let center = Vector3::new(Vertex::QUAD_SIZE / 2.0, Vertex::QUAD_SIZE / 2.0, 0.0);
+        Matrix4::new_translation(&center)
+            * Matrix4::new_nonuniform_scaling(&Vector3::new(sign(flip_x), sign(flip_y), 1.0))
+            * Matrix4::new_translation(&-center)
+    }
+
+    /// The instances of `sprite`: one quad (or tight mesh), or one per cell for a nine-slice
+    /// sprite.
+    /// The cells share the sprite's order keys, so they stay together after sorting.
+    pub(crate) fn for_sprite(
+        sprite: &Sprite,
+        batch: (BatchKey, u32),
+        model: Matrix4<f32>,
+        order: usize,
+    ) -> Vec<Self> {
+        let Some(nine_slice) = &sprite.nine_slice else {
+            return vec![Self::new(sprite, batch, model, order)];
+        };
+
+        // Clés de tri calculées sur l'étendue complète du sprite
+        let (width, height) = nine_slice.size;
+        let quad_scale = |w: f32, h: f32| {
+            Matrix4::new_nonuniform_scaling(&Vector3::new(
+                w / Vertex::QUAD_SIZE,
+                h / Vertex::QUAD_SIZE,
+                1.0,
+            ))
+        };
+        let base = Self::new(sprite, batch, model * quad_scale(width, height), order);
+
+        nine_slice
+            .cells(
+                sprite.uv,
+                sprite.texture_size(),
+                sprite.flip_x,
+                sprite.flip_y,
+            )
+            .into_iter()
+            .map(|cell| {
+                let [x, y, w, h] = cell.rect;
+                let cell_model =
+                    model * Matrix4::new_translation(&Vector3::new(x, y, 0.0)) * quad_scale(w, h);
+                Self {
+                    instance: InstanceData {
+                        model: cell_model.into(),
+                        uv_rect: cell.uv,
+                        corner_colors: packed_corner_colors(
+                            sprite.corner_colors.as_ref(),
+                            [x / width, y / height, w / width, h / height],
+                        ),
+                        ..base.instance
+                    },
+                    ..base
+                }
+            })
+            .collect()
+    }
+
+    pub(crate) fn cmp_draw_order(a: &Self, b: &Self) -> Ordering {
+        a.layer
+            .cmp(&b.layer)
+            .then_with(|| match (a.feet_y, b.feet_y) {
+                (Some(a), Some(b)) => a.total_cmp(&b),
+                (None, Some(_)) => Ordering::Less,
+                (Some(_), None) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            })
+            .then_with(|| a.order.cmp(&b.order))
+    }
+}
+
+/// Coordinate space of a HUD sprite pass (see `SpritePass::hud`).
+/// Positions are in window pixels ([0, 0] = top-left), or in a fixed virtual resolution
+/// scaled uniformly to fit the window (centered, aspect ratio kept) so the HUD looks the
+/// same at any window size.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct HudLayer {
+    /// Virtual canvas size, `None` for window pixels.
+    pub virtual_size: Option<(f32, f32)>,
+}
+
+impl HudLayer {
+    /// HUD laid out in a `width` x `height` virtual canvas.
+    pub fn with_virtual_resolution(width: f32, height: f32) -> Self {
+        Self {
+            virtual_size: Some((width, height)),
+        }
+    }
+
+    /// Scale and offset (window pixels) of the HUD canvas in a `window_width` x
+    /// `window_height` window.
+    pub fn fit(&self, window_width: f32, window_height: f32) -> (f32, Vec2) {
+        match self.virtual_size {
+            Some((width, height)) if width > 0.0 && height > 0.0 => {
+                let scale = (window_width / width).min(window_height / height);
+                let offset = Vec2::new(
+                    (window_width - width * scale) / 2.0,
+                    (window_height - height * scale) / 2.0,
+                );
+                (scale, offset)
+            }
+            _ => (1.0, Vec2::zeros()),
+        }
+    }
+
+    /// Projection of the HUD canvas to clip space.
+    pub fn projection(&self, window_width: f32, window_height: f32) -> Matrix4<f32> {
+        let (window_width, window_height) = (window_width.max(1.0), window_height.max(1.0));
+        let (scale, offset) = self.fit(window_width, window_height);
+        let (sx, sy) = (2.0 * scale / window_width, -2.0 * scale / window_height);
+        Matrix4::new(
+            sx,
+            0.0,
+            0.0,
+            2.0 * offset.x / window_width - 1.0,
+            0.0,
+            sy,
+            0.0,
+            1.0 - 2.0 * offset.y / window_height,
+            0.0,
+            0.0,
+            1.0,
+            0.0,
+            0.0,
+            0.0,
+            0.0,
+            1.0,
+        )
+    }
+
+    /// HUD coordinates of a window position (mouse hit tests on HUD sprites).
+    pub fn window_to_hud(&self, position: Vec2, window_width: f32, window_height: f32) -> Vec2 {
+        let (scale, offset) = self.fit(window_width, window_height);
+        (position - offset) / scale
+    }
+}
+
+/// Passe de rendu pour afficher des sprites.
+/// Draws every entity of the scene that has a `Transform` and a `SpriteComponent`, plus the
+/// sprites added directly to the pass with `add_sprite` (drawn with an identity transform).
+/// A HUD pass (`SpritePass::hud`) draws the `ScreenSpace` entities instead, in window
+/// coordinates. In split screen (`PassManager::execute_players`) both run once per player,
+/// and the HUD uses the player's `HudLayer` inside their viewport.
 pub struct SpritePass {
     renderer: SpriteRenderer,
-    // now we keep Sprite descriptors together with a precomputed bind group for batching
-    sprites: Vec<(Sprite, wgpu::BindGroup)>,
+    /// Screen-space layer drawn by this pass, `None` for the scene (camera) layer.
+    hud: Option<HudLayer>,
+    sprites: Vec<Sprite>,
+    /// One bind group per distinct texture (keyed by `Arc<Texture2D>` pointer), so sprites
+    /// sharing a texture (e.g. regions of the same atlas) batch into a single draw call.
+    /// The `Arc` is kept alongside so the key cannot be reused by another texture while cached.
+    bind_groups: HashMap<usize, (Arc<Texture2D>, wgpu::BindGroup, TrackedMemory)>,
+    /// Depth buffer owned by the pass (window-sized), used when the pass context has none.
+    depth: Option<(wgpu::Texture, wgpu::TextureView)>,
+    /// One texture array (and its bind group) per texture size, when enabled.
+    texture_arrays: Option<TextureArrays>,
 }
 
 impl SpritePass {
-    pub fn new(device: &wgpu::Device, target_format: wgpu::TextureFormat) -> Self {
-        let renderer = SpriteRenderer::new(device, target_format);
+    pub fn new(
+        device: &wgpu::Device,
+        target_format: wgpu::TextureFormat,
+        loader: &AssetLoader,
+    ) -> Result<Self> {
+        Self::from_renderer(SpriteRenderer::new(device, target_format, loader)?)
+    }
 
-        Self {
+    /// Sprite pass with a depth buffer (`RenderTarget::DEPTH_FORMAT`): sprites write the
+    /// depth of their layer, so alpha-tested content does not rely on strict painter order.
+    /// The pass uses `PassContext::depth` when its target has one (render targets must then
+    /// be created with depth), and its own window-sized depth buffer otherwise.
+    pub fn with_depth(
+        device: &wgpu::Device,
+        target_format: wgpu::TextureFormat,
+        loader: &AssetLoader,
+    ) -> Result<Self> {
+        Self::from_renderer(SpriteRenderer::with_depth_format(
+            device,
+            target_format,
+            Some(RenderTarget::DEPTH_FORMAT),
+            loader,
+        )?)
+    }
+
+    /// Sprite pass for the HUD layer: draws the `ScreenSpace` entities (and the sprites
+    /// added with `add_sprite`) in `layer` coordinates, ignoring the scene camera, after the
+    /// scene, its effects and debug shapes.
+    pub fn hud(
+        device: &wgpu::Device,
+        target_format: wgpu::TextureFormat,
+        loader: &AssetLoader,
+        layer: HudLayer,
+    ) -> Result<Self> {
+        let mut pass = Self::new(device, target_format, loader)?;
+        pass.hud = Some(layer);
+        Ok(pass)
+    }
+
+    /// Coordinate space of a HUD pass, `None` for the scene pass.
+    pub fn hud_layer_mut(&mut self) -> Option<&mut HudLayer> {
+        self.hud.as_mut()
+    }
+
+    fn from_renderer(renderer: SpriteRenderer) -> Result<Self> {
+        Ok(Self {
             renderer,
+            hud: None,
             sprites: Vec::new(),
+            bind_groups: HashMap::new(),
+            depth: None,
+            texture_arrays: None,
+        })
+    }
+
+    /// Pack same-size sprite textures into `TextureArray`s so sprites using different
+    /// textures can still share one instanced draw (mega-batching). Textures that do not
+    /// fit (full array) keep their own bind group. A texture stays in its array as long as
+    /// sprites of that size are drawn.
+    pub fn enable_texture_arrays(
+        &mut self,
+        device: &wgpu::Device,
+        loader: &AssetLoader,
+    ) -> Result<()> {
+        self.renderer.enable_texture_arrays(device, loader)?;
+        self.texture_arrays.get_or_insert_with(HashMap::new);
+        Ok(())
+    }
+
+    /// Make sure the pass-owned depth buffer matches `width` x `height`.
+    fn ensure_depth(&mut self, device: &wgpu::Device, width: u32, height: u32) {
+        let matches = self.depth.as_ref().is_some_and(|(texture, _)| {
+            texture.width() == width.max(1) && texture.height() == height.max(1)
+        });
+        if !matches {
+            self.depth = Some(RenderTarget::create_depth(
+                device,
+                "sprite_pass",
+                width.max(1),
+                height.max(1),
+            ));
+        }
+    }
+
+    /// Key identifying the texture of a sprite for batching.
+    fn texture_key(sprite: &Sprite) -> usize {
+        Arc::as_ptr(&sprite.texture) as usize
+    }
+
+    /// Return the batching key of `sprite`, creating the bind group of its texture the first
+    /// time it is seen (using the renderer's `texture_bind_layout`).
+    fn cache_bind_group(
+        bind_groups: &mut HashMap<usize, (Arc<Texture2D>, wgpu::BindGroup, TrackedMemory)>,
+        layout: &wgpu::BindGroupLayout,
+        device: &wgpu::Device,
+        sprite: &Sprite,
+    ) -> usize {
+        let key = Self::texture_key(sprite);
+        bind_groups.entry(key).or_insert_with(|| {
+            (
+                sprite.texture.clone(),
+                sprite.create_bind_group(device, layout),
+                sprite.texture.track_bind_group(),
+            )
+        });
+        key
+    }
+
+    /// Return the batch of `sprite` and the layer it samples: the texture array of its size
+    /// when arrays are enabled and it fits (the copy is recorded in `encoder` the first
+    /// time), its own texture bind group otherwise.
+    fn batch_key(
+        bind_groups: &mut HashMap<usize, (Arc<Texture2D>, wgpu::BindGroup, TrackedMemory)>,
+        texture_arrays: Option<&mut TextureArrays>,
+        renderer: &SpriteRenderer,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        sprite: &Sprite,
+    ) -> (BatchKey, u32) {
+        if let Some(arrays) = texture_arrays
+            && let Some(layout) = renderer.array_texture_bind_layout()
+        {
+            let (width, height) = sprite.texture_size();
+            let (array, bind_group) = arrays.entry((width, height)).or_insert_with(|| {
+                let array = TextureArray::new(device, width, height);
+                let bind_group = array.create_bind_group(device, layout);
+                (array, bind_group)
+            });
+            if let Some((layer, reallocated)) = array.insert(device, encoder, &sprite.texture) {
+                if reallocated {
+                    *bind_group = array.create_bind_group(device, layout);
+                }
+                return (BatchKey::Array(width, height), layer);
+            }
+        }
+
+        let key =
+            Self::cache_bind_group(bind_groups, &renderer.texture_bind_layout, device, sprite);
+        (BatchKey::Texture(key), 0)
+    }
+
+    /// Collects the sprites to draw (the pass's own and the scene's), sorts them in draw
+    /// order and groups them into batches, building this frame's instance data. `player`
+    /// restricts `PlayerHud` elements to that split-screen player.
+    fn build_batches(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        scene: &Scene,
+        player: Option<usize>,
+    ) -> (Vec<InstanceData>, Vec<SpriteBatch>) {
+        // Collect every instance with its draw-order key; batching happens after sorting
+        let mut draws: Vec<SpriteDraw> = Vec::new();
+
+        for sprite in &self.sprites {
+            let batch = Self::batch_key(
+                &mut self.bind_groups,
+                self.texture_arrays.as_mut(),
+                &self.renderer,
+                device,
+                encoder,
+                sprite,
+            );
+            if let Some(mesh) = sprite.tight_mesh() {
+                self.renderer.add_mesh(mesh);
+            }
+            draws.extend(SpriteDraw::for_sprite(
+                sprite,
+                batch,
+                Matrix4::<f32>::identity(),
+                draws.len(),
+            ));
+        }
+
+        for (_entity, (transform, global, affine, component, screen_space, player_hud)) in scene
+            .world
+            .query::<(
+                &Transform,
+                Option<&GlobalTransform>,
+                Option<&Affine2D>,
+                &SpriteComponent,
+                Option<&ScreenSpace>,
+                Option<&PlayerHud>,
+            )>()
+            .iter()
+        {
+            if !component.visible || screen_space.is_some() != self.hud.is_some() {
+                continue;
+            }
+            // Split screen: a player's HUD elements are drawn in their viewport only
+            if let (Some(PlayerHud(owner)), Some(player)) = (player_hud, player)
+                && *owner != player
+            {
+                continue;
+            }
+
+            let sprite = &component.sprite;
+            let batch = Self::batch_key(
+                &mut self.bind_groups,
+                self.texture_arrays.as_mut(),
+                &self.renderer,
+                device,
+                encoder,
+                sprite,
+            );
+            if let Some(mesh) = sprite.tight_mesh() {
+                self.renderer.add_mesh(mesh);
+            }
+            let model = transform.draw_matrix(global, affine);
+            draws.extend(SpriteDraw::for_sprite(sprite, batch, model, draws.len()));
+        }
+
+        // Deterministic painter order: layer, then feet position for y-sorted sprites,
+        // then submission order.
+        draws.sort_by(SpriteDraw::cmp_draw_order);
+
+        // Drop bind groups / texture arrays that are no longer drawn
+        let used: HashSet<BatchKey> = draws.iter().map(|d| d.key).collect();
+        self.bind_groups
+            .retain(|key, _| used.contains(&BatchKey::Texture(*key)));
+        if let Some(arrays) = &mut self.texture_arrays {
+            arrays.retain(|&(width, height), _| used.contains(&BatchKey::Array(width, height)));
+        }
+        let used_meshes: HashSet<usize> = draws.iter().filter_map(|d| d.mesh).collect();
+        self.renderer
+            .retain_meshes(|key| used_meshes.contains(&key));
+        self.renderer.upload_meshes(device);
+
+        // Build the instance data into a single contiguous array: consecutive instances that
+        // share a texture (or texture array), a blend mode and a mesh form one batch (one
+        // instanced draw), and one upload serves them all.
+        let mut instances: Vec<InstanceData> = Vec::with_capacity(draws.len());
+        let mut batches: Vec<SpriteBatch> = Vec::new();
+
+        for draw in draws {
+            let index = instances.len() as u32;
+            instances.push(draw.instance);
+            match batches.last_mut() {
+                Some((key, blend, mesh, range))
+                    if *key == draw.key && *blend == draw.blend && *mesh == draw.mesh =>
+                {
+                    range.end = index + 1
+                }
+                _ => batches.push((draw.key, draw.blend, draw.mesh, index..index + 1)),
+            }
+        }
+
+        (instances, batches)
+    }
+
+    /// Builds this frame's instances and batches for `scene` like `execute`, without drawing
+    /// or uploading anything. Returns the draw calls and instances the frame would take
+    /// (used by the benchmarks in `benches/`).
+    pub fn prepare_frame(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        scene: &Scene,
+    ) -> DrawCounts {
+        let (instances, batches) = self.build_batches(device, encoder, scene, None);
+        DrawCounts {
+            draw_calls: batches.len() as u32,
+            instances: instances.len() as u32,
         }
     }
 
     /// Ajouter une sprite à afficher dans cette passe.
-    /// The provided `Sprite` references a `Texture2D`; we create a bind group for that texture using
-    /// the renderer's `texture_bind_layout` and store the pair for batched rendering.
+    /// Prefer spawning an entity with a `SpriteComponent` in the `Scene` for game objects.
     pub fn add_sprite(&mut self, sprite: Sprite, device: &wgpu::Device) {
-        let bind_group = sprite.create_bind_group(device, &self.renderer.texture_bind_layout);
-        self.sprites.push((sprite, bind_group));
+        Self::cache_bind_group(
+            &mut self.bind_groups,
+            &self.renderer.texture_bind_layout,
+            device,
+            &sprite,
+        );
+        self.sprites.push(sprite);
     }
 }
 
 impl RenderPass for SpritePass {
     fn name(&self) -> &str {
-        "sprite_pass"
+        match self.hud {
+            Some(_) => "hud_sprite_pass",
+            None => "sprite_pass",
+        }
+    }
+
+    fn after(&self) -> &[&str] {
+        match self.hud {
+            Some(_) => &[
+                "sprite_pass",
+                "tilemap_pass",
+                "lighting_pass",
+                "weather_pass",
+                "outline_pass",
+                "shape_pass",
+                "post_process_pass",
+            ],
+            None => &[],
+        }
+    }
+
+    fn before(&self) -> &[&str] {
+        match self.hud {
+            Some(_) => &["egui_pass"],
+            None => &[],
+        }
+    }
+
+    fn per_viewport(&self) -> bool {
+        true
+    }
+
+    fn reload_shader(
+        &mut self,
+        path: &str,
+        device: &wgpu::Device,
+        loader: &AssetLoader,
+    ) -> Result<bool> {
+        self.renderer.reload_shader(path, device, loader)
     }
 
-    fn execute(&self, ctx: &mut PassContext) {
-        // Utiliser la matrice view-projection de la caméra 2D
-        let view_proj = ctx.camera.view_projection_matrix();
+    fn execute(&mut self, ctx: &mut PassContext) {
+        // Utiliser la matrice view-projection de la caméra 2D (repère de la fenêtre pour le HUD,
+        // ou de la zone du joueur en écran partagé)
+        let view_proj = match (&self.hud, &ctx.viewport) {
+            (Some(_), Some(view)) => view
+                .hud
+                .projection(view.viewport.width, view.viewport.height),
+            (Some(layer), None) => layer.projection(
+                ctx.window_state.config.width as f32,
+                ctx.window_state.config.height as f32,
+            ),
+            (None, _) => ctx.camera.view_projection_matrix(),
+        };
+        let player = ctx.viewport.map(|view| view.player);
         self.renderer.update_transform(ctx.queue, view_proj);
 
+        let device = &ctx.window_state.device;
+        let (instances, batches) = self.build_batches(device, ctx.encoder, ctx.scene, player);
+
+        // Grow the GPU buffer if this frame has more instances than it can hold
+        self.renderer
+            .ensure_instance_capacity(&ctx.window_state.device, instances.len());
+
+        // Upload instance data to the GPU
+        if !instances.is_empty() {
+            ctx.queue.write_buffer(
+                &self.renderer.instance_buffer,
+                0,
+                bytemuck::cast_slice(&instances),
+            );
+        }
+
+        // Depth : celui de la cible s'il existe, sinon celui de la passe (taille fenêtre)
+        if self.renderer.depth_format().is_some() && ctx.depth.is_none() {
+            let (width, height) = (
+                ctx.window_state.config.width,
+                ctx.window_state.config.height,
+            );
+            self.ensure_depth(&ctx.window_state.device, width, height);
+        }
+        let depth_view = match self.renderer.depth_format() {
+            Some(_) => ctx.depth.or(self.depth.as_ref().map(|(_, view)| view)),
+            None => None,
+        };
+
         // Créer le descripteur de la render pass
         let descriptor = wgpu::RenderPassDescriptor {
             label: Some("sprite_render_pass"),
@@ -366,70 +1839,174 @@ impl RenderPass for SpritePass {
                     store: wgpu::StoreOp::Store,
                 },
             })],
-            depth_stencil_attachment: None,
+            depth_stencil_attachment: depth_view.map(|view| {
+                wgpu::RenderPassDepthStencilAttachment {
+                    view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: wgpu::StoreOp::Store,
+                    }),
+                    stencil_ops: None,
+                }
+            }),
             occlusion_query_set: None,
             timestamp_writes: None,
         };
 
         // Ouvrir la render pass
         let mut rpass = ctx.encoder.begin_render_pass(&descriptor);
+        if let Some(view) = &ctx.viewport {
+            view.viewport.apply(&mut rpass);
+        }
 
-        // Group sprites by bind_group pointer to batch those that share the same texture
-        use std::collections::HashMap;
+        // One instanced draw per texture group, blend mode and mesh
+        for (key, blend, mesh, range) in batches {
+            ctx.window_state.frame_stats.record_draw(range.len() as u32);
+            match key {
+                BatchKey::Texture(key) => {
+                    let (_texture, bind_group, _) = &self.bind_groups[&key];
+                    self.renderer
+                        .draw_instanced(&mut rpass, blend, bind_group, mesh, range);
+                }
+                BatchKey::Array(width, height) => {
+                    if let Some(arrays) = &self.texture_arrays {
+                        let (_array, bind_group) = &arrays[&(width, height)];
+                        self.renderer
+                            .draw_array_instanced(&mut rpass, blend, bind_group, mesh, range);
+                    }
+                }
+            }
+        }
 
-        let mut groups: HashMap<usize, Vec<usize>> = HashMap::new();
+        // La render pass se termine automatiquement ici
+    }
+}
 
-        for (i, (_sprite, bind_group)) in self.sprites.iter().enumerate() {
-            let key = bind_group as *const _ as usize;
-            groups.entry(key).or_default().push(i);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draw(layer: i32, feet_y: Option<f32>, order: usize) -> SpriteDraw {
+        SpriteDraw {
+            layer,
+            feet_y,
+            order,
+            key: BatchKey::Texture(0),
+            blend: BlendMode::Alpha,
+            mesh: None,
+            instance: InstanceData {
+                model: Matrix4::<f32>::identity().into(),
+                uv_rect: [0.0, 0.0, 1.0, 1.0],
+                depth: SpriteRenderer::layer_depth(layer),
+                texture_layer: 0,
+                tint: [1.0; 4],
+                corner_colors: InstanceData::WHITE_CORNERS,
+            },
         }
+    }
 
-        // For each group, build instance data and draw in a single instanced call
-        for (_key, indices) in groups {
-            // Build instance data for this group
-            let mut instances: Vec<InstanceData> = Vec::with_capacity(indices.len());
+    #[test]
+    fn draw_order_is_layer_then_feet_then_submission() {
+        let mut draws = [
+            draw(100, None, 0),      // UI
+            draw(10, Some(50.0), 1), // character lower on screen
+            draw(10, Some(20.0), 2), // character higher on screen
+            draw(10, None, 3),       // unsorted prop on the character layer
+            draw(0, None, 4),        // ground
+            draw(0, None, 5),
+        ];
+        draws.sort_by(SpriteDraw::cmp_draw_order);
 
-            for &i in &indices {
-                let (sprite, _bg) = &self.sprites[i];
-                // For now, place identity model matrix; you can expand to include position/scale/rotation
-                let model = Matrix4::<f32>::identity();
-                instances.push(InstanceData {
-                    model: model.into(),
-                });
-            }
+        let order: Vec<_> = draws.iter().map(|d| d.order).collect();
+        assert_eq!(order, [4, 5, 3, 2, 1, 0]);
+    }
 
-            // Ensure capacity: if needed, we would resize the GPU buffer (not implemented auto-resize here)
-            if instances.len() > self.renderer.instance_capacity {
-                // If we need to support more instances than capacity, we should recreate the buffer.
-                // For simplicity, clamp to capacity.
-                // In a real implementation, recreate buffer with larger capacity.
-                // Log a warning:
-                log::warn!(
-                    "Instance count {} exceeds buffer capacity {}; clipping.",
-                    instances.len(),
-                    self.renderer.instance_capacity
-                );
-            }
+    #[test]
+    fn blend_modes_round_trip_and_differ() {
+        for mode in BlendMode::ALL {
+            assert_eq!(BlendMode::from_name(mode.name()), Some(mode));
+            assert_eq!(BlendMode::ALL[mode.index()], mode);
+        }
+        assert_eq!(BlendMode::from_name("screen"), None);
+        assert_eq!(BlendMode::default(), BlendMode::Alpha);
+        assert_ne!(
+            BlendMode::Additive.blend_state(),
+            BlendMode::Alpha.blend_state()
+        );
+    }
 
-            // Upload instance data to the GPU
-            let bytes = bytemuck::cast_slice(
-                &instances[..std::cmp::min(instances.len(), self.renderer.instance_capacity)],
-            );
+    #[test]
+    fn higher_layers_get_smaller_depth() {
+        let ground = SpriteRenderer::layer_depth(0);
+        let ui = SpriteRenderer::layer_depth(100);
+        assert!(ui < ground);
+        assert!((0.0..=1.0).contains(&SpriteRenderer::layer_depth(i32::MIN)));
+        assert!((0.0..=1.0).contains(&SpriteRenderer::layer_depth(i32::MAX)));
+        assert!(SpriteRenderer::layer_depth(i32::MAX) > 0.0);
+    }
 
-            ctx.queue
-                .write_buffer(&self.renderer.instance_buffer, 0, bytes);
+    #[test]
+    fn corner_colors_are_interpolated_and_packed() {
+        let red = [1.0, 0.0, 0.0, 1.0];
+        let blue = [0.0, 0.0, 1.0, 0.0];
+        assert_eq!(InstanceData::pack_color([1.0; 4]), u32::MAX);
+        assert_eq!(InstanceData::pack_color(red), 0xff00_00ff);
+        assert_eq!(InstanceData::pack_color([0.0, 0.0, 2.0, -1.0]), 0x00ff_0000);
+        assert_eq!(
+            packed_corner_colors(None, [0.0, 0.0, 1.0, 1.0]),
+            InstanceData::WHITE_CORNERS
+        );
 
-            // Retrieve any bind_group for this group (take first)
-            let first_index = indices[0];
-            let (_sprite0, bind_group0) = &self.sprites[first_index];
+        // Dégradé vertical : la moitié basse va du violet au bleu
+        let corners = [red, red, blue, blue];
+        assert_eq!(gradient_color(&corners, [0.3, 0.5]), [0.5, 0.0, 0.5, 0.5]);
+        let purple = InstanceData::pack_color([0.5, 0.0, 0.5, 0.5]);
+        let blue = InstanceData::pack_color(blue);
+        assert_eq!(
+            packed_corner_colors(Some(&corners), [0.0, 0.5, 1.0, 0.5]),
+            [purple, purple, blue, blue]
+        );
+    }
 
-            // Draw instanced for this group's instances
-            let instance_count = instances.len().min(self.renderer.instance_capacity) as u32;
+    #[test]
+    fn hud_layer_fits_the_virtual_resolution() {
+        let layer = HudLayer::with_virtual_resolution(320.0, 180.0);
+        // 4x, bandes de 40 pixels en haut et en bas
+        let (scale, offset) = layer.fit(1280.0, 800.0);
+        assert_eq!((scale, offset), (4.0, Vec2::new(0.0, 40.0)));
 
-            self.renderer
-                .draw_instanced(&mut rpass, bind_group0, instance_count);
-        }
+        let projection = layer.projection(1280.0, 800.0);
+        let clip = |x: f32, y: f32| {
+            let p = projection * Vector4::new(x, y, 0.0, 1.0);
+            (p.x, p.y)
+        };
+        assert_eq!(clip(0.0, 0.0), (-1.0, 0.9));
+        assert_eq!(clip(320.0, 180.0), (1.0, -0.9));
+        assert_eq!(
+            layer.window_to_hud(Vec2::new(640.0, 400.0), 1280.0, 800.0),
+            Vec2::new(160.0, 90.0)
+        );
 
-        // La render pass se termine automatiquement ici
+        let pixels = HudLayer::default().projection(800.0, 600.0);
+        let p = pixels * Vector4::new(800.0, 600.0, 0.0, 1.0);
+        assert_eq!((p.x, p.y), (1.0, -1.0));
+    }
+
+    #[test]
+    fn nine_slice_keeps_corners_and_stretches_center() {
+        let nine = NineSlice::new([4.0, 4.0, 4.0, 4.0], (100.0, 40.0));
+        let cells = nine.cells([0.0, 0.0, 1.0, 1.0], (16, 16), false, false);
+        assert_eq!(cells.len(), 9);
+        assert_eq!(cells[0].rect, [0.0, 0.0, 4.0, 4.0]);
+        assert_eq!(cells[0].uv, [0.0, 0.0, 0.25, 0.25]);
+        assert_eq!(cells[4].rect, [4.0, 4.0, 92.0, 32.0]);
+        assert_eq!(cells[8].rect, [96.0, 36.0, 4.0, 4.0]);
+
+        // Bordures plus grandes que la taille : réduites, cellules vides ignorées
+        let small = NineSlice::new([4.0, 0.0, 4.0, 0.0], (4.0, 10.0));
+        let cells = small.cells([0.0, 0.0, 1.0, 1.0], (16, 16), true, false);
+        assert_eq!(cells.len(), 2);
+        assert_eq!(cells[0].rect, [2.0, 0.0, 2.0, 10.0]);
+        assert_eq!(cells[0].uv, [0.25, 0.0, 0.0, 1.0]);
     }
 }
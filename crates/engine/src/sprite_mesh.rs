@@ -0,0 +1,298 @@
+//! Tight sprite meshes: a convex polygon hugging the visible pixels of a frame, drawn instead
+//! of the full quad so large, mostly transparent images do not pay for their empty space
+//! (overdraw).
+//!
+//! Meshes are generated once at import time (`SpriteMesh::from_alpha`, "Tight meshes" in
+//! the sprite slicer), stored in the atlas metadata (see `AtlasMetadata`) and picked up by
+//! `Sprite::from_atlas_region`.
+
+use anyhow::{Context, Result, bail};
+
+/// Triangle mesh covering the visible part of a sprite frame.
+/// Vertices are normalized coordinates inside the frame ([0, 0] = top-left,
+/// [1, 1] = bottom-right), used both as position on the sprite quad and as UV inside the
+/// sprite's `uv` rect.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpriteMesh {
+    pub vertices: Vec<[f32; 2]>,
+    /// Triangle list, three indices into `vertices` per triangle.
+    pub indices: Vec<u16>,
+}
+
+impl SpriteMesh {
+    /// Default vertex budget of `from_alpha`: a few more vertices than the quad is enough to
+    /// cut most of the empty corners.
+    pub const DEFAULT_MAX_VERTICES: usize = 8;
+
+    /// A mesh covering more than this fraction of its frame saves too little overdraw to be
+    /// worth breaking batches for: `from_alpha` returns `None`.
+    pub const MAX_COVERAGE: f32 = 0.85;
+
+    /// Fan triangulation of a convex polygon (vertices in order, either winding).
+    pub fn from_convex_polygon(vertices: Vec<[f32; 2]>) -> Self {
+        let indices = (1..vertices.len().saturating_sub(1) as u16)
+            .flat_map(|i| [0, i, i + 1])
+            .collect();
+        Self { vertices, indices }
+    }
+
+    /// Tight mesh of the frame `[x, y, width, height]` of an RGBA8 image of
+    /// `image_width` x `image_height`: convex hull of the pixels with an alpha above `alpha_threshold`, grown
+    /// (never shrunk) until it has at most `max_vertices` vertices.
+    /// Returns `None` when the mesh would barely be smaller than the quad
+    /// (`MAX_COVERAGE`) or the frame is not inside the image, and an empty mesh (nothing
+    /// drawn) for a fully transparent frame.
+    pub fn from_alpha(
+        (image_width, image_height): (u32, u32),
+        rgba: &[u8],
+        [x, y, width, height]: [u32; 4],
+        alpha_threshold: u8,
+        max_vertices: usize,
+    ) -> Option<Self> {
+        if width == 0 || height == 0 || x + width > image_width || y + height > image_height {
+            return None;
+        }
+        let opaque = |px: u32, py: u32| {
+            rgba[(((y + py) * image_width + x + px) * 4 + 3) as usize] > alpha_threshold
+        };
+
+        // The outermost opaque pixels of each row are enough to build the hull
+        let mut points = Vec::new();
+        for py in 0..height {
+            let Some(left) = (0..width).find(|&px| opaque(px, py)) else {
+                continue;
+            };
+            let right = (left..width)
+                .rev()
+                .find(|&px| opaque(px, py))
+                .unwrap_or(left);
+            let (top, bottom) = (py as f32, (py + 1) as f32);
+            points.extend([
+                [left as f32, top],
+                [left as f32, bottom],
+                [(right + 1) as f32, top],
+                [(right + 1) as f32, bottom],
+            ]);
+        }
+        if points.is_empty() {
+            return Some(Self::from_convex_polygon(Vec::new()));
+        }
+
+        let mut hull = convex_hull(points);
+        reduce_hull(
+            &mut hull,
+            max_vertices.max(3),
+            [width as f32, height as f32],
+        );
+        let vertices: Vec<[f32; 2]> = hull
+            .into_iter()
+            .map(|[px, py]| [px / width as f32, py / height as f32])
+            .collect();
+
+        let mesh = Self::from_convex_polygon(vertices);
+        (mesh.area() <= Self::MAX_COVERAGE).then_some(mesh)
+    }
+
+    /// `true` if the mesh draws nothing.
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    /// Area covered by the triangles, as a fraction of the frame.
+    pub fn area(&self) -> f32 {
+        self.indices
+            .chunks_exact(3)
+            .map(|triangle| {
+                let [a, b, c] = [0, 1, 2].map(|i| self.vertices[triangle[i] as usize]);
+                cross(sub(b, a), sub(c, a)).abs() / 2.0
+            })
+            .sum()
+    }
+
+    /// Text form used by `AtlasMetadata`: `u v u v ... | i i i ...`.
+    pub fn encode(&self) -> String {
+        let vertices: Vec<String> = self
+            .vertices
+            .iter()
+            .map(|[u, v]| format!("{} {}", u, v))
+            .collect();
+        let indices: Vec<String> = self.indices.iter().map(u16::to_string).collect();
+        format!("{} | {}", vertices.join(" "), indices.join(" "))
+    }
+
+    /// Parse the text form written by `encode`.
+    pub fn parse(text: &str) -> Result<Self> {
+        let (vertices, indices) = text
+            .split_once('|')
+            .context("expected `u v u v ... | i i i ...`")?;
+        let coordinates = vertices
+            .split_whitespace()
+            .map(str::parse::<f32>)
+            .collect::<Result<Vec<_>, _>>()
+            .context("invalid mesh vertex")?;
+        let indices = indices
+            .split_whitespace()
+            .map(str::parse::<u16>)
+            .collect::<Result<Vec<_>, _>>()
+            .context("invalid mesh index")?;
+
+        if coordinates.len() % 2 != 0 || indices.len() % 3 != 0 {
+            bail!("mesh needs pairs of coordinates and triples of indices");
+        }
+        let vertices: Vec<[f32; 2]> = coordinates.chunks_exact(2).map(|c| [c[0], c[1]]).collect();
+        if let Some(index) = indices.iter().find(|&&i| i as usize >= vertices.len()) {
+            bail!(
+                "mesh index {} out of range ({} vertices)",
+                index,
+                vertices.len()
+            );
+        }
+        Ok(Self { vertices, indices })
+    }
+}
+
+fn sub(a: [f32; 2], b: [f32; 2]) -> [f32; 2] {
+    [a[0] - b[0], a[1] - b[1]]
+}
+
+fn cross(a: [f32; 2], b: [f32; 2]) -> f32 {
+    a[0] * b[1] - a[1] * b[0]
+}
+
+/// Convex hull (monotone chain), without collinear points.
+fn convex_hull(mut points: Vec<[f32; 2]>) -> Vec<[f32; 2]> {
+    points.sort_by(|a, b| a[0].total_cmp(&b[0]).then(a[1].total_cmp(&b[1])));
+    points.dedup();
+    if points.len() < 3 {
+        return points;
+    }
+
+    let chain = |points: &mut dyn Iterator<Item = [f32; 2]>| {
+        let mut chain: Vec<[f32; 2]> = Vec::new();
+        for point in points {
+            while let [.., a, b] = chain[..]
+                && cross(sub(b, a), sub(point, a)) <= 0.0
+            {
+                chain.pop();
+            }
+            chain.push(point);
+        }
+        // The last point of each chain is the first of the other one
+        chain.pop();
+        chain
+    };
+    let mut hull = chain(&mut points.iter().copied());
+    hull.extend(chain(&mut points.iter().rev().copied()));
+    hull
+}
+
+/// Remove edges of the convex `hull` until it has at most `max_vertices` vertices: an edge
+/// is replaced by the intersection of its two neighbouring edges, picking each time the one
+/// adding the least area. The result still contains the original hull and stays inside
+/// `[0, 0]..bounds`; it may keep more vertices if no edge can be removed.
+fn reduce_hull(hull: &mut Vec<[f32; 2]>, max_vertices: usize, bounds: [f32; 2]) {
+    while hull.len() > max_vertices && hull.len() > 3 {
+        let n = hull.len();
+        let mut best: Option<(usize, [f32; 2], f32)> = None;
+        for i in 0..n {
+            let (before, a, b, after) = (
+                hull[(i + n - 1) % n],
+                hull[i],
+                hull[(i + 1) % n],
+                hull[(i + 2) % n],
+            );
+            let (d1, d2, edge) = (sub(a, before), sub(b, after), sub(b, a));
+            let denominator = cross(d1, d2);
+            if denominator.abs() < f32::EPSILON {
+                continue;
+            }
+            // Both neighbouring edges must be extended forward to meet
+            let t = cross(edge, d2) / denominator;
+            let s = cross(edge, d1) / denominator;
+            if t <= 0.0 || s <= 0.0 {
+                continue;
+            }
+            let point = [a[0] + t * d1[0], a[1] + t * d1[1]];
+            let inside =
+                (0.0..=bounds[0]).contains(&point[0]) && (0.0..=bounds[1]).contains(&point[1]);
+            let added = cross(sub(point, a), edge).abs() / 2.0;
+            if inside && best.is_none_or(|(_, _, area)| added < area) {
+                best = Some((i, point, added));
+            }
+        }
+
+        let Some((i, point, _)) = best else {
+            return;
+        };
+        hull[i] = point;
+        hull.remove((i + 1) % n);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// RGBA8 image with a filled disc of radius `radius` centered in a `size` x `size` image.
+    fn disc(size: u32, radius: f32) -> Vec<u8> {
+        let center = size as f32 / 2.0;
+        (0..size * size)
+            .flat_map(|i| {
+                let (x, y) = ((i % size) as f32 + 0.5, (i / size) as f32 + 0.5);
+                let inside = (x - center).hypot(y - center) <= radius;
+                [255, 255, 255, if inside { 255 } else { 0 }]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn tight_mesh_covers_opaque_pixels_with_less_area() {
+        let size = 64;
+        let rgba = disc(size, 20.0);
+        let mesh = SpriteMesh::from_alpha(
+            (size, size),
+            &rgba,
+            [0, 0, size, size],
+            0,
+            SpriteMesh::DEFAULT_MAX_VERTICES,
+        )
+        .unwrap();
+
+        assert!(mesh.vertices.len() <= SpriteMesh::DEFAULT_MAX_VERTICES);
+        assert!(mesh.area() < 0.5);
+
+        // Every opaque pixel center lies inside the polygon
+        let n = mesh.vertices.len();
+        let sign = cross(
+            sub(mesh.vertices[1], mesh.vertices[0]),
+            sub(mesh.vertices[2], mesh.vertices[0]),
+        )
+        .signum();
+        for i in 0..size * size {
+            if rgba[(i * 4 + 3) as usize] == 0 {
+                continue;
+            }
+            let p = [
+                ((i % size) as f32 + 0.5) / size as f32,
+                ((i / size) as f32 + 0.5) / size as f32,
+            ];
+            for k in 0..n {
+                let (a, b) = (mesh.vertices[k], mesh.vertices[(k + 1) % n]);
+                assert!(cross(sub(b, a), sub(p, a)) * sign >= -1e-5);
+            }
+        }
+
+        assert_eq!(SpriteMesh::parse(&mesh.encode()).unwrap(), mesh);
+        assert!(SpriteMesh::parse("0 0 1 0 0 1 | 0 1 3").is_err());
+    }
+
+    #[test]
+    fn opaque_and_empty_frames() {
+        let opaque = vec![255u8; 16 * 16 * 4];
+        assert!(SpriteMesh::from_alpha((16, 16), &opaque, [0, 0, 16, 16], 0, 8).is_none());
+
+        let empty = vec![0u8; 16 * 16 * 4];
+        let mesh = SpriteMesh::from_alpha((16, 16), &empty, [0, 0, 16, 16], 0, 8).unwrap();
+        assert!(mesh.is_empty());
+    }
+}
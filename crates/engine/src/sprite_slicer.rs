@@ -0,0 +1,662 @@
+//! Outil de découpe de planches de sprites (fenêtre de l'éditeur).
+//!
+//! Une planche est découpée en frames sur une grille (taille de case, marge, espacement),
+//! par détection automatique des zones opaques ou à la main (rectangles tracés à la souris).
+//! Chaque frame est nommée et reçoit un pivot ; l'ordre des frames est l'ordre d'animation.
+//! Le tout est sauvegardé en `AtlasMetadata` (`.atlas`) à côté de l'image, pour être chargé
+//! avec `TextureAtlas::load`.
+
+use std::sync::Arc;
+
+use anyhow::{Context, Result, anyhow};
+
+use crate::{AtlasFrame, AtlasMetadata, SpriteMesh, Vfs};
+
+/// Cases d'une grille de `cell_width` x `cell_height` pixels sur une image de
+/// `width` x `height`, ligne par ligne : [x, y, largeur, hauteur]. Les cases partielles
+/// (bords droit / bas) sont ignorées.
+pub fn grid_frames(
+    (width, height): (u32, u32),
+    (cell_width, cell_height): (u32, u32),
+    margin: u32,
+    spacing: u32,
+) -> Vec<[u32; 4]> {
+    if cell_width == 0 || cell_height == 0 {
+        return Vec::new();
+    }
+    let mut frames = Vec::new();
+    let mut y = margin;
+    while y + cell_height <= height.saturating_sub(margin) {
+        let mut x = margin;
+        while x + cell_width <= width.saturating_sub(margin) {
+            frames.push([x, y, cell_width, cell_height]);
+            x += cell_width + spacing;
+        }
+        y += cell_height + spacing;
+    }
+    frames
+}
+
+/// Détecte les frames d'une planche RGBA8 : rectangles englobants des zones de pixels
+/// d'alpha supérieur à `alpha_threshold` (connexité 8), dans l'ordre de lecture (rangées de
+/// haut en bas, puis de gauche à droite). Les zones plus petites que `min_size` pixels de
+/// côté sont ignorées.
+pub fn detect_frames(
+    (width, height): (u32, u32),
+    rgba: &[u8],
+    alpha_threshold: u8,
+    min_size: u32,
+) -> Vec<[u32; 4]> {
+    let (w, h) = (width as usize, height as usize);
+    let opaque = |x: usize, y: usize| rgba[(y * w + x) * 4 + 3] > alpha_threshold;
+    let mut visited = vec![false; w * h];
+    let mut frames = Vec::new();
+    let mut stack = Vec::new();
+
+    for start in 0..w * h {
+        if visited[start] || !opaque(start % w, start / w) {
+            continue;
+        }
+        visited[start] = true;
+        stack.push(start);
+        let (mut x0, mut y0, mut x1, mut y1) = (start % w, start / w, start % w, start / w);
+
+        while let Some(index) = stack.pop() {
+            let (x, y) = (index % w, index / w);
+            (x0, y0, x1, y1) = (x0.min(x), y0.min(y), x1.max(x), y1.max(y));
+            for ny in y.saturating_sub(1)..=(y + 1).min(h - 1) {
+                for nx in x.saturating_sub(1)..=(x + 1).min(w - 1) {
+                    let neighbour = ny * w + nx;
+                    if !visited[neighbour] && opaque(nx, ny) {
+                        visited[neighbour] = true;
+                        stack.push(neighbour);
+                    }
+                }
+            }
+        }
+
+        let frame = [
+            x0 as u32,
+            y0 as u32,
+            (x1 - x0 + 1) as u32,
+            (y1 - y0 + 1) as u32,
+        ];
+        if frame[2] >= min_size && frame[3] >= min_size {
+            frames.push(frame);
+        }
+    }
+
+    // Ordre de lecture : une rangée regroupe les frames qui chevauchent verticalement la
+    // première frame de la rangée
+    frames.sort_by_key(|f| (f[1], f[0]));
+    let mut ordered: Vec<[u32; 4]> = Vec::with_capacity(frames.len());
+    let mut row: Vec<[u32; 4]> = Vec::new();
+    for frame in frames {
+        if let Some(first) = row.first()
+            && frame[1] >= first[1] + first[3]
+        {
+            row.sort_by_key(|f| f[0]);
+            ordered.append(&mut row);
+        }
+        row.push(frame);
+    }
+    row.sort_by_key(|f| f[0]);
+    ordered.append(&mut row);
+    ordered
+}
+
+/// Rectangle [x, y, largeur, hauteur] entre deux coins quelconques `a` et `b` (en pixels,
+/// fractionnaires), arrondi aux pixels touchés et limité à l'image. `None` s'il est vide.
+pub fn rect_between(a: [f32; 2], b: [f32; 2], (width, height): (u32, u32)) -> Option<[u32; 4]> {
+    let clamp = |value: f32, max: u32| value.clamp(0.0, max as f32);
+    let (x0, x1) = (clamp(a[0].min(b[0]), width), clamp(a[0].max(b[0]), width));
+    let (y0, y1) = (clamp(a[1].min(b[1]), height), clamp(a[1].max(b[1]), height));
+    let (x0, y0) = (x0.floor() as u32, y0.floor() as u32);
+    let (x1, y1) = (x1.ceil() as u32, y1.ceil() as u32);
+    (x1 > x0 && y1 > y0).then_some([x0, y0, x1 - x0, y1 - y0])
+}
+
+/// Planche ouverte dans le slicer.
+struct SpriteSheet {
+    path: String,
+    width: u32,
+    height: u32,
+    rgba: Vec<u8>,
+    texture: egui::TextureHandle,
+}
+
+impl SpriteSheet {
+    /// `true` si la zone ne contient que des pixels transparents.
+    fn is_empty(&self, [x, y, width, height]: [u32; 4]) -> bool {
+        (y..y + height).all(|py| {
+            (x..x + width).all(|px| self.rgba[((py * self.width + px) * 4 + 3) as usize] == 0)
+        })
+    }
+}
+
+/// Fenêtre de découpe d'une planche de sprites en `AtlasMetadata`.
+pub struct SpriteSlicer {
+    vfs: Arc<Vfs>,
+    sheet: Option<SpriteSheet>,
+    /// Chemin VFS saisi dans le champ "Sprite sheet".
+    image_path: String,
+    /// Chemin de sauvegarde du `.atlas`.
+    output_path: String,
+    cell_size: (u32, u32),
+    margin: u32,
+    spacing: u32,
+    skip_empty: bool,
+    alpha_threshold: u8,
+    min_size: u32,
+    /// Génère à la sauvegarde un mesh ajusté à l'alpha de chaque frame (`SpriteMesh`).
+    tight_meshes: bool,
+    /// Pivot donné aux nouvelles frames.
+    default_pivot: [f32; 2],
+    frames: Vec<AtlasFrame>,
+    selected: Option<usize>,
+    zoom: f32,
+    /// Coin de départ (pixels de la planche) du rectangle en cours de tracé.
+    drag_start: Option<[f32; 2]>,
+}
+
+impl SpriteSlicer {
+    pub fn new(vfs: Arc<Vfs>) -> Self {
+        Self {
+            vfs,
+            sheet: None,
+            image_path: String::new(),
+            output_path: String::new(),
+            cell_size: (32, 32),
+            margin: 0,
+            spacing: 0,
+            skip_empty: true,
+            alpha_threshold: 0,
+            min_size: 2,
+            tight_meshes: false,
+            default_pivot: [0.5, 1.0],
+            frames: Vec::new(),
+            selected: None,
+            zoom: 2.0,
+            drag_start: None,
+        }
+    }
+
+    /// Frames découpées (et éditées) de la planche courante.
+    pub fn frames(&self) -> &[AtlasFrame] {
+        &self.frames
+    }
+
+    /// Ouvre la planche `path` (chemin VFS). Si un `.atlas` existe déjà à côté, ses frames
+    /// sont reprises.
+    pub fn open(&mut self, ctx: &egui::Context, path: &str) -> Result<()> {
+        let bytes = self.vfs.read_bytes(path)?;
+        let image = image::load_from_memory(&bytes)
+            .with_context(|| format!("failed to decode image {:?}", path))?
+            .to_rgba8();
+        let (width, height) = image.dimensions();
+        let rgba = image.into_raw();
+        let texture = ctx.load_texture(
+            format!("sprite_slicer:{}", path),
+            egui::ColorImage::from_rgba_unmultiplied([width as usize, height as usize], &rgba),
+            egui::TextureOptions::NEAREST,
+        );
+
+        self.sheet = Some(SpriteSheet {
+            path: path.to_string(),
+            width,
+            height,
+            rgba,
+            texture,
+        });
+        self.image_path = path.to_string();
+        self.output_path = Self::atlas_path(path);
+        self.frames.clear();
+        self.selected = None;
+
+        if self.vfs.exists(&self.output_path) {
+            let metadata = AtlasMetadata::load(&self.vfs, &self.output_path)?;
+            self.tight_meshes = metadata.frames.iter().any(|frame| frame.mesh.is_some());
+            self.frames = metadata.frames;
+        }
+        Ok(())
+    }
+
+    /// Chemin du `.atlas` par défaut d'une image : même nom, extension `atlas`.
+    fn atlas_path(image: &str) -> String {
+        let stem = match image.rfind('.') {
+            Some(dot) if !image[dot..].contains('/') => &image[..dot],
+            _ => image,
+        };
+        format!("{}.{}", stem, AtlasMetadata::EXTENSION)
+    }
+
+    /// Nom de base des frames : nom du fichier de la planche, sans extension.
+    fn frame_prefix(&self) -> String {
+        let path = self.sheet.as_ref().map_or("frame", |s| s.path.as_str());
+        let file = path.rsplit('/').next().unwrap_or(path);
+        file.split('.').next().unwrap_or(file).to_string()
+    }
+
+    fn set_frames(&mut self, rects: Vec<[u32; 4]>) {
+        let prefix = self.frame_prefix();
+        self.frames = rects
+            .into_iter()
+            .enumerate()
+            .map(|(index, [x, y, width, height])| AtlasFrame {
+                name: format!("{}_{}", prefix, index),
+                x,
+                y,
+                width,
+                height,
+                pivot: self.default_pivot,
+                mesh: None,
+            })
+            .collect();
+        self.selected = None;
+    }
+
+    /// Ajoute une frame [x, y, largeur, hauteur] à la fin (nom libre suivant) et la
+    /// sélectionne.
+    pub fn add_frame(&mut self, [x, y, width, height]: [u32; 4]) {
+        let prefix = self.frame_prefix();
+        let name = (self.frames.len()..)
+            .map(|index| format!("{}_{}", prefix, index))
+            .find(|name| self.frames.iter().all(|frame| &frame.name != name))
+            .unwrap_or(prefix);
+        self.frames.push(AtlasFrame {
+            name,
+            x,
+            y,
+            width,
+            height,
+            pivot: self.default_pivot,
+            mesh: None,
+        });
+        self.selected = Some(self.frames.len() - 1);
+    }
+
+    /// Découpe la planche selon la grille courante.
+    pub fn slice_grid(&mut self) {
+        let Some(sheet) = &self.sheet else {
+            return;
+        };
+        let mut rects = grid_frames(
+            (sheet.width, sheet.height),
+            self.cell_size,
+            self.margin,
+            self.spacing,
+        );
+        if self.skip_empty {
+            rects.retain(|rect| !sheet.is_empty(*rect));
+        }
+        self.set_frames(rects);
+    }
+
+    /// Découpe la planche par détection des zones opaques.
+    pub fn auto_detect(&mut self) {
+        let Some(sheet) = &self.sheet else {
+            return;
+        };
+        let rects = detect_frames(
+            (sheet.width, sheet.height),
+            &sheet.rgba,
+            self.alpha_threshold,
+            self.min_size,
+        );
+        self.set_frames(rects);
+    }
+
+    /// Métadonnées de l'atlas courant. Avec "Tight meshes", le mesh de chaque frame est
+    /// (re)généré depuis l'alpha de la planche.
+    pub fn metadata(&self) -> Result<AtlasMetadata> {
+        let sheet = self
+            .sheet
+            .as_ref()
+            .ok_or_else(|| anyhow!("no sprite sheet opened"))?;
+        let frames = self
+            .frames
+            .iter()
+            .map(|frame| AtlasFrame {
+                mesh: self
+                    .tight_meshes
+                    .then(|| {
+                        SpriteMesh::from_alpha(
+                            (sheet.width, sheet.height),
+                            &sheet.rgba,
+                            [frame.x, frame.y, frame.width, frame.height],
+                            self.alpha_threshold,
+                            SpriteMesh::DEFAULT_MAX_VERTICES,
+                        )
+                    })
+                    .flatten(),
+                ..frame.clone()
+            })
+            .collect();
+        Ok(AtlasMetadata {
+            image: sheet.path.clone(),
+            frames,
+        })
+    }
+
+    /// Sauvegarde l'atlas dans `output_path`. Les noms de frames doivent être uniques.
+    pub fn save(&self) -> Result<()> {
+        for (index, frame) in self.frames.iter().enumerate() {
+            if frame.name.trim().is_empty() || frame.name.contains(char::is_whitespace) {
+                return Err(anyhow!(
+                    "frame {} has an invalid name {:?}",
+                    index,
+                    frame.name
+                ));
+            }
+            if self.frames[..index].iter().any(|f| f.name == frame.name) {
+                return Err(anyhow!("duplicate frame name {:?}", frame.name));
+            }
+        }
+        self.metadata()?.save(&self.vfs, &self.output_path)?;
+        log::info!(
+            "Saved {} frames to {:?}",
+            self.frames.len(),
+            self.output_path
+        );
+        Ok(())
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Sprite sheet");
+            ui.add(
+                egui::TextEdit::singleline(&mut self.image_path)
+                    .hint_text("assets/sprites/hero.png")
+                    .desired_width(220.0),
+            );
+            if ui.button("Open").clicked() {
+                let path = self.image_path.trim().to_string();
+                if let Err(e) = self.open(ui.ctx(), &path) {
+                    log::error!("{:#}", e);
+                }
+            }
+        });
+
+        if self.sheet.is_none() {
+            ui.weak("No sprite sheet opened");
+            return;
+        }
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label("Cell");
+            ui.add(egui::DragValue::new(&mut self.cell_size.0).range(1..=4096));
+            ui.add(egui::DragValue::new(&mut self.cell_size.1).range(1..=4096));
+            ui.label("Margin");
+            ui.add(egui::DragValue::new(&mut self.margin).range(0..=256));
+            ui.label("Spacing");
+            ui.add(egui::DragValue::new(&mut self.spacing).range(0..=256));
+            ui.checkbox(&mut self.skip_empty, "Skip empty");
+            if ui.button("Slice grid").clicked() {
+                self.slice_grid();
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Alpha threshold");
+            ui.add(egui::DragValue::new(&mut self.alpha_threshold));
+            ui.label("Min size");
+            ui.add(egui::DragValue::new(&mut self.min_size).range(1..=256));
+            if ui.button("Auto-detect").clicked() {
+                self.auto_detect();
+            }
+            if ui
+                .add_enabled(!self.frames.is_empty(), egui::Button::new("Clear"))
+                .clicked()
+            {
+                self.frames.clear();
+                self.selected = None;
+            }
+        });
+        ui.weak("Drag on the sheet to add a frame by hand");
+        ui.horizontal(|ui| {
+            ui.label("Default pivot");
+            ui.add(
+                egui::DragValue::new(&mut self.default_pivot[0])
+                    .range(0.0..=1.0)
+                    .speed(0.01),
+            );
+            ui.add(
+                egui::DragValue::new(&mut self.default_pivot[1])
+                    .range(0.0..=1.0)
+                    .speed(0.01),
+            );
+            ui.checkbox(&mut self.tight_meshes, "Tight meshes")
+                .on_hover_text("Draw each frame with a mesh fitted to its opaque pixels");
+            ui.label("Zoom");
+            ui.add(egui::Slider::new(&mut self.zoom, 0.5..=8.0));
+        });
+
+        ui.separator();
+        ui.columns(2, |columns| {
+            egui::ScrollArea::both()
+                .id_salt("sprite_slicer_preview")
+                .max_height(400.0)
+                .show(&mut columns[0], |ui| self.preview(ui));
+            egui::ScrollArea::vertical()
+                .id_salt("sprite_slicer_frames")
+                .max_height(400.0)
+                .show(&mut columns[1], |ui| self.frame_list(ui));
+        });
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label("Save as");
+            ui.add(egui::TextEdit::singleline(&mut self.output_path).desired_width(220.0));
+            if ui
+                .add_enabled(!self.frames.is_empty(), egui::Button::new("Save"))
+                .clicked()
+                && let Err(e) = self.save()
+            {
+                log::error!("{:#}", e);
+            }
+        });
+    }
+
+    /// Planche avec les frames encadrées et le pivot de la frame sélectionnée. Un clic
+    /// sélectionne la frame sous le curseur, un glisser trace une nouvelle frame.
+    fn preview(&mut self, ui: &mut egui::Ui) {
+        let Some(sheet) = &self.sheet else {
+            return;
+        };
+        let sheet_size = (sheet.width, sheet.height);
+        let size = egui::vec2(sheet.width as f32, sheet.height as f32) * self.zoom;
+        let response = ui.add(
+            egui::Image::new((sheet.texture.id(), size))
+                .fit_to_exact_size(size)
+                .sense(egui::Sense::click_and_drag()),
+        );
+        let origin = response.rect.min;
+        let to_screen = |x: f32, y: f32| origin + egui::vec2(x, y) * self.zoom;
+
+        let painter = ui.painter_at(response.rect);
+        for (index, frame) in self.frames.iter().enumerate() {
+            let rect = egui::Rect::from_min_max(
+                to_screen(frame.x as f32, frame.y as f32),
+                to_screen(
+                    (frame.x + frame.width) as f32,
+                    (frame.y + frame.height) as f32,
+                ),
+            );
+            let color = if self.selected == Some(index) {
+                egui::Color32::YELLOW
+            } else {
+                egui::Color32::from_rgb(80, 160, 255)
+            };
+            painter.rect_stroke(
+                rect,
+                0.0,
+                egui::Stroke::new(1.0, color),
+                egui::StrokeKind::Inside,
+            );
+            if self.selected == Some(index) {
+                let pivot = to_screen(
+                    frame.x as f32 + frame.pivot[0] * frame.width as f32,
+                    frame.y as f32 + frame.pivot[1] * frame.height as f32,
+                );
+                painter.circle_stroke(pivot, 3.0, egui::Stroke::new(1.5, egui::Color32::RED));
+            }
+        }
+
+        // Position du pointeur en pixels de la planche
+        let pointer = response
+            .interact_pointer_pos()
+            .map(|pointer| (pointer - origin) / self.zoom)
+            .map(|local| [local.x, local.y]);
+        if response.drag_started() {
+            self.drag_start = pointer;
+        }
+        if let (Some(start), Some(end)) = (self.drag_start, pointer) {
+            if let Some([x, y, width, height]) = rect_between(start, end, sheet_size) {
+                let rect = egui::Rect::from_min_max(
+                    to_screen(x as f32, y as f32),
+                    to_screen((x + width) as f32, (y + height) as f32),
+                );
+                painter.rect_stroke(
+                    rect,
+                    0.0,
+                    egui::Stroke::new(1.0, egui::Color32::GREEN),
+                    egui::StrokeKind::Inside,
+                );
+            }
+            if response.drag_stopped() {
+                self.drag_start = None;
+                if let Some(rect) = rect_between(start, end, sheet_size) {
+                    self.add_frame(rect);
+                }
+            }
+        }
+
+        if response.clicked()
+            && let Some(pointer) = response.interact_pointer_pos()
+        {
+            let local = (pointer - origin) / self.zoom;
+            self.selected = self.frames.iter().position(|frame| {
+                local.x >= frame.x as f32
+                    && local.y >= frame.y as f32
+                    && local.x < (frame.x + frame.width) as f32
+                    && local.y < (frame.y + frame.height) as f32
+            });
+        }
+    }
+
+    /// Liste éditable des frames : nom, pivot et ordre ; rectangle de la frame sélectionnée.
+    fn frame_list(&mut self, ui: &mut egui::Ui) {
+        if self.frames.is_empty() {
+            ui.weak("Slice the sheet to create frames");
+            return;
+        }
+        if let (Some(index), Some(sheet)) = (self.selected, &self.sheet)
+            && let Some(frame) = self.frames.get_mut(index)
+        {
+            ui.horizontal(|ui| {
+                ui.label("Rect");
+                ui.add(egui::DragValue::new(&mut frame.x).range(0..=sheet.width - 1));
+                ui.add(egui::DragValue::new(&mut frame.y).range(0..=sheet.height - 1));
+                ui.add(egui::DragValue::new(&mut frame.width).range(1..=sheet.width - frame.x));
+                ui.add(egui::DragValue::new(&mut frame.height).range(1..=sheet.height - frame.y));
+            });
+            ui.separator();
+        }
+
+        let mut remove = None;
+        let mut swap = None;
+        let count = self.frames.len();
+        for (index, frame) in self.frames.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                if ui
+                    .selectable_label(self.selected == Some(index), format!("#{}", index))
+                    .clicked()
+                {
+                    self.selected = Some(index);
+                }
+                ui.add(egui::TextEdit::singleline(&mut frame.name).desired_width(100.0));
+                ui.label("pivot");
+                ui.add(
+                    egui::DragValue::new(&mut frame.pivot[0])
+                        .range(0.0..=1.0)
+                        .speed(0.01),
+                );
+                ui.add(
+                    egui::DragValue::new(&mut frame.pivot[1])
+                        .range(0.0..=1.0)
+                        .speed(0.01),
+                );
+                if ui
+                    .add_enabled(index > 0, egui::Button::new("⬆").small())
+                    .clicked()
+                {
+                    swap = Some((index, index - 1));
+                }
+                if ui
+                    .add_enabled(index + 1 < count, egui::Button::new("⬇").small())
+                    .clicked()
+                {
+                    swap = Some((index, index + 1));
+                }
+                if ui.small_button("x").clicked() {
+                    remove = Some(index);
+                }
+            });
+        }
+        if let Some((a, b)) = swap {
+            self.frames.swap(a, b);
+            if self.selected == Some(a) {
+                self.selected = Some(b);
+            }
+        }
+        if let Some(index) = remove {
+            self.frames.remove(index);
+            self.selected = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grid_respects_margin_and_spacing() {
+        let frames = grid_frames((70, 36), (32, 32), 2, 2);
+        assert_eq!(frames, [[2, 2, 32, 32], [36, 2, 32, 32]]);
+        assert!(grid_frames((70, 36), (0, 32), 0, 0).is_empty());
+    }
+
+    #[test]
+    fn freeform_rect_is_normalized_and_clamped() {
+        assert_eq!(
+            rect_between([10.5, 8.0], [2.2, 1.9], (64, 64)),
+            Some([2, 1, 9, 7])
+        );
+        assert_eq!(
+            rect_between([-5.0, 60.0], [70.0, 80.0], (64, 64)),
+            Some([0, 60, 64, 4])
+        );
+        assert_eq!(rect_between([3.0, 3.0], [3.0, 9.0], (64, 64)), None);
+    }
+
+    #[test]
+    fn detects_opaque_islands_in_reading_order() {
+        let (width, height) = (10, 6);
+        let mut rgba = vec![0u8; width * height * 4];
+        let mut fill = |x0: usize, y0: usize, x1: usize, y1: usize| {
+            for y in y0..=y1 {
+                for x in x0..=x1 {
+                    rgba[(y * width + x) * 4 + 3] = 255;
+                }
+            }
+        };
+        fill(6, 0, 8, 2); // en haut à droite
+        fill(0, 1, 2, 3); // à gauche, un peu plus bas : même rangée
+        fill(3, 4, 3, 4); // pixel isolé, en diagonale du précédent : même zone
+        fill(5, 5, 5, 5); // trop petit
+
+        let frames = detect_frames((width as u32, height as u32), &rgba, 0, 2);
+        assert_eq!(frames, [[0, 1, 4, 4], [6, 0, 3, 3]]);
+    }
+}
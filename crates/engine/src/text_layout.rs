@@ -0,0 +1,248 @@
+//! Cache de mise en page du texte : les textes mis en forme (`ShapedText`, voir
+//! `TextShaper`) sont gardés entre les frames, indexés par (texte, parties, taille, largeur
+//! de retour à la ligne), et ne sont recalculés que si une de ces entrées change. Un HUD dont
+//! les textes changent peu ne refait donc pas la mise en forme à chaque frame. Le cache garde
+//! aussi l'atlas des glyphes dessinés (`GlyphAtlas`).
+//!
+//! Les polices sont celles d'egui (`Context::set_fonts`) : quand elles changent, les textes
+//! en cache et l'atlas sont oubliés.
+//!
+//! ```ignore
+//! cache.label(ui, "Score: 120", &font, egui::Color32::WHITE);
+//! // Ou, pour placer le texte soi-même :
+//! let text = cache.layout(ctx, "Score: 120", &font, f32::INFINITY);
+//! cache.paint(ui.painter(), pos, &text, |_, _| GlyphStyle::new(egui::Color32::WHITE));
+//! // Une fois par frame, après le dessin :
+//! cache.end_frame();
+//! ```
+
+use std::{
+    collections::HashMap,
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::Arc,
+};
+
+use crate::{GlyphAtlas, GlyphStyle, ShapedGlyph, ShapedText, TextRun, TextShaper};
+
+/// Entrées d'une mise en page (les flottants comparés bit à bit).
+#[derive(Debug, Clone, PartialEq)]
+struct TextLayoutKey {
+    text: String,
+    runs: Vec<TextRun>,
+    size: u32,
+    wrap_width: u32,
+}
+
+#[derive(Clone)]
+struct CachedLayout {
+    key: TextLayoutKey,
+    text: Arc<ShapedText>,
+    /// Dernière frame où la mise en page a servi.
+    used: u64,
+}
+
+/// Textes mis en forme récemment. Les entrées inutilisées pendant une frame sont retirées
+/// par `end_frame`.
+#[derive(Clone, Default)]
+pub struct TextLayoutCache {
+    /// Indexé par le hash des entrées : une recherche n'alloue pas.
+    entries: HashMap<u64, CachedLayout>,
+    shaper: TextShaper,
+    atlas: GlyphAtlas,
+    /// Frame où les polices d'egui ont été comparées à celles de `shaper`.
+    fonts_checked: Option<u64>,
+    frame: u64,
+    /// Nombre de mises en page calculées (et non trouvées dans le cache).
+    layouts: u64,
+}
+
+impl TextLayoutCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Texte mis en forme, d'une seule police. `wrap_width` = `f32::INFINITY` pour ne couper
+    /// qu'aux retours à la ligne du texte.
+    pub fn layout(
+        &mut self,
+        ctx: &egui::Context,
+        text: &str,
+        font: &egui::FontId,
+        wrap_width: f32,
+    ) -> Arc<ShapedText> {
+        let runs = [TextRun::new(text.len(), font.family.clone())];
+        self.layout_runs(ctx, text, &runs, font.size, wrap_width)
+    }
+
+    /// Comme `layout`, pour un texte à plusieurs parties (voir `TextShaper::layout_runs`).
+    pub fn layout_runs(
+        &mut self,
+        ctx: &egui::Context,
+        text: &str,
+        runs: &[TextRun],
+        size: f32,
+        wrap_width: f32,
+    ) -> Arc<ShapedText> {
+        self.update_fonts(ctx);
+        let mut hasher = DefaultHasher::new();
+        (text, size.to_bits(), wrap_width.to_bits()).hash(&mut hasher);
+        for run in runs {
+            (run.end, &run.family, run.object_width.map(f32::to_bits)).hash(&mut hasher);
+        }
+        let hash = hasher.finish();
+
+        if let Some(entry) = self.entries.get_mut(&hash)
+            && entry.key.text == text
+            && entry.key.runs == runs
+            && entry.key.size == size.to_bits()
+            && entry.key.wrap_width == wrap_width.to_bits()
+        {
+            entry.used = self.frame;
+            return entry.text.clone();
+        }
+
+        let shaped = Arc::new(self.shaper.layout_runs(text, runs, size, wrap_width));
+        self.layouts += 1;
+        let key = TextLayoutKey {
+            text: text.to_string(),
+            runs: runs.to_vec(),
+            size: size.to_bits(),
+            wrap_width: wrap_width.to_bits(),
+        };
+        self.entries.insert(
+            hash,
+            CachedLayout {
+                key,
+                text: shaped.clone(),
+                used: self.frame,
+            },
+        );
+        shaped
+    }
+
+    /// Reprend les polices d'egui si elles ont changé (vérifié une fois par frame).
+    fn update_fonts(&mut self, ctx: &egui::Context) {
+        if self.fonts_checked == Some(self.frame) {
+            return;
+        }
+        self.fonts_checked = Some(self.frame);
+        let shaper = ctx.fonts(|fonts| {
+            let fonts = fonts.lock();
+            let definitions = fonts.fonts.definitions();
+            (!self.shaper.matches(definitions)).then(|| TextShaper::new(definitions))
+        });
+        if let Some(shaper) = shaper {
+            self.shaper = shaper;
+            self.entries.clear();
+            self.atlas.clear();
+        }
+    }
+
+    /// Dessine `text` (mis en forme par ce cache) à `origin`, avec l'apparence de chaque
+    /// glyphe donnée par `style`.
+    pub fn paint(
+        &mut self,
+        painter: &egui::Painter,
+        origin: egui::Pos2,
+        text: &ShapedText,
+        style: impl FnMut(usize, &ShapedGlyph) -> GlyphStyle,
+    ) {
+        self.atlas.paint(painter, &self.shaper, origin, text, style);
+    }
+
+    /// Dessine `text` comme un label egui, sans retour à la ligne automatique.
+    pub fn label(
+        &mut self,
+        ui: &mut egui::Ui,
+        text: &str,
+        font: &egui::FontId,
+        color: egui::Color32,
+    ) -> egui::Response {
+        let shaped = self.layout(&ui.ctx().clone(), text, font, f32::INFINITY);
+        let (rect, response) = ui.allocate_exact_size(shaped.size, egui::Sense::hover());
+        self.paint(ui.painter(), rect.min, &shaped, |_, _| {
+            GlyphStyle::new(color)
+        });
+        response.widget_info(|| egui::WidgetInfo::labeled(egui::WidgetType::Label, true, text));
+        response
+    }
+
+    /// Mise en forme utilisée pour les textes du cache.
+    pub fn shaper(&self) -> &TextShaper {
+        &self.shaper
+    }
+
+    pub fn atlas(&self) -> &GlyphAtlas {
+        &self.atlas
+    }
+
+    /// À appeler une fois par frame, après le dessin : oublie les textes qui n'ont pas été
+    /// dessinés pendant la frame.
+    pub fn end_frame(&mut self) {
+        let frame = self.frame;
+        self.entries.retain(|_, entry| entry.used == frame);
+        self.frame += 1;
+    }
+
+    /// Nombre de textes en cache.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Nombre de mises en page calculées depuis la création du cache.
+    pub fn layout_count(&self) -> u64 {
+        self.layouts
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layouts_are_reused_until_inputs_change() {
+        let ctx = egui::Context::default();
+        let mut cache = TextLayoutCache::new();
+        let font = egui::FontId::proportional(14.0);
+
+        let _ = ctx.run(egui::RawInput::default(), |ctx| {
+            let score = cache.layout(ctx, "Score: 12", &font, f32::INFINITY);
+            let again = cache.layout(ctx, "Score: 12", &font, f32::INFINITY);
+            assert!(Arc::ptr_eq(&score, &again));
+            assert_eq!(cache.layout_count(), 1);
+            assert_eq!(score.glyphs.len(), "Score: 12".len());
+
+            cache.layout(ctx, "Score: 12", &font, 40.0);
+            cache.layout(ctx, "Score: 12", &egui::FontId::monospace(14.0), 40.0);
+            cache.layout(ctx, "Score: 13", &font, f32::INFINITY);
+            assert_eq!(cache.layout_count(), 4);
+        });
+        cache.end_frame();
+        assert_eq!(cache.len(), 4);
+
+        // Seul le texte encore dessiné reste en cache ; ses glyphes sont rastérisés au dessin
+        let output = ctx.run(egui::RawInput::default(), |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                cache.label(ui, "Score: 13", &font, egui::Color32::WHITE);
+            });
+        });
+        cache.end_frame();
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.layout_count(), 4);
+        // Un glyphe par caractère de "Score: 13", l'espace sans image
+        assert_eq!(cache.atlas().len(), 9);
+        let texture = cache.atlas().texture_id(0).unwrap();
+        assert!(output.shapes.iter().any(|shape| matches!(
+            &shape.shape,
+            egui::Shape::Mesh(mesh) if mesh.texture_id == texture && mesh.indices.len() == 6 * 8
+        )));
+    }
+}
@@ -0,0 +1,831 @@
+//! Mise en forme du texte : ordre bidirectionnel, formes contextuelles et polices de secours
+//! par langue.
+//!
+//! `TextShaper` met en forme le texte avec les polices enregistrées dans egui
+//! (`FontDefinitions`). L'algorithme bidirectionnel Unicode (unicode-bidi) donne le niveau de
+//! chaque caractère et l'ordre d'affichage des suites de même sens sur chaque ligne ;
+//! rustybuzz choisit et place les glyphes de chaque suite : formes liées de l'arabe,
+//! ligatures, crénage, parenthèses retournées dans le texte de droite à gauche. Chaque
+//! caractère prend la première police de sa famille qui a son glyphe. Les lignes sont
+//! coupées aux espaces (un mot plus long que la largeur dépasse).
+//!
+//! Le résultat (`ShapedText`) est dessiné avec les glyphes rastérisés par `GlyphAtlas` ;
+//! `TextLayoutCache` garde les deux entre les frames. `FontFallbacks` choisit, selon la langue
+//! du jeu, les polices essayées en premier (arabe, devanagari...). Les polices chargées
+//! depuis le VFS (`load_font`, `load_dir`) sont rechargées par `reload` quand leur fichier
+//! change. Le moteur fournit DejaVu Sans (`engine/fonts/DejaVuSans.ttf` : latin, grec,
+//! cyrillique, arabe, hébreu), absente des polices par défaut d'egui.
+//!
+//! Les polices d'emoji en couleur (COLR, CBDT, sbix) vont dans la famille
+//! `COLOR_EMOJI_FAMILY` (`FontFallbacks::set_emoji_fonts`) : `TextShaper` les essaie avant
+//! les emoji monochromes d'egui, qui restent ceux des textes dessinés par egui lui-même (il
+//! ne sait dessiner que des contours).
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    ops::Range,
+    sync::Arc,
+};
+
+use anyhow::{Context, Result};
+use rustybuzz::{Direction, UnicodeBuffer, ttf_parser};
+use unicode_bidi::{BidiInfo, ParagraphInfo};
+
+use crate::AssetLoader;
+
+/// Famille egui des polices d'emoji en couleur, essayées par `TextShaper` dans toutes les
+/// familles juste avant les emoji d'egui.
+pub const COLOR_EMOJI_FAMILY: &str = "color-emoji";
+
+/// Police d'emoji monochromes des polices par défaut d'egui.
+const EGUI_EMOJI_FONT: &str = "NotoEmoji-Regular";
+
+/// Sens d'écriture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TextDirection {
+    LeftToRight,
+    RightToLeft,
+}
+
+impl TextDirection {
+    /// Sens d'un paragraphe : celui de sa première lettre (de gauche à droite par défaut).
+    pub fn of_text(text: &str) -> Self {
+        let bidi = BidiInfo::new(text, None);
+        match bidi.paragraphs.first() {
+            Some(paragraph) if paragraph.level.is_rtl() => TextDirection::RightToLeft,
+            _ => TextDirection::LeftToRight,
+        }
+    }
+}
+
+/// Glyphe d'une des polices d'un `TextShaper`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FontGlyph {
+    /// Indice de la police (`TextShaper::font_data`).
+    pub font: usize,
+    pub id: u16,
+}
+
+/// Partie d'un texte mis en forme par `TextShaper::layout_runs`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextRun {
+    /// Fin de la partie dans le texte (octets) ; elle commence à la fin de la précédente.
+    pub end: usize,
+    pub family: egui::FontFamily,
+    /// Largeur (points) des objets dessinés à part (icônes) : chaque caractère de la partie
+    /// occupe cette largeur, sans glyphe.
+    pub object_width: Option<f32>,
+}
+
+impl TextRun {
+    pub fn new(end: usize, family: egui::FontFamily) -> Self {
+        Self {
+            end,
+            family,
+            object_width: None,
+        }
+    }
+
+    pub fn object(end: usize, width: f32) -> Self {
+        Self {
+            end,
+            family: egui::FontFamily::Proportional,
+            object_width: Some(width),
+        }
+    }
+}
+
+/// Glyphe placé.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShapedGlyph {
+    /// `None` pour un objet (`TextRun::object_width`).
+    pub glyph: Option<FontGlyph>,
+    /// Origine du glyphe sur la ligne de base, depuis le coin haut gauche du texte (points).
+    pub pos: egui::Pos2,
+    pub advance: f32,
+    /// Taille de la police du glyphe (points).
+    pub size: f32,
+    /// Début (octets) du caractère dans le texte ; le premier caractère pour une ligature.
+    pub cluster: usize,
+    /// Indices de la partie (`TextRun`) et de la ligne.
+    pub run: usize,
+    pub row: usize,
+}
+
+/// Ligne d'un `ShapedText`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShapedRow {
+    pub rect: egui::Rect,
+    /// Hauteur de la ligne de base depuis le haut du texte.
+    pub baseline: f32,
+}
+
+/// Texte mis en forme, glyphes dans l'ordre d'affichage (de gauche à droite, ligne par
+/// ligne).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ShapedText {
+    pub glyphs: Vec<ShapedGlyph>,
+    pub rows: Vec<ShapedRow>,
+    pub size: egui::Vec2,
+}
+
+/// Suite de caractères mis en forme ensemble : même partie, même sens, même police.
+struct Item {
+    range: Range<usize>,
+    run: usize,
+    /// `None` pour les objets.
+    font: Option<usize>,
+    rtl: bool,
+}
+
+/// Texte mis en forme, pas encore découpé en lignes.
+struct Shaping<'a> {
+    items: Vec<Item>,
+    /// Glyphes de chaque suite.
+    glyphs: Vec<Vec<RawGlyph>>,
+    faces: Vec<Option<rustybuzz::Face<'a>>>,
+    /// Polices de chaque partie.
+    chains: Vec<Vec<usize>>,
+    size: f32,
+}
+
+/// Glyphe mis en forme, pas encore placé sur une ligne.
+struct RawGlyph {
+    glyph: Option<FontGlyph>,
+    cluster: usize,
+    advance: f32,
+    offset: egui::Vec2,
+    size: f32,
+}
+
+/// Mise en forme avec les polices d'egui. Les indices de police des glyphes ne valent que
+/// pour le `TextShaper` qui les a produits.
+#[derive(Clone, Default)]
+pub struct TextShaper {
+    fonts: Vec<(String, Arc<egui::FontData>)>,
+    families: BTreeMap<egui::FontFamily, Vec<String>>,
+}
+
+impl TextShaper {
+    pub fn new(definitions: &egui::FontDefinitions) -> Self {
+        Self {
+            fonts: definitions
+                .font_data
+                .iter()
+                .map(|(name, data)| (name.clone(), data.clone()))
+                .collect(),
+            families: definitions.families.clone(),
+        }
+    }
+
+    /// `true` si `definitions` a les mêmes polices (mêmes données) et les mêmes familles.
+    pub fn matches(&self, definitions: &egui::FontDefinitions) -> bool {
+        self.families == definitions.families
+            && self.fonts.len() == definitions.font_data.len()
+            && self.fonts.iter().zip(&definitions.font_data).all(
+                |((name, data), (other_name, other))| {
+                    name == other_name && Arc::ptr_eq(data, other)
+                },
+            )
+    }
+
+    pub fn font_data(&self, font: usize) -> Option<&egui::FontData> {
+        self.fonts.get(font).map(|(_, data)| data.as_ref())
+    }
+
+    /// Police `font` lue par ttf-parser (`None` si le fichier est invalide).
+    pub fn face(&self, font: usize) -> Option<ttf_parser::Face<'_>> {
+        let data = self.font_data(font)?;
+        ttf_parser::Face::parse(&data.font, data.index).ok()
+    }
+
+    /// Polices de `family`, par ordre de priorité (celles de `Proportional` pour une famille
+    /// inconnue).
+    fn chain(&self, family: &egui::FontFamily) -> Vec<usize> {
+        let names = self
+            .families
+            .get(family)
+            .or_else(|| self.families.get(&egui::FontFamily::Proportional))
+            .map_or(&[][..], Vec::as_slice);
+        let emoji = self
+            .families
+            .get(&egui::FontFamily::Name(COLOR_EMOJI_FAMILY.into()))
+            .map_or(&[][..], Vec::as_slice);
+        let at = names
+            .iter()
+            .position(|name| name == EGUI_EMOJI_FONT)
+            .unwrap_or(names.len());
+        names[..at]
+            .iter()
+            .chain(emoji)
+            .chain(&names[at..])
+            .filter_map(|name| self.fonts.iter().position(|(font, _)| font == name))
+            .collect()
+    }
+
+    /// Met en forme `text`, d'une seule police. `wrap_width` = `f32::INFINITY` pour ne couper
+    /// qu'aux retours à la ligne du texte.
+    pub fn layout(
+        &self,
+        text: &str,
+        family: &egui::FontFamily,
+        size: f32,
+        wrap_width: f32,
+    ) -> ShapedText {
+        self.layout_runs(
+            text,
+            &[TextRun::new(text.len(), family.clone())],
+            size,
+            wrap_width,
+        )
+    }
+
+    /// Met en forme `text`, découpé en parties (`runs`, la dernière allant jusqu'à la fin du
+    /// texte). L'ordre bidirectionnel porte sur tout le texte, parties confondues.
+    pub fn layout_runs(
+        &self,
+        text: &str,
+        runs: &[TextRun],
+        size: f32,
+        wrap_width: f32,
+    ) -> ShapedText {
+        let faces: Vec<Option<rustybuzz::Face>> = self
+            .fonts
+            .iter()
+            .map(|(_, data)| rustybuzz::Face::from_slice(&data.font, data.index))
+            .collect();
+        let chains: Vec<Vec<usize>> = runs.iter().map(|run| self.chain(&run.family)).collect();
+        let bidi = BidiInfo::new(text, None);
+        let items = Self::itemize(text, runs, &chains, &bidi, &faces);
+        let glyphs = items
+            .iter()
+            .map(|item| self.shape(text, item, &runs[item.run], &faces, size))
+            .collect();
+        let shaping = Shaping {
+            items,
+            glyphs,
+            faces,
+            chains,
+            size,
+        };
+
+        // Largeur de chaque caractère, comptée au début de son groupe (ligatures)
+        let mut widths = vec![0.0; text.len()];
+        for glyph in shaping.glyphs.iter().flatten() {
+            widths[glyph.cluster] += glyph.advance;
+        }
+
+        let mut layout = ShapedText::default();
+        for paragraph in &bidi.paragraphs {
+            let content = paragraph.range.start
+                ..paragraph.range.start
+                    + text[paragraph.range.clone()]
+                        .trim_end_matches(['\n', '\r'])
+                        .len();
+            for line in break_lines(text, content, &widths, wrap_width) {
+                self.place_row(&mut layout, &shaping, &bidi, paragraph, line);
+            }
+        }
+        if layout.rows.is_empty() {
+            let fonts = shaping.chains.first().map_or(&[][..], Vec::as_slice);
+            let (ascent, height) = self.metrics(&shaping, fonts);
+            layout.rows.push(ShapedRow {
+                rect: egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(0.0, height)),
+                baseline: ascent,
+            });
+            layout.size.y = height;
+        }
+        layout
+    }
+
+    /// Découpe le texte en suites de même partie, même niveau bidirectionnel et même police.
+    /// Un caractère garde la police de la suite en cours si elle a son glyphe (ou si c'est un
+    /// caractère de liaison), sinon il prend la première police de sa famille qui l'a.
+    fn itemize(
+        text: &str,
+        runs: &[TextRun],
+        chains: &[Vec<usize>],
+        bidi: &BidiInfo,
+        faces: &[Option<rustybuzz::Face>],
+    ) -> Vec<Item> {
+        let has_glyph = |font: usize, c: char| {
+            faces[font]
+                .as_ref()
+                .is_some_and(|face| face.glyph_index(c).is_some())
+        };
+        let mut items: Vec<Item> = Vec::new();
+        let mut run = 0;
+        for (index, c) in text.char_indices() {
+            while run + 1 < runs.len() && index >= runs[run].end {
+                run += 1;
+            }
+            let rtl = bidi.levels[index].is_rtl();
+            let paragraph_start = bidi
+                .paragraphs
+                .iter()
+                .any(|paragraph| paragraph.range.start == index);
+            let current = items
+                .last()
+                .filter(|item| item.run == run && item.rtl == rtl && !paragraph_start)
+                .map(|item| item.font);
+            let font = match runs[run].object_width {
+                Some(_) => None,
+                None => match current.flatten() {
+                    Some(font) if has_glyph(font, c) || is_joiner(c) => Some(font),
+                    current => chains[run]
+                        .iter()
+                        .copied()
+                        .find(|&font| has_glyph(font, c))
+                        .or(current)
+                        .or(chains[run].first().copied()),
+                },
+            };
+            let end = index + c.len_utf8();
+            match items.last_mut() {
+                Some(item) if current.is_some() && item.font == font => item.range.end = end,
+                _ => items.push(Item {
+                    range: index..end,
+                    run,
+                    font,
+                    rtl,
+                }),
+            }
+        }
+        items
+    }
+
+    /// Glyphes d'une suite avec rustybuzz, dans l'ordre d'affichage de la suite.
+    fn shape(
+        &self,
+        text: &str,
+        item: &Item,
+        run: &TextRun,
+        faces: &[Option<rustybuzz::Face>],
+        size: f32,
+    ) -> Vec<RawGlyph> {
+        let Some(font) = item.font else {
+            let mut objects: Vec<RawGlyph> = text[item.range.clone()]
+                .char_indices()
+                .map(|(index, _)| RawGlyph {
+                    glyph: None,
+                    cluster: item.range.start + index,
+                    advance: run.object_width.unwrap_or_default(),
+                    offset: egui::Vec2::ZERO,
+                    size,
+                })
+                .collect();
+            if item.rtl {
+                objects.reverse();
+            }
+            return objects;
+        };
+        let Some(face) = &faces[font] else {
+            return Vec::new();
+        };
+        let tweak = &self.fonts[font].1.tweak;
+        let size = size * tweak.scale;
+        let scale = size / face.units_per_em() as f32;
+        let y_offset = size * tweak.y_offset_factor + tweak.y_offset;
+
+        let mut buffer = UnicodeBuffer::new();
+        buffer.push_str(&text[item.range.clone()]);
+        buffer.set_direction(match item.rtl {
+            true => Direction::RightToLeft,
+            false => Direction::LeftToRight,
+        });
+        let output = rustybuzz::shape(face, &[], buffer);
+        output
+            .glyph_infos()
+            .iter()
+            .zip(output.glyph_positions())
+            .map(|(info, position)| RawGlyph {
+                glyph: Some(FontGlyph {
+                    font,
+                    id: info.glyph_id as u16,
+                }),
+                cluster: item.range.start + info.cluster as usize,
+                advance: position.x_advance as f32 * scale,
+                offset: egui::vec2(
+                    position.x_offset as f32 * scale,
+                    y_offset - position.y_offset as f32 * scale,
+                ),
+                size,
+            })
+            .collect()
+    }
+
+    /// Hauteur au-dessus de la ligne de base et hauteur de ligne : les plus grandes des
+    /// polices `fonts`.
+    fn metrics(&self, shaping: &Shaping, fonts: &[usize]) -> (f32, f32) {
+        let (mut ascent, mut descent, mut gap) = (0.0f32, 0.0f32, 0.0f32);
+        for &font in fonts {
+            let Some(face) = &shaping.faces[font] else {
+                continue;
+            };
+            let size = shaping.size * self.fonts[font].1.tweak.scale;
+            let scale = size / face.units_per_em() as f32;
+            ascent = ascent.max(face.ascender() as f32 * scale);
+            descent = descent.max(-face.descender() as f32 * scale);
+            gap = gap.max(face.line_gap() as f32 * scale);
+        }
+        (ascent, ascent + descent + gap)
+    }
+
+    /// Place les glyphes d'une ligne : suites dans l'ordre d'affichage de unicode-bidi, suites
+    /// mises en forme d'un même niveau inversées si ce niveau va de droite à gauche.
+    fn place_row(
+        &self,
+        layout: &mut ShapedText,
+        shaping: &Shaping,
+        bidi: &BidiInfo,
+        paragraph: &ParagraphInfo,
+        line: Range<usize>,
+    ) {
+        let row = layout.rows.len();
+        let start = layout.glyphs.len();
+        let mut fonts = Vec::new();
+        let mut x = 0.0;
+        if !line.is_empty() {
+            let (levels, runs) = bidi.visual_runs(paragraph, line.clone());
+            for range in runs {
+                let mut order: Vec<usize> = (0..shaping.items.len())
+                    .filter(|&i| {
+                        let item = &shaping.items[i].range;
+                        item.start < range.end && range.start < item.end
+                    })
+                    .collect();
+                if levels[range.start].is_rtl() {
+                    order.reverse();
+                }
+                for i in order {
+                    for glyph in shaping.glyphs[i]
+                        .iter()
+                        .filter(|glyph| range.contains(&glyph.cluster))
+                    {
+                        if let Some(FontGlyph { font, .. }) = glyph.glyph
+                            && !fonts.contains(&font)
+                        {
+                            fonts.push(font);
+                        }
+                        layout.glyphs.push(ShapedGlyph {
+                            glyph: glyph.glyph,
+                            pos: egui::pos2(x + glyph.offset.x, glyph.offset.y),
+                            advance: glyph.advance,
+                            size: glyph.size,
+                            cluster: glyph.cluster,
+                            run: shaping.items[i].run,
+                            row,
+                        });
+                        x += glyph.advance;
+                    }
+                }
+            }
+        }
+        // Ligne sans glyphe de police (vide, ou seulement des objets) : hauteur de la première
+        // police de sa partie
+        if fonts.is_empty() {
+            let run = shaping
+                .items
+                .iter()
+                .find(|item| item.range.contains(&line.start))
+                .map_or(0, |item| item.run);
+            fonts.extend(shaping.chains.get(run).and_then(|chain| chain.first()));
+        }
+        let (ascent, height) = self.metrics(shaping, &fonts);
+        let top = layout.size.y;
+        for glyph in &mut layout.glyphs[start..] {
+            glyph.pos.y += top + ascent;
+        }
+        layout.rows.push(ShapedRow {
+            rect: egui::Rect::from_min_size(egui::pos2(0.0, top), egui::vec2(x, height)),
+            baseline: top + ascent,
+        });
+        layout.size.x = layout.size.x.max(x);
+        layout.size.y += height;
+    }
+}
+
+/// Caractères de liaison et sélecteurs, mis en forme avec le caractère qui les précède.
+fn is_joiner(c: char) -> bool {
+    matches!(c, '\u{200c}' | '\u{200d}' | '\u{fe00}'..='\u{fe0f}' | '\u{e0100}'..='\u{e01ef}')
+}
+
+/// Coupe `range` en lignes d'au plus `wrap_width`, après les espaces. Un mot plus long que la
+/// largeur reste sur sa ligne.
+fn break_lines(
+    text: &str,
+    range: Range<usize>,
+    widths: &[f32],
+    wrap_width: f32,
+) -> Vec<Range<usize>> {
+    let mut lines = Vec::new();
+    let (mut line_start, mut line_width) = (range.start, 0.0);
+    let (mut word_start, mut word_width) = (range.start, 0.0);
+    for (offset, c) in text[range.clone()].char_indices() {
+        let index = range.start + offset;
+        word_width += widths[index];
+        let end = index + c.len_utf8();
+        if !c.is_whitespace() && end < range.end {
+            continue;
+        }
+        // Les espaces en fin de ligne ne comptent pas
+        let visible = match c.is_whitespace() {
+            true => word_width - widths[index],
+            false => word_width,
+        };
+        if line_start < word_start && line_width + visible > wrap_width {
+            lines.push(line_start..word_start);
+            (line_start, line_width) = (word_start, 0.0);
+        }
+        line_width += word_width;
+        (word_start, word_width) = (end, 0.0);
+    }
+    lines.push(line_start..range.end);
+    lines
+}
+
+/// Polices de secours par langue. La chaîne d'une langue ("ar", "hi", "he"...) passe en
+/// tête des familles egui : ses polices sont essayées avant la police principale, puis les
+/// polices par défaut d'egui et les autres polices ajoutées complètent les glyphes manquants.
+///
+/// ```ignore
+/// let mut fallbacks = FontFallbacks::new();
+/// fallbacks.add_font("noto_arabic", loader.load_bytes("fonts/NotoNaskhArabic.ttf")?);
+/// fallbacks.load_font(&loader, "dejavu", "engine/fonts/DejaVuSans.ttf")?;
+/// fallbacks.set_chain("ar", ["noto_arabic"]);
+/// fallbacks.add_font("noto_color_emoji", loader.load_bytes("fonts/NotoColorEmoji.ttf")?);
+/// fallbacks.set_emoji_fonts(["noto_color_emoji"]);
+/// ctx.set_fonts(fallbacks.definitions("ar-EG"));
+/// ```
+#[derive(Clone, Default)]
+pub struct FontFallbacks {
+    fonts: BTreeMap<String, Arc<egui::FontData>>,
+    chains: HashMap<String, Vec<String>>,
+    /// Polices de `COLOR_EMOJI_FAMILY`.
+    emoji: Vec<String>,
+    /// Chemin VFS des polices chargées par `load_font`.
+    sources: BTreeMap<String, String>,
+}
+
+impl FontFallbacks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ajoute une police (fichier `.ttf` / `.otf`).
+    pub fn add_font(&mut self, name: impl Into<String>, bytes: Vec<u8>) {
+        self.fonts
+            .insert(name.into(), Arc::new(egui::FontData::from_owned(bytes)));
+    }
+
+    /// Ajoute la police du fichier `path` (VFS) sous le nom `name`, rechargée par `reload`.
+    pub fn load_font(
+        &mut self,
+        loader: &AssetLoader,
+        name: impl Into<String>,
+        path: &str,
+    ) -> Result<()> {
+        let name = name.into();
+        let bytes = loader
+            .load_bytes(path)
+            .with_context(|| format!("failed to load font {:?}", path))?;
+        self.add_font(name.clone(), bytes);
+        self.sources.insert(name, path.to_string());
+        Ok(())
+    }
+
+    /// Charge les `.ttf` / `.otf` de `dir` (VFS), nommées d'après leur fichier sans
+    /// extension. Un fichier illisible est signalé dans le log et ignoré. Retourne le nombre
+    /// de polices chargées.
+    pub fn load_dir(&mut self, loader: &AssetLoader, dir: &str) -> usize {
+        let mut loaded = 0;
+        for entry in loader.vfs().read_dir(dir) {
+            let Some((stem, extension)) = entry.name.rsplit_once('.') else {
+                continue;
+            };
+            if entry.is_dir || !matches!(extension.to_lowercase().as_str(), "ttf" | "otf") {
+                continue;
+            }
+            match self.load_font(loader, stem, &entry.path) {
+                Ok(()) => loaded += 1,
+                Err(err) => log::warn!("Skipping font {:?}: {:#}", entry.path, err),
+            }
+        }
+        loaded
+    }
+
+    /// Relit les polices chargées depuis `path`. Retourne `true` si au moins une a changé :
+    /// les `definitions` sont alors à repasser à egui (`Context::set_fonts`).
+    pub fn reload(&mut self, loader: &AssetLoader, path: &str) -> Result<bool> {
+        let names: Vec<String> = self
+            .sources
+            .iter()
+            .filter(|(_, source)| *source == path)
+            .map(|(name, _)| name.clone())
+            .collect();
+        for name in &names {
+            self.load_font(loader, name.clone(), path)?;
+        }
+        Ok(!names.is_empty())
+    }
+
+    /// Polices de `locale`, par ordre de priorité.
+    pub fn set_chain<S: Into<String>>(
+        &mut self,
+        locale: impl Into<String>,
+        fonts: impl IntoIterator<Item = S>,
+    ) {
+        let fonts = fonts.into_iter().map(Into::into).collect();
+        self.chains.insert(locale.into().to_lowercase(), fonts);
+    }
+
+    /// Polices d'emoji en couleur (`COLOR_EMOJI_FAMILY`), par ordre de priorité. Elles ne
+    /// sont pas ajoutées aux familles d'egui.
+    pub fn set_emoji_fonts<S: Into<String>>(&mut self, fonts: impl IntoIterator<Item = S>) {
+        self.emoji = fonts.into_iter().map(Into::into).collect();
+    }
+
+    /// Chaîne de `locale` : celle de la langue et de la région ("pt-br") si elle existe,
+    /// sinon celle de la langue ("pt"). Vide si aucune n'est configurée.
+    pub fn chain(&self, locale: &str) -> &[String] {
+        let locale = locale.to_lowercase().replace('_', "-");
+        let language = locale.split('-').next().unwrap_or_default();
+        self.chains
+            .get(&locale)
+            .or_else(|| self.chains.get(language))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Polices egui par défaut, avec la chaîne de `locale` en tête de chaque famille et les
+    /// autres polices ajoutées en dernier recours, et la famille `COLOR_EMOJI_FAMILY`. Les
+    /// noms qui n'ont pas été ajoutés avec `add_font` sont ignorés.
+    pub fn definitions(&self, locale: &str) -> egui::FontDefinitions {
+        let mut definitions = egui::FontDefinitions::default();
+        for (name, data) in &self.fonts {
+            definitions.font_data.insert(name.clone(), data.clone());
+        }
+        let chain: Vec<&String> = self
+            .chain(locale)
+            .iter()
+            .filter(|name| self.fonts.contains_key(*name))
+            .collect();
+        for fonts in definitions.families.values_mut() {
+            fonts.retain(|font| !chain.contains(&font));
+            for (index, name) in chain.iter().enumerate() {
+                fonts.insert(index, (*name).clone());
+            }
+            for name in self.fonts.keys() {
+                if !fonts.contains(name) && !self.emoji.contains(name) {
+                    fonts.push(name.clone());
+                }
+            }
+        }
+        let emoji: Vec<String> = self
+            .emoji
+            .iter()
+            .filter(|name| self.fonts.contains_key(*name))
+            .cloned()
+            .collect();
+        if !emoji.is_empty() {
+            definitions
+                .families
+                .insert(egui::FontFamily::Name(COLOR_EMOJI_FAMILY.into()), emoji);
+        }
+        definitions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Polices par défaut d'egui, avec DejaVu Sans (arabe, hébreu) en secours.
+    fn shaper() -> TextShaper {
+        let mut fallbacks = FontFallbacks::new();
+        fallbacks.add_font(
+            "dejavu",
+            include_bytes!("../../../engine/fonts/DejaVuSans.ttf").to_vec(),
+        );
+        TextShaper::new(&fallbacks.definitions("fr"))
+    }
+
+    /// Caractères dans l'ordre d'affichage (le premier de chaque groupe).
+    fn visual(text: &str, shaped: &ShapedText) -> String {
+        shaped
+            .glyphs
+            .iter()
+            .filter_map(|glyph| text[glyph.cluster..].chars().next())
+            .collect()
+    }
+
+    #[test]
+    fn rtl_runs_are_reordered_for_display() {
+        let shaper = shaper();
+        let family = egui::FontFamily::Proportional;
+        let layout = |text: &str| shaper.layout(text, &family, 14.0, f32::INFINITY);
+        let shape = |text: &str| visual(text, &layout(text));
+        assert_eq!(shape("Score: 12"), "Score: 12");
+
+        // Paragraphe de droite à gauche : les lettres et les mots s'inversent, pas les
+        // chiffres
+        assert_eq!(shape("שלום עולם"), "םלוע םולש");
+        assert_eq!(shape("שלום 123"), "123 םולש");
+        // Mot hébreu dans une phrase de gauche à droite : seul le mot s'inverse, les
+        // parenthèses sont au niveau du paragraphe et gardent leur sens
+        assert_eq!(shape("Hi (שלום) you"), "Hi (םולש) you");
+        // Parenthèses de droite à gauche : déplacées avec le texte et dessinées en miroir,
+        // la fermante (à gauche) avec le glyphe de l'ouvrante
+        let quoted = layout("(שלום)");
+        assert_eq!(visual("(שלום)", &quoted), ")םולש(");
+        let first = quoted.glyphs[0].glyph.unwrap();
+        let face = shaper.face(first.font).unwrap();
+        assert_eq!(face.glyph_index('(').map(|id| id.0), Some(first.id));
+
+        // Une ligne par paragraphe, chacune dans son ordre d'affichage
+        let lines = layout("אב\nגד");
+        assert_eq!(visual("אב\nגד", &lines), "באדג");
+        assert_eq!(lines.rows.len(), 2);
+        assert_eq!(
+            lines
+                .glyphs
+                .iter()
+                .map(|glyph| glyph.row)
+                .collect::<Vec<_>>(),
+            [0, 0, 1, 1]
+        );
+        assert_eq!(
+            TextDirection::of_text("123 مرحبا"),
+            TextDirection::RightToLeft
+        );
+
+        // Retour à la ligne aux espaces
+        let wrapped = shaper.layout("one two three", &family, 14.0, 40.0);
+        assert_eq!(wrapped.rows.len(), 3);
+        assert!(wrapped.size.x <= 40.0);
+    }
+
+    #[test]
+    fn arabic_letters_are_joined() {
+        let shaper = shaper();
+        let family = egui::FontFamily::Proportional;
+        let text = "مرحبا";
+        let shaped = shaper.layout(text, &family, 14.0, f32::INFINITY);
+
+        // Un glyphe par lettre, de la dernière à la première, de gauche à droite
+        let clusters: Vec<usize> = shaped.glyphs.iter().map(|glyph| glyph.cluster).collect();
+        assert_eq!(clusters, [8, 6, 4, 2, 0]);
+        assert!(shaped.glyphs.windows(2).all(|g| g[0].pos.x < g[1].pos.x));
+        // Chaque lettre est liée à ses voisines : aucun glyphe n'est la forme isolée du
+        // caractère
+        for glyph in &shaped.glyphs {
+            let id = glyph.glyph.unwrap();
+            let face = shaper.face(id.font).unwrap();
+            let c = text[glyph.cluster..].chars().next().unwrap();
+            let isolated = face.glyph_index(c).unwrap().0;
+            assert_ne!(id.id, isolated, "{:?} is not joined", c);
+        }
+
+        // Lam-alef : une ligature
+        let ligature = shaper.layout("لا", &family, 14.0, f32::INFINITY);
+        assert_eq!(ligature.glyphs.len(), 1);
+        assert_eq!(ligature.glyphs[0].cluster, 0);
+    }
+
+    #[test]
+    fn fallback_chains_follow_the_locale() {
+        let mut fallbacks = FontFallbacks::new();
+        fallbacks.add_font("arabic", Vec::new());
+        fallbacks.add_font("cjk", Vec::new());
+        fallbacks.set_chain("ar", ["arabic", "missing"]);
+        assert_eq!(fallbacks.chain("ar-EG"), ["arabic", "missing"]);
+        assert!(fallbacks.chain("fr").is_empty());
+
+        let definitions = fallbacks.definitions("AR_eg");
+        let proportional = &definitions.families[&egui::FontFamily::Proportional];
+        assert_eq!(proportional[0], "arabic");
+        assert!(!proportional.contains(&"missing".to_string()));
+        assert_eq!(proportional.last().unwrap(), "cjk");
+        let french = fallbacks.definitions("fr");
+        assert_ne!(
+            french.families[&egui::FontFamily::Proportional][0],
+            "arabic"
+        );
+
+        // Les emoji en couleur passent avant ceux d'egui, pour la mise en forme seulement
+        let mut fallbacks = FontFallbacks::new();
+        let dejavu = include_bytes!("../../../engine/fonts/DejaVuSans.ttf");
+        fallbacks.add_font("color", dejavu.to_vec());
+        fallbacks.set_emoji_fonts(["color", "missing"]);
+        let definitions = fallbacks.definitions("fr");
+        let proportional = &definitions.families[&egui::FontFamily::Proportional];
+        assert!(!proportional.contains(&"color".to_string()));
+        let shaper = TextShaper::new(&definitions);
+        let chain: Vec<&str> = shaper
+            .chain(&egui::FontFamily::Proportional)
+            .into_iter()
+            .map(|font| shaper.fonts[font].0.as_str())
+            .collect();
+        let color = chain.iter().position(|name| *name == "color").unwrap();
+        assert_eq!(chain[color + 1], EGUI_EMOJI_FONT);
+    }
+}
@@ -1,6 +1,8 @@
 use egui_wgpu::wgpu;
 use uuid::Uuid;
 
+use crate::{MemoryCategory, MipmapGenerator, TrackedMemory};
+
 #[derive(Clone, Copy)]
 pub struct TextureHandle(Uuid);
 
@@ -18,14 +20,159 @@ pub struct Texture2D {
     pub sampler: wgpu::Sampler,
     pub width: u32,
     pub height: u32,
+    /// VFS path the texture was loaded from (set by `AssetLoader::load_texture`), used to
+    /// reference it from copied / saved entities. `None` for generated textures.
+    pub path: Option<String>,
+    memory: TrackedMemory,
+}
+
+/// How a `Texture2D` is created from an image: sampling filter, wrapping, color space and
+/// mipmaps. Defaults match pixel art: nearest filtering, clamped edges, sRGB, no mipmaps.
+///
+/// ```ignore
+/// let descriptor = TextureDescriptor2D::new().trilinear().repeat().with_mipmaps(true);
+/// let texture = Texture2D::from_bytes_with(device, queue, &bytes, &descriptor)?;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextureDescriptor2D {
+    /// Magnification and minification filter.
+    pub filter: wgpu::FilterMode,
+    /// Filter between mip levels: `Linear` blends the two nearest levels (trilinear with a
+    /// linear `filter`), hiding the level transitions when zooming. Unused without mipmaps.
+    pub mipmap_filter: wgpu::FilterMode,
+    /// Wrapping on both axes, for UVs outside [0, 1].
+    pub address_mode: wgpu::AddressMode,
+    /// Decode the texels as sRGB (colors); `false` for data textures such as normal maps.
+    pub srgb: bool,
+    /// Generate the full mipmap chain (smoother minification, 1/3 more memory).
+    pub mipmaps: bool,
+}
+
+impl Default for TextureDescriptor2D {
+    fn default() -> Self {
+        Self {
+            filter: wgpu::FilterMode::Nearest,
+            mipmap_filter: wgpu::FilterMode::Linear,
+            address_mode: wgpu::AddressMode::ClampToEdge,
+            srgb: true,
+            mipmaps: false,
+        }
+    }
+}
+
+impl TextureDescriptor2D {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_filter(mut self, filter: wgpu::FilterMode) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Bilinear filtering (smooth scaling).
+    pub fn linear(self) -> Self {
+        self.with_filter(wgpu::FilterMode::Linear)
+    }
+
+    pub fn with_mipmap_filter(mut self, mipmap_filter: wgpu::FilterMode) -> Self {
+        self.mipmap_filter = mipmap_filter;
+        self
+    }
+
+    /// Trilinear filtering: bilinear within a mip level and blended between levels, for
+    /// zoomed-out sprites and tilemaps. Only differs from `linear` with mipmaps enabled.
+    pub fn trilinear(self) -> Self {
+        self.linear().with_mipmap_filter(wgpu::FilterMode::Linear)
+    }
+
+    pub fn with_address_mode(mut self, address_mode: wgpu::AddressMode) -> Self {
+        self.address_mode = address_mode;
+        self
+    }
+
+    /// Tile the texture outside [0, 1] (scrolling backgrounds, patterns).
+    pub fn repeat(self) -> Self {
+        self.with_address_mode(wgpu::AddressMode::Repeat)
+    }
+
+    /// Tile the texture mirrored every other repetition (seamless patterns).
+    pub fn mirror(self) -> Self {
+        self.with_address_mode(wgpu::AddressMode::MirrorRepeat)
+    }
+
+    pub fn with_srgb(mut self, srgb: bool) -> Self {
+        self.srgb = srgb;
+        self
+    }
+
+    pub fn with_mipmaps(mut self, mipmaps: bool) -> Self {
+        self.mipmaps = mipmaps;
+        self
+    }
+
+    /// GPU format of the texture.
+    pub fn format(&self) -> wgpu::TextureFormat {
+        match self.srgb {
+            true => wgpu::TextureFormat::Rgba8UnormSrgb,
+            false => wgpu::TextureFormat::Rgba8Unorm,
+        }
+    }
+
+    /// Number of mip levels of a `width` x `height` texture (1 without mipmaps).
+    pub fn mip_level_count(&self, width: u32, height: u32) -> u32 {
+        match self.mipmaps {
+            true => u32::BITS - width.max(height).max(1).leading_zeros(),
+            false => 1,
+        }
+    }
+
+    fn create_sampler(&self, device: &wgpu::Device) -> wgpu::Sampler {
+        device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("texture2d_sampler"),
+            address_mode_u: self.address_mode,
+            address_mode_v: self.address_mode,
+            address_mode_w: self.address_mode,
+            mag_filter: self.filter,
+            min_filter: self.filter,
+            mipmap_filter: self.mipmap_filter,
+            ..Default::default()
+        })
+    }
 }
 
 impl Texture2D {
     /// Create a GPU texture from raw image bytes (any format supported by `image` crate).
+    #[track_caller]
     pub fn from_bytes(
         device: &wgpu::Device,
         queue: &wgpu::Queue,
         bytes: &[u8],
+    ) -> Result<Self, image::ImageError> {
+        Self::from_bytes_with(device, queue, bytes, &TextureDescriptor2D::default())
+    }
+
+    /// Like `from_bytes`, but the texels are sampled as-is (no sRGB decoding): for data
+    /// textures such as normal maps.
+    #[track_caller]
+    pub fn from_bytes_linear(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        bytes: &[u8],
+    ) -> Result<Self, image::ImageError> {
+        let descriptor = TextureDescriptor2D::default().with_srgb(false);
+        Self::from_bytes_with(device, queue, bytes, &descriptor)
+    }
+
+    /// Create a GPU texture from raw image bytes with the filtering, wrapping, format and
+    /// mipmaps of `descriptor`. Mipmaps are generated on the GPU from the uploaded image
+    /// (see `MipmapGenerator`).
+    #[track_caller]
+    pub fn from_bytes_with(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        bytes: &[u8],
+        descriptor: &TextureDescriptor2D,
     ) -> Result<Self, image::ImageError> {
         let img = image::load_from_memory(bytes)?.to_rgba8();
         let (width, height) = img.dimensions();
@@ -34,16 +181,26 @@ impl Texture2D {
             height,
             depth_or_array_layers: 1,
         };
+        let format = descriptor.format();
+        let mip_level_count = descriptor.mip_level_count(width, height);
 
+        // COPY_SRC: can be packed into a `TextureArray`
+        let mut usage = wgpu::TextureUsages::TEXTURE_BINDING
+            | wgpu::TextureUsages::COPY_DST
+            | wgpu::TextureUsages::COPY_SRC;
+        if mip_level_count > 1 {
+            // Mip levels are rendered from the previous one
+            usage |= wgpu::TextureUsages::RENDER_ATTACHMENT;
+        }
         let texture = device.create_texture(&wgpu::TextureDescriptor {
             label: Some("texture2d_texture"),
             size,
-            mip_level_count: 1,
+            mip_level_count,
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::Rgba8UnormSrgb,
-            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
-            view_formats: &[wgpu::TextureFormat::Rgba8UnormSrgb],
+            format,
+            usage,
+            view_formats: &[format],
         });
 
         // Upload pixel data (RGBA8)
@@ -57,23 +214,27 @@ impl Texture2D {
             &img,
             wgpu::TexelCopyBufferLayout {
                 offset: 0,
-                bytes_per_row: Some((4 * width) as u32),
-                rows_per_image: Some(height as u32),
+                bytes_per_row: Some(4 * width),
+                rows_per_image: Some(height),
             },
             size,
         );
+        if mip_level_count > 1 {
+            let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("texture2d_mipmaps"),
+            });
+            MipmapGenerator::new(device).generate(device, &mut encoder, &texture);
+            queue.submit(Some(encoder.finish()));
+        }
+        let bytes = (0..mip_level_count)
+            .map(|level| {
+                let size = size.mip_level_size(level, wgpu::TextureDimension::D2);
+                4 * size.width as u64 * size.height as u64
+            })
+            .sum();
 
         let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
-        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-            label: Some("texture2d_sampler"),
-            address_mode_u: wgpu::AddressMode::ClampToEdge,
-            address_mode_v: wgpu::AddressMode::ClampToEdge,
-            address_mode_w: wgpu::AddressMode::ClampToEdge,
-            mag_filter: wgpu::FilterMode::Nearest,
-            min_filter: wgpu::FilterMode::Nearest,
-            mipmap_filter: wgpu::FilterMode::Nearest,
-            ..Default::default()
-        });
+        let sampler = descriptor.create_sampler(device);
 
         Ok(Self {
             texture,
@@ -81,10 +242,34 @@ impl Texture2D {
             sampler,
             width,
             height,
+            path: None,
+            memory: TrackedMemory::new(MemoryCategory::Textures, bytes),
         })
     }
 
+    /// Set the VFS path of the texture, also used as its label in leak reports.
+    pub fn set_path(&mut self, path: impl Into<String>) {
+        let path = path.into();
+        self.memory.set_label(path.as_str());
+        self.path = Some(path);
+    }
+
+    /// Tracking entry for a bind group created from this texture, to keep next to it in
+    /// bind group caches so leaked ones show up in `live_resources`.
+    #[track_caller]
+    pub fn track_bind_group(&self) -> TrackedMemory {
+        let label = self.path.as_deref().unwrap_or("texture2d");
+        TrackedMemory::labeled(MemoryCategory::BindGroups, 0, label)
+    }
+
+    /// Size of the texture in GPU memory (RGBA8, mipmaps included), as counted by the memory
+    /// panel.
+    pub fn gpu_bytes(&self) -> u64 {
+        self.memory.bytes()
+    }
+
     /// Convenience: load image file from disk and create Texture2D.
+    #[track_caller]
     pub fn from_file(
         device: &wgpu::Device,
         queue: &wgpu::Queue,
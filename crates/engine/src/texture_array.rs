@@ -0,0 +1,209 @@
+use std::sync::Arc;
+
+use egui_wgpu::wgpu;
+
+use crate::{MemoryCategory, Texture2D, TrackedMemory};
+
+/// `D2Array` texture packing same-size `Texture2D`s, one per layer.
+///
+/// Sprites whose textures live in the same array can be drawn in a single instanced call:
+/// each instance carries its layer index (see `InstanceData::texture_layer`).
+/// Layers are filled with GPU copies of the source textures; the array grows (doubling its
+/// layer count, up to the device limit) by reallocating and copying the layers again.
+pub struct TextureArray {
+    pub texture: wgpu::Texture,
+    pub view: wgpu::TextureView,
+    pub sampler: wgpu::Sampler,
+    pub width: u32,
+    pub height: u32,
+    /// Source texture of each layer (kept alive so the layer keys stay valid).
+    layers: Vec<Arc<Texture2D>>,
+    capacity: u32,
+    max_layers: u32,
+    memory: TrackedMemory,
+}
+
+impl TextureArray {
+    /// Format of the layers: the one used by `Texture2D`.
+    pub const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+    /// Number of layers allocated by `new`.
+    pub const INITIAL_CAPACITY: u32 = 8;
+
+    /// Create an empty array for `width` x `height` textures.
+    #[track_caller]
+    pub fn new(device: &wgpu::Device, width: u32, height: u32) -> Self {
+        let max_layers = device.limits().max_texture_array_layers;
+        let capacity = Self::INITIAL_CAPACITY.min(max_layers);
+        let (texture, view) = Self::create_texture(device, width, height, capacity);
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("texture_array_sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Nearest,
+            min_filter: wgpu::FilterMode::Nearest,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        Self {
+            texture,
+            view,
+            sampler,
+            width,
+            height,
+            layers: Vec::new(),
+            capacity,
+            max_layers,
+            memory: Self::track(width, height, capacity),
+        }
+    }
+
+    #[track_caller]
+    fn track(width: u32, height: u32, layers: u32) -> TrackedMemory {
+        let bytes = 4 * width as u64 * height as u64 * layers as u64;
+        let label = format!("texture_array {}x{} ({} layers)", width, height, layers);
+        TrackedMemory::labeled(MemoryCategory::Textures, bytes, label)
+    }
+
+    fn create_texture(
+        device: &wgpu::Device,
+        width: u32,
+        height: u32,
+        layers: u32,
+    ) -> (wgpu::Texture, wgpu::TextureView) {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("texture_array"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: layers,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: Self::FORMAT,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor {
+            dimension: Some(wgpu::TextureViewDimension::D2Array),
+            ..Default::default()
+        });
+        (texture, view)
+    }
+
+    /// Record the copy of `source` into layer `layer`.
+    fn copy_layer(&self, encoder: &mut wgpu::CommandEncoder, source: &Texture2D, layer: u32) {
+        encoder.copy_texture_to_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture: &source.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::TexelCopyTextureInfo {
+                texture: &self.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d {
+                    x: 0,
+                    y: 0,
+                    z: layer,
+                },
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::Extent3d {
+                width: self.width,
+                height: self.height,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+
+    /// Whether `texture` can be stored in this array.
+    pub fn accepts(&self, texture: &Texture2D) -> bool {
+        (texture.width, texture.height) == (self.width, self.height)
+            && texture.texture.format() == Self::FORMAT
+    }
+
+    /// Layer holding `texture`, if it was inserted.
+    pub fn layer_of(&self, texture: &Arc<Texture2D>) -> Option<u32> {
+        self.layers
+            .iter()
+            .position(|layer| Arc::ptr_eq(layer, texture))
+            .map(|index| index as u32)
+    }
+
+    /// Number of layers in use.
+    pub fn len(&self) -> u32 {
+        self.layers.len() as u32
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty()
+    }
+
+    /// Return the layer of `texture`, copying it into a new layer (recorded in `encoder`)
+    /// the first time. Returns `None` if the texture does not fit (see `accepts`) or the
+    /// array is full. The `bool` is `true` when the array was reallocated: bind groups
+    /// created from the previous `view` must then be recreated.
+    #[track_caller]
+    pub fn insert(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        texture: &Arc<Texture2D>,
+    ) -> Option<(u32, bool)> {
+        if let Some(layer) = self.layer_of(texture) {
+            return Some((layer, false));
+        }
+        if !self.accepts(texture) {
+            return None;
+        }
+
+        let layer = self.len();
+        let mut reallocated = false;
+        if layer >= self.capacity {
+            if self.capacity >= self.max_layers {
+                return None;
+            }
+            self.capacity = (self.capacity * 2).min(self.max_layers);
+            (self.texture, self.view) =
+                Self::create_texture(device, self.width, self.height, self.capacity);
+            self.memory = Self::track(self.width, self.height, self.capacity);
+            for (index, source) in self.layers.iter().enumerate() {
+                self.copy_layer(encoder, source, index as u32);
+            }
+            reallocated = true;
+        }
+
+        self.copy_layer(encoder, texture, layer);
+        self.layers.push(texture.clone());
+        Some((layer, reallocated))
+    }
+
+    /// Bind group (binding 0 = `D2Array` view, binding 1 = sampler) for a layout declaring a
+    /// `texture_2d_array` (see `SpriteRenderer::array_texture_bind_layout`).
+    pub fn create_bind_group(
+        &self,
+        device: &wgpu::Device,
+        bind_group_layout: &wgpu::BindGroupLayout,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("texture_array_bind_group"),
+            layout: bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&self.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+            ],
+        })
+    }
+}
@@ -0,0 +1,278 @@
+use crate::Tilemap;
+
+/// How the neighbours of a cell are turned into a terrain bitmask.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TerrainMode {
+    /// 4 bits (north, east, south, west): 16 tiles.
+    Edges,
+    /// 8 bits, a corner only counting when both of its edges are set: 47 tiles ("blob").
+    #[default]
+    Blob,
+}
+
+impl TerrainMode {
+    pub fn name(self) -> &'static str {
+        match self {
+            TerrainMode::Edges => "edges",
+            TerrainMode::Blob => "blob",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "edges" => Some(TerrainMode::Edges),
+            "blob" => Some(TerrainMode::Blob),
+            _ => None,
+        }
+    }
+}
+
+/// Tile of a terrain used for one neighbour bitmask.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TerrainRule {
+    pub mask: u8,
+    /// Tile index in the tileset (gid - `first_gid`).
+    pub tile: u32,
+}
+
+/// A terrain of a `Tileset` (grass, water, wall...): the tile to use for each combination
+/// of neighbours of the same terrain. Painted with `Tilemap::paint_terrain`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Terrain {
+    pub name: String,
+    pub mode: TerrainMode,
+    pub rules: Vec<TerrainRule>,
+}
+
+impl Terrain {
+    /// Neighbour bits of a mask.
+    pub const NORTH: u8 = 1 << 0;
+    pub const EAST: u8 = 1 << 1;
+    pub const SOUTH: u8 = 1 << 2;
+    pub const WEST: u8 = 1 << 3;
+    pub const NORTH_EAST: u8 = 1 << 4;
+    pub const SOUTH_EAST: u8 = 1 << 5;
+    pub const SOUTH_WEST: u8 = 1 << 6;
+    pub const NORTH_WEST: u8 = 1 << 7;
+
+    /// Offset of the neighbour of each bit, in bit order.
+    pub const NEIGHBOURS: [(i32, i32); 8] = [
+        (0, -1),
+        (1, 0),
+        (0, 1),
+        (-1, 0),
+        (1, -1),
+        (1, 1),
+        (-1, 1),
+        (-1, -1),
+    ];
+
+    pub fn new(name: impl Into<String>, mode: TerrainMode) -> Self {
+        Self {
+            name: name.into(),
+            mode,
+            rules: Vec::new(),
+        }
+    }
+
+    /// Drop the bits the mode ignores: corners in `Edges` mode, and corners whose two
+    /// edges are not both set in `Blob` mode.
+    pub fn normalize(&self, mask: u8) -> u8 {
+        let edges = mask & 0x0F;
+        if self.mode == TerrainMode::Edges {
+            return edges;
+        }
+        let corners = [
+            (Self::NORTH_EAST, Self::NORTH | Self::EAST),
+            (Self::SOUTH_EAST, Self::SOUTH | Self::EAST),
+            (Self::SOUTH_WEST, Self::SOUTH | Self::WEST),
+            (Self::NORTH_WEST, Self::NORTH | Self::WEST),
+        ];
+        corners
+            .iter()
+            .filter(|(corner, sides)| mask & corner != 0 && edges & sides == *sides)
+            .fold(edges, |mask, (corner, _)| mask | corner)
+    }
+
+    /// Every distinct normalized mask of the mode (16 or 47), sorted.
+    pub fn masks(&self) -> Vec<u8> {
+        let mut masks: Vec<u8> = (0..=255).map(|mask| self.normalize(mask)).collect();
+        masks.sort_unstable();
+        masks.dedup();
+        masks
+    }
+
+    /// Tile authored for `mask` (normalized first).
+    pub fn rule(&self, mask: u8) -> Option<u32> {
+        let mask = self.normalize(mask);
+        self.rules
+            .iter()
+            .find(|rule| rule.mask == mask)
+            .map(|rule| rule.tile)
+    }
+
+    /// Set (or with `None` remove) the tile of `mask`.
+    pub fn set_rule(&mut self, mask: u8, tile: Option<u32>) {
+        let mask = self.normalize(mask);
+        self.rules.retain(|rule| rule.mask != mask);
+        if let Some(tile) = tile {
+            self.rules.push(TerrainRule { mask, tile });
+            self.rules.sort_by_key(|rule| rule.mask);
+        }
+    }
+
+    /// Tile for a cell whose neighbours of the same terrain are `mask`. Without a rule for
+    /// that exact mask, the rule sharing the most neighbours (and adding the fewest) is used,
+    /// so a partial rule set still paints something sensible.
+    pub fn tile_for(&self, mask: u8) -> Option<u32> {
+        let mask = self.normalize(mask);
+        self.rule(mask).or_else(|| {
+            self.rules
+                .iter()
+                .max_by_key(|rule| {
+                    let shared = (rule.mask & mask).count_ones() as i32;
+                    let extra = (rule.mask & !mask).count_ones() as i32;
+                    // À score égal, le plus petit masque l'emporte
+                    (shared * 2 - extra, std::cmp::Reverse(rule.mask))
+                })
+                .map(|rule| rule.tile)
+        })
+    }
+
+    /// Whether tile index `tile` is one of the terrain's tiles.
+    pub fn contains_tile(&self, tile: u32) -> bool {
+        self.rules.iter().any(|rule| rule.tile == tile)
+    }
+}
+
+impl Tilemap {
+    /// Whether cell (`x`, `y`) of `layer` holds a tile of terrain `terrain` of tileset
+    /// `tileset` (flip flags are ignored; cells outside the map never match).
+    pub fn is_terrain(
+        &self,
+        layer: usize,
+        (tileset, terrain): (usize, usize),
+        x: i64,
+        y: i64,
+    ) -> bool {
+        let (width, height) = self.size();
+        if x < 0 || y < 0 || x >= width as i64 || y >= height as i64 {
+            return false;
+        }
+        let Some(set) = self.tilesets.get(tileset) else {
+            return false;
+        };
+        let Some(terrain) = set.terrains.get(terrain) else {
+            return false;
+        };
+        let gid = self.tile(layer, x as u32, y as u32).unwrap_or_default() & Self::GID_MASK;
+        set.contains(gid) && terrain.contains_tile(gid - set.first_gid)
+    }
+
+    /// Neighbour bitmask (`Terrain::NORTH`...) of cell (`x`, `y`) for a terrain.
+    pub fn terrain_mask(&self, layer: usize, terrain: (usize, usize), x: u32, y: u32) -> u8 {
+        Terrain::NEIGHBOURS
+            .iter()
+            .enumerate()
+            .filter(|(_, (dx, dy))| {
+                self.is_terrain(layer, terrain, x as i64 + *dx as i64, y as i64 + *dy as i64)
+            })
+            .fold(0, |mask, (bit, _)| mask | 1 << bit)
+    }
+
+    /// Paint terrain `terrain` of tileset `tileset` on cell (`x`, `y`) of `layer` (or erase it
+    /// with `paint = false`), then pick again the tiles of the cell and of its neighbours of
+    /// the same terrain from their new bitmask. Returns `false` if nothing changed.
+    pub fn paint_terrain(
+        &mut self,
+        layer: usize,
+        (tileset, terrain): (usize, usize),
+        x: u32,
+        y: u32,
+        paint: bool,
+    ) -> bool {
+        let Some(set) = self.tilesets.get(tileset) else {
+            return false;
+        };
+        let Some(rules) = set.terrains.get(terrain).cloned() else {
+            return false;
+        };
+        let first_gid = set.first_gid;
+        let key = (tileset, terrain);
+
+        let mut changed = false;
+        if paint {
+            if !self.is_terrain(layer, key, x as i64, y as i64) {
+                // Tuile provisoire : la bonne est choisie avec les voisins ci-dessous
+                let Some(tile) = rules.tile_for(0) else {
+                    return false;
+                };
+                changed |= self.set_tile(layer, x, y, first_gid + tile);
+            }
+        } else if self.is_terrain(layer, key, x as i64, y as i64) {
+            changed |= self.set_tile(layer, x, y, Self::EMPTY);
+        }
+
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let (cx, cy) = (x as i64 + dx, y as i64 + dy);
+                if !self.is_terrain(layer, key, cx, cy) {
+                    continue;
+                }
+                let (cx, cy) = (cx as u32, cy as u32);
+                let mask = self.terrain_mask(layer, key, cx, cy);
+                if let Some(tile) = rules.tile_for(mask) {
+                    changed |= self.set_tile(layer, cx, cy, first_gid + tile);
+                }
+            }
+        }
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Tileset;
+
+    #[test]
+    fn blob_mode_has_47_masks() {
+        let terrain = Terrain::new("grass", TerrainMode::Blob);
+        assert_eq!(terrain.masks().len(), 47);
+        assert_eq!(Terrain::new("", TerrainMode::Edges).masks().len(), 16);
+        // Coin sans ses deux côtés : ignoré
+        assert_eq!(
+            terrain.normalize(Terrain::NORTH | Terrain::NORTH_EAST),
+            Terrain::NORTH
+        );
+    }
+
+    #[test]
+    fn painting_terrain_updates_neighbours() {
+        let mut tileset = Tileset::new("terrain", "terrain.png", (64, 64), (16, 16));
+        let mut terrain = Terrain::new("path", TerrainMode::Edges);
+        // 0 = isolée, 1 = bout gauche, 2 = milieu horizontal, 3 = bout droit
+        terrain.set_rule(0, Some(0));
+        terrain.set_rule(Terrain::EAST, Some(1));
+        terrain.set_rule(Terrain::EAST | Terrain::WEST, Some(2));
+        terrain.set_rule(Terrain::WEST, Some(3));
+        tileset.terrains.push(terrain);
+
+        let mut map = Tilemap::new(8, 4, 16, 16);
+        map.add_tileset(tileset);
+        map.add_layer("ground");
+        for x in 1..=3 {
+            assert!(map.paint_terrain(0, (0, 0), x, 1, true));
+        }
+        let row: Vec<_> = (0..5).map(|x| map.tile(0, x, 1).unwrap()).collect();
+        assert_eq!(row, [0, 2, 3, 4, 0]);
+
+        assert!(map.paint_terrain(0, (0, 0), 3, 1, false));
+        assert_eq!(map.tile(0, 2, 1), Some(4));
+        assert_eq!(map.tile(0, 3, 1), Some(Tilemap::EMPTY));
+        // Pas de règle pour un voisin au nord : la plus proche (isolée) est utilisée
+        assert!(map.paint_terrain(0, (0, 0), 6, 2, true));
+        assert!(map.paint_terrain(0, (0, 0), 6, 3, true));
+        assert_eq!(map.tile(0, 6, 3), Some(1));
+    }
+}
@@ -0,0 +1,1294 @@
+use std::{collections::HashMap, sync::Arc};
+
+use anyhow::{Context, Result};
+use nalgebra::{Matrix4, Point3};
+
+use crate::{
+    Biome, Camera2D, CaveGenerator, DungeonGenerator, Heightmap, Rng, Terrain, TerrainMode,
+    TileLayer, Tilemap, Tileset, Vfs,
+};
+
+/// Tool used by `TilemapEditor` when clicking in the viewport.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TileTool {
+    /// Paint the selected tile under the cursor while the button is held.
+    Brush,
+    /// Fill the rectangle between the press and release cells.
+    Rectangle,
+    /// Fill the 4-connected region of identical tiles under the cursor.
+    Bucket,
+    Eraser,
+    /// Select the tile under the cursor, then go back to the brush.
+    Picker,
+    /// Paint the selected collision flags.
+    Collision,
+    /// Paint the selected terrain, choosing edge / corner tiles from its rules.
+    Terrain,
+}
+
+impl TileTool {
+    pub const ALL: [TileTool; 7] = [
+        TileTool::Brush,
+        TileTool::Rectangle,
+        TileTool::Bucket,
+        TileTool::Eraser,
+        TileTool::Picker,
+        TileTool::Collision,
+        TileTool::Terrain,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TileTool::Brush => "Brush",
+            TileTool::Rectangle => "Rectangle",
+            TileTool::Bucket => "Bucket",
+            TileTool::Eraser => "Eraser",
+            TileTool::Picker => "Picker",
+            TileTool::Collision => "Collision",
+            TileTool::Terrain => "Terrain",
+        }
+    }
+}
+
+/// Generator used by the "Generate" section of `TilemapEditor`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MapGenerator {
+    /// Cellular automaton caves (`CaveGenerator`).
+    Caves,
+    /// Rooms joined by corridors (`DungeonGenerator`).
+    Dungeon,
+    /// Noise heightmap split into biomes (`Heightmap`).
+    Terrain,
+}
+
+impl MapGenerator {
+    pub const ALL: [MapGenerator; 3] = [
+        MapGenerator::Caves,
+        MapGenerator::Dungeon,
+        MapGenerator::Terrain,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            MapGenerator::Caves => "Caves",
+            MapGenerator::Dungeon => "Dungeon",
+            MapGenerator::Terrain => "Terrain",
+        }
+    }
+}
+
+/// Change of one cell, with its value before and after the edit.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CellChange<T> {
+    pub x: u32,
+    pub y: u32,
+    pub before: T,
+    pub after: T,
+}
+
+/// An undoable change of a `Tilemap`, recorded in a `TilemapHistory`.
+#[derive(Debug, Clone)]
+pub enum TilemapEdit {
+    Tiles {
+        layer: usize,
+        cells: Vec<CellChange<u32>>,
+    },
+    Collision {
+        cells: Vec<CellChange<u8>>,
+    },
+    InsertLayer {
+        index: usize,
+        layer: TileLayer,
+    },
+    RemoveLayer {
+        index: usize,
+        layer: TileLayer,
+    },
+    MoveLayer {
+        from: usize,
+        to: usize,
+    },
+}
+
+impl TilemapEdit {
+    /// Set `cells` of `layer` to `gid` and record the cells that actually changed.
+    pub fn paint_tiles(
+        map: &mut Tilemap,
+        layer: usize,
+        cells: impl IntoIterator<Item = (u32, u32)>,
+        gid: u32,
+    ) -> Self {
+        Self::set_tiles(map, layer, cells.into_iter().map(|(x, y)| (x, y, gid)))
+    }
+
+    /// Set each `(x, y, gid)` of `tiles` on `layer` (e.g. a generated map, see `CellGrid::tiles`)
+    /// and record the cells that actually changed.
+    pub fn set_tiles(
+        map: &mut Tilemap,
+        layer: usize,
+        tiles: impl IntoIterator<Item = (u32, u32, u32)>,
+    ) -> Self {
+        let mut changes = Vec::new();
+        for (x, y, gid) in tiles {
+            let Some(before) = map.tile(layer, x, y) else {
+                continue;
+            };
+            if map.set_tile(layer, x, y, gid) {
+                changes.push(CellChange {
+                    x,
+                    y,
+                    before,
+                    after: gid,
+                });
+            }
+        }
+        TilemapEdit::Tiles {
+            layer,
+            cells: changes,
+        }
+    }
+
+    /// Set the collision flags of `cells` and record the cells that actually changed.
+    pub fn paint_collision(
+        map: &mut Tilemap,
+        cells: impl IntoIterator<Item = (u32, u32)>,
+        flags: u8,
+    ) -> Self {
+        let mut changes = Vec::new();
+        for (x, y) in cells {
+            let Some(before) = map.collision(x, y) else {
+                continue;
+            };
+            if map.set_collision(x, y, flags) {
+                changes.push(CellChange {
+                    x,
+                    y,
+                    before,
+                    after: flags,
+                });
+            }
+        }
+        TilemapEdit::Collision { cells: changes }
+    }
+
+    /// Paint (or erase) a terrain on `cells` with `Tilemap::paint_terrain` and record every
+    /// tile it changed, neighbours included.
+    pub fn paint_terrain(
+        map: &mut Tilemap,
+        layer: usize,
+        terrain: (usize, usize),
+        cells: impl IntoIterator<Item = (u32, u32)>,
+        paint: bool,
+    ) -> Self {
+        let mut changes = Vec::new();
+        for (x, y) in cells {
+            let area = rect_cells((x.saturating_sub(1), y.saturating_sub(1)), (x + 1, y + 1));
+            let before: Vec<_> = area.iter().map(|&(x, y)| map.tile(layer, x, y)).collect();
+            if !map.paint_terrain(layer, terrain, x, y, paint) {
+                continue;
+            }
+            for (&(x, y), before) in area.iter().zip(before) {
+                let (Some(before), Some(after)) = (before, map.tile(layer, x, y)) else {
+                    continue;
+                };
+                if before != after {
+                    changes.push(CellChange {
+                        x,
+                        y,
+                        before,
+                        after,
+                    });
+                }
+            }
+        }
+        TilemapEdit::Tiles {
+            layer,
+            cells: changes,
+        }
+    }
+
+    /// `true` if undoing / redoing the edit would not change anything.
+    pub fn is_empty(&self) -> bool {
+        match self {
+            TilemapEdit::Tiles { cells, .. } => cells.is_empty(),
+            TilemapEdit::Collision { cells } => cells.is_empty(),
+            TilemapEdit::MoveLayer { from, to } => from == to,
+            TilemapEdit::InsertLayer { .. } | TilemapEdit::RemoveLayer { .. } => false,
+        }
+    }
+
+    /// Append the cells of `other` if both edits paint the same thing (one undo step per
+    /// brush stroke). Returns `other` back otherwise.
+    fn merge(&mut self, other: TilemapEdit) -> Option<TilemapEdit> {
+        match (self, other) {
+            (
+                TilemapEdit::Tiles { layer, cells },
+                TilemapEdit::Tiles {
+                    layer: other_layer,
+                    cells: other_cells,
+                },
+            ) if *layer == other_layer => cells.extend(other_cells),
+            (TilemapEdit::Collision { cells }, TilemapEdit::Collision { cells: other_cells }) => {
+                cells.extend(other_cells)
+            }
+            (_, other) => return Some(other),
+        }
+        None
+    }
+
+    /// Apply the edit to `map` (`undo` = restore the previous state).
+    fn apply(&self, map: &mut Tilemap, undo: bool) {
+        match self {
+            TilemapEdit::Tiles { layer, cells } => {
+                // À rebours pour l'annulation : une case modifiée deux fois retrouve sa
+                // valeur d'origine
+                if undo {
+                    for cell in cells.iter().rev() {
+                        map.set_tile(*layer, cell.x, cell.y, cell.before);
+                    }
+                } else {
+                    for cell in cells {
+                        map.set_tile(*layer, cell.x, cell.y, cell.after);
+                    }
+                }
+            }
+            TilemapEdit::Collision { cells } => {
+                if undo {
+                    for cell in cells.iter().rev() {
+                        map.set_collision(cell.x, cell.y, cell.before);
+                    }
+                } else {
+                    for cell in cells {
+                        map.set_collision(cell.x, cell.y, cell.after);
+                    }
+                }
+            }
+            TilemapEdit::InsertLayer { index, layer }
+            | TilemapEdit::RemoveLayer { index, layer } => {
+                let insert = matches!(self, TilemapEdit::InsertLayer { .. }) != undo;
+                if insert {
+                    map.insert_layer(*index, layer.clone());
+                } else {
+                    map.remove_layer(*index);
+                }
+            }
+            TilemapEdit::MoveLayer { from, to } => {
+                if undo {
+                    map.move_layer(*to, *from);
+                } else {
+                    map.move_layer(*from, *to);
+                }
+            }
+        }
+    }
+}
+
+/// Undo / redo stacks of `TilemapEdit`s.
+#[derive(Debug, Default)]
+pub struct TilemapHistory {
+    undo: Vec<TilemapEdit>,
+    redo: Vec<TilemapEdit>,
+}
+
+impl TilemapHistory {
+    /// Number of edits kept; the oldest ones are dropped.
+    pub const LIMIT: usize = 256;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record an edit already applied to the map. Clears the redo stack.
+    pub fn push(&mut self, edit: TilemapEdit) {
+        if edit.is_empty() {
+            return;
+        }
+        self.redo.clear();
+        self.undo.push(edit);
+        if self.undo.len() > Self::LIMIT {
+            self.undo.remove(0);
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Revert the last edit. Returns `false` if there is nothing to undo.
+    pub fn undo(&mut self, map: &mut Tilemap) -> bool {
+        let Some(edit) = self.undo.pop() else {
+            return false;
+        };
+        edit.apply(map, true);
+        self.redo.push(edit);
+        true
+    }
+
+    /// Apply again the last undone edit. Returns `false` if there is nothing to redo.
+    pub fn redo(&mut self, map: &mut Tilemap) -> bool {
+        let Some(edit) = self.redo.pop() else {
+            return false;
+        };
+        edit.apply(map, false);
+        self.undo.push(edit);
+        true
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
+
+/// Cells of the rectangle with corners `a` and `b` (included), row by row.
+pub fn rect_cells(a: (u32, u32), b: (u32, u32)) -> Vec<(u32, u32)> {
+    let (x0, x1) = (a.0.min(b.0), a.0.max(b.0));
+    let (y0, y1) = (a.1.min(b.1), a.1.max(b.1));
+    (y0..=y1)
+        .flat_map(|y| (x0..=x1).map(move |x| (x, y)))
+        .collect()
+}
+
+/// Cells of the 4-connected region of `layer` holding the same gid as (`x`, `y`).
+pub fn flood_fill_cells(map: &Tilemap, layer: usize, x: u32, y: u32) -> Vec<(u32, u32)> {
+    let Some(target) = map.tile(layer, x, y) else {
+        return Vec::new();
+    };
+    let (width, height) = map.size();
+    let mut visited = vec![false; (width * height) as usize];
+    let mut stack = vec![(x, y)];
+    let mut cells = Vec::new();
+    visited[(y * width + x) as usize] = true;
+
+    while let Some((x, y)) = stack.pop() {
+        cells.push((x, y));
+        let neighbours = [
+            (x.wrapping_sub(1), y),
+            (x + 1, y),
+            (x, y.wrapping_sub(1)),
+            (x, y + 1),
+        ];
+        for (nx, ny) in neighbours {
+            if nx >= width || ny >= height || visited[(ny * width + nx) as usize] {
+                continue;
+            }
+            if map.tile(layer, nx, ny) == Some(target) {
+                visited[(ny * width + nx) as usize] = true;
+                stack.push((nx, ny));
+            }
+        }
+    }
+    cells
+}
+
+/// Parameters of the "Generate" section of `TilemapEditor`.
+struct GenerateForm {
+    generator: MapGenerator,
+    seed: u64,
+    caves: CaveGenerator,
+    dungeon: DungeonGenerator,
+    /// Size of the terrain features, in tiles.
+    terrain_scale: f32,
+    terrain_octaves: u32,
+    biomes: Vec<Biome>,
+    floor_gid: u32,
+    wall_gid: u32,
+}
+
+impl Default for GenerateForm {
+    fn default() -> Self {
+        Self {
+            generator: MapGenerator::Caves,
+            seed: 1,
+            caves: CaveGenerator::default(),
+            dungeon: DungeonGenerator::default(),
+            terrain_scale: 24.0,
+            terrain_octaves: 4,
+            biomes: vec![
+                Biome::new("Water", 0.35, 1),
+                Biome::new("Sand", 0.45, 2),
+                Biome::new("Grass", 0.75, 3),
+                Biome::new("Rock", 1.0, 4),
+            ],
+            floor_gid: 1,
+            wall_gid: 2,
+        }
+    }
+}
+
+impl GenerateForm {
+    /// Tiles of a `width` x `height` map, from the selected generator.
+    fn tiles(&self, width: u32, height: u32) -> Vec<(u32, u32, u32)> {
+        let mut rng = Rng::new(self.seed);
+        match self.generator {
+            MapGenerator::Caves => self
+                .caves
+                .generate(width, height, &mut rng)
+                .tiles(self.floor_gid, self.wall_gid)
+                .collect(),
+            MapGenerator::Dungeon => self
+                .dungeon
+                .generate(width, height, &mut rng)
+                .grid
+                .tiles(self.floor_gid, self.wall_gid)
+                .collect(),
+            MapGenerator::Terrain => {
+                let heights = Heightmap::generate(
+                    width,
+                    height,
+                    self.seed,
+                    self.terrain_scale,
+                    self.terrain_octaves,
+                );
+                heights.biome_tiles(None, &self.biomes).collect()
+            }
+        }
+    }
+}
+
+/// Tilemap painting panel of the editor: tools, layers and tile palette (`ui`), and
+/// painting in the scene viewport (`viewport`), with undo / redo.
+pub struct TilemapEditor {
+    vfs: Arc<Vfs>,
+    pub tool: TileTool,
+    /// Gid (with flip flags) painted by the brush, rectangle and bucket.
+    pub gid: u32,
+    /// Layer being painted.
+    pub layer: usize,
+    /// Flags painted by `TileTool::Collision` (0 clears).
+    pub collision_flags: u8,
+    /// Paint in the viewport (otherwise clicks go to the scene as usual).
+    pub enabled: bool,
+    /// Draw the collision flags over the map even with other tools.
+    pub show_collision: bool,
+    /// Terrain painted by `TileTool::Terrain`: (tileset, terrain) indices.
+    pub terrain: Option<(usize, usize)>,
+    /// `TileTool::Terrain` erases the terrain instead of painting it.
+    pub terrain_erase: bool,
+    history: TilemapHistory,
+    /// Brush stroke in progress, pushed to the history on release.
+    stroke: Option<TilemapEdit>,
+    /// Cell where the rectangle drag started.
+    rect_start: Option<(u32, u32)>,
+    hovered: Option<(u32, u32)>,
+    /// Palette textures by tileset image (`None` = failed to load).
+    palettes: HashMap<String, Option<egui::TextureHandle>>,
+    /// Parameters of the "new tilemap" form.
+    new_map_size: (u32, u32),
+    new_tile_size: (u32, u32),
+    new_tileset: String,
+    generate: GenerateForm,
+}
+
+impl TilemapEditor {
+    /// Size of a tile button in the palette, in points.
+    const PALETTE_TILE: f32 = 28.0;
+
+    pub fn new(vfs: Arc<Vfs>) -> Self {
+        Self {
+            vfs,
+            tool: TileTool::Brush,
+            gid: 1,
+            layer: 0,
+            collision_flags: Tilemap::COLLISION_SOLID,
+            enabled: true,
+            show_collision: false,
+            terrain: None,
+            terrain_erase: false,
+            history: TilemapHistory::new(),
+            stroke: None,
+            rect_start: None,
+            hovered: None,
+            palettes: HashMap::new(),
+            new_map_size: (64, 32),
+            new_tile_size: (16, 16),
+            new_tileset: String::new(),
+            generate: GenerateForm::default(),
+        }
+    }
+
+    pub fn history(&self) -> &TilemapHistory {
+        &self.history
+    }
+
+    /// `true` while clicks in the viewport paint the map (the editor should not capture
+    /// the mouse then).
+    pub fn is_painting(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn undo(&mut self, map: &mut Tilemap) {
+        self.finish_stroke();
+        self.history.undo(map);
+        self.clamp_layer(map);
+    }
+
+    pub fn redo(&mut self, map: &mut Tilemap) {
+        self.finish_stroke();
+        self.history.redo(map);
+        self.clamp_layer(map);
+    }
+
+    /// Forget the history (e.g. when another map is edited).
+    pub fn reset(&mut self) {
+        self.history.clear();
+        self.stroke = None;
+        self.rect_start = None;
+        self.layer = 0;
+        self.terrain = None;
+    }
+
+    fn clamp_layer(&mut self, map: &Tilemap) {
+        self.layer = self.layer.min(map.layers().len().saturating_sub(1));
+    }
+
+    /// Record `edit` (already applied), merged into the current stroke when possible.
+    fn record(&mut self, edit: TilemapEdit) {
+        let edit = match &mut self.stroke {
+            Some(stroke) => stroke.merge(edit),
+            None => Some(edit),
+        };
+        if let Some(edit) = edit {
+            self.finish_stroke();
+            self.stroke = Some(edit);
+        }
+    }
+
+    fn finish_stroke(&mut self) {
+        if let Some(stroke) = self.stroke.take() {
+            self.history.push(stroke);
+        }
+    }
+
+    /// Form creating a map with one layer and one tileset (image path on the VFS).
+    /// Returns the new map when "Create" is clicked.
+    pub fn new_map_ui(&mut self, ui: &mut egui::Ui) -> Option<Tilemap> {
+        ui.horizontal(|ui| {
+            ui.label("Tileset")
+                .on_hover_text("Image, or .tileset asset with its terrains");
+            ui.text_edit_singleline(&mut self.new_tileset);
+        });
+        ui.horizontal(|ui| {
+            ui.label("Tile size");
+            ui.add(egui::DragValue::new(&mut self.new_tile_size.0).range(1..=1024));
+            ui.add(egui::DragValue::new(&mut self.new_tile_size.1).range(1..=1024));
+        });
+        ui.horizontal(|ui| {
+            ui.label("Map size (tiles)");
+            ui.add(egui::DragValue::new(&mut self.new_map_size.0).range(1..=4096));
+            ui.add(egui::DragValue::new(&mut self.new_map_size.1).range(1..=4096));
+        });
+        if !ui.button("Create tilemap").clicked() {
+            return None;
+        }
+        match self.create_map() {
+            Ok(map) => {
+                self.reset();
+                Some(map)
+            }
+            Err(e) => {
+                log::error!("{:#}", e);
+                None
+            }
+        }
+    }
+
+    fn create_map(&self) -> Result<Tilemap> {
+        let (width, height) = self.new_map_size;
+        let (tile_width, tile_height) = self.new_tile_size;
+        let mut map = Tilemap::new(width, height, tile_width, tile_height);
+        if self
+            .new_tileset
+            .ends_with(&format!(".{}", Tileset::EXTENSION))
+        {
+            map.add_tileset(Tileset::load(&self.vfs, &self.new_tileset)?);
+        } else if !self.new_tileset.is_empty() {
+            let bytes = self.vfs.read_bytes(&self.new_tileset)?;
+            let size = image::load_from_memory(&bytes)
+                .with_context(|| format!("failed to decode tileset {:?}", self.new_tileset))?
+                .to_rgba8()
+                .dimensions();
+            let name = self
+                .new_tileset
+                .rsplit('/')
+                .next()
+                .unwrap_or_default()
+                .to_string();
+            map.add_tileset(Tileset::new(
+                name,
+                self.new_tileset.clone(),
+                size,
+                self.new_tile_size,
+            ));
+        }
+        map.add_layer("Layer 1");
+        Ok(map)
+    }
+
+    /// Panel of the tools, layers and tile palette for `map`.
+    pub fn ui(&mut self, ui: &mut egui::Ui, map: &mut Tilemap) {
+        self.clamp_layer(map);
+
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.enabled, "Paint in viewport");
+            ui.checkbox(&mut self.show_collision, "Show collision");
+        });
+        ui.horizontal_wrapped(|ui| {
+            for tool in TileTool::ALL {
+                ui.selectable_value(&mut self.tool, tool, tool.label());
+            }
+        });
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(self.history.can_undo(), egui::Button::new("Undo"))
+                .clicked()
+            {
+                self.undo(map);
+            }
+            if ui
+                .add_enabled(self.history.can_redo(), egui::Button::new("Redo"))
+                .clicked()
+            {
+                self.redo(map);
+            }
+        });
+
+        if self.tool == TileTool::Collision {
+            ui.horizontal(|ui| {
+                ui.label("Collision");
+                ui.selectable_value(&mut self.collision_flags, 0, "None");
+                ui.selectable_value(&mut self.collision_flags, Tilemap::COLLISION_SOLID, "Solid");
+                ui.selectable_value(
+                    &mut self.collision_flags,
+                    Tilemap::COLLISION_ONE_WAY,
+                    "One way",
+                );
+            });
+        }
+
+        if self.tool == TileTool::Terrain {
+            ui.horizontal(|ui| {
+                let name = self
+                    .terrain
+                    .and_then(|(tileset, terrain)| map.tilesets.get(tileset)?.terrains.get(terrain))
+                    .map_or("(none)", |terrain| terrain.name.as_str());
+                ui.label(format!("Terrain: {}", name));
+                ui.checkbox(&mut self.terrain_erase, "Erase");
+            });
+        }
+
+        ui.separator();
+        self.layers_ui(ui, map);
+        ui.separator();
+        self.palette_ui(ui, map);
+        egui::CollapsingHeader::new("Terrains").show(ui, |ui| self.terrains_ui(ui, map));
+        egui::CollapsingHeader::new("Generate").show(ui, |ui| self.generate_ui(ui, map));
+    }
+
+    /// Procedural generation of the current layer (one undo step).
+    fn generate_ui(&mut self, ui: &mut egui::Ui, map: &mut Tilemap) {
+        let form = &mut self.generate;
+        ui.horizontal(|ui| {
+            for generator in MapGenerator::ALL {
+                ui.selectable_value(&mut form.generator, generator, generator.label());
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Seed");
+            ui.add(egui::DragValue::new(&mut form.seed));
+            if ui.button("Random").clicked() {
+                form.seed = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map_or(0, |time| time.as_nanos() as u64);
+            }
+        });
+
+        let selected_gid = self.gid & Tilemap::GID_MASK;
+        let gid_field = |ui: &mut egui::Ui, label: &str, gid: &mut u32| {
+            ui.horizontal(|ui| {
+                ui.label(label);
+                ui.add(egui::DragValue::new(gid));
+                if ui
+                    .button("Use selected")
+                    .on_hover_text("Tile selected in the palette")
+                    .clicked()
+                {
+                    *gid = selected_gid;
+                }
+            });
+        };
+        match form.generator {
+            MapGenerator::Caves => {
+                ui.add(egui::Slider::new(&mut form.caves.fill, 0.2..=0.8).text("Wall fill"));
+                ui.add(egui::Slider::new(&mut form.caves.steps, 0..=10).text("Smoothing steps"));
+            }
+            MapGenerator::Dungeon => {
+                let dungeon = &mut form.dungeon;
+                ui.add(egui::Slider::new(&mut dungeon.room_count, 1..=64).text("Rooms"));
+                ui.horizontal(|ui| {
+                    ui.label("Room size");
+                    ui.add(egui::DragValue::new(&mut dungeon.min_room_size).range(1..=64));
+                    ui.add(
+                        egui::DragValue::new(&mut dungeon.max_room_size)
+                            .range(dungeon.min_room_size..=64),
+                    );
+                });
+            }
+            MapGenerator::Terrain => {
+                ui.add(egui::Slider::new(&mut form.terrain_scale, 2.0..=128.0).text("Scale"));
+                ui.add(egui::Slider::new(&mut form.terrain_octaves, 1..=8).text("Octaves"));
+                ui.label("Biomes (from the lowest)");
+                form.biomes.retain_mut(|biome| {
+                    ui.horizontal(|ui| {
+                        ui.add(egui::TextEdit::singleline(&mut biome.name).desired_width(60.0));
+                        ui.add(egui::Slider::new(&mut biome.max_height, 0.0..=1.0));
+                        gid_field(ui, "Tile", &mut biome.gid);
+                        !ui.small_button("x").clicked()
+                    })
+                    .inner
+                });
+                if ui.button("Add biome").clicked() {
+                    form.biomes.push(Biome::new("Biome", 1.0, selected_gid));
+                }
+            }
+        }
+        if form.generator != MapGenerator::Terrain {
+            gid_field(ui, "Floor tile", &mut form.floor_gid);
+            gid_field(ui, "Wall tile", &mut form.wall_gid);
+        }
+
+        let layer_name = map.layers().get(self.layer).map(|layer| layer.name.clone());
+        let Some(layer_name) = layer_name else {
+            ui.label("Add a layer to generate into.");
+            return;
+        };
+        if ui
+            .button(format!("Generate into \"{}\"", layer_name))
+            .clicked()
+        {
+            let (width, height) = map.size();
+            let tiles = form.tiles(width, height);
+            let edit = TilemapEdit::set_tiles(map, self.layer, tiles);
+            self.record(edit);
+            self.finish_stroke();
+        }
+    }
+
+    fn layers_ui(&mut self, ui: &mut egui::Ui, map: &mut Tilemap) {
+        ui.label("Layers");
+        // Affichées de haut en bas comme dans Tiled : le dernier calque est dessiné devant
+        for index in (0..map.layers().len()).rev() {
+            ui.horizontal(|ui| {
+                let Some(layer) = map.layer_mut(index) else {
+                    return;
+                };
+                ui.checkbox(&mut layer.visible, "");
+                if ui
+                    .selectable_label(self.layer == index, &layer.name)
+                    .clicked()
+                {
+                    self.layer = index;
+                }
+            });
+        }
+        if let Some(layer) = map.layer_mut(self.layer) {
+            ui.horizontal(|ui| {
+                ui.text_edit_singleline(&mut layer.name);
+                ui.add(egui::Slider::new(&mut layer.opacity, 0.0..=1.0).text("Opacity"));
+            });
+        }
+
+        let count = map.layers().len();
+        ui.horizontal(|ui| {
+            if ui.button("Add").clicked() {
+                self.finish_stroke();
+                let index = if count == 0 { 0 } else { self.layer + 1 };
+                let layer = map.empty_layer(format!("Layer {}", count + 1));
+                if map.insert_layer(index, layer.clone()) {
+                    self.history.push(TilemapEdit::InsertLayer { index, layer });
+                    self.layer = index;
+                }
+            }
+            if ui
+                .add_enabled(count > 0, egui::Button::new("Remove"))
+                .clicked()
+            {
+                self.finish_stroke();
+                if let Some(layer) = map.remove_layer(self.layer) {
+                    self.history.push(TilemapEdit::RemoveLayer {
+                        index: self.layer,
+                        layer,
+                    });
+                    self.clamp_layer(map);
+                }
+            }
+            if ui
+                .add_enabled(self.layer + 1 < count, egui::Button::new("Up"))
+                .clicked()
+            {
+                self.move_layer(map, self.layer + 1);
+            }
+            if ui
+                .add_enabled(self.layer > 0, egui::Button::new("Down"))
+                .clicked()
+            {
+                self.move_layer(map, self.layer - 1);
+            }
+        });
+    }
+
+    fn move_layer(&mut self, map: &mut Tilemap, to: usize) {
+        self.finish_stroke();
+        if map.move_layer(self.layer, to) {
+            self.history.push(TilemapEdit::MoveLayer {
+                from: self.layer,
+                to,
+            });
+            self.layer = to;
+        }
+    }
+
+    /// Palette texture of a tileset, loaded from the VFS the first time.
+    fn palette_texture(
+        &mut self,
+        ctx: &egui::Context,
+        tileset: &Tileset,
+    ) -> Option<egui::TextureId> {
+        if tileset.image.is_empty() {
+            return None;
+        }
+        let vfs = &self.vfs;
+        self.palettes
+            .entry(tileset.image.clone())
+            .or_insert_with(|| {
+                let load = || -> Result<egui::TextureHandle> {
+                    let bytes = vfs.read_bytes(&tileset.image)?;
+                    let image = image::load_from_memory(&bytes)?.to_rgba8();
+                    let size = [image.width() as usize, image.height() as usize];
+                    Ok(ctx.load_texture(
+                        format!("tilemap_palette:{}", tileset.image),
+                        egui::ColorImage::from_rgba_unmultiplied(size, &image.into_raw()),
+                        egui::TextureOptions::NEAREST,
+                    ))
+                };
+                load()
+                    .inspect_err(|e| log::error!("Tileset {:?}: {:#}", tileset.image, e))
+                    .ok()
+            })
+            .as_ref()
+            .map(egui::TextureHandle::id)
+    }
+
+    /// Palette button showing tile `gid` of `tileset` (its number without texture).
+    fn tile_button(
+        texture: Option<egui::TextureId>,
+        tileset: &Tileset,
+        gid: u32,
+    ) -> egui::Button<'static> {
+        match (texture, tileset.uv(gid)) {
+            (Some(texture), Some([u0, v0, u1, v1])) => egui::Button::image(
+                egui::Image::new((texture, egui::Vec2::splat(Self::PALETTE_TILE))).uv(
+                    egui::Rect::from_min_max(egui::pos2(u0, v0), egui::pos2(u1, v1)),
+                ),
+            ),
+            _ => egui::Button::new(gid.to_string()).min_size(egui::Vec2::splat(Self::PALETTE_TILE)),
+        }
+    }
+
+    /// Terrains of each tileset and their rules: one tile per neighbour bitmask, assigned
+    /// from the palette selection. Saved with the tileset in a `.tileset` asset.
+    fn terrains_ui(&mut self, ui: &mut egui::Ui, map: &mut Tilemap) {
+        let selected_gid = self.gid & Tilemap::GID_MASK;
+        for tileset_index in 0..map.tilesets.len() {
+            let texture = self.palette_texture(ui.ctx(), &map.tilesets[tileset_index]);
+            let tileset = &mut map.tilesets[tileset_index];
+            ui.push_id(tileset_index, |ui| {
+                ui.label(egui::RichText::new(&tileset.name).strong());
+                for (index, terrain) in tileset.terrains.iter().enumerate() {
+                    let key = (tileset_index, index);
+                    if ui
+                        .selectable_label(self.terrain == Some(key), &terrain.name)
+                        .clicked()
+                    {
+                        self.terrain = Some(key);
+                        self.tool = TileTool::Terrain;
+                    }
+                }
+                ui.horizontal(|ui| {
+                    if ui.button("Add terrain").clicked() {
+                        let name = format!("Terrain {}", tileset.terrains.len() + 1);
+                        tileset.terrains.push(Terrain::new(name, TerrainMode::Blob));
+                        self.terrain = Some((tileset_index, tileset.terrains.len() - 1));
+                    }
+                    if ui.button("Save tileset").clicked() {
+                        let path = Self::tileset_path(tileset);
+                        match tileset.save(&self.vfs, &path) {
+                            Ok(()) => log::info!("Tileset saved to {:?}", path),
+                            Err(e) => log::error!("Failed to save tileset: {:#}", e),
+                        }
+                    }
+                });
+
+                let Some((_, index)) = self.terrain.filter(|(t, _)| *t == tileset_index) else {
+                    return;
+                };
+                // Copie de la grille pour les aperçus pendant que le terrain est emprunté
+                let grid = tileset.clone();
+                let Some(terrain) = tileset.terrains.get_mut(index) else {
+                    return;
+                };
+                ui.horizontal(|ui| {
+                    ui.text_edit_singleline(&mut terrain.name);
+                    egui::ComboBox::from_id_salt("terrain_mode")
+                        .selected_text(terrain.mode.name())
+                        .show_ui(ui, |ui| {
+                            for mode in [TerrainMode::Edges, TerrainMode::Blob] {
+                                ui.selectable_value(&mut terrain.mode, mode, mode.name());
+                            }
+                        });
+                });
+                ui.label("Click to assign the selected tile, right-click to clear.");
+                ui.horizontal_wrapped(|ui| {
+                    for mask in terrain.masks() {
+                        ui.vertical(|ui| {
+                            Self::mask_diagram(ui, mask);
+                            let button = match terrain.rule(mask) {
+                                Some(tile) => {
+                                    Self::tile_button(texture, &grid, grid.first_gid + tile)
+                                }
+                                None => egui::Button::new("-")
+                                    .min_size(egui::Vec2::splat(Self::PALETTE_TILE)),
+                            };
+                            let response = ui.add(button);
+                            if response.clicked() && grid.contains(selected_gid) {
+                                terrain.set_rule(mask, Some(selected_gid - grid.first_gid));
+                            }
+                            if response.secondary_clicked() {
+                                terrain.set_rule(mask, None);
+                            }
+                        });
+                    }
+                });
+                if ui.button("Remove terrain").clicked() {
+                    tileset.terrains.remove(index);
+                    self.terrain = None;
+                }
+            });
+            ui.separator();
+        }
+    }
+
+    /// Path of the `.tileset` asset saved next to the tileset image.
+    fn tileset_path(tileset: &Tileset) -> String {
+        let stem = match tileset.image.rfind('.') {
+            Some(dot) if !tileset.image[dot..].contains('/') => &tileset.image[..dot],
+            _ => &tileset.image,
+        };
+        format!("{}.{}", stem, Tileset::EXTENSION)
+    }
+
+    /// 3x3 diagram of a neighbour bitmask: filled squares are neighbours of the terrain.
+    fn mask_diagram(ui: &mut egui::Ui, mask: u8) {
+        const CELL: f32 = 9.0;
+        let (rect, _) = ui.allocate_exact_size(egui::Vec2::splat(CELL * 3.0), egui::Sense::hover());
+        let painter = ui.painter();
+        painter.rect_filled(rect, 0.0, ui.visuals().extreme_bg_color);
+        let mut filled = vec![(0, 0)];
+        for (bit, offset) in Terrain::NEIGHBOURS.iter().enumerate() {
+            if mask & (1 << bit) != 0 {
+                filled.push(*offset);
+            }
+        }
+        for (dx, dy) in filled {
+            let min = rect.min + egui::vec2((dx + 1) as f32, (dy + 1) as f32) * CELL;
+            painter.rect_filled(
+                egui::Rect::from_min_size(min, egui::Vec2::splat(CELL)).shrink(0.5),
+                0.0,
+                egui::Color32::from_rgb(90, 170, 90),
+            );
+        }
+    }
+
+    fn palette_ui(&mut self, ui: &mut egui::Ui, map: &Tilemap) {
+        ui.horizontal(|ui| {
+            ui.label("Tile");
+            ui.label(format!("{}", self.gid & Tilemap::GID_MASK));
+            let mut flip_x = self.gid & Tilemap::FLIP_X != 0;
+            let mut flip_y = self.gid & Tilemap::FLIP_Y != 0;
+            ui.checkbox(&mut flip_x, "Flip X");
+            ui.checkbox(&mut flip_y, "Flip Y");
+            self.gid = (self.gid & !(Tilemap::FLIP_X | Tilemap::FLIP_Y))
+                | if flip_x { Tilemap::FLIP_X } else { 0 }
+                | if flip_y { Tilemap::FLIP_Y } else { 0 };
+        });
+
+        let flags = self.gid & !Tilemap::GID_MASK;
+        egui::ScrollArea::vertical()
+            .max_height(260.0)
+            .show(ui, |ui| {
+                for tileset in &map.tilesets {
+                    ui.label(&tileset.name);
+                    let texture = self.palette_texture(ui.ctx(), tileset);
+                    ui.horizontal_wrapped(|ui| {
+                        ui.spacing_mut().item_spacing = egui::vec2(2.0, 2.0);
+                        for gid in tileset.first_gid..tileset.first_gid + tileset.tile_count {
+                            let selected = self.gid & Tilemap::GID_MASK == gid;
+                            let button = Self::tile_button(texture, tileset, gid);
+                            if ui.add(button.selected(selected)).clicked() {
+                                self.gid = gid | flags;
+                                if !matches!(self.tool, TileTool::Rectangle | TileTool::Bucket) {
+                                    self.tool = TileTool::Brush;
+                                }
+                            }
+                        }
+                    });
+                }
+            });
+    }
+
+    /// Paint `map` (placed in the world by `model`) with the current tool from the pointer
+    /// over the viewport, handle the undo shortcuts and draw the cursor / collision overlay.
+    /// Call once per frame after the editor windows, so clicks on them are ignored.
+    pub fn viewport(
+        &mut self,
+        ctx: &egui::Context,
+        camera: &Camera2D,
+        map: &mut Tilemap,
+        model: &Matrix4<f32>,
+    ) {
+        self.clamp_layer(map);
+        if !ctx.wants_keyboard_input() {
+            let undo = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Z);
+            let redo = egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Y);
+            if ctx.input_mut(|i| i.consume_shortcut(&undo)) {
+                self.undo(map);
+            }
+            if ctx.input_mut(|i| i.consume_shortcut(&redo)) {
+                self.redo(map);
+            }
+        }
+
+        let pixels_per_point = ctx.pixels_per_point();
+        let Some(inverse) = model.try_inverse() else {
+            return;
+        };
+        let over_ui = ctx.is_pointer_over_area();
+        let (pressed, down, pointer) = ctx.input(|i| {
+            (
+                i.pointer.primary_pressed(),
+                i.pointer.primary_down(),
+                i.pointer.hover_pos(),
+            )
+        });
+        self.hovered = pointer.filter(|_| !over_ui).and_then(|pos| {
+            let world = camera.screen_to_world(pos.x * pixels_per_point, pos.y * pixels_per_point);
+            let local = inverse.transform_point(&Point3::new(world.x, world.y, 0.0));
+            map.cell_at([local.x, local.y])
+        });
+
+        if self.enabled {
+            self.handle_pointer(map, pressed && !over_ui, down);
+        }
+        self.draw_overlay(ctx, camera, map, model);
+    }
+
+    fn handle_pointer(&mut self, map: &mut Tilemap, pressed: bool, down: bool) {
+        if !down {
+            // Relâché : fin du trait de pinceau ou du rectangle
+            self.finish_stroke();
+            if let (Some(start), Some(end)) = (self.rect_start.take(), self.hovered) {
+                let cells = rect_cells(start, end);
+                let edit = TilemapEdit::paint_tiles(map, self.layer, cells, self.gid);
+                self.history.push(edit);
+            }
+            return;
+        }
+        let Some((x, y)) = self.hovered else {
+            return;
+        };
+        if map.layers().is_empty() && self.tool != TileTool::Collision {
+            return;
+        }
+
+        // Le pinceau continue tant que le trait commencé dans le viewport n'est pas relâché
+        let stroking = pressed || self.stroke.is_some();
+        match self.tool {
+            TileTool::Brush | TileTool::Eraser if stroking => {
+                let gid = if self.tool == TileTool::Eraser {
+                    Tilemap::EMPTY
+                } else {
+                    self.gid
+                };
+                let edit = TilemapEdit::paint_tiles(map, self.layer, [(x, y)], gid);
+                self.record(edit);
+            }
+            TileTool::Collision if stroking => {
+                let edit = TilemapEdit::paint_collision(map, [(x, y)], self.collision_flags);
+                self.record(edit);
+            }
+            TileTool::Terrain if stroking => {
+                let Some(terrain) = self.terrain else {
+                    return;
+                };
+                let edit = TilemapEdit::paint_terrain(
+                    map,
+                    self.layer,
+                    terrain,
+                    [(x, y)],
+                    !self.terrain_erase,
+                );
+                self.record(edit);
+            }
+            TileTool::Rectangle if pressed => self.rect_start = Some((x, y)),
+            TileTool::Bucket if pressed => {
+                let cells = flood_fill_cells(map, self.layer, x, y);
+                let edit = TilemapEdit::paint_tiles(map, self.layer, cells, self.gid);
+                self.history.push(edit);
+            }
+            TileTool::Picker if pressed => {
+                if let Some(gid) = map
+                    .tile(self.layer, x, y)
+                    .filter(|&gid| gid != Tilemap::EMPTY)
+                {
+                    self.gid = gid;
+                    self.tool = TileTool::Brush;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn draw_overlay(
+        &self,
+        ctx: &egui::Context,
+        camera: &Camera2D,
+        map: &Tilemap,
+        model: &Matrix4<f32>,
+    ) {
+        let painter = ctx.layer_painter(egui::LayerId::new(
+            egui::Order::Background,
+            egui::Id::new("tilemap_editor_overlay"),
+        ));
+        let pixels_per_point = ctx.pixels_per_point();
+        let (tile_width, tile_height) = map.tile_size();
+        let (tile_width, tile_height) = (tile_width as f32, tile_height as f32);
+        // Coins d'un rectangle de cases, en points écran
+        let corners = |(x0, y0): (u32, u32), (x1, y1): (u32, u32)| {
+            let (x0, y0) = (x0 as f32 * tile_width, y0 as f32 * tile_height);
+            let (x1, y1) = ((x1 + 1) as f32 * tile_width, (y1 + 1) as f32 * tile_height);
+            [[x0, y0], [x1, y0], [x1, y1], [x0, y1]]
+                .map(|[x, y]| {
+                    let world = model.transform_point(&Point3::new(x, y, 0.0));
+                    let screen = camera.world_to_screen(world.x, world.y);
+                    egui::pos2(screen.x, screen.y) / pixels_per_point
+                })
+                .to_vec()
+        };
+
+        if self.show_collision || self.tool == TileTool::Collision {
+            let (min, max) = camera.visible_rect();
+            for (chunk_x, chunk_y) in map.visible_chunks(model, [min.x, min.y], [max.x, max.y]) {
+                let (width, height) = map.size();
+                let x0 = chunk_x * Tilemap::CHUNK_SIZE;
+                let y0 = chunk_y * Tilemap::CHUNK_SIZE;
+                for y in y0..(y0 + Tilemap::CHUNK_SIZE).min(height) {
+                    for x in x0..(x0 + Tilemap::CHUNK_SIZE).min(width) {
+                        let color = match map.collision(x, y).unwrap_or_default() {
+                            0 => continue,
+                            flags if flags & Tilemap::COLLISION_SOLID != 0 => {
+                                egui::Color32::from_rgba_unmultiplied(255, 60, 60, 90)
+                            }
+                            _ => egui::Color32::from_rgba_unmultiplied(60, 160, 255, 90),
+                        };
+                        painter.add(egui::Shape::convex_polygon(
+                            corners((x, y), (x, y)),
+                            color,
+                            egui::Stroke::NONE,
+                        ));
+                    }
+                }
+            }
+        }
+
+        if !self.enabled {
+            return;
+        }
+        let outline = egui::Stroke::new(1.5, egui::Color32::YELLOW);
+        if let Some(start) = self.rect_start {
+            let end = self.hovered.unwrap_or(start);
+            let (min, max) = (
+                (start.0.min(end.0), start.1.min(end.1)),
+                (start.0.max(end.0), start.1.max(end.1)),
+            );
+            painter.add(egui::Shape::closed_line(corners(min, max), outline));
+        } else if let Some(cell) = self.hovered {
+            painter.add(egui::Shape::closed_line(corners(cell, cell), outline));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map() -> Tilemap {
+        let mut map = Tilemap::new(8, 6, 16, 16);
+        map.add_layer("ground");
+        map
+    }
+
+    #[test]
+    fn bucket_fill_stops_at_other_tiles_and_undoes() {
+        let mut map = map();
+        // Mur vertical en x = 3
+        let wall = TilemapEdit::paint_tiles(&mut map, 0, rect_cells((3, 0), (3, 5)), 2);
+        let mut history = TilemapHistory::new();
+        history.push(wall);
+
+        let cells = flood_fill_cells(&map, 0, 0, 0);
+        assert_eq!(cells.len(), 3 * 6);
+        history.push(TilemapEdit::paint_tiles(&mut map, 0, cells, 5));
+        assert_eq!(map.tile(0, 2, 5), Some(5));
+        assert_eq!(map.tile(0, 4, 0), Some(Tilemap::EMPTY));
+
+        assert!(history.undo(&mut map));
+        assert_eq!(map.tile(0, 2, 5), Some(Tilemap::EMPTY));
+        assert_eq!(map.tile(0, 3, 2), Some(2));
+        assert!(history.redo(&mut map));
+        assert_eq!(map.tile(0, 0, 0), Some(5));
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn layer_and_collision_edits_roundtrip() {
+        let mut map = map();
+        let mut history = TilemapHistory::new();
+        map.set_tile(0, 1, 1, 7);
+
+        let layer = map.empty_layer("decor");
+        assert!(map.insert_layer(1, layer.clone()));
+        history.push(TilemapEdit::InsertLayer { index: 1, layer });
+        assert!(map.move_layer(1, 0));
+        history.push(TilemapEdit::MoveLayer { from: 1, to: 0 });
+        history.push(TilemapEdit::paint_collision(
+            &mut map,
+            [(1, 1), (2, 1)],
+            Tilemap::COLLISION_SOLID,
+        ));
+        assert_eq!(map.layers()[1].name, "ground");
+        assert!(map.is_solid(2, 1));
+
+        while history.undo(&mut map) {}
+        assert_eq!(map.layers().len(), 1);
+        assert_eq!(map.tile(0, 1, 1), Some(7));
+        assert!(!map.is_solid(2, 1));
+
+        while history.redo(&mut map) {}
+        assert_eq!(map.layers()[0].name, "decor");
+        assert_eq!(map.collision(1, 1), Some(Tilemap::COLLISION_SOLID));
+    }
+}
@@ -0,0 +1,581 @@
+mod autotile;
+mod editor;
+mod pass;
+mod tileset_asset;
+mod tmx;
+
+pub use autotile::*;
+pub use editor::*;
+pub use pass::*;
+
+use std::sync::{
+    Arc,
+    atomic::{AtomicU64, Ordering},
+};
+
+use nalgebra::{Matrix4, Vector3};
+
+use crate::{InstanceData, SpriteRenderer, Texture2D, Vertex};
+
+/// A grid tileset: one image sliced into `tile_width` x `tile_height` tiles, like a
+/// `TextureAtlas::from_grid`. Tile ids (gids) start at `first_gid`.
+#[derive(Clone)]
+pub struct Tileset {
+    pub name: String,
+    /// VFS path of the image, loaded by `TilemapPass` unless `texture` is set.
+    pub image: String,
+    /// Texture already loaded by the caller (e.g. the texture of a `TextureAtlas`).
+    pub texture: Option<Arc<Texture2D>>,
+    pub image_width: u32,
+    pub image_height: u32,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    /// Pixels around the tiles, and between two tiles.
+    pub margin: u32,
+    pub spacing: u32,
+    pub columns: u32,
+    pub tile_count: u32,
+    /// Gid of the first tile of the tileset (Tiled starts at 1, 0 being the empty tile).
+    pub first_gid: u32,
+    /// Auto-tiling rules (see `Tilemap::paint_terrain`), saved in `.tileset` assets.
+    pub terrains: Vec<Terrain>,
+}
+
+impl Tileset {
+    /// Tileset over the whole `image_width` x `image_height` image, without margin or spacing.
+    pub fn new(
+        name: impl Into<String>,
+        image: impl Into<String>,
+        (image_width, image_height): (u32, u32),
+        (tile_width, tile_height): (u32, u32),
+    ) -> Self {
+        let mut tileset = Self {
+            name: name.into(),
+            image: image.into(),
+            texture: None,
+            image_width,
+            image_height,
+            tile_width,
+            tile_height,
+            margin: 0,
+            spacing: 0,
+            columns: 0,
+            tile_count: 0,
+            first_gid: 1,
+            terrains: Vec::new(),
+        };
+        tileset.compute_grid();
+        tileset
+    }
+
+    /// Tileset sampling an already loaded texture.
+    pub fn from_texture(
+        name: impl Into<String>,
+        texture: Arc<Texture2D>,
+        tile_size: (u32, u32),
+    ) -> Self {
+        let mut tileset = Self::new(name, "", (texture.width, texture.height), tile_size);
+        tileset.texture = Some(texture);
+        tileset
+    }
+
+    /// Derive `columns` and `tile_count` from the image size, margin and spacing.
+    pub fn compute_grid(&mut self) {
+        let count = |size: u32, tile: u32| {
+            let usable = (size + self.spacing).saturating_sub(self.margin * 2);
+            usable / (tile + self.spacing).max(1)
+        };
+        self.columns = count(self.image_width, self.tile_width);
+        self.tile_count = self.columns * count(self.image_height, self.tile_height);
+    }
+
+    /// Whether `gid` (without flip flags) belongs to this tileset.
+    pub fn contains(&self, gid: u32) -> bool {
+        gid >= self.first_gid && gid - self.first_gid < self.tile_count
+    }
+
+    /// UV rectangle [u0, v0, u1, v1] of tile `gid` (without flip flags).
+    pub fn uv(&self, gid: u32) -> Option<[f32; 4]> {
+        if !self.contains(gid) || self.columns == 0 {
+            return None;
+        }
+        let index = gid - self.first_gid;
+        let x = self.margin + (index % self.columns) * (self.tile_width + self.spacing);
+        let y = self.margin + (index / self.columns) * (self.tile_height + self.spacing);
+        let (w, h) = (
+            self.image_width.max(1) as f32,
+            self.image_height.max(1) as f32,
+        );
+        Some([
+            x as f32 / w,
+            y as f32 / h,
+            (x + self.tile_width) as f32 / w,
+            (y + self.tile_height) as f32 / h,
+        ])
+    }
+}
+
+/// One layer of tiles of a `Tilemap`, drawn in layer order.
+#[derive(Debug, Clone)]
+pub struct TileLayer {
+    pub name: String,
+    pub visible: bool,
+    /// Alpha multiplier of the whole layer.
+    pub opacity: f32,
+    /// Gids (with flip flags) row by row, 0 = empty.
+    tiles: Vec<u32>,
+    /// `Tilemap` revision of the last change of each chunk (see `Tilemap::chunk_revision`).
+    chunk_revisions: Vec<u64>,
+}
+
+impl TileLayer {
+    /// Gids of the layer, row by row.
+    pub fn tiles(&self) -> &[u32] {
+        &self.tiles
+    }
+}
+
+static NEXT_TILEMAP_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Grid of tiles referencing `Tileset`s, rendered by `TilemapPass` as an entity component
+/// (with an optional `Transform`).
+///
+/// The grid is split into `CHUNK_SIZE` x `CHUNK_SIZE` chunks, each uploaded in its own GPU
+/// buffer: changing a tile only marks its chunk dirty (see `chunk_revision`), and only the
+/// chunks visible by the camera are drawn.
+pub struct Tilemap {
+    width: u32,
+    height: u32,
+    tile_width: u32,
+    tile_height: u32,
+    pub tilesets: Vec<Tileset>,
+    layers: Vec<TileLayer>,
+    /// Collision flags of each cell (shared by all layers), row by row.
+    collision: Vec<u8>,
+    /// Identity of this map for GPU caches (a clone gets its own).
+    id: u64,
+    revision: u64,
+}
+
+impl Clone for Tilemap {
+    fn clone(&self) -> Self {
+        Self {
+            tilesets: self.tilesets.clone(),
+            layers: self.layers.clone(),
+            collision: self.collision.clone(),
+            id: NEXT_TILEMAP_ID.fetch_add(1, Ordering::Relaxed),
+            ..*self
+        }
+    }
+}
+
+impl Tilemap {
+    /// Side of a chunk, in tiles.
+    pub const CHUNK_SIZE: u32 = 16;
+
+    /// Gid of an empty cell.
+    pub const EMPTY: u32 = 0;
+
+    /// Flip flags stored in the high bits of a gid (same encoding as Tiled).
+    pub const FLIP_X: u32 = 0x8000_0000;
+    pub const FLIP_Y: u32 = 0x4000_0000;
+    /// Swap of the x and y axes, applied before the other flips (90° rotations).
+    pub const FLIP_DIAGONAL: u32 = 0x2000_0000;
+    pub const GID_MASK: u32 = 0x1FFF_FFFF;
+
+    /// Collision flags of a cell (see `collision`).
+    pub const COLLISION_SOLID: u8 = 1;
+    /// Solid from above only (platforms the player can jump through).
+    pub const COLLISION_ONE_WAY: u8 = 2;
+
+    /// Empty map of `width` x `height` tiles of `tile_width` x `tile_height` pixels, without
+    /// layers.
+    pub fn new(width: u32, height: u32, tile_width: u32, tile_height: u32) -> Self {
+        Self {
+            width,
+            height,
+            tile_width,
+            tile_height,
+            tilesets: Vec::new(),
+            layers: Vec::new(),
+            collision: vec![0; (width * height) as usize],
+            id: NEXT_TILEMAP_ID.fetch_add(1, Ordering::Relaxed),
+            revision: 0,
+        }
+    }
+
+    /// Size of the map, in tiles.
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// Size of a cell, in pixels.
+    pub fn tile_size(&self) -> (u32, u32) {
+        (self.tile_width, self.tile_height)
+    }
+
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn add_tileset(&mut self, tileset: Tileset) {
+        self.tilesets.push(tileset);
+    }
+
+    /// Empty layer sized for this map.
+    pub fn empty_layer(&self, name: impl Into<String>) -> TileLayer {
+        let (chunks_x, chunks_y) = self.chunk_count();
+        TileLayer {
+            name: name.into(),
+            visible: true,
+            opacity: 1.0,
+            tiles: vec![Self::EMPTY; (self.width * self.height) as usize],
+            chunk_revisions: vec![self.revision; (chunks_x * chunks_y) as usize],
+        }
+    }
+
+    /// Append an empty layer (drawn above the previous ones) and return its index.
+    pub fn add_layer(&mut self, name: impl Into<String>) -> usize {
+        let index = self.layers.len();
+        self.insert_layer(index, self.empty_layer(name));
+        index
+    }
+
+    /// Insert `layer` (e.g. one returned by `remove_layer`) at `index`.
+    /// Returns `false` if its size does not match the map or `index` is out of range.
+    pub fn insert_layer(&mut self, index: usize, layer: TileLayer) -> bool {
+        if index > self.layers.len() || layer.tiles.len() != self.collision.len() {
+            return false;
+        }
+        self.layers.insert(index, layer);
+        self.touch_layers(index);
+        true
+    }
+
+    pub fn remove_layer(&mut self, index: usize) -> Option<TileLayer> {
+        if index >= self.layers.len() {
+            return None;
+        }
+        let layer = self.layers.remove(index);
+        self.touch_layers(index);
+        Some(layer)
+    }
+
+    /// Move layer `from` to index `to` (changing its draw order).
+    pub fn move_layer(&mut self, from: usize, to: usize) -> bool {
+        if from >= self.layers.len() || to >= self.layers.len() {
+            return false;
+        }
+        let layer = self.layers.remove(from);
+        self.layers.insert(to, layer);
+        self.touch_layers(from.min(to));
+        true
+    }
+
+    /// Mark every chunk of the layers from `first` dirty: their index changed, so GPU
+    /// caches keyed by layer index must be rebuilt.
+    fn touch_layers(&mut self, first: usize) {
+        self.revision += 1;
+        for layer in self.layers.iter_mut().skip(first) {
+            layer.chunk_revisions.fill(self.revision);
+        }
+    }
+
+    pub fn layers(&self) -> &[TileLayer] {
+        &self.layers
+    }
+
+    /// Approximate heap size of the tile, revision and collision data, in bytes
+    /// (tileset textures excluded: they are counted with the other textures).
+    pub fn heap_size(&self) -> usize {
+        let layers: usize = self
+            .layers
+            .iter()
+            .map(|layer| {
+                layer.name.len()
+                    + layer.tiles.len() * std::mem::size_of::<u32>()
+                    + layer.chunk_revisions.len() * std::mem::size_of::<u64>()
+            })
+            .sum();
+        layers + self.collision.len()
+    }
+
+    /// Mutable access to a layer's name, visibility and opacity (tiles go through `set_tile`).
+    pub fn layer_mut(&mut self, layer: usize) -> Option<&mut TileLayer> {
+        self.layers.get_mut(layer)
+    }
+
+    /// Gid (with flip flags) of cell (`x`, `y`) of `layer`.
+    pub fn tile(&self, layer: usize, x: u32, y: u32) -> Option<u32> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let layer = self.layers.get(layer)?;
+        Some(layer.tiles[(y * self.width + x) as usize])
+    }
+
+    /// Set cell (`x`, `y`) of `layer` and mark its chunk dirty.
+    /// Returns `false` if the cell is out of the map or already holds `gid`.
+    pub fn set_tile(&mut self, layer: usize, x: u32, y: u32, gid: u32) -> bool {
+        if self.tile(layer, x, y).is_none_or(|current| current == gid) {
+            return false;
+        }
+        self.revision += 1;
+        let chunk = self.chunk_index(x / Self::CHUNK_SIZE, y / Self::CHUNK_SIZE);
+        let layer = &mut self.layers[layer];
+        layer.tiles[(y * self.width + x) as usize] = gid;
+        layer.chunk_revisions[chunk] = self.revision;
+        true
+    }
+
+    /// Cell containing the map-local point `position` (pixels), if inside the map.
+    pub fn cell_at(&self, [x, y]: [f32; 2]) -> Option<(u32, u32)> {
+        if x < 0.0 || y < 0.0 {
+            return None;
+        }
+        let cell = (
+            (x / self.tile_width.max(1) as f32) as u32,
+            (y / self.tile_height.max(1) as f32) as u32,
+        );
+        (cell.0 < self.width && cell.1 < self.height).then_some(cell)
+    }
+
+    /// Collision flags of cell (`x`, `y`) (`COLLISION_*`).
+    pub fn collision(&self, x: u32, y: u32) -> Option<u8> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        Some(self.collision[(y * self.width + x) as usize])
+    }
+
+    /// Whether cell (`x`, `y`) blocks movement from every direction.
+    pub fn is_solid(&self, x: u32, y: u32) -> bool {
+        self.collision(x, y)
+            .is_some_and(|flags| flags & Self::COLLISION_SOLID != 0)
+    }
+
+    /// Set the collision flags of a cell. Returns `false` if the cell is out of the map or
+    /// already has `flags`.
+    pub fn set_collision(&mut self, x: u32, y: u32, flags: u8) -> bool {
+        if self.collision(x, y).is_none_or(|current| current == flags) {
+            return false;
+        }
+        self.collision[(y * self.width + x) as usize] = flags;
+        true
+    }
+
+    /// Number of chunks along x and y.
+    pub fn chunk_count(&self) -> (u32, u32) {
+        (
+            self.width.div_ceil(Self::CHUNK_SIZE),
+            self.height.div_ceil(Self::CHUNK_SIZE),
+        )
+    }
+
+    fn chunk_index(&self, chunk_x: u32, chunk_y: u32) -> usize {
+        (chunk_y * self.chunk_count().0 + chunk_x) as usize
+    }
+
+    /// Revision of the last change of a chunk: a renderer re-uploads the chunk when it
+    /// differs from the revision it uploaded.
+    pub fn chunk_revision(&self, layer: usize, chunk_x: u32, chunk_y: u32) -> Option<u64> {
+        let (chunks_x, chunks_y) = self.chunk_count();
+        if chunk_x >= chunks_x || chunk_y >= chunks_y {
+            return None;
+        }
+        let index = self.chunk_index(chunk_x, chunk_y);
+        self.layers.get(layer).map(|l| l.chunk_revisions[index])
+    }
+
+    /// Local-space rectangle [x0, y0, x1, y1] covered by a chunk, in pixels.
+    pub fn chunk_bounds(&self, chunk_x: u32, chunk_y: u32) -> [f32; 4] {
+        let x0 = chunk_x * Self::CHUNK_SIZE;
+        let y0 = chunk_y * Self::CHUNK_SIZE;
+        let x1 = (x0 + Self::CHUNK_SIZE).min(self.width);
+        let y1 = (y0 + Self::CHUNK_SIZE).min(self.height);
+        [
+            (x0 * self.tile_width) as f32,
+            (y0 * self.tile_height) as f32,
+            (x1 * self.tile_width) as f32,
+            (y1 * self.tile_height) as f32,
+        ]
+    }
+
+    /// Chunks whose bounds, transformed by `model`, overlap the world rectangle
+    /// `min`..`max` (e.g. `Camera2D::visible_rect`).
+    pub fn visible_chunks(
+        &self,
+        model: &Matrix4<f32>,
+        min: [f32; 2],
+        max: [f32; 2],
+    ) -> Vec<(u32, u32)> {
+        let (chunks_x, chunks_y) = self.chunk_count();
+        let mut visible = Vec::new();
+        for chunk_y in 0..chunks_y {
+            for chunk_x in 0..chunks_x {
+                let [x0, y0, x1, y1] = self.chunk_bounds(chunk_x, chunk_y);
+                let corners = [[x0, y0], [x1, y0], [x1, y1], [x0, y1]]
+                    .map(|[x, y]| model.transform_point(&[x, y, 0.0].into()));
+                let lo = corners
+                    .iter()
+                    .fold([f32::INFINITY; 2], |lo, p| [lo[0].min(p.x), lo[1].min(p.y)]);
+                let hi = corners.iter().fold([f32::NEG_INFINITY; 2], |hi, p| {
+                    [hi[0].max(p.x), hi[1].max(p.y)]
+                });
+                if lo[0] < max[0] && hi[0] > min[0] && lo[1] < max[1] && hi[1] > min[1] {
+                    visible.push((chunk_x, chunk_y));
+                }
+            }
+        }
+        visible
+    }
+
+    /// Tileset holding `gid` (flip flags are ignored), with its index.
+    pub fn tileset_of(&self, gid: u32) -> Option<(usize, &Tileset)> {
+        let gid = gid & Self::GID_MASK;
+        self.tilesets
+            .iter()
+            .enumerate()
+            .filter(|(_, tileset)| tileset.contains(gid))
+            .max_by_key(|(_, tileset)| tileset.first_gid)
+    }
+
+    /// Instances of the non-empty tiles of a chunk, in map-local space, with the index of
+    /// the tileset each one samples, sorted by tileset. Tiles taller than a cell (tileset
+    /// tile size larger than the map's) are aligned on the bottom of their cell, like Tiled.
+    pub fn chunk_instances(
+        &self,
+        layer: usize,
+        chunk_x: u32,
+        chunk_y: u32,
+    ) -> Vec<(usize, InstanceData)> {
+        let Some(tile_layer) = self.layers.get(layer) else {
+            return Vec::new();
+        };
+
+        let x0 = chunk_x * Self::CHUNK_SIZE;
+        let y0 = chunk_y * Self::CHUNK_SIZE;
+        let mut instances = Vec::new();
+        for y in y0..(y0 + Self::CHUNK_SIZE).min(self.height) {
+            for x in x0..(x0 + Self::CHUNK_SIZE).min(self.width) {
+                let gid = tile_layer.tiles[(y * self.width + x) as usize];
+                let Some((tileset_index, tileset)) = self.tileset_of(gid) else {
+                    continue;
+                };
+                let Some(uv) = tileset.uv(gid & Self::GID_MASK) else {
+                    continue;
+                };
+
+                let diagonal = gid & Self::FLIP_DIAGONAL != 0;
+                let (mut flip_u, mut flip_v) = (gid & Self::FLIP_X != 0, gid & Self::FLIP_Y != 0);
+                // La diagonale est appliquée après les UV : les flips passent sur l'autre axe
+                if diagonal {
+                    (flip_u, flip_v) = (flip_v, flip_u);
+                }
+                let [mut u0, mut v0, mut u1, mut v1] = uv;
+                if flip_u {
+                    std::mem::swap(&mut u0, &mut u1);
+                }
+                if flip_v {
+                    std::mem::swap(&mut v0, &mut v1);
+                }
+
+                let (tw, th) = (tileset.tile_width as f32, tileset.tile_height as f32);
+                let position = Vector3::new(
+                    (x * self.tile_width) as f32,
+                    ((y + 1) * self.tile_height) as f32 - th,
+                    0.0,
+                );
+                let mut model = Matrix4::new_translation(&position)
+                    * Matrix4::new_nonuniform_scaling(&Vector3::new(
+                        tw / Vertex::QUAD_SIZE,
+                        th / Vertex::QUAD_SIZE,
+                        1.0,
+                    ));
+                if diagonal {
+                    model *= Matrix4::new(
+                        0.0, 1.0, 0.0, 0.0, //
+                        1.0, 0.0, 0.0, 0.0, //
+                        0.0, 0.0, 1.0, 0.0, //
+                        0.0, 0.0, 0.0, 1.0,
+                    );
+                }
+
+                instances.push((
+                    tileset_index,
+                    InstanceData {
+                        model: model.into(),
+                        uv_rect: [u0, v0, u1, v1],
+                        depth: SpriteRenderer::layer_depth(layer as i32),
+                        texture_layer: 0,
+                        tint: [1.0, 1.0, 1.0, tile_layer.opacity],
+                        corner_colors: InstanceData::WHITE_CORNERS,
+                    },
+                ));
+            }
+        }
+        instances.sort_by_key(|(tileset, _)| *tileset);
+        instances
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map() -> Tilemap {
+        let mut map = Tilemap::new(40, 20, 16, 16);
+        map.add_tileset(Tileset::new("terrain", "terrain.png", (64, 32), (16, 16)));
+        map.add_layer("ground");
+        map
+    }
+
+    #[test]
+    fn set_tile_marks_only_its_chunk_dirty() {
+        let mut map = map();
+        assert_eq!(map.chunk_count(), (3, 2));
+        let before: Vec<_> = (0..3)
+            .map(|cx| map.chunk_revision(0, cx, 1).unwrap())
+            .collect();
+
+        assert!(map.set_tile(0, 20, 17, 3));
+        assert!(!map.set_tile(0, 20, 17, 3));
+        assert!(!map.set_tile(0, 40, 0, 3));
+        assert_eq!(map.tile(0, 20, 17), Some(3));
+
+        assert_ne!(map.chunk_revision(0, 1, 1), Some(before[1]));
+        assert_eq!(map.chunk_revision(0, 0, 1), Some(before[0]));
+        assert_eq!(map.chunk_revision(0, 2, 1), Some(before[2]));
+
+        let instances = map.chunk_instances(0, 1, 1);
+        assert_eq!(instances.len(), 1);
+        // Gid 3 = 3e tuile de la première ligne
+        assert_eq!(instances[0].1.uv_rect, [0.5, 0.0, 0.75, 0.5]);
+        assert_ne!(map.clone().id(), map.id());
+    }
+
+    #[test]
+    fn culls_chunks_outside_the_view() {
+        let map = map();
+        let identity = Matrix4::identity();
+        // Les chunks font 256 px : seul le premier touche ce rectangle
+        assert_eq!(
+            map.visible_chunks(&identity, [0.0, 0.0], [200.0, 200.0]),
+            [(0, 0)]
+        );
+        assert_eq!(
+            map.visible_chunks(&identity, [250.0, 250.0], [300.0, 300.0]),
+            [(0, 0), (1, 0), (0, 1), (1, 1)]
+        );
+
+        let shifted = Matrix4::new_translation(&Vector3::new(-256.0, 0.0, 0.0));
+        assert_eq!(
+            map.visible_chunks(&shifted, [0.0, 0.0], [200.0, 200.0]),
+            [(1, 0)]
+        );
+        assert!(
+            map.visible_chunks(&identity, [1000.0, 0.0], [1200.0, 100.0])
+                .is_empty()
+        );
+    }
+}
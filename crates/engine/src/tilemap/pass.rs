@@ -0,0 +1,307 @@
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Range,
+    sync::Arc,
+};
+
+use anyhow::Result;
+use egui_wgpu::wgpu;
+use hecs::Entity;
+use nalgebra::Matrix4;
+use wgpu::util::DeviceExt;
+
+use crate::{
+    AssetLoader, GlobalTransform, InstanceData, MemoryCategory, PassContext, RenderPass,
+    SpriteRenderer, Texture2D, Tilemap, TrackedMemory, Transform,
+};
+
+/// GPU copy of one chunk of one layer.
+struct ChunkBuffer {
+    /// `Tilemap::chunk_revision` and layer opacity at upload time (`None` = never uploaded).
+    revision: Option<u64>,
+    opacity: f32,
+    /// `None` when the chunk has no tile.
+    buffer: Option<wgpu::Buffer>,
+    /// Size of `buffer`, for the memory panel.
+    memory: TrackedMemory,
+    /// Instance range drawn with each tileset (by index in `Tilemap::tilesets`).
+    batches: Vec<(usize, Range<u32>)>,
+}
+
+/// GPU state of a tilemap entity.
+struct TilemapBuffers {
+    /// `Tilemap::id` of the map the chunks were built from.
+    map_id: u64,
+    /// Chunks keyed by (layer, chunk x, chunk y).
+    chunks: HashMap<(usize, u32, u32), ChunkBuffer>,
+}
+
+/// Passe de rendu des `Tilemap` de la scène (entités avec un composant `Tilemap` et un
+/// `Transform` optionnel), avant les sprites.
+///
+/// Each chunk keeps its instances in its own GPU buffer, rebuilt only when the chunk changed
+/// (`Tilemap::chunk_revision`) and is visible; chunks outside the camera view are skipped.
+pub struct TilemapPass {
+    renderer: SpriteRenderer,
+    loader: AssetLoader,
+    maps: HashMap<Entity, TilemapBuffers>,
+    /// Tileset images loaded by the pass, by VFS path.
+    images: HashMap<String, Arc<Texture2D>>,
+    /// Images that failed to load (logged once).
+    failed_images: HashSet<String>,
+    /// One bind group per texture (keyed by `Arc<Texture2D>` pointer, see `SpritePass`).
+    bind_groups: HashMap<usize, (Arc<Texture2D>, wgpu::BindGroup, TrackedMemory)>,
+}
+
+impl TilemapPass {
+    pub fn new(
+        device: &wgpu::Device,
+        target_format: wgpu::TextureFormat,
+        loader: &AssetLoader,
+    ) -> Result<Self> {
+        Ok(Self {
+            renderer: SpriteRenderer::new(device, target_format, loader)?,
+            loader: loader.clone(),
+            maps: HashMap::new(),
+            images: HashMap::new(),
+            failed_images: HashSet::new(),
+            bind_groups: HashMap::new(),
+        })
+    }
+
+    /// Forget the loaded tileset images so they are read again (e.g. after a hot reload).
+    pub fn reload_images(&mut self) {
+        self.images.clear();
+        self.failed_images.clear();
+    }
+
+    /// Texture of tileset `tileset` of `map`, loading its image the first time.
+    fn tileset_texture(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        map: &Tilemap,
+        tileset: usize,
+    ) -> Option<Arc<Texture2D>> {
+        let tileset = map.tilesets.get(tileset)?;
+        if let Some(texture) = &tileset.texture {
+            return Some(texture.clone());
+        }
+        if let Some(texture) = self.images.get(&tileset.image) {
+            return Some(texture.clone());
+        }
+        if self.failed_images.contains(&tileset.image) {
+            return None;
+        }
+
+        match self.loader.load_texture(&tileset.image, device, queue) {
+            Ok(texture) => {
+                let texture = Arc::new(texture);
+                self.images.insert(tileset.image.clone(), texture.clone());
+                Some(texture)
+            }
+            Err(e) => {
+                log::error!("Tileset {:?}: {:#}", tileset.name, e);
+                self.failed_images.insert(tileset.image.clone());
+                None
+            }
+        }
+    }
+
+    /// Rebuild the buffer of a chunk from the map.
+    fn upload_chunk(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        chunk: &mut ChunkBuffer,
+        map: &Tilemap,
+        (layer, chunk_x, chunk_y): (usize, u32, u32),
+    ) {
+        let mut instances: Vec<InstanceData> = Vec::new();
+        chunk.batches.clear();
+        for (tileset, instance) in map.chunk_instances(layer, chunk_x, chunk_y) {
+            let index = instances.len() as u32;
+            instances.push(instance);
+            match chunk.batches.last_mut() {
+                Some((key, range)) if *key == tileset => range.end = index + 1,
+                _ => chunk.batches.push((tileset, index..index + 1)),
+            }
+        }
+
+        if instances.is_empty() {
+            chunk.buffer = None;
+            chunk.memory = TrackedMemory::default();
+            return;
+        }
+        let bytes: &[u8] = bytemuck::cast_slice(&instances);
+        match &chunk.buffer {
+            // Réutiliser le buffer existant s'il est assez grand
+            Some(buffer) if buffer.size() >= bytes.len() as wgpu::BufferAddress => {
+                queue.write_buffer(buffer, 0, bytes);
+            }
+            _ => {
+                chunk.buffer = Some(
+                    device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                        label: Some("tilemap_chunk_instances"),
+                        contents: bytes,
+                        usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+                    }),
+                );
+                chunk.memory = TrackedMemory::labeled(
+                    MemoryCategory::InstanceBuffers,
+                    bytes.len() as u64,
+                    format!("tilemap chunk {} ({}, {})", layer, chunk_x, chunk_y),
+                );
+            }
+        }
+    }
+}
+
+impl RenderPass for TilemapPass {
+    fn name(&self) -> &str {
+        "tilemap_pass"
+    }
+
+    fn reload_shader(
+        &mut self,
+        path: &str,
+        device: &wgpu::Device,
+        loader: &AssetLoader,
+    ) -> Result<bool> {
+        self.renderer.reload_shader(path, device, loader)
+    }
+
+    fn reload_asset(&mut self, path: &str) -> bool {
+        let failed = self.failed_images.remove(path);
+        self.images.remove(path).is_some() || failed
+    }
+
+    fn before(&self) -> &[&str] {
+        &["sprite_pass"]
+    }
+
+    fn per_viewport(&self) -> bool {
+        true
+    }
+
+    fn execute(&mut self, ctx: &mut PassContext) {
+        let view_proj = ctx.camera.view_projection_matrix();
+        self.renderer.update_transform(ctx.queue, view_proj);
+
+        let device = &ctx.window_state.device;
+        let (min, max) = ctx.camera.visible_rect();
+
+        // Chunks to draw, in order, and the bind group key of each tileset
+        let mut draws: Vec<(Entity, (usize, u32, u32))> = Vec::new();
+        let mut tileset_keys: HashMap<(Entity, usize), usize> = HashMap::new();
+        let mut alive: HashSet<Entity> = HashSet::new();
+
+        for (entity, (map, transform, global)) in ctx
+            .scene
+            .world
+            .query::<(&Tilemap, Option<&Transform>, Option<&GlobalTransform>)>()
+            .iter()
+        {
+            alive.insert(entity);
+            let model = global
+                .map(GlobalTransform::matrix)
+                .or_else(|| transform.map(Transform::matrix))
+                .unwrap_or_else(Matrix4::identity);
+
+            // Textures (et bind groups) des tilesets
+            for tileset in 0..map.tilesets.len() {
+                let Some(texture) = self.tileset_texture(device, ctx.queue, map, tileset) else {
+                    continue;
+                };
+                let key = Arc::as_ptr(&texture) as usize;
+                self.bind_groups.entry(key).or_insert_with(|| {
+                    let bind_group =
+                        texture.create_bind_group(device, &self.renderer.texture_bind_layout);
+                    let memory = texture.track_bind_group();
+                    (texture, bind_group, memory)
+                });
+                tileset_keys.insert((entity, tileset), key);
+            }
+
+            let buffers = self.maps.entry(entity).or_insert_with(|| TilemapBuffers {
+                map_id: map.id(),
+                chunks: HashMap::new(),
+            });
+            if buffers.map_id != map.id() {
+                buffers.map_id = map.id();
+                buffers.chunks.clear();
+            }
+
+            let visible = map.visible_chunks(&model, [min.x, min.y], [max.x, max.y]);
+            for (layer_index, layer) in map.layers().iter().enumerate() {
+                if !layer.visible {
+                    continue;
+                }
+                for &(chunk_x, chunk_y) in &visible {
+                    let key = (layer_index, chunk_x, chunk_y);
+                    let revision = map
+                        .chunk_revision(layer_index, chunk_x, chunk_y)
+                        .unwrap_or_default();
+                    let chunk = buffers.chunks.entry(key).or_insert_with(|| ChunkBuffer {
+                        revision: None,
+                        opacity: layer.opacity,
+                        buffer: None,
+                        memory: TrackedMemory::default(),
+                        batches: Vec::new(),
+                    });
+                    // Re-upload des chunks modifiés seulement (ou jamais envoyés)
+                    if chunk.revision != Some(revision) || chunk.opacity != layer.opacity {
+                        chunk.revision = Some(revision);
+                        chunk.opacity = layer.opacity;
+                        Self::upload_chunk(device, ctx.queue, chunk, map, key);
+                    }
+                    if chunk.buffer.is_some() {
+                        draws.push((entity, key));
+                    }
+                }
+            }
+        }
+
+        // Libérer les buffers des tilemaps retirées de la scène
+        self.maps.retain(|entity, _| alive.contains(entity));
+        let used: HashSet<usize> = tileset_keys.values().copied().collect();
+        self.bind_groups.retain(|key, _| used.contains(key));
+
+        if draws.is_empty() {
+            return;
+        }
+
+        let mut rpass = ctx.encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("tilemap_render_pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: ctx.target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            occlusion_query_set: None,
+            timestamp_writes: None,
+        });
+        if let Some(view) = &ctx.viewport {
+            view.viewport.apply(&mut rpass);
+        }
+
+        for (entity, key) in draws {
+            let chunk = &self.maps[&entity].chunks[&key];
+            let Some(buffer) = &chunk.buffer else {
+                continue;
+            };
+            for (tileset, range) in &chunk.batches {
+                let Some(texture_key) = tileset_keys.get(&(entity, *tileset)) else {
+                    continue;
+                };
+                let (_texture, bind_group, _) = &self.bind_groups[texture_key];
+                ctx.window_state.frame_stats.record_draw(range.len() as u32);
+                self.renderer
+                    .draw_instances_from(&mut rpass, bind_group, buffer, range.clone());
+            }
+        }
+    }
+}
@@ -0,0 +1,146 @@
+//! `.tileset` assets: a `Tileset` and its terrains, one `key = value` entry per line.
+//!
+//! ```text
+//! name = terrain
+//! image = tiles/terrain.png
+//! image_size = 128 64
+//! tile_size = 16 16
+//! margin = 0
+//! spacing = 0
+//! terrain grass = blob
+//! rule grass = 255 12
+//! ```
+//!
+//! `rule <terrain> = <mask> <tile>` gives the tile index used for a neighbour bitmask (see
+//! `Terrain`); the terrain must be declared before its rules.
+
+use anyhow::{Context, Result, anyhow, bail};
+
+use crate::{Terrain, TerrainMode, Tileset, Vfs};
+
+impl Tileset {
+    /// File extension of tileset assets.
+    pub const EXTENSION: &str = "tileset";
+
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut tileset = Tileset::new("", "", (0, 0), (0, 0));
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| anyhow!("line {}: expected `key = value`", number + 1))?;
+            let (key, value) = (key.trim(), value.trim());
+            let invalid = || format!("line {}: invalid value {:?}", number + 1, value);
+            let pair = || -> Result<(u32, u32)> {
+                match value.split_whitespace().collect::<Vec<_>>()[..] {
+                    [a, b] => Ok((a.parse()?, b.parse()?)),
+                    _ => bail!("expected two numbers"),
+                }
+            };
+
+            match key {
+                "name" => tileset.name = value.to_string(),
+                "image" => tileset.image = value.to_string(),
+                "image_size" => {
+                    (tileset.image_width, tileset.image_height) = pair().with_context(invalid)?
+                }
+                "tile_size" => {
+                    (tileset.tile_width, tileset.tile_height) = pair().with_context(invalid)?
+                }
+                "margin" => tileset.margin = value.parse().with_context(invalid)?,
+                "spacing" => tileset.spacing = value.parse().with_context(invalid)?,
+                _ => {
+                    if let Some(name) = key.strip_prefix("terrain ") {
+                        let mode = TerrainMode::from_name(value)
+                            .ok_or_else(|| anyhow!("{}: unknown terrain mode", invalid()))?;
+                        tileset.terrains.push(Terrain::new(name.trim(), mode));
+                    } else if let Some(name) = key.strip_prefix("rule ") {
+                        let name = name.trim();
+                        let terrain = tileset
+                            .terrains
+                            .iter_mut()
+                            .find(|terrain| terrain.name == name)
+                            .ok_or_else(|| {
+                                anyhow!("line {}: undeclared terrain {:?}", number + 1, name)
+                            })?;
+                        let (mask, tile) = pair().with_context(invalid)?;
+                        let mask = u8::try_from(mask).with_context(invalid)?;
+                        terrain.set_rule(mask, Some(tile));
+                    } else {
+                        log::warn!("Unknown tileset entry {:?} (line {})", key, number + 1);
+                    }
+                }
+            }
+        }
+
+        if tileset.image.is_empty() {
+            bail!("missing `image` entry");
+        }
+        if tileset.tile_width == 0 || tileset.tile_height == 0 {
+            bail!("missing `tile_size` entry");
+        }
+        tileset.compute_grid();
+        Ok(tileset)
+    }
+
+    pub fn encode(&self) -> String {
+        let mut text = format!(
+            "name = {}\nimage = {}\nimage_size = {} {}\ntile_size = {} {}\nmargin = {}\nspacing = {}\n",
+            self.name,
+            self.image,
+            self.image_width,
+            self.image_height,
+            self.tile_width,
+            self.tile_height,
+            self.margin,
+            self.spacing
+        );
+        for terrain in &self.terrains {
+            text += &format!("terrain {} = {}\n", terrain.name, terrain.mode.name());
+            for rule in &terrain.rules {
+                text += &format!("rule {} = {} {}\n", terrain.name, rule.mask, rule.tile);
+            }
+        }
+        text
+    }
+
+    pub fn load(vfs: &Vfs, path: &str) -> Result<Self> {
+        let text = vfs.read_to_string(path)?;
+        Self::parse(&text).with_context(|| format!("failed to parse tileset {:?}", path))
+    }
+
+    pub fn save(&self, vfs: &Vfs, path: &str) -> Result<()> {
+        vfs.write_bytes(path, self.encode().as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tileset_asset_roundtrip() {
+        let mut tileset = Tileset::new("terrain", "tiles/terrain.png", (69, 35), (16, 16));
+        tileset.margin = 1;
+        tileset.spacing = 1;
+        tileset.compute_grid();
+        let mut grass = Terrain::new("tall grass", TerrainMode::Blob);
+        grass.set_rule(0xFF, Some(12));
+        grass.set_rule(Terrain::EAST | Terrain::SOUTH, Some(3));
+        tileset.terrains.push(grass);
+        tileset
+            .terrains
+            .push(Terrain::new("water", TerrainMode::Edges));
+
+        let parsed = Tileset::parse(&tileset.encode()).unwrap();
+        assert_eq!(parsed.terrains, tileset.terrains);
+        assert_eq!(
+            (parsed.columns, parsed.tile_count, parsed.margin),
+            (4, 8, 1)
+        );
+        assert!(Tileset::parse("image = a.png\ntile_size = 8 8\nrule grass = 0 1").is_err());
+    }
+}
@@ -0,0 +1,359 @@
+//! Loader for maps made with the Tiled editor (`.tmx`, with `.tsx` external tilesets).
+//!
+//! Supported: orthogonal, finite maps with tile layers in CSV or XML format, inline or
+//! external tilesets using a single image. Object layers and image layers are skipped;
+//! layers inside groups are flattened.
+
+use anyhow::{Context, Result, anyhow, bail};
+use quick_xml::{
+    Reader,
+    events::{BytesStart, Event},
+};
+
+use crate::{AssetLoader, Tilemap, Tileset};
+
+impl Tilemap {
+    /// Load a `.tmx` map through the VFS of `loader`. The map is registered in the asset
+    /// graph as depending on its external tilesets and tileset images.
+    pub fn load_tmx(loader: &AssetLoader, path: &str) -> Result<Self> {
+        let text = loader.load_string(path)?;
+        let map = Self::parse_tmx(&text, path, |tsx| {
+            loader.add_dependency(path, tsx);
+            loader.load_string(tsx)
+        })
+        .with_context(|| format!("failed to load tilemap {:?}", path))?;
+
+        for tileset in &map.tilesets {
+            loader.add_dependency(path, &tileset.image);
+        }
+        Ok(map)
+    }
+
+    /// Parse the content of a `.tmx` file located at `path` (used to resolve the relative
+    /// paths of tilesets and images). `read_tsx` returns the content of an external tileset
+    /// given its resolved path.
+    pub fn parse_tmx(
+        text: &str,
+        path: &str,
+        mut read_tsx: impl FnMut(&str) -> Result<String>,
+    ) -> Result<Self> {
+        let mut reader = Reader::from_str(text);
+        reader.config_mut().trim_text(true);
+
+        let mut map: Option<Tilemap> = None;
+        // Index of the layer being read, and the encoding of its `<data>`
+        let mut layer: Option<usize> = None;
+        let mut encoding: Option<String> = None;
+        let mut xml_tiles: Vec<u32> = Vec::new();
+
+        loop {
+            let event = reader.read_event()?;
+            let (element, empty) = match &event {
+                Event::Start(element) => (Some(element), false),
+                Event::Empty(element) => (Some(element), true),
+                _ => (None, false),
+            };
+
+            if let Some(element) = element {
+                match element.name().as_ref() {
+                    b"map" => {
+                        if let Some(orientation) = attribute(element, "orientation")?
+                            && orientation != "orthogonal"
+                        {
+                            bail!("unsupported map orientation {:?}", orientation);
+                        }
+                        if attribute(element, "infinite")?.as_deref() == Some("1") {
+                            bail!("infinite maps are not supported");
+                        }
+                        map = Some(Tilemap::new(
+                            required(element, "width")?,
+                            required(element, "height")?,
+                            required(element, "tilewidth")?,
+                            required(element, "tileheight")?,
+                        ));
+                    }
+                    b"tileset" => {
+                        let map = map
+                            .as_mut()
+                            .ok_or_else(|| anyhow!("tileset outside <map>"))?;
+                        let first_gid = required(element, "firstgid")?;
+                        let mut tileset = match attribute(element, "source")? {
+                            Some(source) => {
+                                let tsx_path = resolve_path(path, &source);
+                                let tsx = read_tsx(&tsx_path).with_context(|| {
+                                    format!("failed to read tileset {:?}", tsx_path)
+                                })?;
+                                parse_tsx(&tsx, &tsx_path).with_context(|| {
+                                    format!("failed to parse tileset {:?}", tsx_path)
+                                })?
+                            }
+                            None if empty => bail!("tileset without image or source"),
+                            None => read_tileset(&mut reader, element, path)?,
+                        };
+                        tileset.first_gid = first_gid;
+                        map.add_tileset(tileset);
+                    }
+                    b"layer" => {
+                        let map = map.as_mut().ok_or_else(|| anyhow!("layer outside <map>"))?;
+                        let index = map.add_layer(attribute(element, "name")?.unwrap_or_default());
+                        let new_layer = &mut map.layers[index];
+                        new_layer.visible = attribute(element, "visible")?.as_deref() != Some("0");
+                        if let Some(opacity) = attribute(element, "opacity")? {
+                            new_layer.opacity = opacity
+                                .parse()
+                                .with_context(|| format!("invalid opacity {:?}", opacity))?;
+                        }
+                        xml_tiles.clear();
+                        layer = Some(index);
+                    }
+                    b"data" if layer.is_some() => {
+                        if attribute(element, "compression")?.is_some() {
+                            bail!("compressed layer data is not supported, use CSV");
+                        }
+                        encoding = attribute(element, "encoding")?;
+                        if let Some(other) = encoding.as_deref().filter(|e| *e != "csv") {
+                            bail!("unsupported layer encoding {:?}, use CSV", other);
+                        }
+                        xml_tiles.clear();
+                    }
+                    b"chunk" if layer.is_some() => bail!("chunked layer data is not supported"),
+                    b"tile" if layer.is_some() => {
+                        xml_tiles.push(attribute_or(element, "gid", 0)?);
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+
+            match event {
+                Event::Text(text) if layer.is_some() && encoding.as_deref() == Some("csv") => {
+                    let text = text.unescape()?;
+                    xml_tiles = text
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|gid| !gid.is_empty())
+                        .map(|gid| {
+                            gid.parse::<u32>()
+                                .with_context(|| format!("invalid tile gid {:?}", gid))
+                        })
+                        .collect::<Result<_>>()?;
+                }
+                Event::End(end) if end.name().as_ref() == b"layer" => {
+                    let map = map.as_mut().ok_or_else(|| anyhow!("layer outside <map>"))?;
+                    let index = layer.take().ok_or_else(|| anyhow!("unexpected </layer>"))?;
+                    let new_layer = &mut map.layers[index];
+                    if xml_tiles.len() != new_layer.tiles.len() {
+                        bail!(
+                            "layer {:?} has {} tiles, expected {}",
+                            new_layer.name,
+                            xml_tiles.len(),
+                            new_layer.tiles.len()
+                        );
+                    }
+                    new_layer.tiles = std::mem::take(&mut xml_tiles);
+                    encoding = None;
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+
+        map.ok_or_else(|| anyhow!("missing <map> element"))
+    }
+}
+
+/// Parse the content of a `.tsx` external tileset located at `path`.
+fn parse_tsx(text: &str, path: &str) -> Result<Tileset> {
+    let mut reader = Reader::from_str(text);
+    reader.config_mut().trim_text(true);
+    loop {
+        match reader.read_event()? {
+            Event::Start(element) if element.name().as_ref() == b"tileset" => {
+                return read_tileset(&mut reader, &element, path);
+            }
+            Event::Eof => bail!("missing <tileset> element"),
+            _ => {}
+        }
+    }
+}
+
+/// Read a `<tileset>` whose start tag is `element`, up to its end tag.
+fn read_tileset(reader: &mut Reader<&[u8]>, element: &BytesStart, path: &str) -> Result<Tileset> {
+    let name = attribute(element, "name")?.unwrap_or_default();
+    let mut image: Option<(String, u32, u32)> = None;
+    let mut depth = 0;
+    loop {
+        let event = reader.read_event()?;
+        if let Event::Start(child) | Event::Empty(child) = &event
+            && child.name().as_ref() == b"image"
+            && depth == 0
+        {
+            let source = attribute(child, "source")?
+                .ok_or_else(|| anyhow!("tileset {:?}: image without source", name))?;
+            image = Some((
+                resolve_path(path, &source),
+                required(child, "width")?,
+                required(child, "height")?,
+            ));
+        }
+        match event {
+            Event::Start(_) => depth += 1,
+            // Fin de `<tileset>` (les noms de balise fermante sont vérifiés par le reader)
+            Event::End(_) if depth == 0 => break,
+            Event::End(_) => depth -= 1,
+            Event::Eof => bail!("unterminated <tileset>"),
+            _ => {}
+        }
+    }
+
+    let (image, image_width, image_height) = image.ok_or_else(|| {
+        anyhow!(
+            "tileset {:?} has no image (image collections are not supported)",
+            name
+        )
+    })?;
+    let mut tileset = Tileset::new(
+        name,
+        image,
+        (image_width, image_height),
+        (
+            required(element, "tilewidth")?,
+            required(element, "tileheight")?,
+        ),
+    );
+    tileset.margin = attribute_or(element, "margin", 0)?;
+    tileset.spacing = attribute_or(element, "spacing", 0)?;
+    tileset.compute_grid();
+    if let Some(columns) = attribute(element, "columns")? {
+        tileset.columns = columns
+            .parse()
+            .with_context(|| format!("invalid columns {:?}", columns))?;
+    }
+    if let Some(count) = attribute(element, "tilecount")? {
+        tileset.tile_count = count
+            .parse()
+            .with_context(|| format!("invalid tilecount {:?}", count))?;
+    }
+    Ok(tileset)
+}
+
+fn attribute(element: &BytesStart, name: &str) -> Result<Option<String>> {
+    for attribute in element.attributes() {
+        let attribute = attribute?;
+        if attribute.key.as_ref() == name.as_bytes() {
+            return Ok(Some(attribute.unescape_value()?.into_owned()));
+        }
+    }
+    Ok(None)
+}
+
+fn attribute_or(element: &BytesStart, name: &str, default: u32) -> Result<u32> {
+    match attribute(element, name)? {
+        Some(value) => value
+            .parse()
+            .with_context(|| format!("invalid {} {:?}", name, value)),
+        None => Ok(default),
+    }
+}
+
+fn required(element: &BytesStart, name: &str) -> Result<u32> {
+    let value = attribute(element, name)?.ok_or_else(|| {
+        anyhow!(
+            "<{}> is missing {:?}",
+            String::from_utf8_lossy(element.name().as_ref()),
+            name
+        )
+    })?;
+    value
+        .parse()
+        .with_context(|| format!("invalid {} {:?}", name, value))
+}
+
+/// Resolve `relative` against the directory of the VFS path `base` (handles `..`).
+fn resolve_path(base: &str, relative: &str) -> String {
+    let mut parts: Vec<&str> = base.split('/').collect();
+    parts.pop();
+    for part in relative.split(['/', '\\']) {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            part => parts.push(part),
+        }
+    }
+    parts.join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TMX: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<map version="1.10" orientation="orthogonal" renderorder="right-down" width="3" height="2" tilewidth="16" tileheight="16" infinite="0">
+ <tileset firstgid="1" name="props" tilewidth="16" tileheight="16" tilecount="4" columns="2">
+  <image source="props.png" width="32" height="32"/>
+ </tileset>
+ <tileset firstgid="5" source="../tilesets/terrain.tsx"/>
+ <layer id="1" name="ground" width="3" height="2">
+  <data encoding="csv">
+5,6,7,
+2147483653,0,1
+</data>
+ </layer>
+ <objectgroup id="2" name="spawns">
+  <object id="1" x="8" y="8"/>
+ </objectgroup>
+ <layer id="3" name="decals" width="3" height="2" visible="0" opacity="0.5">
+  <data>
+   <tile gid="1"/><tile/><tile/>
+   <tile/><tile/><tile gid="4"/>
+  </data>
+ </layer>
+</map>"#;
+
+    const TSX: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<tileset version="1.10" name="terrain" tilewidth="16" tileheight="16" spacing="1" margin="1" tilecount="6" columns="3">
+ <image source="terrain.png" width="52" height="35"/>
+ <tile id="0"><properties><property name="solid" value="true"/></properties></tile>
+</tileset>"#;
+
+    #[test]
+    fn parses_tmx_with_external_tileset() {
+        let mut requested = Vec::new();
+        let map = Tilemap::parse_tmx(TMX, "assets/maps/level1.tmx", |path| {
+            requested.push(path.to_string());
+            Ok(TSX.to_string())
+        })
+        .unwrap();
+
+        assert_eq!(requested, ["assets/tilesets/terrain.tsx"]);
+        assert_eq!(map.size(), (3, 2));
+        assert_eq!(map.tilesets.len(), 2);
+        assert_eq!(map.tilesets[0].image, "assets/maps/props.png");
+        let terrain = &map.tilesets[1];
+        assert_eq!(terrain.image, "assets/tilesets/terrain.png");
+        assert_eq!(
+            (terrain.first_gid, terrain.columns, terrain.tile_count),
+            (5, 3, 6)
+        );
+
+        let layers = map.layers();
+        assert_eq!(layers.len(), 2);
+        assert_eq!(layers[0].tiles(), [5, 6, 7, 5 | Tilemap::FLIP_X, 0, 1]);
+        assert_eq!(layers[1].tiles(), [1, 0, 0, 0, 0, 4]);
+        assert!(!layers[1].visible);
+        assert_eq!(layers[1].opacity, 0.5);
+
+        // Gid 6 = 2e tuile de terrain, après la marge et l'espacement
+        assert_eq!(map.tileset_of(6).unwrap().0, 1);
+        assert_eq!(terrain.uv(6).unwrap()[0], 18.0 / 52.0);
+
+        assert!(
+            Tilemap::parse_tmx(&TMX.replace("csv", "base64"), "level1.tmx", |_| {
+                Ok(TSX.to_string())
+            })
+            .is_err()
+        );
+    }
+}
@@ -30,6 +30,22 @@ pub struct Vertex {
 }
 
 impl Vertex {
+    /// Côté du quad unitaire des sprites (`quad_vertices`), en pixels.
+    pub const QUAD_SIZE: f32 = 100.0;
+
+    /// Sommet du quad unitaire aux coordonnées `uv` ([0, 0] = haut-gauche) : sert aux
+    /// meshes ajustés des sprites (`SpriteMesh`).
+    pub fn from_uv(uv: [f32; 2]) -> Self {
+        Self {
+            position: [uv[0] * Self::QUAD_SIZE, uv[1] * Self::QUAD_SIZE],
+            uv,
+        }
+    }
+
+    pub fn position(&self) -> [f32; 2] {
+        self.position
+    }
+
     pub fn layout<'a>() -> wgpu::VertexBufferLayout<'a> {
         wgpu::VertexBufferLayout {
             array_stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
@@ -54,7 +70,7 @@ impl Vertex {
     // }
 
     pub fn quad_vertices() -> [Vertex; 4] {
-        let size = 100.0; // Taille en pixels
+        let size = Self::QUAD_SIZE; // Taille en pixels
         [
             Vertex {
                 position: [0.0, 0.0],
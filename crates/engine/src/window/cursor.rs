@@ -0,0 +1,246 @@
+//! Gestion du curseur : libre, confiné à un rectangle, ou en mode relatif (caméra, outils
+//! de glisser-déposer).
+//!
+//! Les plateformes ne supportent pas toutes les modes de `CursorGrabMode` (`Locked` n'existe
+//! pas sous Windows / X11, `Confined` pas sous macOS). `CursorController` essaie le mode
+//! natif et l'émule sinon : recentrage du curseur en mode relatif, et replacement dans le
+//! rectangle quand il en sort (winit ne sait confiner qu'à la fenêtre entière).
+
+use winit::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    window::{CursorGrabMode, Window as WinitWindow},
+};
+
+/// Rectangle de confinement, en pixels physiques relatifs à la fenêtre.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CursorRect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl CursorRect {
+    pub fn new(x: f64, y: f64, width: f64, height: f64) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Rectangle couvrant toute la fenêtre.
+    pub fn from_size(size: PhysicalSize<u32>) -> Self {
+        Self::new(0.0, 0.0, size.width as f64, size.height as f64)
+    }
+
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        x >= self.x && y >= self.y && x < self.x + self.width && y < self.y + self.height
+    }
+
+    /// Point du rectangle le plus proche de (`x`, `y`). Le bord droit / bas est exclu.
+    pub fn clamp(&self, x: f64, y: f64) -> (f64, f64) {
+        let max_x = (self.x + self.width - 1.0).max(self.x);
+        let max_y = (self.y + self.height - 1.0).max(self.y);
+        (x.clamp(self.x, max_x), y.clamp(self.y, max_y))
+    }
+
+    pub fn center(&self) -> (f64, f64) {
+        (self.x + self.width / 2.0, self.y + self.height / 2.0)
+    }
+}
+
+/// Comportement demandé pour le curseur.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum CursorMode {
+    #[default]
+    Free,
+    /// Visible, mais ne peut pas sortir du rectangle (ex: viewport de l'éditeur).
+    Confined(CursorRect),
+    /// Caché et immobile : seuls les déplacements relatifs (`DeviceEvent::MouseMotion`)
+    /// sont utilisés.
+    Relative,
+}
+
+/// Applique un `CursorMode` à une fenêtre winit en contournant les limites de la
+/// plateforme. Les évènements `CursorMoved` et `Focused` de la fenêtre doivent lui être
+/// transmis (`handle_cursor_moved`, `handle_focus`).
+#[derive(Debug, Default)]
+pub struct CursorController {
+    mode: CursorMode,
+    /// Mode de grab effectivement obtenu de la plateforme.
+    grab: Option<CursorGrabMode>,
+    /// Le mode relatif est émulé (pas de `Locked`) : le curseur est recentré quand il bouge.
+    emulate_relative: bool,
+}
+
+impl CursorController {
+    /// Marge (pixels) avant les bords de la fenêtre à partir de laquelle le curseur est
+    /// recentré en mode relatif émulé.
+    pub const RECENTER_MARGIN: f64 = 32.0;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn mode(&self) -> CursorMode {
+        self.mode
+    }
+
+    /// `true` en mode relatif (souris "capturée").
+    pub fn is_relative(&self) -> bool {
+        self.mode == CursorMode::Relative
+    }
+
+    /// Mode de grab obtenu de la plateforme lors du dernier `set_mode`.
+    pub fn grab_mode(&self) -> Option<CursorGrabMode> {
+        self.grab
+    }
+
+    pub fn set_mode(&mut self, window: &WinitWindow, mode: CursorMode) {
+        self.mode = mode;
+        self.apply(window);
+    }
+
+    /// Applique le mode courant (à rappeler quand la fenêtre reprend le focus : certaines
+    /// plateformes relâchent le grab en arrière-plan).
+    pub fn apply(&mut self, window: &WinitWindow) {
+        let try_grab = |modes: &[CursorGrabMode]| {
+            modes
+                .iter()
+                .copied()
+                .find(|mode| window.set_cursor_grab(*mode).is_ok())
+        };
+
+        match self.mode {
+            CursorMode::Free => {
+                self.grab = try_grab(&[CursorGrabMode::None]);
+                self.emulate_relative = false;
+                window.set_cursor_visible(true);
+            }
+            CursorMode::Confined(_) => {
+                // Confiné à la fenêtre si possible ; le rectangle est appliqué par
+                // `handle_cursor_moved`
+                self.grab = try_grab(&[CursorGrabMode::Confined, CursorGrabMode::None]);
+                self.emulate_relative = false;
+                window.set_cursor_visible(true);
+            }
+            CursorMode::Relative => {
+                self.grab = try_grab(&[
+                    CursorGrabMode::Locked,
+                    CursorGrabMode::Confined,
+                    CursorGrabMode::None,
+                ]);
+                self.emulate_relative = self.grab != Some(CursorGrabMode::Locked);
+                window.set_cursor_visible(false);
+            }
+        }
+    }
+
+    /// À appeler sur `WindowEvent::Focused` : relâche le curseur quand la fenêtre perd le
+    /// focus et réapplique le mode quand elle le reprend.
+    pub fn handle_focus(&mut self, window: &WinitWindow, focused: bool) {
+        if focused {
+            self.apply(window);
+        } else {
+            window.set_cursor_grab(CursorGrabMode::None).ok();
+            window.set_cursor_visible(true);
+        }
+    }
+
+    /// Position à laquelle replacer le curseur après un déplacement à `position`, selon le
+    /// mode (`None` = ne pas bouger). `window_size` est la taille intérieure de la fenêtre.
+    pub fn correction(
+        &self,
+        position: PhysicalPosition<f64>,
+        window_size: PhysicalSize<u32>,
+    ) -> Option<PhysicalPosition<f64>> {
+        match self.mode {
+            CursorMode::Free => None,
+            CursorMode::Confined(rect) => {
+                if rect.contains(position.x, position.y) {
+                    return None;
+                }
+                let (x, y) = rect.clamp(position.x, position.y);
+                Some(PhysicalPosition::new(x, y))
+            }
+            CursorMode::Relative if self.emulate_relative => {
+                let window = CursorRect::from_size(window_size);
+                let inner = CursorRect::new(
+                    Self::RECENTER_MARGIN,
+                    Self::RECENTER_MARGIN,
+                    window.width - Self::RECENTER_MARGIN * 2.0,
+                    window.height - Self::RECENTER_MARGIN * 2.0,
+                );
+                if inner.contains(position.x, position.y) {
+                    return None;
+                }
+                let (x, y) = window.center();
+                Some(PhysicalPosition::new(x, y))
+            }
+            CursorMode::Relative => None,
+        }
+    }
+
+    /// À appeler sur `WindowEvent::CursorMoved` : replace le curseur si nécessaire (voir
+    /// `correction`). Retourne la position corrigée du curseur.
+    pub fn handle_cursor_moved(
+        &mut self,
+        window: &WinitWindow,
+        position: PhysicalPosition<f64>,
+    ) -> PhysicalPosition<f64> {
+        match self.correction(position, window.inner_size()) {
+            Some(corrected) => {
+                if let Err(e) = window.set_cursor_position(corrected) {
+                    log::debug!("Cannot move the cursor: {}", e);
+                    return position;
+                }
+                corrected
+            }
+            None => position,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn confined_and_emulated_relative_corrections() {
+        let size = PhysicalSize::new(800, 600);
+        let mut cursor = CursorController::new();
+        assert_eq!(
+            cursor.correction(PhysicalPosition::new(-5.0, 10.0), size),
+            None
+        );
+
+        cursor.mode = CursorMode::Confined(CursorRect::new(100.0, 50.0, 400.0, 300.0));
+        assert_eq!(
+            cursor.correction(PhysicalPosition::new(200.0, 100.0), size),
+            None
+        );
+        assert_eq!(
+            cursor.correction(PhysicalPosition::new(20.0, 500.0), size),
+            Some(PhysicalPosition::new(100.0, 349.0))
+        );
+
+        cursor.mode = CursorMode::Relative;
+        cursor.emulate_relative = true;
+        assert_eq!(
+            cursor.correction(PhysicalPosition::new(400.0, 300.0), size),
+            None
+        );
+        assert_eq!(
+            cursor.correction(PhysicalPosition::new(790.0, 300.0), size),
+            Some(PhysicalPosition::new(400.0, 300.0))
+        );
+        // Avec `Locked`, la plateforme garde le curseur immobile
+        cursor.emulate_relative = false;
+        assert_eq!(
+            cursor.correction(PhysicalPosition::new(790.0, 300.0), size),
+            None
+        );
+    }
+}
@@ -5,7 +5,7 @@ use egui_winit::{EventResponse, State};
 use winit::event::WindowEvent;
 use winit::window::Window;
 
-use crate::{PassContext, RenderPass};
+use crate::{PassContext, RenderPass, RenderTarget};
 
 /// A small, focused wrapper around egui_winit + egui_wgpu renderer.
 /// Purpose: provide the minimal API a Window needs to begin an egui frame,
@@ -71,6 +71,79 @@ impl EguiRenderer {
         self.state.on_window_event(window, event)
     }
 
+    /// Queue a synthetic input event (e.g. pointer events from the gamepad virtual cursor)
+    /// for the next `begin_frame`.
+    pub fn push_event(&mut self, event: egui::Event) {
+        self.state.egui_input_mut().events.push(event);
+    }
+
+    /// Expose egui's widget tree (roles, labels, focus) to assistive technologies through
+    /// AccessKit. Must be called before the window is first shown; events sent to
+    /// `event_loop_proxy` should be routed back to `on_accesskit_event`.
+    /// `WindowManager::create_window` and `handle_user_event` do both.
+    #[cfg(feature = "accesskit")]
+    pub fn init_accesskit<T: From<egui_winit::accesskit_winit::Event> + Send>(
+        &mut self,
+        event_loop: &winit::event_loop::ActiveEventLoop,
+        window: &Window,
+        event_loop_proxy: winit::event_loop::EventLoopProxy<T>,
+    ) {
+        self.state
+            .init_accesskit(event_loop, window, event_loop_proxy);
+    }
+
+    /// Handle an AccessKit event for this window. Returns true if the UI must be redrawn.
+    #[cfg(feature = "accesskit")]
+    pub fn on_accesskit_event(&mut self, event: egui_winit::accesskit_winit::WindowEvent) -> bool {
+        use egui_winit::accesskit_winit::WindowEvent;
+        match event {
+            WindowEvent::InitialTreeRequested => {
+                // The full tree is sent with the next frame's platform output.
+                self.state.egui_ctx().enable_accesskit();
+                true
+            }
+            WindowEvent::ActionRequested(request) => {
+                self.state.on_accesskit_action_request(request);
+                true
+            }
+            WindowEvent::AccessibilityDeactivated => {
+                self.state.egui_ctx().disable_accesskit();
+                false
+            }
+        }
+    }
+
+    /// Expose a `RenderTarget` to egui (e.g. a scene viewport drawn with `ui.image`).
+    /// Call `update_render_target` after the target is resized.
+    pub fn register_render_target(
+        &mut self,
+        device: &Device,
+        target: &RenderTarget,
+    ) -> egui::TextureId {
+        self.renderer
+            .register_native_texture(device, &target.view, wgpu::FilterMode::Linear)
+    }
+
+    /// Point an already registered egui texture at the (possibly recreated) target view.
+    pub fn update_render_target(
+        &mut self,
+        device: &Device,
+        id: egui::TextureId,
+        target: &RenderTarget,
+    ) {
+        self.renderer.update_egui_texture_from_wgpu_texture(
+            device,
+            &target.view,
+            wgpu::FilterMode::Linear,
+            id,
+        );
+    }
+
+    /// Release an egui texture registered with `register_render_target`.
+    pub fn unregister_render_target(&mut self, id: egui::TextureId) {
+        self.renderer.free_texture(&id);
+    }
+
     /// Start an egui frame. Must be called before `draw`/user UI code runs.
     pub fn begin_frame(&mut self, window: &Window) {
         let raw_input = self.state.take_egui_input(window);
@@ -179,7 +252,7 @@ impl RenderPass for EguiPass {
         // Nothing to prepare here; resources live per-window in WindowState.
     }
 
-    fn execute(&self, ctx: &mut PassContext) {
+    fn execute(&mut self, ctx: &mut PassContext) {
         let width = ctx.window_state.config.width;
         let height = ctx.window_state.config.height;
         let pixels_per_point = ctx.window.scale_factor() as f32 * ctx.window_state.scale_factor;
@@ -1,11 +1,15 @@
+mod cursor;
 mod gui;
 mod tool_window;
 mod traits;
+mod virtual_cursor;
 mod window_manager;
 mod window_state;
 
+pub use cursor::*;
 pub use gui::*;
 pub use tool_window::*;
 pub use traits::*;
+pub use virtual_cursor::*;
 pub use window_manager::*;
 pub use window_state::*;
@@ -13,7 +13,7 @@ use egui::Context;
 use egui_wgpu::wgpu;
 use winit::{event::DeviceEvent, window::Window as WinitWindow};
 
-use crate::{Window, WindowFactory, WindowState};
+use crate::{EngineHandle, Window, WindowFactory, WindowState};
 
 /// A very small tool window: owns its rendering state and exposes an egui callback.
 pub struct ToolWindow {
@@ -161,6 +161,7 @@ impl Window for ToolWindow {
 impl WindowFactory for ToolWindow {
     fn create(
         winit_window: winit::window::Window,
+        _engine: EngineHandle,
     ) -> Pin<Box<dyn Future<Output = Result<Self, Box<dyn std::error::Error>>> + Send>>
     where
         Self: Sized,
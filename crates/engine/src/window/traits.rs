@@ -1,11 +1,14 @@
 use egui_wgpu::{ScreenDescriptor, wgpu};
-use std::sync::{Arc, Mutex};
+use std::{
+    sync::{Arc, Mutex},
+    time::Instant,
+};
 use winit::{
     error::ExternalError, event::DeviceEvent, event_loop::ActiveEventLoop, keyboard::KeyCode,
     window::CursorGrabMode,
 };
 
-use crate::WindowState;
+use crate::{CursorRect, FramePhase, Profiler, WindowState};
 
 pub trait Window {
     fn state(&self) -> &Arc<Mutex<WindowState>>;
@@ -52,18 +55,19 @@ pub trait Window {
         self.window().set_cursor_visible(visible)
     }
 
+    /// Capture (mode relatif) ou libère la souris, via le `CursorController` du `WindowState`.
     fn set_mouse_capture(&mut self, capture: bool) {
-        if capture {
-            self.set_cursor_grab(CursorGrabMode::Locked)
-                .or_else(|_| self.set_cursor_grab(CursorGrabMode::Confined))
-                .ok();
-            self.set_cursor_visible(false);
-        } else {
-            self.set_cursor_grab(CursorGrabMode::None)
-                .or_else(|_| self.set_cursor_grab(CursorGrabMode::Confined))
-                .ok();
-            self.set_cursor_visible(true);
-        }
+        let window = Arc::clone(self.window());
+        self.state()
+            .lock()
+            .unwrap()
+            .set_mouse_capture(&window, capture);
+    }
+
+    /// Confine le curseur à `rect` (pixels physiques), ou le libère avec `None`.
+    fn confine_cursor(&mut self, rect: Option<CursorRect>) {
+        let window = Arc::clone(self.window());
+        self.state().lock().unwrap().confine_cursor(&window, rect);
     }
 
     fn handle_resized(&mut self, width: u32, height: u32) {
@@ -82,15 +86,25 @@ pub trait Window {
 
         let state_arc = Arc::clone(self.state());
 
+        Profiler::new_frame();
+        let _redraw = tracing::trace_span!("redraw").entered();
+
         let (width, height, scale_factor) = {
             let state = state_arc.lock().unwrap();
             (state.config.width, state.config.height, state.scale_factor)
         };
 
         let surface_texture = {
-            let state = state_arc.lock().unwrap();
+            let mut state = state_arc.lock().unwrap();
+            state.watchdog.begin_frame();
+            state.frame_stats.begin_frame();
+            state.frame_budgets.begin_frame();
+            let texture = {
+                let _scope = tracing::trace_span!("acquire_surface").entered();
+                state.surface.get_current_texture()
+            };
 
-            match state.surface.get_current_texture() {
+            match texture {
                 Ok(tex) => tex,
                 Err(wgpu::SurfaceError::Outdated) => return,
                 Err(wgpu::SurfaceError::Lost) => {
@@ -131,23 +145,52 @@ pub trait Window {
                         label: Some("Render Encoder"),
                     });
 
+            state.update_virtual_cursor();
+            let render_scope = tracing::trace_span!("render").entered();
             self.render(&mut encoder, &surface_view, &mut *state);
+            drop(render_scope);
 
+            let ui_scope = tracing::trace_span!("ui").entered();
             let ctx = {
                 state.begin_frame(&window_arc);
                 state.egui_context()
             };
 
             self.draw(&ctx);
+            state.frame_stats.overlay(&ctx);
+            state.virtual_cursor.paint(&ctx);
 
             state.end_frame_and_draw(&mut encoder, &window_arc, &surface_view, screen_descriptor);
+            drop(ui_scope);
+            let submit_scope = tracing::trace_span!("submit").entered();
+            let submit_started = Instant::now();
             state.queue.submit(Some(encoder.finish()));
+            state
+                .frame_budgets
+                .record(FramePhase::Submit, submit_started.elapsed());
+            drop(submit_scope);
         }
 
-        surface_texture.present();
+        let present_started = Instant::now();
+        {
+            let _scope = tracing::trace_span!("present").entered();
+            surface_texture.present();
+        }
+        {
+            let mut state = state_arc.lock().unwrap();
+            state
+                .frame_budgets
+                .record(FramePhase::Present, present_started.elapsed());
+            state.watchdog.end_frame();
+            state.frame_budgets.end_frame();
+        }
         window_arc.request_redraw();
     }
 
+    /// Appelé par `Engine::shutdown` avant la destruction de la fenêtre : libérer ici les
+    /// ressources GPU (scène, passes) tant que le device et la surface existent.
+    fn on_shutdown(&mut self) {}
+
     fn on_key_pressed(&mut self, key: KeyCode) {}
     fn on_key_released(&mut self, key: KeyCode) {}
 }
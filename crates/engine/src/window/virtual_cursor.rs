@@ -0,0 +1,146 @@
+//! Curseur virtuel piloté au stick, pour utiliser les menus et l'UI du jeu à la manette.
+//!
+//! Il s'active dès que le stick bouge et s'efface quand la vraie souris bouge. Actif, il
+//! remplace le curseur de la souris partout où celui-ci est lu : `WindowState::cursor_position`
+//! (picking, `cursor_world_position`), le bouton gauche de l'`Input` et les événements de
+//! pointeur d'egui (voir `WindowState::update_virtual_cursor`).
+
+use std::time::Instant;
+
+use crate::{CursorRect, GamepadAxis, GamepadButton, Input, InputButton};
+
+/// Curseur virtuel, en pixels physiques relatifs à la fenêtre.
+#[derive(Debug, Clone)]
+pub struct VirtualCursor {
+    pub position: (f32, f32),
+    /// Vitesse stick en butée (pixels par seconde).
+    pub speed: f32,
+    /// Axes horizontal et vertical qui déplacent le curseur.
+    pub axes: (GamepadAxis, GamepadAxis),
+    /// Bouton de manette qui clique (bouton gauche de la souris).
+    pub click: GamepadButton,
+    pub enabled: bool,
+    active: bool,
+    last_update: Option<Instant>,
+}
+
+impl Default for VirtualCursor {
+    fn default() -> Self {
+        Self {
+            position: (0.0, 0.0),
+            speed: Self::DEFAULT_SPEED,
+            axes: (GamepadAxis::LeftStickX, GamepadAxis::LeftStickY),
+            click: GamepadButton::South,
+            enabled: true,
+            active: false,
+            last_update: None,
+        }
+    }
+}
+
+impl VirtualCursor {
+    pub const DEFAULT_SPEED: f32 = 900.0;
+    /// Rayon du curseur dessiné par `paint` (points egui).
+    const RADIUS: f32 = 7.0;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `true` tant que le stick a bougé plus récemment que la souris.
+    pub fn is_active(&self) -> bool {
+        self.enabled && self.active
+    }
+
+    /// La vraie souris a bougé (`WindowEvent::CursorMoved`) : le curseur virtuel s'efface et
+    /// repartira de cette position.
+    pub fn on_mouse_moved(&mut self, position: (f32, f32)) {
+        self.active = false;
+        self.position = position;
+    }
+
+    /// Déplace le curseur d'après le temps écoulé depuis l'appel précédent. Retourne `true`
+    /// s'il a bougé.
+    pub fn update(&mut self, input: &Input, bounds: CursorRect) -> bool {
+        let now = Instant::now();
+        let delta_time = self
+            .last_update
+            .replace(now)
+            .map_or(0.0, |last| (now - last).as_secs_f32().min(0.1));
+        self.advance(input, delta_time, bounds)
+    }
+
+    /// Comme `update`, avec un pas de temps explicite.
+    pub fn advance(&mut self, input: &Input, delta_time: f32, bounds: CursorRect) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        let (dx, dy) = (input.axis_value(self.axes.0), input.axis_value(self.axes.1));
+        if dx == 0.0 && dy == 0.0 {
+            return false;
+        }
+        self.active = true;
+        let x = self.position.0 + dx * self.speed * delta_time;
+        let y = self.position.1 + dy * self.speed * delta_time;
+        let (x, y) = bounds.clamp(x as f64, y as f64);
+        let previous = self.position;
+        self.position = (x as f32, y as f32);
+        self.position != previous
+    }
+
+    /// Bouton de clic enfoncé / relâché pendant cette frame.
+    pub fn click_edges(&self, input: &Input) -> (bool, bool) {
+        let button = InputButton::Gamepad(self.click);
+        (
+            input.button_just_pressed(button),
+            input.button_just_released(button),
+        )
+    }
+
+    /// Dessine le curseur par-dessus l'UI quand il est actif.
+    pub fn paint(&self, ctx: &egui::Context) {
+        if !self.is_active() {
+            return;
+        }
+        let center = egui::pos2(self.position.0, self.position.1) / ctx.pixels_per_point();
+        let painter = ctx.layer_painter(egui::LayerId::new(
+            egui::Order::Tooltip,
+            egui::Id::new("virtual_cursor"),
+        ));
+        painter.circle(
+            center,
+            Self::RADIUS,
+            egui::Color32::from_white_alpha(200),
+            egui::Stroke::new(2.0, egui::Color32::BLACK),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stick_moves_and_mouse_hides() {
+        let bounds = CursorRect::new(0.0, 0.0, 800.0, 600.0);
+        let mut input = Input::default();
+        let mut cursor = VirtualCursor::new();
+        cursor.on_mouse_moved((100.0, 100.0));
+        assert!(!cursor.advance(&input, 0.1, bounds));
+        assert!(!cursor.is_active());
+
+        input.set_axis(GamepadAxis::LeftStickX, 1.0);
+        assert!(cursor.advance(&input, 0.1, bounds));
+        assert!(cursor.is_active());
+        assert!((cursor.position.0 - 190.0).abs() < 1e-3);
+        assert_eq!(cursor.position.1, 100.0);
+
+        // Bloqué au bord de la fenêtre
+        cursor.advance(&input, 10.0, bounds);
+        assert!(cursor.position.0 < 800.0);
+
+        cursor.on_mouse_moved((5.0, 5.0));
+        assert!(!cursor.is_active());
+        assert_eq!(cursor.position, (5.0, 5.0));
+    }
+}
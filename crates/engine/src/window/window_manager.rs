@@ -1,17 +1,35 @@
 use std::sync::{Arc, Mutex};
 
 use winit::{
-    event_loop::ActiveEventLoop,
+    event_loop::{ActiveEventLoop, EventLoopProxy},
     window::{WindowAttributes, WindowId},
 };
 
-use crate::Window;
+use crate::{EngineHandle, Gamepads, Window};
+
+/// Événement utilisateur de la boucle winit (`EventLoop<EngineEvent>`), à passer à
+/// `WindowManager::handle_user_event`.
+#[derive(Debug)]
+pub enum EngineEvent {
+    /// Événement AccessKit (arbre initial demandé, action d'un lecteur d'écran...).
+    #[cfg(feature = "accesskit")]
+    AccessKit(egui_winit::accesskit_winit::Event),
+}
+
+#[cfg(feature = "accesskit")]
+impl From<egui_winit::accesskit_winit::Event> for EngineEvent {
+    fn from(event: egui_winit::accesskit_winit::Event) -> Self {
+        EngineEvent::AccessKit(event)
+    }
+}
 
 pub trait WindowFactory {
     /// Create a window asynchronously.
     /// Returns a pinned boxed Future so this can be expressed without async-trait.
+    /// `engine` gives the window access to the shared engine subsystems (VFS, loader, mods...).
     fn create(
         winit_window: winit::window::Window,
+        engine: EngineHandle,
     ) -> std::pin::Pin<
         Box<dyn std::future::Future<Output = Result<Self, Box<dyn std::error::Error>>> + Send>,
     >
@@ -26,6 +44,11 @@ pub struct WindowManager {
     /// because Window methods require `&mut self` in many places.
     pub windows: Vec<Arc<Mutex<dyn Window + Send>>>,
     pub active_window: Option<Arc<Mutex<dyn Window + Send>>>,
+    /// Proxy de la boucle, donné à AccessKit pour qu'il y renvoie ses événements.
+    #[cfg_attr(not(feature = "accesskit"), allow(dead_code))]
+    event_loop_proxy: Option<EventLoopProxy<EngineEvent>>,
+    /// Ouvertes au premier `update_gamepads`.
+    gamepads: Option<Gamepads>,
 }
 
 impl WindowManager {
@@ -33,24 +56,47 @@ impl WindowManager {
         Self {
             windows: Vec::new(),
             active_window: None,
+            event_loop_proxy: None,
+            gamepads: None,
         }
     }
 
+    /// À appeler avant de créer les fenêtres : sans proxy, AccessKit n'est pas attaché.
+    pub fn set_event_loop_proxy(&mut self, proxy: EventLoopProxy<EngineEvent>) {
+        self.event_loop_proxy = Some(proxy);
+    }
+
     // Méthode générique pour créer n'importe quel type de fenêtre.
     // Note: the window type must be Send so it can be owned by the manager safely.
     pub async fn create_window<W>(
         &mut self,
         event_loop: &ActiveEventLoop,
+        engine: &EngineHandle,
     ) -> Result<Arc<Mutex<W>>, Box<dyn std::error::Error>>
     where
         W: Window + Send + 'static,
         W: WindowFactory, // Trait pour créer des fenêtres
     {
+        // AccessKit doit être attaché avant le premier affichage : la fenêtre est créée
+        // cachée et montrée une fois l'adaptateur en place.
+        let attributes = WindowAttributes::default().with_visible(!cfg!(feature = "accesskit"));
         let winit_window = event_loop
-            .create_window(WindowAttributes::default())
+            .create_window(attributes)
             .map_err(|e| format!("Impossible de créer la fenêtre: {}", e))?;
 
-        let window = W::create(winit_window).await?;
+        let window = W::create(winit_window, engine.clone()).await?;
+        #[cfg(feature = "accesskit")]
+        {
+            let winit_window = Arc::clone(window.window());
+            if let Some(proxy) = &self.event_loop_proxy {
+                window.state().lock().unwrap().egui_renderer.init_accesskit(
+                    event_loop,
+                    &winit_window,
+                    proxy.clone(),
+                );
+            }
+            winit_window.set_visible(true);
+        }
         let window = Arc::new(Mutex::new(window));
 
         // Cast vers le trait Window pour l'ajouter à la liste générale
@@ -106,6 +152,56 @@ impl WindowManager {
             .cloned()
     }
 
+    /// Relève les événements des manettes et les passe à la fenêtre active, puis joue les
+    /// vibrations qu'elle a demandées (`WindowState::haptics`). À appeler une fois par tour de
+    /// boucle, avant les redraws (`ApplicationHandler::about_to_wait`).
+    pub fn update_gamepads(&mut self) {
+        let gamepads = self.gamepads.get_or_insert_with(Gamepads::new);
+        let events = gamepads.poll();
+        let Some(window) = &self.active_window else {
+            return;
+        };
+        let Ok(window) = window.lock() else {
+            return;
+        };
+        let mut state = window.state().lock().unwrap();
+        for event in events {
+            state.handle_gamepad_event(event);
+        }
+        for request in state.haptics.take_requests() {
+            gamepads.rumble(request);
+        }
+    }
+
+    /// Manettes branchées, une fois ouvertes par `update_gamepads`.
+    pub fn gamepads(&self) -> Option<&Gamepads> {
+        self.gamepads.as_ref()
+    }
+
+    /// Traite un événement utilisateur de la boucle (voir `EngineEvent`).
+    pub fn handle_user_event(&mut self, event: EngineEvent) {
+        match event {
+            #[cfg(feature = "accesskit")]
+            EngineEvent::AccessKit(event) => {
+                let Some(window) = self.get_window(event.window_id) else {
+                    return;
+                };
+                let Ok(guard) = window.lock() else {
+                    return;
+                };
+                let redraw = guard
+                    .state()
+                    .lock()
+                    .unwrap()
+                    .egui_renderer
+                    .on_accesskit_event(event.window_event);
+                if redraw {
+                    guard.request_redraw();
+                }
+            }
+        }
+    }
+
     pub fn window_count(&self) -> usize {
         self.windows.len()
     }
@@ -156,6 +252,23 @@ impl WindowManager {
         }
     }
 
+    /// Arrêt des fenêtres (voir `Engine::shutdown`) : chacune libère ses ressources GPU
+    /// (`Window::on_shutdown`), le GPU termine le travail soumis, puis les fenêtres sont
+    /// détruites avec leur surface.
+    pub fn shutdown(&mut self) {
+        for window in &self.windows {
+            let Ok(mut guard) = window.lock() else {
+                continue;
+            };
+            guard.on_shutdown();
+            let state = guard.state().lock().unwrap();
+            if let Err(e) = state.device.poll(egui_wgpu::wgpu::PollType::Wait) {
+                log::warn!("Failed to wait for the GPU at shutdown: {}", e);
+            }
+        }
+        self.close_all_windows();
+    }
+
     // Méthode pour fermer toutes les fenêtres
     pub fn close_all_windows(&mut self) {
         self.windows.clear();
@@ -1,18 +1,21 @@
 //! Simplified WindowState
 //! - conserve l'essentiel : wgpu device/queue/surface & configuration
 //! - renderer egui encapsulé (EguiRenderer)
-//! - helpers d'entrée (touches pressées, mouse delta, capture souris)
+//! - helpers d'entrée (touches et boutons pressés, mouse delta, molette, position du curseur,
+//!   capture souris)
 //!
 //! L'objectif : petite surface d'état claire et facile à maintenir.
 
-use std::collections::HashSet;
-
 use egui_wgpu::{ScreenDescriptor, wgpu};
-use winit::event::DeviceEvent;
+use winit::event::{DeviceEvent, MouseButton, MouseScrollDelta};
 use winit::keyboard::KeyCode;
-use winit::window::{CursorGrabMode, Window as WinitWindow};
+use winit::window::Window as WinitWindow;
 
-use crate::EguiRenderer;
+use crate::{
+    Camera2D, CursorController, CursorMode, CursorRect, EguiRenderer, EngineInfo, FrameBudgets,
+    FrameStats, FrameWatchdog, GamepadEvent, Haptics, Input, InputButton, InputMap, RenderTarget,
+    Vec2, VirtualCursor,
+};
 
 pub struct WindowState {
     // WGPU core
@@ -23,11 +26,29 @@ pub struct WindowState {
     pub format: wgpu::TextureFormat,
     /// multiplier additionnel (optionnel) appliqué au scale factor de la fenêtre
     pub scale_factor: f32,
+    /// Infos adapter / capacités GPU capturées à la création du device.
+    pub info: EngineInfo,
+    /// Diagnostic des frames trop longues (voir `Window::handle_redraw`).
+    pub watchdog: FrameWatchdog,
+    /// Temps de frame et compteurs de rendu (draw calls), avec leur overlay.
+    pub frame_stats: FrameStats,
+    /// Budgets de temps par phase de la frame (voir `Window::handle_redraw`).
+    pub frame_budgets: FrameBudgets,
 
-    // Input (minimal)
-    pressed_keys: HashSet<KeyCode>,
+    // Input
+    input: Input,
     mouse_delta: (f32, f32),
-    mouse_captured: bool,
+    /// Molette accumulée depuis le dernier `take_scroll_delta`, en crans.
+    scroll_delta: (f32, f32),
+    /// Position du curseur dans la fenêtre (pixels physiques), `None` hors de la fenêtre.
+    cursor_position: Option<(f32, f32)>,
+    cursor: CursorController,
+    /// Curseur piloté au stick (voir `update_virtual_cursor`).
+    pub virtual_cursor: VirtualCursor,
+    /// Événements manette de la frame (voir `handle_gamepad_event`).
+    gamepad_events: Vec<GamepadEvent>,
+    /// Vibrations demandées aux manettes, jouées par `WindowManager::update_gamepads`.
+    pub haptics: Haptics,
 
     // Egui renderer wrapper (see engine::window::gui::EguiRenderer)
     pub egui_renderer: EguiRenderer,
@@ -60,6 +81,9 @@ impl WindowState {
 
         let caps = surface.get_capabilities(&adapter);
 
+        let info = EngineInfo::query(&adapter, &caps);
+        log::info!("{}", info.summary());
+
         // Choisir un format raisonnable (préférence Bgra8 sRGB quand disponible)
         let preferred = wgpu::TextureFormat::Bgra8UnormSrgb;
         let format = caps
@@ -97,9 +121,18 @@ impl WindowState {
             config,
             format,
             scale_factor: 1.0,
-            pressed_keys: HashSet::new(),
+            info,
+            watchdog: FrameWatchdog::default(),
+            frame_stats: FrameStats::default(),
+            frame_budgets: FrameBudgets::default(),
+            input: Input::new(InputMap::editor()),
             mouse_delta: (0.0, 0.0),
-            mouse_captured: false,
+            scroll_delta: (0.0, 0.0),
+            cursor_position: None,
+            cursor: CursorController::new(),
+            virtual_cursor: VirtualCursor::new(),
+            gamepad_events: Vec::new(),
+            haptics: Haptics::default(),
             egui_renderer,
         }
     }
@@ -111,7 +144,7 @@ impl WindowState {
     /// Handle low-level device events (e.g. MouseMotion). Accumule la delta quand la souris est capturée.
     pub fn handle_device_event(&mut self, event: &DeviceEvent) {
         if let DeviceEvent::MouseMotion { delta } = event {
-            if self.mouse_captured {
+            if self.cursor.is_relative() {
                 self.mouse_delta.0 += delta.0 as f32;
                 self.mouse_delta.1 += delta.1 as f32;
             }
@@ -119,15 +152,155 @@ impl WindowState {
     }
 
     pub fn press_key(&mut self, key: KeyCode) {
-        self.pressed_keys.insert(key);
+        self.input.press_key(key);
     }
 
     pub fn release_key(&mut self, key: KeyCode) {
-        self.pressed_keys.remove(&key);
+        self.input.release_key(key);
     }
 
     pub fn is_key_pressed(&self, key: KeyCode) -> bool {
-        self.pressed_keys.contains(&key)
+        self.input.is_down(InputButton::Key(key))
+    }
+
+    /// `key` enfoncée pendant cette frame (voir `end_frame`).
+    pub fn just_pressed(&self, key: KeyCode) -> bool {
+        self.input.key_just_pressed(key)
+    }
+
+    /// `key` relâchée pendant cette frame.
+    pub fn just_released(&self, key: KeyCode) -> bool {
+        self.input.key_just_released(key)
+    }
+
+    pub fn mouse_button_just_pressed(&self, button: MouseButton) -> bool {
+        self.input.button_just_pressed(InputButton::Mouse(button))
+    }
+
+    /// Clôt la frame de l'entrée : les `just_pressed` / `just_released` repartent de zéro.
+    /// Appelé par `end_frame_and_draw` ; à appeler soi-même pour une frame sans egui.
+    pub fn end_frame(&mut self) {
+        self.input.end_frame();
+        self.gamepad_events.clear();
+    }
+
+    /// Événement d'une manette (`WindowManager::update_gamepads`) : appliqué à l'`Input` et
+    /// gardé jusqu'à la fin de la frame.
+    pub fn handle_gamepad_event(&mut self, event: GamepadEvent) {
+        event.apply(&mut self.input);
+        self.gamepad_events.push(event);
+    }
+
+    /// Événements manette reçus depuis la frame précédente, par manette (joueurs locaux,
+    /// invites de commande...).
+    pub fn gamepad_events(&self) -> &[GamepadEvent] {
+        &self.gamepad_events
+    }
+
+    pub fn press_mouse_button(&mut self, button: MouseButton) {
+        self.input.press_mouse_button(button);
+    }
+
+    pub fn release_mouse_button(&mut self, button: MouseButton) {
+        self.input.release_mouse_button(button);
+    }
+
+    pub fn is_mouse_button_pressed(&self, button: MouseButton) -> bool {
+        self.input.is_down(InputButton::Mouse(button))
+    }
+
+    /// Hauteur d'un cran de molette pour les deltas en pixels.
+    const PIXELS_PER_LINE: f32 = 40.0;
+
+    /// Accumule un `WindowEvent::MouseWheel`. Les deltas en pixels (pavés tactiles) sont
+    /// convertis en crans.
+    pub fn handle_mouse_wheel(&mut self, delta: MouseScrollDelta) {
+        let (x, y) = match delta {
+            MouseScrollDelta::LineDelta(x, y) => (x, y),
+            MouseScrollDelta::PixelDelta(position) => (
+                position.x as f32 / Self::PIXELS_PER_LINE,
+                position.y as f32 / Self::PIXELS_PER_LINE,
+            ),
+        };
+        self.scroll_delta.0 += x;
+        self.scroll_delta.1 += y;
+    }
+
+    /// Retourne la molette accumulée (crans, y > 0 vers le haut) et la remet à zéro.
+    pub fn take_scroll_delta(&mut self) -> (f32, f32) {
+        std::mem::take(&mut self.scroll_delta)
+    }
+
+    /// Met à jour la position du curseur (`WindowEvent::CursorMoved`), ou l'efface quand il
+    /// quitte la fenêtre (`CursorLeft`).
+    pub fn set_cursor_position(&mut self, position: Option<(f32, f32)>) {
+        self.cursor_position = position;
+    }
+
+    /// Mouvement de la vraie souris (`WindowEvent::CursorMoved`) : efface le curseur virtuel.
+    pub fn handle_mouse_moved(&mut self, position: (f32, f32)) {
+        self.virtual_cursor.on_mouse_moved(position);
+        self.cursor_position = Some(position);
+    }
+
+    /// Déplace le curseur virtuel au stick. Actif, il sert de curseur : position, bouton
+    /// gauche de l'`Input` et événements de pointeur d'egui. À appeler avant `begin_frame`.
+    pub fn update_virtual_cursor(&mut self) {
+        let bounds = CursorRect::new(
+            0.0,
+            0.0,
+            self.config.width as f64,
+            self.config.height as f64,
+        );
+        let moved = self.virtual_cursor.update(&self.input, bounds);
+        if !self.virtual_cursor.is_active() {
+            return;
+        }
+        let (x, y) = self.virtual_cursor.position;
+        self.cursor_position = Some((x, y));
+        let pos = egui::pos2(x, y) / self.egui_renderer.context().pixels_per_point();
+        if moved {
+            self.egui_renderer
+                .push_event(egui::Event::PointerMoved(pos));
+        }
+        let (pressed, released) = self.virtual_cursor.click_edges(&self.input);
+        for (edge, down) in [(pressed, true), (released, false)] {
+            if !edge {
+                continue;
+            }
+            if down {
+                self.input.press_mouse_button(MouseButton::Left);
+            } else {
+                self.input.release_mouse_button(MouseButton::Left);
+            }
+            self.egui_renderer.push_event(egui::Event::PointerButton {
+                pos,
+                button: egui::PointerButton::Primary,
+                pressed: down,
+                modifiers: egui::Modifiers::default(),
+            });
+        }
+    }
+
+    /// Position du curseur dans la fenêtre (pixels, origine en haut à gauche).
+    pub fn cursor_position(&self) -> Option<(f32, f32)> {
+        self.cursor_position
+    }
+
+    /// Position du curseur dans le monde vu par `camera` (viewport couvrant la fenêtre).
+    pub fn cursor_world_position(&self, camera: &Camera2D) -> Option<Vec2> {
+        self.cursor_position
+            .map(|(x, y)| camera.screen_to_world(x, y))
+    }
+
+    /// Actions d'entrée (`pressed`, `just_pressed`, `axis`...) de la frame en cours.
+    pub fn input(&self) -> &Input {
+        &self.input
+    }
+
+    /// Accès à l'`InputMap` (`input_mut().map`) et aux axes de manette.
+    pub fn input_mut(&mut self) -> &mut Input {
+        &mut self.input
     }
 
     /// Retourne la delta souris accumulée et la remet à zéro.
@@ -137,26 +310,39 @@ impl WindowState {
         d
     }
 
-    /// Toggle capture de la souris (modifie aussi l'état du Winit `Window`).
+    /// Toggle capture de la souris : mode relatif (`CursorMode::Relative`) ou curseur libre.
     pub fn set_mouse_capture(&mut self, window: &WinitWindow, capture: bool) {
-        self.mouse_captured = capture;
-        if capture {
-            window
-                .set_cursor_grab(CursorGrabMode::Locked)
-                .or_else(|_| window.set_cursor_grab(CursorGrabMode::Confined))
-                .ok();
-            window.set_cursor_visible(false);
+        let mode = if capture {
+            CursorMode::Relative
         } else {
-            window
-                .set_cursor_grab(CursorGrabMode::None)
-                .or_else(|_| window.set_cursor_grab(CursorGrabMode::Confined))
-                .ok();
-            window.set_cursor_visible(true);
-        }
+            CursorMode::Free
+        };
+        self.set_cursor_mode(window, mode);
     }
 
     pub fn is_mouse_captured(&self) -> bool {
-        self.mouse_captured
+        self.cursor.is_relative()
+    }
+
+    pub fn set_cursor_mode(&mut self, window: &WinitWindow, mode: CursorMode) {
+        if mode != CursorMode::Relative {
+            self.mouse_delta = (0.0, 0.0);
+        }
+        self.cursor.set_mode(window, mode);
+    }
+
+    /// Confine le curseur à `rect` (ex: viewport de l'éditeur), ou le libère avec `None`.
+    pub fn confine_cursor(&mut self, window: &WinitWindow, rect: Option<CursorRect>) {
+        self.set_cursor_mode(window, rect.map_or(CursorMode::Free, CursorMode::Confined));
+    }
+
+    pub fn cursor(&self) -> &CursorController {
+        &self.cursor
+    }
+
+    /// Accès au contrôleur du curseur, pour lui transmettre `CursorMoved` / `Focused`.
+    pub fn cursor_mut(&mut self) -> &mut CursorController {
+        &mut self.cursor
     }
 
     // ----------------
@@ -174,7 +360,8 @@ impl WindowState {
     }
 
     /// Termine la frame egui et effectue les opérations GPU nécessaires.
-    /// Cette méthode invoque le renderer egui avec les device/queue/encoder fournis.
+    /// Cette méthode invoque le renderer egui avec les device/queue/encoder fournis, puis
+    /// clôt la frame de l'entrée (`end_frame`).
     pub fn end_frame_and_draw(
         &mut self,
         encoder: &mut wgpu::CommandEncoder,
@@ -190,6 +377,7 @@ impl WindowState {
             window_surface_view,
             screen_descriptor,
         );
+        self.end_frame();
     }
 
     /// Reconfigure la surface après un resize.
@@ -202,6 +390,18 @@ impl WindowState {
         self.surface.configure(&self.device, &self.config);
     }
 
+    /// Crée une cible hors-écran au format de la surface : les pipelines créés pour la
+    /// fenêtre (sprites, egui...) peuvent donc y dessiner tels quels.
+    pub fn create_render_target(
+        &self,
+        label: &str,
+        width: u32,
+        height: u32,
+        with_depth: bool,
+    ) -> RenderTarget {
+        RenderTarget::new(&self.device, label, width, height, self.format, with_depth)
+    }
+
     // Petites commodités d'accès
     pub fn queue(&self) -> &wgpu::Queue {
         &self.queue
@@ -0,0 +1,245 @@
+//! Filesystem en lecture seule servi par une archive zip : un jeu livré monte un seul
+//! fichier compressé (ex: `assets.pak`) à la place du dossier `assets/`.
+//!
+//! `pack_directory` produit l'archive à partir d'un dossier (voir aussi `app pack`).
+
+use std::{
+    collections::BTreeSet,
+    fs::File,
+    io::{BufReader, Cursor, Read, Seek, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use anyhow::{Context, Result, anyhow};
+use zip::{CompressionMethod, ZipArchive, ZipWriter, write::SimpleFileOptions};
+
+use crate::{FileSystem, Metadata, fs::path_key};
+
+/// Filesystem en lecture seule lisant les fichiers d'une archive zip.
+pub struct ZipFs<R: Read + Seek + Send + 'static = BufReader<File>> {
+    /// `ZipArchive` a besoin de `&mut` pour lire une entrée.
+    archive: Mutex<ZipArchive<R>>,
+    /// Fichiers de l'archive (sans les entrées de dossier), chemins séparés par `/`.
+    files: BTreeSet<String>,
+    name: String,
+}
+
+impl ZipFs {
+    /// Ouvre l'archive `path` sur le disque (lue à la demande, pas chargée en mémoire).
+    pub fn open(path: impl AsRef<Path>, name: impl Into<String>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path).with_context(|| format!("failed to open {:?}", path))?;
+        Self::new(BufReader::new(file), name)
+            .with_context(|| format!("failed to read archive {:?}", path))
+    }
+}
+
+impl ZipFs<Cursor<Vec<u8>>> {
+    /// Archive déjà en mémoire (ex: embarquée avec `include_bytes!`).
+    pub fn from_bytes(bytes: Vec<u8>, name: impl Into<String>) -> Result<Self> {
+        Self::new(Cursor::new(bytes), name)
+    }
+}
+
+impl<R: Read + Seek + Send + 'static> ZipFs<R> {
+    pub fn new(reader: R, name: impl Into<String>) -> Result<Self> {
+        let archive = ZipArchive::new(reader).context("invalid zip archive")?;
+        let files = archive
+            .file_names()
+            .filter(|file| !file.ends_with('/'))
+            .map(str::to_string)
+            .collect();
+        Ok(Self {
+            archive: Mutex::new(archive),
+            files,
+            name: name.into(),
+        })
+    }
+
+    /// Nombre de fichiers de l'archive.
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+}
+
+impl<R: Read + Seek + Send + 'static> FileSystem for ZipFs<R> {
+    fn read_to_string(&self, path: &Path) -> Result<String> {
+        let bytes = self.read_bytes(path)?;
+        String::from_utf8(bytes)
+            .with_context(|| format!("ZipFs({}) {:?} is not valid UTF-8", self.name, path))
+    }
+
+    fn read_bytes(&self, path: &Path) -> Result<Vec<u8>> {
+        let name = path_key(path);
+        let mut archive = self.archive.lock().unwrap();
+        let mut file = archive
+            .by_name(&name)
+            .with_context(|| format!("ZipFs({}) failed to read {:?}", self.name, name))?;
+        let mut bytes = Vec::with_capacity(file.size() as usize);
+        file.read_to_end(&mut bytes)
+            .with_context(|| format!("ZipFs({}) failed to read {:?}", self.name, name))?;
+        Ok(bytes)
+    }
+
+    fn write_bytes(&self, path: &Path, _data: &[u8]) -> Result<()> {
+        Err(anyhow!(
+            "ZipFs({}) is read-only, cannot write {:?}",
+            self.name,
+            path
+        ))
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        let name = path_key(path);
+        if self.files.contains(&name) {
+            return true;
+        }
+        // Dossier : au moins un fichier en dessous
+        let dir = format!("{}/", name);
+        name.is_empty()
+            || self
+                .files
+                .range(dir.clone()..)
+                .next()
+                .is_some_and(|file| file.starts_with(&dir))
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn list_files(&self, dir: &Path) -> Vec<PathBuf> {
+        let dir = path_key(dir);
+        let prefix = if dir.is_empty() {
+            String::new()
+        } else {
+            format!("{}/", dir)
+        };
+        self.files
+            .range(prefix.clone()..)
+            .take_while(|file| file.starts_with(&prefix))
+            .map(PathBuf::from)
+            .collect()
+    }
+
+    /// Pas de date de modification : l'archive est construite sans la feature `time` de zip.
+    fn metadata(&self, path: &Path) -> Result<Metadata> {
+        let name = path_key(path);
+        if self.files.contains(&name) {
+            let mut archive = self.archive.lock().unwrap();
+            let file = archive.by_name(&name)?;
+            return Ok(Metadata {
+                size: file.size(),
+                modified: None,
+                is_dir: false,
+            });
+        }
+        if self.exists(path) {
+            return Ok(Metadata {
+                size: 0,
+                modified: None,
+                is_dir: true,
+            });
+        }
+        Err(anyhow!("ZipFs({}) has no file {:?}", self.name, name))
+    }
+}
+
+/// Extensions déjà compressées, stockées telles quelles dans l'archive.
+const STORED_EXTENSIONS: [&str; 6] = ["png", "jpg", "jpeg", "ogg", "mp3", "zip"];
+
+/// Écrit tous les fichiers de `dir` (récursivement) dans l'archive `output`, lisible par
+/// `ZipFs`. Les fichiers sont triés pour que deux packs du même dossier soient identiques.
+/// Retourne le nombre de fichiers écrits.
+pub fn pack_directory(dir: impl AsRef<Path>, output: impl AsRef<Path>) -> Result<usize> {
+    let (dir, output) = (dir.as_ref(), output.as_ref());
+    let mut files = Vec::new();
+    let mut pending = vec![PathBuf::new()];
+    while let Some(current) = pending.pop() {
+        let entries = std::fs::read_dir(dir.join(&current))
+            .with_context(|| format!("failed to list {:?}", dir.join(&current)))?;
+        for entry in entries {
+            let entry = entry?;
+            let path = current.join(entry.file_name());
+            if entry.file_type()?.is_dir() {
+                pending.push(path);
+            } else if dir.join(&path) != output {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+
+    let file = File::create(output).with_context(|| format!("failed to create {:?}", output))?;
+    let mut writer = ZipWriter::new(file);
+    for path in &files {
+        let stored = path
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase())
+            .is_some_and(|extension| STORED_EXTENSIONS.contains(&extension.as_str()));
+        let method = if stored {
+            CompressionMethod::Stored
+        } else {
+            CompressionMethod::Deflated
+        };
+        let options = SimpleFileOptions::default()
+            .compression_method(method)
+            .unix_permissions(0o644);
+        let data = std::fs::read(dir.join(path))
+            .with_context(|| format!("failed to read {:?}", dir.join(path)))?;
+        writer.start_file(path_key(path), options)?;
+        writer.write_all(&data)?;
+    }
+    writer
+        .finish()
+        .with_context(|| format!("failed to write {:?}", output))?;
+    Ok(files.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use tempfile::tempdir;
+
+    use crate::Vfs;
+
+    use super::*;
+
+    #[test]
+    fn packed_directory_is_mounted_read_only() {
+        let dir = tempdir().unwrap();
+        let assets = dir.path().join("assets");
+        std::fs::create_dir_all(assets.join("sprites")).unwrap();
+        std::fs::write(assets.join("level.tmx"), "<map/>").unwrap();
+        std::fs::write(assets.join("sprites/player.png"), [1u8, 2, 3]).unwrap();
+
+        let archive = dir.path().join("assets.pak");
+        assert_eq!(pack_directory(&assets, &archive).unwrap(), 2);
+
+        let vfs = Vfs::new();
+        let zip = ZipFs::open(&archive, "assets.pak").unwrap();
+        assert_eq!(zip.len(), 2);
+        vfs.mount("assets", Arc::new(zip), false);
+
+        assert_eq!(vfs.read_to_string("assets/level.tmx").unwrap(), "<map/>");
+        assert_eq!(
+            vfs.read_bytes("assets/sprites/player.png").unwrap(),
+            [1, 2, 3]
+        );
+        assert!(vfs.exists("assets/sprites"));
+        assert!(!vfs.exists("assets/missing.png"));
+        assert!(vfs.read_bytes("assets/missing.png").is_err());
+        assert_eq!(
+            vfs.list_files("assets/sprites"),
+            ["assets/sprites/player.png"]
+        );
+        assert_eq!(vfs.list_files("").len(), 2);
+        assert!(vfs.write_bytes("assets/new.txt", b"x").is_err());
+    }
+}
@@ -0,0 +1,27 @@
+// Composition de l'éclairage (`LightingPass`) : la couleur de la scène est multipliée par
+// le light buffer (blending `Dst * Src`), avec un triangle plein écran.
+
+@group(0) @binding(0)
+var light_texture: texture_2d<f32>;
+@group(0) @binding(1)
+var light_sampler: sampler;
+
+struct VSOut {
+    @builtin(position) Position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VSOut {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+
+    var out: VSOut;
+    out.Position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VSOut) -> @location(0) vec4<f32> {
+    return vec4<f32>(textureSample(light_texture, light_sampler, in.uv).rgb, 1.0);
+}
@@ -0,0 +1,87 @@
+// Accumulation des lumières (`LightingPass`) : un quad par lumière couvrant son rayon,
+// éclairage diffus à partir du normal buffer, additionné dans le light buffer.
+
+struct LightUniforms {
+    // View-projection de la caméra
+    view_proj: mat4x4<f32>,
+    // Taille du normal buffer, en pixels
+    screen_size: vec2<f32>,
+    _padding: vec2<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> uniforms: LightUniforms;
+
+@group(1) @binding(0)
+var normal_texture: texture_2d<f32>;
+@group(1) @binding(1)
+var normal_sampler: sampler;
+
+struct LightIn {
+    @location(0) position: vec2<f32>,
+    @location(1) radius: f32,
+    @location(2) height: f32,
+    // Couleur multipliée par l'intensité
+    @location(3) color: vec4<f32>,
+    // Direction (unitaire) et cosinus des angles extérieur / intérieur du cône ;
+    // cône extérieur à -2 pour une lumière ponctuelle
+    @location(4) direction: vec2<f32>,
+    @location(5) cone: vec2<f32>,
+};
+
+struct VSOut {
+    @builtin(position) Position: vec4<f32>,
+    @location(0) world: vec2<f32>,
+    @location(1) @interpolate(flat) center: vec2<f32>,
+    @location(2) @interpolate(flat) radius_height: vec2<f32>,
+    @location(3) @interpolate(flat) color: vec4<f32>,
+    @location(4) @interpolate(flat) direction: vec2<f32>,
+    @location(5) @interpolate(flat) cone: vec2<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32, light: LightIn) -> VSOut {
+    // Deux triangles couvrant le carré [-1, 1]² autour de la lumière
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(-1.0, -1.0), vec2<f32>(1.0, -1.0), vec2<f32>(1.0, 1.0),
+        vec2<f32>(-1.0, -1.0), vec2<f32>(1.0, 1.0), vec2<f32>(-1.0, 1.0),
+    );
+    let world = light.position + corners[index] * light.radius;
+
+    var out: VSOut;
+    out.Position = uniforms.view_proj * vec4<f32>(world, 0.0, 1.0);
+    out.world = world;
+    out.center = light.position;
+    out.radius_height = vec2<f32>(light.radius, light.height);
+    out.color = light.color;
+    out.direction = light.direction;
+    out.cone = light.cone;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VSOut) -> @location(0) vec4<f32> {
+    let offset = in.center - in.world;
+    let distance = length(offset);
+    let radius = in.radius_height.x;
+    if (distance >= radius) {
+        discard;
+    }
+    let falloff = 1.0 - distance / radius;
+    let attenuation = falloff * falloff;
+
+    // Normale en espace tangent ; le y du monde descend, celui des normal maps monte
+    let encoded = textureSampleLevel(normal_texture, normal_sampler, in.Position.xy / uniforms.screen_size, 0.0);
+    var normal = normalize(encoded.xyz * 2.0 - 1.0);
+    normal.y = -normal.y;
+    let to_light = normalize(vec3<f32>(offset, in.radius_height.y));
+    let diffuse = max(dot(normal, to_light), 0.0);
+
+    var spot = 1.0;
+    if (distance > 0.0) {
+        let cos_angle = dot(-offset / distance, in.direction);
+        spot = smoothstep(in.cone.x, in.cone.y, cos_angle);
+    }
+
+    return vec4<f32>(in.color.rgb * attenuation * diffuse * spot, 1.0);
+}
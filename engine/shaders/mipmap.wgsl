@@ -0,0 +1,28 @@
+// Génération des mipmaps (`MipmapGenerator`) : chaque niveau est la moyenne du niveau
+// précédent, dessinée avec un triangle plein écran et un échantillonnage bilinéaire (qui
+// moyenne les 4 texels parents). Avec une vue sRGB, la moyenne est faite en linéaire.
+
+@group(0) @binding(0)
+var source_texture: texture_2d<f32>;
+@group(0) @binding(1)
+var source_sampler: sampler;
+
+struct VSOut {
+    @builtin(position) Position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VSOut {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+
+    var out: VSOut;
+    out.Position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VSOut) -> @location(0) vec4<f32> {
+    return textureSample(source_texture, source_sampler, in.uv);
+}
@@ -0,0 +1,63 @@
+// Contour des entités surlignées (`OutlinePass`) : dilatation du masque de mask.wgsl,
+// dessinée seulement à l'extérieur des sprites, avec un triangle plein écran.
+
+struct OutlineParams {
+    // Épaisseur du contour en pixels (bornée à MAX_WIDTH).
+    width: f32,
+    _padding: vec3<f32>,
+};
+
+@group(0) @binding(0)
+var mask_texture: texture_2d<f32>;
+@group(0) @binding(1)
+var<uniform> params: OutlineParams;
+
+// Doit rester égal à `OutlinePass::MAX_WIDTH`.
+const MAX_WIDTH: i32 = 8;
+
+struct VSOut {
+    @builtin(position) Position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VSOut {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+
+    var out: VSOut;
+    out.Position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VSOut) -> @location(0) vec4<f32> {
+    let size = vec2<i32>(textureDimensions(mask_texture));
+    let coord = min(vec2<i32>(in.Position.xy), size - 1);
+    if (textureLoad(mask_texture, coord, 0).a > 0.0) {
+        discard;
+    }
+
+    let width = clamp(params.width, 0.0, f32(MAX_WIDTH));
+    let radius = i32(ceil(width));
+    // Pixel du masque le plus couvrant dans le rayon ; le dernier pixel est adouci
+    var best = vec4<f32>(0.0);
+    for (var y = -MAX_WIDTH; y <= MAX_WIDTH; y = y + 1) {
+        for (var x = -MAX_WIDTH; x <= MAX_WIDTH; x = x + 1) {
+            if (abs(x) > radius || abs(y) > radius) {
+                continue;
+            }
+            let texel = clamp(coord + vec2<i32>(x, y), vec2<i32>(0), size - 1);
+            let mask = textureLoad(mask_texture, texel, 0);
+            let distance = length(vec2<f32>(f32(x), f32(y)));
+            let coverage = mask.a * clamp(width + 1.0 - distance, 0.0, 1.0);
+            if (coverage > best.a) {
+                best = vec4<f32>(mask.rgb, coverage);
+            }
+        }
+    }
+    if (best.a <= 0.0) {
+        discard;
+    }
+    return best;
+}
@@ -0,0 +1,55 @@
+// Masque des entités surlignées (`OutlinePass`) : la couleur du contour (passée dans le
+// tint de l'instance) là où le sprite est opaque. Même entrée de vertex que sprite.wgsl.
+
+struct Uniforms {
+    transform: mat4x4<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> uniforms : Uniforms;
+
+@group(1) @binding(0)
+var my_texture: texture_2d<f32>;
+@group(1) @binding(1)
+var my_sampler: sampler;
+
+struct VSOut {
+    @builtin(position) Position: vec4<f32>,
+    @location(0) fragUV: vec2<f32>,
+    @location(2) color: vec4<f32>,
+};
+
+struct InstanceIn {
+    @location(2) model_0: vec4<f32>,
+    @location(3) model_1: vec4<f32>,
+    @location(4) model_2: vec4<f32>,
+    @location(5) model_3: vec4<f32>,
+    @location(6) uv_rect: vec4<f32>,
+    @location(7) depth: f32,
+    @location(8) texture_layer: u32,
+    // Couleur du contour (`Highlight::color`)
+    @location(9) tint: vec4<f32>,
+};
+
+@vertex
+fn vs_main(
+    @location(0) position: vec2<f32>,
+    @location(1) uv: vec2<f32>,
+    instance: InstanceIn,
+) -> VSOut {
+    let model = mat4x4<f32>(instance.model_0, instance.model_1, instance.model_2, instance.model_3);
+
+    var out: VSOut;
+    out.Position = uniforms.transform * model * vec4<f32>(position, 0.0, 1.0);
+    out.fragUV = mix(instance.uv_rect.xy, instance.uv_rect.zw, uv);
+    out.color = instance.tint;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VSOut) -> @location(0) vec4<f32> {
+    if (textureSample(my_texture, my_sampler, in.fragUV).a < 0.5) {
+        discard;
+    }
+    return in.color;
+}
@@ -0,0 +1,33 @@
+// Bloom en une passe : extraction des zones lumineuses + flou, ajoutés à l'image.
+// values[0].x = seuil de luminance, values[0].y = intensité, values[0].z = rayon (pixels)
+
+fn luminance(color: vec3<f32>) -> f32 {
+    return dot(color, vec3<f32>(0.2126, 0.7152, 0.0722));
+}
+
+fn bright(uv: vec2<f32>, threshold: f32) -> vec3<f32> {
+    let color = textureSample(input_texture, input_sampler, uv).rgb;
+    return color * max(luminance(color) - threshold, 0.0);
+}
+
+@fragment
+fn fs_main(in: VSOut) -> @location(0) vec4<f32> {
+    let threshold = params.values[0].x;
+    let intensity = params.values[0].y;
+    let radius = params.values[0].z;
+
+    let base = textureSample(input_texture, input_sampler, in.uv);
+
+    var glow = vec3<f32>(0.0);
+    var total = 0.0;
+    for (var y = -2; y <= 2; y = y + 1) {
+        for (var x = -2; x <= 2; x = x + 1) {
+            let offset = vec2<f32>(f32(x), f32(y));
+            let weight = exp(-dot(offset, offset) / 4.0);
+            glow = glow + bright(in.uv + offset * radius * params.texel_size, threshold) * weight;
+            total = total + weight;
+        }
+    }
+
+    return vec4<f32>(base.rgb + glow / total * intensity, base.a);
+}
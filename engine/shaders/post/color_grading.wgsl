@@ -0,0 +1,19 @@
+// Étalonnage simple.
+// values[0].x = exposition (EV), values[0].y = contraste, values[0].z = saturation
+// values[1].rgb = teinte multiplicative
+
+@fragment
+fn fs_main(in: VSOut) -> @location(0) vec4<f32> {
+    let exposure = params.values[0].x;
+    let contrast = params.values[0].y;
+    let saturation = params.values[0].z;
+    let tint = params.values[1].rgb;
+
+    let color = textureSample(input_texture, input_sampler, in.uv);
+    var rgb = color.rgb * exp2(exposure) * tint;
+    rgb = (rgb - vec3<f32>(0.5)) * contrast + vec3<f32>(0.5);
+    let gray = dot(rgb, vec3<f32>(0.2126, 0.7152, 0.0722));
+    rgb = mix(vec3<f32>(gray), rgb, saturation);
+
+    return vec4<f32>(max(rgb, vec3<f32>(0.0)), color.a);
+}
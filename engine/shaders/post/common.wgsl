@@ -0,0 +1,35 @@
+// Prélude commun aux effets de post-process (préfixé à chaque shader d'effet).
+// Un triangle plein écran ; l'effet ne définit que `fs_main`.
+
+struct PostParams {
+    // Paramètres de l'effet (voir `PostEffect::params`).
+    values: array<vec4<f32>, 2>,
+    // 1 / taille de l'entrée, en pixels.
+    texel_size: vec2<f32>,
+    // Heure du jour de la scène (0..24, voir `DayNightCycle`).
+    time_of_day: f32,
+    _padding: f32,
+};
+
+@group(0) @binding(0)
+var input_texture: texture_2d<f32>;
+@group(0) @binding(1)
+var input_sampler: sampler;
+@group(0) @binding(2)
+var<uniform> params: PostParams;
+
+struct VSOut {
+    @builtin(position) Position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VSOut {
+    // (0,0), (2,0), (0,2) en UV : couvre tout l'écran avec un seul triangle
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+
+    var out: VSOut;
+    out.Position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
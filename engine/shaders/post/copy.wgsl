@@ -0,0 +1,5 @@
+// Recopie l'entrée telle quelle (utilisé quand aucun effet n'est actif).
+@fragment
+fn fs_main(in: VSOut) -> @location(0) vec4<f32> {
+    return textureSample(input_texture, input_sampler, in.uv);
+}
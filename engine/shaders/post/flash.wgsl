@@ -0,0 +1,9 @@
+// Flash / teinte plein écran (`ScreenFlash`).
+// values[0] = couleur du flash (rgb) et intensité (a, 0 = aucun effet)
+
+@fragment
+fn fs_main(in: VSOut) -> @location(0) vec4<f32> {
+    let flash = params.values[0];
+    let color = textureSample(input_texture, input_sampler, in.uv);
+    return vec4<f32>(mix(color.rgb, flash.rgb, clamp(flash.a, 0.0, 1.0)), color.a);
+}
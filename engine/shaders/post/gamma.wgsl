@@ -0,0 +1,8 @@
+// Correction gamma. values[0].x = gamma (1.0 = aucun effet)
+
+@fragment
+fn fs_main(in: VSOut) -> @location(0) vec4<f32> {
+    let gamma = max(params.values[0].x, 0.0001);
+    let color = textureSample(input_texture, input_sampler, in.uv);
+    return vec4<f32>(pow(max(color.rgb, vec3<f32>(0.0)), vec3<f32>(1.0 / gamma)), color.a);
+}
@@ -0,0 +1,15 @@
+// Assombrit les bords de l'image.
+// values[0].x = intensité (0 = aucun effet), values[0].y = rayon, values[0].z = douceur
+
+@fragment
+fn fs_main(in: VSOut) -> @location(0) vec4<f32> {
+    let strength = params.values[0].x;
+    let radius = params.values[0].y;
+    let softness = params.values[0].z;
+
+    let color = textureSample(input_texture, input_sampler, in.uv);
+    let distance = length(in.uv - vec2<f32>(0.5));
+    let vignette = smoothstep(radius, radius - softness, distance);
+
+    return vec4<f32>(color.rgb * mix(1.0, vignette, strength), color.a);
+}
@@ -0,0 +1,29 @@
+// Formes de debug (`ShapePass`) : triangles colorés en coordonnées monde.
+
+struct Uniforms {
+    transform: mat4x4<f32>, // view-projection de la caméra
+};
+
+@group(0) @binding(0)
+var<uniform> uniforms : Uniforms;
+
+struct VSOut {
+    @builtin(position) Position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+};
+
+@vertex
+fn vs_main(
+    @location(0) position: vec2<f32>,
+    @location(1) color: vec4<f32>,
+) -> VSOut {
+    var out: VSOut;
+    out.Position = uniforms.transform * vec4<f32>(position, 0.0, 1.0);
+    out.color = color;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VSOut) -> @location(0) vec4<f32> {
+    return in.color;
+}
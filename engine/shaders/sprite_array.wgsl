@@ -0,0 +1,73 @@
+// Sprites regroupés dans un TextureArray (même taille de texture) : un seul draw instancié.
+struct Uniforms {
+    transform: mat4x4<f32>, // matrice orthographique 2D
+};
+
+@group(0) @binding(0)
+var<uniform> uniforms : Uniforms;
+
+// Fragments plus transparents que ce seuil sont rejetés (utile avec le depth buffer).
+override ALPHA_CUTOFF: f32 = 0.0;
+
+@group(1) @binding(0)
+var my_texture: texture_2d_array<f32>;
+@group(1) @binding(1)
+var my_sampler: sampler;
+
+struct VSOut {
+    @builtin(position) Position: vec4<f32>,
+    @location(0) fragUV: vec2<f32>,
+    @location(2) tint: vec4<f32>,
+    @location(1) @interpolate(flat) layer: u32,
+};
+
+struct InstanceIn {
+    @location(2) model_0: vec4<f32>,
+    @location(3) model_1: vec4<f32>,
+    @location(4) model_2: vec4<f32>,
+    @location(5) model_3: vec4<f32>,
+    // [u0, v0, u1, v1] : sous-rectangle de la texture (atlas)
+    @location(6) uv_rect: vec4<f32>,
+    // Profondeur [0, 1] dérivée du layer (utilisée seulement avec un depth buffer)
+    @location(7) depth: f32,
+    // Couche du texture array
+    @location(8) texture_layer: u32,
+    // Couleur multipliée avec la texture (blanc = inchangé)
+    @location(9) tint: vec4<f32>,
+    // Couleurs des coins (haut-gauche, haut-droit, bas-droit, bas-gauche) en RGBA8,
+    // interpolées sur le quad
+    @location(10) corner_colors: vec4<u32>,
+};
+
+// Couleur du dégradé des coins au point `uv` du quad ([0, 0] = haut-gauche).
+fn corner_color(corners: vec4<u32>, uv: vec2<f32>) -> vec4<f32> {
+    let top = mix(unpack4x8unorm(corners.x), unpack4x8unorm(corners.y), uv.x);
+    let bottom = mix(unpack4x8unorm(corners.w), unpack4x8unorm(corners.z), uv.x);
+    return mix(top, bottom, uv.y);
+}
+
+@vertex
+fn vs_main(
+    @location(0) position: vec2<f32>,
+    @location(1) uv: vec2<f32>,
+    instance: InstanceIn,
+) -> VSOut {
+    let model = mat4x4<f32>(instance.model_0, instance.model_1, instance.model_2, instance.model_3);
+
+    var out: VSOut;
+    let clip = uniforms.transform * model * vec4<f32>(position, 0.0, 1.0);
+    out.Position = vec4<f32>(clip.xy, instance.depth * clip.w, clip.w);
+    out.fragUV = mix(instance.uv_rect.xy, instance.uv_rect.zw, uv);
+    out.tint = instance.tint * corner_color(instance.corner_colors, uv);
+    out.layer = instance.texture_layer;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VSOut) -> @location(0) vec4<f32> {
+    let color = textureSample(my_texture, my_sampler, in.fragUV, in.layer) * in.tint;
+    if (color.a < ALPHA_CUTOFF) {
+        discard;
+    }
+    return color;
+}
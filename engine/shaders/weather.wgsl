@@ -0,0 +1,169 @@
+// Météo de la scène (`WeatherPass`) : pluie, neige et brouillard procéduraux, dessinés
+// par-dessus la scène avec un triangle plein écran. Les particules sont placées dans une
+// grille (une au plus par case) qui défile avec le temps et la caméra, sur LAYERS plans
+// de profondeur.
+
+struct WeatherParams {
+    // Largeur et hauteur du viewport (pixels), temps (secondes), zoom de la caméra.
+    screen: vec4<f32>,
+    // Position de la caméra (monde), vent (pixels/s), heure du jour (0..24).
+    camera: vec4<f32>,
+    // Intensité, vitesse de chute (pixels/s), taille (pixels), inutilisé.
+    rain: vec4<f32>,
+    rain_color: vec4<f32>,
+    snow: vec4<f32>,
+    snow_color: vec4<f32>,
+    // Densité, taille des nappes (pixels), inutilisés.
+    fog: vec4<f32>,
+    fog_color: vec4<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> params: WeatherParams;
+
+const LAYERS: i32 = 3;
+
+struct VSOut {
+    @builtin(position) Position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VSOut {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+
+    var out: VSOut;
+    out.Position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+fn hash21(p: vec2<f32>) -> f32 {
+    var q = fract(p * vec2<f32>(123.34, 456.21));
+    q = q + dot(q, q + 45.32);
+    return fract(q.x * q.y);
+}
+
+fn value_noise(p: vec2<f32>) -> f32 {
+    let i = floor(p);
+    let f = fract(p);
+    let u = f * f * (3.0 - 2.0 * f);
+    let a = hash21(i);
+    let b = hash21(i + vec2<f32>(1.0, 0.0));
+    let c = hash21(i + vec2<f32>(0.0, 1.0));
+    let d = hash21(i + vec2<f32>(1.0, 1.0));
+    return mix(mix(a, b, u.x), mix(c, d, u.x), u.y);
+}
+
+fn fbm(p: vec2<f32>) -> f32 {
+    var value = 0.0;
+    var amplitude = 0.5;
+    var q = p;
+    for (var i = 0; i < 4; i = i + 1) {
+        value = value + amplitude * value_noise(q);
+        q = q * 2.03 + vec2<f32>(17.1, 9.2);
+        amplitude = amplitude * 0.5;
+    }
+    return value;
+}
+
+// Profondeur du plan `layer` : 1 = plan de la scène, les plans lointains défilent moins
+// vite et ont des particules plus petites.
+fn layer_depth(layer: i32) -> f32 {
+    return 1.0 - f32(layer) * 0.25;
+}
+
+// Position (pixels monde) du fragment vue depuis un plan de profondeur `depth`.
+fn layer_position(frag: vec2<f32>, depth: f32) -> vec2<f32> {
+    let zoom = max(params.screen.w, 0.0001);
+    return frag / zoom + params.camera.xy * depth;
+}
+
+// Couverture d'une couche de pluie : traits fins inclinés par le vent.
+fn rain_layer(frag: vec2<f32>, layer: i32) -> f32 {
+    let depth = layer_depth(layer);
+    let size = max(params.rain.z * depth, 1.0);
+    let speed = max(params.rain.y * depth, 1.0);
+    let slope = clamp(params.camera.z / max(params.rain.y, 1.0), -2.0, 2.0);
+
+    // Dans ce repère incliné, les gouttes tombent verticalement
+    let p = layer_position(frag, depth);
+    let s = vec2<f32>(p.x - p.y * slope, p.y - speed * params.screen.z);
+
+    let cell_size = vec2<f32>(size * 0.6, size * 2.5);
+    let cell = floor(s / cell_size);
+    let seed = cell + vec2<f32>(f32(layer) * 37.0, 11.0);
+    if (hash21(seed) >= params.rain.x * 0.5) {
+        return 0.0;
+    }
+
+    let local = s - cell * cell_size;
+    let x = hash21(seed + 1.7) * cell_size.x;
+    let y = hash21(seed + 4.3) * (cell_size.y - size);
+    let width = max(size * 0.05, 0.75);
+    let along = (local.y - y) / size;
+    if (along < 0.0 || along > 1.0) {
+        return 0.0;
+    }
+    // La tête de la goutte (en bas) est la plus opaque
+    return (1.0 - smoothstep(0.0, width, abs(local.x - x))) * along * depth;
+}
+
+// Couverture d'une couche de neige : flocons ronds qui oscillent en tombant.
+fn snow_layer(frag: vec2<f32>, layer: i32) -> f32 {
+    let depth = layer_depth(layer);
+    let time = params.screen.z;
+    let radius = max(params.snow.z * depth * 0.5, 0.5);
+    let drift = vec2<f32>(params.camera.z, params.snow.y) * depth * time;
+    let s = layer_position(frag, depth) - drift;
+
+    let cell_size = max(params.snow.z * 6.0, 4.0) * depth;
+    let cell = floor(s / cell_size);
+    let seed = cell + vec2<f32>(f32(layer) * 53.0, 29.0);
+    let chance = hash21(seed);
+    if (chance >= params.snow.x) {
+        return 0.0;
+    }
+
+    let sway = sin(time * 1.3 + chance * 6.283) * 0.2;
+    let center = cell_size
+        * vec2<f32>(0.25 + 0.5 * hash21(seed + 2.1) + sway, 0.25 + 0.5 * hash21(seed + 5.9));
+    let distance = length(s - cell * cell_size - center);
+    return (1.0 - smoothstep(radius * 0.5, radius, distance)) * (0.5 + 0.5 * depth);
+}
+
+// Composition "over" en alpha prémultiplié.
+fn over(dst: vec4<f32>, color: vec3<f32>, alpha: f32) -> vec4<f32> {
+    return vec4<f32>(color * alpha, alpha) + dst * (1.0 - alpha);
+}
+
+@fragment
+fn fs_main(in: VSOut) -> @location(0) vec4<f32> {
+    let frag = in.Position.xy;
+    let time = params.screen.z;
+    var color = vec4<f32>(0.0);
+
+    if (params.fog.x > 0.0) {
+        let zoom = max(params.screen.w, 0.0001);
+        let p = frag / zoom + params.camera.xy * 0.8 - vec2<f32>(params.camera.z * 0.5 * time, 0.0);
+        let density = smoothstep(0.3, 0.8, fbm(p / max(params.fog.y, 1.0)));
+        color = over(color, params.fog_color.rgb, params.fog.x * density);
+    }
+
+    // Plans lointains d'abord
+    for (var layer = LAYERS - 1; layer >= 0; layer = layer - 1) {
+        if (params.snow.x > 0.0) {
+            let coverage = snow_layer(frag, layer) * params.snow_color.a;
+            color = over(color, params.snow_color.rgb, coverage);
+        }
+        if (params.rain.x > 0.0) {
+            let coverage = rain_layer(frag, layer) * params.rain_color.a;
+            color = over(color, params.rain_color.rgb, coverage);
+        }
+    }
+
+    if (color.a <= 0.0) {
+        discard;
+    }
+    return color;
+}